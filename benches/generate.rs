@@ -0,0 +1,292 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarks of the debug datapack generation. Run with: `cargo bench` in the `benches` directory.
+//!
+//! In CI the benchmarks of a pull request are compared to the benchmarks of its merge base.
+
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use mcfunction_debugger::{
+    bench::DatapackFunctions,
+    config::{Config, DEFAULT_MAX_COMMANDS},
+    generate_debug_datapack,
+    parser::command::CommandParser,
+};
+use std::{
+    collections::BTreeSet,
+    ffi::OsStr,
+    fs::{create_dir_all, remove_dir_all, write},
+    path::{Path, PathBuf},
+};
+use tokio::runtime::Runtime;
+use walkdir::WalkDir;
+
+const FUNCTION_COUNTS: [usize; 3] = [10, 50, 200];
+const LINES_PER_FUNCTION: usize = 20;
+/// The number of functions of the datapack that compares a cached with a fresh parser.
+const PARSER_FUNCTION_COUNT: usize = 50;
+
+/// Creates a datapack with `function_count` functions of [LINES_PER_FUNCTION] lines each. Every
+/// fifth line is a breakpoint that is followed by a function call two lines later.
+fn create_datapack(function_count: usize) -> PathBuf {
+    let datapack = std::env::temp_dir()
+        .join("mcfunction-debugger-bench")
+        .join(format!("datapack_{}", function_count));
+    let _ = remove_dir_all(&datapack);
+    let functions = datapack.join("data/bench/functions");
+    write_pack_mcmeta(&datapack);
+    create_dir_all(&functions).unwrap();
+    for function in 0..function_count {
+        let content = (0..LINES_PER_FUNCTION)
+            .map(|line| match line % 5 {
+                0 => "# breakpoint".to_string(),
+                1 => "scoreboard players add @s bench_counter 1".to_string(),
+                2 => format!(
+                    "execute as @e[type=armor_stand,tag=bench] at @s run function bench:fn_{}",
+                    (function + line + 1) % function_count
+                ),
+                3 => format!("say fn_{} line {}", function, line),
+                _ => "execute if score @s bench_counter matches 10.. run kill @s".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        write(
+            functions.join(format!("fn_{}.mcfunction", function)),
+            content,
+        )
+        .unwrap();
+    }
+    datapack
+}
+
+fn bench_config() -> Config<'static> {
+    Config {
+        namespace: "mcfd",
+        shadow: false,
+        exclude: &[],
+        excluded_functions: BTreeSet::new(),
+        skip_invalid_functions: false,
+        additional_datapacks: &[],
+        max_commands: DEFAULT_MAX_COMMANDS,
+        adapter: None,
+    }
+}
+
+/// Creates a datapack with 20 namespaces, each with 100 directories that are nested 5 levels deep
+/// and contain a function on every level.
+fn create_nested_datapack() -> PathBuf {
+    let datapack = std::env::temp_dir()
+        .join("mcfunction-debugger-bench")
+        .join("nested_datapack");
+    let _ = remove_dir_all(&datapack);
+    write_pack_mcmeta(&datapack);
+    for namespace in 0..20 {
+        let functions = datapack.join(format!("data/ns{}/functions", namespace));
+        for branch in 0..100 {
+            let mut dir = functions.join(format!("dir{}", branch));
+            for level in 0..5 {
+                create_dir_all(&dir).unwrap();
+                write(dir.join(format!("fn{}.mcfunction", level)), "say hi").unwrap();
+                dir = dir.join("sub");
+            }
+        }
+    }
+    datapack
+}
+
+fn write_pack_mcmeta(datapack: &Path) {
+    create_dir_all(datapack).unwrap();
+    write(datapack.join("pack.mcmeta"), "").unwrap();
+}
+
+/// Discovers the function files by walking the namespaces one after another on the current thread.
+/// [DatapackFunctions::find] walks each namespace on the blocking thread pool instead.
+fn find_function_files_sequential(datapack: &Path) -> usize {
+    let mut count = 0;
+    for entry in datapack.join("data").read_dir().unwrap() {
+        let functions = entry.unwrap().path().join("functions");
+        count += WalkDir::new(functions)
+            .into_iter()
+            .filter(|entry| {
+                entry.as_ref().unwrap().path().extension() == Some(OsStr::new("mcfunction"))
+            })
+            .count();
+    }
+    count
+}
+
+fn bench_find_function_files(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let config = &bench_config();
+    let datapack = &create_nested_datapack();
+    let mut group = c.benchmark_group("find_function_files");
+    group.bench_function("sequential", |b| {
+        b.iter(|| find_function_files_sequential(datapack))
+    });
+    group.bench_function("parallel", |b| {
+        b.to_async(&runtime).iter(|| async move {
+            black_box(DatapackFunctions::find(datapack, config).await.unwrap());
+        })
+    });
+    group.finish();
+}
+
+fn bench_generate_debug_datapack(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let parser = &CommandParser::default().unwrap();
+    let config = &bench_config();
+    let mut group = c.benchmark_group("generate_debug_datapack");
+    for function_count in FUNCTION_COUNTS {
+        let datapack = create_datapack(function_count);
+        let output_path = &datapack.join("output");
+        group.throughput(Throughput::Elements(function_count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(function_count),
+            &datapack,
+            |b, datapack| {
+                b.to_async(&runtime).iter_batched(
+                    // Without a previous output the generation can't be incremental
+                    || {
+                        let _ = remove_dir_all(output_path);
+                    },
+                    |()| async move {
+                        generate_debug_datapack(datapack, output_path, parser, config)
+                            .await
+                            .unwrap()
+                    },
+                    BatchSize::PerIteration,
+                )
+            },
+        );
+    }
+
+    // The adapter reuses its parser for every generation instead of creating a new one
+    let datapack = &create_datapack(PARSER_FUNCTION_COUNT);
+    let output_path = &datapack.join("output");
+    group.throughput(Throughput::Elements(PARSER_FUNCTION_COUNT as u64));
+    group.bench_function("cached_parser", |b| {
+        b.to_async(&runtime).iter_batched(
+            || {
+                let _ = remove_dir_all(output_path);
+            },
+            |()| async move {
+                generate_debug_datapack(datapack, output_path, parser, config)
+                    .await
+                    .unwrap()
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.bench_function("fresh_parser", |b| {
+        b.to_async(&runtime).iter_batched(
+            || {
+                let _ = remove_dir_all(output_path);
+            },
+            |()| async move {
+                let parser = CommandParser::default().unwrap();
+                generate_debug_datapack(datapack, output_path, &parser, config)
+                    .await
+                    .unwrap()
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+}
+
+fn bench_parse_functions(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let parser = &CommandParser::default().unwrap();
+    let config = &bench_config();
+    let mut group = c.benchmark_group("parse_functions");
+    for function_count in FUNCTION_COUNTS {
+        let datapack = create_datapack(function_count);
+        let functions = runtime
+            .block_on(DatapackFunctions::find(&datapack, config))
+            .unwrap();
+        group.throughput(Throughput::Elements(function_count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(function_count),
+            &functions,
+            |b, functions| {
+                b.to_async(&runtime).iter(|| async move {
+                    black_box(functions.parse(parser, config).await.unwrap());
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Compares reading the function files asynchronously with reading them one after another with
+/// blocking reads.
+fn bench_parse_functions_blocking_vs_async(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let parser = &CommandParser::default().unwrap();
+    let config = &bench_config();
+    let mut group = c.benchmark_group("parse_functions_blocking_vs_async");
+    for function_count in FUNCTION_COUNTS.into_iter().filter(|count| *count >= 50) {
+        let datapack = create_datapack(function_count);
+        let functions = &runtime
+            .block_on(DatapackFunctions::find(&datapack, config))
+            .unwrap();
+        group.throughput(Throughput::Elements(function_count as u64));
+        group.bench_function(BenchmarkId::new("blocking", function_count), |b| {
+            b.iter(|| black_box(functions.parse_blocking(parser, config).unwrap()))
+        });
+        group.bench_function(BenchmarkId::new("async", function_count), |b| {
+            b.to_async(&runtime).iter(|| async move {
+                black_box(functions.parse(parser, config).await.unwrap());
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_partition(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let parser = &CommandParser::default().unwrap();
+    let config = &bench_config();
+    let mut group = c.benchmark_group("partition");
+    for function_count in FUNCTION_COUNTS {
+        let datapack = create_datapack(function_count);
+        let functions = runtime
+            .block_on(DatapackFunctions::find(&datapack, config))
+            .unwrap();
+        let parsed = runtime.block_on(functions.parse(parser, config)).unwrap();
+        group.throughput(Throughput::Elements(function_count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(function_count),
+            &parsed,
+            |b, parsed| b.iter(|| parsed.partition(black_box(config))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_find_function_files,
+    bench_generate_debug_datapack,
+    bench_parse_functions,
+    bench_parse_functions_blocking_vs_async,
+    bench_partition
+);
+criterion_main!(benches);
@@ -1,9 +1,9 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     env,
     ffi::OsStr,
     fmt::Display,
-    fs::{copy, create_dir_all, read_dir, write, File},
+    fs::{copy, create_dir_all, read_dir, read_to_string, write, File},
     io::{BufRead, BufReader, BufWriter, Write},
     path::Path,
 };
@@ -16,6 +16,8 @@ fn main() {
 
     remove_license_header_from_templates(&out_dir);
 
+    check_template_references();
+
     generate_tests(out_dir);
 }
 
@@ -36,10 +38,9 @@ fn set_build_env() {
 }
 
 fn remove_license_header_from_templates(out_dir: impl AsRef<Path>) {
-    let in_dir = "src/datapack_template";
-    println!("cargo:rerun-if-changed={}", in_dir);
+    println!("cargo:rerun-if-changed={}", TEMPLATE_DIR);
 
-    for entry in WalkDir::new(&in_dir) {
+    for entry in WalkDir::new(TEMPLATE_DIR) {
         let entry = entry.unwrap();
         let in_path = entry.path();
         let out_path = out_dir.as_ref().join(in_path);
@@ -67,6 +68,228 @@ fn remove_license_header_from_templates(out_dir: impl AsRef<Path>) {
     }
 }
 
+const TEMPLATE_DIR: &str = "src/datapack_template";
+
+/// Fails the build if `include_template!` references a template that does not exist or is empty and
+/// warns about templates that are never referenced. Templates are referenced either directly or
+/// through macros that pass their argument on to `include_template!` like `expand_template!`. Paths
+/// can also be given by macros like `PATH!()` that expand to a string literal.
+fn check_template_references() {
+    let templates = find_templates();
+
+    let src_dir = "src";
+    println!("cargo:rerun-if-changed={}", src_dir);
+    let sources = WalkDir::new(src_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.path().extension() == Some(OsStr::new("rs")))
+        .map(|entry| {
+            let path = entry.path().to_path_buf();
+            let content = read_to_string(&path).unwrap();
+            (path, content)
+        })
+        .collect::<Vec<_>>();
+
+    let mut forwarding_macros = BTreeSet::from(["include_template".to_string()]);
+    loop {
+        let count = forwarding_macros.len();
+        for (_path, content) in &sources {
+            for (name, body) in find_macro_definitions(content) {
+                let forwards = find_macro_invocations(body, &forwarding_macros)
+                    .any(|(_index, args)| args.contains('$'));
+                if forwards {
+                    forwarding_macros.insert(name.to_string());
+                }
+            }
+        }
+        if forwarding_macros.len() == count {
+            break;
+        }
+    }
+
+    let mut referenced = BTreeSet::new();
+    let mut errors = Vec::new();
+    for (path, content) in &sources {
+        let definitions = find_macro_definitions(content)
+            .filter_map(|(name, body)| {
+                let index = body.as_ptr() as usize - content.as_ptr() as usize;
+                Some((name, index, find_path_macro_value(body)?))
+            })
+            .collect::<Vec<_>>();
+
+        for (index, args) in find_macro_invocations(content, &forwarding_macros) {
+            let line_number = content[..index].matches('\n').count() + 1;
+            let mut paths = find_string_literals(args).collect::<Vec<_>>();
+            // A macro like PATH!() resolves to the closest definition before the invocation
+            for (name, _) in find_macro_invocations(args, &BTreeSet::new()) {
+                let name = &args[name..];
+                if let Some((_, _, value)) = definitions
+                    .iter()
+                    .rev()
+                    .find(|(n, i, _)| name.starts_with(&format!("{}!()", n)) && *i < index)
+                {
+                    paths.push(value);
+                }
+            }
+            for template in paths.into_iter().filter(|p| is_template_path(p)) {
+                match templates.get(template) {
+                    Some(true) => {
+                        referenced.insert(template);
+                    }
+                    Some(false) => errors.push(format!(
+                        "{}:{}: template {} is empty",
+                        path.display(),
+                        line_number,
+                        template
+                    )),
+                    None => errors.push(format!(
+                        "{}:{}: template {} not found in {}",
+                        path.display(),
+                        line_number,
+                        template,
+                        TEMPLATE_DIR
+                    )),
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        panic!("Invalid template references:\n{}", errors.join("\n"));
+    }
+
+    for template in templates.keys() {
+        if !referenced.contains(template.as_str()) {
+            println!(
+                "cargo:warning=Template {}/{} is never referenced",
+                TEMPLATE_DIR, template
+            );
+        }
+    }
+}
+
+/// Returns the paths of all templates relative to [TEMPLATE_DIR] with `/` as separator and whether
+/// the template is non-empty.
+fn find_templates() -> BTreeMap<String, bool> {
+    WalkDir::new(TEMPLATE_DIR)
+        .into_iter()
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| {
+            let relative_path = entry.path().strip_prefix(TEMPLATE_DIR).unwrap();
+            let relative_path = relative_path
+                .to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, "/");
+            let non_empty = entry.metadata().unwrap().len() > 0;
+            (relative_path, non_empty)
+        })
+        .collect()
+}
+
+fn is_template_path(literal: &str) -> bool {
+    !literal.is_empty()
+        && literal.contains('.')
+        && literal
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./".contains(c))
+}
+
+/// Returns the name and body of every `macro_rules!` definition.
+fn find_macro_definitions(content: &str) -> impl Iterator<Item = (&str, &str)> {
+    content
+        .match_indices("macro_rules!")
+        .filter_map(|(index, _)| {
+            let rest = &content[index + "macro_rules!".len()..];
+            let name = rest.trim_start();
+            let name_len = name
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(name.len());
+            let open = rest.find('{')?;
+            let close = find_closing_delimiter(&rest[open..])?;
+            Some((&name[..name_len], &rest[open + 1..open + close]))
+        })
+}
+
+/// Returns the value of a macro like `macro_rules! PATH { () => { "..." }; }`.
+fn find_path_macro_value(body: &str) -> Option<&str> {
+    let (matcher, transcriber) = body.split_once("=>")?;
+    if matcher.trim() != "()" {
+        return None;
+    }
+    let mut literals = find_string_literals(transcriber);
+    let literal = literals.next()?;
+    literals.next().is_none().then_some(literal)
+}
+
+/// Returns the index and arguments of every invocation of one of the given macros. If `names` is
+/// empty, every macro invocation is returned.
+fn find_macro_invocations<'a>(
+    content: &'a str,
+    names: &'a BTreeSet<String>,
+) -> impl Iterator<Item = (usize, &'a str)> {
+    content.match_indices("!(").filter_map(move |(index, _)| {
+        let prefix = &content[..index];
+        let name_start = prefix
+            .rfind(|c: char| !c.is_alphanumeric() && c != '_')
+            .map_or(0, |i| i + 1);
+        let name = &prefix[name_start..];
+        if name.is_empty() || (!names.is_empty() && !names.contains(name)) {
+            return None;
+        }
+        let open = index + 1;
+        let close = find_closing_delimiter(&content[open..])?;
+        Some((name_start, &content[open + 1..open + close]))
+    })
+}
+
+/// Returns the index of the delimiter that closes the one at the start of `content`, skipping
+/// delimiters in string literals.
+fn find_closing_delimiter(content: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in content.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_string_literals(content: &str) -> impl Iterator<Item = &str> {
+    let mut rest = content;
+    std::iter::from_fn(move || {
+        let start = rest.find('"')? + 1;
+        let mut escaped = false;
+        let len = rest[start..].find(|c| {
+            let end = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            end
+        })?;
+        let literal = &rest[start..start + len];
+        rest = &rest[start + len + 1..];
+        Some(literal)
+    })
+}
+
 const DATAPACKS_PATH: &str = "tests/datapacks";
 
 fn generate_tests(out_dir: impl AsRef<Path>) {
@@ -0,0 +1,32 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mcfunction_debugger::parser::command::CommandParser;
+use std::sync::OnceLock;
+
+static PARSER: OnceLock<CommandParser> = OnceLock::new();
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(command) = std::str::from_utf8(data) {
+        let parser = PARSER.get_or_init(|| CommandParser::default().unwrap());
+        parser.parse(command);
+    }
+});
@@ -16,45 +16,100 @@
 // You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 // If not, see <http://www.gnu.org/licenses/>.
 
+pub(crate) mod connection;
+mod generator;
+mod log_errors;
+mod log_file;
+mod snbt;
+mod suspend;
+mod timing;
 pub mod utils;
+mod watchdog;
 
 use crate::{
+    adapter::connection::{
+        Connect, Connection, ConnectionType, ReconnectEvent, ReconnectingConnection,
+        RecordingMinecraftConnection, DEFAULT_RCON_PORT,
+    },
     adapter::utils::{
-        can_resume_from, events_between, generate_datapack, parse_function_path,
-        to_stopped_event_reason, BreakpointPosition, McfunctionStackFrame, StoppedData,
-        StoppedEvent,
+        can_resume_from, check_pack_format, events_between, filter_variables,
+        find_all_function_namespaces, find_breakpoint_comments, find_end_column, find_functions,
+        find_functions_with_hash, generate_datapack, hash_function_file, is_modified_since,
+        is_summon_output, merge_breakpoints, parse_function_path, read_pack_format,
+        to_stopped_event_reason, BreakpointPosition, DifficultyOutput, EntityDataOutput,
+        GameruleOutput, GeneratedData, GlobalsScope, McfunctionStackFrame, ObjectivesListOutput,
+        PendingExecutor, StoppedData, StoppedEvent, TagListOutput, TimeQueryOutput, VariableKind,
+        DIMENSIONS, GAMERULES,
+    },
+    adapter::{
+        generator::GeneratorCommand,
+        log_errors::LogErrorForwarder,
+        log_file::{LogFileChange, LogFileMonitor},
+        snbt::{format_nbt_path, NbtPathSegment},
+        suspend::{OnSuspend, SuspendControl, TICK_COMMAND_PACK_FORMAT},
+        timing::{SessionTicks, Timing},
+        watchdog::{Watchdog, WatchdogAction, WatchdogTicker},
     },
+    api::ProgressContext,
+    custom::{CustomRequest, Message},
+    doctor::check_world_dir,
     error::{PartialErrorResponse, RequestError},
-    installer::establish_connection,
+    installer::{establish_connection, reconnect_with, update_progress},
+    recording::{Recorder, REPLAY_FILE_ATTRIBUTE},
+    trace::{Tracer, TRACE_FILE_ATTRIBUTE},
     DebugAdapter, DebugAdapterContext,
 };
 use async_trait::async_trait;
 use debug_adapter_protocol::{
-    events::{Event, OutputCategory, OutputEventBody, StoppedEventBody, TerminatedEventBody},
+    events::{
+        BreakpointEventBody, BreakpointEventReason, Event, LoadedSourceEventBody,
+        LoadedSourceEventReason, OutputCategory, OutputEventBody, StoppedEventBody,
+        StoppedEventReason, TerminatedEventBody,
+    },
     requests::{
-        ContinueRequestArguments, EvaluateRequestArguments, InitializeRequestArguments,
-        LaunchRequestArguments, NextRequestArguments, PathFormat, PauseRequestArguments,
-        ScopesRequestArguments, SetBreakpointsRequestArguments, StackTraceRequestArguments,
-        StepInRequestArguments, StepOutRequestArguments, TerminateRequestArguments,
-        VariablesRequestArguments,
+        BreakpointLocationsRequestArguments, ContinueRequestArguments, DisconnectRequestArguments,
+        EvaluateRequestArguments, GotoRequestArguments, GotoTargetsRequestArguments,
+        InitializeRequestArguments, LaunchRequestArguments, NextRequestArguments, PathFormat,
+        PauseRequestArguments, ScopesRequestArguments, SetBreakpointsRequestArguments,
+        StackTraceRequestArguments, StepInRequestArguments, StepInTargetsRequestArguments,
+        StepOutRequestArguments, TerminateRequestArguments, VariablesRequestArguments,
     },
     responses::{
-        ContinueResponseBody, EvaluateResponseBody, ScopesResponseBody, SetBreakpointsResponseBody,
-        StackTraceResponseBody, ThreadsResponseBody, VariablesResponseBody,
+        BreakpointLocationsResponseBody, ContinueResponseBody, EvaluateResponseBody,
+        GotoTargetsResponseBody, LoadedSourcesResponseBody, ScopesResponseBody,
+        SetBreakpointsResponseBody, StackTraceResponseBody, StepInTargetsResponseBody,
+        ThreadsResponseBody, VariablesResponseBody,
+    },
+    types::{
+        Breakpoint, BreakpointLocation, Capabilities, GotoTarget, Scope, Source, StepInTarget,
+        SteppingGranularity, Thread, Variable,
     },
-    types::{Breakpoint, Capabilities, Scope, Thread, Variable},
-    ProtocolMessage,
 };
-use futures::future::Either;
-use log::trace;
+use futures::{
+    future::{select, Either},
+    pin_mut,
+    stream::BoxStream,
+    FutureExt,
+};
+use log::{trace, warn};
 use mcfunction_debugger::{
-    config::adapter::{
-        BreakpointKind, BreakpointPositionInLine, LocalBreakpoint, LocalBreakpointPosition,
+    archive::{is_archive, is_file, join_datapack_path, read_to_string},
+    config::{
+        adapter::{
+            BreakpointKind, BreakpointPositionInLine, LocalBreakpoint, LocalBreakpointPosition,
+            SuspendSchedules,
+        },
+        find_exclude_pattern, DEFAULT_MAX_COMMANDS, SKIP_MARKER, VERSION_OBJECTIVE,
     },
+    find_partitions,
     parser::{
         command::{resource_location::ResourceLocation, CommandParser},
-        parse_line, Line,
+        find_run_index, parse_function_calls, parse_line, Line,
     },
+    sink::FsSink,
+    summary::PartitionRange,
+    utils::minecraft::{find_minecraft_log_file, find_minecraft_world, minecraft_saves_dir},
+    write_dynamic_scores_functions,
 };
 use minect::{
     command::{
@@ -63,22 +118,150 @@ use minect::{
         SummonNamedEntityOutput,
     },
     log::LogEvent,
-    Command, MinecraftConnection,
+    Command,
 };
 use multimap::MultiMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     convert::TryFrom,
     io,
+    iter::once,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
 };
-use tokio::{
-    fs::{read_to_string, remove_dir_all, File},
-    io::{AsyncBufReadExt, BufReader},
-    sync::mpsc::UnboundedSender,
-};
-use tokio_stream::{wrappers::LinesStream, StreamExt};
+use tokio::{fs::remove_dir_all, sync::mpsc::UnboundedSender, time::timeout};
+use tokio_stream::StreamExt;
+
+pub(crate) const LISTENER_NAME: &'static str = "mcfunction_debugger";
+
+const NOT_IN_DATAPACK_MESSAGE: &str = "Function is not part of the debugged datapack";
+
+/// How long a terminate request waits for the program to run to completion before aborting it by
+/// default.
+const DEFAULT_RUN_TO_COMPLETION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for Minecraft to uninstall the debug datapack before deleting it anyway.
+const UNINSTALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for Minecraft to execute an injected batch of commands before proceeding
+/// anyway.
+const BATCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The name of the entity that marks the end of an injected batch of commands is this prefix
+/// followed by the sequence number of the batch.
+const BATCH_MARKER_PREFIX: &str = "batch.";
+
+/// The score holder of the sequence number of the last injected batch that reloads, which the load
+/// function of the debug datapack reports.
+const RELOAD_BATCH: &str = "reload_batch";
+
+const RUN_TO_COMPLETION_TIMEOUT_ATTRIBUTE: &str = "runToCompletionTimeoutMs";
+
+const CONNECTION_TIMEOUT_ATTRIBUTE: &str = "connectionTimeoutMs";
+
+const CONNECTION_WATCHDOG_ATTRIBUTE: &str = "connectionWatchdogMs";
+
+const CONNECTION_TYPE_ATTRIBUTE: &str = "connectionType";
+
+const SOCKET_PORT_ATTRIBUTE: &str = "socketPort";
+
+const PIPE_PATH_ATTRIBUTE: &str = "pipePath";
+
+const RCON_PORT_ATTRIBUTE: &str = "rconPort";
+
+const RCON_PASSWORD_ATTRIBUTE: &str = "rconPassword";
+
+/// The file name of the named pipe in the temporary directory if no `pipePath` is configured.
+const DEFAULT_PIPE_FILE_NAME: &str = "mcfd_pipe";
+
+const IGNORE_BREAKPOINT_COMMENTS_ATTRIBUTE: &str = "ignoreBreakpointComments";
+
+const ON_SUSPEND_ATTRIBUTE: &str = "onSuspend";
+
+const STOP_ON_ENTRY_ATTRIBUTE: &str = "stopOnEntry";
+
+const SUSPEND_SCHEDULES_ATTRIBUTE: &str = "suspendSchedules";
+
+const DISABLED_DATAPACKS_ATTRIBUTE: &str = "disabledDatapacks";
+
+const MAX_RECONNECT_ATTRIBUTE: &str = "maxReconnectMs";
+
+const FORWARD_MINECRAFT_LOG_ERRORS_ATTRIBUTE: &str = "forwardMinecraftLogErrors";
+
+const ADDITIONAL_DATAPACKS_ATTRIBUTE: &str = "additionalDatapacks";
+
+const GENERATOR_COMMAND_ATTRIBUTE: &str = "generatorCommand";
+
+/// How long the adapter tries to replace a lost connection to Minecraft by default.
+const DEFAULT_MAX_RECONNECT: Duration = Duration::from_secs(30);
 
-const LISTENER_NAME: &'static str = "mcfunction_debugger";
+/// How long Minecraft may take to respond to a probe of the connection watchdog by default.
+const DEFAULT_CONNECTION_WATCHDOG: Duration = Duration::from_secs(10);
+
+/// The tag that the probe of the connection watchdog adds to the command block minecart.
+const WATCHDOG_TAG: &str = "watchdog";
+
+/// The tag that the datapack adds when a chain of commands was aborted by Minecraft.
+const TRUNCATED_TAG: &str = "truncated";
+
+/// How often Minecraft's log file is checked for truncation or replacement.
+const LOG_FILE_CHECK_PERIOD: Duration = Duration::from_secs(1);
+
+/// Selects the context entities of the executors that did not run the suspended function yet, if
+/// combined with a check of their depth.
+const PENDING_CONTEXTS: &str =
+    "@e[type=area_effect_cloud,tag=-ns-_context,tag=-ns-_active,tag=!-ns-_current]";
+
+/// The tag of the context entities that a single thread continue suspends before they run.
+const PENDING_EXECUTOR_TAG: &str = "-ns-_pending";
+
+/// Custom request that continues until a location is reached, similar to a temporary breakpoint.
+pub const RUN_TO_LOCATION_COMMAND: &str = "mcfunctionRunToLocation";
+
+/// Custom request that returns the regions of a function that are executed without the debugger
+/// being able to suspend, for example to shade them in an editor.
+pub const PARTITIONS_COMMAND: &str = "mcfunctionPartitions";
+
+/// Custom request that returns how many ticks passed until the last stop, see [Timing].
+pub const TIMING_COMMAND: &str = "mcfunctionTiming";
+
+/// The attach request is received as a custom request, because its arguments in
+/// [debug_adapter_protocol] can't hold the attributes of this debug adapter.
+const ATTACH_COMMAND: &str = "attach";
+
+/// The continue request is received as a custom request, because its arguments in
+/// [debug_adapter_protocol] can't hold the attribute `singleThread`.
+const CONTINUE_COMMAND: &str = "continue";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContinueArguments {
+    thread_id: i32,
+    #[serde(default)]
+    single_thread: bool,
+}
+
+#[derive(Deserialize)]
+struct PartitionsArguments {
+    path: String,
+}
+
+#[derive(Serialize)]
+struct PartitionsResponseBody {
+    partitions: Vec<PartitionRange>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunToLocationArguments {
+    path: String,
+    line: i32,
+    #[serde(default)]
+    column: Option<i32>,
+}
 
 struct ClientSession {
     lines_start_at_1: bool,
@@ -86,8 +269,19 @@ struct ClientSession {
     path_format: PathFormat,
     minecraft_session: Option<MinecraftSession>,
     breakpoints: MultiMap<ResourceLocation, LocalBreakpoint>,
+    breakpoint_ids: MultiMap<ResourceLocation, i32>,
+    next_breakpoint_id: i32,
+    /// The ids and line numbers of the `# breakpoint` comments that were registered as breakpoints.
+    /// They are also contained in `breakpoints` and `breakpoint_ids`.
+    comment_breakpoints: MultiMap<ResourceLocation, (i32, usize)>,
     temporary_breakpoints: MultiMap<ResourceLocation, LocalBreakpoint>,
-    parser: CommandParser,
+    verified_lines: HashMap<ResourceLocation, VerifiedLines>,
+    /// The content hashes of functions with breakpoints, used to find a function after its file
+    /// was renamed or moved.
+    function_hashes: HashMap<ResourceLocation, u64>,
+    partitions: HashMap<ResourceLocation, CachedPartitions>,
+    /// Shared with the generation task, see [generate_datapack].
+    parser: Arc<CommandParser>,
 }
 impl ClientSession {
     fn get_line_offset(&self) -> usize {
@@ -105,19 +299,367 @@ impl ClientSession {
             1
         }
     }
+
+    fn get_source_path<'s>(&self, source: &'s Source) -> Result<&'s String, PartialErrorResponse> {
+        match self.path_format {
+            PathFormat::Path => source.path.as_ref().ok_or_else(|| {
+                PartialErrorResponse::new("Missing argument source.path".to_string())
+            }),
+            PathFormat::URI => todo!("Implement path URIs"),
+        }
+    }
+
+    /// Creates events that update the verification of all valid breakpoints in the functions
+    /// matching the filter.
+    fn get_breakpoint_events(
+        &self,
+        minecraft_session: &MinecraftSession,
+        filter: impl Fn(&ResourceLocation) -> bool,
+    ) -> Vec<BreakpointEventBody> {
+        let mut events = Vec::new();
+        for (function, breakpoints) in self.breakpoints.iter_all() {
+            if !filter(function) {
+                continue;
+            }
+            let message = minecraft_session.get_unverified_reason(function);
+            let verified = message.is_none();
+            let ids = self.breakpoint_ids.get_vec(function).into_iter().flatten();
+            for (breakpoint, id) in breakpoints.iter().zip(ids) {
+                if breakpoint.kind == BreakpointKind::Normal {
+                    let line_number = breakpoint.position.line_number - self.get_line_offset();
+                    events.push(
+                        BreakpointEventBody::builder()
+                            .reason(BreakpointEventReason::Changed)
+                            .breakpoint(
+                                Breakpoint::builder()
+                                    .id(Some(*id))
+                                    .verified(verified)
+                                    .message(message.clone())
+                                    .line(Some(line_number as i32))
+                                    .build(),
+                            )
+                            .build(),
+                    );
+                }
+            }
+        }
+        events
+    }
+
+    /// Finds the function that `function` was renamed or moved to by comparing the content of all
+    /// functions in the datapack. Returns [None] if there is no unique match.
+    async fn find_moved_function(
+        &self,
+        function: &ResourceLocation,
+        datapack: &Path,
+    ) -> Result<Option<(ResourceLocation, PathBuf)>, PartialErrorResponse> {
+        let hash = match self.function_hashes.get(function) {
+            Some(hash) => *hash,
+            None => return Ok(None),
+        };
+        let mut candidates = find_functions_with_hash(datapack, hash)
+            .await
+            .map_err(PartialErrorResponse::from)?;
+        candidates.retain(|(candidate, _path)| candidate != function);
+        if candidates.len() == 1 {
+            Ok(candidates.pop())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Registers the `# breakpoint` comments of the function as breakpoints in addition to the
+    /// breakpoints of the client. The ids of previously registered comments are reused in order, so editing
+    /// the file moves them. Comments on a line with a breakpoint of the client are skipped. Returns
+    /// the events that inform the client about the changes.
+    async fn update_comment_breakpoints(
+        &mut self,
+        function: &ResourceLocation,
+        path: &str,
+        unverified_reason: Option<String>,
+    ) -> io::Result<Vec<BreakpointEventBody>> {
+        let old = self.take_comment_breakpoints(function);
+        let breakpoints = self.breakpoints.remove(function).unwrap_or_default();
+        let ids = self.breakpoint_ids.remove(function).unwrap_or_default();
+        let (ids, breakpoints): (Vec<_>, Vec<_>) = ids
+            .into_iter()
+            .zip(breakpoints)
+            .filter(|(id, _)| !old.iter().any(|(it, _)| it == id))
+            .unzip();
+        self.breakpoints.insert_many(function.clone(), breakpoints);
+        self.breakpoint_ids.insert_many(function.clone(), ids);
+
+        let client_lines = self
+            .breakpoints
+            .get_vec(function)
+            .into_iter()
+            .flatten()
+            .map(|breakpoint| breakpoint.position.line_number)
+            .collect::<BTreeSet<_>>();
+        let mut lines = find_breakpoint_comments(&self.parser, path).await?;
+        lines.retain(|line_number| !client_lines.contains(line_number));
+
+        let mut events =
+            get_removed_comment_events(old.iter().skip(lines.len()).copied().collect());
+        for (index, line_number) in lines.into_iter().enumerate() {
+            let (id, reason) = match old.get(index) {
+                Some((id, old_line_number)) if *old_line_number == line_number => (*id, None),
+                Some((id, _)) => (*id, Some(BreakpointEventReason::Changed)),
+                None => {
+                    let id = self.next_breakpoint_id;
+                    self.next_breakpoint_id += 1;
+                    (id, Some(BreakpointEventReason::New))
+                }
+            };
+            if let Some(reason) = reason {
+                events.push(
+                    BreakpointEventBody::builder()
+                        .reason(reason)
+                        .breakpoint(
+                            Breakpoint::builder()
+                                .id(Some(id))
+                                .verified(unverified_reason.is_none())
+                                .message(unverified_reason.clone())
+                                .source(Some(source(path)))
+                                .line(Some((line_number - self.get_line_offset()) as i32))
+                                .build(),
+                        )
+                        .build(),
+                );
+            }
+            self.breakpoints.insert(
+                function.clone(),
+                LocalBreakpoint {
+                    kind: BreakpointKind::Normal,
+                    position: LocalBreakpointPosition {
+                        line_number,
+                        position_in_line: BreakpointPositionInLine::Breakpoint,
+                    },
+                },
+            );
+            self.breakpoint_ids.insert(function.clone(), id);
+            self.comment_breakpoints
+                .insert(function.clone(), (id, line_number));
+        }
+        Ok(events)
+    }
+
+    /// Forgets the `# breakpoint` comments of the function and returns their ids and line numbers.
+    /// They remain in `breakpoints` and `breakpoint_ids`.
+    fn take_comment_breakpoints(&mut self, function: &ResourceLocation) -> Vec<(i32, usize)> {
+        self.comment_breakpoints
+            .remove(function)
+            .unwrap_or_default()
+    }
+
+    /// Forgets all breakpoints of the function and returns them with their ids.
+    fn remove_breakpoints(&mut self, function: &ResourceLocation) -> Vec<(i32, LocalBreakpoint)> {
+        let breakpoints = self.breakpoints.remove(function).unwrap_or_default();
+        let ids = self.breakpoint_ids.remove(function).unwrap_or_default();
+        self.verified_lines.remove(function);
+        self.function_hashes.remove(function);
+        ids.into_iter().zip(breakpoints).collect()
+    }
+}
+
+/// Caches the partitions of a function until its file or breakpoints change.
+struct CachedPartitions {
+    computed_at: SystemTime,
+    breakpoints: Vec<LocalBreakpoint>,
+    partitions: Vec<PartitionRange>,
+}
+
+/// Caches the results of [verify_breakpoint] for the lines of a function.
+struct VerifiedLines {
+    verified_at: SystemTime,
+    lines: HashMap<usize, (bool, Option<usize>)>,
+}
+impl VerifiedLines {
+    fn new() -> VerifiedLines {
+        VerifiedLines {
+            verified_at: SystemTime::now(),
+            lines: HashMap::new(),
+        }
+    }
 }
 
 struct MinecraftSession {
-    connection: MinecraftConnection,
+    connection: ReconnectingConnection,
+    /// The progress of replacing a lost connection, if one is being replaced.
+    reconnect_progress: Option<ProgressContext>,
+    connection_watchdog: Option<(Watchdog, WatchdogTicker)>,
+    /// Only log file connections read Minecraft's log file.
+    log_file_monitor: Option<LogFileMonitor>,
+    log_error_forwarder: Option<LogErrorForwarder>,
+    /// Periodically checks Minecraft's log file if it is monitored or errors are forwarded.
+    _log_file_ticker: Option<WatchdogTicker>,
     datapack: PathBuf,
+    /// Datapacks whose functions are debugged together with the functions of `datapack`.
+    additional_datapacks: Vec<PathBuf>,
+    /// The namespaces of all debugged datapacks.
+    datapack_namespaces: BTreeSet<String>,
+    /// The functions that the client was told about by the loadedSources request or
+    /// [LoadedSourceEventBody] events, see [MinecraftSession::find_loaded_sources].
+    loaded_sources: BTreeMap<ResourceLocation, PathBuf>,
+    /// Generates the debug datapack in a separate process if configured.
+    generator: Option<GeneratorCommand>,
+    exclude: Vec<String>,
+    generated: Option<GeneratedData>,
+    /// Held while the debug datapack is generated, so the files of a cancelled generation are
+    /// written before the next generation starts.
+    generation_lock: Arc<tokio::sync::Mutex<()>>,
+    keep_debug_datapack: bool,
     namespace: String,
     output_path: PathBuf,
+    run_to_completion_on_terminate: bool,
+    /// How long a terminate request waits for the program to run to completion.
+    run_to_completion_timeout: Duration,
+    /// Whether `# breakpoint` comments are registered as breakpoints.
+    breakpoint_comments: bool,
+    suspend_control: SuspendControl,
+    /// Which scheduled functions are deferred while suspended.
+    suspend_schedules: SuspendSchedules,
     scopes: Vec<ScopeReference>,
+    /// The NBT of the selected entity of each frame. It is queried once per stop, because it can be
+    /// large and is usually requested in pages.
+    entity_nbt: HashMap<i32, Value>,
+    /// The state of the world shown in the Globals scope of every frame. It is queried once per
+    /// stop.
+    globals: Option<GlobalsScope>,
+    /// Whether the objectives of the world were already compared with the known objectives during
+    /// this stop.
+    objectives_checked: bool,
+    /// The objectives that were created at runtime and are logged by `-ns-:log_scores_dynamic`.
+    dynamic_objectives: BTreeSet<String>,
+    /// The commands of `-ns-:log_scores_dynamic` if it was written, but not reloaded yet. It is
+    /// reloaded with the next resume.
+    unloaded_log_scores: Option<Vec<String>>,
     stopped_data: Option<StoppedData>,
+    /// The position of the last stop, if any.
+    last_position: Option<BreakpointPosition>,
+    /// The first line of the launched function if the launch attribute `stopOnEntry` is true and
+    /// the program did not stop yet. A stop at this position is reported with the reason `entry`.
+    entry_position: Option<BreakpointPosition>,
+    /// The ticks counted by the debug datapack at the last stop, if any.
+    session_ticks: Option<SessionTicks>,
+    /// How many ticks passed until the last stop, if any.
+    timing: Option<Timing>,
+    /// The sequence number of the last batch of commands injected by
+    /// [MinecraftSession::inject_commands].
+    injected_batch: u64,
+    /// The sequence number of the last batch that Minecraft is known to have executed.
+    applied_batch: u64,
+    /// Receives the markers of injected batches. It is drained whenever a batch is injected or
+    /// awaited. The mutex only makes the stream [Sync].
+    batch_events: Mutex<BoxStream<'static, LogEvent>>,
+    /// Whether the session was attached to a function that was already suspended. Disconnecting
+    /// from an attached session leaves the function suspended.
+    attached: bool,
+    tracer: Tracer,
 }
 impl MinecraftSession {
     fn get_function_path(&self, function: &ResourceLocation) -> PathBuf {
-        self.datapack.join("data").join(function.mcfunction_path())
+        join_datapack_path(
+            self.get_datapack(function),
+            Path::new("data").join(function.mcfunction_path()),
+        )
+    }
+
+    /// Returns the debugged datapack that contains the function. Functions that don't exist are
+    /// attributed to the datapack of the launched function.
+    fn get_datapack(&self, function: &ResourceLocation) -> &Path {
+        let path = function.mcfunction_path();
+        [&self.datapack]
+            .into_iter()
+            .chain(&self.additional_datapacks)
+            .find(|datapack| is_file(join_datapack_path(datapack, Path::new("data").join(&path))))
+            .unwrap_or(&self.datapack)
+    }
+
+    /// Returns the launched datapack followed by the additional datapacks.
+    fn datapacks(&self) -> Vec<PathBuf> {
+        let mut datapacks = vec![self.datapack.clone()];
+        datapacks.extend(self.additional_datapacks.iter().cloned());
+        datapacks
+    }
+
+    /// Returns all functions of the debugged datapacks that are not excluded by a pattern. A
+    /// function that exists in multiple datapacks is listed with the path that Minecraft loads. The
+    /// debug datapack is not scanned, so the functions generated by the debugger are never listed.
+    async fn find_loaded_sources(&self) -> io::Result<BTreeMap<ResourceLocation, PathBuf>> {
+        let mut loaded_sources = BTreeMap::new();
+        for datapack in self.datapacks() {
+            for (function, path) in find_functions(&datapack).await? {
+                if find_exclude_pattern(&self.exclude, &function).is_none() {
+                    loaded_sources.entry(function).or_insert(path);
+                }
+            }
+        }
+        Ok(loaded_sources)
+    }
+
+    /// Scans the debugged datapacks again and returns an event for every function that was added
+    /// or removed since the last scan.
+    async fn update_loaded_sources(&mut self) -> io::Result<Vec<LoadedSourceEventBody>> {
+        let loaded_sources = self.find_loaded_sources().await?;
+        let removed = self
+            .loaded_sources
+            .iter()
+            .filter(|(function, _)| !loaded_sources.contains_key(function))
+            .map(|(function, path)| {
+                loaded_source_event(LoadedSourceEventReason::Removed, function, path)
+            });
+        let new = loaded_sources
+            .iter()
+            .filter(|(function, _)| !self.loaded_sources.contains_key(function))
+            .map(|(function, path)| {
+                loaded_source_event(LoadedSourceEventReason::New, function, path)
+            });
+        let events = removed.chain(new).collect();
+        self.loaded_sources = loaded_sources;
+        Ok(events)
+    }
+
+    /// Returns why breakpoints in the function can't be verified or [None] if they can.
+    fn get_unverified_reason(&self, function: &ResourceLocation) -> Option<String> {
+        if !self.datapack_namespaces.contains(function.namespace()) {
+            Some(NOT_IN_DATAPACK_MESSAGE.to_string())
+        } else if let Some(pattern) = find_exclude_pattern(&self.exclude, function) {
+            Some(format!("Function is excluded by pattern '{}'", pattern))
+        } else if self
+            .generated
+            .as_ref()
+            .is_some_and(|generated| generated.is_excluded(function))
+        {
+            Some(format!("Function is marked with '{}'", SKIP_MARKER))
+        } else if self
+            .generated
+            .as_ref()
+            .is_some_and(|generated| generated.is_oversized(function))
+        {
+            Some(format!(
+                "Function has more than {} consecutive commands without a function call or \
+                breakpoint, so Minecraft may not run it completely",
+                DEFAULT_MAX_COMMANDS
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the function was generated by the debugger to call the functions of a
+    /// function tag. These functions are named `<namespace>:tags/<tag namespace>/<tag path>` and
+    /// don't exist in the datapack.
+    fn is_tag_function(&self, function: &ResourceLocation) -> bool {
+        function.namespace() == self.namespace && function.path().starts_with("tags/")
+    }
+
+    fn is_excluded(&self, function: &ResourceLocation) -> bool {
+        find_exclude_pattern(&self.exclude, function).is_some()
+            || self
+                .generated
+                .as_ref()
+                .is_some_and(|generated| generated.is_excluded(function))
     }
 
     fn new_step_breakpoint(
@@ -139,6 +681,8 @@ impl MinecraftSession {
     async fn create_step_in_breakpoints(
         &self,
         stack_trace: &[McfunctionStackFrame],
+        target_id: Option<i32>,
+        granularity: &SteppingGranularity,
         parser: &CommandParser,
     ) -> Result<Vec<(ResourceLocation, LocalBreakpoint)>, RequestError<io::Error>> {
         let mut breakpoints = Vec::new();
@@ -150,9 +694,41 @@ impl MinecraftSession {
         let current_depth = stack_trace.len() - 1;
         let current_path = self.get_function_path(&current.location.function);
 
-        let callee =
-            get_function_command(current_path, current.location.line_number, &parser).await?;
-        if let Some(callee) = callee {
+        // Stepping into an excluded function or a function tag behaves like stepping over it
+        let can_step_into =
+            |callee: &ResourceLocation| !self.is_excluded(callee) && !callee.is_tag();
+        let callee = if let Some(target_id) = target_id {
+            let callee = get_function_calls(current_path, current.location.line_number, parser)
+                .await?
+                .into_iter()
+                .filter(|(_column_index, callee)| can_step_into(callee))
+                .find(|(column_index, _callee)| *column_index as i32 == target_id);
+            if callee.is_none() {
+                return Err(PartialErrorResponse::new(format!(
+                    "Unknown step in target: {}",
+                    target_id
+                ))
+                .into());
+            }
+            callee
+        } else {
+            get_function_command(current_path, current.location.line_number, parser)
+                .await?
+                .filter(|(_column_index, callee)| can_step_into(callee))
+        };
+        // Before the line the execute prefix is the next statement, so it is stepped over first
+        let before_line = self
+            .stopped_data
+            .as_ref()
+            .map(|it| it.position.position_in_line)
+            == Some(BreakpointPositionInLine::Breakpoint);
+        let steps_after_execute = target_id.is_none()
+            && before_line
+            && self
+                .find_step_after_execute(stack_trace, granularity, parser)
+                .await?
+                .is_some();
+        if let Some((_column_index, callee)) = callee.filter(|_| !steps_after_execute) {
             let callee_path = self.get_function_path(&callee);
             let callee_line_number = find_first_target_line_number(&callee_path, &parser).await?;
 
@@ -165,7 +741,7 @@ impl MinecraftSession {
         }
 
         breakpoints.extend(
-            self.create_step_over_breakpoints(&stack_trace, &parser)
+            self.create_step_over_breakpoints(&stack_trace, granularity, &parser)
                 .await?,
         );
 
@@ -175,6 +751,7 @@ impl MinecraftSession {
     async fn create_step_over_breakpoints(
         &self,
         stack_trace: &[McfunctionStackFrame],
+        granularity: &SteppingGranularity,
         parser: &CommandParser,
     ) -> Result<Vec<(ResourceLocation, LocalBreakpoint)>, RequestError<io::Error>> {
         let mut breakpoints = Vec::new();
@@ -186,6 +763,13 @@ impl MinecraftSession {
         let current_depth = stack_trace.len() - 1;
         let current_path = self.get_function_path(&current.location.function);
 
+        if let Some(breakpoint) = self
+            .find_step_after_execute(stack_trace, granularity, parser)
+            .await?
+        {
+            breakpoints.push(breakpoint);
+        }
+
         let next_line_number = find_step_target_line_number(
             &current_path,
             current.location.line_number,
@@ -193,6 +777,9 @@ impl MinecraftSession {
             false,
         )
         .await?;
+        // A return command that is executed ends the function early, so we might need to step out
+        let may_return =
+            is_return_command(&current_path, current.location.line_number, parser).await?;
         if let Some(next_line_number) = next_line_number {
             breakpoints.push(self.new_step_breakpoint(
                 current.location.function.clone(),
@@ -200,7 +787,8 @@ impl MinecraftSession {
                 BreakpointPositionInLine::Breakpoint,
                 current_depth,
             ));
-        } else {
+        }
+        if next_line_number.is_none() || may_return {
             breakpoints.extend(
                 self.create_step_out_breakpoint(&stack_trace, &parser)
                     .await?,
@@ -219,6 +807,44 @@ impl MinecraftSession {
         Ok(breakpoints)
     }
 
+    /// With statement granularity the execute prefix and the function call of a line are separate
+    /// statements. Returns a step breakpoint after the execute prefix if the current line has one
+    /// and execution is not already past the function call.
+    async fn find_step_after_execute(
+        &self,
+        stack_trace: &[McfunctionStackFrame],
+        granularity: &SteppingGranularity,
+        parser: &CommandParser,
+    ) -> Result<Option<(ResourceLocation, LocalBreakpoint)>, RequestError<io::Error>> {
+        if *granularity == SteppingGranularity::Line {
+            return Ok(None);
+        }
+        let (current, position) = match (stack_trace.first(), self.stopped_data.as_ref()) {
+            (Some(current), Some(stopped_data)) => (current, &stopped_data.position),
+            _ => return Ok(None),
+        };
+        if position.position_in_line == BreakpointPositionInLine::AfterFunction {
+            return Ok(None);
+        }
+        let current_path = self.get_function_path(&current.location.function);
+        let has_execute_prefix =
+            get_function_command(current_path, current.location.line_number, parser)
+                .await?
+                .filter(|(column_index, callee)| *column_index > 0 && !self.is_excluded(callee))
+                .is_some();
+        if !has_execute_prefix {
+            return Ok(None);
+        }
+        // The breakpoint is checked while the depth is already increased for the callee
+        let callee_depth = stack_trace.len();
+        Ok(Some(self.new_step_breakpoint(
+            current.location.function.clone(),
+            current.location.line_number,
+            BreakpointPositionInLine::AfterExecute,
+            callee_depth,
+        )))
+    }
+
     async fn create_step_out_breakpoint(
         &self,
         stack_trace: &[McfunctionStackFrame],
@@ -226,10 +852,14 @@ impl MinecraftSession {
     ) -> Result<Vec<(ResourceLocation, LocalBreakpoint)>, RequestError<io::Error>> {
         let mut breakpoints = Vec::new();
 
-        if stack_trace.len() <= 1 {
-            return Ok(breakpoints);
-        }
-        let caller = &stack_trace[1];
+        // The generated function of a function tag has no source, so we step out to its caller
+        let caller_index = match (1..stack_trace.len())
+            .find(|index| !self.is_tag_function(&stack_trace[*index].location.function))
+        {
+            Some(caller_index) => caller_index,
+            None => return Ok(breakpoints),
+        };
+        let caller = &stack_trace[caller_index];
 
         let line_number = find_step_target_line_number(
             self.get_function_path(&caller.location.function),
@@ -246,7 +876,7 @@ impl MinecraftSession {
         };
 
         let current_depth = stack_trace.len() - 1;
-        let caller_depth = current_depth - 1;
+        let caller_depth = current_depth - caller_index;
         breakpoints.push(self.new_step_breakpoint(
             caller.location.function.clone(),
             line_number.unwrap_or(caller.location.line_number),
@@ -257,141 +887,1038 @@ impl MinecraftSession {
         Ok(breakpoints)
     }
 
-    fn inject_commands(&mut self, commands: Vec<Command>) -> Result<(), PartialErrorResponse> {
-        inject_commands(&mut self.connection, commands)
-            .map_err(|e| PartialErrorResponse::new(format!("Failed to inject commands: {}", e)))
-    }
+    /// Creates a breakpoint that suspends the next executor of the suspended function before it
+    /// runs, so that only the suspended executor is resumed. Returns no breakpoints if there are no
+    /// [pending executors](MinecraftSession::get_pending_executors).
+    async fn create_next_executor_breakpoint(
+        &mut self,
+        parser: &CommandParser,
+    ) -> Result<Vec<(ResourceLocation, LocalBreakpoint)>, RequestError<io::Error>> {
+        let pending_executors = self
+            .get_pending_executors()
+            .await
+            .map_err(RequestError::Terminate)?;
+        if pending_executors.is_empty() {
+            return Ok(Vec::new());
+        }
+        let depth = self.get_cached_stack_trace()?.len() - 1;
+        // Unwrap is safe, because there are only pending executors while stopped
+        let position = &self.stopped_data.as_ref().unwrap().position;
+        let function = position.function.clone();
+        let (line_number, position_in_line, depth) =
+            if position.position_in_line == BreakpointPositionInLine::AfterExecute {
+                // Every executor suspends after the execute prefix before it calls the function
+                let line_number = position.line_number;
+                (
+                    line_number,
+                    BreakpointPositionInLine::AfterExecute,
+                    depth + 1,
+                )
+            } else {
+                let path = self.get_function_path(&function);
+                let line_number = find_first_target_line_number(path, parser).await?;
+                (line_number, BreakpointPositionInLine::Breakpoint, depth)
+            };
 
-    fn replace_ns(&self, command: &str) -> String {
-        command.replace("-ns-", &self.namespace)
+        // Only the executors that are pending now are tagged, so a later call of the function at
+        // the same depth is not suspended
+        self.inject_commands(vec![
+            Command::new(self.replace_ns(&format!(
+                "tag @e[type=area_effect_cloud,tag={0}] remove {0}",
+                PENDING_EXECUTOR_TAG
+            ))),
+            Command::new(self.replace_ns(&format!(
+                "execute as {} if score @s -ns-_depth = current -ns-_depth run tag @s add {}",
+                PENDING_CONTEXTS, PENDING_EXECUTOR_TAG
+            ))),
+        ])?;
+        let condition = self.replace_ns(&format!(
+            "if score current -ns-_depth matches {0} if entity @e[\
+                type=area_effect_cloud,\
+                tag=-ns-_context,\
+                tag=-ns-_current,\
+                tag={1},\
+                scores={{-ns-_depth={0}}},\
+            ]",
+            depth, PENDING_EXECUTOR_TAG
+        ));
+        let kind = BreakpointKind::Step { condition };
+        let position = LocalBreakpointPosition {
+            line_number,
+            position_in_line,
+        };
+        Ok(vec![(function, LocalBreakpoint { kind, position })])
     }
 
-    async fn get_context_entity_id(&mut self, depth: i32) -> Result<i32, PartialErrorResponse> {
-        let events = self.connection.add_listener();
-
-        const START: &str = "get_context_entity_id.start";
-        const END: &str = "get_context_entity_id.end";
+    /// Resumes the suspended program with all breakpoints disabled and waits until it exits.
+    /// Returns false if the program did not exit within
+    /// [run_to_completion_timeout](MinecraftSession::run_to_completion_timeout).
+    async fn run_to_completion(
+        &mut self,
+        parser: &Arc<CommandParser>,
+    ) -> Result<bool, PartialErrorResponse> {
+        let stopped_data = match self.stopped_data.take() {
+            Some(stopped_data) => stopped_data,
+            None => return Ok(false),
+        };
+        self.scopes.clear();
+        self.entity_nbt.clear();
+        self.globals = None;
+        self.objectives_checked = false;
+
+        // The continue point is the only breakpoint, so the program can resume but not suspend
+        let mut continue_point = MultiMap::new();
+        continue_point.insert(
+            stopped_data.position.function.clone(),
+            LocalBreakpoint {
+                kind: BreakpointKind::Continue,
+                position: LocalBreakpointPosition {
+                    line_number: stopped_data.position.line_number,
+                    position_in_line: stopped_data.position.position_in_line,
+                },
+            },
+        );
+        generate_datapack(self, parser, &MultiMap::new(), &continue_point).await?;
 
-        let scoreboard = self.replace_ns("-ns-_id");
+        let events = self.connection.add_named_listener(LISTENER_NAME);
         self.inject_commands(vec![
-            Command::named(LISTENER_NAME, summon_named_entity_command(START)),
-            Command::new(query_scoreboard_command(
-                self.replace_ns(&format!(
-                    "@e[\
-                        type=area_effect_cloud,\
-                        tag=-ns-_context,\
-                        tag=-ns-_active,\
-                        tag=-ns-_current,\
-                        scores={{-ns-_depth={}}},\
-                    ]",
-                    depth
-                )),
-                &scoreboard,
-            )),
-            Command::named(LISTENER_NAME, summon_named_entity_command(END)),
+            Command::new("reload"),
+            Command::new("function debug:resume"),
         ])?;
 
-        events_between(events, START, END)
-            .filter_map(|event| event.output.parse::<QueryScoreboardOutput>().ok())
-            .filter(|output| output.scoreboard == scoreboard)
-            .map(|output| output.score)
-            .next()
-            .await
-            .ok_or_else(|| PartialErrorResponse::new("Minecraft connection closed".to_string()))
+        let mut exited = events
+            .filter_map(|event| event.output.parse::<AddTagOutput>().ok())
+            .filter(|output| output.entity == LISTENER_NAME && output.tag == "exited");
+        Ok(matches!(
+            timeout(self.run_to_completion_timeout, exited.next()).await,
+            Ok(Some(_))
+        ))
     }
 
-    fn get_cached_stack_trace(
-        &self,
-    ) -> Result<&Vec<McfunctionStackFrame>, RequestError<io::Error>> {
-        let stack_trace = &self
-            .stopped_data
-            .as_ref()
-            .ok_or(PartialErrorResponse::new("Not stopped".to_string()))?
-            .stack_trace;
-        Ok(stack_trace)
+    /// Injects a batch of commands and returns its sequence number, see
+    /// [MinecraftSession::await_applied]. A batch ends with a marker carrying this number. Minecraft
+    /// finishes a reload only after the commands of the batch ran, so for a batch that reloads, the
+    /// number is stored in a score instead, which the load function of the debug datapack reports.
+    fn inject_commands(&mut self, mut commands: Vec<Command>) -> Result<u64, PartialErrorResponse> {
+        self.drain_batch_events();
+        let batch = self.injected_batch + 1;
+        if commands
+            .iter()
+            .any(|command| command.get_command() == "reload")
+        {
+            let set_reload_batch = [
+                // The objective is missing until the debug datapack is installed
+                "scoreboard objectives add -ns-_global dummy".to_string(),
+                format!(
+                    "scoreboard players set {} -ns-_global {}",
+                    RELOAD_BATCH, batch
+                ),
+            ];
+            let set_reload_batch = set_reload_batch.map(|it| Command::new(self.replace_ns(&it)));
+            commands.splice(0..0, set_reload_batch);
+        } else {
+            commands.push(Command::named(
+                LISTENER_NAME,
+                summon_named_entity_command(&batch_marker(batch)),
+            ));
+        }
+        inject_commands(&mut self.connection, &self.tracer, commands)
+            .map_err(|e| PartialErrorResponse::new(format!("Failed to inject commands: {}", e)))?;
+        self.injected_batch = batch;
+        Ok(batch)
     }
 
-    async fn get_stack_trace(&mut self) -> io::Result<Vec<McfunctionStackFrame>> {
-        const START: &str = "stack_trace.start";
-        const END: &str = "stack_trace.end";
-        let stack_trace_tag = self.replace_ns("-ns-_stack_trace");
-        let depth_scoreboard = self.replace_ns("-ns-_depth");
-
-        let events = self.connection.add_listener();
-
-        let commands = vec![
-            Command::named(LISTENER_NAME, summon_named_entity_command(START)),
-            Command::new(self.replace_ns(&format!(
-                "execute as @e[type=area_effect_cloud,tag=-ns-_function_call] run {}",
-                query_scoreboard_command("@s", &depth_scoreboard)
-            ))),
-            Command::new(self.replace_ns(&format!(
-                "execute as @e[type=area_effect_cloud,tag=-ns-_breakpoint] run tag @s add {}",
-                stack_trace_tag
-            ))),
-            Command::new(self.replace_ns(&format!(
-                "execute as @e[type=area_effect_cloud,tag=-ns-_breakpoint] run tag @s remove {}",
-                stack_trace_tag
-            ))),
-            Command::named(LISTENER_NAME, summon_named_entity_command(END)),
-        ];
-        inject_commands(&mut self.connection, commands)?;
-
-        let mut stack_trace = Vec::new();
-        let mut events = events_between(events, START, END);
-        while let Some(event) = events.next().await {
-            if let Ok(location) = event.executor.parse() {
-                let id = if let Some(output) = event
-                    .output
-                    .parse::<QueryScoreboardOutput>()
-                    .ok()
-                    .filter(|output| output.scoreboard == depth_scoreboard)
-                {
-                    output.score // depth
-                } else if let Some(_) = event
-                    .output
-                    .parse::<AddTagOutput>()
-                    .ok()
-                    .filter(|output| output.tag == stack_trace_tag)
-                {
-                    stack_trace.len() as i32 // Breakpoint
-                } else {
-                    continue; // Shouldn't actually happen
-                };
-                stack_trace.push(McfunctionStackFrame { id, location });
+    /// Waits until Minecraft executed the batch of commands with the given sequence number and
+    /// therefore all batches before it. Operations that depend on an earlier batch, like resuming
+    /// after a reload, call this instead of hoping that Minecraft is fast enough. If Minecraft does
+    /// not execute the batch within [BATCH_TIMEOUT] a warning is logged and the session proceeds.
+    async fn await_applied(&mut self, batch: u64) {
+        self.drain_batch_events();
+        let global_objective = self.replace_ns("-ns-_global");
+        let events = self.batch_events.get_mut().unwrap();
+        let applied_batch = &mut self.applied_batch;
+        let _ = timeout(BATCH_TIMEOUT, async {
+            while *applied_batch < batch {
+                match events.next().await {
+                    Some(event) => apply_batch_marker(&event, &global_objective, applied_batch),
+                    None => break,
+                }
             }
+        })
+        .await;
+        if self.applied_batch < batch {
+            warn!(
+                "Minecraft did not execute the injected commands {} within {:?}",
+                batch, BATCH_TIMEOUT
+            );
         }
-        stack_trace.sort_by_key(|it| -it.id);
-        Ok(stack_trace)
     }
 
-    async fn uninstall_datapack(&mut self) -> io::Result<()> {
-        let events = self.connection.add_listener();
+    /// Processes the markers that Minecraft logged so far without waiting, so the events of the
+    /// listener don't pile up.
+    fn drain_batch_events(&mut self) {
+        let global_objective = self.replace_ns("-ns-_global");
+        let events = self.batch_events.get_mut().unwrap();
+        while let Some(Some(event)) = events.next().now_or_never() {
+            apply_batch_marker(&event, &global_objective, &mut self.applied_batch);
+        }
+    }
+
+    /// Waits until Minecraft executed all batches of commands injected so far.
+    async fn await_all_applied(&mut self) {
+        self.await_applied(self.injected_batch).await
+    }
+
+    fn replace_ns(&self, command: &str) -> String {
+        command.replace("-ns-", &self.namespace)
+    }
+
+    /// Executes `commands` while the selected entity of the frame is the only entity outside of the
+    /// debugger with a `-ns-_id` score of 0 and returns the log events in between.
+    async fn query_selected_entity(
+        &mut self,
+        frame_id: i32,
+        commands: Vec<Command>,
+    ) -> Result<Vec<LogEvent>, PartialErrorResponse> {
+        const START: &str = "variables.start";
+        const END: &str = "variables.end";
+
+        let events = self.connection.add_listener();
+
+        let execute_as_context = format!(
+            "execute as @e[\
+                type=area_effect_cloud,\
+                tag=-ns-_context,\
+                tag=-ns-_active,\
+                tag=-ns-_current,\
+                scores={{-ns-_depth={}}},\
+            ] run",
+            frame_id
+        );
+        let decrement_ids = self.replace_ns(&format!(
+            "{} scoreboard players operation @e[tag=!-ns-_context] -ns-_id -= @s -ns-_id",
+            execute_as_context
+        ));
+        let increment_ids = self.replace_ns(&format!(
+            "{} scoreboard players operation @e[tag=!-ns-_context] -ns-_id += @s -ns-_id",
+            execute_as_context
+        ));
+        let mut all_commands = vec![
+            Command::new(logged_command(enable_logging_command())),
+            Command::new(named_logged_command(
+                LISTENER_NAME,
+                summon_named_entity_command(START),
+            )),
+            Command::new(logged_command(decrement_ids)),
+        ];
+        all_commands.extend(commands);
+        all_commands.extend([
+            Command::new(logged_command(increment_ids)),
+            Command::new(named_logged_command(
+                LISTENER_NAME,
+                summon_named_entity_command(END),
+            )),
+            Command::new(logged_command(reset_logging_command())),
+        ]);
+        self.inject_commands(all_commands)?;
+
+        Ok(events_between(events, START, END).collect().await)
+    }
+
+    /// Returns the NBT of the selected entity of the frame. The NBT is cached until the next resume.
+    async fn query_nbt(&mut self, frame_id: i32) -> Result<&Value, PartialErrorResponse> {
+        if !self.entity_nbt.contains_key(&frame_id) {
+            let command =
+                self.replace_ns("data get entity @e[tag=!-ns-_context,scores={-ns-_id=0},limit=1]");
+            let command = Command::new(logged_command(command));
+            let nbt = self
+                .query_selected_entity(frame_id, vec![command])
+                .await?
+                .into_iter()
+                .find_map(|event| event.output.parse::<EntityDataOutput>().ok())
+                .map(|output| output.data)
+                .unwrap_or_else(|| Value::Object(Default::default()));
+            self.entity_nbt.insert(frame_id, nbt);
+        }
+        Ok(&self.entity_nbt[&frame_id])
+    }
+
+    /// Returns the tags of the selected entity of the frame except for the tags of the debugger.
+    async fn query_tags(&mut self, frame_id: i32) -> Result<Vec<String>, PartialErrorResponse> {
+        let command = self.replace_ns("tag @e[tag=!-ns-_context,scores={-ns-_id=0}] list");
+        let command = Command::new(logged_command(command));
+        let internal_prefix = self.replace_ns("-ns-_");
+        Ok(self
+            .query_selected_entity(frame_id, vec![command])
+            .await?
+            .into_iter()
+            .filter_map(|event| event.output.parse::<TagListOutput>().ok())
+            .flat_map(|output| output.tags)
+            .filter(|tag| !tag.starts_with(&internal_prefix))
+            .collect())
+    }
+
+    /// Returns the `Pos` list of the selected entity of the frame and its dimension, if it is one
+    /// of [DIMENSIONS].
+    async fn query_position(
+        &mut self,
+        frame_id: i32,
+    ) -> Result<(Vec<Value>, Option<&'static str>), PartialErrorResponse> {
+        let dimension_names = (0..DIMENSIONS.len())
+            .map(|index| format!("position.dimension.{}", index))
+            .collect::<Vec<_>>();
+        // The distance argument limits the selector to the dimension of the execute command
+        let commands = DIMENSIONS
+            .iter()
+            .zip(&dimension_names)
+            .map(|(dimension, name)| {
+                Command::new(named_logged_command(
+                    name,
+                    self.replace_ns(&format!(
+                        "execute in {} as @e[tag=!-ns-_context,scores={{-ns-_id=0}},distance=0..] \
+                        run data get entity @s Pos",
+                        dimension
+                    )),
+                ))
+            })
+            .collect();
+        let position = self
+            .query_selected_entity(frame_id, commands)
+            .await?
+            .into_iter()
+            .find_map(|event| {
+                let index = dimension_names
+                    .iter()
+                    .position(|name| *name == event.executor)?;
+                let output = event.output.parse::<EntityDataOutput>().ok()?;
+                Some((output.data, DIMENSIONS[index]))
+            });
+        Ok(match position {
+            Some((Value::Array(pos), dimension)) => (pos, Some(dimension)),
+            // The entity is in a custom dimension
+            _ => match self.query_nbt(frame_id).await?.get("Pos") {
+                Some(Value::Array(pos)) => (pos.clone(), None),
+                _ => (Vec::new(), None),
+            },
+        })
+    }
+
+    /// Returns the state of the world. All values are queried in a single batch of commands and
+    /// cached until the next resume.
+    async fn query_globals(&mut self) -> Result<&GlobalsScope, PartialErrorResponse> {
+        const START: &str = "globals.start";
+        const GAMETIME: &str = "globals.gametime";
+        const DAYTIME: &str = "globals.daytime";
+        const RAINING: &str = "globals.raining";
+        const THUNDERING: &str = "globals.thundering";
+        const DIFFICULTY: &str = "globals.difficulty";
+        const GAMERULE: &str = "globals.gamerule";
+        const END: &str = "globals.end";
+
+        if self.globals.is_none() {
+            let events = self.connection.add_listener();
+
+            let mut commands = vec![
+                Command::new(logged_command(enable_logging_command())),
+                Command::new(named_logged_command(
+                    LISTENER_NAME,
+                    summon_named_entity_command(START),
+                )),
+                Command::new(named_logged_command(GAMETIME, "time query gametime")),
+                Command::new(named_logged_command(DAYTIME, "time query daytime")),
+                // Only a passed test is logged
+                Command::new(named_logged_command(
+                    RAINING,
+                    self.replace_ns("execute if predicate -ns-:raining"),
+                )),
+                Command::new(named_logged_command(
+                    THUNDERING,
+                    self.replace_ns("execute if predicate -ns-:thundering"),
+                )),
+                Command::new(named_logged_command(DIFFICULTY, "difficulty")),
+            ];
+            commands.extend(GAMERULES.iter().map(|gamerule| {
+                Command::new(named_logged_command(
+                    GAMERULE,
+                    format!("gamerule {}", gamerule),
+                ))
+            }));
+            commands.extend([
+                Command::new(named_logged_command(
+                    LISTENER_NAME,
+                    summon_named_entity_command(END),
+                )),
+                Command::new(logged_command(reset_logging_command())),
+            ]);
+            self.inject_commands(commands)?;
+
+            let mut globals = GlobalsScope::default();
+            let mut gamerules = HashMap::new();
+            let mut events = Box::pin(events_between(events, START, END));
+            while let Some(event) = events.next().await {
+                match event.executor.as_str() {
+                    GAMETIME => {
+                        globals.gametime = event
+                            .output
+                            .parse::<TimeQueryOutput>()
+                            .ok()
+                            .map(|it| it.time)
+                    }
+                    DAYTIME => {
+                        globals.daytime = event
+                            .output
+                            .parse::<TimeQueryOutput>()
+                            .ok()
+                            .map(|it| it.time)
+                    }
+                    RAINING => globals.raining = true,
+                    THUNDERING => globals.thundering = true,
+                    DIFFICULTY => {
+                        globals.difficulty = event
+                            .output
+                            .parse::<DifficultyOutput>()
+                            .ok()
+                            .map(|it| it.difficulty)
+                    }
+                    GAMERULE => {
+                        if let Ok(output) = event.output.parse::<GameruleOutput>() {
+                            gamerules.insert(output.gamerule, output.value);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            globals.gamerules = GAMERULES
+                .iter()
+                .filter_map(|gamerule| Some((gamerule.to_string(), gamerules.remove(*gamerule)?)))
+                .collect();
+            self.globals = Some(globals);
+        }
+        Ok(self.globals.as_ref().unwrap())
+    }
+
+    /// Returns true if the world still contains the objectives of a previous debug session. The
+    /// objectives are kept when a session terminates, so they are reused instead of being added
+    /// again, but the data of the previous session has to be reset.
+    async fn has_installed_objectives(&mut self) -> Result<bool, PartialErrorResponse> {
+        const START: &str = "installed_objectives.start";
+        const OBJECTIVES: &str = "installed_objectives.list";
+        const END: &str = "installed_objectives.end";
+
+        let events = self.connection.add_listener();
+        self.inject_commands(vec![
+            Command::new(logged_command(enable_logging_command())),
+            Command::new(named_logged_command(
+                LISTENER_NAME,
+                summon_named_entity_command(START),
+            )),
+            Command::new(named_logged_command(
+                OBJECTIVES,
+                "scoreboard objectives list",
+            )),
+            Command::new(named_logged_command(
+                LISTENER_NAME,
+                summon_named_entity_command(END),
+            )),
+            Command::new(logged_command(reset_logging_command())),
+        ])?;
+        let version_objective = self.replace_ns(VERSION_OBJECTIVE);
+        let installed = Box::pin(events_between(events, START, END))
+            .filter(|event| event.executor == OBJECTIVES)
+            .filter_map(|event| event.output.parse::<ObjectivesListOutput>().ok())
+            .next()
+            .await
+            .is_some_and(|output| output.objectives.contains(&version_objective));
+        Ok(installed)
+    }
+
+    /// Compares the objectives of the world with the objectives known to the debug datapack once
+    /// per stop. If objectives were created at runtime, `-ns-:log_scores_dynamic` and
+    /// `-ns-:update_scores_dynamic` are written again and reloaded with the next resume. Returns the
+    /// commands that log the scores of the objectives created at runtime.
+    async fn query_dynamic_objectives(&mut self) -> Result<Vec<Command>, PartialErrorResponse> {
+        const START: &str = "objectives.start";
+        const OBJECTIVES: &str = "objectives.list";
+        const END: &str = "objectives.end";
+
+        if !self.objectives_checked {
+            let events = self.connection.add_listener();
+            self.inject_commands(vec![
+                Command::new(logged_command(enable_logging_command())),
+                Command::new(named_logged_command(
+                    LISTENER_NAME,
+                    summon_named_entity_command(START),
+                )),
+                Command::new(named_logged_command(
+                    OBJECTIVES,
+                    "scoreboard objectives list",
+                )),
+                Command::new(named_logged_command(
+                    LISTENER_NAME,
+                    summon_named_entity_command(END),
+                )),
+                Command::new(logged_command(reset_logging_command())),
+            ])?;
+            let internal_prefix = self.replace_ns("-ns-_");
+            let static_objectives = self.generated.as_ref().map(|it| &it.objectives);
+            let dynamic_objectives = Box::pin(events_between(events, START, END))
+                .filter(|event| event.executor == OBJECTIVES)
+                .filter_map(|event| event.output.parse::<ObjectivesListOutput>().ok())
+                .next()
+                .await
+                .map(|output| output.objectives)
+                .unwrap_or_default()
+                .into_iter()
+                // Display names can contain spaces, but names can't
+                .filter(|objective| !objective.contains(' '))
+                .filter(|objective| !objective.starts_with(&internal_prefix))
+                .filter(|objective| static_objectives.map_or(true, |it| !it.contains(objective)))
+                .collect::<BTreeSet<_>>();
+
+            if dynamic_objectives != self.dynamic_objectives {
+                let log_scores = write_dynamic_scores_functions(
+                    &self.output_path,
+                    &self.namespace,
+                    LISTENER_NAME,
+                    &dynamic_objectives,
+                    &FsSink,
+                )
+                .await
+                .map_err(|e| {
+                    PartialErrorResponse::new(format!("Failed to write scores functions: {}", e))
+                })?;
+                self.dynamic_objectives = dynamic_objectives;
+                self.unloaded_log_scores = Some(log_scores);
+            }
+            self.objectives_checked = true;
+        }
+
+        Ok(match &self.unloaded_log_scores {
+            Some(log_scores) => log_scores.iter().map(Command::new).collect(),
+            None => vec![Command::new(
+                self.replace_ns("function -ns-:log_scores_dynamic"),
+            )],
+        })
+    }
+
+    fn get_cached_stack_trace(
+        &self,
+    ) -> Result<&Vec<McfunctionStackFrame>, RequestError<io::Error>> {
+        let stack_trace = &self
+            .stopped_data
+            .as_ref()
+            .ok_or(PartialErrorResponse::new("Not stopped".to_string()))?
+            .stack_trace;
+        Ok(stack_trace)
+    }
+
+    /// Queries the ticks that `-ns-:tick` counted since the launch.
+    async fn get_session_ticks(&mut self) -> io::Result<SessionTicks> {
+        const START: &str = "session_ticks.start";
+        const END: &str = "session_ticks.end";
+        let global_scoreboard = self.replace_ns("-ns-_global");
+
+        let events = self.connection.add_listener();
+        let commands = vec![
+            Command::named(LISTENER_NAME, summon_named_entity_command(START)),
+            Command::new(query_scoreboard_command(
+                "session_ticks",
+                &global_scoreboard,
+            )),
+            Command::new(query_scoreboard_command(
+                "suspended_ticks",
+                &global_scoreboard,
+            )),
+            Command::named(LISTENER_NAME, summon_named_entity_command(END)),
+        ];
+        inject_commands(&mut self.connection, &self.tracer, commands)?;
+
+        let mut session_ticks = SessionTicks::default();
+        let mut events = events_between(events, START, END);
+        while let Some(event) = events.next().await {
+            if let Some(output) = event
+                .output
+                .parse::<QueryScoreboardOutput>()
+                .ok()
+                .filter(|output| output.scoreboard == global_scoreboard)
+            {
+                match output.entity.as_str() {
+                    "session_ticks" => session_ticks.total = output.score,
+                    "suspended_ticks" => session_ticks.suspended = output.score,
+                    _ => {}
+                }
+            }
+        }
+        Ok(session_ticks)
+    }
+
+    /// Queries all frames of the stack trace together with their execution context in a single
+    /// batch of commands, so that only one tick is spent waiting for Minecraft.
+    async fn get_stack_trace(&mut self) -> io::Result<Vec<McfunctionStackFrame>> {
+        // The stack trace must reflect the last resume
+        self.await_all_applied().await;
+
+        const START: &str = "stack_trace.start";
+        const SERVER_CONTEXTS: &str = "stack_trace.server_contexts";
+        const END: &str = "stack_trace.end";
+        let stack_trace_tag = self.replace_ns("-ns-_stack_trace");
+        let depth_scoreboard = self.replace_ns("-ns-_depth");
+        let dimension_names = (0..DIMENSIONS.len())
+            .map(|index| format!("stack_trace.dimension.{}", index))
+            .collect::<Vec<_>>();
+
+        let events = self.connection.add_listener();
+
+        let mut commands = vec![
+            Command::named(LISTENER_NAME, summon_named_entity_command(START)),
+            Command::new(self.replace_ns(&format!(
+                "execute as @e[type=area_effect_cloud,tag=-ns-_function_call] run {}",
+                query_scoreboard_command("@s", &depth_scoreboard)
+            ))),
+            Command::new(self.replace_ns(&format!(
+                "execute as @e[type=area_effect_cloud,tag=-ns-_breakpoint] run tag @s add {}",
+                stack_trace_tag
+            ))),
+            Command::new(self.replace_ns(&format!(
+                "execute as @e[type=area_effect_cloud,tag=-ns-_breakpoint] run tag @s remove {}",
+                stack_trace_tag
+            ))),
+            Command::named(LISTENER_NAME, summon_named_entity_command(SERVER_CONTEXTS)),
+            Command::new(self.replace_ns(&format!(
+                "execute as @e[\
+                    type=area_effect_cloud,\
+                    tag=-ns-_context,\
+                    tag=-ns-_active,\
+                    tag=-ns-_current,\
+                    scores={{-ns-_id=0}},\
+                ] run {}",
+                query_scoreboard_command("@s", &depth_scoreboard)
+            ))),
+        ];
+        // The distance argument limits the selectors to the dimension of the execute command
+        for (dimension, name) in DIMENSIONS.iter().zip(&dimension_names) {
+            commands.extend([
+                Command::named(LISTENER_NAME, summon_named_entity_command(name)),
+                Command::new(self.replace_ns(&format!(
+                    "execute in {} as @e[\
+                        type=area_effect_cloud,\
+                        tag=-ns-_function_call,\
+                        distance=0..,\
+                    ] run {}",
+                    dimension,
+                    query_scoreboard_command("@s", &depth_scoreboard)
+                ))),
+                Command::new(self.replace_ns(&format!(
+                    "execute in {} as @e[\
+                        type=area_effect_cloud,\
+                        tag=-ns-_breakpoint,\
+                        distance=0..,\
+                    ] run tag @s add {}",
+                    dimension, stack_trace_tag
+                ))),
+                Command::new(self.replace_ns(&format!(
+                    "execute as @e[type=area_effect_cloud,tag=-ns-_breakpoint] run tag @s remove {}",
+                    stack_trace_tag
+                ))),
+            ]);
+        }
+        commands.push(Command::named(
+            LISTENER_NAME,
+            summon_named_entity_command(END),
+        ));
+        inject_commands(&mut self.connection, &self.tracer, commands)?;
+
+        let mut stack_trace = Vec::new();
+        let mut server_contexts = HashSet::new();
+        let mut dimensions = HashMap::new();
+        let mut breakpoint_id = None;
+        let mut breakpoint_dimension = None;
+        let mut in_server_contexts = false;
+        let mut in_dimension = None;
+        let mut events = events_between(events, START, END);
+        while let Some(event) = events.next().await {
+            if is_summon_output(&event, SERVER_CONTEXTS) {
+                in_server_contexts = true;
+            } else if let Some(index) = dimension_names
+                .iter()
+                .position(|name| is_summon_output(&event, name))
+            {
+                in_dimension = Some(DIMENSIONS[index]);
+            } else if let Some(dimension) = in_dimension {
+                if let Some(output) = event
+                    .output
+                    .parse::<QueryScoreboardOutput>()
+                    .ok()
+                    .filter(|output| output.scoreboard == depth_scoreboard)
+                {
+                    dimensions.insert(output.score, dimension);
+                } else if let Some(_) = event
+                    .output
+                    .parse::<AddTagOutput>()
+                    .ok()
+                    .filter(|output| output.tag == stack_trace_tag)
+                {
+                    breakpoint_dimension = Some(dimension);
+                }
+            } else if in_server_contexts {
+                if let Some(output) = event
+                    .output
+                    .parse::<QueryScoreboardOutput>()
+                    .ok()
+                    .filter(|output| output.scoreboard == depth_scoreboard)
+                {
+                    server_contexts.insert(output.score);
+                }
+            } else if let Ok(location) = event.executor.parse() {
+                let id = if let Some(output) = event
+                    .output
+                    .parse::<QueryScoreboardOutput>()
+                    .ok()
+                    .filter(|output| output.scoreboard == depth_scoreboard)
+                {
+                    output.score // depth
+                } else if let Some(_) = event
+                    .output
+                    .parse::<AddTagOutput>()
+                    .ok()
+                    .filter(|output| output.tag == stack_trace_tag)
+                {
+                    let id = stack_trace.len() as i32; // Breakpoint
+                    breakpoint_id = Some(id);
+                    id
+                } else {
+                    continue; // Shouldn't actually happen
+                };
+                stack_trace.push(McfunctionStackFrame {
+                    id,
+                    location,
+                    server_context: false,
+                    dimension: None,
+                    end_column: None,
+                });
+            }
+        }
+        for frame in &mut stack_trace {
+            frame.server_context = server_contexts.contains(&frame.id);
+            frame.dimension = if breakpoint_id == Some(frame.id) {
+                breakpoint_dimension
+            } else {
+                dimensions.get(&frame.id).copied()
+            };
+            let path = self.get_function_path(&frame.location.function);
+            frame.end_column = find_end_column(path, &frame.location).await;
+        }
+        stack_trace.sort_by_key(|it| -it.id);
+        Ok(stack_trace)
+    }
+
+    /// Queries the executors of the suspended function that did not run it yet, in the order in
+    /// which Minecraft runs them. The result is cached until the program resumes.
+    async fn get_pending_executors(&mut self) -> io::Result<Vec<PendingExecutor>> {
+        match &self.stopped_data {
+            Some(StoppedData {
+                pending_executors: Some(pending_executors),
+                ..
+            }) => return Ok(pending_executors.clone()),
+            Some(_) => {}
+            None => return Ok(Vec::new()),
+        }
+
+        const START: &str = "pending_executors.start";
+        const IDS: &str = "pending_executors.ids";
+        const END: &str = "pending_executors.end";
+        let iter_scoreboard = self.replace_ns("-ns-_iter");
+        let id_scoreboard = self.replace_ns("-ns-_id");
+        let query_contexts = |scoreboard: &str| {
+            Command::new(self.replace_ns(&format!(
+                "execute as {} if score @s -ns-_depth = current -ns-_depth run {}",
+                PENDING_CONTEXTS,
+                query_scoreboard_command("@s", scoreboard)
+            )))
+        };
+        let query_iterations = query_contexts(&iter_scoreboard);
+        let query_ids = query_contexts(&id_scoreboard);
+        let events = self.connection.add_listener();
+        let commands = vec![
+            Command::named(LISTENER_NAME, summon_named_entity_command(START)),
+            query_iterations,
+            Command::named(LISTENER_NAME, summon_named_entity_command(IDS)),
+            query_ids,
+            Command::named(LISTENER_NAME, summon_named_entity_command(END)),
+        ];
+        inject_commands(&mut self.connection, &self.tracer, commands)?;
+
+        // Both queries select the context entities in the same order
+        let mut iterations = Vec::new();
+        let mut ids = Vec::new();
+        let mut in_ids = false;
+        let mut events = events_between(events, START, END);
+        while let Some(event) = events.next().await {
+            if is_summon_output(&event, IDS) {
+                in_ids = true;
+            } else if let Ok(output) = event.output.parse::<QueryScoreboardOutput>() {
+                if in_ids && output.scoreboard == id_scoreboard {
+                    ids.push(output.score);
+                } else if !in_ids && output.scoreboard == iter_scoreboard {
+                    iterations.push(output.score);
+                }
+            }
+        }
+        let names = self.get_executor_names(&ids).await?;
+        let mut pending_executors = iterations
+            .into_iter()
+            .zip(ids)
+            .map(|(iteration, id)| PendingExecutor {
+                iteration,
+                name: names
+                    .get(&id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Executor {}", iteration)),
+            })
+            .collect::<Vec<_>>();
+        pending_executors.sort_by_key(|it| it.iteration);
+
+        if let Some(stopped_data) = &mut self.stopped_data {
+            stopped_data.pending_executors = Some(pending_executors.clone());
+        }
+        Ok(pending_executors)
+    }
+
+    /// Queries the names of the executors with the given `-ns-_id` scores. The server has the id 0,
+    /// executors that were killed are missing.
+    async fn get_executor_names(&mut self, ids: &[i32]) -> io::Result<HashMap<i32, String>> {
+        let mut names = HashMap::from([(0, "Server".to_string())]);
+        let ids = ids.iter().filter(|id| **id != 0).collect::<BTreeSet<_>>();
+        if ids.is_empty() {
+            return Ok(names);
+        }
+
+        const START: &str = "executor_names.start";
+        const END: &str = "executor_names.end";
+        let id_scoreboard = self.replace_ns("-ns-_id");
+        let events = self.connection.add_listener();
+        let mut commands = vec![Command::named(
+            LISTENER_NAME,
+            summon_named_entity_command(START),
+        )];
+        for id in ids {
+            commands.push(Command::new(self.replace_ns(&format!(
+                "execute as @e[tag=!-ns-_context,scores={{-ns-_id={}}},limit=1] run {}",
+                id,
+                query_scoreboard_command("@s", &id_scoreboard)
+            ))));
+        }
+        commands.push(Command::named(
+            LISTENER_NAME,
+            summon_named_entity_command(END),
+        ));
+        inject_commands(&mut self.connection, &self.tracer, commands)?;
+
+        let mut events = events_between(events, START, END);
+        while let Some(event) = events.next().await {
+            if let Some(output) = event
+                .output
+                .parse::<QueryScoreboardOutput>()
+                .ok()
+                .filter(|output| output.scoreboard == id_scoreboard)
+            {
+                names.insert(output.score, output.entity);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Finds the position at which a function is suspended or [None] if no function is suspended.
+    /// The location of the breakpoint is the top frame of the stack trace, but its position in the
+    /// line is only known from the tags of the breakpoint entity.
+    async fn find_suspended_position(
+        &mut self,
+        stack_trace: &[McfunctionStackFrame],
+    ) -> io::Result<Option<BreakpointPosition>> {
+        const START: &str = "suspended_position.start";
+        const END: &str = "suspended_position.end";
+        let location = match stack_trace.first() {
+            Some(frame) => &frame.location,
+            None => return Ok(None),
+        };
+        let positions = [
+            BreakpointPositionInLine::Breakpoint,
+            BreakpointPositionInLine::AfterExecute,
+            BreakpointPositionInLine::AfterFunction,
+        ]
+        .map(|position_in_line| BreakpointPosition {
+            function: location.function.clone(),
+            line_number: location.line_number,
+            position_in_line,
+        });
+        let position_names = (0..positions.len())
+            .map(|index| format!("suspended_position.{}", index))
+            .collect::<Vec<_>>();
+        let suspended_tag = self.replace_ns("-ns-_suspended");
+
+        let events = self.connection.add_listener();
+
+        let mut commands = vec![Command::named(
+            LISTENER_NAME,
+            summon_named_entity_command(START),
+        )];
+        for (position, name) in positions.iter().zip(&position_names) {
+            commands.extend([
+                Command::named(LISTENER_NAME, summon_named_entity_command(name)),
+                Command::new(self.replace_ns(&format!(
+                    "execute as @e[type=area_effect_cloud,tag=-ns-_breakpoint,tag=-ns-+{}] run \
+                    tag @s add {}",
+                    position, suspended_tag
+                ))),
+                Command::new(self.replace_ns(&format!(
+                    "execute as @e[type=area_effect_cloud,tag=-ns-_breakpoint] run tag @s remove {}",
+                    suspended_tag
+                ))),
+            ]);
+        }
+        commands.push(Command::named(
+            LISTENER_NAME,
+            summon_named_entity_command(END),
+        ));
+        inject_commands(&mut self.connection, &self.tracer, commands)?;
+
+        let mut suspended_position = None;
+        let mut current_position = None;
+        let mut events = events_between(events, START, END);
+        while let Some(event) = events.next().await {
+            if let Some(index) = position_names
+                .iter()
+                .position(|name| is_summon_output(&event, name))
+            {
+                current_position = Some(&positions[index]);
+            } else if let Some(_) = event
+                .output
+                .parse::<AddTagOutput>()
+                .ok()
+                .filter(|output| output.tag == suspended_tag)
+            {
+                suspended_position = current_position.cloned();
+            }
+        }
+        Ok(suspended_position)
+    }
+
+    /// Falls back to [OnSuspend::FreezeSession] if the datapack targets a Minecraft version without
+    /// the `tick` command. Returns a warning for the user in that case.
+    async fn check_on_suspend(&mut self) -> Option<String> {
+        if *self.suspend_control.on_suspend() != OnSuspend::FreezeTick {
+            return None;
+        }
+        let pack_format = read_pack_format(&self.datapack).await;
+        if pack_format.is_some_and(|pack_format| pack_format >= TICK_COMMAND_PACK_FORMAT) {
+            return None;
+        }
+        self.suspend_control = SuspendControl::new(OnSuspend::FreezeSession);
+        Some(format!(
+            "onSuspend freezeTick requires Minecraft 1.20.3 or later, but the datapack does not \
+            have a pack_format of at least {}. The world keeps running while suspended.\n",
+            TICK_COMMAND_PACK_FORMAT
+        ))
+    }
+
+    /// Injects the commands of [OnSuspend] after the debugged function was suspended.
+    fn suspend_world(&mut self) -> io::Result<()> {
+        let commands = self.suspend_control.on_stopped();
+        if !commands.is_empty() {
+            let commands = commands.into_iter().map(Command::new).collect();
+            inject_commands(&mut self.connection, &self.tracer, commands)?;
+        }
+        Ok(())
+    }
+
+    /// Undoes the commands of [OnSuspend] if the world is suspended.
+    fn restore_world(&mut self) -> io::Result<()> {
+        let commands = self.suspend_control.on_resumed();
+        if !commands.is_empty() {
+            let commands = commands.into_iter().map(Command::new).collect();
+            inject_commands(&mut self.connection, &self.tracer, commands)?;
+        }
+        Ok(())
+    }
+
+    async fn uninstall_datapack(&mut self) -> io::Result<()> {
+        if self.keep_debug_datapack {
+            return Ok(());
+        }
+
+        match self.uninstall_datapack_in_world().await {
+            Ok(true) => {}
+            Ok(false) => warn!(
+                "Minecraft did not uninstall the debug datapack in time, \
+                 scoreboards and entities of the debugger may remain in the world"
+            ),
+            Err(e) => warn!(
+                "Failed to uninstall the debug datapack, \
+                 scoreboards and entities of the debugger may remain in the world: {}",
+                e
+            ),
+        }
+
+        remove_dir_all(&self.output_path).await
+    }
+
+    /// Removes the debug datapack without uninstalling it in the world, because Minecraft no longer
+    /// responds.
+    async fn remove_datapack(&self) -> io::Result<()> {
+        if self.keep_debug_datapack {
+            return Ok(());
+        }
+        remove_dir_all(&self.output_path).await
+    }
+
+    /// Returns false if Minecraft did not confirm the uninstallation within [UNINSTALL_TIMEOUT].
+    async fn uninstall_datapack_in_world(&mut self) -> io::Result<bool> {
+        let events = self.connection.add_listener();
 
         let uninstalled = format!("{}.uninstalled", LISTENER_NAME);
+        let reset_session = self.replace_ns("function -ns-:reset_session");
         inject_commands(
             &mut self.connection,
+            &self.tracer,
             vec![
-                Command::new("function debug:uninstall"),
+                // Objectives are kept to be reused by the next session
+                Command::new(reset_session),
                 Command::new(summon_named_entity_command(&uninstalled)),
             ],
         )?;
 
         trace!("Waiting for datapack to be uninstalled...");
-        events
+        let mut uninstalled_events = events
             .filter_map(|e| e.output.parse::<SummonNamedEntityOutput>().ok())
-            .filter(|o| o.name == uninstalled)
-            .next()
-            .await;
+            .filter(|o| o.name == uninstalled);
+        if !matches!(
+            timeout(UNINSTALL_TIMEOUT, uninstalled_events.next()).await,
+            Ok(Some(_))
+        ) {
+            return Ok(false);
+        }
         trace!("Datapack is uninstalled");
 
-        remove_dir_all(&self.output_path).await?;
-        Ok(())
+        let debug_datapack_name = self
+            .output_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        inject_commands(
+            &mut self.connection,
+            &self.tracer,
+            vec![Command::new(format!(
+                "datapack disable \"file/{}\"",
+                debug_datapack_name
+            ))],
+        )?;
+        Ok(true)
     }
 }
 
 pub(crate) fn inject_commands(
-    connection: &mut MinecraftConnection,
+    connection: &mut dyn Connection,
+    tracer: &Tracer,
     commands: Vec<Command>,
 ) -> io::Result<()> {
     trace!(
@@ -401,21 +1928,104 @@ pub(crate) fn inject_commands(
             .map(|it| it.get_command())
             .fold(String::new(), |joined, command| joined + "\n" + command)
     );
+    tracer.minecraft_commands(&commands);
     connection.execute_commands(commands)?;
     Ok(())
 }
 
+/// Sends all events of the debugger in Minecraft's log to the adapter until the connection is
+/// dropped.
+/// Removes a debug datapack when dropped before [DatapackGuard::disarm] is called, for example
+/// because the request that generates it was cancelled.
+struct DatapackGuard {
+    output_path: Option<PathBuf>,
+}
+impl DatapackGuard {
+    fn new(output_path: PathBuf) -> DatapackGuard {
+        DatapackGuard {
+            output_path: Some(output_path),
+        }
+    }
+
+    fn disarm(mut self) {
+        self.output_path = None;
+    }
+}
+impl Drop for DatapackGuard {
+    fn drop(&mut self) {
+        if let Some(output_path) = self.output_path.take() {
+            match std::fs::remove_dir_all(&output_path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => warn!(
+                    "Failed to remove debug datapack {}: {}",
+                    output_path.display(),
+                    e
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn batch_marker(batch: u64) -> String {
+    format!("{}{}", BATCH_MARKER_PREFIX, batch)
+}
+
+/// Updates `applied_batch` if the event is the marker of an injected batch, see
+/// [MinecraftSession::inject_commands].
+fn apply_batch_marker(event: &LogEvent, global_objective: &str, applied_batch: &mut u64) {
+    let batch = if let Ok(output) = event.output.parse::<QueryScoreboardOutput>() {
+        Some(output)
+            .filter(|it| it.entity == RELOAD_BATCH && it.scoreboard == global_objective)
+            .and_then(|it| u64::try_from(it.score).ok())
+    } else {
+        event
+            .output
+            .parse::<SummonNamedEntityOutput>()
+            .ok()
+            .and_then(|it| it.name.strip_prefix(BATCH_MARKER_PREFIX)?.parse().ok())
+    };
+    if let Some(batch) = batch {
+        *applied_batch = (*applied_batch).max(batch);
+    }
+}
+
+fn forward_events(
+    connection: &mut dyn Connection,
+    message_sender: UnboundedSender<Either<Message, McfunctionMessage>>,
+) {
+    let mut events = connection.add_named_listener(LISTENER_NAME);
+    tokio::spawn(async move {
+        while let Some(event) = events.next().await {
+            if let Err(_) = message_sender.send(Either::Right(McfunctionMessage::LogEvent(event))) {
+                break;
+            }
+        }
+    });
+}
+
 const MAIN_THREAD_ID: i32 = 0;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum ScopeKind {
     SelectedEntityScores,
+    SelectedEntityTags,
+    SelectedEntityNbt,
+    SelectedEntityPosition,
+    Globals,
 }
 pub const SELECTED_ENTITY_SCORES: &str = "@s scores";
+pub const SELECTED_ENTITY_TAGS: &str = "Tags";
+pub const SELECTED_ENTITY_NBT: &str = "NBT";
+pub const SELECTED_ENTITY_POSITION: &str = "Position";
+pub const GLOBALS: &str = "Globals";
 impl ScopeKind {
     fn get_display_name(&self) -> &'static str {
         match self {
             ScopeKind::SelectedEntityScores => SELECTED_ENTITY_SCORES,
+            ScopeKind::SelectedEntityTags => SELECTED_ENTITY_TAGS,
+            ScopeKind::SelectedEntityNbt => SELECTED_ENTITY_NBT,
+            ScopeKind::SelectedEntityPosition => SELECTED_ENTITY_POSITION,
+            ScopeKind::Globals => GLOBALS,
         }
     }
 }
@@ -423,20 +2033,327 @@ impl ScopeKind {
 struct ScopeReference {
     frame_id: i32,
     kind: ScopeKind,
+    /// The path of a compound or list in the NBT of the selected entity. Empty for the scope itself.
+    nbt_path: Vec<NbtPathSegment>,
+    /// The objective whose scores are shown, if this references a group of the scores scope.
+    objective: Option<String>,
+}
+
+/// A message to the adapter that does not come from the client.
+#[derive(Debug)]
+pub enum McfunctionMessage {
+    LogEvent(LogEvent),
+    /// Sent periodically during a debug session to check the connection to Minecraft.
+    WatchdogTick,
+    /// Sent periodically during a debug session to check whether Minecraft's log file was truncated
+    /// or replaced.
+    LogFileTick,
+    /// Sent while a lost connection to Minecraft is replaced.
+    Reconnect(ReconnectEvent),
+    /// Sent for every line that the generator command writes to stdout or stderr.
+    Output(OutputEventBody),
 }
 
 pub struct McfunctionDebugAdapter {
-    message_sender: UnboundedSender<Either<ProtocolMessage, LogEvent>>,
+    message_sender: UnboundedSender<Either<Message, McfunctionMessage>>,
     client_session: Option<ClientSession>,
+    tracer: Tracer,
+    recorder: Recorder,
+    /// Replaces the connection to Minecraft, for example with a mock connection in tests.
+    connect: Option<Connect>,
 }
 impl McfunctionDebugAdapter {
-    pub fn new(message_sender: UnboundedSender<Either<ProtocolMessage, LogEvent>>) -> Self {
+    pub fn new(message_sender: UnboundedSender<Either<Message, McfunctionMessage>>) -> Self {
+        McfunctionDebugAdapter::with_tracer(message_sender, Tracer::disabled())
+    }
+
+    /// Creates an adapter that records injected commands and events from Minecraft with the
+    /// given [Tracer]. If the [Tracer] is not started yet, it is started or disabled on launch
+    /// depending on the launch attribute `traceFile`.
+    pub fn with_tracer(
+        message_sender: UnboundedSender<Either<Message, McfunctionMessage>>,
+        tracer: Tracer,
+    ) -> Self {
         McfunctionDebugAdapter {
             message_sender,
             client_session: None,
+            tracer,
+            recorder: Recorder::disabled(),
+            connect: None,
+        }
+    }
+
+    /// Records the debug sessions with the given [Recorder]. If the [Recorder] is not started yet,
+    /// it is started or disabled on launch depending on the launch attribute `replayFile`.
+    pub fn with_recorder(self, recorder: Recorder) -> Self {
+        McfunctionDebugAdapter { recorder, ..self }
+    }
+
+    /// Uses `connect` instead of connecting to the Minecraft of the launch configuration. It is also
+    /// used to reconnect.
+    #[cfg(test)]
+    pub(crate) fn with_connection(self, connect: Connect) -> Self {
+        McfunctionDebugAdapter {
+            connect: Some(connect),
+            ..self
         }
     }
 
+    /// Starts tracing and recording and connects to Minecraft. Used by both launch and attach requests.
+    async fn connect(
+        &self,
+        config: &Config<'_>,
+        context: &mut (impl DebugAdapterContext + Send),
+    ) -> Result<ReconnectingConnection, RequestError<io::Error>> {
+        if let Some(trace_file) = config.trace_file {
+            self.tracer.start(trace_file).await.map_err(|e| {
+                PartialErrorResponse::new(format!("Failed to create trace file: {}", e))
+            })?;
+        } else {
+            self.tracer.disable();
+        }
+        if let Some(replay_file) = config.replay_file {
+            self.recorder.start(replay_file).await.map_err(|e| {
+                PartialErrorResponse::new(format!("Failed to create replay file: {}", e))
+            })?;
+        } else {
+            self.recorder.disable();
+        }
+
+        // The log file may not exist yet while Minecraft is starting, so only the world directory
+        // is checked before connecting
+        let world_dir = check_world_dir(&config.minecraft_world_dir);
+        if let Some(message) = world_dir.failure_message() {
+            return Err(PartialErrorResponse::new(message).into());
+        }
+
+        let (connection, reconnect) = match &self.connect {
+            Some(connect) => {
+                let connection = connect().map_err(|e| {
+                    PartialErrorResponse::new(format!("Failed to connect to Minecraft: {}", e))
+                })?;
+                (connection, connect.clone())
+            }
+            None => {
+                let connection = establish_connection(
+                    &config.minecraft_world_dir,
+                    &config.minecraft_log_file,
+                    config.connection_type.clone(),
+                    config.connection_timeout,
+                    context,
+                )
+                .await?;
+                let reconnect = reconnect_with(
+                    &config.minecraft_world_dir,
+                    &config.minecraft_log_file,
+                    config.connection_type.clone(),
+                );
+                (connection, reconnect)
+            }
+        };
+        let connection = Box::new(RecordingMinecraftConnection::new(
+            connection,
+            self.recorder.clone(),
+        ));
+
+        let recorder = self.recorder.clone();
+        let reconnect: Connect = Arc::new(move || {
+            let connection = reconnect()?;
+            Ok(Box::new(RecordingMinecraftConnection::new(
+                connection,
+                recorder.clone(),
+            )) as _)
+        });
+        let message_sender = self.message_sender.clone();
+        let mut connection = ReconnectingConnection::new(
+            connection,
+            reconnect,
+            config.max_reconnect,
+            move |event| {
+                let _ = message_sender.send(Either::Right(McfunctionMessage::Reconnect(event)));
+            },
+        );
+        forward_events(&mut connection, self.message_sender.clone());
+        Ok(connection)
+    }
+
+    async fn create_minecraft_session(
+        &self,
+        config: &Config<'_>,
+        mut connection: ReconnectingConnection,
+    ) -> Result<MinecraftSession, RequestError<io::Error>> {
+        let namespace = "mcfd".to_string(); // Hardcoded in installer as well
+        let debug_datapack_name = format!("debug-{}", config.datapack_name);
+        let output_path = config
+            .minecraft_world_dir
+            .join("datapacks")
+            .join(&debug_datapack_name);
+
+        let mut datapacks = vec![config.datapack.to_path_buf()];
+        datapacks.extend(config.additional_datapacks.iter().cloned());
+        let datapack_namespaces = find_all_function_namespaces(&datapacks)
+            .await
+            .map_err(PartialErrorResponse::from)?;
+
+        let connection_watchdog = config.connection_watchdog.map(|timeout| {
+            let ticker = WatchdogTicker::start(timeout, self.message_sender.clone(), || {
+                Either::Right(McfunctionMessage::WatchdogTick)
+            });
+            (Watchdog::new(timeout), ticker)
+        });
+        let log_file_monitor = match config.connection_type {
+            ConnectionType::LogFile | ConnectionType::Rcon { .. } => {
+                Some(LogFileMonitor::new(&config.minecraft_log_file))
+            }
+            ConnectionType::Socket { .. } => None,
+            #[cfg(unix)]
+            ConnectionType::Pipe { .. } => None,
+        };
+        let log_error_forwarder = if config.forward_log_errors {
+            let namespaces = datapack_namespaces.iter().chain([&namespace]);
+            Some(LogErrorForwarder::new(
+                &config.minecraft_log_file,
+                namespaces,
+            ))
+        } else {
+            None
+        };
+        let log_file_ticker = if log_file_monitor.is_some() || log_error_forwarder.is_some() {
+            Some(WatchdogTicker::start(
+                LOG_FILE_CHECK_PERIOD,
+                self.message_sender.clone(),
+                || Either::Right(McfunctionMessage::LogFileTick),
+            ))
+        } else {
+            None
+        };
+
+        // The listener survives reconnects, see ReconnectingConnection
+        let batch_events = connection.add_named_listener(LISTENER_NAME);
+        let mut minecraft_session = MinecraftSession {
+            connection,
+            reconnect_progress: None,
+            connection_watchdog,
+            log_file_monitor,
+            log_error_forwarder,
+            _log_file_ticker: log_file_ticker,
+            datapack: config.datapack.to_path_buf(),
+            additional_datapacks: config.additional_datapacks.clone(),
+            datapack_namespaces,
+            loaded_sources: BTreeMap::new(),
+            generator: config
+                .generator_command
+                .clone()
+                .map(|command| GeneratorCommand::new(command, self.message_sender.clone())),
+            exclude: config.exclude.clone(),
+            generated: None,
+            generation_lock: Arc::new(tokio::sync::Mutex::new(())),
+            keep_debug_datapack: config.keep_debug_datapack,
+            namespace,
+            output_path,
+            run_to_completion_on_terminate: config.run_to_completion_on_terminate,
+            run_to_completion_timeout: config.run_to_completion_timeout,
+            breakpoint_comments: config.breakpoint_comments,
+            suspend_control: SuspendControl::new(config.on_suspend.clone()),
+            suspend_schedules: config.suspend_schedules,
+            scopes: Vec::new(),
+            entity_nbt: HashMap::new(),
+            globals: None,
+            objectives_checked: false,
+            dynamic_objectives: BTreeSet::new(),
+            unloaded_log_scores: None,
+            stopped_data: None,
+            last_position: None,
+            entry_position: None,
+            session_ticks: None,
+            timing: None,
+            injected_batch: 0,
+            applied_batch: 0,
+            batch_events: Mutex::new(batch_events),
+            attached: false,
+            tracer: self.tracer.clone(),
+        };
+        minecraft_session.loaded_sources = minecraft_session
+            .find_loaded_sources()
+            .await
+            .map_err(PartialErrorResponse::from)?;
+        Ok(minecraft_session)
+    }
+
+    /// Connects to a function that is suspended by a debug datapack that is already installed in
+    /// Minecraft, for example after the debug adapter crashed. The debug datapack is not generated
+    /// again, so the function stays suspended at its position.
+    async fn attach(
+        &mut self,
+        args: LaunchRequestArguments,
+        mut context: impl DebugAdapterContext + Send,
+    ) -> Result<(), RequestError<io::Error>> {
+        Self::unwrap_client_session(&mut self.client_session)?;
+
+        let config = get_config(&args)?;
+
+        let connection = self.connect(&config, &mut context).await?;
+
+        let mut minecraft_session = self.create_minecraft_session(&config, connection).await?;
+        minecraft_session.attached = true;
+        if !minecraft_session.output_path.is_dir() {
+            return Err(PartialErrorResponse::new(format!(
+                "Cannot attach, because there is no debug datapack at {}",
+                minecraft_session.output_path.display()
+            ))
+            .into());
+        }
+
+        let mut stack_trace = minecraft_session
+            .get_stack_trace()
+            .await
+            .map_err(Self::map_custom_error)?;
+        let position = minecraft_session
+            .find_suspended_position(&stack_trace)
+            .await
+            .map_err(Self::map_custom_error)?
+            .ok_or_else(|| {
+                PartialErrorResponse::new(
+                    "Cannot attach, because no function is suspended by the debugger".to_string(),
+                )
+            })?;
+        remove_suspended_function_call(&mut stack_trace, &position);
+        minecraft_session.last_position = Some(position.clone());
+        minecraft_session.stopped_data = Some(StoppedData {
+            position,
+            stack_trace,
+            pending_executors: None,
+        });
+        self.tracer.set_stopped(true);
+        if let Some(warning) = minecraft_session.check_on_suspend().await {
+            let event = OutputEventBody::builder()
+                .category(OutputCategory::Important)
+                .output(warning)
+                .build();
+            context.fire_event(event);
+        }
+        minecraft_session
+            .suspend_world()
+            .map_err(Self::map_custom_error)?;
+
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        let events = client_session.get_breakpoint_events(&minecraft_session, |function| {
+            minecraft_session.get_unverified_reason(function).is_some()
+        });
+        for event in events {
+            context.fire_event(event);
+        }
+        client_session.minecraft_session = Some(minecraft_session);
+
+        let event = StoppedEventBody::builder()
+            .reason(StoppedEventReason::Entry)
+            .description(Some("Attached to suspended function".to_string()))
+            .thread_id(Some(MAIN_THREAD_ID))
+            .build();
+        context.fire_event(event);
+        Ok(())
+    }
+
     async fn on_stopped(
         &mut self,
         event: StoppedEvent,
@@ -444,13 +2361,32 @@ impl McfunctionDebugAdapter {
     ) -> io::Result<()> {
         if let Some(client_session) = &mut self.client_session {
             if let Some(minecraft_session) = &mut client_session.minecraft_session {
+                let mut stack_trace = minecraft_session.get_stack_trace().await?;
+                remove_suspended_function_call(&mut stack_trace, &event.position);
+                let session_ticks = minecraft_session.get_session_ticks().await?;
+                let timing = Timing::new(minecraft_session.session_ticks, session_ticks);
+                minecraft_session.session_ticks = Some(session_ticks);
+                minecraft_session.timing = Some(timing);
+                let reason =
+                    if minecraft_session.entry_position.take().as_ref() == Some(&event.position) {
+                        StoppedEventReason::Entry
+                    } else {
+                        to_stopped_event_reason(event.reason)
+                    };
+                minecraft_session.last_position = Some(event.position.clone());
                 minecraft_session.stopped_data = Some(StoppedData {
                     position: event.position,
-                    stack_trace: minecraft_session.get_stack_trace().await?,
+                    stack_trace,
+                    pending_executors: None,
                 });
+                // Whether they were hit or not, temporary breakpoints only apply until the next stop
+                remove_temporary_breakpoints(&mut client_session.temporary_breakpoints);
+                self.tracer.set_stopped(true);
+                minecraft_session.suspend_world()?;
 
                 let event = StoppedEventBody::builder()
-                    .reason(to_stopped_event_reason(event.reason))
+                    .description(Some(timing.describe(&reason)))
+                    .reason(reason)
                     .thread_id(Some(MAIN_THREAD_ID))
                     .build();
                 context.fire_event(event);
@@ -460,12 +2396,57 @@ impl McfunctionDebugAdapter {
         Ok(())
     }
 
-    async fn on_exited(
+    async fn on_truncated(
         &mut self,
         context: &mut (impl DebugAdapterContext + Send),
     ) -> io::Result<()> {
         if let Some(client_session) = &mut self.client_session {
             if let Some(minecraft_session) = &mut client_session.minecraft_session {
+                let largest_partition = minecraft_session
+                    .generated
+                    .as_ref()
+                    .map_or(0, |generated| generated.largest_partition);
+                context.fire_event(
+                    OutputEventBody::builder()
+                        .category(OutputCategory::Important)
+                        .output(format!(
+                            "Execution appears truncated; consider raising the gamerule \
+                            maxCommandChainLength, currently need at least {} commands\n",
+                            largest_partition
+                        ))
+                        .build(),
+                );
+
+                if let Some(position) = minecraft_session.last_position.clone() {
+                    let stack_trace = minecraft_session.get_stack_trace().await?;
+                    minecraft_session.stopped_data = Some(StoppedData {
+                        position,
+                        stack_trace,
+                        pending_executors: None,
+                    });
+                    self.tracer.set_stopped(true);
+                    minecraft_session.suspend_world()?;
+
+                    let event = StoppedEventBody::builder()
+                        .reason(StoppedEventReason::Exception)
+                        .thread_id(Some(MAIN_THREAD_ID))
+                        .build();
+                    context.fire_event(event);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn on_exited(
+        &mut self,
+        context: &mut (impl DebugAdapterContext + Send),
+    ) -> io::Result<()> {
+        if let Some(client_session) = &mut self.client_session {
+            if let Some(mut minecraft_session) = client_session.minecraft_session.take() {
+                remove_temporary_breakpoints(&mut client_session.temporary_breakpoints);
+                self.tracer.set_stopped(false);
                 minecraft_session.uninstall_datapack().await?;
 
                 context.fire_event(TerminatedEventBody::builder().build());
@@ -475,6 +2456,302 @@ impl McfunctionDebugAdapter {
         Ok(())
     }
 
+    async fn on_watchdog_tick(
+        &mut self,
+        context: &mut (impl DebugAdapterContext + Send),
+    ) -> io::Result<()> {
+        let client_session = match &mut self.client_session {
+            Some(client_session) => client_session,
+            None => return Ok(()),
+        };
+        let minecraft_session = match &mut client_session.minecraft_session {
+            Some(minecraft_session) => minecraft_session,
+            None => return Ok(()),
+        };
+        let (watchdog, _ticker) = match &mut minecraft_session.connection_watchdog {
+            Some(connection_watchdog) => connection_watchdog,
+            None => return Ok(()),
+        };
+        if minecraft_session.connection.is_reconnecting() {
+            // Probes can't be answered until the connection is replaced
+            watchdog.on_response();
+            return Ok(());
+        }
+        match watchdog.on_tick(Instant::now()) {
+            WatchdogAction::SendProbe => {
+                let probe =
+                    named_logged_command(LISTENER_NAME, format!("tag @s add {}", WATCHDOG_TAG));
+                inject_commands(
+                    &mut minecraft_session.connection,
+                    &minecraft_session.tracer,
+                    vec![Command::new(probe)],
+                )?;
+            }
+            WatchdogAction::Wait => {}
+            WatchdogAction::ConnectionLost => {
+                let message = "Minecraft did not respond, the debug session was terminated. \
+                    This can happen if the server was restarted or the world was reloaded."
+                    .to_string();
+                self.terminate_after_connection_loss(message, context)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Terminates the debug session, because the connection to Minecraft was lost.
+    async fn terminate_after_connection_loss(
+        &mut self,
+        message: String,
+        context: &mut (impl DebugAdapterContext + Send),
+    ) -> io::Result<()> {
+        let client_session = match &mut self.client_session {
+            Some(client_session) => client_session,
+            None => return Ok(()),
+        };
+        if let Some(mut minecraft_session) = client_session.minecraft_session.take() {
+            remove_temporary_breakpoints(&mut client_session.temporary_breakpoints);
+            self.tracer.set_stopped(false);
+            // Minecraft may still run the commands, for example after a restart
+            if let Err(e) = minecraft_session.restore_world() {
+                warn!(
+                    "Failed to restore the world after the connection was lost: {}",
+                    e
+                );
+            }
+            minecraft_session.remove_datapack().await?;
+
+            let event = OutputEventBody::builder()
+                .category(OutputCategory::Important)
+                .output(message)
+                .build();
+            context.fire_event(event);
+            context.fire_event(TerminatedEventBody::builder().build());
+        }
+        Ok(())
+    }
+
+    /// Reports the progress of replacing a lost connection to Minecraft. If no new connection is
+    /// established in time, the debug session is terminated.
+    async fn on_reconnect_event(
+        &mut self,
+        event: ReconnectEvent,
+        context: &mut (impl DebugAdapterContext + Send),
+    ) -> io::Result<()> {
+        let minecraft_session = match &mut self.client_session {
+            Some(ClientSession {
+                minecraft_session: Some(minecraft_session),
+                ..
+            }) => minecraft_session,
+            _ => return Ok(()),
+        };
+        match event {
+            ReconnectEvent::Reconnecting {
+                attempt,
+                delay,
+                error,
+            } => {
+                let message = format!(
+                    "Lost connection to Minecraft, attempt {} in {}s: {}",
+                    attempt,
+                    delay.as_secs(),
+                    error
+                );
+                match &minecraft_session.reconnect_progress {
+                    Some(progress) => update_progress(context, &progress.progress_id, message),
+                    None => {
+                        let title = "Reconnecting to Minecraft".to_string();
+                        let progress = context.start_cancellable_progress(title, Some(message));
+                        minecraft_session.reconnect_progress = Some(progress);
+                    }
+                }
+                let data = json!({
+                    "attempt": attempt,
+                    "delayMs": delay.as_millis() as u64,
+                    "error": error,
+                });
+                context.fire_event(telemetry_event("reconnecting", data));
+                Ok(())
+            }
+            ReconnectEvent::Reconnected => {
+                if let Some(progress) = minecraft_session.reconnect_progress.take() {
+                    let message = "Successfully reconnected to Minecraft".to_string();
+                    context.end_cancellable_progress(progress.progress_id.clone(), Some(message));
+                }
+                context.fire_event(telemetry_event("reconnected", json!({})));
+                Ok(())
+            }
+            ReconnectEvent::Failed { elapsed, error } => {
+                if let Some(progress) = minecraft_session.reconnect_progress.take() {
+                    let message = "Failed to reconnect to Minecraft".to_string();
+                    context.end_cancellable_progress(progress.progress_id.clone(), Some(message));
+                }
+                let data = json!({
+                    "elapsedMs": elapsed.as_millis() as u64,
+                    "error": error,
+                });
+                context.fire_event(telemetry_event("reconnectFailed", data));
+
+                let message = format!(
+                    "Could not reconnect to Minecraft within {}s, the debug session was \
+                    terminated: {}",
+                    elapsed.as_secs(),
+                    error
+                );
+                self.terminate_after_connection_loss(message, context).await
+            }
+        }
+    }
+
+    fn on_log_file_tick(&mut self, context: &mut (impl DebugAdapterContext + Send)) {
+        let minecraft_session = match &mut self.client_session {
+            Some(ClientSession {
+                minecraft_session: Some(minecraft_session),
+                ..
+            }) => minecraft_session,
+            _ => return,
+        };
+        Self::check_log_file(minecraft_session, context);
+        Self::forward_log_errors(minecraft_session, context);
+    }
+
+    /// If Minecraft's log file was truncated or replaced, a new connection is created that reads the
+    /// new log file from its end. Events that Minecraft logged before the change was detected are
+    /// lost, but the connection watchdog terminates the session if one of them was important.
+    fn check_log_file(
+        minecraft_session: &mut MinecraftSession,
+        context: &mut (impl DebugAdapterContext + Send),
+    ) {
+        let monitor = match &mut minecraft_session.log_file_monitor {
+            Some(log_file_monitor) => log_file_monitor,
+            None => return,
+        };
+        let description = match monitor.check() {
+            LogFileChange::Unchanged => return,
+            LogFileChange::Truncated => "truncated",
+            LogFileChange::Replaced => "replaced",
+        };
+        warn!("Minecraft's log file was {}, reconnecting", description);
+        minecraft_session.connection.reconnect();
+
+        let event = OutputEventBody::builder()
+            .category(OutputCategory::Console)
+            .output(format!(
+                "Minecraft's log file was {}, continuing with the new log file.\n",
+                description
+            ))
+            .build();
+        context.fire_event(event);
+    }
+
+    /// Shows errors that Minecraft logged for the debugged datapack in the debug console. Minecraft
+    /// does not log which function failed, so the errors are prefixed with the current position if
+    /// execution is suspended, or else with the last known position.
+    fn forward_log_errors(
+        minecraft_session: &mut MinecraftSession,
+        context: &mut (impl DebugAdapterContext + Send),
+    ) {
+        let forwarder = match &mut minecraft_session.log_error_forwarder {
+            Some(forwarder) => forwarder,
+            None => return,
+        };
+        let errors = match forwarder.read_errors(Instant::now()) {
+            Ok(errors) => errors,
+            Err(e) => {
+                warn!("Failed to read errors from Minecraft's log file: {}", e);
+                return;
+            }
+        };
+        let prefix = match (
+            &minecraft_session.stopped_data,
+            &minecraft_session.last_position,
+        ) {
+            (Some(stopped_data), _) => format!(
+                "[{}:{}] ",
+                stopped_data.position.function, stopped_data.position.line_number
+            ),
+            (None, Some(position)) => {
+                format!("[after {}:{}] ", position.function, position.line_number)
+            }
+            (None, None) => String::new(),
+        };
+        for error in errors {
+            let event = OutputEventBody::builder()
+                .category(OutputCategory::Stderr)
+                .output(format!("{}{}\n", prefix, error))
+                .build();
+            context.fire_event(event);
+        }
+    }
+
+    fn on_watchdog_response(&mut self) {
+        if let Some(ClientSession {
+            minecraft_session:
+                Some(MinecraftSession {
+                    connection_watchdog: Some((watchdog, _ticker)),
+                    ..
+                }),
+            ..
+        }) = &mut self.client_session
+        {
+            watchdog.on_response();
+        }
+    }
+
+    /// Responds to a request for a file that does not exist and was not renamed or moved within the
+    /// datapack. All breakpoints of the function are removed from the debug datapack.
+    async fn set_breakpoints_in_missing_file(
+        client_session: &mut ClientSession,
+        args: &SetBreakpointsRequestArguments,
+        function: &ResourceLocation,
+        path: &str,
+        context: &mut (impl DebugAdapterContext + Send),
+    ) -> Result<SetBreakpointsResponseBody, RequestError<io::Error>> {
+        for event in get_removed_comment_events(client_session.take_comment_breakpoints(function)) {
+            context.fire_event(event);
+        }
+        let removed = client_session.remove_breakpoints(function);
+        if let Some(minecraft_session) = client_session.minecraft_session.as_mut() {
+            let events = minecraft_session
+                .update_loaded_sources()
+                .await
+                .map_err(PartialErrorResponse::from)?;
+            for event in events {
+                context.fire_event(event);
+            }
+            if context.is_superseded() {
+                // The queued request generates the datapack with the latest breakpoints
+                minecraft_session.generated = None;
+            } else if !removed.is_empty() || minecraft_session.generated.is_none() {
+                // Without a generated datapack, a superseded request may have changed breakpoints
+                generate_datapack(
+                    minecraft_session,
+                    &client_session.parser,
+                    &client_session.breakpoints,
+                    &client_session.temporary_breakpoints,
+                )
+                .await?;
+                minecraft_session.inject_commands(vec![Command::new("reload")])?;
+            }
+        }
+
+        let message = format!("Function file {} does not exist", path);
+        let breakpoints = args
+            .breakpoints
+            .iter()
+            .map(|source_breakpoint| {
+                Breakpoint::builder()
+                    .verified(false)
+                    .message(Some(message.clone()))
+                    .line(Some(source_breakpoint.line))
+                    .build()
+            })
+            .collect();
+        Ok(SetBreakpointsResponseBody::builder()
+            .breakpoints(breakpoints)
+            .build())
+    }
+
     fn unwrap_client_session(
         client_session: &mut Option<ClientSession>,
     ) -> Result<&mut ClientSession, PartialErrorResponse> {
@@ -502,8 +2779,16 @@ impl McfunctionDebugAdapter {
         let client_session = Self::unwrap_client_session(&mut self.client_session)?;
         let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
 
+        // Resuming before Minecraft reloaded changed breakpoints would use the old breakpoints
+        mc_session.await_all_applied().await;
+
         if let Some(stopped_data) = mc_session.stopped_data.as_ref() {
-            let mut dirty = false;
+            // Temporary breakpoints were removed when stopping, but may still be generated. If it is
+            // unknown what was generated, for example after attaching, it must be generated again
+            let mut dirty = mc_session
+                .generated
+                .as_ref()
+                .map_or(true, GeneratedData::contains_temporary_breakpoints);
 
             if !client_session.temporary_breakpoints.is_empty() {
                 client_session.temporary_breakpoints.clear();
@@ -538,26 +2823,206 @@ impl McfunctionDebugAdapter {
             if dirty {
                 generate_datapack(
                     mc_session,
+                    &client_session.parser,
                     &client_session.breakpoints,
                     &client_session.temporary_breakpoints,
                 )
                 .await?;
-                commands.push(Command::new("reload"));
+                commands.push(Command::new("reload"));
+            } else if mc_session.unloaded_log_scores.take().is_some() {
+                commands.push(Command::new("reload"));
+            }
+
+            // Other datapacks must be enabled before the debugged function calls them again
+            commands.extend(
+                mc_session
+                    .suspend_control
+                    .on_resumed()
+                    .into_iter()
+                    .map(Command::new),
+            );
+            commands.push(Command::new("function debug:resume"));
+            mc_session.inject_commands(commands)?;
+            mc_session.stopped_data = None;
+            mc_session.scopes.clear();
+            mc_session.entity_nbt.clear();
+            mc_session.globals = None;
+            mc_session.objectives_checked = false;
+            self.tracer.set_stopped(false);
+        }
+
+        Ok(())
+    }
+
+    /// Resumes all executors or with `single_thread` only the suspended executor. Minecraft runs
+    /// the executors of a function one after another, so the next executor is suspended before it
+    /// runs, see [MinecraftSession::create_next_executor_breakpoint].
+    async fn continue_thread(
+        &mut self,
+        thread_id: i32,
+        single_thread: bool,
+    ) -> Result<ContinueResponseBody, RequestError<io::Error>> {
+        let mut temporary_breakpoints = Vec::new();
+        if single_thread {
+            let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+            let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
+            if thread_id != MAIN_THREAD_ID {
+                return Err(PartialErrorResponse::new(
+                    "Only the suspended executor can be continued, because Minecraft runs the \
+                    executors of a function one after another"
+                        .to_string(),
+                )
+                .into());
+            }
+            temporary_breakpoints = mc_session
+                .create_next_executor_breakpoint(&client_session.parser)
+                .await?;
+        }
+        // Without other executors, resuming the suspended executor resumes the whole program
+        let all_threads_continued = temporary_breakpoints.is_empty();
+        self.continue_internal(temporary_breakpoints).await?;
+
+        Ok(ContinueResponseBody::builder()
+            .all_threads_continued(all_threads_continued)
+            .build())
+    }
+
+    fn timing(&mut self) -> Result<Timing, RequestError<io::Error>> {
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
+        mc_session
+            .timing
+            .ok_or_else(|| PartialErrorResponse::new("Not stopped yet".to_string()).into())
+    }
+
+    async fn partitions(
+        &mut self,
+        args: PartitionsArguments,
+    ) -> Result<Vec<PartitionRange>, RequestError<io::Error>> {
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        let offset = client_session.get_line_offset();
+        let (_datapack, function) = parse_function_path(args.path.as_ref())
+            .map_err(|e| PartialErrorResponse::new(format!("Argument path {}", e)))?;
+
+        // Breakpoints end partitions, so the cache is only valid for the same breakpoints
+        let breakpoints = merge_breakpoints(
+            &client_session.breakpoints,
+            &client_session.temporary_breakpoints,
+        );
+        let function_breakpoints = breakpoints.get_vec(&function).cloned().unwrap_or_default();
+        let up_to_date = match client_session.partitions.get(&function) {
+            Some(cached) => {
+                cached.breakpoints == function_breakpoints
+                    && !is_modified_since(&args.path, cached.computed_at).await
+            }
+            None => false,
+        };
+        if !up_to_date {
+            let computed_at = SystemTime::now();
+            let content = read_to_string(&args.path).await.map_err(|e| {
+                PartialErrorResponse::new(format!("Failed to read file {}: {}", args.path, e))
+            })?;
+            let exclude = client_session
+                .minecraft_session
+                .as_ref()
+                .map(|minecraft_session| minecraft_session.exclude.as_slice())
+                .unwrap_or_default();
+            let partitions = find_partitions(
+                &function,
+                &content,
+                &client_session.parser,
+                exclude,
+                &breakpoints,
+            );
+            let cached = CachedPartitions {
+                computed_at,
+                breakpoints: function_breakpoints,
+                partitions,
             };
+            client_session.partitions.insert(function.clone(), cached);
+        }
 
-            commands.push(Command::new("function debug:resume"));
-            mc_session.inject_commands(commands)?;
-            mc_session.stopped_data = None;
-            mc_session.scopes.clear();
+        Ok(client_session.partitions[&function]
+            .partitions
+            .iter()
+            .map(|partition| PartitionRange {
+                start_line: partition.start_line - offset,
+                end_line: partition.end_line - offset,
+                ..partition.clone()
+            })
+            .collect())
+    }
+
+    async fn run_to_location(
+        &mut self,
+        args: RunToLocationArguments,
+    ) -> Result<(), RequestError<io::Error>> {
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        let offset = client_session.get_line_offset();
+        let column_offset = client_session.get_column_offset();
+        let (_datapack, function) = parse_function_path(args.path.as_ref())
+            .map_err(|e| PartialErrorResponse::new(format!("Argument path {}", e)))?;
+        let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
+
+        if let Some(reason) = mc_session.get_unverified_reason(&function) {
+            return Err(PartialErrorResponse::new(reason).into());
+        }
+        let line_number = args.line as usize + offset;
+        let (valid, after_execute_column) =
+            verify_breakpoint(&client_session.parser, &args.path, line_number, false)
+                .await
+                .map_err(|e| {
+                    PartialErrorResponse::new(format!(
+                        "Failed to verify location {}:{}: {}",
+                        function, line_number, e
+                    ))
+                })?;
+        if !valid {
+            return Err(PartialErrorResponse::new(format!(
+                "Cannot run to line {} of function {}",
+                args.line, function
+            ))
+            .into());
         }
+        let column = args.column.map(|column| column as usize + column_offset);
+        let position_in_line = if after_execute_column
+            .filter(|after_execute_column| column >= Some(*after_execute_column))
+            .is_some()
+        {
+            BreakpointPositionInLine::AfterExecute
+        } else {
+            BreakpointPositionInLine::Breakpoint
+        };
+        let breakpoint = LocalBreakpoint {
+            kind: BreakpointKind::Temporary,
+            position: LocalBreakpointPosition {
+                line_number,
+                position_in_line,
+            },
+        };
 
+        if mc_session.stopped_data.is_some() {
+            self.continue_internal(vec![(function, breakpoint)]).await?;
+        } else {
+            client_session
+                .temporary_breakpoints
+                .insert(function, breakpoint);
+            generate_datapack(
+                mc_session,
+                &client_session.parser,
+                &client_session.breakpoints,
+                &client_session.temporary_breakpoints,
+            )
+            .await?;
+            mc_session.inject_commands(vec![Command::new("reload")])?;
+        }
         Ok(())
     }
 }
 
 #[async_trait]
 impl DebugAdapter for McfunctionDebugAdapter {
-    type Message = LogEvent;
+    type Message = McfunctionMessage;
     type CustomError = io::Error;
 
     async fn handle_other_message(
@@ -565,11 +3030,24 @@ impl DebugAdapter for McfunctionDebugAdapter {
         msg: Self::Message,
         mut context: impl DebugAdapterContext + Send,
     ) -> Result<(), Self::CustomError> {
+        let msg = match msg {
+            McfunctionMessage::LogEvent(event) => event,
+            McfunctionMessage::WatchdogTick => return self.on_watchdog_tick(&mut context).await,
+            McfunctionMessage::LogFileTick => return Ok(self.on_log_file_tick(&mut context)),
+            McfunctionMessage::Reconnect(event) => {
+                return self.on_reconnect_event(event, &mut context).await
+            }
+            McfunctionMessage::Output(event) => {
+                context.fire_event(event);
+                return Ok(());
+            }
+        };
         trace!(
             "Received message from Minecraft by {}: {}",
             msg.executor,
             msg.output
         );
+        self.tracer.minecraft_event(&msg);
         if let Ok(output) = msg.output.parse::<AddTagOutput>() {
             if output.entity == LISTENER_NAME {
                 if let Ok(event) = output.tag.parse() {
@@ -578,19 +3056,154 @@ impl DebugAdapter for McfunctionDebugAdapter {
                 if output.tag == "exited" {
                     self.on_exited(&mut context).await?;
                 }
+                if output.tag == WATCHDOG_TAG {
+                    self.on_watchdog_response();
+                }
+                if output.tag == TRUNCATED_TAG {
+                    self.on_truncated(&mut context).await?;
+                }
             }
         }
         Ok(())
     }
 
+    async fn handle_custom_request(
+        &mut self,
+        request: CustomRequest,
+        context: impl DebugAdapterContext + Send,
+    ) -> Result<Value, RequestError<Self::CustomError>> {
+        let invalid_arguments = |e: serde_json::Error| {
+            PartialErrorResponse::new(format!(
+                "Invalid arguments for request '{}': {}",
+                request.command, e
+            ))
+        };
+        match request.command.as_str() {
+            RUN_TO_LOCATION_COMMAND => {
+                let args =
+                    serde_json::from_value(request.arguments.clone()).map_err(invalid_arguments)?;
+                self.run_to_location(args).await?;
+                Ok(Value::Null)
+            }
+            PARTITIONS_COMMAND => {
+                let args =
+                    serde_json::from_value(request.arguments.clone()).map_err(invalid_arguments)?;
+                let partitions = self.partitions(args).await?;
+                Ok(serde_json::to_value(PartitionsResponseBody { partitions }).unwrap())
+            }
+            TIMING_COMMAND => {
+                let timing = self.timing()?;
+                Ok(serde_json::to_value(timing).unwrap())
+            }
+            ATTACH_COMMAND => {
+                let args =
+                    serde_json::from_value(request.arguments.clone()).map_err(invalid_arguments)?;
+                self.attach(args, context).await?;
+                Ok(Value::Null)
+            }
+            CONTINUE_COMMAND => {
+                let args = serde_json::from_value::<ContinueArguments>(request.arguments.clone())
+                    .map_err(invalid_arguments)?;
+                let body = self
+                    .continue_thread(args.thread_id, args.single_thread)
+                    .await?;
+                Ok(serde_json::to_value(body).unwrap())
+            }
+            _ => Err(PartialErrorResponse::new(format!(
+                "Unsupported request '{}'",
+                request.command
+            ))
+            .into()),
+        }
+    }
+
+    async fn breakpoint_locations(
+        &mut self,
+        args: BreakpointLocationsRequestArguments,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<BreakpointLocationsResponseBody, RequestError<Self::CustomError>> {
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        let line_offset = client_session.get_line_offset();
+        let column_offset = client_session.get_column_offset();
+        let path = client_session.get_source_path(&args.source)?;
+
+        let start_line_number = args.line as usize + line_offset;
+        let end_line_number = args.end_line.map_or(start_line_number, |end_line| {
+            end_line as usize + line_offset
+        });
+        let start_column = args.column.map(|column| column as usize + column_offset);
+        let end_column = args
+            .end_column
+            .map(|column| column as usize + column_offset);
+
+        let comments = client_session
+            .minecraft_session
+            .as_ref()
+            .is_some_and(|minecraft_session| minecraft_session.breakpoint_comments);
+        let mut breakpoints = Vec::new();
+        for line_number in start_line_number..=end_line_number {
+            let (valid, after_execute_column) =
+                verify_breakpoint(&client_session.parser, path, line_number, comments)
+                    .await
+                    .map_err(|e| {
+                        PartialErrorResponse::new(format!("Failed to read file {}: {}", path, e))
+                    })?;
+            if !valid {
+                continue;
+            }
+            let columns = once(1)
+                .chain(after_execute_column)
+                .filter(|column| line_number != start_line_number || start_column <= Some(*column))
+                .filter(|column| {
+                    line_number != end_line_number
+                        || end_column
+                            .filter(|end_column| column > end_column)
+                            .is_none()
+                });
+            for column in columns {
+                breakpoints.push(
+                    BreakpointLocation::builder()
+                        .line((line_number - line_offset) as i32)
+                        .column(Some((column - column_offset) as i32))
+                        .build(),
+                );
+            }
+        }
+
+        Ok(BreakpointLocationsResponseBody::builder()
+            .breakpoints(breakpoints)
+            .build())
+    }
+
     async fn continue_(
         &mut self,
-        _args: ContinueRequestArguments,
+        args: ContinueRequestArguments,
         _context: impl DebugAdapterContext + Send,
     ) -> Result<ContinueResponseBody, RequestError<Self::CustomError>> {
-        self.continue_internal(Vec::new()).await?;
+        self.continue_thread(args.thread_id, false).await
+    }
 
-        Ok(ContinueResponseBody::builder().build())
+    async fn disconnect(
+        &mut self,
+        args: DisconnectRequestArguments,
+        mut context: impl DebugAdapterContext + Send,
+    ) -> Result<(), RequestError<Self::CustomError>> {
+        let mut result = Ok(());
+        if let Some(client_session) = &mut self.client_session {
+            if let Some(mut minecraft_session) = client_session.minecraft_session.take() {
+                // The world is restored even if the function stays suspended
+                result = minecraft_session.restore_world();
+                // Like other debug adapters, only terminate an attached program if requested
+                let terminate = args
+                    .terminate_debuggee
+                    .unwrap_or(!minecraft_session.attached);
+                if terminate {
+                    result = result.and(minecraft_session.uninstall_datapack().await);
+                }
+            }
+        }
+        context.shutdown();
+        result.map_err(|e| RequestError::Respond(e.into()))
     }
 
     async fn evaluate(
@@ -608,6 +3221,120 @@ impl DebugAdapter for McfunctionDebugAdapter {
         )))
     }
 
+    async fn goto(
+        &mut self,
+        args: GotoRequestArguments,
+        mut context: impl DebugAdapterContext + Send,
+    ) -> Result<(), RequestError<Self::CustomError>> {
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
+
+        let stopped_data = mc_session
+            .stopped_data
+            .as_ref()
+            .ok_or_else(|| PartialErrorResponse::new("Not stopped".to_string()))?;
+        if stopped_data.position.position_in_line == BreakpointPositionInLine::AfterExecute {
+            return Err(PartialErrorResponse::new(
+                "Cannot jump while stopped after the execute prefix of a function call".to_string(),
+            )
+            .into());
+        }
+        let function = stopped_data.position.function.clone();
+        let line_number = args.target_id as usize;
+        let path = mc_session.get_function_path(&function);
+        if find_goto_target_line_number(path, line_number, &client_session.parser).await?
+            != Some(line_number)
+        {
+            return Err(PartialErrorResponse::new(format!(
+                "Unknown goto target: {}",
+                args.target_id
+            ))
+            .into());
+        }
+
+        let old_position = stopped_data.position.clone();
+        let new_position = BreakpointPosition {
+            function,
+            line_number,
+            position_in_line: BreakpointPositionInLine::Breakpoint,
+        };
+        // Resuming from the new position is handled by resume_self via the tag of the breakpoint
+        // entity, continue_internal ensures that the partition exists.
+        mc_session.inject_commands(vec![
+            Command::new(mc_session.replace_ns(&format!(
+                "tag @e[type=area_effect_cloud,tag=-ns-_breakpoint] remove -ns-+{}",
+                old_position
+            ))),
+            Command::new(mc_session.replace_ns(&format!(
+                "tag @e[type=area_effect_cloud,tag=-ns-_breakpoint] add -ns-+{}",
+                new_position
+            ))),
+        ])?;
+
+        if let Some(stopped_data) = mc_session.stopped_data.as_mut() {
+            if let Some(frame) = stopped_data.stack_trace.first_mut() {
+                frame.location.line_number = line_number;
+                frame.location.column_number = 1;
+            }
+            stopped_data.position = new_position;
+        }
+
+        let event = StoppedEventBody::builder()
+            .reason(StoppedEventReason::Goto)
+            .thread_id(Some(MAIN_THREAD_ID))
+            .build();
+        context.fire_event(event);
+        Ok(())
+    }
+
+    async fn goto_targets(
+        &mut self,
+        args: GotoTargetsRequestArguments,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<GotoTargetsResponseBody, RequestError<Self::CustomError>> {
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        let offset = client_session.get_line_offset();
+        let path = client_session.get_source_path(&args.source)?;
+        let (_datapack, function) = parse_function_path(path.as_ref())
+            .map_err(|e| PartialErrorResponse::new(format!("Argument source.path {}", e)))?;
+        let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
+
+        let stopped_data = mc_session
+            .stopped_data
+            .as_ref()
+            .ok_or_else(|| PartialErrorResponse::new("Not stopped".to_string()))?;
+        if stopped_data.position.function != function {
+            return Err(PartialErrorResponse::new(format!(
+                "Cannot jump from function {} to function {}",
+                stopped_data.position.function, function
+            ))
+            .into());
+        }
+        if stopped_data.position.position_in_line == BreakpointPositionInLine::AfterExecute {
+            return Err(PartialErrorResponse::new(
+                "Cannot jump while stopped after the execute prefix of a function call".to_string(),
+            )
+            .into());
+        }
+
+        let path = mc_session.get_function_path(&function);
+        let line_number = args.line as usize + offset;
+        let target_line_number =
+            find_goto_target_line_number(path, line_number, &client_session.parser).await?;
+
+        let targets = target_line_number
+            .into_iter()
+            .map(|line_number| {
+                GotoTarget::builder()
+                    .id(line_number as i32)
+                    .label(format!("Line {}", line_number - offset))
+                    .line((line_number - offset) as i32)
+                    .build()
+            })
+            .collect();
+        Ok(GotoTargetsResponseBody::builder().targets(targets).build())
+    }
+
     async fn initialize(
         &mut self,
         args: InitializeRequestArguments,
@@ -622,14 +3349,25 @@ impl DebugAdapter for McfunctionDebugAdapter {
             path_format: args.path_format,
             minecraft_session: None,
             breakpoints: MultiMap::new(),
+            breakpoint_ids: MultiMap::new(),
+            next_breakpoint_id: 0,
+            comment_breakpoints: MultiMap::new(),
             temporary_breakpoints: MultiMap::new(),
-            parser,
+            verified_lines: HashMap::new(),
+            function_hashes: HashMap::new(),
+            partitions: HashMap::new(),
+            parser: Arc::new(parser),
         });
 
         context.fire_event(Event::Initialized);
 
         Ok(Capabilities::builder()
+            .supports_breakpoint_locations_request(true)
             .supports_cancel_request(true)
+            .supports_goto_targets_request(true)
+            .supports_loaded_sources_request(true)
+            .supports_step_in_targets_request(true)
+            .supports_stepping_granularity(true)
             .supports_terminate_request(true)
             .build())
     }
@@ -637,71 +3375,217 @@ impl DebugAdapter for McfunctionDebugAdapter {
     async fn launch(
         &mut self,
         args: LaunchRequestArguments,
-        context: impl DebugAdapterContext + Send,
+        mut context: impl DebugAdapterContext + Send,
     ) -> Result<(), RequestError<Self::CustomError>> {
-        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        Self::unwrap_client_session(&mut self.client_session)?;
 
         let config = get_config(&args)?;
 
-        let mut connection = establish_connection(
-            &config.minecraft_world_dir,
-            &config.minecraft_log_file,
-            context,
-        )
-        .await?;
+        let connection = self.connect(&config, &mut context).await?;
 
-        let mut events = connection.add_named_listener(LISTENER_NAME);
-        let message_sender = self.message_sender.clone();
-        tokio::spawn(async move {
-            while let Some(event) = events.next().await {
-                if let Err(_) = message_sender.send(Either::Right(event)) {
-                    break;
+        if let Some(pattern) = find_exclude_pattern(&config.exclude, &config.function) {
+            return Err(PartialErrorResponse::new(format!(
+                "Attribute 'program' is excluded by pattern '{}'",
+                pattern
+            ))
+            .into());
+        }
+
+        let mut minecraft_session = self.create_minecraft_session(&config, connection).await?;
+        let debug_datapack_name = format!("debug-{}", config.datapack_name);
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+
+        if minecraft_session.breakpoint_comments {
+            // Lines verified before the launch did not consider the comments
+            client_session.verified_lines.clear();
+            let mut functions = Vec::new();
+            for datapack in minecraft_session.datapacks() {
+                functions.extend(
+                    find_functions(&datapack)
+                        .await
+                        .map_err(PartialErrorResponse::from)?,
+                );
+            }
+            for (function, path) in functions {
+                if find_exclude_pattern(&minecraft_session.exclude, &function).is_some() {
+                    continue;
+                }
+                // Path is known to be UTF-8
+                let path = path.to_str().unwrap();
+                let events = client_session
+                    .update_comment_breakpoints(&function, path, None)
+                    .await
+                    .map_err(|e| {
+                        PartialErrorResponse::new(format!("Failed to read file {}: {}", path, e))
+                    })?;
+                for event in events {
+                    context.fire_event(event);
                 }
             }
-        });
+        }
 
-        let namespace = "mcfd".to_string(); // Hardcoded in installer as well
-        let debug_datapack_name = format!("debug-{}", config.datapack_name);
-        let output_path = config
-            .minecraft_world_dir
-            .join("datapacks")
-            .join(&debug_datapack_name);
+        if config.stop_on_entry {
+            // Removed after the first stop like every temporary breakpoint, so recursive calls of
+            // the program don't stop on entry again
+            let path = minecraft_session.get_function_path(&config.function);
+            let line_number = find_first_target_line_number(&path, &client_session.parser).await?;
+            let position = LocalBreakpointPosition {
+                line_number,
+                position_in_line: BreakpointPositionInLine::Breakpoint,
+            };
+            client_session.temporary_breakpoints.insert(
+                config.function.clone(),
+                LocalBreakpoint {
+                    kind: BreakpointKind::Temporary,
+                    position,
+                },
+            );
+            minecraft_session.entry_position = Some(BreakpointPosition::from_breakpoint(
+                config.function.clone(),
+                &position,
+            ));
+        }
 
-        let mut minecraft_session = MinecraftSession {
-            connection,
-            datapack: config.datapack.to_path_buf(),
-            namespace,
-            output_path,
-            scopes: Vec::new(),
-            stopped_data: None,
+        // Partially generated files are removed if the launch is cancelled or fails
+        let datapack_guard = (!minecraft_session.keep_debug_datapack)
+            .then(|| DatapackGuard::new(minecraft_session.output_path.clone()));
+
+        let mut progress =
+            context.start_cancellable_progress("Generating debug datapack".to_string(), None);
+        let progress_id = progress.progress_id.to_string();
+        let result = {
+            let generate = generate_datapack(
+                &mut minecraft_session,
+                &client_session.parser,
+                &client_session.breakpoints,
+                &client_session.temporary_breakpoints,
+            );
+            pin_mut!(generate);
+            let cancel = progress.next_cancel_request();
+            pin_mut!(cancel);
+            match select(generate, cancel).await {
+                Either::Left((result, _)) => Some(result),
+                Either::Right(_) => None,
+            }
         };
+        let progress_end_message = match &result {
+            Some(Ok(())) => "Successfully generated debug datapack",
+            Some(Err(_)) => "Failed to generate debug datapack",
+            None => "Cancelled generating debug datapack",
+        };
+        context.end_cancellable_progress(progress_id, Some(progress_end_message.to_string()));
+        result.ok_or_else(|| PartialErrorResponse::new("cancelled".to_string()))??;
+
+        if let Some(warning) = minecraft_session.check_on_suspend().await {
+            let event = OutputEventBody::builder()
+                .category(OutputCategory::Important)
+                .output(warning)
+                .build();
+            context.fire_event(event);
+        }
 
-        generate_datapack(
-            &minecraft_session,
-            &client_session.breakpoints,
-            &client_session.temporary_breakpoints,
-        )
-        .await?;
+        if let Some(warning) =
+            check_pack_format(&minecraft_session.datapack, &config.minecraft_log_file).await
+        {
+            let event = OutputEventBody::builder()
+                .category(OutputCategory::Important)
+                .output(warning)
+                .build();
+            context.fire_event(event);
+        }
+
+        let warnings = minecraft_session
+            .generated
+            .iter()
+            .flat_map(|it| &it.warnings);
+        for warning in warnings {
+            let event = OutputEventBody::builder()
+                .category(OutputCategory::Important)
+                .output(format!("{}\n", warning))
+                .build();
+            context.fire_event(event);
+        }
+
+        let events = client_session.get_breakpoint_events(&minecraft_session, |function| {
+            minecraft_session.get_unverified_reason(function).is_some()
+        });
+        for event in events {
+            context.fire_event(event);
+        }
 
-        minecraft_session.inject_commands(vec![
+        let deferred_load = minecraft_session
+            .generated
+            .as_ref()
+            .is_some_and(|generated| generated.deferred_load);
+        let reuse_objectives = minecraft_session.has_installed_objectives().await?;
+        let mut commands = vec![
             Command::new("reload"),
             Command::new(format!("datapack enable \"file/{}\"", debug_datapack_name)),
-            // After loading the datapack we must wait one tick for it to install itself
-            // By scheduling this function call we also have a defined execution position
-            Command::new(format!(
-                "schedule function debug:{}/{} 1t",
-                config.function.namespace(),
-                config.function.path(),
-            )),
-        ])?;
-
+        ];
+        // After loading the datapack we must wait one tick for it to install itself
+        // By scheduling these function calls we also have a defined execution position
+        let mut delay = 1;
+        if reuse_objectives {
+            let event = OutputEventBody::builder()
+                .category(OutputCategory::Console)
+                .output(
+                    "Reusing the objectives of a previous debug session. To remove all \
+                    objectives, tags and entities of the debugger execute: /function debug:purge\n"
+                        .to_string(),
+                )
+                .build();
+            context.fire_event(event);
+            commands.push(Command::new(
+                minecraft_session.replace_ns("schedule function -ns-:reset_session 1t"),
+            ));
+        }
+        if deferred_load {
+            let event = OutputEventBody::builder()
+                .category(OutputCategory::Console)
+                .output(
+                    "Execution of #minecraft:load was deferred until the debug datapack is \
+                    installed, because it calls functions with breakpoints.\n"
+                        .to_string(),
+                )
+                .build();
+            context.fire_event(event);
+            commands.push(Command::new("schedule function debug:run_load 1t"));
+            delay += 1;
+        }
+        commands.push(Command::new(format!(
+            "schedule function debug:{}/{} {}t",
+            config.function.namespace(),
+            config.function.path(),
+            delay,
+        )));
+        minecraft_session.inject_commands(commands)?;
+
+        if let Some(datapack_guard) = datapack_guard {
+            datapack_guard.disarm();
+        }
         client_session.minecraft_session = Some(minecraft_session);
         Ok(())
     }
 
+    async fn loaded_sources(
+        &mut self,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<LoadedSourcesResponseBody, RequestError<Self::CustomError>> {
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        let sources = client_session
+            .minecraft_session
+            .iter()
+            .flat_map(|minecraft_session| &minecraft_session.loaded_sources)
+            .map(|(function, path)| loaded_source(function, path))
+            .collect();
+        Ok(LoadedSourcesResponseBody::builder()
+            .sources(sources)
+            .build())
+    }
+
     async fn next(
         &mut self,
-        _args: NextRequestArguments,
+        args: NextRequestArguments,
         _context: impl DebugAdapterContext + Send,
     ) -> Result<(), RequestError<Self::CustomError>> {
         let client_session = Self::unwrap_client_session(&mut self.client_session)?;
@@ -709,7 +3593,7 @@ impl DebugAdapter for McfunctionDebugAdapter {
 
         let stack_trace = mc_session.get_cached_stack_trace()?;
         let temporary_breakpoints = mc_session
-            .create_step_over_breakpoints(stack_trace, &client_session.parser)
+            .create_step_over_breakpoints(stack_trace, &args.granularity, &client_session.parser)
             .await?;
         self.continue_internal(temporary_breakpoints).await?;
 
@@ -743,35 +3627,103 @@ impl DebugAdapter for McfunctionDebugAdapter {
         let client_session = Self::unwrap_client_session(&mut self.client_session)?;
         let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
 
+        let frame = mc_session
+            .get_cached_stack_trace()?
+            .iter()
+            .find(|frame| frame.id == args.frame_id)
+            .ok_or_else(|| {
+                PartialErrorResponse::new(format!("Unknown frame id {}", args.frame_id))
+            })?;
+
+        let server_context = frame.server_context;
+
         let mut scopes = Vec::new();
-        let is_server_context = mc_session.get_context_entity_id(args.frame_id).await? == 0;
-        if !is_server_context {
-            scopes.push(create_selected_entity_scores_scope(mc_session, args));
+        if !server_context {
+            let kind = ScopeKind::SelectedEntityScores;
+            scopes.push(create_scope(mc_session, args.frame_id, kind));
+            let tag_count = mc_session.query_tags(args.frame_id).await?.len();
+            if tag_count > 0 {
+                let kind = ScopeKind::SelectedEntityTags;
+                let mut scope = create_scope(mc_session, args.frame_id, kind);
+                scope.indexed_variables = Some(tag_count as i32);
+                scopes.push(scope);
+            }
+            let kind = ScopeKind::SelectedEntityPosition;
+            scopes.push(create_scope(mc_session, args.frame_id, kind));
+            let kind = ScopeKind::SelectedEntityNbt;
+            let mut scope = create_scope(mc_session, args.frame_id, kind);
+            scope.expensive = true;
+            scopes.push(scope);
         }
+        scopes.push(create_scope(mc_session, args.frame_id, ScopeKind::Globals));
         Ok(ScopesResponseBody::builder().scopes(scopes).build().into())
     }
 
     async fn set_breakpoints(
         &mut self,
         args: SetBreakpointsRequestArguments,
-        _context: impl DebugAdapterContext + Send,
+        mut context: impl DebugAdapterContext + Send,
     ) -> Result<SetBreakpointsResponseBody, RequestError<Self::CustomError>> {
         let client_session = Self::unwrap_client_session(&mut self.client_session)?;
 
         let offset = client_session.get_line_offset();
-        let path = match client_session.path_format {
-            PathFormat::Path => args.source.path.as_ref().ok_or_else(|| {
-                PartialErrorResponse::new("Missing argument source.path".to_string())
-            })?,
-            PathFormat::URI => todo!("Implement path URIs"),
-        };
-        let (_datapack, function) = parse_function_path(path.as_ref())
+        let column_offset = client_session.get_column_offset();
+        let mut path = client_session.get_source_path(&args.source)?.clone();
+        let (datapack, mut function) = parse_function_path(Path::new(&path))
             .map_err(|e| PartialErrorResponse::new(format!("Argument source.path {}", e)))?;
+        let datapack = datapack.to_path_buf();
+
+        // The file may have been renamed or moved while breakpoints were set
+        let mut moved_breakpoints = None;
+        if !is_file(&path) {
+            match client_session
+                .find_moved_function(&function, &datapack)
+                .await?
+            {
+                Some((new_function, new_path)) => {
+                    // The comments are registered again for the new file and keep their ids
+                    let comments = client_session.take_comment_breakpoints(&function);
+                    let mut breakpoints = client_session.remove_breakpoints(&function);
+                    breakpoints.retain(|(id, _)| !comments.iter().any(|(it, _)| it == id));
+                    moved_breakpoints = Some(breakpoints);
+                    client_session
+                        .comment_breakpoints
+                        .insert_many(new_function.clone(), comments);
+                    function = new_function;
+                    // Path is known to be UTF-8
+                    path = new_path.to_str().unwrap().to_string();
+                }
+                None => {
+                    return Self::set_breakpoints_in_missing_file(
+                        client_session,
+                        &args,
+                        &function,
+                        &path,
+                        &mut context,
+                    )
+                    .await;
+                }
+            }
+        }
+        let hash = hash_function_file(&path).await.map_err(|e| {
+            PartialErrorResponse::new(format!("Failed to read file {}: {}", path, e))
+        })?;
+        client_session
+            .function_hashes
+            .insert(function.clone(), hash);
 
         let breakpoints = args
             .breakpoints
             .iter()
-            .map(|source_breakpoint| (function.clone(), source_breakpoint.line as usize + offset))
+            .map(|source_breakpoint| {
+                (
+                    function.clone(),
+                    source_breakpoint.line as usize + offset,
+                    source_breakpoint
+                        .column
+                        .map(|column| column as usize + column_offset),
+                )
+            })
             .collect::<Vec<_>>();
 
         let mut response = Vec::new();
@@ -779,33 +3731,112 @@ impl DebugAdapter for McfunctionDebugAdapter {
             .breakpoints
             .remove(&function)
             .unwrap_or_default();
+        client_session.breakpoint_ids.remove(&function);
+
+        let mut added_namespaces = BTreeSet::new();
+        if let Some(minecraft_session) = client_session.minecraft_session.as_mut() {
+            if !minecraft_session
+                .datapack_namespaces
+                .contains(function.namespace())
+            {
+                // The namespace may have been added to the datapack after launch
+                let namespaces = find_all_function_namespaces(&minecraft_session.datapacks())
+                    .await
+                    .map_err(PartialErrorResponse::from)?;
+                added_namespaces = namespaces
+                    .difference(&minecraft_session.datapack_namespaces)
+                    .cloned()
+                    .collect();
+                minecraft_session.datapack_namespaces = namespaces;
+            }
+
+            // The function may have been created, renamed or moved since the last scan
+            if args.source_modified
+                || moved_breakpoints.is_some()
+                || !minecraft_session.loaded_sources.contains_key(&function)
+            {
+                let events = minecraft_session
+                    .update_loaded_sources()
+                    .await
+                    .map_err(PartialErrorResponse::from)?;
+                for event in events {
+                    context.fire_event(event);
+                }
+            }
+        }
+        let mut unverified_reason = None;
+        if let Some(minecraft_session) = client_session.minecraft_session.as_ref() {
+            let events = client_session.get_breakpoint_events(minecraft_session, |function| {
+                added_namespaces.contains(function.namespace())
+            });
+            for event in events {
+                context.fire_event(event);
+            }
+            unverified_reason = minecraft_session.get_unverified_reason(&function);
+        }
+
+        let comments = client_session
+            .minecraft_session
+            .as_ref()
+            .is_some_and(|minecraft_session| minecraft_session.breakpoint_comments);
+        let verified_lines = client_session
+            .verified_lines
+            .entry(function.clone())
+            .or_insert_with(VerifiedLines::new);
+        if args.source_modified || is_modified_since(&path, verified_lines.verified_at).await {
+            *verified_lines = VerifiedLines::new();
+        }
+
         let mut new_breakpoints = Vec::with_capacity(breakpoints.len());
-        for (i, (function, line_number)) in breakpoints.into_iter().enumerate() {
-            let id = (i + client_session.breakpoints.len()) as i32;
-            let verified = verify_breakpoint(&client_session.parser, path, line_number)
-                .await
-                .map_err(|e| {
-                    PartialErrorResponse::new(format!(
-                        "Failed to verify breakpoint {}:{}: {}",
-                        function, line_number, e
-                    ))
-                })?;
+        let mut new_breakpoint_ids = Vec::with_capacity(breakpoints.len());
+        for (function, line_number, column) in breakpoints {
+            let id = client_session.next_breakpoint_id;
+            client_session.next_breakpoint_id += 1;
+            let (valid, after_execute_column) = match verified_lines.lines.get(&line_number) {
+                Some(verification) => *verification,
+                None => {
+                    let verification =
+                        verify_breakpoint(&client_session.parser, &path, line_number, comments)
+                            .await
+                            .map_err(|e| {
+                                PartialErrorResponse::new(format!(
+                                    "Failed to verify breakpoint {}:{}: {}",
+                                    function, line_number, e
+                                ))
+                            })?;
+                    verified_lines.lines.insert(line_number, verification);
+                    verification
+                }
+            };
+            let verified = valid && unverified_reason.is_none();
+            // A breakpoint after the execute prefix of a function call is hit for every executor
+            let after_execute_column = after_execute_column
+                .filter(|after_execute_column| column >= Some(*after_execute_column));
+            let position_in_line = if after_execute_column.is_some() {
+                BreakpointPositionInLine::AfterExecute
+            } else {
+                BreakpointPositionInLine::Breakpoint
+            };
             new_breakpoints.push(LocalBreakpoint {
-                kind: if verified {
+                kind: if valid {
                     BreakpointKind::Normal
                 } else {
                     BreakpointKind::Invalid
                 },
                 position: LocalBreakpointPosition {
                     line_number,
-                    position_in_line: BreakpointPositionInLine::Breakpoint,
+                    position_in_line,
                 },
             });
+            new_breakpoint_ids.push(id);
             response.push(
                 Breakpoint::builder()
-                    .id(verified.then(|| id))
+                    .id(Some(id))
                     .verified(verified)
+                    .message(unverified_reason.clone().filter(|_| valid))
+                    .source(moved_breakpoints.as_ref().map(|_| source(&path)))
                     .line(Some((line_number - offset) as i32))
+                    .column(after_execute_column.map(|column| (column - column_offset) as i32))
                     .build(),
             );
         }
@@ -813,12 +3844,77 @@ impl DebugAdapter for McfunctionDebugAdapter {
         client_session
             .breakpoints
             .insert_many(function.clone(), new_breakpoints);
+        client_session
+            .breakpoint_ids
+            .insert_many(function.clone(), new_breakpoint_ids);
+        let events = if comments {
+            client_session
+                .update_comment_breakpoints(&function, &path, unverified_reason.clone())
+                .await
+                .map_err(|e| {
+                    PartialErrorResponse::new(format!("Failed to read file {}: {}", path, e))
+                })?
+        } else {
+            get_removed_comment_events(client_session.take_comment_breakpoints(&function))
+        };
+        for event in events {
+            context.fire_event(event);
+        }
         // Unwrap is safe, because we just inserted the value
         let new_breakpoints = client_session.breakpoints.get_vec(&function).unwrap();
 
+        // Rebind the breakpoints that the client set in the old file to the new file
+        for (id, breakpoint) in moved_breakpoints.iter().flatten() {
+            if breakpoint.kind == BreakpointKind::Normal {
+                let line_number = breakpoint.position.line_number - offset;
+                let event = BreakpointEventBody::builder()
+                    .reason(BreakpointEventReason::Changed)
+                    .breakpoint(
+                        Breakpoint::builder()
+                            .id(Some(*id))
+                            .verified(unverified_reason.is_none())
+                            .message(unverified_reason.clone())
+                            .source(Some(source(&path)))
+                            .line(Some(line_number as i32))
+                            .build(),
+                    )
+                    .build();
+                context.fire_event(event);
+            }
+        }
+
         if let Some(minecraft_session) = client_session.minecraft_session.as_mut() {
+            // Clients may resend identical breakpoints, skip the reload to not disturb Minecraft
+            let breakpoints = merge_breakpoints(
+                &client_session.breakpoints,
+                &client_session.temporary_breakpoints,
+            );
+            let up_to_date = match &minecraft_session.generated {
+                // The old function has to be removed from the debug datapack
+                Some(generated) if !args.source_modified && moved_breakpoints.is_none() => {
+                    generated
+                        .is_up_to_date(&function, &breakpoints, &path)
+                        .await
+                }
+                _ => false,
+            };
+            if up_to_date {
+                return Ok(SetBreakpointsResponseBody::builder()
+                    .breakpoints(response)
+                    .build());
+            }
+            // Moving the breakpoints of a modified source needs the reload of this request
+            if context.is_superseded() && !args.source_modified {
+                // The queued request generates the datapack with the latest breakpoints
+                minecraft_session.generated = None;
+                return Ok(SetBreakpointsResponseBody::builder()
+                    .breakpoints(response)
+                    .build());
+            }
+
             generate_datapack(
                 minecraft_session,
+                &client_session.parser,
                 &client_session.breakpoints,
                 &client_session.temporary_breakpoints,
             )
@@ -845,7 +3941,7 @@ impl DebugAdapter for McfunctionDebugAdapter {
 
     async fn stack_trace(
         &mut self,
-        _args: StackTraceRequestArguments,
+        args: StackTraceRequestArguments,
         _context: impl DebugAdapterContext + Send,
     ) -> Result<StackTraceResponseBody, RequestError<Self::CustomError>> {
         let client_session = Self::unwrap_client_session(&mut self.client_session)?;
@@ -853,11 +3949,35 @@ impl DebugAdapter for McfunctionDebugAdapter {
         let get_column_offset = client_session.get_column_offset();
         let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
 
-        let stack_trace = mc_session
+        // A pending executor waits at the function call of the suspended function
+        let after_execute = mc_session.stopped_data.as_ref().is_some_and(|it| {
+            it.position.position_in_line == BreakpointPositionInLine::AfterExecute
+        });
+        let skipped_frames = usize::from(args.thread_id != MAIN_THREAD_ID && !after_execute);
+
+        let mut stack_trace = Vec::new();
+        for it in mc_session
             .get_cached_stack_trace()?
-            .into_iter()
-            .map(|it| it.to_stack_frame(&mc_session.datapack, get_line_offset, get_column_offset))
-            .collect::<Vec<_>>();
+            .iter()
+            .skip(skipped_frames)
+        {
+            let datapack = mc_session.get_datapack(&it.location.function);
+            let path = mc_session.get_function_path(&it.location.function);
+            let content = read_to_string(path).await.unwrap_or_default();
+            let source_changed = mc_session.generated.as_ref().is_some_and(|generated| {
+                generated.is_source_changed(
+                    &it.location.function,
+                    it.location.line_number,
+                    &content,
+                )
+            });
+            stack_trace.push(it.to_stack_frame(
+                datapack,
+                get_line_offset,
+                get_column_offset,
+                source_changed,
+            ));
+        }
 
         Ok(StackTraceResponseBody::builder()
             .total_frames(Some(stack_trace.len() as i32))
@@ -867,11 +3987,22 @@ impl DebugAdapter for McfunctionDebugAdapter {
 
     async fn terminate(
         &mut self,
-        _args: TerminateRequestArguments,
+        args: TerminateRequestArguments,
         _context: impl DebugAdapterContext + Send,
     ) -> Result<(), RequestError<Self::CustomError>> {
         if let Some(client_session) = &mut self.client_session {
             if let Some(minecraft_session) = &mut client_session.minecraft_session {
+                minecraft_session
+                    .restore_world()
+                    .map_err(Self::map_custom_error)?;
+                if !args.restart
+                    && minecraft_session.run_to_completion_on_terminate
+                    && minecraft_session
+                        .run_to_completion(&client_session.parser)
+                        .await?
+                {
+                    return Ok(());
+                }
                 minecraft_session.inject_commands(vec![Command::new("function debug:stop")])?;
             }
         }
@@ -880,7 +4011,7 @@ impl DebugAdapter for McfunctionDebugAdapter {
 
     async fn step_in(
         &mut self,
-        _args: StepInRequestArguments,
+        args: StepInRequestArguments,
         _context: impl DebugAdapterContext + Send,
     ) -> Result<(), RequestError<Self::CustomError>> {
         let client_session = Self::unwrap_client_session(&mut self.client_session)?;
@@ -888,13 +4019,52 @@ impl DebugAdapter for McfunctionDebugAdapter {
 
         let stack_trace = mc_session.get_cached_stack_trace()?;
         let temporary_breakpoints = mc_session
-            .create_step_in_breakpoints(stack_trace, &client_session.parser)
+            .create_step_in_breakpoints(
+                stack_trace,
+                args.target_id,
+                &args.granularity,
+                &client_session.parser,
+            )
             .await?;
         self.continue_internal(temporary_breakpoints).await?;
 
         Ok(())
     }
 
+    async fn step_in_targets(
+        &mut self,
+        args: StepInTargetsRequestArguments,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<StepInTargetsResponseBody, RequestError<Self::CustomError>> {
+        let client_session = Self::unwrap_client_session(&mut self.client_session)?;
+        let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
+
+        let stack_trace = mc_session.get_cached_stack_trace()?;
+        let frame = stack_trace
+            .iter()
+            .find(|frame| frame.id == args.frame_id)
+            .ok_or_else(|| {
+                PartialErrorResponse::new(format!("Unknown frame id: {}", args.frame_id))
+            })?;
+        let path = mc_session.get_function_path(&frame.location.function);
+        let callees = get_function_calls(path, frame.location.line_number, &client_session.parser)
+            .await?
+            .into_iter()
+            .filter(|(_column_index, callee)| !mc_session.is_excluded(callee) && !callee.is_tag());
+
+        let targets = callees
+            .map(|(column_index, callee)| {
+                StepInTarget::builder()
+                    .id(column_index as i32)
+                    .label(format!("{} (column {})", callee, column_index + 1))
+                    .build()
+            })
+            .collect();
+        Ok(StepInTargetsResponseBody::builder()
+            .targets(targets)
+            .build())
+    }
+
     async fn step_out(
         &mut self,
         _args: StepOutRequestArguments,
@@ -917,14 +4087,26 @@ impl DebugAdapter for McfunctionDebugAdapter {
         _context: impl DebugAdapterContext + Send,
     ) -> Result<ThreadsResponseBody, RequestError<Self::CustomError>> {
         let client_session = Self::unwrap_client_session(&mut self.client_session)?;
-        let _mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
+        let mc_session = Self::unwrap_minecraft_session(&mut client_session.minecraft_session)?;
 
-        let thread = Thread::builder()
+        let mut threads = vec![Thread::builder()
             .id(MAIN_THREAD_ID)
             .name("Main Thread".to_string())
-            .build();
+            .build()];
+        let pending_executors = mc_session
+            .get_pending_executors()
+            .await
+            .map_err(Self::map_custom_error)?;
+        for executor in pending_executors {
+            threads.push(
+                Thread::builder()
+                    .id(executor.iteration)
+                    .name(format!("{} (pending)", executor.name))
+                    .build(),
+            );
+        }
         Ok(ThreadsResponseBody::builder()
-            .threads(vec![thread])
+            .threads(threads)
             .build()
             .into())
     }
@@ -950,67 +4132,264 @@ impl DebugAdapter for McfunctionDebugAdapter {
             .get(scope_id)
             .ok_or_else(unknown_variables_reference)?;
 
-        const START: &str = "variables.start";
-        const END: &str = "variables.end";
-
-        match scope.kind {
+        let frame_id = scope.frame_id;
+        let variables = match scope.kind {
             ScopeKind::SelectedEntityScores => {
-                let events = mc_session.connection.add_listener();
-
-                let execute_as_context = format!(
-                    "execute as @e[\
-                        type=area_effect_cloud,\
-                        tag=-ns-_context,\
-                        tag=-ns-_active,\
-                        tag=-ns-_current,\
-                        scores={{-ns-_depth={}}},\
-                    ] run",
-                    scope.frame_id
-                );
-                let decrement_ids = mc_session.replace_ns(&format!(
-                    "{} scoreboard players operation @e[tag=!-ns-_context] -ns-_id -= @s -ns-_id",
-                    execute_as_context
-                ));
-                let increment_ids = mc_session.replace_ns(&format!(
-                    "{} scoreboard players operation @e[tag=!-ns-_context] -ns-_id += @s -ns-_id",
-                    execute_as_context
-                ));
-                mc_session.inject_commands(vec![
-                    Command::new(logged_command(enable_logging_command())),
-                    Command::new(named_logged_command(
-                        LISTENER_NAME,
-                        summon_named_entity_command(START),
-                    )),
-                    Command::new(logged_command(decrement_ids)),
-                    Command::new(mc_session.replace_ns("function -ns-:log_scores")),
-                    Command::new(logged_command(increment_ids)),
-                    Command::new(named_logged_command(
-                        LISTENER_NAME,
-                        summon_named_entity_command(END),
-                    )),
-                    Command::new(logged_command(reset_logging_command())),
-                ])?;
-
-                let variables = events_between(events, START, END)
+                let objective = scope.objective.clone();
+                let mut commands = vec![Command::new(
+                    mc_session.replace_ns("function -ns-:log_scores"),
+                )];
+                commands.extend(mc_session.query_dynamic_objectives().await?);
+                let mut scores = BTreeMap::<_, Vec<_>>::new();
+                for output in mc_session
+                    .query_selected_entity(frame_id, commands)
+                    .await?
+                    .into_iter()
                     .filter_map(|event| event.output.parse::<QueryScoreboardOutput>().ok())
-                    .map(|output| {
+                {
+                    scores
+                        .entry(output.scoreboard)
+                        .or_default()
+                        .push((output.entity, output.score));
+                }
+                // Scores are always named variables
+                match objective {
+                    Some(objective) => filter_variables(
+                        scores
+                            .remove(&objective)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|it| (VariableKind::Named, it)),
+                        &args,
+                    )
+                    .into_iter()
+                    .map(|(entity, score)| {
+                        Variable::builder()
+                            .name(entity)
+                            .value(score.to_string())
+                            .variables_reference(0)
+                            .build()
+                    })
+                    .collect::<Vec<_>>(),
+                    // With a single objective there is nothing to group
+                    None if scores.len() <= 1 => filter_variables(
+                        scores
+                            .into_iter()
+                            .flat_map(|(objective, scores)| {
+                                scores
+                                    .into_iter()
+                                    .map(move |(_entity, score)| (objective.clone(), score))
+                            })
+                            .map(|it| (VariableKind::Named, it)),
+                        &args,
+                    )
+                    .into_iter()
+                    .map(|(objective, score)| {
                         Variable::builder()
-                            .name(output.scoreboard)
-                            .value(output.score.to_string())
+                            .name(objective)
+                            .value(score.to_string())
+                            .variables_reference(0)
+                            .build()
+                    })
+                    .collect::<Vec<_>>(),
+                    None => filter_variables(
+                        scores.into_iter().map(|it| (VariableKind::Named, it)),
+                        &args,
+                    )
+                    .into_iter()
+                    .map(|(objective, scores)| {
+                        let value = match scores.as_slice() {
+                            [(_entity, score)] => score.to_string(),
+                            _ => format!("{} scores", scores.len()),
+                        };
+                        let named_variables = scores.len() as i32;
+                        mc_session.scopes.push(ScopeReference {
+                            frame_id,
+                            kind: ScopeKind::SelectedEntityScores,
+                            nbt_path: Vec::new(),
+                            objective: Some(objective.clone()),
+                        });
+                        Variable::builder()
+                            .name(objective)
+                            .value(value)
+                            .variables_reference(mc_session.scopes.len() as i32)
+                            .named_variables(Some(named_variables))
+                            .build()
+                    })
+                    .collect::<Vec<_>>(),
+                }
+            }
+            ScopeKind::SelectedEntityTags => {
+                let tags = mc_session.query_tags(frame_id).await?;
+                let tags = tags
+                    .into_iter()
+                    .enumerate()
+                    .map(|it| (VariableKind::Indexed, it));
+                filter_variables(tags, &args)
+                    .into_iter()
+                    .map(|(index, tag)| {
+                        Variable::builder()
+                            .name(index.to_string())
+                            .value(tag)
                             .variables_reference(0)
                             .build()
                     })
                     .collect::<Vec<_>>()
-                    .await;
-
-                Ok(VariablesResponseBody::builder()
-                    .variables(variables)
-                    .build())
             }
-        }
+            ScopeKind::SelectedEntityPosition => {
+                let (pos, dimension) = mc_session.query_position(frame_id).await?;
+                let mut variables = pos
+                    .iter()
+                    .zip(["x", "y", "z"])
+                    .enumerate()
+                    .map(|(index, (value, name))| {
+                        let path = [
+                            NbtPathSegment::Key("Pos".to_string()),
+                            NbtPathSegment::Index(index),
+                        ];
+                        Variable::builder()
+                            .name(name.to_string())
+                            .value(value.to_string())
+                            .evaluate_name(Some(format!("nbt:@s {}", format_nbt_path(&path))))
+                            .variables_reference(0)
+                            .build()
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(dimension) = dimension {
+                    variables.push(
+                        Variable::builder()
+                            .name("dimension".to_string())
+                            .value(dimension.to_string())
+                            .variables_reference(0)
+                            .build(),
+                    );
+                }
+                let variables = variables.into_iter().map(|it| (VariableKind::Named, it));
+                filter_variables(variables, &args)
+            }
+            ScopeKind::Globals => {
+                let globals = mc_session.query_globals().await?;
+                let mut values = vec![
+                    (
+                        "gametime".to_string(),
+                        globals.gametime.map(|it| it.to_string()),
+                    ),
+                    (
+                        "daytime".to_string(),
+                        globals.daytime.map(|it| it.to_string()),
+                    ),
+                    ("weather".to_string(), Some(globals.weather().to_string())),
+                    (
+                        "difficulty".to_string(),
+                        globals.difficulty.map(|it| it.to_string()),
+                    ),
+                ];
+                values.extend(
+                    globals
+                        .gamerules
+                        .iter()
+                        .map(|(gamerule, value)| (gamerule.clone(), Some(value.clone()))),
+                );
+                let variables = values.into_iter().filter_map(|(name, value)| {
+                    let variable = Variable::builder()
+                        .name(name)
+                        .value(value?)
+                        .variables_reference(0)
+                        .build();
+                    Some((VariableKind::Named, variable))
+                });
+                filter_variables(variables, &args)
+            }
+            ScopeKind::SelectedEntityNbt => {
+                let nbt_path = scope.nbt_path.clone();
+                let nbt = mc_session.query_nbt(frame_id).await?;
+                let value = nbt_path
+                    .iter()
+                    .try_fold(nbt, |value, segment| segment.get(value))
+                    .ok_or_else(unknown_variables_reference)?;
+                // Compound entries are named and list entries are indexed
+                let children = match value {
+                    Value::Object(compound) => compound
+                        .iter()
+                        .map(|(key, value)| {
+                            let segment = NbtPathSegment::Key(key.clone());
+                            (VariableKind::Named, (segment, value.clone()))
+                        })
+                        .collect::<Vec<_>>(),
+                    Value::Array(list) => list
+                        .iter()
+                        .enumerate()
+                        .map(|(index, value)| {
+                            let segment = NbtPathSegment::Index(index);
+                            (VariableKind::Indexed, (segment, value.clone()))
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                filter_variables(children, &args)
+                    .into_iter()
+                    .map(|(segment, value)| {
+                        let name = match &segment {
+                            NbtPathSegment::Key(key) => key.clone(),
+                            NbtPathSegment::Index(index) => index.to_string(),
+                        };
+                        let mut path = nbt_path.clone();
+                        path.push(segment);
+                        let evaluate_name = format_nbt_path(&path);
+                        let (value, named_variables, indexed_variables) = match &value {
+                            Value::Object(compound) => {
+                                ("{...}".to_string(), Some(compound.len() as i32), None)
+                            }
+                            Value::Array(list) => {
+                                ("[...]".to_string(), None, Some(list.len() as i32))
+                            }
+                            value => (value.to_string(), None, None),
+                        };
+                        let variables_reference =
+                            if named_variables.is_some() || indexed_variables.is_some() {
+                                mc_session.scopes.push(ScopeReference {
+                                    frame_id,
+                                    kind: ScopeKind::SelectedEntityNbt,
+                                    nbt_path: path,
+                                    objective: None,
+                                });
+                                mc_session.scopes.len()
+                            } else {
+                                0
+                            };
+                        Variable::builder()
+                            .name(name)
+                            .value(value)
+                            .evaluate_name(Some(evaluate_name))
+                            .variables_reference(variables_reference as i32)
+                            .named_variables(named_variables)
+                            .indexed_variables(indexed_variables)
+                            .build()
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        Ok(VariablesResponseBody::builder()
+            .variables(variables)
+            .build())
+    }
+}
+
+fn remove_suspended_function_call(
+    stack_trace: &mut Vec<McfunctionStackFrame>,
+    position: &BreakpointPosition,
+) {
+    if position.position_in_line == BreakpointPositionInLine::AfterExecute && stack_trace.len() > 1
+    {
+        // The function call is already in the stack trace as the suspended position
+        stack_trace.remove(1);
     }
 }
 
+fn remove_temporary_breakpoints(breakpoints: &mut MultiMap<ResourceLocation, LocalBreakpoint>) {
+    breakpoints.retain(|_function, breakpoint| breakpoint.kind != BreakpointKind::Temporary);
+}
+
 async fn find_first_target_line_number(
     path: impl AsRef<Path>,
     parser: &CommandParser,
@@ -1058,43 +4437,101 @@ async fn find_step_target_line_number(
     }
 }
 
+/// Execution can only be continued before a command, so the target of a goto is the first command
+/// at or after the given line.
+async fn find_goto_target_line_number(
+    path: impl AsRef<Path>,
+    line_number: usize,
+    parser: &CommandParser,
+) -> Result<Option<usize>, RequestError<io::Error>> {
+    find_step_target_line_number(path, line_number.saturating_sub(1), parser, false).await
+}
+
 async fn get_function_command(
     path: impl AsRef<Path>,
     line_number: usize,
     parser: &CommandParser,
-) -> Result<Option<ResourceLocation>, RequestError<io::Error>> {
-    let file = File::open(&path).await.map_err(|e| {
+) -> Result<Option<(usize, ResourceLocation)>, RequestError<io::Error>> {
+    if let Some(Line::FunctionCall {
+        column_index, name, ..
+    }) = read_line(path, line_number, parser).await?
+    {
+        return Ok(Some((column_index, name)));
+    }
+    Ok(None)
+}
+
+/// Returns all functions that the command in the line calls, see [parse_function_calls].
+async fn get_function_calls(
+    path: impl AsRef<Path>,
+    line_number: usize,
+    parser: &CommandParser,
+) -> Result<Vec<(usize, ResourceLocation)>, RequestError<io::Error>> {
+    let line = read_raw_line(path, line_number).await?;
+    Ok(line
+        .map(|line| parse_function_calls(parser, &line))
+        .unwrap_or_default())
+}
+
+async fn is_return_command(
+    path: impl AsRef<Path>,
+    line_number: usize,
+    parser: &CommandParser,
+) -> Result<bool, RequestError<io::Error>> {
+    let line = read_line(path, line_number, parser).await?;
+    Ok(matches!(line, Some(Line::Return { .. })))
+}
+
+async fn read_line(
+    path: impl AsRef<Path>,
+    line_number: usize,
+    parser: &CommandParser,
+) -> Result<Option<Line>, RequestError<io::Error>> {
+    let line = read_raw_line(path, line_number).await?;
+    Ok(line.map(|line| parse_line(parser, &line, false)))
+}
+
+async fn read_raw_line(
+    path: impl AsRef<Path>,
+    line_number: usize,
+) -> Result<Option<String>, RequestError<io::Error>> {
+    let content = read_to_string(&path).await.map_err(|e| {
         PartialErrorResponse::new(format!(
-            "Failed to open file {}: {}",
+            "Failed to read file {}: {}",
             path.as_ref().display(),
             e
         ))
     })?;
-    let lines = BufReader::new(file).lines();
-    let mut lines = LinesStream::new(lines).skip(line_number - 1);
-    if let Some(line) = lines.next().await {
-        let line = line.map_err(|e| {
-            PartialErrorResponse::new(format!(
-                "Failed to read file {}: {}",
-                path.as_ref().display(),
-                e
-            ))
-        })?;
-
-        let line = parse_line(parser, &line, false);
-        if let Line::FunctionCall { name, .. } = line {
-            return Ok(Some(name));
-        }
-    }
-    Ok(None)
+    Ok(content
+        .lines()
+        .nth(line_number - 1)
+        .map(ToString::to_string))
 }
 
 struct Config<'l> {
     datapack: &'l Path,
     datapack_name: &'l str,
     function: ResourceLocation,
-    minecraft_world_dir: &'l Path,
-    minecraft_log_file: &'l Path,
+    exclude: Vec<String>,
+    keep_debug_datapack: bool,
+    minecraft_world_dir: PathBuf,
+    minecraft_log_file: PathBuf,
+    run_to_completion_on_terminate: bool,
+    run_to_completion_timeout: Duration,
+    trace_file: Option<&'l Path>,
+    replay_file: Option<&'l Path>,
+    connection_watchdog: Option<Duration>,
+    connection_timeout: Option<Duration>,
+    connection_type: ConnectionType,
+    breakpoint_comments: bool,
+    stop_on_entry: bool,
+    on_suspend: OnSuspend,
+    suspend_schedules: SuspendSchedules,
+    max_reconnect: Duration,
+    forward_log_errors: bool,
+    additional_datapacks: Vec<PathBuf>,
+    /// The program and first arguments of the generator command, see [GeneratorCommand].
+    generator_command: Option<Vec<String>>,
 }
 
 fn get_config(args: &LaunchRequestArguments) -> Result<Config, PartialErrorResponse> {
@@ -1114,41 +4551,350 @@ fn get_config(args: &LaunchRequestArguments) -> Result<Config, PartialErrorRespo
         .to_str()
         .unwrap(); // Path is known to be UTF-8
 
-    let minecraft_world_dir = get_path(&args, "minecraftWorldDir")?;
-    let minecraft_log_file = get_path(&args, "minecraftLogFile")?;
+    let minecraft_world_dir = match get_optional_path(&args, "minecraftWorldDir")? {
+        Some(minecraft_world_dir) => minecraft_world_dir.to_path_buf(),
+        None => find_minecraft_world(datapack).ok_or_else(|| {
+            let saves_dir = minecraft_saves_dir()
+                .map(|it| it.display().to_string())
+                .unwrap_or_else(|| "the saves directory of Minecraft".to_string());
+            PartialErrorResponse::new(format!(
+                "Missing attribute 'minecraftWorldDir' and no world with the datapack '{}' was \
+                found in {}. Set 'minecraftWorldDir' in the launch configuration to the directory \
+                of your Minecraft world.",
+                datapack_name, saves_dir
+            ))
+        })?,
+    };
+    let minecraft_log_file = match get_optional_path(&args, "minecraftLogFile")? {
+        Some(minecraft_log_file) => minecraft_log_file.to_path_buf(),
+        None => find_minecraft_log_file(&minecraft_world_dir).ok_or_else(|| {
+            PartialErrorResponse::new(format!(
+                "Missing attribute 'minecraftLogFile' and no logs/latest.log was found two or \
+                three directories above {}. Set 'minecraftLogFile' in the launch configuration to \
+                the log file of Minecraft.",
+                minecraft_world_dir.display()
+            ))
+        })?,
+    };
+    let run_to_completion_on_terminate =
+        get_bool(&args, "runToCompletionOnTerminate")?.unwrap_or(false);
+    let run_to_completion_timeout = get_u64(&args, RUN_TO_COMPLETION_TIMEOUT_ATTRIBUTE)?
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_RUN_TO_COMPLETION_TIMEOUT);
+    let keep_debug_datapack = get_bool(&args, "keepDebugDatapack")?.unwrap_or(false);
+    let exclude = get_string_array(&args, "exclude")?.unwrap_or_default();
+    let trace_file = get_optional_path(&args, TRACE_FILE_ATTRIBUTE)?;
+    let replay_file = get_optional_path(&args, REPLAY_FILE_ATTRIBUTE)?;
+    let connection_watchdog = match get_u64(&args, CONNECTION_WATCHDOG_ATTRIBUTE)? {
+        Some(0) => None,
+        Some(millis) => Some(Duration::from_millis(millis)),
+        None => Some(DEFAULT_CONNECTION_WATCHDOG),
+    };
+    let connection_timeout = get_u64(&args, CONNECTION_TIMEOUT_ATTRIBUTE)?
+        .filter(|millis| *millis != 0)
+        .map(Duration::from_millis);
+    let connection_type = get_connection_type(&args)?;
+    let breakpoint_comments =
+        !get_bool(&args, IGNORE_BREAKPOINT_COMMENTS_ATTRIBUTE)?.unwrap_or(false);
+    let stop_on_entry = get_bool(&args, STOP_ON_ENTRY_ATTRIBUTE)?.unwrap_or(false);
+    let on_suspend = get_on_suspend(&args)?;
+    let suspend_schedules = get_suspend_schedules(args)?;
+    let max_reconnect = get_u64(&args, MAX_RECONNECT_ATTRIBUTE)?
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_MAX_RECONNECT);
+    let forward_log_errors =
+        get_bool(&args, FORWARD_MINECRAFT_LOG_ERRORS_ATTRIBUTE)?.unwrap_or(true);
+    let additional_datapacks = get_additional_datapacks(&args, datapack)?;
+    let generator_command = get_string_array(&args, GENERATOR_COMMAND_ATTRIBUTE)?;
+    if let Some(generator_command) = &generator_command {
+        if generator_command.is_empty() {
+            return Err(PartialErrorResponse::new(format!(
+                "Attribute '{}' must not be empty",
+                GENERATOR_COMMAND_ATTRIBUTE
+            )));
+        }
+        if !additional_datapacks.is_empty() {
+            return Err(PartialErrorResponse::new(format!(
+                "Attribute '{}' can't be used with '{}'",
+                GENERATOR_COMMAND_ATTRIBUTE, ADDITIONAL_DATAPACKS_ATTRIBUTE
+            )));
+        }
+    }
     Ok(Config {
         datapack,
         datapack_name,
         function,
+        exclude,
+        keep_debug_datapack,
         minecraft_world_dir,
         minecraft_log_file,
+        run_to_completion_on_terminate,
+        run_to_completion_timeout,
+        trace_file,
+        replay_file,
+        connection_watchdog,
+        connection_timeout,
+        connection_type,
+        breakpoint_comments,
+        stop_on_entry,
+        on_suspend,
+        suspend_schedules,
+        max_reconnect,
+        forward_log_errors,
+        additional_datapacks,
+        generator_command,
     })
 }
 
+fn get_additional_datapacks(
+    args: &LaunchRequestArguments,
+    datapack: &Path,
+) -> Result<Vec<PathBuf>, PartialErrorResponse> {
+    let additional_datapacks = get_string_array(args, ADDITIONAL_DATAPACKS_ATTRIBUTE)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    for additional_datapack in &additional_datapacks {
+        if !additional_datapack.join("data").is_dir() && !is_archive(additional_datapack) {
+            return Err(PartialErrorResponse::new(format!(
+                "Attribute '{}' contains {}, which is not a datapack",
+                ADDITIONAL_DATAPACKS_ATTRIBUTE,
+                additional_datapack.display()
+            )));
+        }
+        if additional_datapack == datapack {
+            return Err(PartialErrorResponse::new(format!(
+                "Attribute '{}' contains the datapack of attribute 'program'",
+                ADDITIONAL_DATAPACKS_ATTRIBUTE
+            )));
+        }
+    }
+    Ok(additional_datapacks)
+}
+
+fn get_on_suspend(args: &LaunchRequestArguments) -> Result<OnSuspend, PartialErrorResponse> {
+    let on_suspend = args
+        .additional_attributes
+        .get(ON_SUSPEND_ATTRIBUTE)
+        .map(|value| {
+            value.as_str().ok_or_else(|| {
+                PartialErrorResponse::new(format!(
+                    "Attribute '{}' is not of type string",
+                    ON_SUSPEND_ATTRIBUTE
+                ))
+            })
+        })
+        .transpose()?;
+    match on_suspend {
+        None | Some("freezeSession") => Ok(OnSuspend::FreezeSession),
+        Some("freezeTick") => Ok(OnSuspend::FreezeTick),
+        Some("disableOtherPacks") => {
+            let datapacks = get_string_array(args, DISABLED_DATAPACKS_ATTRIBUTE)?
+                .filter(|datapacks| !datapacks.is_empty())
+                .ok_or_else(|| {
+                    PartialErrorResponse::new(format!(
+                        "Missing attribute '{}', it is required if '{}' is disableOtherPacks",
+                        DISABLED_DATAPACKS_ATTRIBUTE, ON_SUSPEND_ATTRIBUTE
+                    ))
+                })?;
+            Ok(OnSuspend::DisableOtherPacks(datapacks))
+        }
+        Some(on_suspend) => Err(PartialErrorResponse::new(format!(
+            "Attribute '{}' has an unknown value '{}', expected freezeSession, freezeTick or \
+            disableOtherPacks",
+            ON_SUSPEND_ATTRIBUTE, on_suspend
+        ))),
+    }
+}
+
+fn get_suspend_schedules(
+    args: &LaunchRequestArguments,
+) -> Result<SuspendSchedules, PartialErrorResponse> {
+    let suspend_schedules = args
+        .additional_attributes
+        .get(SUSPEND_SCHEDULES_ATTRIBUTE)
+        .map(|value| {
+            value.as_str().ok_or_else(|| {
+                PartialErrorResponse::new(format!(
+                    "Attribute '{}' is not of type string",
+                    SUSPEND_SCHEDULES_ATTRIBUTE
+                ))
+            })
+        })
+        .transpose()?;
+    match suspend_schedules {
+        None => Ok(SuspendSchedules::default()),
+        Some(suspend_schedules) => suspend_schedules.parse().map_err(|()| {
+            PartialErrorResponse::new(format!(
+                "Attribute '{}' has an unknown value '{}', expected all, callStackOnly or none",
+                SUSPEND_SCHEDULES_ATTRIBUTE, suspend_schedules
+            ))
+        }),
+    }
+}
+
+fn get_connection_type(
+    args: &LaunchRequestArguments,
+) -> Result<ConnectionType, PartialErrorResponse> {
+    let connection_type = args
+        .additional_attributes
+        .get(CONNECTION_TYPE_ATTRIBUTE)
+        .map(|value| {
+            value.as_str().ok_or_else(|| {
+                PartialErrorResponse::new(format!(
+                    "Attribute '{}' is not of type string",
+                    CONNECTION_TYPE_ATTRIBUTE
+                ))
+            })
+        })
+        .transpose()?;
+    match connection_type {
+        None | Some("logFile") => Ok(ConnectionType::LogFile),
+        Some("socket") => {
+            let port = get_port(args, SOCKET_PORT_ATTRIBUTE)?.ok_or_else(|| {
+                PartialErrorResponse::new(format!(
+                    "Missing attribute '{}', it is required for the connection type socket",
+                    SOCKET_PORT_ATTRIBUTE
+                ))
+            })?;
+            Ok(ConnectionType::Socket { port })
+        }
+        #[cfg(unix)]
+        Some("pipe") => {
+            let path = get_optional_path(args, PIPE_PATH_ATTRIBUTE)?
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| std::env::temp_dir().join(DEFAULT_PIPE_FILE_NAME));
+            Ok(ConnectionType::Pipe { path })
+        }
+        #[cfg(not(unix))]
+        Some("pipe") => Err(PartialErrorResponse::new(
+            "The connection type pipe is only supported on Unix".to_string(),
+        )),
+        Some("rcon") => {
+            let port = get_port(args, RCON_PORT_ATTRIBUTE)?.unwrap_or(DEFAULT_RCON_PORT);
+            let password = get_string(args, RCON_PASSWORD_ATTRIBUTE)?
+                .ok_or_else(|| {
+                    PartialErrorResponse::new(format!(
+                        "Missing attribute '{}', it is required for the connection type rcon",
+                        RCON_PASSWORD_ATTRIBUTE
+                    ))
+                })?
+                .to_string();
+            Ok(ConnectionType::Rcon { port, password })
+        }
+        Some(other) => Err(PartialErrorResponse::new(format!(
+            "Attribute '{}' must be 'logFile', 'socket', 'pipe' or 'rcon', but was '{}'",
+            CONNECTION_TYPE_ATTRIBUTE, other
+        ))),
+    }
+}
+
+fn get_port(args: &LaunchRequestArguments, key: &str) -> Result<Option<u16>, PartialErrorResponse> {
+    get_u64(args, key)?
+        .map(|port| {
+            port.try_into().map_err(|_| {
+                PartialErrorResponse::new(format!("Attribute '{}' is not a valid port", key))
+            })
+        })
+        .transpose()
+}
+
+fn get_string<'a>(
+    args: &'a LaunchRequestArguments,
+    key: &str,
+) -> Result<Option<&'a str>, PartialErrorResponse> {
+    args.additional_attributes
+        .get(key)
+        .map(|value| {
+            value.as_str().ok_or_else(|| {
+                PartialErrorResponse::new(format!("Attribute '{}' is not of type string", key))
+            })
+        })
+        .transpose()
+}
+
 fn get_path<'a>(
     args: &'a LaunchRequestArguments,
     key: &str,
 ) -> Result<&'a Path, PartialErrorResponse> {
-    let value = args
-        .additional_attributes
+    get_optional_path(args, key)?
+        .ok_or_else(|| PartialErrorResponse::new(format!("Missing attribute '{}'", key)))
+}
+
+fn get_optional_path<'a>(
+    args: &'a LaunchRequestArguments,
+    key: &str,
+) -> Result<Option<&'a Path>, PartialErrorResponse> {
+    args.additional_attributes
         .get(key)
-        .ok_or_else(|| PartialErrorResponse::new(format!("Missing attribute '{}'", key)))?
-        .as_str()
-        .ok_or_else(|| {
-            PartialErrorResponse::new(format!("Attribute '{}' is not of type string", key))
-        })?;
-    let value = Path::new(value);
-    Ok(value)
+        .map(|value| {
+            value.as_str().map(Path::new).ok_or_else(|| {
+                PartialErrorResponse::new(format!("Attribute '{}' is not of type string", key))
+            })
+        })
+        .transpose()
+}
+
+fn get_bool(
+    args: &LaunchRequestArguments,
+    key: &str,
+) -> Result<Option<bool>, PartialErrorResponse> {
+    args.additional_attributes
+        .get(key)
+        .map(|value| {
+            value.as_bool().ok_or_else(|| {
+                PartialErrorResponse::new(format!("Attribute '{}' is not of type boolean", key))
+            })
+        })
+        .transpose()
+}
+
+fn get_u64(args: &LaunchRequestArguments, key: &str) -> Result<Option<u64>, PartialErrorResponse> {
+    args.additional_attributes
+        .get(key)
+        .map(|value| {
+            value.as_u64().ok_or_else(|| {
+                PartialErrorResponse::new(format!(
+                    "Attribute '{}' is not a non-negative integer",
+                    key
+                ))
+            })
+        })
+        .transpose()
+}
+
+fn get_string_array(
+    args: &LaunchRequestArguments,
+    key: &str,
+) -> Result<Option<Vec<String>>, PartialErrorResponse> {
+    args.additional_attributes
+        .get(key)
+        .map(|value| {
+            value
+                .as_array()
+                .and_then(|values| {
+                    values
+                        .iter()
+                        .map(|value| value.as_str().map(String::from))
+                        .collect()
+                })
+                .ok_or_else(|| {
+                    PartialErrorResponse::new(format!(
+                        "Attribute '{}' is not an array of strings",
+                        key
+                    ))
+                })
+        })
+        .transpose()
 }
 
-fn create_selected_entity_scores_scope(
-    mc_session: &mut MinecraftSession,
-    args: ScopesRequestArguments,
-) -> Scope {
-    let kind = ScopeKind::SelectedEntityScores;
+fn create_scope(mc_session: &mut MinecraftSession, frame_id: i32, kind: ScopeKind) -> Scope {
     mc_session.scopes.push(ScopeReference {
-        frame_id: args.frame_id,
+        frame_id,
         kind,
+        nbt_path: Vec::new(),
+        objective: None,
     });
     let variables_reference = mc_session.scopes.len();
     Scope::builder()
@@ -1158,21 +4904,76 @@ fn create_selected_entity_scores_scope(
         .build()
 }
 
+fn telemetry_event(name: &str, data: Value) -> OutputEventBody {
+    OutputEventBody::builder()
+        .category(OutputCategory::Telemetry)
+        .output(name.to_string())
+        .data(Some(data))
+        .build()
+}
+
+fn source(path: &str) -> Source {
+    Source::builder().path(Some(path.to_string())).build()
+}
+
+fn loaded_source(function: &ResourceLocation, path: &Path) -> Source {
+    Source::builder()
+        .name(Some(function.to_string()))
+        .path(Some(path.display().to_string()))
+        .origin(Some("datapack".to_string()))
+        .build()
+}
+
+fn loaded_source_event(
+    reason: LoadedSourceEventReason,
+    function: &ResourceLocation,
+    path: &Path,
+) -> LoadedSourceEventBody {
+    LoadedSourceEventBody::builder()
+        .reason(reason)
+        .source(loaded_source(function, path))
+        .build()
+}
+
+/// Returns whether a breakpoint can be set in the line and the column after the execute prefix if
+/// the line calls a function with an execute prefix. A `# breakpoint` comment is a valid location
+/// if `breakpoint_comments` is true.
 async fn verify_breakpoint(
     parser: &CommandParser,
     path: impl AsRef<Path>,
     line_number: usize,
-) -> io::Result<bool> {
-    let file = File::open(path).await?;
-    let lines = BufReader::new(file).lines();
-    if let Some(result) = LinesStream::new(lines).skip(line_number - 1).next().await {
-        let line = result?;
-        let line = parse_line(parser, &line, false);
-        return Ok(is_command(line));
+    breakpoint_comments: bool,
+) -> io::Result<(bool, Option<usize>)> {
+    let content = read_to_string(path).await?;
+    if let Some(line) = content.lines().nth(line_number - 1) {
+        let parsed = parse_line(parser, line, breakpoint_comments);
+        if matches!(parsed, Line::Breakpoint) {
+            return Ok((true, None));
+        }
+        let after_execute_column = match &parsed {
+            Line::FunctionCall { column_index, .. } => {
+                find_run_index(&line, *column_index).map(|run_index| run_index + 1)
+            }
+            _ => None,
+        };
+        return Ok((is_command(parsed), after_execute_column));
     } else {
-        Ok(false)
+        Ok((false, None))
     }
 }
+/// Creates events that remove the `# breakpoint` comments from the client.
+fn get_removed_comment_events(comments: Vec<(i32, usize)>) -> Vec<BreakpointEventBody> {
+    comments
+        .into_iter()
+        .map(|(id, _line_number)| {
+            BreakpointEventBody::builder()
+                .reason(BreakpointEventReason::Removed)
+                .breakpoint(Breakpoint::builder().id(Some(id)).verified(false).build())
+                .build()
+        })
+        .collect()
+}
+
 fn get_move_breakpoint_commands(
     old_positions: impl ExactSizeIterator<Item = BreakpointPosition>,
     new_positions: impl ExactSizeIterator<Item = BreakpointPosition>,
@@ -1209,3 +5010,6 @@ fn get_move_breakpoint_commands(
 fn is_command(line: Line) -> bool {
     !matches!(line, Line::Empty | Line::Comment | Line::Breakpoint)
 }
+
+#[cfg(test)]
+mod tests;
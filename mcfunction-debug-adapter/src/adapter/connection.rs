@@ -0,0 +1,222 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use futures::{stream::BoxStream, StreamExt};
+use log::warn;
+use minect::{log::LogEvent, Command, MinecraftConnection};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, TcpStream},
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// The interface the debug adapter uses to execute commands in Minecraft and to receive the output
+/// of commands.
+pub(crate) trait Connection: Send + Sync {
+    /// Returns a stream of all [LogEvent]s. To remove the listener simply drop the stream.
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent>;
+
+    /// Returns a stream of the [LogEvent]s whose executor is `name`. To remove the listener simply
+    /// drop the stream.
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent>;
+
+    fn execute_commands(&mut self, commands: Vec<Command>) -> io::Result<()>;
+}
+
+/// Executes commands with structure files in the world directory and reads the output of commands
+/// from Minecraft's log file.
+impl Connection for MinecraftConnection {
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent> {
+        MinecraftConnection::add_listener(self).boxed()
+    }
+
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent> {
+        MinecraftConnection::add_named_listener(self, name.to_string()).boxed()
+    }
+
+    fn execute_commands(&mut self, commands: Vec<Command>) -> io::Result<()> {
+        MinecraftConnection::execute_commands(self, commands)?;
+        Ok(())
+    }
+}
+
+/// How the debug adapter connects to Minecraft, configured by the launch attribute
+/// `connectionType`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ConnectionType {
+    /// Commands are executed with structure files and their output is read from the log file.
+    LogFile,
+    /// Commands and their output are exchanged with a companion mod listening on
+    /// `localhost:<port>`, see [SocketConnection].
+    Socket { port: u16 },
+    /// Commands are executed with structure files and their output is read from a named pipe, see
+    /// [PipeConnection].
+    #[cfg(unix)]
+    Pipe { path: std::path::PathBuf },
+    /// Commands are executed through Minecraft's RCON server on `localhost:<port>` and their output
+    /// is read from the log file, see [RconConnection].
+    Rcon { port: u16, password: String },
+}
+
+/// A connection to a companion mod or socket bridge in Minecraft that avoids the latency of
+/// tailing the log file. Both directions use newline delimited JSON. Every command is sent as:
+/// ```json
+/// {"command": "say hi", "name": null}
+/// ```
+/// where `name` is the optional custom name of the executor. The mod sends the output of every
+/// command as:
+/// ```json
+/// {"executor": "mcfunction-debugger", "output": "Added tag 'exited' to mcfunction-debugger"}
+/// ```
+pub(crate) struct SocketConnection {
+    stream: TcpStream,
+    listeners: Arc<Mutex<Vec<Listener>>>,
+}
+
+pub(crate) struct Listener {
+    name: Option<String>,
+    sender: UnboundedSender<LogEvent>,
+}
+
+#[derive(Serialize)]
+struct SocketCommand<'l> {
+    command: &'l str,
+    name: Option<&'l str>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SocketEvent {
+    executor: String,
+    output: String,
+}
+impl SocketEvent {
+    /// [LogEvent]s can only be created by parsing a line of Minecraft's log file, so the event is
+    /// converted to such a line first.
+    pub(crate) fn into_log_event(self) -> Option<LogEvent> {
+        format!(
+            "[00:00:00] [Server thread/INFO]: [{}: {}]",
+            self.executor, self.output
+        )
+        .parse()
+        .ok()
+    }
+}
+
+impl SocketConnection {
+    /// Connects to `localhost:<port>`. Events are read on a separate thread until the connection is
+    /// dropped or closed by Minecraft.
+    pub(crate) fn connect(port: u16) -> io::Result<SocketConnection> {
+        let stream = TcpStream::connect(("localhost", port))?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let listeners = Arc::new(Mutex::new(Vec::<Listener>::new()));
+        let reader_listeners = listeners.clone();
+        std::thread::spawn(move || {
+            for line in reader.lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                match parse_event(&line) {
+                    Some(event) => dispatch(&reader_listeners, event),
+                    None => warn!("Ignoring invalid event from Minecraft: {}", line),
+                }
+            }
+        });
+        Ok(SocketConnection { stream, listeners })
+    }
+}
+
+/// Parses a line of newline delimited JSON sent by a companion mod or process.
+fn parse_event(line: &str) -> Option<LogEvent> {
+    serde_json::from_str::<SocketEvent>(line)
+        .ok()
+        .and_then(SocketEvent::into_log_event)
+}
+
+pub(crate) fn add_listener(
+    listeners: &Mutex<Vec<Listener>>,
+    name: Option<String>,
+) -> BoxStream<'static, LogEvent> {
+    let (sender, receiver) = unbounded_channel();
+    listeners.lock().unwrap().push(Listener { name, sender });
+    UnboundedReceiverStream::new(receiver).boxed()
+}
+
+/// Sends the event to all matching listeners and removes the listeners whose stream was dropped.
+pub(crate) fn dispatch(listeners: &Mutex<Vec<Listener>>, event: LogEvent) {
+    listeners.lock().unwrap().retain(|listener| {
+        if listener.sender.is_closed() {
+            return false;
+        }
+        if listener
+            .name
+            .as_ref()
+            .map_or(true, |it| *it == event.executor)
+        {
+            let _ = listener.sender.send(event.clone());
+        }
+        true
+    });
+}
+
+impl Connection for SocketConnection {
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, None)
+    }
+
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, Some(name.to_string()))
+    }
+
+    fn execute_commands(&mut self, commands: Vec<Command>) -> io::Result<()> {
+        let mut lines = String::new();
+        for command in &commands {
+            let command = SocketCommand {
+                command: command.get_command(),
+                name: command.get_name(),
+            };
+            lines.push_str(&serde_json::to_string(&command)?);
+            lines.push('\n');
+        }
+        self.stream.write_all(lines.as_bytes())?;
+        self.stream.flush()
+    }
+}
+
+impl Drop for SocketConnection {
+    fn drop(&mut self) {
+        // Stops the thread that reads events
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(unix)]
+mod pipe;
+#[cfg(unix)]
+pub(crate) use pipe::PipeConnection;
+mod rcon;
+pub(crate) use rcon::{RconConnection, DEFAULT_RCON_PORT};
+mod reconnect;
+pub(crate) use reconnect::{Connect, ReconnectEvent, ReconnectingConnection};
+mod recording;
+pub(crate) use recording::RecordingMinecraftConnection;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,148 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::adapter::connection::{add_listener, dispatch, parse_event, Connection, Listener};
+use futures::stream::BoxStream;
+use log::warn;
+use minect::{log::LogEvent, Command, MinecraftConnection};
+use std::{
+    ffi::CString,
+    fs::{remove_file, File, OpenOptions},
+    io::{self, BufRead, BufReader},
+    os::unix::{ffi::OsStrExt, fs::FileTypeExt, fs::OpenOptionsExt},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// A connection that reads the output of commands from a named pipe (FIFO) instead of Minecraft's
+/// log file, which avoids the latency of polling the log file. The pipe is written by a companion
+/// process in the same newline delimited JSON format as a
+/// [SocketConnection](super::SocketConnection). Commands are still executed with structure files.
+///
+/// When the write end of the pipe is closed, for example because the companion process was
+/// restarted, the pipe is opened again and waits for the next writer.
+pub(crate) struct PipeConnection {
+    connection: MinecraftConnection,
+    path: PathBuf,
+    listeners: Arc<Mutex<Vec<Listener>>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl PipeConnection {
+    /// Creates the pipe at `path` unless it already exists and starts reading events on a separate
+    /// thread.
+    pub(crate) fn open(
+        connection: MinecraftConnection,
+        path: impl Into<PathBuf>,
+    ) -> io::Result<PipeConnection> {
+        let path = path.into();
+        create_fifo(&path)?;
+        let listeners = Arc::new(Mutex::new(Vec::<Listener>::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let reader_path = path.clone();
+        let reader_listeners = listeners.clone();
+        let reader_closed = closed.clone();
+        std::thread::spawn(move || {
+            while !reader_closed.load(Ordering::SeqCst) {
+                // Blocks until a writer opens the pipe
+                let file = match File::open(&reader_path) {
+                    Ok(file) => file,
+                    Err(e) => {
+                        warn!("Failed to open pipe {}: {}", reader_path.display(), e);
+                        break;
+                    }
+                };
+                // Reading ends when all writers closed the pipe
+                for line in BufReader::new(file).lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(_) => break,
+                    };
+                    match parse_event(&line) {
+                        Some(event) => dispatch(&reader_listeners, event),
+                        None => warn!("Ignoring invalid event from Minecraft: {}", line),
+                    }
+                }
+            }
+        });
+
+        Ok(PipeConnection {
+            connection,
+            path,
+            listeners,
+            closed,
+        })
+    }
+}
+
+/// Creates a FIFO at `path`. An existing FIFO is reused, any other existing file is an error.
+fn create_fifo(path: &Path) -> io::Result<()> {
+    match path.metadata() {
+        Ok(metadata) if metadata.file_type().is_fifo() => return Ok(()),
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists, but is not a named pipe", path.display()),
+            ))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    // Safe, because the path is a valid null terminated string
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+impl Connection for PipeConnection {
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, None)
+    }
+
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, Some(name.to_string()))
+    }
+
+    fn execute_commands(&mut self, commands: Vec<Command>) -> io::Result<()> {
+        self.connection.execute_commands(commands)?;
+        Ok(())
+    }
+}
+
+impl Drop for PipeConnection {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        // Opening the write end unblocks the thread if it waits for a writer. Without a waiting
+        // reader this fails, which is fine.
+        let _ = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&self.path);
+        let _ = remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,117 @@
+use super::*;
+use futures::StreamExt;
+use std::{fs::create_dir_all, process::Command as Process, time::Duration};
+use tokio::time::timeout;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debug-adapter-pipe-test")
+        .join(name);
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn open_pipe(dir: &Path) -> PipeConnection {
+    let connection = MinecraftConnection::builder("mcfunction-debugger", dir).build();
+    PipeConnection::open(connection, dir.join("pipe")).unwrap()
+}
+
+/// Spawns a process that writes the lines to the pipe and closes it.
+fn spawn_writer(pipe: &Path, lines: &[&str]) -> std::process::Child {
+    Process::new("sh")
+        .arg("-c")
+        .arg(r#"for line in "$@"; do printf '%s\n' "$line"; done > "$0""#)
+        .arg(pipe)
+        .args(lines)
+        .spawn()
+        .unwrap()
+}
+
+async fn next_output(events: &mut BoxStream<'static, LogEvent>) -> String {
+    timeout(Duration::from_secs(5), events.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .output
+}
+
+#[tokio::test]
+async fn test_pipe_connection_receives_events_in_order() {
+    // given:
+    let dir = test_dir("test_pipe_connection_receives_events_in_order");
+    let mut connection = open_pipe(&dir);
+    let mut events = connection.add_named_listener("mcfunction-debugger");
+
+    // when:
+    let mut writer = spawn_writer(
+        &dir.join("pipe"),
+        &[
+            r#"{"executor": "mcfunction-debugger", "output": "Added tag 'first' to mcfunction-debugger"}"#,
+            r#"{"executor": "other", "output": "Added tag 'other' to other"}"#,
+            r#"{"executor": "mcfunction-debugger", "output": "Added tag 'second' to mcfunction-debugger"}"#,
+        ],
+    );
+
+    // then:
+    assert_eq!(
+        next_output(&mut events).await,
+        "Added tag 'first' to mcfunction-debugger"
+    );
+    assert_eq!(
+        next_output(&mut events).await,
+        "Added tag 'second' to mcfunction-debugger"
+    );
+    assert!(writer.wait().unwrap().success());
+}
+
+#[tokio::test]
+async fn test_pipe_connection_reopens_after_writer_closed() {
+    // given:
+    let dir = test_dir("test_pipe_connection_reopens_after_writer_closed");
+    let mut connection = open_pipe(&dir);
+    let mut events = connection.add_listener();
+    let mut writer = spawn_writer(
+        &dir.join("pipe"),
+        &[r#"{"executor": "first", "output": "Summoned new first"}"#],
+    );
+    assert_eq!(next_output(&mut events).await, "Summoned new first");
+    assert!(writer.wait().unwrap().success());
+
+    // when:
+    let mut writer = spawn_writer(
+        &dir.join("pipe"),
+        &[r#"{"executor": "second", "output": "Summoned new second"}"#],
+    );
+
+    // then:
+    assert_eq!(next_output(&mut events).await, "Summoned new second");
+    assert!(writer.wait().unwrap().success());
+}
+
+#[test]
+fn test_pipe_connection_removes_pipe_when_dropped() {
+    // given:
+    let dir = test_dir("test_pipe_connection_removes_pipe_when_dropped");
+    let connection = open_pipe(&dir);
+    assert!(dir.join("pipe").metadata().unwrap().file_type().is_fifo());
+
+    // when:
+    drop(connection);
+
+    // then:
+    assert!(!dir.join("pipe").exists());
+}
+
+#[test]
+fn test_create_fifo_fails_for_regular_file() {
+    // given:
+    let dir = test_dir("test_create_fifo_fails_for_regular_file");
+    let path = dir.join("regular");
+    std::fs::write(&path, "").unwrap();
+
+    // when:
+    let actual = create_fifo(&path);
+
+    // then:
+    assert_eq!(actual.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+}
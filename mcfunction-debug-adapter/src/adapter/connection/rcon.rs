@@ -0,0 +1,199 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::adapter::connection::Connection;
+use futures::{stream::BoxStream, StreamExt};
+use log::trace;
+use minect::{
+    command::{enable_logging_command, named_logged_command, reset_logging_command},
+    log::LogEvent,
+    Command, MinecraftConnection,
+};
+use std::{
+    io::{self, Read, Write},
+    iter::once,
+    net::{Shutdown, TcpStream},
+    time::Duration,
+};
+
+/// The port of Minecraft's RCON server if `rcon.port` is not set in `server.properties`.
+pub(crate) const DEFAULT_RCON_PORT: u16 = 25575;
+
+/// The packet type of a login request. The response has the type [COMMAND].
+const LOGIN: i32 = 3;
+const COMMAND: i32 = 2;
+const RESPONSE: i32 = 0;
+
+/// Minecraft rejects requests whose body is longer than this.
+const MAX_REQUEST_BODY_LEN: usize = 1446;
+
+/// Minecraft splits responses into packets with a body of at most this length.
+const MAX_RESPONSE_BODY_LEN: usize = 4096;
+
+/// How long to wait for a response of Minecraft's RCON server.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A connection that executes commands through Minecraft's RCON server instead of structure files.
+/// The output of commands is still read from Minecraft's log file.
+///
+/// Every command is executed by a command block minecart with the name of the command, so that its
+/// output is logged under that name and in the same order as with structure files. This requires
+/// the connection of minect to be installed in the world.
+pub(crate) struct RconConnection {
+    connection: MinecraftConnection,
+    stream: TcpStream,
+    next_id: i32,
+}
+
+#[derive(Debug, PartialEq)]
+struct Packet {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+impl RconConnection {
+    /// Connects to the RCON server on `localhost:<port>` and authenticates with `password`.
+    pub(crate) fn connect(
+        connection: MinecraftConnection,
+        port: u16,
+        password: &str,
+    ) -> io::Result<RconConnection> {
+        let stream = TcpStream::connect(("localhost", port))?;
+        stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+        let mut rcon = RconConnection {
+            connection,
+            stream,
+            next_id: 1,
+        };
+        let id = rcon.send(LOGIN, password)?;
+        let response = read_packet(&mut rcon.stream)?;
+        if response.id == -1 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "RCON authentication failed, check the password",
+            ));
+        }
+        if response.id != id || response.kind != COMMAND {
+            return Err(invalid_data("Unexpected response to RCON login"));
+        }
+        Ok(rcon)
+    }
+
+    /// Executes a command and returns the response of Minecraft.
+    pub(crate) fn execute(&mut self, command: &str) -> io::Result<String> {
+        let id = self.send(COMMAND, command)?;
+        loop {
+            let response = read_packet(&mut self.stream)?;
+            // Skips the remaining packets of responses that were split into multiple packets
+            if response.id == id && response.kind == RESPONSE {
+                return Ok(response.body);
+            }
+        }
+    }
+
+    fn send(&mut self, kind: i32, body: &str) -> io::Result<i32> {
+        let id = self.next_id;
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        write_packet(&mut self.stream, id, kind, body)?;
+        Ok(id)
+    }
+}
+
+fn write_packet(writer: &mut impl Write, id: i32, kind: i32, body: &str) -> io::Result<()> {
+    if body.len() > MAX_REQUEST_BODY_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "RCON requests are limited to {} bytes, but got {}",
+                MAX_REQUEST_BODY_LEN,
+                body.len()
+            ),
+        ));
+    }
+    // The length does not include itself, the body is followed by two null bytes
+    let length = 4 + 4 + body.len() as i32 + 2;
+    let mut packet = Vec::with_capacity(4 + length as usize);
+    packet.extend_from_slice(&length.to_le_bytes());
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&kind.to_le_bytes());
+    packet.extend_from_slice(body.as_bytes());
+    packet.extend_from_slice(&[0, 0]);
+    writer.write_all(&packet)?;
+    writer.flush()
+}
+
+fn read_packet(reader: &mut impl Read) -> io::Result<Packet> {
+    let length = read_i32(reader)?;
+    if length < 10 || length as usize > 10 + MAX_RESPONSE_BODY_LEN {
+        return Err(invalid_data(format!(
+            "Invalid RCON packet length {}",
+            length
+        )));
+    }
+    let id = read_i32(reader)?;
+    let kind = read_i32(reader)?;
+    let mut body = vec![0; length as usize - 8];
+    reader.read_exact(&mut body)?;
+    body.truncate(body.len() - 2);
+    let body = String::from_utf8(body).map_err(invalid_data)?;
+    Ok(Packet { id, kind, body })
+}
+
+fn read_i32(reader: &mut impl Read) -> io::Result<i32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(i32::from_le_bytes(bytes))
+}
+
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+impl Connection for RconConnection {
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent> {
+        self.connection.add_listener().boxed()
+    }
+
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent> {
+        self.connection.add_named_listener(name.to_string()).boxed()
+    }
+
+    fn execute_commands(&mut self, commands: Vec<Command>) -> io::Result<()> {
+        let commands = once(Command::new(enable_logging_command()))
+            .chain(commands)
+            .chain(once(Command::new(reset_logging_command())));
+        for command in commands {
+            // Command blocks of structure files are named @ by default
+            let name = command.get_name().unwrap_or("@");
+            let command = named_logged_command(name, command.get_command());
+            let response = self.execute(&command)?;
+            trace!("RCON response: {}", response);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RconConnection {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod tests;
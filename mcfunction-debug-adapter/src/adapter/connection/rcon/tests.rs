@@ -0,0 +1,162 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::installer::create_connection;
+use std::{net::TcpListener, thread::JoinHandle};
+
+/// Starts a server that accepts a single RCON client with the given `password` and answers every
+/// command with the next of `responses`. Returns the port and the commands received.
+fn start_mock_server(
+    password: &'static str,
+    responses: &'static [&'static str],
+) -> (u16, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind(("localhost", 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let login = read_packet(&mut stream).unwrap();
+        assert_eq!(login.kind, LOGIN);
+        if login.body != password {
+            write_packet(&mut stream, -1, COMMAND, "").unwrap();
+            return Vec::new();
+        }
+        write_packet(&mut stream, login.id, COMMAND, "").unwrap();
+
+        let mut commands = Vec::new();
+        for response in responses {
+            let command = match read_packet(&mut stream) {
+                Ok(command) => command,
+                Err(_) => break,
+            };
+            assert_eq!(command.kind, COMMAND);
+            write_packet(&mut stream, command.id, RESPONSE, response).unwrap();
+            commands.push(command.body);
+        }
+        commands
+    });
+    (port, server)
+}
+
+fn log_connection() -> MinecraftConnection {
+    let dir = std::env::temp_dir().join("mcfunction-debugger-rcon-test");
+    create_connection(&dir, dir.join("latest.log"))
+}
+
+#[test]
+fn test_write_packet() {
+    // given:
+    let mut actual = Vec::new();
+
+    // when:
+    write_packet(&mut actual, 7, COMMAND, "say hi").unwrap();
+
+    // then:
+    let mut expected = Vec::new();
+    expected.extend_from_slice(&16i32.to_le_bytes());
+    expected.extend_from_slice(&7i32.to_le_bytes());
+    expected.extend_from_slice(&2i32.to_le_bytes());
+    expected.extend_from_slice(b"say hi\0\0");
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_read_packet_reads_written_packet() {
+    // given:
+    let mut bytes = Vec::new();
+    write_packet(&mut bytes, 3, RESPONSE, "Added tag 'a' to b").unwrap();
+
+    // when:
+    let actual = read_packet(&mut bytes.as_slice()).unwrap();
+
+    // then:
+    assert_eq!(
+        actual,
+        Packet {
+            id: 3,
+            kind: RESPONSE,
+            body: "Added tag 'a' to b".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_write_packet_too_long() {
+    // given:
+    let body = "a".repeat(MAX_REQUEST_BODY_LEN + 1);
+
+    // when:
+    let actual = write_packet(&mut Vec::new(), 1, COMMAND, &body);
+
+    // then:
+    assert_eq!(actual.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_rcon_connection_executes_command() {
+    // given:
+    let (port, server) = start_mock_server("secret", &["Set the time to 1000"]);
+    let mut connection = RconConnection::connect(log_connection(), port, "secret").unwrap();
+
+    // when:
+    let actual = connection.execute("time set 1000").unwrap();
+
+    // then:
+    assert_eq!(actual, "Set the time to 1000");
+    drop(connection);
+    assert_eq!(server.join().unwrap(), vec!["time set 1000"]);
+}
+
+#[test]
+fn test_rcon_connection_executes_commands_with_logged_minecarts() {
+    // given:
+    let (port, server) = start_mock_server("secret", &["", "", ""]);
+    let mut connection = RconConnection::connect(log_connection(), port, "secret").unwrap();
+
+    // when:
+    connection
+        .execute_commands(vec![Command::named("mcfunction_debugger", "tag @s add a")])
+        .unwrap();
+
+    // then:
+    drop(connection);
+    assert_eq!(
+        server.join().unwrap(),
+        vec![
+            named_logged_command("@", enable_logging_command()),
+            named_logged_command("mcfunction_debugger", "tag @s add a"),
+            named_logged_command("@", reset_logging_command()),
+        ]
+    );
+}
+
+#[test]
+fn test_rcon_connection_wrong_password() {
+    // given:
+    let (port, server) = start_mock_server("secret", &[]);
+
+    // when:
+    let actual = RconConnection::connect(log_connection(), port, "wrong");
+
+    // then:
+    assert_eq!(
+        actual.err().unwrap().kind(),
+        io::ErrorKind::PermissionDenied
+    );
+    server.join().unwrap();
+}
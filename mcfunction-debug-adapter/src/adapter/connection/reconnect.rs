@@ -0,0 +1,282 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    adapter::connection::{add_listener, dispatch, Connection, Listener},
+    installer::Backoff,
+};
+use futures::{stream::BoxStream, StreamExt};
+use log::warn;
+use minect::{log::LogEvent, Command};
+use std::{
+    io, mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{task::JoinHandle, time::sleep};
+
+/// The delay before the first attempt to reconnect. It doubles after every failed attempt.
+pub(crate) const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Creates a new connection to Minecraft to replace a lost one.
+pub(crate) type Connect = Arc<dyn Fn() -> io::Result<Box<dyn Connection>> + Send + Sync>;
+
+/// Reported by a [ReconnectingConnection] while it replaces a lost connection.
+#[derive(Debug)]
+pub enum ReconnectEvent {
+    /// The connection was lost or the previous attempt failed with `error`. The next attempt is
+    /// made after `delay`.
+    Reconnecting {
+        attempt: u32,
+        delay: Duration,
+        error: String,
+    },
+    Reconnected,
+    /// No attempt succeeded within the maximum time. Commands can no longer be executed.
+    Failed {
+        elapsed: Duration,
+        error: String,
+    },
+}
+
+/// Decides when to attempt to reconnect after a connection was lost. The delays between attempts
+/// double every time until their sum would exceed the maximum time to reconnect.
+pub(crate) struct Reconnect {
+    backoff: Backoff,
+    attempts: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ReconnectAction {
+    Retry { attempt: u32, delay: Duration },
+    GiveUp { elapsed: Duration },
+}
+
+impl Reconnect {
+    pub(crate) fn new(initial_delay: Duration, max_reconnect: Duration) -> Reconnect {
+        Reconnect {
+            backoff: Backoff::new(initial_delay, Some(max_reconnect)),
+            attempts: 0,
+        }
+    }
+
+    /// Called when the connection was lost and after every failed attempt to reconnect.
+    pub(crate) fn on_failure(&mut self) -> ReconnectAction {
+        match self.backoff.next() {
+            Some(delay) => {
+                self.attempts += 1;
+                ReconnectAction::Retry {
+                    attempt: self.attempts,
+                    delay,
+                }
+            }
+            None => ReconnectAction::GiveUp {
+                elapsed: self.backoff.elapsed(),
+            },
+        }
+    }
+}
+
+enum State {
+    Connected {
+        connection: Box<dyn Connection>,
+        _forwarder: Task,
+    },
+    /// Commands executed while reconnecting are executed once a new connection is established.
+    Reconnecting {
+        pending: Vec<Command>,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// Aborts the task when dropped.
+struct Task(JoinHandle<()>);
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// A [Connection] that replaces its inner connection when executing commands fails, for example
+/// because the server is down or the disk is full. Listeners stay registered across reconnects and
+/// commands executed while reconnecting are executed after the new connection is established.
+/// Progress is reported as [ReconnectEvent]s.
+pub(crate) struct ReconnectingConnection {
+    state: Arc<Mutex<State>>,
+    listeners: Arc<Mutex<Vec<Listener>>>,
+    connect: Connect,
+    max_reconnect: Duration,
+    on_event: Arc<dyn Fn(ReconnectEvent) + Send + Sync>,
+    reconnect_task: Option<Task>,
+}
+
+impl ReconnectingConnection {
+    pub(crate) fn new(
+        mut connection: Box<dyn Connection>,
+        connect: Connect,
+        max_reconnect: Duration,
+        on_event: impl Fn(ReconnectEvent) + Send + Sync + 'static,
+    ) -> ReconnectingConnection {
+        let listeners = Arc::new(Mutex::new(Vec::new()));
+        let forwarder = forward_events(connection.as_mut(), listeners.clone());
+        ReconnectingConnection {
+            state: Arc::new(Mutex::new(State::Connected {
+                connection,
+                _forwarder: forwarder,
+            })),
+            listeners,
+            connect,
+            max_reconnect,
+            on_event: Arc::new(on_event),
+            reconnect_task: None,
+        }
+    }
+
+    pub(crate) fn is_reconnecting(&self) -> bool {
+        matches!(*self.state.lock().unwrap(), State::Reconnecting { .. })
+    }
+
+    /// Replaces the inner connection immediately, for example because Minecraft's log file was
+    /// replaced.
+    pub(crate) fn reconnect(&mut self) {
+        let reconnecting = self.reconnect_task.take().is_some() && self.is_reconnecting();
+        match (self.connect)().and_then(|it| install(&self.state, &self.listeners, it)) {
+            Ok(()) if reconnecting => (self.on_event)(ReconnectEvent::Reconnected),
+            Ok(()) => {}
+            Err(error) => self.start_reconnecting(error),
+        }
+    }
+
+    fn start_reconnecting(&mut self, error: io::Error) {
+        warn!("Lost connection to Minecraft, reconnecting: {}", error);
+        let state = self.state.clone();
+        let listeners = self.listeners.clone();
+        let connect = self.connect.clone();
+        let on_event = self.on_event.clone();
+        let mut reconnect = Reconnect::new(INITIAL_RECONNECT_DELAY, self.max_reconnect);
+        self.reconnect_task = Some(Task(tokio::spawn(async move {
+            let mut error = error.to_string();
+            loop {
+                match reconnect.on_failure() {
+                    ReconnectAction::Retry { attempt, delay } => {
+                        on_event(ReconnectEvent::Reconnecting {
+                            attempt,
+                            delay,
+                            error: error.clone(),
+                        });
+                        sleep(delay).await;
+                        match connect().and_then(|it| install(&state, &listeners, it)) {
+                            Ok(()) => {
+                                on_event(ReconnectEvent::Reconnected);
+                                return;
+                            }
+                            Err(e) => error = e.to_string(),
+                        }
+                    }
+                    ReconnectAction::GiveUp { elapsed } => {
+                        *state.lock().unwrap() = State::Failed {
+                            error: error.clone(),
+                        };
+                        on_event(ReconnectEvent::Failed { elapsed, error });
+                        return;
+                    }
+                }
+            }
+        })));
+    }
+}
+
+/// Makes `connection` the inner connection after executing the commands that are pending because
+/// of a reconnect. If that fails, the commands stay pending.
+fn install(
+    state: &Mutex<State>,
+    listeners: &Arc<Mutex<Vec<Listener>>>,
+    mut connection: Box<dyn Connection>,
+) -> io::Result<()> {
+    let forwarder = forward_events(connection.as_mut(), listeners.clone());
+    let mut state = state.lock().unwrap();
+    if let State::Reconnecting { pending } = &mut *state {
+        let commands = pending.iter().map(copy_command).collect();
+        connection.execute_commands(commands)?;
+        pending.clear();
+    }
+    *state = State::Connected {
+        connection,
+        _forwarder: forwarder,
+    };
+    Ok(())
+}
+
+fn forward_events(connection: &mut dyn Connection, listeners: Arc<Mutex<Vec<Listener>>>) -> Task {
+    let mut events = connection.add_listener();
+    Task(tokio::spawn(async move {
+        while let Some(event) = events.next().await {
+            dispatch(&listeners, event);
+        }
+    }))
+}
+
+fn copy_command(command: &Command) -> Command {
+    match command.get_name() {
+        Some(name) => Command::named(name, command.get_command()),
+        None => Command::new(command.get_command()),
+    }
+}
+
+impl Connection for ReconnectingConnection {
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, None)
+    }
+
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, Some(name.to_string()))
+    }
+
+    fn execute_commands(&mut self, commands: Vec<Command>) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let error = match &mut *state {
+            State::Connected { connection, .. } => {
+                let copies = commands.iter().map(copy_command).collect();
+                match connection.execute_commands(copies) {
+                    Ok(()) => return Ok(()),
+                    Err(error) => error,
+                }
+            }
+            State::Reconnecting { pending } => {
+                pending.extend(commands);
+                return Ok(());
+            }
+            State::Failed { error } => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    format!("Could not reconnect to Minecraft: {}", error),
+                ))
+            }
+        };
+        *state = State::Reconnecting { pending: commands };
+        mem::drop(state);
+        self.start_reconnecting(error);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
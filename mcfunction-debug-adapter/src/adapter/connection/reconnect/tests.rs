@@ -0,0 +1,210 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+const SECOND: Duration = Duration::from_secs(1);
+
+#[test]
+fn test_reconnect_delays_double_until_max_reconnect() {
+    // given:
+    let mut reconnect = Reconnect::new(SECOND, 20 * SECOND);
+
+    // when:
+    let actual = (0..6).map(|_| reconnect.on_failure()).collect::<Vec<_>>();
+
+    // then:
+    assert_eq!(
+        actual,
+        vec![
+            ReconnectAction::Retry {
+                attempt: 1,
+                delay: SECOND
+            },
+            ReconnectAction::Retry {
+                attempt: 2,
+                delay: 2 * SECOND
+            },
+            ReconnectAction::Retry {
+                attempt: 3,
+                delay: 4 * SECOND
+            },
+            ReconnectAction::Retry {
+                attempt: 4,
+                delay: 8 * SECOND
+            },
+            ReconnectAction::Retry {
+                attempt: 5,
+                delay: 5 * SECOND
+            },
+            ReconnectAction::GiveUp {
+                elapsed: 20 * SECOND
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_reconnect_without_max_reconnect_gives_up_immediately() {
+    // given:
+    let mut reconnect = Reconnect::new(SECOND, Duration::ZERO);
+
+    // when:
+    let actual = reconnect.on_failure();
+
+    // then:
+    assert_eq!(
+        actual,
+        ReconnectAction::GiveUp {
+            elapsed: Duration::ZERO
+        }
+    );
+}
+
+struct MockConnection {
+    fail: bool,
+    executed: Arc<Mutex<Vec<String>>>,
+    listeners: Arc<Mutex<Vec<Listener>>>,
+}
+
+impl Connection for MockConnection {
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, None)
+    }
+
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, Some(name.to_string()))
+    }
+
+    fn execute_commands(&mut self, commands: Vec<Command>) -> io::Result<()> {
+        if self.fail {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"));
+        }
+        let mut executed = self.executed.lock().unwrap();
+        executed.extend(commands.iter().map(|it| it.get_command().to_string()));
+        Ok(())
+    }
+}
+
+fn failing_connection() -> Box<dyn Connection> {
+    Box::new(MockConnection {
+        fail: true,
+        executed: Arc::default(),
+        listeners: Arc::default(),
+    })
+}
+
+fn reconnecting_connection(
+    connect: Connect,
+    max_reconnect: Duration,
+) -> (ReconnectingConnection, UnboundedReceiver<ReconnectEvent>) {
+    let (sender, receiver) = unbounded_channel();
+    let connection =
+        ReconnectingConnection::new(failing_connection(), connect, max_reconnect, move |event| {
+            let _ = sender.send(event);
+        });
+    (connection, receiver)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_reconnecting_connection_executes_pending_commands_after_reconnect() {
+    // given:
+    let executed = Arc::new(Mutex::new(Vec::new()));
+    let new_listeners = Arc::new(Mutex::new(Vec::new()));
+    let failed_attempts = Arc::new(AtomicU32::new(0));
+    let connect: Connect = {
+        let executed = executed.clone();
+        let new_listeners = new_listeners.clone();
+        Arc::new(move || {
+            if failed_attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "server is down"));
+            }
+            Ok(Box::new(MockConnection {
+                fail: false,
+                executed: executed.clone(),
+                listeners: new_listeners.clone(),
+            }) as Box<dyn Connection>)
+        })
+    };
+    let (mut connection, mut events) = reconnecting_connection(connect, 60 * SECOND);
+    let mut listener = connection.add_named_listener("mcfunction-debugger");
+
+    // when:
+    connection
+        .execute_commands(vec![Command::new("say first")])
+        .unwrap();
+    connection
+        .execute_commands(vec![Command::new("say second")])
+        .unwrap();
+
+    // then:
+    let mut delays = Vec::new();
+    loop {
+        match events.recv().await.unwrap() {
+            ReconnectEvent::Reconnecting { attempt, delay, .. } => {
+                assert_eq!(attempt as usize, delays.len() + 1);
+                delays.push(delay);
+            }
+            ReconnectEvent::Reconnected => break,
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+    assert_eq!(delays, vec![SECOND, 2 * SECOND, 4 * SECOND]);
+    assert!(!connection.is_reconnecting());
+    assert_eq!(*executed.lock().unwrap(), vec!["say first", "say second"]);
+
+    let event = "[00:00:00] [Server thread/INFO]: [mcfunction-debugger: Added tag 'exited' to mcfunction-debugger]";
+    dispatch(&new_listeners, event.parse().unwrap());
+    let event = listener.next().await.unwrap();
+    assert_eq!(event.output, "Added tag 'exited' to mcfunction-debugger");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_reconnecting_connection_fails_after_max_reconnect() {
+    // given:
+    let connect: Connect =
+        Arc::new(|| Err(io::Error::new(io::ErrorKind::NotFound, "server is down")));
+    let (mut connection, mut events) = reconnecting_connection(connect, 3 * SECOND);
+
+    // when:
+    connection
+        .execute_commands(vec![Command::new("say hi")])
+        .unwrap();
+
+    // then:
+    assert_reconnecting(events.recv().await.unwrap(), SECOND);
+    assert_reconnecting(events.recv().await.unwrap(), 2 * SECOND);
+    match events.recv().await.unwrap() {
+        ReconnectEvent::Failed { elapsed, error } => {
+            assert_eq!(elapsed, 3 * SECOND);
+            assert_eq!(error, "server is down");
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    }
+    let actual = connection.execute_commands(vec![Command::new("say hi")]);
+    assert_eq!(actual.unwrap_err().kind(), io::ErrorKind::NotConnected);
+}
+
+fn assert_reconnecting(event: ReconnectEvent, expected_delay: Duration) {
+    match event {
+        ReconnectEvent::Reconnecting { delay, .. } => assert_eq!(delay, expected_delay),
+        event => panic!("Unexpected event: {:?}", event),
+    }
+}
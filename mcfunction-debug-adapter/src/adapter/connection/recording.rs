@@ -0,0 +1,83 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{adapter::connection::Connection, recording::Recorder};
+use futures::{stream::BoxStream, StreamExt};
+use minect::{log::LogEvent, Command};
+use std::io;
+
+/// A [Connection] that records every command it executes as `mc_command` and every event it
+/// receives as `mc_event` with a [Recorder].
+pub(crate) struct RecordingMinecraftConnection {
+    connection: Box<dyn Connection>,
+    recorder: Recorder,
+}
+
+impl RecordingMinecraftConnection {
+    pub(crate) fn new(
+        connection: Box<dyn Connection>,
+        recorder: Recorder,
+    ) -> RecordingMinecraftConnection {
+        RecordingMinecraftConnection {
+            connection,
+            recorder,
+        }
+    }
+
+    fn record_events(&self, events: BoxStream<'static, LogEvent>) -> BoxStream<'static, LogEvent> {
+        let recorder = self.recorder.clone();
+        events
+            .inspect(move |event| recorder.mc_event(event))
+            .boxed()
+    }
+}
+
+impl Connection for RecordingMinecraftConnection {
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent> {
+        let events = self.connection.add_listener();
+        self.record_events(events)
+    }
+
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent> {
+        let events = self.connection.add_named_listener(name);
+        self.record_events(events)
+    }
+
+    /// Commands are only recorded if they were executed successfully, because a
+    /// [ReconnectingConnection](super::ReconnectingConnection) executes failed commands again after
+    /// reconnecting.
+    fn execute_commands(&mut self, commands: Vec<Command>) -> io::Result<()> {
+        let recorded = commands
+            .iter()
+            .map(|it| {
+                (
+                    it.get_name().map(str::to_string),
+                    it.get_command().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.connection.execute_commands(commands)?;
+        for (name, command) in &recorded {
+            self.recorder.mc_command(name.as_deref(), command);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,124 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::adapter::connection::{add_listener, dispatch, Listener};
+use serde_json::Value;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tokio::fs::{read_to_string, remove_file};
+
+struct MockConnection {
+    fail: bool,
+    listeners: Arc<Mutex<Vec<Listener>>>,
+}
+
+impl Connection for MockConnection {
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, None)
+    }
+
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, Some(name.to_string()))
+    }
+
+    fn execute_commands(&mut self, _commands: Vec<Command>) -> io::Result<()> {
+        if self.fail {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"));
+        }
+        Ok(())
+    }
+}
+
+async fn started_recorder(name: &str) -> (Recorder, PathBuf) {
+    let dir = std::env::temp_dir().join("mcfunction-debug-adapter-recording-connection-test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let _ = remove_file(&path).await;
+    let recorder = Recorder::new();
+    recorder.start(&path).await.unwrap();
+    (recorder, path)
+}
+
+async fn read_records(recorder: Recorder, path: PathBuf) -> Vec<Value> {
+    recorder.finish().await.unwrap();
+    read_to_string(path)
+        .await
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_records_executed_commands_and_events() {
+    // given:
+    let (recorder, path) =
+        started_recorder("test_records_executed_commands_and_events.ndjson").await;
+    let listeners = Arc::new(Mutex::new(Vec::new()));
+    let mut connection = RecordingMinecraftConnection::new(
+        Box::new(MockConnection {
+            fail: false,
+            listeners: listeners.clone(),
+        }),
+        recorder.clone(),
+    );
+    let mut events = connection.add_listener();
+
+    // when:
+    connection
+        .execute_commands(vec![Command::named("mcfunction-debugger", "say hi")])
+        .unwrap();
+    let event = "[00:00:00] [Server thread/INFO]: [mcfunction-debugger: hi]"
+        .parse::<LogEvent>()
+        .unwrap();
+    dispatch(&listeners, event);
+    events.next().await.unwrap();
+
+    // then:
+    let records = read_records(recorder, path).await;
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["kind"], "mc_command");
+    assert_eq!(records[0]["data"]["name"], "mcfunction-debugger");
+    assert_eq!(records[0]["data"]["command"], "say hi");
+    assert_eq!(records[1]["kind"], "mc_event");
+    assert_eq!(records[1]["data"]["output"], "hi");
+}
+
+#[tokio::test]
+async fn test_does_not_record_failed_commands() {
+    // given:
+    let (recorder, path) = started_recorder("test_does_not_record_failed_commands.ndjson").await;
+    let mut connection = RecordingMinecraftConnection::new(
+        Box::new(MockConnection {
+            fail: true,
+            listeners: Arc::default(),
+        }),
+        recorder.clone(),
+    );
+
+    // when:
+    let result = connection.execute_commands(vec![Command::new("say hi")]);
+
+    // then:
+    assert!(result.is_err());
+    let records = read_records(recorder, path).await;
+    assert!(records.is_empty(), "{:?}", records);
+}
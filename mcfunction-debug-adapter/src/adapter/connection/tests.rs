@@ -0,0 +1,93 @@
+use super::*;
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    time::Duration,
+};
+use tokio::time::timeout;
+
+/// Starts a mock of the companion mod on a free port. It answers the first command it receives
+/// with the scripted events.
+fn start_mock_server(events: &'static [&'static str]) -> (u16, std::thread::JoinHandle<String>) {
+    let listener = TcpListener::bind(("localhost", 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut command = String::new();
+        reader.read_line(&mut command).unwrap();
+        for event in events {
+            writeln!(stream, "{}", event).unwrap();
+        }
+        command
+    });
+    (port, server)
+}
+
+#[tokio::test]
+async fn test_socket_connection_sends_commands_and_receives_events() {
+    // given:
+    let (port, server) = start_mock_server(&[
+        r#"{"executor": "other", "output": "Summoned new other"}"#,
+        r#"{"executor": "mcfunction-debugger", "output": "Added tag 'exited' to mcfunction-debugger"}"#,
+    ]);
+    let mut connection = SocketConnection::connect(port).unwrap();
+    let mut all_events = connection.add_listener();
+    let mut named_events = connection.add_named_listener("mcfunction-debugger");
+
+    // when:
+    connection
+        .execute_commands(vec![Command::named("mcfunction-debugger", "say hi")])
+        .unwrap();
+
+    // then:
+    let command = server.join().unwrap();
+    assert_eq!(
+        command.trim_end(),
+        r#"{"command":"say hi","name":"mcfunction-debugger"}"#
+    );
+
+    let event = timeout(Duration::from_secs(5), all_events.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.executor, "other");
+    assert_eq!(event.output, "Summoned new other");
+
+    let event = timeout(Duration::from_secs(5), named_events.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.executor, "mcfunction-debugger");
+    assert_eq!(event.output, "Added tag 'exited' to mcfunction-debugger");
+}
+
+#[test]
+fn test_socket_event_into_log_event() {
+    // given:
+    let event = serde_json::from_str::<SocketEvent>(
+        r#"{"executor": "mcfunction-debugger", "output": "Added tag 'exited' to mcfunction-debugger"}"#,
+    )
+    .unwrap();
+
+    // when:
+    let actual = event.into_log_event().unwrap();
+
+    // then:
+    assert_eq!(actual.executor, "mcfunction-debugger");
+    assert_eq!(actual.output, "Added tag 'exited' to mcfunction-debugger");
+}
+
+#[test]
+fn test_socket_connection_refused() {
+    // given:
+    let listener = TcpListener::bind(("localhost", 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+
+    // when:
+    let actual = SocketConnection::connect(port);
+
+    // then:
+    assert!(actual.is_err());
+}
@@ -0,0 +1,206 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{adapter::McfunctionMessage, custom::Message};
+use debug_adapter_protocol::events::{OutputCategory, OutputEventBody};
+use futures::future::Either;
+use mcfunction_debugger::{
+    config::{
+        adapter::{breakpoints_to_json, SuspendSchedules},
+        Config,
+    },
+    summary::GeneratedSummary,
+};
+use std::{
+    env,
+    ffi::OsString,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+    process::{Command, Stdio},
+};
+use tokio::{
+    fs::{create_dir_all, read_to_string, remove_dir_all, write},
+    sync::mpsc::UnboundedSender,
+    task::spawn_blocking,
+};
+
+/// Generates the debug datapack by running a separate process instead of calling the generator
+/// directly, for example if the debug adapter is not allowed to write to the world directory. The
+/// process is invoked like the command line interface of mcfunction-debugger, see [generator_args].
+/// Its stdout and stderr are forwarded to the client as output events.
+#[derive(Clone)]
+pub(crate) struct GeneratorCommand {
+    /// The program followed by its first arguments.
+    command: Vec<String>,
+    message_sender: UnboundedSender<Either<Message, McfunctionMessage>>,
+}
+
+impl GeneratorCommand {
+    pub(crate) fn new(
+        command: Vec<String>,
+        message_sender: UnboundedSender<Either<Message, McfunctionMessage>>,
+    ) -> GeneratorCommand {
+        assert!(
+            !command.is_empty(),
+            "The generator command must not be empty"
+        );
+        GeneratorCommand {
+            command,
+            message_sender,
+        }
+    }
+
+    pub(crate) async fn run(
+        &self,
+        datapack: &Path,
+        output_path: &Path,
+        config: &Config<'_>,
+    ) -> io::Result<GeneratedSummary> {
+        let temp_dir = env::temp_dir().join(format!(
+            "mcfunction-debug-adapter-generator-{}",
+            uuid::Uuid::new_v4()
+        ));
+        create_dir_all(&temp_dir).await?;
+        let result = self.run_in(&temp_dir, datapack, output_path, config).await;
+        let _ = remove_dir_all(&temp_dir).await;
+        result
+    }
+
+    async fn run_in(
+        &self,
+        temp_dir: &Path,
+        datapack: &Path,
+        output_path: &Path,
+        config: &Config<'_>,
+    ) -> io::Result<GeneratedSummary> {
+        let breakpoints_path = temp_dir.join("breakpoints.json");
+        let summary_path = temp_dir.join("summary.json");
+        let breakpoints = config.adapter.map(|adapter| adapter.breakpoints);
+        if let Some(breakpoints) = breakpoints {
+            write(&breakpoints_path, breakpoints_to_json(breakpoints)?).await?;
+        }
+
+        let args = generator_args(
+            datapack,
+            output_path,
+            config,
+            breakpoints.map(|_| breakpoints_path.as_path()),
+            &summary_path,
+        );
+        let mut command = Command::new(&self.command[0]);
+        command
+            .args(&self.command[1..])
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let message_sender = self.message_sender.clone();
+        let status = spawn_blocking(move || {
+            let mut child = command.spawn()?;
+            let stderr = child.stderr.take().unwrap();
+            let stderr_sender = message_sender.clone();
+            let stderr_thread = std::thread::spawn(move || {
+                forward_lines(stderr, OutputCategory::Stderr, &stderr_sender)
+            });
+            forward_lines(
+                child.stdout.take().unwrap(),
+                OutputCategory::Stdout,
+                &message_sender,
+            );
+            let _ = stderr_thread.join();
+            child.wait()
+        })
+        .await??;
+
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "Generator command {} failed with {}",
+                self.command[0], status
+            )));
+        }
+        let summary = read_to_string(&summary_path).await?;
+        Ok(serde_json::from_str(&summary)?)
+    }
+}
+
+fn forward_lines(
+    read: impl Read,
+    category: OutputCategory,
+    message_sender: &UnboundedSender<Either<Message, McfunctionMessage>>,
+) {
+    for line in BufReader::new(read).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let event = OutputEventBody::builder()
+            .category(category.clone())
+            .output(format!("{}\n", line))
+            .build();
+        let _ = message_sender.send(Either::Right(McfunctionMessage::Output(event)));
+    }
+}
+
+/// The arguments that are passed to the generator command after the configured arguments:
+/// `--input`, `--output`, `--namespace`, `--adapter-listener`, `--suspend-schedules` and
+/// `--breakpoints-json` like the command line interface of mcfunction-debugger expects them,
+/// followed by the remaining options of the config.
+pub(crate) fn generator_args(
+    datapack: &Path,
+    output_path: &Path,
+    config: &Config<'_>,
+    breakpoints_path: Option<&Path>,
+    summary_path: &Path,
+) -> Vec<OsString> {
+    let mut args = Vec::<OsString>::new();
+    args.extend(["--input".into(), datapack.into()]);
+    args.extend(["--output".into(), output_path.into()]);
+    args.extend(["--namespace".into(), config.namespace.into()]);
+    if let Some(adapter) = config.adapter {
+        args.extend([
+            "--adapter-listener".into(),
+            adapter.adapter_listener_name.into(),
+        ]);
+        if adapter.suspend_schedules != SuspendSchedules::All {
+            args.extend([
+                "--suspend-schedules".into(),
+                adapter.suspend_schedules.to_string().into(),
+            ]);
+        }
+    }
+    if let Some(breakpoints_path) = breakpoints_path {
+        args.extend(["--breakpoints-json".into(), breakpoints_path.into()]);
+    }
+    args.extend(["--summary-json".into(), summary_path.into()]);
+    args.extend([
+        "--max-commands".into(),
+        config.max_commands.to_string().into(),
+    ]);
+    if config.skip_invalid_functions {
+        args.push("--skip-invalid".into());
+    }
+    if config.shadow {
+        args.push("--shadow".into());
+    }
+    for pattern in config.exclude {
+        args.extend(["--exclude".into(), pattern.into()]);
+    }
+    args
+}
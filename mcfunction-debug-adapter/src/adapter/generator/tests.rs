@@ -0,0 +1,143 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use mcfunction_debugger::{
+    config::{
+        adapter::{AdapterConfig, LocalBreakpoint, SuspendSchedules},
+        DEFAULT_MAX_COMMANDS,
+    },
+    parser::command::resource_location::ResourceLocation,
+};
+use multimap::MultiMap;
+use std::collections::BTreeSet;
+use tokio::sync::mpsc::unbounded_channel;
+
+fn config<'l>(
+    exclude: &'l [String],
+    breakpoints: &'l MultiMap<ResourceLocation, LocalBreakpoint>,
+) -> Config<'l> {
+    Config {
+        namespace: "mcfd",
+        shadow: false,
+        exclude,
+        excluded_functions: BTreeSet::new(),
+        skip_invalid_functions: true,
+        additional_datapacks: &[],
+        max_commands: DEFAULT_MAX_COMMANDS,
+        adapter: Some(AdapterConfig {
+            adapter_listener_name: "listener",
+            breakpoints,
+            suspend_schedules: SuspendSchedules::All,
+        }),
+    }
+}
+
+#[test]
+fn test_generator_args() {
+    // given:
+    let exclude = ["a:**".to_string()];
+    let breakpoints = MultiMap::new();
+    let config = config(&exclude, &breakpoints);
+
+    // when:
+    let actual = generator_args(
+        Path::new("in"),
+        Path::new("out"),
+        &config,
+        Some(Path::new("breakpoints.json")),
+        Path::new("summary.json"),
+    );
+
+    // then:
+    let max_commands = DEFAULT_MAX_COMMANDS.to_string();
+    let expected = [
+        "--input",
+        "in",
+        "--output",
+        "out",
+        "--namespace",
+        "mcfd",
+        "--adapter-listener",
+        "listener",
+        "--breakpoints-json",
+        "breakpoints.json",
+        "--summary-json",
+        "summary.json",
+        "--max-commands",
+        &max_commands,
+        "--skip-invalid",
+        "--exclude",
+        "a:**",
+    ];
+    assert_eq!(actual, expected.map(OsString::from));
+}
+
+#[test]
+fn test_generator_args_with_suspend_schedules() {
+    // given:
+    let breakpoints = MultiMap::new();
+    let mut config = config(&[], &breakpoints);
+    if let Some(adapter) = config.adapter.as_mut() {
+        adapter.suspend_schedules = SuspendSchedules::CallStackOnly;
+    }
+
+    // when:
+    let actual = generator_args(
+        Path::new("in"),
+        Path::new("out"),
+        &config,
+        None,
+        Path::new("summary.json"),
+    );
+
+    // then:
+    let expected = ["--suspend-schedules", "callStackOnly"].map(OsString::from);
+    assert!(actual.windows(2).any(|args| args == expected));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_failing_generator_command() {
+    // given:
+    let (message_sender, mut messages) = unbounded_channel();
+    let generator = GeneratorCommand::new(
+        vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo generating; exit 3".to_string(),
+        ],
+        message_sender,
+    );
+    let breakpoints = MultiMap::new();
+    let config = config(&[], &breakpoints);
+
+    // when:
+    let actual = generator
+        .run(Path::new("in"), Path::new("out"), &config)
+        .await;
+
+    // then:
+    assert!(actual.is_err());
+    match messages.try_recv() {
+        Ok(Either::Right(McfunctionMessage::Output(event))) => {
+            assert_eq!(event.output, "generating\n")
+        }
+        _ => panic!("Expected the output of the generator command"),
+    }
+}
@@ -0,0 +1,208 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// How many errors are forwarded per [ERROR_PERIOD] at most.
+const MAX_ERRORS_PER_PERIOD: usize = 10;
+const ERROR_PERIOD: Duration = Duration::from_secs(5);
+
+/// Words that indicate that a line of Minecraft's log file describes a failed command.
+const COMMAND_ERROR_WORDS: [&str; 7] = [
+    "error",
+    "exception",
+    "expected",
+    "failed",
+    "incorrect",
+    "invalid",
+    "unknown",
+];
+
+/// Reads errors and warnings that Minecraft writes to its log file, for example when a function of
+/// the debugged datapack contains an unknown item id or invalid NBT. Only errors that mention one of
+/// the given namespaces are returned. Lines that were written before the forwarder was created are
+/// ignored.
+pub(crate) struct LogErrorForwarder {
+    path: PathBuf,
+    position: u64,
+    /// The start of a line that was not completely written yet.
+    incomplete_line: Vec<u8>,
+    namespaces: Vec<String>,
+    rate_limit: RateLimit,
+}
+
+impl LogErrorForwarder {
+    pub(crate) fn new(
+        path: impl Into<PathBuf>,
+        namespaces: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> LogErrorForwarder {
+        let path = path.into();
+        let position = path.metadata().map(|it| it.len()).unwrap_or(0);
+        LogErrorForwarder {
+            path,
+            position,
+            incomplete_line: Vec::new(),
+            namespaces: namespaces
+                .into_iter()
+                .map(|namespace| format!("{}:", namespace.as_ref()))
+                .collect(),
+            rate_limit: RateLimit::new(MAX_ERRORS_PER_PERIOD, ERROR_PERIOD),
+        }
+    }
+
+    /// Returns the errors that were logged since the last call. If more errors are logged than the
+    /// rate limit allows, the last message says how many were suppressed.
+    pub(crate) fn read_errors(&mut self, now: Instant) -> io::Result<Vec<String>> {
+        let text = self.read_complete_lines()?;
+        let mut errors = Vec::new();
+        for error in find_errors(&text) {
+            if self.namespaces.iter().any(|it| error.contains(it.as_str()))
+                && self.rate_limit.check(now)
+            {
+                errors.push(error);
+            }
+        }
+        let suppressed = self.rate_limit.take_suppressed();
+        if suppressed > 0 {
+            errors.push(format!(
+                "{} more errors from Minecraft's log file were suppressed",
+                suppressed
+            ));
+        }
+        Ok(errors)
+    }
+
+    fn read_complete_lines(&mut self) -> io::Result<String> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            // While the log file is rotated it may not exist
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(String::new()),
+            Err(e) => return Err(e),
+        };
+        if file.metadata()?.len() < self.position {
+            // The log file was truncated or replaced
+            self.position = 0;
+            self.incomplete_line.clear();
+        }
+        file.seek(SeekFrom::Start(self.position))?;
+        let mut bytes = std::mem::take(&mut self.incomplete_line);
+        let start = bytes.len();
+        file.read_to_end(&mut bytes)?;
+        self.position += (bytes.len() - start) as u64;
+
+        let end = bytes
+            .iter()
+            .rposition(|it| *it == b'\n')
+            .map_or(0, |it| it + 1);
+        self.incomplete_line = bytes.split_off(end);
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Returns the messages of all errors and warnings that look like a command failed. Lines that do
+/// not start with a timestamp, such as the message of an exception, belong to the previous line.
+/// Stack traces are omitted.
+pub(crate) fn find_errors(text: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut current: Option<String> = None;
+    for line in text.lines() {
+        if line.starts_with('[') {
+            errors.extend(current.take());
+            current = parse_error_line(line)
+                .filter(|message| looks_like_command_error(message))
+                .map(ToString::to_string);
+        } else if let Some(current) = &mut current {
+            if !line.trim_start().starts_with("at ") && !line.trim().is_empty() {
+                current.push('\n');
+                current.push_str(line);
+            }
+        }
+    }
+    errors.extend(current);
+    errors
+}
+
+/// Returns the message of a line like `[12:34:56] [Server thread/ERROR]: message` if it is an error
+/// or a warning.
+fn parse_error_line(line: &str) -> Option<&str> {
+    let (prefix, message) = line.split_once("]: ")?;
+    // Includes the closing bracket
+    let prefix = &line[..prefix.len() + 1];
+    if prefix.contains("/ERROR]") || prefix.contains("/WARN]") {
+        Some(message)
+    } else {
+        None
+    }
+}
+
+fn looks_like_command_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    COMMAND_ERROR_WORDS.iter().any(|it| message.contains(it))
+}
+
+/// Allows a limited number of events per period and counts the rest.
+pub(crate) struct RateLimit {
+    limit: usize,
+    period: Duration,
+    period_start: Option<Instant>,
+    count: usize,
+    suppressed: usize,
+}
+
+impl RateLimit {
+    pub(crate) fn new(limit: usize, period: Duration) -> RateLimit {
+        RateLimit {
+            limit,
+            period,
+            period_start: None,
+            count: 0,
+            suppressed: 0,
+        }
+    }
+
+    /// Returns whether an event at `now` is allowed.
+    pub(crate) fn check(&mut self, now: Instant) -> bool {
+        match self.period_start {
+            Some(start) if now.duration_since(start) < self.period => {}
+            _ => {
+                self.period_start = Some(now);
+                self.count = 0;
+            }
+        }
+        if self.count < self.limit {
+            self.count += 1;
+            true
+        } else {
+            self.suppressed += 1;
+            false
+        }
+    }
+
+    /// Returns how many events were not allowed since the last call.
+    pub(crate) fn take_suppressed(&mut self) -> usize {
+        std::mem::take(&mut self.suppressed)
+    }
+}
+
+#[cfg(test)]
+mod tests;
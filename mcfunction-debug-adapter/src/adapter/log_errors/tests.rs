@@ -0,0 +1,144 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use std::fs::{remove_file, OpenOptions};
+use std::io::Write;
+
+const SECOND: Duration = Duration::from_secs(1);
+
+#[test]
+fn test_find_errors() {
+    // given:
+    let text = "\
+[12:00:00] [Server thread/INFO]: [mcfunction_debugger: Added tag 'exited' to mcfunction_debugger]
+[12:00:01] [Worker-Main-2/ERROR]: Failed to load function example:main
+java.util.concurrent.CompletionException: Unknown item 'minecraft:diamon' at position 5
+\tat java.base/java.util.concurrent.CompletableFuture.encodeThrowable(Unknown Source)
+[12:00:02] [Server thread/WARN]: Can't keep up! Is the server overloaded?
+[12:00:03] [Server thread/INFO]: Unknown function example:other
+";
+
+    // when:
+    let actual = find_errors(text);
+
+    // then:
+    assert_eq!(
+        actual,
+        vec![
+            "Failed to load function example:main\n\
+            java.util.concurrent.CompletionException: Unknown item 'minecraft:diamon' at position 5"
+        ]
+    );
+}
+
+#[test]
+fn test_rate_limit() {
+    // given:
+    let now = Instant::now();
+    let mut rate_limit = RateLimit::new(2, 5 * SECOND);
+
+    // when:
+    let actual = [
+        rate_limit.check(now),
+        rate_limit.check(now + SECOND),
+        rate_limit.check(now + 2 * SECOND),
+        rate_limit.check(now + 3 * SECOND),
+        rate_limit.check(now + 5 * SECOND),
+    ];
+
+    // then:
+    assert_eq!(actual, [true, true, false, false, true]);
+    assert_eq!(rate_limit.take_suppressed(), 2);
+    assert_eq!(rate_limit.take_suppressed(), 0);
+}
+
+#[test]
+fn test_log_error_forwarder_reads_new_errors_of_namespaces() {
+    // given:
+    let path = std::env::temp_dir().join("mcfunction-debugger-log-errors-test.log");
+    let _ = remove_file(&path);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .unwrap();
+    writeln!(
+        file,
+        "[12:00:00] [Server thread/ERROR]: Failed to load function example:old"
+    )
+    .unwrap();
+    let mut forwarder = LogErrorForwarder::new(&path, ["example"]);
+
+    // when:
+    writeln!(
+        file,
+        "[12:00:01] [Server thread/ERROR]: Failed to load function other:main"
+    )
+    .unwrap();
+    writeln!(
+        file,
+        "[12:00:02] [Server thread/ERROR]: Failed to load function example:main"
+    )
+    .unwrap();
+    write!(
+        file,
+        "[12:00:03] [Server thread/ERROR]: Failed to load function exa"
+    )
+    .unwrap();
+    let first = forwarder.read_errors(Instant::now()).unwrap();
+    writeln!(file, "mple:incomplete").unwrap();
+    let second = forwarder.read_errors(Instant::now()).unwrap();
+
+    // then:
+    assert_eq!(first, vec!["Failed to load function example:main"]);
+    assert_eq!(second, vec!["Failed to load function example:incomplete"]);
+    remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_log_error_forwarder_reports_suppressed_errors() {
+    // given:
+    let path = std::env::temp_dir().join("mcfunction-debugger-log-errors-rate-test.log");
+    let _ = remove_file(&path);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .unwrap();
+    let mut forwarder = LogErrorForwarder::new(&path, ["example"]);
+
+    // when:
+    for i in 0..MAX_ERRORS_PER_PERIOD + 3 {
+        writeln!(
+            file,
+            "[12:00:00] [Server thread/ERROR]: Unknown function example:f{}",
+            i
+        )
+        .unwrap();
+    }
+    let actual = forwarder.read_errors(Instant::now()).unwrap();
+
+    // then:
+    assert_eq!(actual.len(), MAX_ERRORS_PER_PERIOD + 1);
+    assert_eq!(
+        actual.last().unwrap(),
+        "3 more errors from Minecraft's log file were suppressed"
+    );
+    remove_file(&path).unwrap();
+}
@@ -0,0 +1,90 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    fs::{metadata, Metadata},
+    path::PathBuf,
+};
+
+/// Detects when Minecraft's log file is truncated or replaced while the adapter is reading it. The
+/// log observer of minect keeps reading at its old position, so it would miss all new events.
+pub(crate) struct LogFileMonitor {
+    path: PathBuf,
+    last_state: Option<LogFileState>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum LogFileChange {
+    Unchanged,
+    Truncated,
+    Replaced,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct LogFileState {
+    len: u64,
+    /// Identifies the file independent of its path. Only available on unix.
+    file_id: Option<u64>,
+}
+impl LogFileState {
+    fn new(metadata: &Metadata) -> LogFileState {
+        LogFileState {
+            len: metadata.len(),
+            file_id: get_file_id(metadata),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn get_file_id(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn get_file_id(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+impl LogFileMonitor {
+    pub(crate) fn new(path: impl Into<PathBuf>) -> LogFileMonitor {
+        let path = path.into();
+        let last_state = metadata(&path).ok().map(|it| LogFileState::new(&it));
+        LogFileMonitor { path, last_state }
+    }
+
+    /// Compares the log file with the last check. While the log file does not exist, for example in
+    /// the middle of a log rotation, it is reported as unchanged.
+    pub(crate) fn check(&mut self) -> LogFileChange {
+        let state = match metadata(&self.path) {
+            Ok(metadata) => LogFileState::new(&metadata),
+            Err(_) => return LogFileChange::Unchanged,
+        };
+        let change = match self.last_state {
+            Some(last) if last.file_id != state.file_id => LogFileChange::Replaced,
+            Some(last) if last.len > state.len => LogFileChange::Truncated,
+            Some(_) => LogFileChange::Unchanged,
+            None => LogFileChange::Replaced,
+        };
+        self.last_state = Some(state);
+        change
+    }
+}
+
+#[cfg(test)]
+mod tests;
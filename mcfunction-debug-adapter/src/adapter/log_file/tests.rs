@@ -0,0 +1,105 @@
+use super::*;
+use std::{
+    fs::{create_dir_all, remove_dir_all, remove_file, rename, write, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+fn create_log_file(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debug-adapter-test")
+        .join(name);
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    let path = dir.join("latest.log");
+    write(&path, "[12:00:00] [Server thread/INFO]: first\n").unwrap();
+    path
+}
+
+fn append(path: &Path, line: &str) {
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    writeln!(file, "{}", line).unwrap();
+}
+
+#[test]
+fn test_appended_log_file_is_unchanged() {
+    // given:
+    let path = create_log_file("test_appended_log_file_is_unchanged");
+    let mut monitor = LogFileMonitor::new(&path);
+    append(&path, "[12:00:01] [Server thread/INFO]: second");
+
+    // when:
+    let actual = monitor.check();
+
+    // then:
+    assert_eq!(actual, LogFileChange::Unchanged);
+}
+
+#[test]
+fn test_truncated_log_file() {
+    // given:
+    let path = create_log_file("test_truncated_log_file");
+    let mut monitor = LogFileMonitor::new(&path);
+    OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+
+    // when:
+    let actual = monitor.check();
+
+    // then:
+    assert_eq!(actual, LogFileChange::Truncated);
+}
+
+#[test]
+fn test_truncated_and_rewritten_log_file_is_only_reported_once() {
+    // given:
+    let path = create_log_file("test_truncated_and_rewritten_log_file_is_only_reported_once");
+    let mut monitor = LogFileMonitor::new(&path);
+    write(&path, "").unwrap();
+    monitor.check();
+    append(&path, "[12:00:02] [Server thread/INFO]: third");
+
+    // when:
+    let actual = monitor.check();
+
+    // then:
+    assert_eq!(actual, LogFileChange::Unchanged);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_replaced_log_file() {
+    // given:
+    let path = create_log_file("test_replaced_log_file");
+    let mut monitor = LogFileMonitor::new(&path);
+    let new_path = path.with_extension("new");
+    write(
+        &new_path,
+        "[12:00:03] [Server thread/INFO]: a much longer first line\n",
+    )
+    .unwrap();
+    rename(&new_path, &path).unwrap();
+
+    // when:
+    let actual = monitor.check();
+
+    // then:
+    assert_eq!(actual, LogFileChange::Replaced);
+}
+
+#[test]
+fn test_missing_log_file_is_unchanged() {
+    // given:
+    let path = create_log_file("test_missing_log_file_is_unchanged");
+    let mut monitor = LogFileMonitor::new(&path);
+    remove_file(&path).unwrap();
+
+    // when:
+    let actual = monitor.check();
+
+    // then:
+    assert_eq!(actual, LogFileChange::Unchanged);
+}
@@ -0,0 +1,243 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use serde_json::{Map, Number, Value};
+
+/// Parses stringified NBT as printed by Minecraft, for example by `data get entity`. Compounds are
+/// parsed as objects, lists and arrays as arrays. Numbers lose their type suffix.
+pub(crate) fn parse_snbt(string: &str) -> Result<Value, String> {
+    let mut parser = SnbtParser { string, index: 0 };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.index < string.len() {
+        return Err(parser.error("Expected end of input"));
+    }
+    Ok(value)
+}
+
+struct SnbtParser<'l> {
+    string: &'l str,
+    index: usize,
+}
+
+impl<'l> SnbtParser<'l> {
+    fn remaining(&self) -> &'l str {
+        &self.string[self.index..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let remaining = self.remaining();
+        self.index += remaining.len() - remaining.trim_start().len();
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.index += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected)))
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at index {}", message, self.index)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.parse_compound(),
+            Some('[') => self.parse_list(),
+            Some('"') | Some('\'') => self.parse_quoted_string().map(Value::String),
+            _ => Ok(parse_unquoted_value(self.parse_unquoted_string()?)),
+        }
+    }
+
+    fn parse_compound(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut compound = Map::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.index += 1;
+            return Ok(Value::Object(compound));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some('"') | Some('\'') => self.parse_quoted_string()?,
+                _ => self.parse_unquoted_string()?.to_string(),
+            };
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            compound.insert(key, value);
+            if self.parse_separator('}')? {
+                return Ok(Value::Object(compound));
+            }
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        // Typed arrays like [I; 1, 2] start with the type of their elements
+        let remaining = self.remaining();
+        if let Some(suffix) = remaining
+            .get(1..)
+            .filter(|_| remaining.starts_with(['B', 'I', 'L']))
+        {
+            if suffix.trim_start().starts_with(';') {
+                self.index += 1;
+                self.expect(';')?;
+            }
+        }
+        let mut list = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.index += 1;
+            return Ok(Value::Array(list));
+        }
+        loop {
+            list.push(self.parse_value()?);
+            if self.parse_separator(']')? {
+                return Ok(Value::Array(list));
+            }
+        }
+    }
+
+    /// Parses either a `,` or the end of a compound or list. Returns true at the end.
+    fn parse_separator(&mut self, end: char) -> Result<bool, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.index += 1;
+                Ok(false)
+            }
+            Some(c) if c == end => {
+                self.index += 1;
+                Ok(true)
+            }
+            _ => Err(self.error(&format!("Expected ',' or '{}'", end))),
+        }
+    }
+
+    fn parse_quoted_string(&mut self) -> Result<String, String> {
+        let quote = self.peek().ok_or_else(|| self.error("Expected string"))?;
+        self.index += quote.len_utf8();
+        let mut string = String::new();
+        let mut escaped = false;
+        for (index, c) in self.remaining().char_indices() {
+            if escaped {
+                string.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                self.index += index + c.len_utf8();
+                return Ok(string);
+            } else {
+                string.push(c);
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+
+    fn parse_unquoted_string(&mut self) -> Result<&'l str, String> {
+        let remaining = self.remaining();
+        let len = remaining
+            .find(|c: char| !is_allowed_in_unquoted_string(c))
+            .unwrap_or(remaining.len());
+        if len == 0 {
+            return Err(self.error("Expected value"));
+        }
+        self.index += len;
+        Ok(&remaining[..len])
+    }
+}
+
+fn is_allowed_in_unquoted_string(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+/// Unquoted values are numbers with an optional type suffix, booleans or strings.
+fn parse_unquoted_value(string: &str) -> Value {
+    match string {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+    let (number, suffix) = match string.char_indices().last() {
+        Some((index, c)) if "bBsSlLfFdD".contains(c) => (&string[..index], Some(c)),
+        _ => (string, None),
+    };
+    let is_integer = match suffix {
+        Some('f' | 'F' | 'd' | 'D') => false,
+        Some(_) => true,
+        None => !number.contains(['.', 'e', 'E']),
+    };
+    if is_integer {
+        if let Ok(number) = number.parse::<i64>() {
+            return Value::Number(number.into());
+        }
+    } else if let Some(number) = number.parse::<f64>().ok().and_then(Number::from_f64) {
+        return Value::Number(number);
+    }
+    Value::String(string.to_string())
+}
+
+/// A step of an NBT path like `Inventory[0].id`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum NbtPathSegment {
+    Key(String),
+    Index(usize),
+}
+impl NbtPathSegment {
+    pub(crate) fn get<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        match self {
+            NbtPathSegment::Key(key) => value.as_object()?.get(key),
+            NbtPathSegment::Index(index) => value.as_array()?.get(*index),
+        }
+    }
+}
+
+/// Formats a path in the syntax of Minecraft's NBT paths, for example for `data get`.
+pub(crate) fn format_nbt_path(path: &[NbtPathSegment]) -> String {
+    let mut result = String::new();
+    for segment in path {
+        match segment {
+            NbtPathSegment::Key(key) => {
+                if !result.is_empty() {
+                    result.push('.');
+                }
+                if key.chars().all(is_allowed_in_unquoted_string) && !key.contains('.') {
+                    result.push_str(key);
+                } else {
+                    result.push_str(&Value::String(key.to_string()).to_string());
+                }
+            }
+            NbtPathSegment::Index(index) => result.push_str(&format!("[{}]", index)),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,62 @@
+use super::*;
+use serde_json::json;
+
+#[test]
+fn test_parse_snbt_entity_data() {
+    // given:
+    let snbt = r#"{Health: 8.0f, Tags: ["test", 'a "b"'], UUID: [I; 1, -2, 3, 4], Pos: [0.5d, 1.0E-4d, -3.5d], CustomNameVisible: 0b, "key with space": {}}"#;
+
+    // when:
+    let actual = parse_snbt(snbt);
+
+    // then:
+    assert_eq!(
+        actual,
+        Ok(json!({
+            "Health": 8.0,
+            "Tags": ["test", "a \"b\""],
+            "UUID": [1, -2, 3, 4],
+            "Pos": [0.5, 0.0001, -3.5],
+            "CustomNameVisible": 0,
+            "key with space": {},
+        }))
+    );
+}
+
+#[test]
+fn test_parse_snbt_unquoted_values() {
+    // when:
+    let actual = parse_snbt("{id: \"minecraft:stone\", Count: 1b, Name: abc, Invulnerable: true}");
+
+    // then:
+    assert_eq!(
+        actual,
+        Ok(json!({"id": "minecraft:stone", "Count": 1, "Name": "abc", "Invulnerable": true}))
+    );
+}
+
+#[test]
+fn test_parse_snbt_rejects_trailing_input() {
+    // when:
+    let actual = parse_snbt("{} {}");
+
+    // then:
+    assert!(actual.is_err());
+}
+
+#[test]
+fn test_format_nbt_path() {
+    // given:
+    let path = [
+        NbtPathSegment::Key("Inventory".to_string()),
+        NbtPathSegment::Index(0),
+        NbtPathSegment::Key("tag".to_string()),
+        NbtPathSegment::Key("a.b c".to_string()),
+    ];
+
+    // when:
+    let actual = format_nbt_path(&path);
+
+    // then:
+    assert_eq!(actual, r#"Inventory[0].tag."a.b c""#);
+}
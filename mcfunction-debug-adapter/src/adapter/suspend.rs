@@ -0,0 +1,86 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+/// The pack_format of Minecraft 1.20.3, which added the `tick` command.
+pub(crate) const TICK_COMMAND_PACK_FORMAT: u64 = 26;
+
+/// What else is paused while the debugged function is suspended.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum OnSuspend {
+    /// Only the debugged function is suspended, the rest of the world keeps running.
+    FreezeSession,
+    /// Additionally freezes the game with `tick freeze`.
+    FreezeTick,
+    /// Additionally disables the datapacks, so none of their functions run.
+    DisableOtherPacks(Vec<String>),
+}
+
+/// Keeps track of whether the commands of [OnSuspend] were injected, so that every suspend is
+/// undone exactly once, even if the session ends while suspended.
+pub(crate) struct SuspendControl {
+    on_suspend: OnSuspend,
+    suspended: bool,
+}
+
+impl SuspendControl {
+    pub(crate) fn new(on_suspend: OnSuspend) -> SuspendControl {
+        SuspendControl {
+            on_suspend,
+            suspended: false,
+        }
+    }
+
+    pub(crate) fn on_suspend(&self) -> &OnSuspend {
+        &self.on_suspend
+    }
+
+    /// Returns the commands to inject when the debugged function is suspended.
+    pub(crate) fn on_stopped(&mut self) -> Vec<String> {
+        if self.suspended {
+            return Vec::new();
+        }
+        self.suspended = true;
+        match &self.on_suspend {
+            OnSuspend::FreezeSession => Vec::new(),
+            OnSuspend::FreezeTick => vec!["tick freeze".to_string()],
+            OnSuspend::DisableOtherPacks(datapacks) => datapacks
+                .iter()
+                .map(|datapack| format!("datapack disable \"{}\"", datapack))
+                .collect(),
+        }
+    }
+
+    /// Returns the commands to inject before the debugged function is resumed or terminated.
+    pub(crate) fn on_resumed(&mut self) -> Vec<String> {
+        if !self.suspended {
+            return Vec::new();
+        }
+        self.suspended = false;
+        match &self.on_suspend {
+            OnSuspend::FreezeSession => Vec::new(),
+            OnSuspend::FreezeTick => vec!["tick unfreeze".to_string()],
+            OnSuspend::DisableOtherPacks(datapacks) => datapacks
+                .iter()
+                .map(|datapack| format!("datapack enable \"{}\"", datapack))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
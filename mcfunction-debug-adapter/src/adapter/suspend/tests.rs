@@ -0,0 +1,97 @@
+use super::*;
+
+#[test]
+fn test_freeze_session_injects_nothing() {
+    // given:
+    let mut control = SuspendControl::new(OnSuspend::FreezeSession);
+
+    // when:
+    let stopped = control.on_stopped();
+    let resumed = control.on_resumed();
+
+    // then:
+    assert_eq!(stopped, Vec::<String>::new());
+    assert_eq!(resumed, Vec::<String>::new());
+}
+
+#[test]
+fn test_freeze_tick() {
+    // given:
+    let mut control = SuspendControl::new(OnSuspend::FreezeTick);
+
+    // when:
+    let stopped = control.on_stopped();
+    let resumed = control.on_resumed();
+
+    // then:
+    assert_eq!(stopped, vec!["tick freeze"]);
+    assert_eq!(resumed, vec!["tick unfreeze"]);
+}
+
+#[test]
+fn test_disable_other_packs() {
+    // given:
+    let mut control = SuspendControl::new(OnSuspend::DisableOtherPacks(vec![
+        "file/first".to_string(),
+        "file/second".to_string(),
+    ]));
+
+    // when:
+    let stopped = control.on_stopped();
+    let resumed = control.on_resumed();
+
+    // then:
+    assert_eq!(
+        stopped,
+        vec![
+            "datapack disable \"file/first\"",
+            "datapack disable \"file/second\"",
+        ]
+    );
+    assert_eq!(
+        resumed,
+        vec![
+            "datapack enable \"file/first\"",
+            "datapack enable \"file/second\"",
+        ]
+    );
+}
+
+#[test]
+fn test_stopped_twice_suspends_once() {
+    // given:
+    let mut control = SuspendControl::new(OnSuspend::FreezeTick);
+    control.on_stopped();
+
+    // when:
+    let actual = control.on_stopped();
+
+    // then:
+    assert_eq!(actual, Vec::<String>::new());
+}
+
+#[test]
+fn test_resumed_without_stop_injects_nothing() {
+    // given:
+    let mut control = SuspendControl::new(OnSuspend::FreezeTick);
+
+    // when:
+    let actual = control.on_resumed();
+
+    // then:
+    assert_eq!(actual, Vec::<String>::new());
+}
+
+#[test]
+fn test_resumed_twice_restores_once() {
+    // given:
+    let mut control = SuspendControl::new(OnSuspend::FreezeTick);
+    control.on_stopped();
+    control.on_resumed();
+
+    // when:
+    let actual = control.on_resumed();
+
+    // then:
+    assert_eq!(actual, Vec::<String>::new());
+}
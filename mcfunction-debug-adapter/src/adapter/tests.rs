@@ -0,0 +1,745 @@
+use crate::test_utils::{
+    mock_connection::{MockMinecraftConnection, Scenario, RELOAD_FINISHED},
+    test_adapter::{TestAdapter, TestWorld},
+};
+use debug_adapter_protocol::{events::StoppedEventReason, types::StackFrame};
+use mcfunction_debugger::StoppedReason;
+use serde_json::json;
+use std::{fs::read_dir, time::Duration};
+use tokio::time::sleep;
+
+fn names(stack_trace: &[StackFrame]) -> Vec<&str> {
+    stack_trace
+        .iter()
+        .map(|frame| frame.name.as_str())
+        .collect()
+}
+
+/// Creates `adapter_test:outer`, which calls `adapter_test:inner` in line 2, and returns their paths.
+fn create_outer_and_inner(
+    world: &TestWorld,
+    outer: &[&str],
+    inner: &[&str],
+) -> (std::path::PathBuf, std::path::PathBuf) {
+    let outer_path = world.create_function("adapter_test:outer", outer);
+    let inner_path = world.create_function("adapter_test:inner", inner);
+    (outer_path, inner_path)
+}
+
+#[tokio::test]
+async fn test_breakpoint() {
+    // given:
+    let world = TestWorld::new("test_breakpoint");
+    let test_path = world.create_function("adapter_test:test", &["say 1", "say 2"]);
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/test 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:test:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&test_path, &[2]).await;
+
+    // when:
+    adapter.launch(&world, &test_path).await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(names(&stack_trace), vec!["adapter_test:test:2"]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_stop_on_entry() {
+    // given:
+    let world = TestWorld::new("test_stop_on_entry");
+    let test_path = world.create_function(
+        "adapter_test:test",
+        &["# Comments are skipped", "say 1", "say 2"],
+    );
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/test 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:test:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+
+    // when:
+    adapter
+        .launch_with_attributes(&world, &test_path, [("stopOnEntry", json!(true))])
+        .await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Entry).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(names(&stack_trace), vec!["adapter_test:test:2"]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_step_out_of_root_function() {
+    // given:
+    let world = TestWorld::new("test_step_out_of_root_function");
+    let test_path = world.create_function("adapter_test:test", &["say 1", "say 2", "say 3"]);
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/test 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:test:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&test_path, &[2]).await;
+    adapter.launch(&world, &test_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+
+    // when:
+    adapter.step_out().await;
+
+    // then:
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_step_out_of_inner_function() {
+    // given:
+    let world = TestWorld::new("test_step_out_of_inner_function");
+    let (outer_path, inner_path) = create_outer_and_inner(
+        &world,
+        &["say 1", "function adapter_test:inner", "say 3"],
+        &["say inner 1", "say inner 2"],
+    );
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/outer 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:inner:1_breakpoint",
+                &["adapter_test:outer:2"],
+            )
+            .expect("function debug:resume")
+            .stop(StoppedReason::Step, "adapter_test:outer:3_breakpoint", &[])
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&inner_path, &[1]).await;
+    adapter.launch(&world, &outer_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(
+        names(&stack_trace),
+        vec!["adapter_test:inner:1", "adapter_test:outer:2"]
+    );
+
+    // when:
+    adapter.step_out().await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(names(&stack_trace), vec!["adapter_test:outer:3"]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_step_out_into_end_of_function() {
+    // given:
+    let world = TestWorld::new("test_step_out_into_end_of_function");
+    let (outer_path, inner_path) = create_outer_and_inner(
+        &world,
+        &["say 1", "function adapter_test:inner"],
+        &["say inner 1", "say inner 2"],
+    );
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/outer 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:inner:1_breakpoint",
+                &["adapter_test:outer:2"],
+            )
+            .expect("function debug:resume")
+            .stop(
+                StoppedReason::Step,
+                "adapter_test:outer:2_after_function",
+                &[],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&inner_path, &[1]).await;
+    adapter.launch(&world, &outer_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+
+    // when:
+    adapter.step_out().await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(names(&stack_trace), vec!["adapter_test:outer:2"]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_next_steps_over_command() {
+    // given:
+    let world = TestWorld::new("test_next_steps_over_command");
+    let test_path = world.create_function("adapter_test:test", &["say 1", "say 2", "say 3"]);
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/test 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:test:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .stop(StoppedReason::Step, "adapter_test:test:3_breakpoint", &[])
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&test_path, &[2]).await;
+    adapter.launch(&world, &test_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+
+    // when:
+    adapter.next().await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(names(&stack_trace), vec!["adapter_test:test:3"]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_next_steps_over_function() {
+    // given:
+    let world = TestWorld::new("test_next_steps_over_function");
+    let (outer_path, _inner_path) = create_outer_and_inner(
+        &world,
+        &["say 1", "function adapter_test:inner", "say 3"],
+        &["say inner 1", "say inner 2"],
+    );
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/outer 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:outer:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .stop(StoppedReason::Step, "adapter_test:outer:3_breakpoint", &[])
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&outer_path, &[2]).await;
+    adapter.launch(&world, &outer_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+
+    // when:
+    adapter.next().await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(names(&stack_trace), vec!["adapter_test:outer:3"]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_next_steps_into_function_with_breakpoint() {
+    // given:
+    let world = TestWorld::new("test_next_steps_into_function_with_breakpoint");
+    let (outer_path, inner_path) = create_outer_and_inner(
+        &world,
+        &["say 1", "function adapter_test:inner", "say 3"],
+        &["say inner 1", "say inner 2"],
+    );
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/outer 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:outer:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:inner:2_breakpoint",
+                &["adapter_test:outer:2"],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&outer_path, &[2]).await;
+    adapter.set_breakpoints(&inner_path, &[2]).await;
+    adapter.launch(&world, &outer_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+
+    // when:
+    adapter.next().await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(
+        names(&stack_trace),
+        vec!["adapter_test:inner:2", "adapter_test:outer:2"]
+    );
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_next_steps_out_of_function() {
+    // given:
+    let world = TestWorld::new("test_next_steps_out_of_function");
+    let (outer_path, inner_path) = create_outer_and_inner(
+        &world,
+        &["say 1", "function adapter_test:inner", "say 3"],
+        &["say inner 1", "say inner 2"],
+    );
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/outer 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:inner:2_breakpoint",
+                &["adapter_test:outer:2"],
+            )
+            .expect("function debug:resume")
+            .stop(StoppedReason::Step, "adapter_test:outer:3_breakpoint", &[])
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&inner_path, &[2]).await;
+    adapter.launch(&world, &outer_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+
+    // when:
+    adapter.next().await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(names(&stack_trace), vec!["adapter_test:outer:3"]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_step_in_steps_over_command() {
+    // given:
+    let world = TestWorld::new("test_step_in_steps_over_command");
+    let test_path = world.create_function("adapter_test:test", &["say 1", "say 2", "say 3"]);
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/test 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:test:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .stop(StoppedReason::Step, "adapter_test:test:3_breakpoint", &[])
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&test_path, &[2]).await;
+    adapter.launch(&world, &test_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+
+    // when:
+    adapter.step_in().await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(names(&stack_trace), vec!["adapter_test:test:3"]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_step_in_function() {
+    // given:
+    let world = TestWorld::new("test_step_in_function");
+    let (outer_path, _inner_path) = create_outer_and_inner(
+        &world,
+        &["say 1", "function adapter_test:inner", "say 3"],
+        &["say inner 1"],
+    );
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/outer 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:outer:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .stop(
+                StoppedReason::Step,
+                "adapter_test:inner:1_breakpoint",
+                &["adapter_test:outer:2"],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&outer_path, &[2]).await;
+    adapter.launch(&world, &outer_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+
+    // when:
+    adapter.step_in().await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(
+        names(&stack_trace),
+        vec!["adapter_test:inner:1", "adapter_test:outer:2"]
+    );
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_step_in_target_of_line_with_two_callees() {
+    // given:
+    let world = TestWorld::new("test_step_in_target_of_line_with_two_callees");
+    let outer_path = world.create_function(
+        "adapter_test:outer",
+        &[
+            "say 1",
+            "execute if function adapter_test:condition run function adapter_test:inner",
+        ],
+    );
+    world.create_function("adapter_test:condition", &["return 1"]);
+    world.create_function("adapter_test:inner", &["say inner 1"]);
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/outer 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:outer:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .stop(
+                StoppedReason::Step,
+                "adapter_test:inner:1_breakpoint",
+                &["adapter_test:outer:2"],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&outer_path, &[2]).await;
+    adapter.launch(&world, &outer_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+    let stack_trace = adapter.stack_trace().await;
+
+    // when:
+    let targets = adapter.step_in_targets(stack_trace[0].id).await;
+    adapter.step_in_target(targets[1].id).await;
+
+    // then:
+    let labels = targets
+        .iter()
+        .map(|target| target.label.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![
+            "adapter_test:condition (column 12)",
+            "adapter_test:inner (column 48)"
+        ]
+    );
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(
+        names(&stack_trace),
+        vec!["adapter_test:inner:1", "adapter_test:outer:2"]
+    );
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_step_in_steps_out_of_function() {
+    // given:
+    let world = TestWorld::new("test_step_in_steps_out_of_function");
+    let (outer_path, inner_path) = create_outer_and_inner(
+        &world,
+        &["say 1", "function adapter_test:inner", "say 3"],
+        &["say inner 1", "say inner 2"],
+    );
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/outer 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:inner:2_breakpoint",
+                &["adapter_test:outer:2"],
+            )
+            .expect("function debug:resume")
+            .stop(StoppedReason::Step, "adapter_test:outer:3_breakpoint", &[])
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&inner_path, &[2]).await;
+    adapter.launch(&world, &outer_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+
+    // when:
+    adapter.step_in().await;
+
+    // then:
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(names(&stack_trace), vec!["adapter_test:outer:3"]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_continue_waits_for_reload_of_removed_breakpoint() {
+    // given:
+    let world = TestWorld::new("test_continue_waits_for_reload_of_removed_breakpoint");
+    let test_path = world.create_function("adapter_test:test", &["say 1", "say 2", "say 3"]);
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .reload_duration(Duration::from_millis(200))
+            .expect("schedule function debug:adapter_test/test 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:test:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&test_path, &[2, 3]).await;
+    adapter.launch(&world, &test_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+    adapter.set_breakpoints(&test_path, &[2]).await;
+
+    // when:
+    adapter.continue_().await;
+
+    // then:
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+    let commands = mock.commands();
+    let resume = commands
+        .iter()
+        .rposition(|it| it == "function debug:resume")
+        .unwrap();
+    let reload = commands[..resume]
+        .iter()
+        .rposition(|it| it == "reload")
+        .unwrap();
+    assert!(
+        commands[reload..resume].contains(&RELOAD_FINISHED.to_string()),
+        "{:#?}",
+        commands
+    );
+}
+
+#[tokio::test]
+async fn test_set_breakpoints_during_generation_uses_last_breakpoints() {
+    // given:
+    let world = TestWorld::new("test_set_breakpoints_during_generation_uses_last_breakpoints");
+    let test_path = world.create_function("adapter_test:test", &["say 1", "say 2", "say 3"]);
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .reload_duration(Duration::from_millis(200))
+            .expect("schedule function debug:adapter_test/test 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:test:2_breakpoint",
+                &[],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&test_path, &[2]).await;
+    adapter.launch(&world, &test_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+    // The next generation waits until Minecraft reloaded these breakpoints
+    adapter.set_breakpoints(&test_path, &[1, 2]).await;
+    let first = adapter.send_set_breakpoints(&test_path, &[2, 3]);
+    sleep(Duration::from_millis(50)).await;
+
+    // when:
+    let second = adapter.send_set_breakpoints(&test_path, &[1]);
+    let third = adapter.send_set_breakpoints(&test_path, &[2]);
+    for request_seq in [first, second, third] {
+        adapter.assert_set_breakpoints_response(request_seq).await;
+    }
+
+    // then:
+    let function_dir = world
+        .debug_datapack()
+        .join("data/mcfd/functions/adapter_test/test");
+    let mut suspend_files = read_dir(function_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.starts_with("suspend_at_"))
+        .collect::<Vec<_>>();
+    suspend_files.sort();
+    assert_eq!(suspend_files, ["suspend_at_2_breakpoint.mcfunction"]);
+
+    // The second request was superseded by the third one
+    let commands = mock.commands();
+    let stop = commands
+        .iter()
+        .position(|it| it == "schedule function debug:adapter_test/test 1t")
+        .unwrap();
+    let reloads = commands[stop..].iter().filter(|it| *it == "reload").count();
+    assert_eq!(reloads, 3, "{:#?}", commands);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
+
+#[tokio::test]
+async fn test_continue_single_thread_suspends_next_executor() {
+    // given:
+    let world = TestWorld::new("test_continue_single_thread_suspends_next_executor");
+    let (outer_path, inner_path) = create_outer_and_inner(
+        &world,
+        &["say start", "execute as @e run function adapter_test:inner"],
+        &["say 1", "say 2"],
+    );
+    let mock = MockMinecraftConnection::new(
+        Scenario::new(world.debug_datapack())
+            .expect("schedule function debug:adapter_test/outer 1t")
+            .stop(
+                StoppedReason::Breakpoint,
+                "adapter_test:inner:2_breakpoint",
+                &["adapter_test:outer:2"],
+            )
+            .pending_executors(&["Alex"])
+            .expect("function debug:resume")
+            .stop(
+                StoppedReason::Step,
+                "adapter_test:inner:1_breakpoint",
+                &["adapter_test:outer:2"],
+            )
+            .expect("function debug:resume")
+            .exit(),
+    );
+    let mut adapter = TestAdapter::start(&mock);
+    adapter.initialize().await;
+    adapter.set_breakpoints(&inner_path, &[2]).await;
+    adapter.launch(&world, &outer_path).await;
+    adapter.assert_stopped(StoppedEventReason::Breakpoint).await;
+    let threads = adapter.threads().await;
+    let thread_names = threads
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(thread_names, ["Main Thread", "Alex (pending)"]);
+
+    // when:
+    let all_threads_continued = adapter.continue_single_thread().await;
+
+    // then:
+    assert!(!all_threads_continued);
+    adapter.assert_stopped(StoppedEventReason::Step).await;
+    let stack_trace = adapter.stack_trace().await;
+    assert_eq!(
+        names(&stack_trace),
+        vec!["adapter_test:inner:1", "adapter_test:outer:2"]
+    );
+    assert!(mock
+        .commands()
+        .iter()
+        .any(|command| command.ends_with("run tag @s add mcfd_pending")));
+    let threads = adapter.threads().await;
+    assert_eq!(threads.len(), 1);
+
+    assert!(adapter.continue_single_thread().await);
+    adapter.assert_terminated().await;
+    mock.assert_finished();
+}
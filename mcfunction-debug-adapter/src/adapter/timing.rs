@@ -0,0 +1,88 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use debug_adapter_protocol::events::StoppedEventReason;
+use serde::Serialize;
+
+/// The ticks that the debug datapack counted since the launch. Both counters stop at [i32::MAX]
+/// instead of overflowing.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct SessionTicks {
+    /// The score `session_ticks` of `-ns-_global`.
+    pub(crate) total: i32,
+    /// The score `suspended_ticks` of `-ns-_global`.
+    pub(crate) suspended: i32,
+}
+
+impl SessionTicks {
+    fn running(&self) -> i32 {
+        self.total.saturating_sub(self.suspended)
+    }
+}
+
+/// How many ticks passed until a stop. This is the response body of the custom request
+/// `mcfunctionTiming`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Timing {
+    /// The ticks since the launch.
+    pub(crate) session_ticks: i32,
+    /// The ticks that the program ran since it was resumed at the previous stop or launched.
+    pub(crate) ran_ticks: i32,
+    /// The ticks that the program was suspended at the previous stop or `None` for the first stop.
+    pub(crate) suspended_ticks: Option<i32>,
+}
+
+impl Timing {
+    pub(crate) fn new(previous: Option<SessionTicks>, current: SessionTicks) -> Timing {
+        let since = previous.unwrap_or_default();
+        Timing {
+            session_ticks: current.total,
+            ran_ticks: current.running().saturating_sub(since.running()).max(0),
+            suspended_ticks: previous
+                .map(|previous| current.suspended.saturating_sub(previous.suspended).max(0)),
+        }
+    }
+
+    /// Returns the description of a Stopped event, for example
+    /// `breakpoint (ran 3 ticks since last stop, was suspended 120 ticks)`.
+    pub(crate) fn describe(&self, reason: &StoppedEventReason) -> String {
+        let reason = serde_json::to_value(reason).unwrap();
+        let reason = reason.as_str().unwrap_or_default();
+        match self.suspended_ticks {
+            Some(suspended_ticks) => format!(
+                "{} (ran {} since last stop, was suspended {})",
+                reason,
+                ticks(self.ran_ticks),
+                ticks(suspended_ticks)
+            ),
+            None => format!("{} (ran {} since launch)", reason, ticks(self.ran_ticks)),
+        }
+    }
+}
+
+fn ticks(ticks: i32) -> String {
+    if ticks == 1 {
+        "1 tick".to_string()
+    } else {
+        format!("{} ticks", ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests;
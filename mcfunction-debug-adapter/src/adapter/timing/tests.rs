@@ -0,0 +1,99 @@
+use super::*;
+
+#[test]
+fn test_first_stop() {
+    // given:
+    let current = SessionTicks {
+        total: 5,
+        suspended: 0,
+    };
+
+    // when:
+    let timing = Timing::new(None, current);
+
+    // then:
+    assert_eq!(
+        timing,
+        Timing {
+            session_ticks: 5,
+            ran_ticks: 5,
+            suspended_ticks: None,
+        }
+    );
+    assert_eq!(
+        timing.describe(&StoppedEventReason::Breakpoint),
+        "breakpoint (ran 5 ticks since launch)"
+    );
+}
+
+#[test]
+fn test_subsequent_stop() {
+    // given:
+    let previous = SessionTicks {
+        total: 5,
+        suspended: 0,
+    };
+    let current = SessionTicks {
+        total: 128,
+        suspended: 120,
+    };
+
+    // when:
+    let timing = Timing::new(Some(previous), current);
+
+    // then:
+    assert_eq!(
+        timing,
+        Timing {
+            session_ticks: 128,
+            ran_ticks: 3,
+            suspended_ticks: Some(120),
+        }
+    );
+    assert_eq!(
+        timing.describe(&StoppedEventReason::Step),
+        "step (ran 3 ticks since last stop, was suspended 120 ticks)"
+    );
+}
+
+#[test]
+fn test_stop_in_same_tick() {
+    // given:
+    let previous = SessionTicks {
+        total: 5,
+        suspended: 0,
+    };
+    let current = SessionTicks {
+        total: 6,
+        suspended: 1,
+    };
+
+    // when:
+    let timing = Timing::new(Some(previous), current);
+
+    // then:
+    assert_eq!(
+        timing.describe(&StoppedEventReason::Step),
+        "step (ran 0 ticks since last stop, was suspended 1 tick)"
+    );
+}
+
+#[test]
+fn test_clamped_counters_do_not_go_negative() {
+    // given:
+    let previous = SessionTicks {
+        total: i32::MAX,
+        suspended: 10,
+    };
+    let current = SessionTicks {
+        total: i32::MAX,
+        suspended: 20,
+    };
+
+    // when:
+    let timing = Timing::new(Some(previous), current);
+
+    // then:
+    assert_eq!(timing.ran_ticks, 0);
+    assert_eq!(timing.suspended_ticks, Some(10));
+}
@@ -17,33 +17,63 @@
 // If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-    adapter::{MinecraftSession, LISTENER_NAME},
+    adapter::{generator::GeneratorCommand, snbt::parse_snbt, MinecraftSession, LISTENER_NAME},
     error::PartialErrorResponse,
 };
 use debug_adapter_protocol::{
     events::StoppedEventReason,
-    types::{Source, StackFrame},
+    requests::{VariablesFilter, VariablesRequestArguments},
+    types::{Source, StackFrame, StackFramePresentationHint},
 };
 use futures::Stream;
 use mcfunction_debugger::{
+    archive::{
+        archive_entry_path, is_archive, join_datapack_path, list_archive, physical_path,
+        read_to_string, split_archive_path,
+    },
     config::{
         adapter::{
             AdapterConfig, BreakpointKind, BreakpointPositionInLine, LocalBreakpoint,
-            LocalBreakpointPosition,
+            LocalBreakpointPosition, SuspendSchedules,
         },
-        Config,
+        Config, MinecraftVersion, DEFAULT_MAX_COMMANDS,
     },
     generate_debug_datapack,
-    parser::command::resource_location::ResourceLocation,
+    parser::{
+        command::{
+            resource_location::{highlight_invalid_char, ResourceLocation},
+            CommandParser,
+        },
+        parse_line, Line,
+    },
+    sink::WriteFailures,
+    source_map::{SourceMap, SOURCE_MAP_PATH},
+    summary::GeneratedSummary,
     StoppedReason,
 };
 use minect::{command::SummonNamedEntityOutput, log::LogEvent};
 use multimap::MultiMap;
-use std::{fmt::Display, path::Path, str::FromStr};
-use tokio::fs::remove_dir_all;
+use serde_json::Value;
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap, HashSet},
+    fmt::Display,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::SystemTime,
+};
+use tokio::{
+    fs::{metadata, read_dir},
+    task::spawn,
+};
 use tokio_stream::StreamExt;
 
 pub fn parse_function_path(path: &Path) -> Result<(&Path, ResourceLocation), String> {
+    if let Some((archive, entry)) = split_archive_path(path) {
+        return parse_archive_function_path(path, archive, &entry);
+    }
     let datapack = find_parent_datapack(path).ok_or_else(|| {
         format!(
             "does not denote a path in a datapack directory with a pack.mcmeta file: {}",
@@ -61,6 +91,30 @@ pub fn parse_function_path(path: &Path) -> Result<(&Path, ResourceLocation), Str
     Ok((datapack, function))
 }
 
+/// Like [parse_function_path] for a virtual path of the form
+/// `<pack.zip>!/data/ns/functions/foo.mcfunction`, see [mcfunction_debugger::archive].
+fn parse_archive_function_path<'p>(
+    path: &Path,
+    archive: &'p Path,
+    entry: &str,
+) -> Result<(&'p Path, ResourceLocation), String> {
+    if !archive.is_file() {
+        return Err(format!(
+            "does not denote a path in an existing zip file: {}",
+            &path.display()
+        ));
+    }
+    let data_path = entry.strip_prefix("data/").ok_or_else(|| {
+        format!(
+            "does not denote a path in the data directory of datapack {}: {}",
+            &archive.display(),
+            &path.display()
+        )
+    })?;
+    let function = get_function_name(data_path, path)?;
+    Ok((archive, function))
+}
+
 pub fn find_parent_datapack(mut path: &Path) -> Option<&Path> {
     while let Some(p) = path.parent() {
         path = p;
@@ -96,17 +150,459 @@ pub fn get_function_name(
         .to_str()
         .unwrap() // Path is known to be UTF-8
         .replace(std::path::MAIN_SEPARATOR, "/");
+    let name = format!("{}:{}", namespace, fn_path);
+    if let Some(highlighted) = highlight_invalid_char(&name) {
+        return Err(format!(
+            "denotes a function with an invalid name that Minecraft does not load: {}",
+            highlighted
+        ));
+    }
     Ok(ResourceLocation::new(&namespace, &fn_path))
 }
 
+/// Returns the namespaces of the datapack that contain a functions directory.
+pub async fn find_function_namespaces(datapack: &Path) -> io::Result<BTreeSet<String>> {
+    if is_archive(datapack) {
+        let entries = list_archive(datapack)?;
+        return Ok(entries
+            .iter()
+            .filter_map(|entry| split_function_entry(entry))
+            .map(|(namespace, _)| namespace.to_string())
+            .collect());
+    }
+    let mut namespaces = BTreeSet::new();
+    let mut entries = read_dir(datapack.join("data")).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.path().join("functions").is_dir() {
+            namespaces.insert(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(namespaces)
+}
+
+/// Returns the namespaces of all the datapacks that contain a functions directory.
+pub(crate) async fn find_all_function_namespaces(
+    datapacks: &[PathBuf],
+) -> io::Result<BTreeSet<String>> {
+    let mut namespaces = BTreeSet::new();
+    for datapack in datapacks {
+        namespaces.extend(find_function_namespaces(datapack).await?);
+    }
+    Ok(namespaces)
+}
+
+/// Hashes the content of a function file, so the function can be recognized after its file was
+/// renamed or moved.
+pub(crate) async fn hash_function_file(path: impl AsRef<Path>) -> io::Result<u64> {
+    let content = read_to_string(path).await?;
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// Returns all functions of the datapack whose file content has the given hash, see
+/// [hash_function_file].
+pub(crate) async fn find_functions_with_hash(
+    datapack: &Path,
+    hash: u64,
+) -> io::Result<Vec<(ResourceLocation, PathBuf)>> {
+    let mut functions = Vec::new();
+    for (function, path) in find_functions(datapack).await? {
+        if hash_function_file(&path).await? == hash {
+            functions.push((function, path));
+        }
+    }
+    Ok(functions)
+}
+
+/// Returns all functions of the datapack that Minecraft loads, sorted by name.
+pub(crate) async fn find_functions(
+    datapack: &Path,
+) -> io::Result<Vec<(ResourceLocation, PathBuf)>> {
+    if is_archive(datapack) {
+        let mut functions = list_archive(datapack)?
+            .into_iter()
+            .filter_map(|entry| {
+                // Files with an invalid name are not loaded by Minecraft, so they are skipped.
+                let (namespace, path) = split_function_entry(&entry)?;
+                let function = get_function_name(Path::new(namespace).join(path), &entry).ok()?;
+                Some((function, archive_entry_path(datapack, &entry)))
+            })
+            .collect::<Vec<_>>();
+        functions.sort();
+        return Ok(functions);
+    }
+    let data_path = datapack.join("data");
+    let mut dirs = find_function_namespaces(datapack)
+        .await?
+        .into_iter()
+        .map(|namespace| data_path.join(namespace).join("functions"))
+        .collect::<Vec<_>>();
+    let mut functions = Vec::new();
+    while let Some(dir) = dirs.pop() {
+        let mut entries = read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                dirs.push(path);
+            } else if path.extension().map_or(false, |it| it == "mcfunction") {
+                // Unwrap is safe, because the path is in the data directory.
+                // Files with an invalid name are not loaded by Minecraft, so they are skipped.
+                if let Ok(function) =
+                    get_function_name(path.strip_prefix(&data_path).unwrap(), &path)
+                {
+                    functions.push((function, path));
+                }
+            }
+        }
+    }
+    functions.sort();
+    Ok(functions)
+}
+
+/// Splits the name of a zip entry of the form `data/<namespace>/functions/<path>.mcfunction` into
+/// the namespace and `functions/<path>.mcfunction`.
+fn split_function_entry(entry: &str) -> Option<(&str, &str)> {
+    let (namespace, path) = entry.strip_prefix("data/")?.split_once('/')?;
+    Some((namespace, path))
+        .filter(|_| path.starts_with("functions/") && path.ends_with(".mcfunction"))
+}
+
+/// Returns the line numbers of the `# breakpoint` comments in the function file.
+pub(crate) async fn find_breakpoint_comments(
+    parser: &CommandParser,
+    path: impl AsRef<Path>,
+) -> io::Result<Vec<usize>> {
+    let content = read_to_string(path).await?;
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| matches!(parse_line(parser, line, true), Line::Breakpoint))
+        .map(|(line_index, _)| line_index + 1)
+        .collect())
+}
+
+/// Returns the pack_format of the datapack's pack.mcmeta or None if it can't be read.
+pub async fn read_pack_format(datapack: &Path) -> Option<u64> {
+    let pack_mcmeta = read_to_string(join_datapack_path(datapack, "pack.mcmeta"))
+        .await
+        .ok()?;
+    let pack_mcmeta = serde_json::from_str::<Value>(&pack_mcmeta).ok()?;
+    pack_mcmeta.pointer("/pack/pack_format")?.as_u64()
+}
+
+/// The text that precedes the version in the line that Minecraft logs when a (integrated) server
+/// starts.
+const SERVER_VERSION_MARKER: &str = "minecraft server version ";
+
+/// Returns the version of the Minecraft server that was started last according to its log.
+pub(crate) fn find_minecraft_version(log: &str) -> Option<MinecraftVersion> {
+    let start = log.rfind(SERVER_VERSION_MARKER)? + SERVER_VERSION_MARKER.len();
+    log[start..].split_whitespace().next()?.parse().ok()
+}
+
+/// The first version of each range of Minecraft versions that use the same pack_format. Versions
+/// after the last known range have no pack_format.
+const PACK_FORMATS: [(MinecraftVersion, Option<u64>); 13] = [
+    (MinecraftVersion::new(1, 17, 0), Some(7)),
+    (MinecraftVersion::new(1, 18, 0), Some(8)),
+    (MinecraftVersion::new(1, 18, 2), Some(9)),
+    (MinecraftVersion::new(1, 19, 0), Some(10)),
+    (MinecraftVersion::new(1, 19, 4), Some(12)),
+    (MinecraftVersion::new(1, 20, 0), Some(15)),
+    (MinecraftVersion::new(1, 20, 2), Some(18)),
+    (MinecraftVersion::new(1, 20, 3), Some(26)),
+    (MinecraftVersion::new(1, 20, 5), Some(41)),
+    (MinecraftVersion::new(1, 21, 0), Some(48)),
+    (MinecraftVersion::new(1, 21, 2), Some(57)),
+    (MinecraftVersion::new(1, 21, 4), Some(61)),
+    (MinecraftVersion::new(1, 21, 5), None),
+];
+
+/// Returns the pack_format of datapacks for the Minecraft version or None if it is not known.
+pub(crate) fn pack_format_of(version: MinecraftVersion) -> Option<u64> {
+    PACK_FORMATS
+        .iter()
+        .rev()
+        .find(|(first_version, _)| *first_version <= version)
+        .and_then(|(_, pack_format)| *pack_format)
+}
+
+/// Returns a warning if the pack_format of the datapack does not match the version of the
+/// Minecraft server that was started last according to Minecraft's log file. Nothing is checked if
+/// the log file, the pack_format or the pack_format of the Minecraft version is unknown.
+pub(crate) async fn check_pack_format(
+    datapack: &Path,
+    minecraft_log_file: &Path,
+) -> Option<String> {
+    let log = tokio::fs::read(minecraft_log_file).await.ok()?;
+    let version = find_minecraft_version(&String::from_utf8_lossy(&log))?;
+    let expected = pack_format_of(version)?;
+    let actual = read_pack_format(datapack).await?;
+    if actual == expected {
+        return None;
+    }
+    Some(format!(
+        "The datapack has pack_format {}, but Minecraft {} uses pack_format {}. Minecraft may \
+        not load the datapack as expected and breakpoints may never be hit.\n",
+        actual, version, expected
+    ))
+}
+
+/// The maximum number of files that are listed when writing the debug datapack failed.
+const MAX_LISTED_WRITE_FAILURES: usize = 10;
+
+fn describe_generate_error(error: &io::Error) -> String {
+    let failures = match WriteFailures::find(error) {
+        Some(write_failures) => &write_failures.failures,
+        None => return error.to_string(),
+    };
+    let mut description = format!("Failed to write {} files:", failures.len());
+    for failure in failures.iter().take(MAX_LISTED_WRITE_FAILURES) {
+        description.push_str(&format!("\n{}", failure.error));
+    }
+    if failures.len() > MAX_LISTED_WRITE_FAILURES {
+        description.push_str(&format!(
+            "\n... and {} more",
+            failures.len() - MAX_LISTED_WRITE_FAILURES
+        ));
+    }
+    description
+}
+
 pub(super) async fn generate_datapack(
-    minecraft_session: &MinecraftSession,
+    minecraft_session: &mut MinecraftSession,
+    parser: &Arc<CommandParser>,
     breakpoints: &MultiMap<ResourceLocation, LocalBreakpoint>,
     temporary_breakpoints: &MultiMap<ResourceLocation, LocalBreakpoint>,
 ) -> Result<(), PartialErrorResponse> {
-    let mut breakpoints = breakpoints.clone();
+    // Files must not change while Minecraft may still be reloading them
+    minecraft_session.await_all_applied().await;
+
+    let breakpoints = merge_breakpoints(breakpoints, temporary_breakpoints);
+    let generated_at = SystemTime::now();
+    // If the request is cancelled while the files are written, the datapack must not be
+    // considered up to date
+    minecraft_session.generated = None;
+
+    let task = GenerateTask {
+        datapack: minecraft_session.datapack.clone(),
+        output_path: minecraft_session.output_path.clone(),
+        namespace: minecraft_session.namespace.clone(),
+        exclude: minecraft_session.exclude.clone(),
+        additional_datapacks: minecraft_session.additional_datapacks.clone(),
+        breakpoints: breakpoints.clone(),
+        suspend_schedules: minecraft_session.suspend_schedules,
+        generator: minecraft_session.generator.clone(),
+        parser: parser.clone(),
+    };
+    task.config().validate().map_err(|e| {
+        PartialErrorResponse::new(format!("Invalid debug datapack configuration: {}", e))
+    })?;
+    // The task keeps running if the request is cancelled, so the next generation waits for its
+    // files instead of writing them at the same time
+    let lock = minecraft_session.generation_lock.clone().lock_owned().await;
+    let summary = spawn(async move {
+        let summary = task.run().await;
+        drop(lock);
+        summary
+    })
+    .await
+    .map_err(io::Error::from)
+    .and_then(|summary| summary)
+    .map_err(|e| {
+        PartialErrorResponse::new(format!(
+            "Failed to generate debug datapack: {}",
+            describe_generate_error(&e)
+        ))
+    })?;
+
+    let source_maps = read_source_maps(
+        &minecraft_session.output_path,
+        &minecraft_session.namespace,
+        summary.functions.iter().map(|it| it.name.as_str()),
+    )
+    .await;
+    minecraft_session.generated = Some(GeneratedData {
+        breakpoints,
+        generated_at,
+        oversized_functions: summary.oversized_functions.into_iter().collect(),
+        excluded_functions: summary.excluded_functions.into_iter().collect(),
+        warnings: summary.warnings.iter().map(ToString::to_string).collect(),
+        largest_partition: summary.largest_partition,
+        deferred_load: summary.deferred_load,
+        objectives: summary.objectives.into_iter().collect(),
+        source_maps,
+    });
+    // Generating the datapack removed the objectives created at runtime
+    minecraft_session.dynamic_objectives.clear();
+    minecraft_session.unloaded_log_scores = None;
+    Ok(())
+}
+
+/// The inputs of a generation that are owned, so it can run in a separate task.
+struct GenerateTask {
+    datapack: PathBuf,
+    output_path: PathBuf,
+    namespace: String,
+    exclude: Vec<String>,
+    additional_datapacks: Vec<PathBuf>,
+    breakpoints: MultiMap<ResourceLocation, LocalBreakpoint>,
+    suspend_schedules: SuspendSchedules,
+    generator: Option<GeneratorCommand>,
+    parser: Arc<CommandParser>,
+}
+
+impl GenerateTask {
+    fn config(&self) -> Config<'_> {
+        Config {
+            namespace: &self.namespace,
+            shadow: false,
+            exclude: &self.exclude,
+            excluded_functions: BTreeSet::new(),
+            skip_invalid_functions: true,
+            additional_datapacks: &self.additional_datapacks,
+            max_commands: DEFAULT_MAX_COMMANDS,
+            adapter: Some(AdapterConfig {
+                adapter_listener_name: LISTENER_NAME,
+                breakpoints: &self.breakpoints,
+                suspend_schedules: self.suspend_schedules,
+            }),
+        }
+    }
+
+    async fn run(&self) -> io::Result<GeneratedSummary> {
+        let config = self.config();
+        match &self.generator {
+            Some(generator) => {
+                generator
+                    .run(&self.datapack, &self.output_path, &config)
+                    .await
+            }
+            None => {
+                generate_debug_datapack(&self.datapack, &self.output_path, &self.parser, &config)
+                    .await
+            }
+        }
+    }
+}
+
+/// What the debug datapack was last generated from.
+pub(crate) struct GeneratedData {
+    pub(crate) breakpoints: MultiMap<ResourceLocation, LocalBreakpoint>,
+    pub(crate) generated_at: SystemTime,
+    /// Functions that Minecraft may not run, because they contain too many commands.
+    pub(crate) oversized_functions: HashSet<String>,
+    /// Functions that are not debugged, because they are marked with `# mcfd:skip`.
+    pub(crate) excluded_functions: HashSet<String>,
+    /// Problems of the debug datapack that don't prevent debugging, see
+    /// [mcfunction_debugger::summary::GeneratedSummary::warnings].
+    pub(crate) warnings: Vec<String>,
+    /// The largest number of commands Minecraft has to execute without suspending.
+    pub(crate) largest_partition: usize,
+    /// Whether the functions of `#minecraft:load` only run when `debug:run_load` is called.
+    pub(crate) deferred_load: bool,
+    /// The scoreboard objectives whose scores are logged by `-ns-:log_scores`.
+    pub(crate) objectives: BTreeSet<String>,
+    /// The source maps of all debugged functions by function name.
+    pub(crate) source_maps: HashMap<String, SourceMap>,
+}
+impl GeneratedData {
+    /// Returns true if the debug datapack would not change by generating it with `breakpoints` for
+    /// `function`, because neither the breakpoints of the function nor its file were changed.
+    pub(crate) async fn is_up_to_date(
+        &self,
+        function: &ResourceLocation,
+        breakpoints: &MultiMap<ResourceLocation, LocalBreakpoint>,
+        path: impl AsRef<Path>,
+    ) -> bool {
+        fn as_set<'l>(
+            breakpoints: &'l MultiMap<ResourceLocation, LocalBreakpoint>,
+            function: &ResourceLocation,
+        ) -> HashSet<&'l LocalBreakpoint> {
+            breakpoints
+                .get_vec(function)
+                .into_iter()
+                .flatten()
+                .collect()
+        }
+        as_set(&self.breakpoints, function) == as_set(breakpoints, function)
+            && !is_modified_since(path, self.generated_at).await
+    }
+
+    pub(crate) fn is_oversized(&self, function: &ResourceLocation) -> bool {
+        self.oversized_functions.contains(&function.to_string())
+    }
+
+    pub(crate) fn is_excluded(&self, function: &ResourceLocation) -> bool {
+        self.excluded_functions.contains(&function.to_string())
+    }
+
+    /// Returns true if the line of the function was changed on disk since the debug datapack was
+    /// generated, so Minecraft executes a different line than the one at `line_number` in
+    /// `content`.
+    pub(crate) fn is_source_changed(
+        &self,
+        function: &ResourceLocation,
+        line_number: usize,
+        content: &str,
+    ) -> bool {
+        self.source_maps
+            .get(&function.to_string())
+            .is_some_and(|source_map| source_map.is_changed(line_number, content))
+    }
+
+    pub(crate) fn contains_temporary_breakpoints(&self) -> bool {
+        self.breakpoints
+            .iter_all()
+            .flat_map(|(_function, breakpoints)| breakpoints)
+            .any(|breakpoint| breakpoint.kind == BreakpointKind::Temporary)
+    }
+}
+
+/// Reads the source maps of the generated functions, see [SOURCE_MAP_PATH]. Functions without a
+/// readable source map are left out.
+async fn read_source_maps(
+    output_path: &Path,
+    namespace: &str,
+    functions: impl Iterator<Item = &str>,
+) -> HashMap<String, SourceMap> {
+    let mut source_maps = HashMap::new();
+    for function in functions {
+        if let Some((orig_ns, orig_fn)) = function.split_once(':') {
+            let path = SOURCE_MAP_PATH
+                .replace("-ns-", namespace)
+                .replace("-orig_ns-", orig_ns)
+                .replace("-orig/fn-", orig_fn);
+            let source_map = read_to_string(output_path.join(path))
+                .await
+                .ok()
+                .and_then(|it| serde_json::from_str(&it).ok());
+            if let Some(source_map) = source_map {
+                source_maps.insert(function.to_string(), source_map);
+            }
+        }
+    }
+    source_maps
+}
+
+/// Returns true if the file was modified since `time` or if its modification time can't be read.
+pub(crate) async fn is_modified_since(path: impl AsRef<Path>, time: SystemTime) -> bool {
+    match metadata(physical_path(path.as_ref()))
+        .await
+        .and_then(|metadata| metadata.modified())
+    {
+        Ok(modified) => modified >= time,
+        Err(_) => true,
+    }
+}
 
-    // Add all generated breakpoints that are not at the same position as user breakpoints
+/// Adds all temporary breakpoints that are not at the same position as user breakpoints.
+pub(crate) fn merge_breakpoints(
+    breakpoints: &MultiMap<ResourceLocation, LocalBreakpoint>,
+    temporary_breakpoints: &MultiMap<ResourceLocation, LocalBreakpoint>,
+) -> MultiMap<ResourceLocation, LocalBreakpoint> {
+    let mut breakpoints = breakpoints.clone();
     for (key, values) in temporary_breakpoints.iter_all() {
         for value in values {
             if !contains_breakpoint(
@@ -117,24 +613,7 @@ pub(super) async fn generate_datapack(
             }
         }
     }
-
-    let config = Config {
-        namespace: &minecraft_session.namespace,
-        shadow: false,
-        adapter: Some(AdapterConfig {
-            adapter_listener_name: LISTENER_NAME,
-            breakpoints: &breakpoints,
-        }),
-    };
-    let _ = remove_dir_all(&minecraft_session.output_path).await;
-    generate_debug_datapack(
-        &minecraft_session.datapack,
-        &minecraft_session.output_path,
-        &config,
-    )
-    .await
-    .map_err(|e| PartialErrorResponse::new(format!("Failed to generate debug datapack: {}", e)))?;
-    Ok(())
+    breakpoints
 }
 
 pub(crate) fn can_resume_from(
@@ -179,7 +658,7 @@ pub(crate) fn events_between<'l>(
         .skip(1) // Skip start tag
         .take_while(move |event| !is_summon_output(event, stop))
 }
-fn is_summon_output(event: &LogEvent, name: &str) -> bool {
+pub(crate) fn is_summon_output(event: &LogEvent, name: &str) -> bool {
     event.executor == LISTENER_NAME
         && event
             .output
@@ -241,9 +720,220 @@ impl Display for BreakpointPosition {
     }
 }
 
+/// The output of the command `tag <entity> list`, for example:
+/// ```none
+/// Sheep has 2 tags: my_tag, other_tag
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct TagListOutput {
+    /// The custom name or type of the entity.
+    pub(crate) entity: String,
+    pub(crate) tags: Vec<String>,
+}
+impl FromStr for TagListOutput {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        fn from_str_inner(string: &str) -> Option<TagListOutput> {
+            if let Some(entity) = string.strip_suffix(" has no tags") {
+                return Some(TagListOutput {
+                    entity: entity.to_string(),
+                    tags: Vec::new(),
+                });
+            }
+            let (prefix, tags) = string.rsplit_once(" tags: ")?;
+            let (entity, count) = prefix.rsplit_once(" has ")?;
+            let count = count.parse::<usize>().ok()?;
+            let tags = tags.split(", ").map(str::to_string).collect::<Vec<_>>();
+            if tags.len() != count {
+                return None;
+            }
+            Some(TagListOutput {
+                entity: entity.to_string(),
+                tags,
+            })
+        }
+        from_str_inner(string).ok_or(())
+    }
+}
+
+/// The output of the command `data get entity <entity>`, for example:
+/// ```none
+/// Sheep has the following entity data: {Health: 8.0f, Tags: ["test"]}
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct EntityDataOutput {
+    /// The custom name or type of the entity.
+    pub(crate) entity: String,
+    pub(crate) data: Value,
+}
+impl FromStr for EntityDataOutput {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        fn from_str_inner(string: &str) -> Option<EntityDataOutput> {
+            let (entity, data) = string.split_once(" has the following entity data: ")?;
+            let data = parse_snbt(data).ok()?;
+            Some(EntityDataOutput {
+                entity: entity.to_string(),
+                data,
+            })
+        }
+        from_str_inner(string).ok_or(())
+    }
+}
+
+/// The output of the command `time query <gametime|daytime>`, for example:
+/// ```none
+/// The time is 6000
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct TimeQueryOutput {
+    pub(crate) time: i64,
+}
+impl FromStr for TimeQueryOutput {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let time = string.strip_prefix("The time is ").ok_or(())?;
+        let time = time.parse().map_err(|_| ())?;
+        Ok(TimeQueryOutput { time })
+    }
+}
+
+/// The output of the command `scoreboard objectives list`, for example:
+/// ```none
+/// There are 2 objective(s): [my_objective], [other_objective]
+/// ```
+/// Minecraft lists the display names of the objectives, which are the names unless a display name
+/// was set.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct ObjectivesListOutput {
+    pub(crate) objectives: Vec<String>,
+}
+impl FromStr for ObjectivesListOutput {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        fn from_str_inner(string: &str) -> Option<ObjectivesListOutput> {
+            if string == "There are no objectives" {
+                return Some(ObjectivesListOutput {
+                    objectives: Vec::new(),
+                });
+            }
+            let string = string.strip_prefix("There are ")?;
+            let (count, objectives) = string.split_once(" objective(s): ")?;
+            let count = count.parse::<usize>().ok()?;
+            let objectives = objectives
+                .strip_prefix('[')?
+                .strip_suffix(']')?
+                .split("], [")
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            if objectives.len() != count {
+                return None;
+            }
+            Some(ObjectivesListOutput { objectives })
+        }
+        from_str_inner(string).ok_or(())
+    }
+}
+
+/// The difficulties in the order of their ids.
+const DIFFICULTIES: [&str; 4] = ["Peaceful", "Easy", "Normal", "Hard"];
+
+/// The output of the command `difficulty`, for example:
+/// ```none
+/// The difficulty is Normal
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct DifficultyOutput {
+    /// The id of the difficulty from 0 (peaceful) to 3 (hard).
+    pub(crate) difficulty: usize,
+}
+impl FromStr for DifficultyOutput {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let difficulty = string.strip_prefix("The difficulty is ").ok_or(())?;
+        let difficulty = DIFFICULTIES
+            .iter()
+            .position(|it| *it == difficulty)
+            .ok_or(())?;
+        Ok(DifficultyOutput { difficulty })
+    }
+}
+
+/// The output of the command `gamerule <rule>`, for example:
+/// ```none
+/// Gamerule doDaylightCycle is currently set to: true
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct GameruleOutput {
+    pub(crate) gamerule: String,
+    pub(crate) value: String,
+}
+impl FromStr for GameruleOutput {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.strip_prefix("Gamerule ").ok_or(())?;
+        let (gamerule, value) = string.split_once(" is currently set to: ").ok_or(())?;
+        Ok(GameruleOutput {
+            gamerule: gamerule.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// The state of the world shown in the Globals scope. It is the same for all frames and queried
+/// once per stop.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct GlobalsScope {
+    pub(crate) gametime: Option<i64>,
+    pub(crate) daytime: Option<i64>,
+    pub(crate) raining: bool,
+    pub(crate) thundering: bool,
+    pub(crate) difficulty: Option<usize>,
+    /// The values of [GAMERULES] in the same order.
+    pub(crate) gamerules: Vec<(String, String)>,
+}
+impl GlobalsScope {
+    pub(crate) fn weather(&self) -> &'static str {
+        if self.thundering {
+            "thunder"
+        } else if self.raining {
+            "rain"
+        } else {
+            "clear"
+        }
+    }
+}
+
+/// The game rules shown in the Globals scope. The rules changed by
+/// [minect::command::enable_logging_command] are left out, because they would not show the values
+/// of the world.
+pub(crate) const GAMERULES: [&str; 4] = [
+    "doDaylightCycle",
+    "doWeatherCycle",
+    "maxCommandChainLength",
+    "randomTickSpeed",
+];
+
 pub(crate) struct StoppedData {
     pub(crate) position: BreakpointPosition,
     pub(crate) stack_trace: Vec<McfunctionStackFrame>,
+    /// The executors that did not run the suspended function yet, once they were queried.
+    pub(crate) pending_executors: Option<Vec<PendingExecutor>>,
+}
+
+/// An executor of the suspended function that did not run it yet. Minecraft runs the executors of
+/// a function one after another in the order of their iteration.
+#[derive(Clone, Debug)]
+pub(crate) struct PendingExecutor {
+    /// The `-ns-_iter` score of the context entity, which is also the id of its thread.
+    pub(crate) iteration: i32,
+    pub(crate) name: String,
 }
 
 pub(crate) struct StoppedEvent {
@@ -274,6 +964,12 @@ pub(crate) fn to_stopped_event_reason(reason: StoppedReason) -> StoppedEventReas
 pub(crate) struct McfunctionStackFrame {
     pub(crate) id: i32,
     pub(crate) location: SourceLocation,
+    /// Whether this frame is executed by the server rather than an entity.
+    pub(crate) server_context: bool,
+    /// The dimension this frame is executed in, if it is one of [DIMENSIONS].
+    pub(crate) dimension: Option<&'static str>,
+    /// The column after the command that is executed next or the function call of a caller.
+    pub(crate) end_column: Option<usize>,
 }
 impl McfunctionStackFrame {
     pub(crate) fn to_stack_frame(
@@ -281,23 +977,56 @@ impl McfunctionStackFrame {
         datapack: impl AsRef<Path>,
         line_offset: usize,
         column_offset: usize,
+        source_changed: bool,
     ) -> StackFrame {
-        let path = datapack
-            .as_ref()
-            .join("data")
-            .join(self.location.function.mcfunction_path())
-            .display()
-            .to_string();
+        let path = join_datapack_path(
+            datapack,
+            Path::new("data").join(self.location.function.mcfunction_path()),
+        )
+        .display()
+        .to_string();
+        // The line Minecraft executes is no longer in the file, so the line would be misleading
+        let (name, presentation_hint) = if source_changed {
+            let name = format!("{} (source changed)", self.get_name());
+            (name, Some(StackFramePresentationHint::Subtle))
+        } else {
+            (self.get_name(), None)
+        };
         StackFrame::builder()
             .id(self.id)
-            .name(self.location.get_name())
+            .name(name)
             .source(Some(Source::builder().path(Some(path)).build()))
             .line((self.location.line_number - line_offset) as i32)
             .column((self.location.column_number - column_offset) as i32)
+            .end_line(
+                self.end_column
+                    .map(|_| (self.location.line_number - line_offset) as i32),
+            )
+            .end_column(
+                self.end_column
+                    .map(|end_column| (end_column - column_offset) as i32),
+            )
+            .presentation_hint(presentation_hint)
             .build()
     }
+
+    fn get_name(&self) -> String {
+        let name = self.location.get_name();
+        if let Some(dimension) = self.dimension {
+            format!("{} in {}", name, dimension)
+        } else {
+            name
+        }
+    }
 }
 
+/// The dimensions that are reported in the stack trace.
+pub(crate) const DIMENSIONS: [&str; 3] = [
+    "minecraft:overworld",
+    "minecraft:the_nether",
+    "minecraft:the_end",
+];
+
 #[derive(Clone, Debug)]
 pub(crate) struct SourceLocation {
     pub(crate) function: ResourceLocation,
@@ -337,6 +1066,57 @@ impl SourceLocation {
     }
 }
 
+/// Returns the column after the command at the location or [None] if there is no command. Both the
+/// command that is executed next and the function call of a caller end with their line.
+pub(crate) async fn find_end_column(
+    path: impl AsRef<Path>,
+    location: &SourceLocation,
+) -> Option<usize> {
+    let content = read_to_string(path).await.ok()?;
+    let line = content
+        .split('\n')
+        .nth(location.line_number.checked_sub(1)?)?;
+    let end_column = line.trim_end().len() + 1;
+    Some(end_column).filter(|end_column| *end_column > location.column_number)
+}
+
+/// Whether a variable is a named child of its parent, like an entry of a compound, or an indexed
+/// child, like an element of a list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum VariableKind {
+    Named,
+    Indexed,
+}
+impl VariableKind {
+    fn matches(&self, filter: Option<&VariablesFilter>) -> bool {
+        match filter {
+            Some(VariablesFilter::Named) => *self == VariableKind::Named,
+            Some(VariablesFilter::Indexed) => *self == VariableKind::Indexed,
+            None => true,
+        }
+    }
+}
+
+/// Applies the `filter`, `start` and `count` of the request to the children of a variable.
+pub(crate) fn filter_variables<T>(
+    children: impl IntoIterator<Item = (VariableKind, T)>,
+    args: &VariablesRequestArguments,
+) -> Vec<T> {
+    // A count of 0 requests all remaining variables
+    let count = if args.count > 0 {
+        args.count as usize
+    } else {
+        usize::MAX
+    };
+    children
+        .into_iter()
+        .filter(|(kind, _)| kind.matches(args.filter.as_ref()))
+        .map(|(_, child)| child)
+        .skip(args.start.max(0) as usize)
+        .take(count)
+        .collect()
+}
+
 fn parse_resource_location(function: &str, seperator: char) -> Option<ResourceLocation> {
     if let [orig_ns, orig_fn @ ..] = function.split(seperator).collect::<Vec<_>>().as_slice() {
         Some(ResourceLocation::new(orig_ns, &orig_fn.join("/")))
@@ -344,3 +1124,6 @@ fn parse_resource_location(function: &str, seperator: char) -> Option<ResourceLo
         None
     }
 }
+
+#[cfg(test)]
+mod tests;
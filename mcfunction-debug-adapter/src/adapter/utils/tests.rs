@@ -0,0 +1,395 @@
+use super::*;
+use mcfunction_debugger::sink::WriteFailure;
+use std::fs::{create_dir_all, remove_dir_all, write};
+
+fn create_datapack(name: &str, functions: &[(&str, &str)]) -> PathBuf {
+    let datapack = std::env::temp_dir()
+        .join("mcfunction-debug-adapter-utils-test")
+        .join(name);
+    let _ = remove_dir_all(&datapack);
+    create_dir_all(&datapack).unwrap();
+    write(datapack.join("pack.mcmeta"), "{}").unwrap();
+    for (function, content) in functions {
+        let (namespace, path) = function.split_once(':').unwrap();
+        let path = datapack
+            .join("data")
+            .join(namespace)
+            .join("functions")
+            .join(format!("{}.mcfunction", path));
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, content).unwrap();
+    }
+    datapack
+}
+
+#[tokio::test]
+async fn test_find_functions_with_hash() {
+    // given:
+    let datapack = create_datapack(
+        "test_find_functions_with_hash",
+        &[
+            ("test:main", "say main"),
+            ("test:renamed/main", "say renamed"),
+            ("other:main", "say other"),
+        ],
+    );
+    let path = datapack.join("data/test/functions/renamed/main.mcfunction");
+    let hash = hash_function_file(&path).await.unwrap();
+
+    // when:
+    let actual = find_functions_with_hash(&datapack, hash).await.unwrap();
+
+    // then:
+    assert_eq!(
+        actual,
+        vec![(ResourceLocation::new("test", "renamed/main"), path)]
+    );
+}
+
+#[tokio::test]
+async fn test_find_breakpoint_comments() {
+    // given:
+    let datapack = create_datapack(
+        "test_find_breakpoint_comments",
+        &[(
+            "test:main",
+            "say one\n# breakpoint\nsay two\n# a comment\n# breakpoint\n",
+        )],
+    );
+    let path = datapack.join("data/test/functions/main.mcfunction");
+    let parser = CommandParser::default().unwrap();
+
+    // when:
+    let actual = find_breakpoint_comments(&parser, &path).await.unwrap();
+
+    // then:
+    assert_eq!(actual, vec![2, 5]);
+}
+
+#[test]
+fn test_get_function_name_with_uppercase_letter() {
+    // when:
+    let actual = get_function_name(
+        Path::new("test/functions/dir/MyFunc.mcfunction"),
+        "datapack/data/test/functions/dir/MyFunc.mcfunction",
+    );
+
+    // then:
+    let error = actual.unwrap_err();
+    assert!(
+        error.contains("test:dir/[M]yFunc"),
+        "Unexpected error: {}",
+        error
+    );
+}
+
+#[test]
+fn test_parse_function_path_in_zip() {
+    // given:
+    let dir = create_datapack("test_parse_function_path_in_zip", &[]);
+    let zip = dir.join("pack.zip");
+    write(&zip, "").unwrap();
+    let path = format!("{}!/data/test/functions/dir/main.mcfunction", zip.display());
+
+    // when:
+    let actual = parse_function_path(Path::new(&path));
+
+    // then:
+    let (datapack, function) = actual.unwrap();
+    assert_eq!(datapack, zip);
+    assert_eq!(function, ResourceLocation::new("test", "dir/main"));
+}
+
+#[test]
+fn test_parse_function_path_in_missing_zip() {
+    // given:
+    let dir = create_datapack("test_parse_function_path_in_missing_zip", &[]);
+    let path = format!(
+        "{}!/data/test/functions/main.mcfunction",
+        dir.join("pack.zip").display()
+    );
+
+    // when:
+    let actual = parse_function_path(Path::new(&path));
+
+    // then:
+    let error = actual.unwrap_err();
+    assert!(
+        error.starts_with("does not denote a path in an existing zip file"),
+        "Unexpected error: {}",
+        error
+    );
+}
+
+#[test]
+fn test_parse_tag_list_output() {
+    // when:
+    let actual = "Sheep has 2 tags: my_tag, mcfd_context".parse::<TagListOutput>();
+
+    // then:
+    assert_eq!(
+        actual,
+        Ok(TagListOutput {
+            entity: "Sheep".to_string(),
+            tags: vec!["my_tag".to_string(), "mcfd_context".to_string()],
+        })
+    );
+}
+
+#[test]
+fn test_parse_tag_list_output_without_tags() {
+    // when:
+    let actual = "Sheep has no tags".parse::<TagListOutput>();
+
+    // then:
+    assert_eq!(
+        actual,
+        Ok(TagListOutput {
+            entity: "Sheep".to_string(),
+            tags: vec![],
+        })
+    );
+}
+
+#[test]
+fn test_parse_objectives_list_output() {
+    // when:
+    let actual =
+        "There are 2 objective(s): [test_global], [test_dynamic]".parse::<ObjectivesListOutput>();
+
+    // then:
+    assert_eq!(
+        actual,
+        Ok(ObjectivesListOutput {
+            objectives: vec!["test_global".to_string(), "test_dynamic".to_string()],
+        })
+    );
+}
+
+#[test]
+fn test_parse_objectives_list_output_without_objectives() {
+    // when:
+    let actual = "There are no objectives".parse::<ObjectivesListOutput>();
+
+    // then:
+    assert_eq!(actual, Ok(ObjectivesListOutput { objectives: vec![] }));
+}
+
+#[test]
+fn test_parse_time_query_output() {
+    // when:
+    let actual = "The time is 24042".parse::<TimeQueryOutput>();
+
+    // then:
+    assert_eq!(actual, Ok(TimeQueryOutput { time: 24042 }));
+}
+
+#[test]
+fn test_parse_difficulty_output() {
+    // when:
+    let actual = "The difficulty is Hard".parse::<DifficultyOutput>();
+
+    // then:
+    assert_eq!(actual, Ok(DifficultyOutput { difficulty: 3 }));
+}
+
+#[test]
+fn test_parse_gamerule_output() {
+    // when:
+    let actual = "Gamerule doDaylightCycle is currently set to: false".parse::<GameruleOutput>();
+
+    // then:
+    assert_eq!(
+        actual,
+        Ok(GameruleOutput {
+            gamerule: "doDaylightCycle".to_string(),
+            value: "false".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_parse_entity_data_output() {
+    // when:
+    let actual = "Sheep has the following entity data: {Health: 8.0f, Tags: [\"test\"]}"
+        .parse::<EntityDataOutput>();
+
+    // then:
+    assert_eq!(
+        actual,
+        Ok(EntityDataOutput {
+            entity: "Sheep".to_string(),
+            data: serde_json::json!({"Health": 8.0, "Tags": ["test"]}),
+        })
+    );
+}
+
+fn mixed_variables() -> Vec<(VariableKind, &'static str)> {
+    vec![
+        (VariableKind::Named, "Health"),
+        (VariableKind::Indexed, "0"),
+        (VariableKind::Named, "Tags"),
+        (VariableKind::Indexed, "1"),
+    ]
+}
+
+#[test]
+fn test_filter_variables_without_filter() {
+    // given:
+    let args = VariablesRequestArguments::builder()
+        .variables_reference(1)
+        .build();
+
+    // when:
+    let actual = filter_variables(mixed_variables(), &args);
+
+    // then:
+    assert_eq!(actual, vec!["Health", "0", "Tags", "1"]);
+}
+
+#[test]
+fn test_filter_variables_named() {
+    // given:
+    let args = VariablesRequestArguments::builder()
+        .variables_reference(1)
+        .filter(Some(VariablesFilter::Named))
+        .build();
+
+    // when:
+    let actual = filter_variables(mixed_variables(), &args);
+
+    // then:
+    assert_eq!(actual, vec!["Health", "Tags"]);
+}
+
+#[test]
+fn test_filter_variables_indexed_with_paging() {
+    // given:
+    let args = VariablesRequestArguments::builder()
+        .variables_reference(1)
+        .filter(Some(VariablesFilter::Indexed))
+        .start(1)
+        .count(1)
+        .build();
+
+    // when:
+    let actual = filter_variables(mixed_variables(), &args);
+
+    // then:
+    assert_eq!(actual, vec!["1"]);
+}
+
+#[test]
+fn test_describe_generate_error_lists_at_most_10_write_failures() {
+    // given:
+    let failures = (0..12)
+        .map(|index| {
+            let path = PathBuf::from(format!("out/fn{}.mcfunction", index));
+            let error = io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{}: locked", path.display()),
+            );
+            WriteFailure { path, error }
+        })
+        .collect();
+    let error = io::Error::new(io::ErrorKind::PermissionDenied, WriteFailures { failures });
+
+    // when:
+    let actual = describe_generate_error(&error);
+
+    // then:
+    let mut expected = vec!["Failed to write 12 files:".to_string()];
+    expected.extend((0..10).map(|index| format!("out/fn{}.mcfunction: locked", index)));
+    expected.push("... and 2 more".to_string());
+    assert_eq!(actual, expected.join("\n"));
+}
+
+#[test]
+fn test_describe_generate_error_without_write_failures() {
+    // given:
+    let error = io::Error::new(io::ErrorKind::NotFound, "missing");
+
+    // when:
+    let actual = describe_generate_error(&error);
+
+    // then:
+    assert_eq!(actual, "missing");
+}
+
+#[test]
+fn test_find_minecraft_version() {
+    // given:
+    let log = "\
+[12:00:00] [Server thread/INFO]: Starting minecraft server version 1.19.4
+[12:05:00] [Server thread/INFO]: Stopping server
+[12:10:00] [Server thread/INFO]: Starting integrated minecraft server version 1.20.4
+[12:10:01] [Server thread/INFO]: Preparing level \"world\"
+";
+
+    // when:
+    let actual = find_minecraft_version(log);
+
+    // then:
+    assert_eq!(actual, Some(MinecraftVersion::new(1, 20, 4)));
+}
+
+#[test]
+fn test_pack_format_of() {
+    // given:
+    let versions = [
+        "1.16.5", "1.17", "1.18.1", "1.19.4", "1.20.2", "1.21.1", "1.21.4", "1.21.5",
+    ];
+
+    // when:
+    let actual = versions
+        .iter()
+        .map(|version| pack_format_of(version.parse().unwrap()))
+        .collect::<Vec<_>>();
+
+    // then:
+    assert_eq!(
+        actual,
+        vec![
+            None,
+            Some(7),
+            Some(8),
+            Some(12),
+            Some(18),
+            Some(48),
+            Some(61),
+            None
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_check_pack_format() {
+    // given:
+    let datapack = create_datapack("test_check_pack_format", &[]);
+    write(
+        datapack.join("pack.mcmeta"),
+        r#"{"pack":{"pack_format":15,"description":""}}"#,
+    )
+    .unwrap();
+    let log_file = datapack.join("latest.log");
+    let starting = "[Server thread/INFO]: Starting minecraft server version";
+
+    // when:
+    write(&log_file, format!("{} 1.20.1\n", starting)).unwrap();
+    let matching = check_pack_format(&datapack, &log_file).await;
+    write(&log_file, format!("{} 1.20.4\n", starting)).unwrap();
+    let mismatching = check_pack_format(&datapack, &log_file).await;
+    write(&log_file, format!("{} 1.99\n", starting)).unwrap();
+    let unknown = check_pack_format(&datapack, &log_file).await;
+
+    // then:
+    assert_eq!(matching, None);
+    assert_eq!(
+        mismatching.as_deref(),
+        Some(
+            "The datapack has pack_format 15, but Minecraft 1.20.4 uses pack_format 26. Minecraft \
+            may not load the datapack as expected and breakpoints may never be hit.\n"
+        )
+    );
+    assert_eq!(unknown, None);
+}
@@ -0,0 +1,91 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::{Duration, Instant};
+use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle, time::interval};
+
+/// Detects a lost connection to Minecraft, for example because the server was restarted. On every
+/// tick a probe command is sent unless the previous probe is still waiting for its response. If a
+/// probe is not answered within the timeout, the connection is considered lost.
+pub(crate) struct Watchdog {
+    timeout: Duration,
+    probe_sent_at: Option<Instant>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum WatchdogAction {
+    SendProbe,
+    Wait,
+    ConnectionLost,
+}
+
+impl Watchdog {
+    pub(crate) fn new(timeout: Duration) -> Watchdog {
+        Watchdog {
+            timeout,
+            probe_sent_at: None,
+        }
+    }
+
+    pub(crate) fn on_tick(&mut self, now: Instant) -> WatchdogAction {
+        match self.probe_sent_at {
+            None => {
+                self.probe_sent_at = Some(now);
+                WatchdogAction::SendProbe
+            }
+            Some(sent_at) if now.duration_since(sent_at) >= self.timeout => {
+                WatchdogAction::ConnectionLost
+            }
+            Some(_) => WatchdogAction::Wait,
+        }
+    }
+
+    pub(crate) fn on_response(&mut self) {
+        self.probe_sent_at = None;
+    }
+}
+
+/// Periodically sends a tick message to the adapter until it is dropped.
+pub(crate) struct WatchdogTicker(JoinHandle<()>);
+
+impl WatchdogTicker {
+    pub(crate) fn start<M: Send + 'static>(
+        period: Duration,
+        sender: UnboundedSender<M>,
+        tick: impl Fn() -> M + Send + 'static,
+    ) -> WatchdogTicker {
+        WatchdogTicker(tokio::spawn(async move {
+            let mut interval = interval(period);
+            loop {
+                interval.tick().await;
+                if sender.send(tick()).is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+}
+
+impl Drop for WatchdogTicker {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,58 @@
+use super::*;
+
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+#[test]
+fn test_first_tick_sends_probe() {
+    // given:
+    let mut watchdog = Watchdog::new(TIMEOUT);
+
+    // when:
+    let actual = watchdog.on_tick(Instant::now());
+
+    // then:
+    assert_eq!(actual, WatchdogAction::SendProbe);
+}
+
+#[test]
+fn test_tick_waits_for_pending_probe() {
+    // given:
+    let mut watchdog = Watchdog::new(TIMEOUT);
+    let start = Instant::now();
+    watchdog.on_tick(start);
+
+    // when:
+    let actual = watchdog.on_tick(start + TIMEOUT / 2);
+
+    // then:
+    assert_eq!(actual, WatchdogAction::Wait);
+}
+
+#[test]
+fn test_response_allows_next_probe() {
+    // given:
+    let mut watchdog = Watchdog::new(TIMEOUT);
+    let start = Instant::now();
+    watchdog.on_tick(start);
+    watchdog.on_response();
+
+    // when:
+    let actual = watchdog.on_tick(start + TIMEOUT * 2);
+
+    // then:
+    assert_eq!(actual, WatchdogAction::SendProbe);
+}
+
+#[test]
+fn test_unanswered_probe_loses_connection() {
+    // given:
+    let mut watchdog = Watchdog::new(TIMEOUT);
+    let start = Instant::now();
+    watchdog.on_tick(start);
+
+    // when:
+    let actual = watchdog.on_tick(start + TIMEOUT);
+
+    // then:
+    assert_eq!(actual, WatchdogAction::ConnectionLost);
+}
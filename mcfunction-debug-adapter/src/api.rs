@@ -17,27 +17,32 @@
 // If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
+    custom::CustomRequest,
     error::{PartialErrorResponse, RequestError},
-    get_command, Outbox,
+    get_command, CancelData, Outbox,
 };
 use async_trait::async_trait;
 use debug_adapter_protocol::{
-    events::Event,
+    events::{Event, ProgressEndEventBody},
     requests::{
-        ContinueRequestArguments, DisconnectRequestArguments, EvaluateRequestArguments,
+        BreakpointLocationsRequestArguments, ContinueRequestArguments, DisconnectRequestArguments,
+        EvaluateRequestArguments, GotoRequestArguments, GotoTargetsRequestArguments,
         InitializeRequestArguments, LaunchRequestArguments, NextRequestArguments,
         PauseRequestArguments, Request, ScopesRequestArguments, SetBreakpointsRequestArguments,
-        StackTraceRequestArguments, StepInRequestArguments, StepOutRequestArguments,
-        TerminateRequestArguments, VariablesRequestArguments,
+        StackTraceRequestArguments, StepInRequestArguments, StepInTargetsRequestArguments,
+        StepOutRequestArguments, TerminateRequestArguments, VariablesRequestArguments,
     },
     responses::{
-        ContinueResponseBody, ErrorResponse, ErrorResponseBody, EvaluateResponseBody,
-        ScopesResponseBody, SetBreakpointsResponseBody, StackTraceResponseBody, SuccessResponse,
-        ThreadsResponseBody, VariablesResponseBody,
+        BreakpointLocationsResponseBody, ContinueResponseBody, ErrorResponse, ErrorResponseBody,
+        EvaluateResponseBody, GotoTargetsResponseBody, LoadedSourcesResponseBody,
+        ScopesResponseBody, SetBreakpointsResponseBody, StackTraceResponseBody,
+        StepInTargetsResponseBody, SuccessResponse, ThreadsResponseBody, VariablesResponseBody,
     },
     types::Capabilities,
     SequenceNumber,
 };
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc::UnboundedReceiver;
 use typed_builder::TypedBuilder;
 
@@ -52,6 +57,11 @@ pub trait DebugAdapterContext {
 
     fn end_cancellable_progress(&mut self, progress_id: String, message: Option<String>);
 
+    /// Returns true if the client already sent another request with the same command that is
+    /// handled right after the current request, so work that only depends on the latest of these
+    /// requests can be left to it.
+    fn is_superseded(&self) -> bool;
+
     fn shutdown(&mut self);
 }
 
@@ -59,17 +69,20 @@ pub struct ProgressContext {
     pub progress_id: String,
     cancel_receiver: UnboundedReceiver<SequenceNumber>,
     outbox: Outbox,
+    cancel_data: Arc<Mutex<CancelData>>,
 }
 impl ProgressContext {
     pub(super) fn new(
         progress_id: String,
         cancel_receiver: UnboundedReceiver<SequenceNumber>,
         outbox: Outbox,
+        cancel_data: Arc<Mutex<CancelData>>,
     ) -> ProgressContext {
         ProgressContext {
             progress_id,
             cancel_receiver,
             outbox,
+            cancel_data,
         }
     }
 
@@ -88,6 +101,18 @@ impl Drop for ProgressContext {
             self.outbox
                 .respond_unknown_progress(open_request, self.progress_id.to_string())
         }
+        // The progress was not ended, because the request that started it was cancelled
+        let removed = {
+            let mut cancel_data = self.cancel_data.lock().unwrap();
+            cancel_data.current_progresses.remove(&self.progress_id)
+        };
+        if removed.is_some() {
+            let event = ProgressEndEventBody::builder()
+                .progress_id(self.progress_id.to_string())
+                .message(Some("Cancelled".to_string()))
+                .build();
+            self.outbox.send(Event::from(event));
+        }
     }
 }
 
@@ -169,6 +194,10 @@ pub trait DebugAdapter {
         context: impl DebugAdapterContext + Send,
     ) -> Result<SuccessResponse, RequestError<Self::CustomError>> {
         match request {
+            Request::BreakpointLocations(args) => self
+                .breakpoint_locations(args, context)
+                .await
+                .map(SuccessResponse::BreakpointLocations),
             Request::ConfigurationDone => self
                 .configuration_done(context)
                 .await
@@ -185,6 +214,14 @@ pub trait DebugAdapter {
                 .evaluate(args, context)
                 .await
                 .map(SuccessResponse::Evaluate),
+            Request::Goto(args) => self
+                .goto(args, context)
+                .await
+                .map(|()| SuccessResponse::Goto),
+            Request::GotoTargets(args) => self
+                .goto_targets(args, context)
+                .await
+                .map(SuccessResponse::GotoTargets),
             Request::Initialize(args) => self
                 .initialize(args, context)
                 .await
@@ -193,6 +230,10 @@ pub trait DebugAdapter {
                 .launch(args, context)
                 .await
                 .map(|()| SuccessResponse::Launch),
+            Request::LoadedSources => self
+                .loaded_sources(context)
+                .await
+                .map(SuccessResponse::LoadedSources),
             Request::Next(args) => self
                 .next(args, context)
                 .await
@@ -217,6 +258,10 @@ pub trait DebugAdapter {
                 .step_in(args, context)
                 .await
                 .map(|()| SuccessResponse::StepIn),
+            Request::StepInTargets(args) => self
+                .step_in_targets(args, context)
+                .await
+                .map(SuccessResponse::StepInTargets),
             Request::StepOut(args) => self
                 .step_out(args, context)
                 .await
@@ -240,6 +285,29 @@ pub trait DebugAdapter {
         }
     }
 
+    /// Handles a request that is not part of the Debug Adapter Protocol. The returned value is sent
+    /// as the body of the response.
+    async fn handle_custom_request(
+        &mut self,
+        request: CustomRequest,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<Value, RequestError<Self::CustomError>> {
+        Err(RequestError::Respond(PartialErrorResponse::new(format!(
+            "Unsupported request '{}'",
+            request.command
+        ))))
+    }
+
+    async fn breakpoint_locations(
+        &mut self,
+        _args: BreakpointLocationsRequestArguments,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<BreakpointLocationsResponseBody, RequestError<Self::CustomError>> {
+        Err(RequestError::Respond(PartialErrorResponse::new(
+            "Unsupported request 'breakpointLocations'".to_string(),
+        )))
+    }
+
     async fn configuration_done(
         &mut self,
         _context: impl DebugAdapterContext + Send,
@@ -274,6 +342,26 @@ pub trait DebugAdapter {
         )))
     }
 
+    async fn goto(
+        &mut self,
+        _args: GotoRequestArguments,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<(), RequestError<Self::CustomError>> {
+        Err(RequestError::Respond(PartialErrorResponse::new(
+            "Unsupported request 'goto'".to_string(),
+        )))
+    }
+
+    async fn goto_targets(
+        &mut self,
+        _args: GotoTargetsRequestArguments,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<GotoTargetsResponseBody, RequestError<Self::CustomError>> {
+        Err(RequestError::Respond(PartialErrorResponse::new(
+            "Unsupported request 'gotoTargets'".to_string(),
+        )))
+    }
+
     async fn initialize(
         &mut self,
         _args: InitializeRequestArguments,
@@ -294,6 +382,15 @@ pub trait DebugAdapter {
         )))
     }
 
+    async fn loaded_sources(
+        &mut self,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<LoadedSourcesResponseBody, RequestError<Self::CustomError>> {
+        Err(RequestError::Respond(PartialErrorResponse::new(
+            "Unsupported request 'loadedSources'".to_string(),
+        )))
+    }
+
     async fn next(
         &mut self,
         _args: NextRequestArguments,
@@ -354,6 +451,16 @@ pub trait DebugAdapter {
         )))
     }
 
+    async fn step_in_targets(
+        &mut self,
+        _args: StepInTargetsRequestArguments,
+        _context: impl DebugAdapterContext + Send,
+    ) -> Result<StepInTargetsResponseBody, RequestError<Self::CustomError>> {
+        Err(RequestError::Respond(PartialErrorResponse::new(
+            "Unsupported request 'stepInTargets'".to_string(),
+        )))
+    }
+
     async fn step_out(
         &mut self,
         _args: StepOutRequestArguments,
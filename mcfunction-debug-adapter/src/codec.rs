@@ -16,6 +16,7 @@
 // You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 // If not, see <http://www.gnu.org/licenses/>.
 
+use crate::custom::Message;
 use bytes::{Buf, BytesMut};
 use debug_adapter_protocol::ProtocolMessage;
 use std::{collections::BTreeMap, io};
@@ -26,6 +27,13 @@ impl Encoder<ProtocolMessage> for ProtocolMessageEncoder {
     type Error = std::io::Error;
 
     fn encode(&mut self, item: ProtocolMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(Message::from(item), dst)
+    }
+}
+impl Encoder<Message> for ProtocolMessageEncoder {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: Message, dst: &mut BytesMut) -> Result<(), Self::Error> {
         const HEADER_PREFIX: &str = "Content-Length: ";
         const HEADER_DELIMITER: &str = "\r\n\r\n";
         let json = serde_json::to_string(&item).unwrap();
@@ -43,7 +51,7 @@ impl Encoder<ProtocolMessage> for ProtocolMessageEncoder {
 
 pub struct ProtocolMessageDecoder;
 impl Decoder for ProtocolMessageDecoder {
-    type Item = ProtocolMessage;
+    type Item = Message;
     type Error = io::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
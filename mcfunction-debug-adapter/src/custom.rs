@@ -0,0 +1,156 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use debug_adapter_protocol::{ProtocolMessage, SequenceNumber};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::fmt::Display;
+
+/// The commands of protocol requests that are deserialized as [CustomRequest]s, because their
+/// arguments in [debug_adapter_protocol] can't hold the additional attributes of this debug
+/// adapter.
+const CUSTOM_PROTOCOL_COMMANDS: [&str; 2] = ["attach", "continue"];
+
+/// A message from or to the client. Besides the messages of the Debug Adapter Protocol, clients that
+/// know this debug adapter can send custom requests.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Message {
+    Protocol(ProtocolMessage),
+    Custom(CustomMessage),
+}
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let is_custom_protocol_request = value.get("type").and_then(Value::as_str)
+            == Some("request")
+            && value
+                .get("command")
+                .and_then(Value::as_str)
+                .is_some_and(|command| CUSTOM_PROTOCOL_COMMANDS.contains(&command));
+        if !is_custom_protocol_request {
+            if let Ok(message) = ProtocolMessage::deserialize(&value) {
+                return Ok(Message::Protocol(message));
+            }
+        }
+        CustomMessage::deserialize(value)
+            .map(Message::Custom)
+            .map_err(|_| D::Error::custom("data did not match any variant of enum Message"))
+    }
+}
+impl Message {
+    pub fn seq(&self) -> SequenceNumber {
+        match self {
+            Message::Protocol(message) => message.seq,
+            Message::Custom(message) => message.seq,
+        }
+    }
+}
+impl From<ProtocolMessage> for Message {
+    fn from(message: ProtocolMessage) -> Self {
+        Message::Protocol(message)
+    }
+}
+impl From<CustomMessage> for Message {
+    fn from(message: CustomMessage) -> Self {
+        Message::Custom(message)
+    }
+}
+impl Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Message::Protocol(message) => message.fmt(f),
+            Message::Custom(message) => {
+                let json = serde_json::to_string(&message).unwrap();
+                write!(f, "Content-Length: {}\r\n\r\n{}", json.len(), json)
+            }
+        }
+    }
+}
+
+/// A message that is not part of the Debug Adapter Protocol.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CustomMessage {
+    pub seq: SequenceNumber,
+
+    #[serde(flatten)]
+    pub content: CustomMessageContent,
+}
+impl CustomMessage {
+    pub fn new(seq: SequenceNumber, content: impl Into<CustomMessageContent>) -> CustomMessage {
+        CustomMessage {
+            seq,
+            content: content.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum CustomMessageContent {
+    Request(CustomRequest),
+    /// Only successful responses are custom, error responses to custom requests are regular
+    /// [ErrorResponse](debug_adapter_protocol::responses::ErrorResponse)s.
+    Response(CustomResponse),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CustomRequest {
+    pub command: String,
+
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub arguments: Value,
+}
+impl From<CustomRequest> for CustomMessageContent {
+    fn from(request: CustomRequest) -> Self {
+        CustomMessageContent::Request(request)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CustomResponse {
+    pub request_seq: SequenceNumber,
+
+    pub success: bool,
+
+    pub command: String,
+
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub body: Value,
+}
+impl CustomResponse {
+    pub fn success(request_seq: SequenceNumber, command: String, body: Value) -> CustomResponse {
+        CustomResponse {
+            request_seq,
+            success: true,
+            command,
+            body,
+        }
+    }
+}
+impl From<CustomResponse> for CustomMessageContent {
+    fn from(response: CustomResponse) -> Self {
+        CustomMessageContent::Response(response)
+    }
+}
+
+#[cfg(test)]
+mod tests;
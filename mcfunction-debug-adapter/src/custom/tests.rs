@@ -0,0 +1,85 @@
+use super::*;
+use debug_adapter_protocol::requests::Request;
+use serde_json::json;
+
+#[test]
+fn test_deserialize_protocol_request() {
+    // given:
+    let json = r#"{"seq":1,"type":"request","command":"threads"}"#;
+
+    // when:
+    let actual = serde_json::from_str::<Message>(json).unwrap();
+
+    // then:
+    assert_eq!(actual, ProtocolMessage::new(1, Request::Threads).into());
+}
+
+#[test]
+fn test_deserialize_custom_request() {
+    // given:
+    let json = r#"{"seq":2,"type":"request","command":"custom","arguments":{"line":3}}"#;
+
+    // when:
+    let actual = serde_json::from_str::<Message>(json).unwrap();
+
+    // then:
+    let request = CustomRequest {
+        command: "custom".to_string(),
+        arguments: json!({"line": 3}),
+    };
+    assert_eq!(actual, CustomMessage::new(2, request).into());
+}
+
+#[test]
+fn test_serialize_custom_response() {
+    // given:
+    let response = CustomResponse::success(2, "custom".to_string(), Value::Null);
+    let message = Message::from(CustomMessage::new(5, response));
+
+    // when:
+    let actual = serde_json::to_value(&message).unwrap();
+
+    // then:
+    assert_eq!(
+        actual,
+        json!({
+            "seq": 5,
+            "type": "response",
+            "request_seq": 2,
+            "success": true,
+            "command": "custom",
+        })
+    );
+}
+
+#[test]
+fn test_deserialize_attach_request_as_custom_request() {
+    // given:
+    let json = r#"{"seq":3,"type":"request","command":"attach","arguments":{"minecraftWorldDir":"world"}}"#;
+
+    // when:
+    let actual = serde_json::from_str::<Message>(json).unwrap();
+
+    // then:
+    let request = CustomRequest {
+        command: "attach".to_string(),
+        arguments: json!({"minecraftWorldDir": "world"}),
+    };
+    assert_eq!(actual, CustomMessage::new(3, request).into());
+}
+
+#[test]
+fn test_deserialize_continue_request_as_custom_request() {
+    // given:
+    let json = r#"{"seq":5,"type":"request","command":"continue","arguments":{"threadId":0,"singleThread":true}}"#;
+
+    // when:
+    let actual = serde_json::from_str::<Message>(json).unwrap();
+
+    // then:
+    let request = CustomRequest {
+        command: "continue".to_string(),
+        arguments: json!({"threadId": 0, "singleThread": true}),
+    };
+    assert_eq!(actual, CustomMessage::new(5, request).into());
+}
@@ -0,0 +1,314 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Checks the environment of the debug adapter without starting a debug session. Most problems
+//! with launching are caused by a wrong world directory or log file, so these checks explain what
+//! is wrong and how to fix it.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{adapter::utils::read_pack_format, installer::create_connection};
+use mcfunction_debugger::config::PACK_FORMAT;
+use minect::Command;
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::{
+    fs::{metadata, read_dir, remove_file, write},
+    time::{sleep, Instant},
+};
+
+/// How long to wait for Minecraft to write the injected `say` command to the log file.
+pub const DEFAULT_LOG_TIMEOUT: Duration = Duration::from_secs(10);
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct DoctorConfig<'l> {
+    pub minecraft_world_dir: &'l Path,
+    pub minecraft_log_file: &'l Path,
+    /// The datapack to debug, used to check its pack_format.
+    pub datapack: Option<&'l Path>,
+    pub log_timeout: Duration,
+}
+
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+}
+impl Check {
+    fn new(name: &'static str, status: CheckStatus) -> Check {
+        Check { name, status }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self.status, CheckStatus::Failed { .. })
+    }
+
+    /// The problem and remediation hint of a failed check, suitable for an error response.
+    pub fn failure_message(&self) -> Option<String> {
+        match &self.status {
+            CheckStatus::Failed { problem, hint } => Some(format!("{}. {}", problem, hint)),
+            _ => None,
+        }
+    }
+}
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.status {
+            CheckStatus::Passed => write!(f, "[PASS] {}", self.name),
+            CheckStatus::Failed { problem, hint } => {
+                write!(f, "[FAIL] {}: {}\n       {}", self.name, problem, hint)
+            }
+            CheckStatus::Skipped { reason } => write!(f, "[SKIP] {}: {}", self.name, reason),
+        }
+    }
+}
+
+pub enum CheckStatus {
+    Passed,
+    Failed { problem: String, hint: String },
+    Skipped { reason: String },
+}
+impl CheckStatus {
+    fn failed(problem: impl Into<String>, hint: impl Into<String>) -> CheckStatus {
+        CheckStatus::Failed {
+            problem: problem.into(),
+            hint: hint.into(),
+        }
+    }
+}
+
+/// Runs all checks in order. Checks that depend on a failed check are skipped.
+pub async fn run_checks(config: &DoctorConfig<'_>) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let world_dir = check_world_dir(config.minecraft_world_dir);
+    let world_dir_ok = !world_dir.is_failed();
+    checks.push(world_dir);
+
+    let log_file = check_log_file(config.minecraft_log_file).await;
+    let log_file_ok = !log_file.is_failed();
+    checks.push(log_file);
+
+    if world_dir_ok {
+        let minect = check_minect_datapack(config.minecraft_world_dir);
+        let minect_ok = !minect.is_failed();
+        checks.push(minect);
+        checks.push(check_output_writable(config.minecraft_world_dir).await);
+        checks.push(check_leftover_datapacks(config.minecraft_world_dir).await);
+
+        if minect_ok && log_file_ok {
+            checks.push(
+                check_log_activity(
+                    config.minecraft_world_dir,
+                    config.minecraft_log_file,
+                    config.log_timeout,
+                )
+                .await,
+            );
+        } else {
+            checks.push(skipped(
+                LOG_ACTIVITY,
+                "The minect datapack or log file is missing",
+            ));
+        }
+    } else {
+        let reason = "The world directory is invalid";
+        checks.push(skipped(MINECT_DATAPACK, reason));
+        checks.push(skipped(OUTPUT_WRITABLE, reason));
+        checks.push(skipped(LEFTOVER_DATAPACKS, reason));
+        checks.push(skipped(LOG_ACTIVITY, reason));
+    }
+
+    if let Some(datapack) = config.datapack {
+        checks.push(check_pack_format(datapack).await);
+    }
+
+    checks
+}
+
+fn skipped(name: &'static str, reason: &str) -> Check {
+    let reason = reason.to_string();
+    Check::new(name, CheckStatus::Skipped { reason })
+}
+
+const WORLD_DIR: &str = "World directory";
+const LOG_FILE: &str = "Log file";
+const MINECT_DATAPACK: &str = "Minect datapack";
+const OUTPUT_WRITABLE: &str = "Write permission";
+const LEFTOVER_DATAPACKS: &str = "Leftover debug datapacks";
+const LOG_ACTIVITY: &str = "Log activity";
+const PACK_FORMAT_CHECK: &str = "Pack format";
+
+pub fn check_world_dir(minecraft_world_dir: &Path) -> Check {
+    let status = if !minecraft_world_dir.is_dir() {
+        CheckStatus::failed(
+            format!("{} is not a directory", minecraft_world_dir.display()),
+            "Set 'minecraftWorldDir' to the directory of your world, for example \
+            '.minecraft/saves/<world>'.",
+        )
+    } else if !minecraft_world_dir.join("datapacks").is_dir() {
+        CheckStatus::failed(
+            format!(
+                "{} does not contain a datapacks directory",
+                minecraft_world_dir.display()
+            ),
+            "Make sure 'minecraftWorldDir' is the directory of a world that was opened in \
+            Minecraft at least once.",
+        )
+    } else {
+        CheckStatus::Passed
+    };
+    Check::new(WORLD_DIR, status)
+}
+
+pub async fn check_log_file(minecraft_log_file: &Path) -> Check {
+    let status = match metadata(minecraft_log_file).await {
+        Ok(metadata) if metadata.is_file() => CheckStatus::Passed,
+        _ => CheckStatus::failed(
+            format!("{} does not exist", minecraft_log_file.display()),
+            "Set 'minecraftLogFile' to 'logs/latest.log' in the directory of your Minecraft \
+            installation or server.",
+        ),
+    };
+    Check::new(LOG_FILE, status)
+}
+
+pub fn check_minect_datapack(minecraft_world_dir: &Path) -> Check {
+    let connection = create_connection(minecraft_world_dir, "");
+    let datapack_dir = connection.get_datapack_dir();
+    let status = if datapack_dir.is_dir() {
+        CheckStatus::Passed
+    } else {
+        CheckStatus::failed(
+            format!("{} does not exist", datapack_dir.display()),
+            "Launch a debug session once to create it, then execute /reload in Minecraft and \
+            follow the instructions in the chat.",
+        )
+    };
+    Check::new(MINECT_DATAPACK, status)
+}
+
+pub async fn check_output_writable(minecraft_world_dir: &Path) -> Check {
+    let path = minecraft_world_dir
+        .join("datapacks")
+        .join(".mcfunction-debugger-doctor");
+    let status = match write(&path, "").await {
+        Ok(()) => {
+            let _ = remove_file(&path).await;
+            CheckStatus::Passed
+        }
+        Err(e) => CheckStatus::failed(
+            format!("Failed to write to {}: {}", path.display(), e),
+            "The debug datapack is written to the datapacks directory of the world. Make sure \
+            you have permission to write there.",
+        ),
+    };
+    Check::new(OUTPUT_WRITABLE, status)
+}
+
+/// Debug datapacks are removed when a debug session terminates, so a remaining `debug-*` datapack
+/// means that a previous session crashed and its objectives, tags and entities are still in the
+/// world.
+pub async fn check_leftover_datapacks(minecraft_world_dir: &Path) -> Check {
+    let datapacks_dir = minecraft_world_dir.join("datapacks");
+    let mut leftovers = Vec::new();
+    if let Ok(mut entries) = read_dir(&datapacks_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with("debug-") && entry.path().is_dir() {
+                leftovers.push(name);
+            }
+        }
+    }
+    leftovers.sort();
+    let status = if leftovers.is_empty() {
+        CheckStatus::Passed
+    } else {
+        CheckStatus::failed(
+            format!(
+                "{} contains debug datapacks of previous sessions: {}",
+                datapacks_dir.display(),
+                leftovers.join(", ")
+            ),
+            "Execute /function debug:purge in Minecraft to remove all objectives, tags and \
+            entities of the debugger, then delete the debug datapacks.",
+        )
+    };
+    Check::new(LEFTOVER_DATAPACKS, status)
+}
+
+pub async fn check_log_activity(
+    minecraft_world_dir: &Path,
+    minecraft_log_file: &Path,
+    timeout: Duration,
+) -> Check {
+    let len = |path: PathBuf| async move { metadata(path).await.map(|it| it.len()).ok() };
+    let initial_len = len(minecraft_log_file.to_path_buf()).await;
+
+    let mut connection = create_connection(minecraft_world_dir, minecraft_log_file);
+    let command = Command::new("say mcfunction-debugger doctor");
+    if let Err(e) = connection.execute_commands([command]) {
+        let status = CheckStatus::failed(
+            format!("Failed to inject a command: {}", e),
+            "Make sure you have permission to write to the world directory.",
+        );
+        return Check::new(LOG_ACTIVITY, status);
+    }
+
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        // The log file may also be rotated, so any change in length counts as activity
+        if len(minecraft_log_file.to_path_buf()).await != initial_len {
+            return Check::new(LOG_ACTIVITY, CheckStatus::Passed);
+        }
+        sleep(LOG_POLL_INTERVAL).await;
+    }
+    let status = CheckStatus::failed(
+        format!(
+            "{} did not change within {}s after injecting a say command",
+            minecraft_log_file.display(),
+            timeout.as_secs()
+        ),
+        "Make sure the world is open in Minecraft and 'minecraftLogFile' belongs to the same \
+        Minecraft instance. If this is the first connection, execute /reload in Minecraft.",
+    );
+    Check::new(LOG_ACTIVITY, status)
+}
+
+pub async fn check_pack_format(datapack: &Path) -> Check {
+    let status = match read_pack_format(datapack).await {
+        Some(pack_format) if pack_format >= PACK_FORMAT => CheckStatus::Passed,
+        Some(pack_format) => CheckStatus::failed(
+            format!(
+                "{} has pack_format {}, but the debugger requires at least pack_format {}",
+                datapack.display(),
+                pack_format,
+                PACK_FORMAT
+            ),
+            "The debugger supports Minecraft 1.17 and later.",
+        ),
+        None => CheckStatus::failed(
+            format!("Failed to read the pack_format of {}", datapack.display()),
+            "Make sure the datapack contains a valid pack.mcmeta file.",
+        ),
+    };
+    Check::new(PACK_FORMAT_CHECK, status)
+}
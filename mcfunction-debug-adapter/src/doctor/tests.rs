@@ -0,0 +1,127 @@
+use super::*;
+use std::fs::{create_dir_all, remove_dir_all, write};
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debug-adapter-doctor-test")
+        .join(name);
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn create_world(name: &str) -> PathBuf {
+    let world = test_dir(name).join("world");
+    create_dir_all(world.join("datapacks")).unwrap();
+    world
+}
+
+fn create_datapack(dir: &Path, pack_format: u64) -> PathBuf {
+    let datapack = dir.join("datapack");
+    create_dir_all(&datapack).unwrap();
+    let pack_mcmeta = format!(
+        r#"{{"pack": {{"pack_format": {}, "description": ""}}}}"#,
+        pack_format
+    );
+    write(datapack.join("pack.mcmeta"), pack_mcmeta).unwrap();
+    datapack
+}
+
+#[test]
+fn test_world_dir_without_datapacks() {
+    // given:
+    let dir = test_dir("test_world_dir_without_datapacks");
+
+    // when:
+    let actual = check_world_dir(&dir);
+
+    // then:
+    assert!(actual.is_failed());
+}
+
+#[test]
+fn test_world_dir() {
+    // given:
+    let world = create_world("test_world_dir");
+
+    // when:
+    let actual = check_world_dir(&world);
+
+    // then:
+    assert!(!actual.is_failed());
+}
+
+#[tokio::test]
+async fn test_leftover_datapacks() {
+    // given:
+    let world = create_world("test_leftover_datapacks");
+    create_dir_all(world.join("datapacks").join("debug-my_datapack")).unwrap();
+    create_dir_all(world.join("datapacks").join("my_datapack")).unwrap();
+
+    // when:
+    let actual = check_leftover_datapacks(&world).await;
+
+    // then:
+    assert_eq!(
+        actual
+            .failure_message()
+            .map(|it| it.contains("debug-my_datapack")),
+        Some(true)
+    );
+}
+
+#[tokio::test]
+async fn test_pack_format_too_old() {
+    // given:
+    let dir = test_dir("test_pack_format_too_old");
+    let datapack = create_datapack(&dir, PACK_FORMAT - 1);
+
+    // when:
+    let actual = check_pack_format(&datapack).await;
+
+    // then:
+    assert!(actual.is_failed());
+}
+
+#[tokio::test]
+async fn test_pack_format_newer() {
+    // given:
+    let dir = test_dir("test_pack_format_newer");
+    let datapack = create_datapack(&dir, PACK_FORMAT + 8);
+
+    // when:
+    let actual = check_pack_format(&datapack).await;
+
+    // then:
+    assert!(!actual.is_failed());
+}
+
+#[tokio::test]
+async fn test_run_checks_skips_dependent_checks() {
+    // given:
+    let dir = test_dir("test_run_checks_skips_dependent_checks");
+    let datapack = create_datapack(&dir, PACK_FORMAT);
+    let config = DoctorConfig {
+        minecraft_world_dir: &dir.join("missing"),
+        minecraft_log_file: &dir.join("latest.log"),
+        datapack: Some(&datapack),
+        log_timeout: Duration::ZERO,
+    };
+
+    // when:
+    let actual = run_checks(&config).await;
+
+    // then:
+    let statuses = actual
+        .iter()
+        .map(|check| match check.status {
+            CheckStatus::Passed => "PASS",
+            CheckStatus::Failed { .. } => "FAIL",
+            CheckStatus::Skipped { .. } => "SKIP",
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statuses,
+        ["FAIL", "FAIL", "SKIP", "SKIP", "SKIP", "SKIP", "PASS"]
+    );
+}
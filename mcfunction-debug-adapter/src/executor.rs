@@ -17,8 +17,12 @@
 // If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-    api::DebugAdapter, error::RequestError, get_command, CancelData, DebugAdapterContextImpl,
-    Outbox,
+    api::DebugAdapter,
+    custom::{CustomMessage, CustomMessageContent, Message},
+    error::RequestError,
+    get_command,
+    trace::Tracer,
+    CancelData, DebugAdapterContextImpl, Outbox,
 };
 use debug_adapter_protocol::{
     responses::{ErrorResponse, ErrorResponseBody, SuccessResponse},
@@ -30,6 +34,7 @@ use futures::{
 };
 use log::trace;
 use std::{
+    collections::VecDeque,
     io,
     sync::{Arc, Mutex},
 };
@@ -40,11 +45,15 @@ where
     D: DebugAdapter,
 {
     pub cancel_data: Arc<Mutex<CancelData>>,
-    pub inbox_receiver: UnboundedReceiver<Either<ProtocolMessage, <D as DebugAdapter>::Message>>,
+    pub inbox_receiver: UnboundedReceiver<Either<Message, <D as DebugAdapter>::Message>>,
+    /// Messages that were taken from the inbox to look ahead, see
+    /// [DebugAdapterExecutor::is_followed_by].
+    pub queued: VecDeque<Either<Message, <D as DebugAdapter>::Message>>,
     pub outbox: Outbox,
     pub cancel_receiver: UnboundedReceiver<SequenceNumber>,
     pub adapter: D,
     pub shutdown_sender: mpsc::Sender<()>,
+    pub tracer: Tracer,
 }
 
 impl<D> DebugAdapterExecutor<D>
@@ -53,23 +62,17 @@ where
 {
     pub async fn run(mut self) -> Result<(), <D as DebugAdapter>::CustomError> {
         trace!("Starting executor");
-        while let Some(msg) = self.inbox_receiver.recv().await {
+        while let Some(msg) = self.next_message().await {
             match msg {
                 Either::Left(client_msg) => {
                     trace!("Handling message from client: {}", client_msg);
 
+                    let seq = client_msg.seq(); // TODO: seq zu i32 machen
+                    let mut maybe_cancel_request_id = None;
+                    let command = get_request_command(&client_msg).unwrap_or_default();
                     let mut context =
                         DebugAdapterContextImpl::new(self.outbox.clone(), self.cancel_data.clone());
-
-                    let seq = client_msg.seq; // TODO: seq zu i32 machen
-                    let mut maybe_cancel_request_id = None;
-                    // TODO: ugly
-                    let command =
-                        if let ProtocolMessageContent::Request(request) = &client_msg.content {
-                            get_command(request)
-                        } else {
-                            "".to_string()
-                        };
+                    context.superseded = self.is_followed_by(&command);
                     if self.start_request(seq as i32) {
                         {
                             let cancel = self.cancel_receiver.recv();
@@ -134,11 +137,33 @@ where
         Ok(())
     }
 
+    async fn next_message(&mut self) -> Option<Either<Message, <D as DebugAdapter>::Message>> {
+        match self.queued.pop_front() {
+            Some(msg) => Some(msg),
+            None => self.inbox_receiver.recv().await,
+        }
+    }
+
+    /// Returns true if the next message in the inbox is a request with the given command.
+    fn is_followed_by(&mut self, command: &str) -> bool {
+        if self.queued.is_empty() {
+            // A disconnect is noticed when the next message is received
+            if let Ok(msg) = self.inbox_receiver.try_recv() {
+                self.queued.push_back(msg);
+            }
+        }
+        match self.queued.front() {
+            Some(Either::Left(msg)) => get_request_command(msg).is_some_and(|it| it == command),
+            _ => false,
+        }
+    }
+
     fn start_request(&self, request_id: i32) -> bool {
         let mut cancel_data = self.cancel_data.lock().unwrap();
         let is_cancelled = cancel_data.cancelled_request_ids.remove(&request_id);
         if !is_cancelled {
             cancel_data.current_request_id = Some(request_id);
+            self.tracer.set_request_id(Some(request_id));
         }
         !is_cancelled
     }
@@ -146,21 +171,25 @@ where
     fn finish_request(&mut self) -> io::Result<()> {
         let mut cancel_data = self.cancel_data.lock().unwrap();
         cancel_data.current_request_id = None;
+        self.tracer.set_request_id(None);
         clear_channel(&mut self.cancel_receiver)?; // Clear all remaining cancel requests
         Ok(())
     }
 }
 
 async fn handle_client_message<D>(
-    msg: ProtocolMessage,
+    msg: Message,
     adapter: &mut D,
     context: &mut DebugAdapterContextImpl,
 ) -> Result<(), <D as DebugAdapter>::CustomError>
 where
     D: DebugAdapter + Send,
 {
-    match msg.content {
-        ProtocolMessageContent::Request(request) => {
+    match msg {
+        Message::Protocol(ProtocolMessage {
+            seq,
+            content: ProtocolMessageContent::Request(request),
+        }) => {
             let command = get_command(&request);
 
             // Reborrow context to allow reusing &mut after handle_client_request
@@ -172,7 +201,26 @@ where
                 Err(RequestError::Respond(response)) => Err(response.with_command(command)),
                 Err(RequestError::Terminate(e)) => return Err(e),
             };
-            context.outbox.respond(msg.seq, response);
+            context.outbox.respond(seq, response);
+            Ok(())
+        }
+        Message::Custom(CustomMessage {
+            seq,
+            content: CustomMessageContent::Request(request),
+        }) => {
+            let command = request.command.clone();
+
+            // Reborrow context to allow reusing &mut after handle_custom_request
+            let c = &mut *context;
+            match adapter.handle_custom_request(request, c).await {
+                Ok(body) => context.outbox.respond_custom(seq, command, body),
+                Err(RequestError::Respond(response)) => {
+                    context
+                        .outbox
+                        .respond(seq, Err(response.with_command(command)));
+                }
+                Err(RequestError::Terminate(e)) => return Err(e),
+            }
             Ok(())
         }
         _ => {
@@ -181,6 +229,20 @@ where
     }
 }
 
+fn get_request_command(msg: &Message) -> Option<String> {
+    match msg {
+        Message::Protocol(ProtocolMessage {
+            content: ProtocolMessageContent::Request(request),
+            ..
+        }) => Some(get_command(request)),
+        Message::Custom(CustomMessage {
+            content: CustomMessageContent::Request(request),
+            ..
+        }) => Some(request.command.clone()),
+        _ => None,
+    }
+}
+
 fn clear_channel<E>(receiver: &mut UnboundedReceiver<E>) -> io::Result<()> {
     loop {
         match receiver.try_recv() {
@@ -16,23 +16,44 @@
 // You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 // If not, see <http://www.gnu.org/licenses/>.
 
+#[cfg(unix)]
+use crate::adapter::connection::PipeConnection;
 use crate::{
+    adapter::connection::{Connect, Connection, ConnectionType, RconConnection, SocketConnection},
     api::ProgressContext,
     error::{PartialErrorResponse, RequestError},
     DebugAdapterContext,
 };
+use debug_adapter_protocol::events::ProgressUpdateEventBody;
 use futures::{
     future::{select, Either},
-    pin_mut,
+    pin_mut, Future,
 };
 use minect::MinecraftConnection;
-use std::{io, path::Path};
+use std::{
+    fmt::Display,
+    fs::metadata,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::sleep;
+
+/// The first interval after which the progress of connecting to Minecraft is updated. Every following
+/// interval is twice as long.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
 
-pub async fn establish_connection(
+/// Connects to Minecraft. While waiting for Minecraft to respond the progress is updated in
+/// exponentially growing intervals. If connecting fails with an error it is retried after the next
+/// interval. If a `timeout` is given and Minecraft does not respond in time, connecting fails.
+pub(crate) async fn establish_connection(
     minecraft_world_dir: impl AsRef<Path>,
     minecraft_log_file: impl AsRef<Path>,
-    mut context: impl DebugAdapterContext,
-) -> Result<MinecraftConnection, RequestError<io::Error>> {
+    connection_type: ConnectionType,
+    timeout: Option<Duration>,
+    context: &mut impl DebugAdapterContext,
+) -> Result<Box<dyn Connection>, RequestError<io::Error>> {
     let mut progress = context.start_cancellable_progress(
         "Connecting to Minecraft".to_string(),
         Some(
@@ -40,34 +61,203 @@ pub async fn establish_connection(
                 .to_string(),
         ),
     );
+    let progress_id = progress.progress_id.to_string();
 
-    let mut connection =
-        MinecraftConnection::builder("mcfunction-debugger", minecraft_world_dir.as_ref())
-            .log_file(minecraft_log_file.as_ref())
-            .build();
-    let result = connect(&mut connection, &mut progress).await;
+    let mut backoff = Backoff::new(INITIAL_RETRY_DELAY, timeout);
+    let result = match connection_type {
+        ConnectionType::LogFile => {
+            let connection = create_connection(minecraft_world_dir, minecraft_log_file);
+            connect_with_log_file(connection, &mut backoff, &mut progress, context)
+                .await
+                .map(|it| Box::new(it) as Box<dyn Connection>)
+        }
+        ConnectionType::Socket { port } => {
+            connect_with_socket(port, &mut backoff, &mut progress, context)
+                .await
+                .map(|it| Box::new(it) as Box<dyn Connection>)
+        }
+        #[cfg(unix)]
+        ConnectionType::Pipe { path } => {
+            // The log file is only read to install the connection in the world
+            let connection = create_connection(minecraft_world_dir, minecraft_log_file);
+            match connect_with_log_file(connection, &mut backoff, &mut progress, context).await {
+                Ok(connection) => PipeConnection::open(connection, &path)
+                    .map(|it| Box::new(it) as Box<dyn Connection>)
+                    .map_err(|e| ConnectError::Pipe(path, e)),
+                Err(e) => Err(e),
+            }
+        }
+        ConnectionType::Rcon { port, password } => {
+            // The log file is read to install the connection in the world, which is needed to log
+            // the output of commands executed through RCON
+            let connection = create_connection(minecraft_world_dir, minecraft_log_file);
+            match connect_with_log_file(connection, &mut backoff, &mut progress, context).await {
+                Ok(connection) => RconConnection::connect(connection, port, &password)
+                    .map(|it| Box::new(it) as Box<dyn Connection>)
+                    .map_err(|e| ConnectError::Rcon(port, e)),
+                Err(e) => Err(e),
+            }
+        }
+    };
 
-    let progress_id = progress.progress_id.to_string();
     let progress_end_message = match &result {
-        Ok(()) => "Successfully connected to Minecraft".to_string(),
+        Ok(_) => "Successfully connected to Minecraft".to_string(),
         Err(ConnectError::Cancelled) => "Cancelled connecting to Minecraft".to_string(),
-        Err(ConnectError::Failed(error)) => format!("Failed to connect to Minecraft: {}", error),
+        Err(error) => format!("Failed to connect to Minecraft: {}", error),
     };
     context.end_cancellable_progress(progress_id, Some(progress_end_message));
 
-    result
+    let connection = result
         .map_err(|e| match e {
-            ConnectError::Cancelled => "Launch was cancelled.".to_string(),
-            ConnectError::Failed(error) => format!("Failed to connect to Minecraft: {}", error),
+            ConnectError::Cancelled => "cancelled".to_string(),
+            error => format!("Failed to connect to Minecraft: {}", error),
         })
         .map_err(PartialErrorResponse::new)?;
 
     Ok(connection)
 }
 
+async fn connect_with_log_file(
+    mut connection: MinecraftConnection,
+    backoff: &mut Backoff,
+    progress: &mut ProgressContext,
+    context: &mut impl DebugAdapterContext,
+) -> Result<MinecraftConnection, ConnectError> {
+    let progress_id = progress.progress_id.to_string();
+    loop {
+        let attempt = connect(&mut connection, progress);
+        let on_wait = |elapsed: Duration| {
+            let message = format!(
+                "Waiting for Minecraft to respond ({}s). If you are connecting for the first time \
+                please execute /reload in Minecraft.",
+                elapsed.as_secs()
+            );
+            update_progress(context, &progress_id, message);
+        };
+        match wait_with_backoff(attempt, backoff, on_wait).await {
+            Ok(Err(ConnectError::Failed(error))) => match backoff.next() {
+                Some(delay) => {
+                    let message = format!(
+                        "Failed to connect to Minecraft, retrying in {}s: {}",
+                        delay.as_secs(),
+                        error
+                    );
+                    update_progress(context, &progress_id, message);
+                    let cancel = progress.next_cancel_request();
+                    pin_mut!(cancel);
+                    if let Either::Right(_) = select(Box::pin(sleep(delay)), cancel).await {
+                        return Err(ConnectError::Cancelled);
+                    }
+                }
+                None => return Err(ConnectError::Failed(error)),
+            },
+            Ok(result) => return result.map(|()| connection),
+            Err(TimedOut) => return Err(ConnectError::TimedOut(backoff.elapsed())),
+        }
+    }
+}
+
+/// Connects to a companion mod listening on `localhost:<port>`. A refused connection is retried
+/// after the next interval of the backoff.
+async fn connect_with_socket(
+    port: u16,
+    backoff: &mut Backoff,
+    progress: &mut ProgressContext,
+    context: &mut impl DebugAdapterContext,
+) -> Result<SocketConnection, ConnectError> {
+    let progress_id = progress.progress_id.to_string();
+    loop {
+        match SocketConnection::connect(port) {
+            Ok(connection) => return Ok(connection),
+            Err(error) => match backoff.next() {
+                Some(delay) => {
+                    let message = format!(
+                        "Failed to connect to localhost:{}, retrying in {}s: {}",
+                        port,
+                        delay.as_secs(),
+                        error
+                    );
+                    update_progress(context, &progress_id, message);
+                    let cancel = progress.next_cancel_request();
+                    pin_mut!(cancel);
+                    if let Either::Right(_) = select(Box::pin(sleep(delay)), cancel).await {
+                        return Err(ConnectError::Cancelled);
+                    }
+                }
+                None => return Err(ConnectError::Socket(port, error)),
+            },
+        }
+    }
+}
+
+pub(crate) fn update_progress(
+    context: &mut impl DebugAdapterContext,
+    progress_id: &str,
+    message: String,
+) {
+    let event = ProgressUpdateEventBody::builder()
+        .progress_id(progress_id.to_string())
+        .message(Some(message))
+        .build();
+    context.fire_event(event);
+}
+
+/// Creates a connection without connecting to Minecraft. This can be used to read a new log file
+/// with a connection that was already established.
+pub fn create_connection(
+    minecraft_world_dir: impl AsRef<Path>,
+    minecraft_log_file: impl AsRef<Path>,
+) -> MinecraftConnection {
+    MinecraftConnection::builder("mcfunction-debugger", minecraft_world_dir.as_ref())
+        .log_file(minecraft_log_file.as_ref())
+        .build()
+}
+
+/// Returns a function that creates a new connection of the given type to replace a lost one. In
+/// contrast to [establish_connection] it does not wait for Minecraft to respond, because the
+/// connection was already installed in the world.
+pub(crate) fn reconnect_with(
+    minecraft_world_dir: impl AsRef<Path>,
+    minecraft_log_file: impl AsRef<Path>,
+    connection_type: ConnectionType,
+) -> Connect {
+    let minecraft_world_dir = minecraft_world_dir.as_ref().to_path_buf();
+    let minecraft_log_file = minecraft_log_file.as_ref().to_path_buf();
+    Arc::new(move || match &connection_type {
+        ConnectionType::LogFile => {
+            // The log file does not exist while the server is down
+            metadata(&minecraft_log_file)?;
+            let connection = create_connection(&minecraft_world_dir, &minecraft_log_file);
+            Ok(Box::new(connection) as Box<dyn Connection>)
+        }
+        ConnectionType::Socket { port } => {
+            let connection = SocketConnection::connect(*port)?;
+            Ok(Box::new(connection) as Box<dyn Connection>)
+        }
+        #[cfg(unix)]
+        ConnectionType::Pipe { path } => {
+            metadata(&minecraft_log_file)?;
+            let connection = create_connection(&minecraft_world_dir, &minecraft_log_file);
+            let connection = PipeConnection::open(connection, path)?;
+            Ok(Box::new(connection) as Box<dyn Connection>)
+        }
+        ConnectionType::Rcon { port, password } => {
+            metadata(&minecraft_log_file)?;
+            let connection = create_connection(&minecraft_world_dir, &minecraft_log_file);
+            let connection = RconConnection::connect(connection, *port, password)?;
+            Ok(Box::new(connection) as Box<dyn Connection>)
+        }
+    })
+}
+
 enum ConnectError {
     Cancelled,
     Failed(minect::ConnectError),
+    Socket(u16, io::Error),
+    #[cfg(unix)]
+    Pipe(PathBuf, io::Error),
+    Rcon(u16, io::Error),
+    TimedOut(Duration),
 }
 impl From<minect::ConnectError> for ConnectError {
     fn from(error: minect::ConnectError) -> Self {
@@ -78,6 +268,35 @@ impl From<minect::ConnectError> for ConnectError {
         }
     }
 }
+impl Display for ConnectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectError::Cancelled => write!(f, "Cancelled"),
+            ConnectError::Failed(error) => error.fmt(f),
+            ConnectError::Socket(port, error) => {
+                write!(f, "Could not connect to localhost:{}: {}", port, error)
+            }
+            #[cfg(unix)]
+            ConnectError::Pipe(path, error) => {
+                write!(f, "Could not open pipe {}: {}", path.display(), error)
+            }
+            ConnectError::Rcon(port, error) => {
+                write!(
+                    f,
+                    "Could not connect to RCON on localhost:{}: {}",
+                    port, error
+                )
+            }
+            ConnectError::TimedOut(elapsed) => write!(
+                f,
+                "Minecraft did not respond within {}s. Make sure the world is loaded and the \
+                datapacks were reloaded with /reload.",
+                elapsed.as_secs()
+            ),
+        }
+    }
+}
+
 async fn connect(
     connection: &mut MinecraftConnection,
     progress: &mut ProgressContext,
@@ -86,6 +305,8 @@ async fn connect(
     pin_mut!(connect);
     let cancel = progress.next_cancel_request();
     pin_mut!(cancel);
+    // If Minecraft responds while a cancel request is pending, the connection is used, because
+    // select prefers the first future
     match select(connect, cancel).await {
         Either::Left((result, _)) => {
             result?;
@@ -94,3 +315,66 @@ async fn connect(
         Either::Right(_) => return Err(ConnectError::Cancelled),
     }
 }
+
+/// The delays between checks of a pending connection, which double every time until the optional
+/// total timeout is used up.
+pub(crate) struct Backoff {
+    next_delay: Duration,
+    remaining: Option<Duration>,
+    elapsed: Duration,
+}
+impl Backoff {
+    pub(crate) fn new(initial_delay: Duration, timeout: Option<Duration>) -> Backoff {
+        Backoff {
+            next_delay: initial_delay,
+            remaining: timeout,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// The sum of all delays returned so far.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+impl Iterator for Backoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let delay = match &mut self.remaining {
+            Some(remaining) if remaining.is_zero() => return None,
+            Some(remaining) => {
+                let delay = self.next_delay.min(*remaining);
+                *remaining -= delay;
+                delay
+            }
+            None => self.next_delay,
+        };
+        self.next_delay *= 2;
+        self.elapsed += delay;
+        Some(delay)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct TimedOut;
+
+/// Waits for the `future` to complete. `on_wait` is called with the total elapsed time after every
+/// delay of the `backoff` that passes without the `future` completing.
+pub(crate) async fn wait_with_backoff<F: Future>(
+    future: F,
+    backoff: &mut Backoff,
+    mut on_wait: impl FnMut(Duration),
+) -> Result<F::Output, TimedOut> {
+    pin_mut!(future);
+    while let Some(delay) = backoff.next() {
+        match select(future.as_mut(), Box::pin(sleep(delay))).await {
+            Either::Left((output, _)) => return Ok(output),
+            Either::Right(_) => on_wait(backoff.elapsed()),
+        }
+    }
+    Err(TimedOut)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,97 @@
+use super::*;
+use tokio::time::Instant;
+
+const SECOND: Duration = Duration::from_secs(1);
+
+#[test]
+fn test_backoff_doubles_delay() {
+    // given:
+    let backoff = Backoff::new(SECOND, None);
+
+    // when:
+    let actual = backoff.take(4).collect::<Vec<_>>();
+
+    // then:
+    assert_eq!(actual, vec![SECOND, 2 * SECOND, 4 * SECOND, 8 * SECOND]);
+}
+
+#[test]
+fn test_backoff_stops_at_timeout() {
+    // given:
+    let mut backoff = Backoff::new(SECOND, Some(Duration::from_millis(5500)));
+
+    // when:
+    let actual = backoff.by_ref().collect::<Vec<_>>();
+
+    // then:
+    assert_eq!(
+        actual,
+        vec![SECOND, 2 * SECOND, Duration::from_millis(2500)]
+    );
+    assert_eq!(backoff.elapsed(), Duration::from_millis(5500));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_wait_with_backoff_for_delayed_response() {
+    // given:
+    let start = Instant::now();
+    let response = async {
+        sleep(Duration::from_millis(5500)).await;
+        "connected"
+    };
+    let mut backoff = Backoff::new(SECOND, None);
+    let mut waits = Vec::new();
+
+    // when:
+    let actual = wait_with_backoff(response, &mut backoff, |elapsed| waits.push(elapsed)).await;
+
+    // then:
+    assert_eq!(actual, Ok("connected"));
+    assert_eq!(waits, vec![SECOND, 3 * SECOND]);
+    assert_eq!(start.elapsed(), Duration::from_millis(5500));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_wait_with_backoff_times_out() {
+    // given:
+    let start = Instant::now();
+    let response = async {
+        sleep(Duration::from_secs(60)).await;
+        "connected"
+    };
+    let mut backoff = Backoff::new(SECOND, Some(10 * SECOND));
+    let mut waits = Vec::new();
+
+    // when:
+    let actual = wait_with_backoff(response, &mut backoff, |elapsed| waits.push(elapsed)).await;
+
+    // then:
+    assert_eq!(actual, Err(TimedOut));
+    assert_eq!(waits, vec![SECOND, 3 * SECOND, 7 * SECOND, 10 * SECOND]);
+    assert_eq!(start.elapsed(), 10 * SECOND);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_wait_with_backoff_continues_backoff_of_previous_attempt() {
+    // given:
+    let mut backoff = Backoff::new(SECOND, Some(10 * SECOND));
+    let failed_attempt = async {
+        sleep(Duration::from_millis(1500)).await;
+        Err::<(), _>("failed")
+    };
+    wait_with_backoff(failed_attempt, &mut backoff, |_| {})
+        .await
+        .unwrap()
+        .unwrap_err();
+    let mut waits = Vec::new();
+
+    // when:
+    let actual = wait_with_backoff(std::future::pending::<()>(), &mut backoff, |elapsed| {
+        waits.push(elapsed)
+    })
+    .await;
+
+    // then:
+    assert_eq!(actual, Err(TimedOut));
+    assert_eq!(waits, vec![7 * SECOND, 10 * SECOND]);
+}
@@ -19,13 +19,21 @@
 pub mod adapter;
 pub mod api;
 pub mod codec;
+pub mod custom;
+pub mod doctor;
 pub mod error;
 mod executor;
 mod installer;
 mod receiver;
+mod record_file;
+pub mod recording;
 mod sender;
+#[cfg(test)]
+mod test_utils;
+pub mod trace;
 
 use api::{CancelErrorResponse, DebugAdapter, DebugAdapterContext, ProgressContext};
+use custom::{CustomMessage, CustomMessageContent, CustomResponse, Message};
 use debug_adapter_protocol::{
     events::{Event, ProgressEndEventBody, ProgressStartEventBody},
     requests::Request,
@@ -35,12 +43,13 @@ use debug_adapter_protocol::{
 use error::DebugAdapterError;
 use executor::DebugAdapterExecutor;
 use futures::{future::Either, FutureExt, Sink, SinkExt, Stream, TryFutureExt};
-use log::trace;
+use log::{error, trace};
 use receiver::DebugAdapterReceiver;
+use recording::{Recorder, RecordingMessageWriter};
 use sender::DebugAdapterSender;
 use serde_json::Value;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     sync::{Arc, Mutex},
 };
 use tokio::{
@@ -48,24 +57,58 @@ use tokio::{
     sync::mpsc::{self, unbounded_channel, UnboundedSender},
     try_join,
 };
+use trace::Tracer;
 use uuid::Uuid;
 
 pub async fn run_adapter<D, I, O, E>(
     input: I,
     output: O,
-    adapter_factory: impl FnOnce(
-        UnboundedSender<Either<ProtocolMessage, <D as DebugAdapter>::Message>>,
-    ) -> D,
-) -> Result<
-    (),
-    DebugAdapterError<E, <O as Sink<ProtocolMessage>>::Error, <D as DebugAdapter>::CustomError>,
->
+    adapter_factory: impl FnOnce(UnboundedSender<Either<Message, <D as DebugAdapter>::Message>>) -> D,
+) -> Result<(), DebugAdapterError<E, <O as Sink<Message>>::Error, <D as DebugAdapter>::CustomError>>
 where
     D: DebugAdapter + Send + 'static,
-    I: Stream<Item = Result<ProtocolMessage, E>> + Unpin + Send + 'static,
-    O: Sink<ProtocolMessage> + Unpin + Send + 'static,
+    I: Stream<Item = Result<Message, E>> + Unpin + Send + 'static,
+    O: Sink<Message> + Unpin + Send + 'static,
     E: Send + 'static,
-    <O as Sink<ProtocolMessage>>::Error: Send + 'static,
+    <O as Sink<Message>>::Error: Send + 'static,
+    <D as DebugAdapter>::CustomError: Send + 'static,
+{
+    run_traced_adapter(input, output, Tracer::disabled(), adapter_factory).await
+}
+
+/// Like [run_adapter], but records all messages from and to the client with the given [Tracer].
+pub async fn run_traced_adapter<D, I, O, E>(
+    input: I,
+    output: O,
+    tracer: Tracer,
+    adapter_factory: impl FnOnce(UnboundedSender<Either<Message, <D as DebugAdapter>::Message>>) -> D,
+) -> Result<(), DebugAdapterError<E, <O as Sink<Message>>::Error, <D as DebugAdapter>::CustomError>>
+where
+    D: DebugAdapter + Send + 'static,
+    I: Stream<Item = Result<Message, E>> + Unpin + Send + 'static,
+    O: Sink<Message> + Unpin + Send + 'static,
+    E: Send + 'static,
+    <O as Sink<Message>>::Error: Send + 'static,
+    <D as DebugAdapter>::CustomError: Send + 'static,
+{
+    run_recorded_adapter(input, output, tracer, Recorder::disabled(), adapter_factory).await
+}
+
+/// Like [run_traced_adapter], but additionally records the debug session with the given
+/// [Recorder]. The replay file is written when the debug session ends.
+pub async fn run_recorded_adapter<D, I, O, E>(
+    input: I,
+    output: O,
+    tracer: Tracer,
+    recorder: Recorder,
+    adapter_factory: impl FnOnce(UnboundedSender<Either<Message, <D as DebugAdapter>::Message>>) -> D,
+) -> Result<(), DebugAdapterError<E, <O as Sink<Message>>::Error, <D as DebugAdapter>::CustomError>>
+where
+    D: DebugAdapter + Send + 'static,
+    I: Stream<Item = Result<Message, E>> + Unpin + Send + 'static,
+    O: Sink<Message> + Unpin + Send + 'static,
+    E: Send + 'static,
+    <O as Sink<Message>>::Error: Send + 'static,
     <D as DebugAdapter>::CustomError: Send + 'static,
 {
     let (outbox_sender, outbox_receiver) = unbounded_channel();
@@ -83,18 +126,23 @@ where
         cancel_sender,
         input,
         shutdown_receiver,
+        tracer: tracer.clone(),
+        recorder: recorder.clone(),
     };
 
     let executor = DebugAdapterExecutor {
         inbox_receiver,
+        queued: VecDeque::new(),
         outbox,
         cancel_data,
         cancel_receiver,
         adapter,
         shutdown_sender,
+        tracer: tracer.clone(),
     };
 
-    let message_writer = MessageWriter::new(output);
+    let message_writer = MessageWriter::new(output).with_tracer(tracer);
+    let message_writer = RecordingMessageWriter::new(message_writer, recorder.clone());
     let sender = DebugAdapterSender {
         message_writer,
         outbox_receiver,
@@ -104,7 +152,7 @@ where
     let executor = spawn(executor.run());
     let sender = spawn(sender.run());
 
-    try_join!(
+    let result = try_join!(
         receiver
             .map(Result::unwrap) // Propagate panic
             .map_err(DebugAdapterError::Input),
@@ -114,8 +162,15 @@ where
         sender
             .map(Result::unwrap) // Propagate panic
             .map_err(DebugAdapterError::Output),
-    )?;
+    );
 
+    if let Err(e) = recorder.finish().await {
+        error!("Failed to write replay file: {}", e);
+    }
+    if recorder.dropped() > 0 {
+        error!("Dropped {} records of the replay file", recorder.dropped());
+    }
+    result?;
     Ok(())
 }
 
@@ -137,6 +192,7 @@ impl CancelData {
 pub struct DebugAdapterContextImpl {
     outbox: Outbox,
     cancel_data: Arc<Mutex<CancelData>>,
+    superseded: bool,
     shutdown: bool,
 }
 impl DebugAdapterContextImpl {
@@ -144,6 +200,7 @@ impl DebugAdapterContextImpl {
         DebugAdapterContextImpl {
             outbox,
             cancel_data,
+            superseded: false,
             shutdown: false,
         }
     }
@@ -178,7 +235,12 @@ impl DebugAdapterContext for &mut DebugAdapterContextImpl {
 
         let progress_id = progress_id.to_string();
         let outbox = self.outbox.clone();
-        ProgressContext::new(progress_id, cancel_receiver, outbox)
+        ProgressContext::new(
+            progress_id,
+            cancel_receiver,
+            outbox,
+            self.cancel_data.clone(),
+        )
     }
 
     fn end_cancellable_progress(&mut self, progress_id: String, message: Option<String>) {
@@ -193,6 +255,10 @@ impl DebugAdapterContext for &mut DebugAdapterContextImpl {
         self.fire_event(event);
     }
 
+    fn is_superseded(&self) -> bool {
+        self.superseded
+    }
+
     fn shutdown(&mut self) {
         trace!("Shutting down executor");
         self.shutdown = true
@@ -201,11 +267,15 @@ impl DebugAdapterContext for &mut DebugAdapterContextImpl {
 
 #[derive(Clone)]
 struct Outbox {
-    outbox_sender: UnboundedSender<ProtocolMessageContent>,
+    outbox_sender: UnboundedSender<Either<ProtocolMessageContent, CustomMessageContent>>,
 }
 impl Outbox {
     fn send(&self, message: impl Into<ProtocolMessageContent>) {
-        let _ = self.outbox_sender.send(message.into());
+        let _ = self.outbox_sender.send(Either::Left(message.into()));
+    }
+
+    fn send_custom(&self, message: impl Into<CustomMessageContent>) {
+        let _ = self.outbox_sender.send(Either::Right(message.into()));
     }
 
     fn respond(&self, request_id: SequenceNumber, result: Result<SuccessResponse, ErrorResponse>) {
@@ -216,6 +286,10 @@ impl Outbox {
         self.send(response);
     }
 
+    fn respond_custom(&self, request_id: SequenceNumber, command: String, body: Value) {
+        self.send_custom(CustomResponse::success(request_id, command, body));
+    }
+
     fn respond_unknown_progress(&self, request_id: SequenceNumber, progress_id: String) {
         let response = Err(CancelErrorResponse::builder()
             .message(format!("Unknown progress id: {}", progress_id))
@@ -227,18 +301,27 @@ impl Outbox {
 
 pub struct MessageWriter<O>
 where
-    O: Sink<ProtocolMessage>,
+    O: Sink<Message>,
 {
     seq: SequenceNumber,
     output: O,
+    tracer: Tracer,
 }
 
 impl<O> MessageWriter<O>
 where
-    O: Sink<ProtocolMessage> + Unpin,
+    O: Sink<Message> + Unpin,
 {
     pub fn new(output: O) -> MessageWriter<O> {
-        MessageWriter { seq: 0, output }
+        MessageWriter {
+            seq: 0,
+            output,
+            tracer: Tracer::disabled(),
+        }
+    }
+
+    fn with_tracer(self, tracer: Tracer) -> MessageWriter<O> {
+        MessageWriter { tracer, ..self }
     }
 
     pub async fn respond(
@@ -246,20 +329,50 @@ where
         request_seq: SequenceNumber,
         result: Result<SuccessResponse, ErrorResponse>,
     ) -> Result<(), O::Error> {
-        self.write_msg(ProtocolMessageContent::Response(Response {
-            request_seq,
-            result,
-        }))
-        .await
+        let msg = self.new_response(request_seq, result);
+        self.write(msg).await
     }
 
     pub async fn write_msg(
         &mut self,
         content: impl Into<ProtocolMessageContent>,
     ) -> Result<(), O::Error> {
+        let msg = self.new_msg(content);
+        self.write(msg).await
+    }
+
+    pub async fn write_custom_msg(
+        &mut self,
+        content: impl Into<CustomMessageContent>,
+    ) -> Result<(), O::Error> {
+        let msg = self.new_custom_msg(content);
+        self.write(msg).await
+    }
+
+    fn new_response(
+        &mut self,
+        request_seq: SequenceNumber,
+        result: Result<SuccessResponse, ErrorResponse>,
+    ) -> Message {
+        self.new_msg(ProtocolMessageContent::Response(Response {
+            request_seq,
+            result,
+        }))
+    }
+
+    fn new_msg(&mut self, content: impl Into<ProtocolMessageContent>) -> Message {
+        self.seq += 1;
+        ProtocolMessage::new(self.seq, content).into()
+    }
+
+    fn new_custom_msg(&mut self, content: impl Into<CustomMessageContent>) -> Message {
         self.seq += 1;
-        let msg = ProtocolMessage::new(self.seq, content);
+        CustomMessage::new(self.seq, content).into()
+    }
+
+    async fn write(&mut self, msg: Message) -> Result<(), O::Error> {
         trace!("Sending message to client: {}", msg);
+        self.tracer.adapter_message(&msg);
         self.output.send(msg).await
     }
 }
@@ -21,14 +21,27 @@ use log::{error, LevelFilter};
 use mcfunction_debug_adapter::{
     adapter::McfunctionDebugAdapter,
     codec::{ProtocolMessageDecoder, ProtocolMessageEncoder},
-    run_adapter,
+    doctor::{run_checks, DoctorConfig, DEFAULT_LOG_TIMEOUT},
+    recording::Recorder,
+    run_recorded_adapter,
+    trace::{read_client_messages, replay, Tracer},
 };
 use simplelog::{Config, WriteLogger};
-use std::io::{self};
+use std::{
+    io::{self},
+    path::Path,
+    process::exit,
+};
 use tokio_util::codec::{FramedRead, FramedWrite};
 
+const DATAPACK_ARG: &str = "datapack";
+const DOCTOR_ARG: &str = "doctor";
 const LOG_FILE_ARG: &str = "log-file";
 const LOG_LEVEL_ARG: &str = "log-level";
+const MINECRAFT_LOG_FILE_ARG: &str = "minecraft-log-file";
+const MINECRAFT_WORLD_DIR_ARG: &str = "minecraft-world-dir";
+const REPLAY_ARG: &str = "replay";
+const TRACE_FILE_ARG: &str = "trace-file";
 
 // Copy of private field log::LOG_LEVEL_NAMES
 const LOG_LEVEL_NAMES: [&str; 6] = ["OFF", "ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
@@ -65,6 +78,26 @@ See the GNU General Public License for more details.
 ",
             crate_authors!(" & ")
         ))
+        .arg(
+            Arg::with_name(DATAPACK_ARG)
+                .help("Path to the datapack whose pack_format should be checked by --doctor.")
+                .long("datapack")
+                .value_name("DATAPACK")
+                .takes_value(true)
+                .requires(DOCTOR_ARG),
+        )
+        .arg(
+            Arg::with_name(DOCTOR_ARG)
+                .help("Checks the Minecraft world and log file and exits.")
+                .long_help(
+                    "Checks the Minecraft world and log file and exits. The checks include \
+                    whether Minecraft executes commands injected by the debugger. Prints \
+                    remediation hints for every failed check and exits with a nonzero status \
+                    if any check fails.",
+                )
+                .long("doctor")
+                .requires_all(&[MINECRAFT_WORLD_DIR_ARG, MINECRAFT_LOG_FILE_ARG]),
+        )
         .arg(
             Arg::with_name(LOG_FILE_ARG)
                 .help("Path at which to create a log file.")
@@ -85,6 +118,47 @@ See the GNU General Public License for more details.
                 .possible_values(&LOG_LEVEL_NAMES)
                 .default_value(LevelFilter::Info.as_str()),
         )
+        .arg(
+            Arg::with_name(MINECRAFT_LOG_FILE_ARG)
+                .help("The log file of Minecraft, used by --doctor.")
+                .long("minecraft-log-file")
+                .value_name("MINECRAFT_LOG_FILE")
+                .takes_value(true)
+                .requires(DOCTOR_ARG),
+        )
+        .arg(
+            Arg::with_name(MINECRAFT_WORLD_DIR_ARG)
+                .help("The directory of the Minecraft world, used by --doctor.")
+                .long("minecraft-world-dir")
+                .value_name("MINECRAFT_WORLD_DIR")
+                .takes_value(true)
+                .requires(DOCTOR_ARG),
+        )
+        .arg(
+            Arg::with_name(REPLAY_ARG)
+                .help("Path to a trace file whose client messages should be replayed.")
+                .long_help(
+                    "Path to a trace file whose client messages should be replayed. Instead of \
+                    reading messages from stdin, the debug adapter sends the messages that the \
+                    client sent in the traced session with the same delays between them.",
+                )
+                .long("replay")
+                .value_name("TRACE_FILE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(TRACE_FILE_ARG)
+                .help("Path at which to create a trace file.")
+                .long_help(
+                    "Path at which to create a trace file. The trace file contains one JSON \
+                    object per line for every message from and to the client, every batch of \
+                    commands injected into Minecraft and every event received from Minecraft. \
+                    Takes precedence over the launch attribute 'traceFile'.",
+                )
+                .long("trace-file")
+                .value_name("TRACE_FILE")
+                .takes_value(true),
+        )
         .get_matches();
 
     if let Some(log_file) = matches.value_of(LOG_FILE_ARG) {
@@ -93,15 +167,52 @@ See the GNU General Public License for more details.
         WriteLogger::init(log_level, Config::default(), log_file).unwrap();
     }
 
-    let input = FramedRead::new(tokio::io::stdin(), ProtocolMessageDecoder);
+    if matches.is_present(DOCTOR_ARG) {
+        let config = DoctorConfig {
+            minecraft_world_dir: Path::new(matches.value_of(MINECRAFT_WORLD_DIR_ARG).unwrap()),
+            minecraft_log_file: Path::new(matches.value_of(MINECRAFT_LOG_FILE_ARG).unwrap()),
+            datapack: matches.value_of(DATAPACK_ARG).map(Path::new),
+            log_timeout: DEFAULT_LOG_TIMEOUT,
+        };
+        let checks = run_checks(&config).await;
+        for check in &checks {
+            println!("{}", check);
+        }
+        if checks.iter().any(|check| check.is_failed()) {
+            exit(1);
+        }
+        return Ok(());
+    }
+
+    let tracer = Tracer::new();
+    if let Some(trace_file) = matches.value_of(TRACE_FILE_ARG) {
+        tracer.start(trace_file).await?;
+    }
+
     let output = FramedWrite::new(tokio::io::stdout(), ProtocolMessageEncoder);
-    run_adapter(input, output, McfunctionDebugAdapter::new)
-        .await
-        .map_err(|e| {
-            let e = e.into_inner();
-            error!("Stopping due to: {}", e);
-            e
-        })
+    let recorder = Recorder::new();
+    let adapter_tracer = tracer.clone();
+    let adapter_recorder = recorder.clone();
+    let adapter_factory = move |message_sender| {
+        McfunctionDebugAdapter::with_tracer(message_sender, adapter_tracer)
+            .with_recorder(adapter_recorder)
+    };
+    let result = if let Some(trace_file) = matches.value_of(REPLAY_ARG) {
+        let input = replay(read_client_messages(trace_file).await?);
+        run_recorded_adapter(input, output, tracer.clone(), recorder, adapter_factory).await
+    } else {
+        let input = FramedRead::new(tokio::io::stdin(), ProtocolMessageDecoder);
+        run_recorded_adapter(input, output, tracer.clone(), recorder, adapter_factory).await
+    };
+    tracer.close().await;
+    if tracer.dropped() > 0 {
+        error!("Dropped {} entries of the trace file", tracer.dropped());
+    }
+    result.map_err(|e| {
+        let e = e.into_inner();
+        error!("Stopping due to: {}", e);
+        e
+    })
 }
 
 fn parse_log_level(log_level: &str) -> Option<LevelFilter> {
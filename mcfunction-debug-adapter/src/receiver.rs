@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{CancelData, Outbox};
+use crate::{custom::Message, recording::Recorder, trace::Tracer, CancelData, Outbox};
 use debug_adapter_protocol::{
     requests::{CancelRequestArguments, Request},
     ProtocolMessage, ProtocolMessageContent, SequenceNumber,
@@ -31,29 +31,41 @@ use tokio::sync::mpsc::{self, UnboundedSender};
 
 pub(super) struct DebugAdapterReceiver<I, E, M>
 where
-    I: Stream<Item = Result<ProtocolMessage, E>> + Unpin + 'static + Send,
+    I: Stream<Item = Result<Message, E>> + Unpin + 'static + Send,
 {
-    pub inbox_sender: UnboundedSender<Either<ProtocolMessage, M>>,
+    pub inbox_sender: UnboundedSender<Either<Message, M>>,
     pub outbox: Outbox,
     pub cancel_data: Arc<Mutex<CancelData>>,
     pub cancel_sender: UnboundedSender<SequenceNumber>,
     pub input: I,
     pub shutdown_receiver: mpsc::Receiver<()>,
+    pub tracer: Tracer,
+    pub recorder: Recorder,
 }
 
 impl<I, E, M> DebugAdapterReceiver<I, E, M>
 where
-    I: Stream<Item = Result<ProtocolMessage, E>> + Unpin + Send + 'static,
+    I: Stream<Item = Result<Message, E>> + Unpin + Send + 'static,
 {
     pub async fn run(mut self) -> Result<(), E> {
         trace!("Starting receiver");
         while let Some(message) = self.next_input().await {
             let message = message?;
             trace!("Received message from client: {}", message);
-            if let ProtocolMessageContent::Request(Request::Cancel(args)) = message.content {
-                self.handle_cancel_request(message.seq, args);
+            self.tracer.client_message(&message);
+            self.recorder.dap_request(&message);
+            if let Message::Protocol(ProtocolMessage {
+                seq,
+                content: ProtocolMessageContent::Request(Request::Cancel(args)),
+            }) = message
+            {
+                self.handle_cancel_request(seq, args);
             } else {
-                if let ProtocolMessageContent::Request(Request::Terminate(_)) = &message.content {
+                if let Message::Protocol(ProtocolMessage {
+                    content: ProtocolMessageContent::Request(Request::Terminate(_)),
+                    ..
+                }) = &message
+                {
                     self.cancel_all_progresses();
                 }
                 let _ = self.inbox_sender.send(Either::Left(message));
@@ -63,7 +75,7 @@ where
         Ok(())
     }
 
-    async fn next_input(&mut self) -> Option<Result<ProtocolMessage, E>> {
+    async fn next_input(&mut self) -> Option<Result<Message, E>> {
         let shutdown = self.shutdown_receiver.recv();
         pin_mut!(shutdown);
         match select(self.input.next(), shutdown).await {
@@ -0,0 +1,166 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
+        Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+use tokio::{fs::File, task::spawn_blocking};
+
+/// What happens to a record if `capacity` records are already waiting to be written.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Overflow {
+    /// The record is dropped and counted, so recording never blocks.
+    Drop,
+    /// Recording blocks until the record can be queued, so no record is lost once the file is
+    /// started.
+    Wait,
+}
+
+/// The streaming core shared by the [Recorder](crate::recording::Recorder) and the
+/// [Tracer](crate::trace::Tracer). Records are written as one line each by a background thread, so
+/// at most `capacity` records are held in memory. Records made before the file is known are
+/// buffered up to the same capacity; further ones are dropped and counted.
+pub(crate) struct RecordFile {
+    capacity: usize,
+    overflow: Overflow,
+    output: Mutex<RecordOutput>,
+    writer: Mutex<Option<JoinHandle<io::Result<()>>>>,
+    dropped: AtomicU64,
+}
+
+enum RecordOutput {
+    /// Records are buffered until the file is known or recording is disabled.
+    Pending(Vec<String>),
+    Started(SyncSender<String>),
+    Disabled,
+}
+
+impl RecordFile {
+    /// Creates a [RecordFile] that buffers records until either [RecordFile::start] or
+    /// [RecordFile::disable] is called.
+    pub fn new(capacity: usize, overflow: Overflow) -> RecordFile {
+        RecordFile::with_output(capacity, overflow, RecordOutput::Pending(Vec::new()))
+    }
+
+    /// Creates a [RecordFile] that never records anything.
+    pub fn disabled() -> RecordFile {
+        RecordFile::with_output(0, Overflow::Drop, RecordOutput::Disabled)
+    }
+
+    fn with_output(capacity: usize, overflow: Overflow, output: RecordOutput) -> RecordFile {
+        RecordFile {
+            capacity,
+            overflow,
+            output: Mutex::new(output),
+            writer: Mutex::new(None),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates the file and starts writing all buffered records to it. Has no effect if the
+    /// [RecordFile] is already started or disabled.
+    pub async fn start(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        if !matches!(*self.output.lock().unwrap(), RecordOutput::Pending(..)) {
+            return Ok(());
+        }
+        let file = File::create(path).await?.into_std().await;
+
+        let (sender, receiver) = sync_channel(self.capacity);
+        let mut output = self.output.lock().unwrap();
+        if let RecordOutput::Pending(records) = &mut *output {
+            for record in records.drain(..) {
+                // Can't be full, because at most capacity records are pending
+                let _ = sender.try_send(record);
+            }
+            *output = RecordOutput::Started(sender);
+            *self.writer.lock().unwrap() = Some(thread::spawn(|| write_records(file, receiver)));
+        }
+        Ok(())
+    }
+
+    /// Discards all buffered records and stops recording. Has no effect if the [RecordFile] is
+    /// already started.
+    pub fn disable(&self) {
+        let mut output = self.output.lock().unwrap();
+        if let RecordOutput::Pending(..) = *output {
+            *output = RecordOutput::Disabled;
+        }
+    }
+
+    /// Stops recording and waits until all queued records are written to the file.
+    pub async fn close(&self) -> io::Result<()> {
+        *self.output.lock().unwrap() = RecordOutput::Disabled;
+        let writer = self.writer.lock().unwrap().take();
+        match writer {
+            Some(writer) => spawn_blocking(|| writer.join())
+                .await
+                .map_err(io::Error::other)?
+                .unwrap_or_else(|_| Err(io::Error::other("The record writer panicked"))),
+            None => Ok(()),
+        }
+    }
+
+    /// The number of records that were dropped, because they could not be written fast enough.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Queues the record created by `record` for writing. `record` is only called if recording is
+    /// not disabled and it is called while holding a lock, so records are written in the order in
+    /// which they were created.
+    pub fn record(&self, record: impl FnOnce() -> String) {
+        let mut output = self.output.lock().unwrap();
+        let dropped = match &mut *output {
+            RecordOutput::Pending(records) if records.len() < self.capacity => {
+                records.push(record());
+                false
+            }
+            RecordOutput::Pending(..) => true,
+            RecordOutput::Started(sender) => match self.overflow {
+                Overflow::Drop => matches!(sender.try_send(record()), Err(TrySendError::Full(..))),
+                // Only fails if the writer stopped after an error, which close returns
+                Overflow::Wait => sender.send(record()).is_err(),
+            },
+            RecordOutput::Disabled => false,
+        };
+        if dropped {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn write_records(file: fs::File, receiver: Receiver<String>) -> io::Result<()> {
+    let mut writer = BufWriter::new(file);
+    for record in receiver {
+        writer.write_all(record.as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
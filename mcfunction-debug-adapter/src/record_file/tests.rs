@@ -0,0 +1,93 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use std::{
+    fs::{create_dir_all, read_to_string, remove_file},
+    path::PathBuf,
+};
+
+fn record_file(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("mcfunction-debug-adapter-record-file-test");
+    create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let _ = remove_file(&path);
+    path
+}
+
+fn read_lines(path: &Path) -> Vec<String> {
+    read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_wait_streams_more_records_than_capacity() {
+    // given:
+    let path = record_file("test_wait_streams_more_records_than_capacity.ndjson");
+    let file = RecordFile::new(2, Overflow::Wait);
+    file.record(|| "0".to_string());
+    file.start(&path).await.unwrap();
+
+    // when:
+    for index in 1..100 {
+        file.record(|| index.to_string());
+    }
+    file.close().await.unwrap();
+
+    // then:
+    let expected = (0..100).map(|index| index.to_string()).collect::<Vec<_>>();
+    assert_eq!(read_lines(&path), expected);
+    assert_eq!(file.dropped(), 0);
+}
+
+#[tokio::test]
+async fn test_drops_pending_records_over_capacity() {
+    // given:
+    let path = record_file("test_drops_pending_records_over_capacity.ndjson");
+    let file = RecordFile::new(2, Overflow::Wait);
+
+    // when:
+    for index in 0..5 {
+        file.record(|| index.to_string());
+    }
+    file.start(&path).await.unwrap();
+    file.close().await.unwrap();
+
+    // then:
+    assert_eq!(read_lines(&path), ["0", "1"]);
+    assert_eq!(file.dropped(), 3);
+}
+
+#[tokio::test]
+async fn test_disabled_file_is_not_created() {
+    // given:
+    let path = record_file("test_disabled_file_is_not_created.ndjson");
+    let file = RecordFile::new(2, Overflow::Drop);
+    file.disable();
+
+    // when:
+    file.start(&path).await.unwrap();
+    file.record(|| unreachable!());
+    file.close().await.unwrap();
+
+    // then:
+    assert!(!path.exists());
+}
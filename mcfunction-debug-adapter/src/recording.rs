@@ -0,0 +1,207 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    custom::{CustomMessageContent, Message},
+    record_file::{Overflow, RecordFile},
+    MessageWriter,
+};
+use debug_adapter_protocol::responses::{ErrorResponse, SuccessResponse};
+use debug_adapter_protocol::{ProtocolMessageContent, SequenceNumber};
+use futures::Sink;
+use minect::log::LogEvent;
+use serde_json::{json, Value};
+use std::{io, path::Path, sync::Arc, time::Instant};
+
+/// How many records can be queued for writing before recording waits for the replay file.
+const REPLAY_CAPACITY: usize = 1024;
+
+const DAP_REQUEST: &str = "dap_request";
+const DAP_RESPONSE: &str = "dap_response";
+const MC_COMMAND: &str = "mc_command";
+const MC_EVENT: &str = "mc_event";
+
+pub(crate) const REPLAY_FILE_ATTRIBUTE: &str = "replayFile";
+
+/// Records a debug session for offline analysis. The replay file contains one JSON object per line
+/// for every message from the client (`dap_request`), every message to the client
+/// (`dap_response`), every command injected into Minecraft (`mc_command`) and every event received
+/// from Minecraft (`mc_event`):
+/// ```json
+/// {"data":{"executor":"mcfunction-debugger","output":"Added tag 'exited' to mcfunction-debugger"},"kind":"mc_event","t":1520348}
+/// ```
+/// `t` is the number of nanoseconds since the [Recorder] was created. Records are written in the
+/// order in which they were recorded, so `t` never decreases.
+///
+/// Records are streamed to the replay file like the entries of a [Tracer](crate::trace::Tracer),
+/// but once the replay file is started a [Recorder] waits for the file instead of dropping records.
+#[derive(Clone)]
+pub struct Recorder {
+    inner: Arc<RecorderInner>,
+}
+
+struct RecorderInner {
+    start: Instant,
+    file: RecordFile,
+}
+
+impl Recorder {
+    /// Creates a [Recorder] that buffers records until either [Recorder::start] or
+    /// [Recorder::disable] is called.
+    pub fn new() -> Recorder {
+        Recorder::with_file(RecordFile::new(REPLAY_CAPACITY, Overflow::Wait))
+    }
+
+    /// Creates a [Recorder] that never records anything.
+    pub fn disabled() -> Recorder {
+        Recorder::with_file(RecordFile::disabled())
+    }
+
+    fn with_file(file: RecordFile) -> Recorder {
+        Recorder {
+            inner: Arc::new(RecorderInner {
+                start: Instant::now(),
+                file,
+            }),
+        }
+    }
+
+    /// Creates the replay file and starts writing all buffered records to it. Has no effect if the
+    /// [Recorder] is already started or disabled.
+    pub async fn start(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.inner.file.start(path).await
+    }
+
+    /// Discards all buffered records and stops recording. Has no effect if the [Recorder] is
+    /// already started.
+    pub fn disable(&self) {
+        self.inner.file.disable()
+    }
+
+    /// Stops recording and waits until all records are written to the replay file, if the
+    /// [Recorder] was started.
+    pub async fn finish(&self) -> io::Result<()> {
+        self.inner.file.close().await
+    }
+
+    /// The number of records that were dropped, because more than [REPLAY_CAPACITY] records were
+    /// made before the replay file was started.
+    pub fn dropped(&self) -> u64 {
+        self.inner.file.dropped()
+    }
+
+    pub(crate) fn dap_request(&self, message: &Message) {
+        self.record(DAP_REQUEST, || serde_json::to_value(message).unwrap());
+    }
+
+    pub(crate) fn dap_response(&self, message: &Message) {
+        self.record(DAP_RESPONSE, || serde_json::to_value(message).unwrap());
+    }
+
+    pub(crate) fn mc_command(&self, name: Option<&str>, command: &str) {
+        self.record(MC_COMMAND, || {
+            json!({
+                "name": name,
+                "command": command,
+            })
+        });
+    }
+
+    pub(crate) fn mc_event(&self, event: &LogEvent) {
+        self.record(MC_EVENT, || {
+            json!({
+                "executor": event.executor,
+                "output": event.output,
+            })
+        });
+    }
+
+    fn record(&self, kind: &str, data: impl FnOnce() -> Value) {
+        self.inner.file.record(|| {
+            // The time is taken while holding the lock of the file to keep the records ordered by
+            // time
+            let t = self.inner.start.elapsed().as_nanos() as u64;
+            json!({
+                "t": t,
+                "kind": kind,
+                "data": data(),
+            })
+            .to_string()
+        });
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder::new()
+    }
+}
+
+/// A [MessageWriter] that records every message it writes as `dap_response` with a [Recorder].
+pub struct RecordingMessageWriter<O>
+where
+    O: Sink<Message>,
+{
+    message_writer: MessageWriter<O>,
+    recorder: Recorder,
+}
+
+impl<O> RecordingMessageWriter<O>
+where
+    O: Sink<Message> + Unpin,
+{
+    pub fn new(message_writer: MessageWriter<O>, recorder: Recorder) -> RecordingMessageWriter<O> {
+        RecordingMessageWriter {
+            message_writer,
+            recorder,
+        }
+    }
+
+    pub async fn respond(
+        &mut self,
+        request_seq: SequenceNumber,
+        result: Result<SuccessResponse, ErrorResponse>,
+    ) -> Result<(), O::Error> {
+        let msg = self.message_writer.new_response(request_seq, result);
+        self.write(msg).await
+    }
+
+    pub async fn write_msg(
+        &mut self,
+        content: impl Into<ProtocolMessageContent>,
+    ) -> Result<(), O::Error> {
+        let msg = self.message_writer.new_msg(content);
+        self.write(msg).await
+    }
+
+    pub async fn write_custom_msg(
+        &mut self,
+        content: impl Into<CustomMessageContent>,
+    ) -> Result<(), O::Error> {
+        let msg = self.message_writer.new_custom_msg(content);
+        self.write(msg).await
+    }
+
+    async fn write(&mut self, msg: Message) -> Result<(), O::Error> {
+        self.recorder.dap_response(&msg);
+        self.message_writer.write(msg).await
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,138 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use debug_adapter_protocol::{requests::Request, ProtocolMessage};
+use futures::channel::mpsc::unbounded;
+use minect::log::LogEvent;
+use std::{fs::create_dir_all, path::PathBuf};
+use tokio::fs::{read_to_string, remove_file};
+
+async fn replay_file(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("mcfunction-debug-adapter-recording-test");
+    create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let _ = remove_file(&path).await;
+    path
+}
+
+async fn read_records(path: &Path) -> Vec<Value> {
+    read_to_string(path)
+        .await
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn log_event(executor: &str, output: &str) -> LogEvent {
+    format!(
+        "[00:00:00] [Server thread/INFO]: [{}: {}]",
+        executor, output
+    )
+    .parse()
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_records_in_order() {
+    // given:
+    let path = replay_file("test_records_in_order.ndjson").await;
+    let recorder = Recorder::new();
+    recorder.start(&path).await.unwrap();
+
+    // when:
+    let request = ProtocolMessage::new(1, Request::Threads).into();
+    recorder.dap_request(&request);
+    recorder.mc_command(Some("mcfunction-debugger"), "function debug:resume");
+    recorder.mc_event(&log_event("mcfunction-debugger", "Resumed"));
+    recorder.finish().await.unwrap();
+
+    // then:
+    let records = read_records(&path).await;
+    let kinds = records.iter().map(|it| &it["kind"]).collect::<Vec<_>>();
+    assert_eq!(kinds, [DAP_REQUEST, MC_COMMAND, MC_EVENT]);
+    assert_eq!(records[0]["data"], serde_json::to_value(&request).unwrap());
+    assert_eq!(records[1]["data"]["name"], "mcfunction-debugger");
+    assert_eq!(records[1]["data"]["command"], "function debug:resume");
+    assert_eq!(records[2]["data"]["executor"], "mcfunction-debugger");
+    assert_eq!(records[2]["data"]["output"], "Resumed");
+    let times = records
+        .iter()
+        .map(|it| it["t"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert!(times.windows(2).all(|it| it[0] <= it[1]), "{:?}", times);
+}
+
+#[tokio::test]
+async fn test_writes_pending_records_on_finish() {
+    // given:
+    let path = replay_file("test_writes_pending_records_on_finish.ndjson").await;
+    let recorder = Recorder::new();
+    recorder.mc_command(None, "reload");
+
+    // when:
+    recorder.start(&path).await.unwrap();
+    recorder.finish().await.unwrap();
+
+    // then:
+    let records = read_records(&path).await;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["data"]["command"], "reload");
+}
+
+#[tokio::test]
+async fn test_disabled_recorder_does_not_record() {
+    // given:
+    let path = replay_file("test_disabled_recorder_does_not_record.ndjson").await;
+    let recorder = Recorder::new();
+    recorder.disable();
+
+    // when:
+    recorder.start(&path).await.unwrap();
+    recorder.mc_command(None, "reload");
+    recorder.finish().await.unwrap();
+
+    // then:
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_recording_message_writer() {
+    // given:
+    let path = replay_file("test_recording_message_writer.ndjson").await;
+    let recorder = Recorder::new();
+    recorder.start(&path).await.unwrap();
+    let (output, _receiver) = unbounded();
+    let mut writer = RecordingMessageWriter::new(MessageWriter::new(output), recorder.clone());
+
+    // when:
+    writer
+        .respond(1, Ok(SuccessResponse::Disconnect))
+        .await
+        .unwrap();
+    recorder.finish().await.unwrap();
+
+    // then:
+    let records = read_records(&path).await;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["kind"], DAP_RESPONSE);
+    assert_eq!(records[0]["data"]["seq"], 1);
+    assert_eq!(records[0]["data"]["request_seq"], 1);
+    assert_eq!(records[0]["data"]["command"], "disconnect");
+}
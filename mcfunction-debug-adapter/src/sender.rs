@@ -16,28 +16,34 @@
 // You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use crate::MessageWriter;
-use debug_adapter_protocol::{ProtocolMessage, ProtocolMessageContent};
-use futures::Sink;
+use crate::{
+    custom::{CustomMessageContent, Message},
+    recording::RecordingMessageWriter,
+};
+use debug_adapter_protocol::ProtocolMessageContent;
+use futures::{future::Either, Sink};
 use log::trace;
 use tokio::sync::mpsc::UnboundedReceiver;
 
 pub(super) struct DebugAdapterSender<O>
 where
-    O: Sink<ProtocolMessage>,
+    O: Sink<Message>,
 {
-    pub message_writer: MessageWriter<O>,
-    pub outbox_receiver: UnboundedReceiver<ProtocolMessageContent>,
+    pub message_writer: RecordingMessageWriter<O>,
+    pub outbox_receiver: UnboundedReceiver<Either<ProtocolMessageContent, CustomMessageContent>>,
 }
 
 impl<O> DebugAdapterSender<O>
 where
-    O: Sink<ProtocolMessage> + Unpin,
+    O: Sink<Message> + Unpin,
 {
     pub async fn run(mut self) -> Result<(), O::Error> {
         trace!("Starting sender");
         while let Some(message) = self.outbox_receiver.recv().await {
-            self.message_writer.write_msg(message).await?;
+            match message {
+                Either::Left(message) => self.message_writer.write_msg(message).await?,
+                Either::Right(message) => self.message_writer.write_custom_msg(message).await?,
+            }
         }
         trace!("Stopped sender");
         Ok(())
@@ -0,0 +1,482 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::adapter::{
+    connection::{add_listener, dispatch, Connect, Connection, Listener},
+    LISTENER_NAME,
+};
+use futures::stream::BoxStream;
+use mcfunction_debugger::StoppedReason;
+use minect::{log::LogEvent, Command};
+use serde_json::Value;
+use std::{
+    fs::read_to_string,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::sleep;
+
+/// The namespace of the debug datapack, which is hardcoded in the adapter.
+const NAMESPACE: &str = "mcfd";
+/// The executor of commands that are executed without a name.
+const DEFAULT_EXECUTOR: &str = "@";
+/// Recorded as a command when a reload finished.
+pub(crate) const RELOAD_FINISHED: &str = "# reload finished";
+const LOGGED_COMMAND_PREFIX: &str = "execute at @e[type=area_effect_cloud,tag=minect_connection,\
+    limit=1] run summon command_block_minecart ~ ~ ~ {";
+
+/// A description of what Minecraft does in response to the commands that the adapter injects.
+///
+/// The expected commands must be injected in the given order, other commands are answered like
+/// Minecraft would for the commands the adapter uses to query its state: named entities are
+/// summoned and the stack trace of the current stop is reported.
+pub(crate) struct Scenario {
+    debug_datapack: PathBuf,
+    steps: Vec<Step>,
+    reload_duration: Duration,
+}
+
+struct Step {
+    command: String,
+    responses: Vec<Response>,
+}
+
+enum Response {
+    Stop {
+        reason: StoppedReason,
+        position: String,
+        callers: Vec<String>,
+        pending_executors: Vec<String>,
+    },
+    Exit,
+}
+
+impl Scenario {
+    /// Creates an empty scenario for a debug datapack that is generated at `debug_datapack`.
+    pub(crate) fn new(debug_datapack: impl Into<PathBuf>) -> Scenario {
+        Scenario {
+            debug_datapack: debug_datapack.into(),
+            steps: Vec::new(),
+            reload_duration: Duration::ZERO,
+        }
+    }
+
+    /// Like Minecraft, the mock finishes a reload after the batch of commands that contains it. With
+    /// a `duration` other batches may run before a reload finished.
+    pub(crate) fn reload_duration(mut self, duration: Duration) -> Scenario {
+        self.reload_duration = duration;
+        self
+    }
+
+    /// Expects a command that contains `command` after all commands expected so far.
+    pub(crate) fn expect(mut self, command: impl Into<String>) -> Scenario {
+        self.steps.push(Step {
+            command: command.into(),
+            responses: Vec::new(),
+        });
+        self
+    }
+
+    /// Responds to the last expected command by suspending at `position`, for example
+    /// `test:main:2_breakpoint`. `callers` are the locations of the function calls from the
+    /// outermost to the innermost one.
+    ///
+    /// Like Minecraft, the mock can only suspend where the debug datapack has a suspend function,
+    /// so this verifies that the adapter generated the breakpoint.
+    pub(crate) fn stop(
+        mut self,
+        reason: StoppedReason,
+        position: &str,
+        callers: &[&str],
+    ) -> Scenario {
+        self.last_step().responses.push(Response::Stop {
+            reason,
+            position: position.to_string(),
+            callers: callers.iter().map(ToString::to_string).collect(),
+            pending_executors: Vec::new(),
+        });
+        self
+    }
+
+    /// Lets the executors with the given `names` wait for the suspended function in the last stop,
+    /// as if it was called with `execute as`. They are reported in the order in which they run.
+    pub(crate) fn pending_executors(mut self, names: &[&str]) -> Scenario {
+        match self.last_step().responses.last_mut() {
+            Some(Response::Stop {
+                pending_executors, ..
+            }) => *pending_executors = names.iter().map(ToString::to_string).collect(),
+            _ => panic!("Pending executors must follow a stop"),
+        }
+        self
+    }
+
+    /// Responds to the last expected command by finishing the execution of the debugged function.
+    pub(crate) fn exit(mut self) -> Scenario {
+        self.last_step().responses.push(Response::Exit);
+        self
+    }
+
+    fn last_step(&mut self) -> &mut Step {
+        self.steps
+            .last_mut()
+            .expect("Responses must follow an expected command")
+    }
+}
+
+struct State {
+    scenario: Scenario,
+    next_step: usize,
+    /// The locations of the current stop from the outermost caller to the suspended function.
+    stack_trace: Vec<String>,
+    /// The names of the executors that wait for the suspended function of the current stop.
+    pending_executors: Vec<String>,
+    /// The score of `reload_batch`, which the load function of the debug datapack reports.
+    reload_batch: Option<i32>,
+    commands: Vec<String>,
+}
+
+/// A [Connection] that plays a [Scenario] instead of executing commands in Minecraft. Clones share
+/// their listeners and scenario, so a clone can verify the scenario after the adapter finished.
+#[derive(Clone)]
+pub(crate) struct MockMinecraftConnection {
+    state: Arc<Mutex<State>>,
+    listeners: Arc<Mutex<Vec<Listener>>>,
+}
+
+impl MockMinecraftConnection {
+    pub(crate) fn new(scenario: Scenario) -> MockMinecraftConnection {
+        MockMinecraftConnection {
+            state: Arc::new(Mutex::new(State {
+                scenario,
+                next_step: 0,
+                stack_trace: Vec::new(),
+                pending_executors: Vec::new(),
+                reload_batch: None,
+                commands: Vec::new(),
+            })),
+            listeners: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns a [Connect] that connects to this mock, see
+    /// [McfunctionDebugAdapter::with_connection](crate::adapter::McfunctionDebugAdapter).
+    pub(crate) fn connect(&self) -> Connect {
+        let connection = self.clone();
+        Arc::new(move || Ok(Box::new(connection.clone()) as Box<dyn Connection>))
+    }
+
+    /// Panics unless all expected commands of the scenario were injected.
+    pub(crate) fn assert_finished(&self) {
+        let state = self.state.lock().unwrap();
+        let missing = state.scenario.steps[state.next_step..]
+            .iter()
+            .map(|step| step.command.as_str())
+            .collect::<Vec<_>>();
+        assert!(
+            missing.is_empty(),
+            "Expected commands were not injected: {:?}\nInjected commands:\n{}",
+            missing,
+            state.commands.join("\n")
+        );
+    }
+
+    /// Returns the injected commands, including [RELOAD_FINISHED] whenever a reload finished.
+    pub(crate) fn commands(&self) -> Vec<String> {
+        self.state.lock().unwrap().commands.clone()
+    }
+
+    /// Runs the load function of the debug datapack like Minecraft does at the end of a reload.
+    fn finish_reload(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.commands.push(RELOAD_FINISHED.to_string());
+        let load_function = state
+            .scenario
+            .debug_datapack
+            .join(format!("data/{}/functions/load.mcfunction", NAMESPACE));
+        let reports_reload_batch = read_to_string(load_function)
+            .is_ok_and(|content| content.contains(&reload_batch_query()));
+        if let (true, Some(reload_batch)) = (reports_reload_batch, state.reload_batch) {
+            self.send(
+                LISTENER_NAME,
+                format!(
+                    "Added 0 to [{}_global] for reload_batch (now {})",
+                    NAMESPACE, reload_batch
+                ),
+            );
+        }
+    }
+
+    fn execute_command(&self, state: &mut State, command: &Command) {
+        let (name, command) = match unwrap_logged_command(command.get_command()) {
+            Some((name, command)) => (name, command),
+            None => (
+                command.get_name().map(ToString::to_string),
+                command.get_command().to_string(),
+            ),
+        };
+        let executor = name.as_deref().unwrap_or(DEFAULT_EXECUTOR);
+        state.commands.push(command.clone());
+
+        let set_reload_batch = format!("scoreboard players set reload_batch {}_global ", NAMESPACE);
+        if let Some(name) = parse_summon_named_entity(&command) {
+            self.send(executor, format!("Summoned new {}", name));
+        } else if let Some(tag) = command.strip_prefix("tag @s add ") {
+            self.send(executor, format!("Added tag '{}' to {}", tag, executor));
+        } else if command == function_call_query() {
+            let callers = &state.stack_trace[..state.stack_trace.len().saturating_sub(1)];
+            for (depth, location) in callers.iter().enumerate() {
+                self.send(
+                    location,
+                    format!(
+                        "Added 0 to [{}_depth] for {} (now {})",
+                        NAMESPACE, location, depth
+                    ),
+                );
+            }
+        } else if let Some(score) = command.strip_prefix(&set_reload_batch) {
+            state.reload_batch = score.parse().ok();
+        } else if command == breakpoint_query() {
+            if let Some(location) = state.stack_trace.last() {
+                self.send(
+                    location,
+                    format!("Added tag '{}_stack_trace' to {}", NAMESPACE, location),
+                );
+            }
+        } else if command == pending_contexts_query("iter") {
+            // The first iteration is the suspended executor
+            for (index, name) in state.pending_executors.iter().enumerate() {
+                self.send(
+                    name,
+                    format!(
+                        "Added 0 to [{}_iter] for {} (now {})",
+                        NAMESPACE,
+                        name,
+                        index + 2
+                    ),
+                );
+            }
+        } else if command == pending_contexts_query("id") {
+            for (index, name) in state.pending_executors.iter().enumerate() {
+                self.send(
+                    name,
+                    format!(
+                        "Added 0 to [{}_id] for {} (now {})",
+                        NAMESPACE,
+                        name,
+                        index + 1
+                    ),
+                );
+            }
+        } else if let Some(id) = parse_executor_query(&command) {
+            if let Some(name) = id
+                .checked_sub(1)
+                .and_then(|index| state.pending_executors.get(index))
+            {
+                self.send(
+                    name,
+                    format!("Added 0 to [{}_id] for {} (now {})", NAMESPACE, name, id),
+                );
+            }
+        }
+
+        let responses = match state.scenario.steps.get_mut(state.next_step) {
+            Some(step) if command.contains(&step.command) => std::mem::take(&mut step.responses),
+            _ => return,
+        };
+        state.next_step += 1;
+        for response in responses {
+            match response {
+                Response::Stop {
+                    reason,
+                    position,
+                    callers,
+                    pending_executors,
+                } => {
+                    let (function, line_position) = position.rsplit_once(':').unwrap();
+                    let (line_number, _position_in_line) = line_position.split_once('_').unwrap();
+                    let (namespace, path) = function.split_once(':').unwrap();
+                    let suspend_function =
+                        suspend_function_dir(&state.scenario.debug_datapack, namespace, path)
+                            .join(format!("suspend_at_{}.mcfunction", line_position));
+                    assert!(
+                        suspend_function.is_file(),
+                        "The debug datapack cannot suspend at {}, because {} does not exist",
+                        position,
+                        suspend_function.display()
+                    );
+                    let mut stack_trace = callers;
+                    stack_trace.push(format!("{}:{}", function, line_number));
+                    state.stack_trace = stack_trace;
+                    state.pending_executors = pending_executors;
+                    self.send(
+                        LISTENER_NAME,
+                        format!(
+                            "Added tag 'stopped+{}+{}+{}+{}' to {}",
+                            reason,
+                            namespace,
+                            path.replace('/', "+"),
+                            line_position,
+                            LISTENER_NAME
+                        ),
+                    );
+                }
+                Response::Exit => {
+                    state.stack_trace.clear();
+                    state.pending_executors.clear();
+                    self.send(
+                        LISTENER_NAME,
+                        format!("Added tag 'exited' to {}", LISTENER_NAME),
+                    );
+                }
+            }
+        }
+    }
+
+    fn send(&self, executor: &str, output: String) {
+        // LogEvents can only be created by parsing a line of Minecraft's log file
+        let event = format!(
+            "[00:00:00] [Server thread/INFO]: [{}: {}]",
+            executor, output
+        )
+        .parse::<LogEvent>()
+        .unwrap();
+        dispatch(&self.listeners, event);
+    }
+}
+
+impl Connection for MockMinecraftConnection {
+    fn add_listener(&mut self) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, None)
+    }
+
+    fn add_named_listener(&mut self, name: &str) -> BoxStream<'static, LogEvent> {
+        add_listener(&self.listeners, Some(name.to_string()))
+    }
+
+    fn execute_commands(&mut self, commands: Vec<Command>) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        for command in &commands {
+            self.execute_command(&mut state, command);
+        }
+        let reload_duration = state.scenario.reload_duration;
+        drop(state);
+        if commands
+            .iter()
+            .any(|command| command.get_command() == "reload")
+        {
+            if reload_duration.is_zero() {
+                self.finish_reload();
+            } else {
+                let connection = self.clone();
+                tokio::spawn(async move {
+                    sleep(reload_duration).await;
+                    connection.finish_reload();
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn suspend_function_dir(debug_datapack: &Path, namespace: &str, path: &str) -> PathBuf {
+    path.split('/').fold(
+        debug_datapack
+            .join("data")
+            .join(NAMESPACE)
+            .join("functions")
+            .join(namespace),
+        |dir, segment| dir.join(segment),
+    )
+}
+
+fn function_call_query() -> String {
+    format!(
+        "execute as @e[type=area_effect_cloud,tag={0}_function_call] run \
+        scoreboard players add @s {0}_depth 0",
+        NAMESPACE
+    )
+}
+
+fn reload_batch_query() -> String {
+    format!("scoreboard players add reload_batch {}_global 0", NAMESPACE)
+}
+
+fn breakpoint_query() -> String {
+    format!(
+        "execute as @e[type=area_effect_cloud,tag={0}_breakpoint] run tag @s add {0}_stack_trace",
+        NAMESPACE
+    )
+}
+
+fn pending_contexts_query(scoreboard: &str) -> String {
+    format!(
+        "execute as @e[type=area_effect_cloud,tag={0}_context,tag={0}_active,tag=!{0}_current] \
+        if score @s {0}_depth = current {0}_depth run scoreboard players add @s {0}_{1} 0",
+        NAMESPACE, scoreboard
+    )
+}
+
+/// Returns the id if the command queries the name of the executor with that `-ns-_id` score.
+fn parse_executor_query(command: &str) -> Option<usize> {
+    let prefix = format!("execute as @e[tag=!{0}_context,scores={{{0}_id=", NAMESPACE);
+    let (id, rest) = command.strip_prefix(&prefix)?.split_once('}')?;
+    let suffix = format!(",limit=1] run scoreboard players add @s {}_id 0", NAMESPACE);
+    (rest == suffix).then_some(())?;
+    id.parse().ok()
+}
+
+/// Returns the name of the entity if the command summons a named area effect cloud.
+fn parse_summon_named_entity(command: &str) -> Option<String> {
+    let nbt = command.strip_prefix("summon area_effect_cloud ~ ~ ~ {\"CustomName\":")?;
+    let (name, _) = read_json_string(nbt)?;
+    Some(
+        serde_json::from_str::<Value>(&name).ok()?["text"]
+            .as_str()?
+            .to_string(),
+    )
+}
+
+/// Returns the name and command of a command that was generated with minect's `logged_command` or
+/// `named_logged_command`.
+fn unwrap_logged_command(command: &str) -> Option<(Option<String>, String)> {
+    let nbt = command.strip_prefix(LOGGED_COMMAND_PREFIX)?;
+    let (name, nbt) = match nbt.strip_prefix("\"CustomName\":") {
+        Some(nbt) => {
+            let (custom_name, nbt) = read_json_string(nbt)?;
+            let name = serde_json::from_str::<Value>(&custom_name).ok()?["text"]
+                .as_str()?
+                .to_string();
+            (Some(name), nbt.strip_prefix(',')?)
+        }
+        None => (None, nbt),
+    };
+    let (command, _) = read_json_string(nbt.strip_prefix("\"Command\":")?)?;
+    Some((name, command))
+}
+
+/// Reads the JSON string at the start of `json` and returns it unescaped together with the rest.
+fn read_json_string(json: &str) -> Option<(String, &str)> {
+    let mut strings = serde_json::Deserializer::from_str(json).into_iter::<String>();
+    let string = strings.next()?.ok()?;
+    Some((string, &json[strings.byte_offset()..]))
+}
+
+#[cfg(test)]
+mod tests;
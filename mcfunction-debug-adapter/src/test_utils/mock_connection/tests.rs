@@ -0,0 +1,144 @@
+use super::*;
+use futures::StreamExt;
+use minect::command::{named_logged_command, summon_named_entity_command};
+use std::fs::{create_dir_all, remove_dir_all, write};
+
+fn debug_datapack(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debug-adapter-mock-connection-test")
+        .join(name);
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn create_suspend_function(debug_datapack: &Path, function: &str, position: &str) {
+    let (namespace, path) = function.split_once(':').unwrap();
+    let dir = suspend_function_dir(debug_datapack, namespace, path);
+    create_dir_all(&dir).unwrap();
+    write(dir.join(format!("suspend_at_{}.mcfunction", position)), "").unwrap();
+}
+
+#[test]
+fn test_unwrap_logged_command() {
+    // given:
+    let command = named_logged_command(LISTENER_NAME, summon_named_entity_command("start"));
+
+    // when:
+    let actual = unwrap_logged_command(&command);
+
+    // then:
+    assert_eq!(
+        actual,
+        Some((
+            Some(LISTENER_NAME.to_string()),
+            summon_named_entity_command("start")
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_summon_named_entity_is_answered() {
+    // given:
+    let mut connection = MockMinecraftConnection::new(Scenario::new(debug_datapack(
+        "test_summon_named_entity_is_answered",
+    )));
+    let mut events = connection.add_named_listener(LISTENER_NAME);
+
+    // when:
+    connection
+        .execute_commands(vec![Command::new(named_logged_command(
+            LISTENER_NAME,
+            summon_named_entity_command("start"),
+        ))])
+        .unwrap();
+
+    // then:
+    let event = events.next().await.unwrap();
+    assert_eq!(event.output, "Summoned new start");
+}
+
+#[tokio::test]
+async fn test_stop_reports_stack_trace() {
+    // given:
+    let debug_datapack = debug_datapack("test_stop_reports_stack_trace");
+    create_suspend_function(&debug_datapack, "test:inner", "1_breakpoint");
+    let mut connection = MockMinecraftConnection::new(
+        Scenario::new(&debug_datapack)
+            .expect("schedule function debug:test/outer")
+            .stop(
+                StoppedReason::Breakpoint,
+                "test:inner:1_breakpoint",
+                &["test:outer:2"],
+            ),
+    );
+    let events = connection.add_listener();
+
+    // when:
+    connection
+        .execute_commands(vec![
+            Command::new("schedule function debug:test/outer 1t"),
+            Command::new(function_call_query()),
+            Command::new(breakpoint_query()),
+        ])
+        .unwrap();
+
+    // then:
+    let outputs = events
+        .take(3)
+        .map(|event| (event.executor, event.output))
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(
+        outputs,
+        vec![
+            (
+                LISTENER_NAME.to_string(),
+                format!(
+                    "Added tag 'stopped+breakpoint+test+inner+1_breakpoint' to {}",
+                    LISTENER_NAME
+                )
+            ),
+            (
+                "test:outer:2".to_string(),
+                "Added 0 to [mcfd_depth] for test:outer:2 (now 0)".to_string()
+            ),
+            (
+                "test:inner:1".to_string(),
+                "Added tag 'mcfd_stack_trace' to test:inner:1".to_string()
+            ),
+        ]
+    );
+    connection.assert_finished();
+}
+
+#[test]
+#[should_panic(expected = "The debug datapack cannot suspend at test:main:2_breakpoint")]
+fn test_stop_without_suspend_function() {
+    // given:
+    let mut connection = MockMinecraftConnection::new(
+        Scenario::new(debug_datapack("test_stop_without_suspend_function"))
+            .expect("schedule function debug:test/main")
+            .stop(StoppedReason::Breakpoint, "test:main:2_breakpoint", &[]),
+    );
+
+    // when:
+    let _ = connection.execute_commands(vec![Command::new("schedule function debug:test/main 1t")]);
+}
+
+#[test]
+#[should_panic(expected = "Expected commands were not injected: [\"function debug:resume\"]")]
+fn test_assert_finished_with_missing_command() {
+    // given:
+    let mut connection = MockMinecraftConnection::new(
+        Scenario::new(debug_datapack("test_assert_finished_with_missing_command"))
+            .expect("reload")
+            .expect("function debug:resume"),
+    );
+    connection
+        .execute_commands(vec![Command::new("reload")])
+        .unwrap();
+
+    // when:
+    connection.assert_finished();
+}
@@ -0,0 +1,390 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    adapter::McfunctionDebugAdapter,
+    custom::{CustomMessage, CustomMessageContent, CustomRequest, CustomResponse, Message},
+    error::DebugAdapterError,
+    run_adapter,
+    test_utils::mock_connection::MockMinecraftConnection,
+};
+use debug_adapter_protocol::{
+    events::{Event, StoppedEventReason},
+    requests::{
+        ContinueRequestArguments, DisconnectRequestArguments, InitializeRequestArguments,
+        LaunchRequestArguments, NextRequestArguments, Request, SetBreakpointsRequestArguments,
+        StackTraceRequestArguments, StepInRequestArguments, StepInTargetsRequestArguments,
+        StepOutRequestArguments,
+    },
+    responses::{ContinueResponseBody, Response, SuccessResponse},
+    types::{Source, SourceBreakpoint, StackFrame, StepInTarget, Thread},
+    ProtocolMessage, ProtocolMessageContent, SequenceNumber,
+};
+use futures::SinkExt;
+use sender_sink::wrappers::UnboundedSenderSink;
+use serde_json::{json, Map, Value};
+use std::{
+    fs::{create_dir_all, remove_dir_all, write},
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+    time::timeout,
+};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const THREAD_ID: i32 = 0;
+const DATAPACK_NAME: &str = "adapter-test";
+
+/// A world directory with a datapack and a log file in the temp directory.
+pub(crate) struct TestWorld {
+    dir: PathBuf,
+}
+
+impl TestWorld {
+    /// Creates an empty world, `name` must be unique for every test.
+    pub(crate) fn new(name: &str) -> TestWorld {
+        let dir = std::env::temp_dir()
+            .join("mcfunction-debug-adapter-mock-test")
+            .join(name);
+        let _ = remove_dir_all(&dir);
+        let world = TestWorld { dir };
+        create_dir_all(world.datapack()).unwrap();
+        write(
+            world.datapack().join("pack.mcmeta"),
+            r#"{"pack":{"pack_format":7,"description":"McFunction-Debugger adapter test"}}"#,
+        )
+        .unwrap();
+        write(world.log_file(), "").unwrap();
+        world
+    }
+
+    fn world_dir(&self) -> PathBuf {
+        self.dir.join("world")
+    }
+
+    fn log_file(&self) -> PathBuf {
+        self.dir.join("latest.log")
+    }
+
+    fn datapack(&self) -> PathBuf {
+        self.world_dir().join("datapacks").join(DATAPACK_NAME)
+    }
+
+    /// The directory the adapter generates the debug datapack in.
+    pub(crate) fn debug_datapack(&self) -> PathBuf {
+        self.world_dir()
+            .join("datapacks")
+            .join(format!("debug-{}", DATAPACK_NAME))
+    }
+
+    /// Writes a function to the datapack and returns its path.
+    pub(crate) fn create_function(&self, name: &str, lines: &[&str]) -> PathBuf {
+        let (namespace, path) = name.split_once(':').unwrap();
+        let path = self
+            .datapack()
+            .join("data")
+            .join(namespace)
+            .join("functions")
+            .join(format!("{}.mcfunction", path));
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    fn launch_attributes(&self, program: &Path) -> Map<String, Value> {
+        Map::from_iter([
+            ("minecraftLogFile".to_string(), json!(self.log_file())),
+            ("minecraftWorldDir".to_string(), json!(self.world_dir())),
+            ("program".to_string(), json!(program)),
+        ])
+    }
+}
+
+/// Runs a [McfunctionDebugAdapter] that is connected to a [MockMinecraftConnection] and sends it
+/// requests like a client. Events that are irrelevant for stepping, like progress and output
+/// events, are skipped.
+pub(crate) struct TestAdapter {
+    handle: JoinHandle<Result<(), DebugAdapterError<io::Error, io::Error, io::Error>>>,
+    input: UnboundedSender<io::Result<Message>>,
+    output: UnboundedReceiver<Message>,
+    seq: SequenceNumber,
+}
+
+impl TestAdapter {
+    pub(crate) fn start(connection: &MockMinecraftConnection) -> TestAdapter {
+        let (input, input_receiver) = unbounded_channel();
+        let (output_sender, output) = unbounded_channel();
+        let connect = connection.connect();
+        let handle = tokio::spawn(async move {
+            let output_sink = UnboundedSenderSink::from(output_sender)
+                .sink_map_err(|_| io::Error::new(io::ErrorKind::ConnectionAborted, ""));
+            run_adapter(
+                UnboundedReceiverStream::new(input_receiver),
+                output_sink,
+                |message_sender| {
+                    McfunctionDebugAdapter::new(message_sender).with_connection(connect)
+                },
+            )
+            .await
+        });
+        TestAdapter {
+            handle,
+            input,
+            output,
+            seq: 0,
+        }
+    }
+
+    fn send(&mut self, content: impl Into<ProtocolMessageContent>) -> SequenceNumber {
+        self.seq += 1;
+        let message = ProtocolMessage::new(self.seq, content);
+        self.input.send(Ok(Message::Protocol(message))).unwrap();
+        self.seq
+    }
+
+    async fn next_message(&mut self) -> ProtocolMessageContent {
+        loop {
+            match self.next_relevant_message().await {
+                Message::Protocol(message) => return message.content,
+                Message::Custom(_) => continue,
+            }
+        }
+    }
+
+    async fn next_relevant_message(&mut self) -> Message {
+        loop {
+            let message = timeout(TIMEOUT, self.output.recv())
+                .await
+                .expect("Timed out waiting for the adapter")
+                .expect("The adapter stopped");
+            match &message {
+                Message::Protocol(ProtocolMessage {
+                    content:
+                        ProtocolMessageContent::Event(
+                            Event::Breakpoint(_)
+                            | Event::LoadedSource(_)
+                            | Event::Output(_)
+                            | Event::ProgressEnd(_)
+                            | Event::ProgressStart(_)
+                            | Event::Initialized,
+                        ),
+                    ..
+                }) => continue,
+                _ => return message,
+            }
+        }
+    }
+
+    /// Sends a request that is handled as a custom request and returns the body of its response.
+    async fn custom_request(&mut self, command: &str, arguments: Value) -> Value {
+        self.seq += 1;
+        let request = CustomRequest {
+            command: command.to_string(),
+            arguments,
+        };
+        let message = CustomMessage::new(self.seq, request);
+        self.input.send(Ok(Message::Custom(message))).unwrap();
+        match self.next_relevant_message().await {
+            Message::Custom(CustomMessage {
+                content:
+                    CustomMessageContent::Response(CustomResponse {
+                        request_seq,
+                        success: true,
+                        body,
+                        ..
+                    }),
+                ..
+            }) if request_seq == self.seq => body,
+            message => panic!("Expected a successful response, but got {:?}", message),
+        }
+    }
+
+    async fn request(&mut self, content: impl Into<ProtocolMessageContent>) -> SuccessResponse {
+        let request_seq = self.send(content);
+        self.response(request_seq).await
+    }
+
+    async fn response(&mut self, request_seq: SequenceNumber) -> SuccessResponse {
+        match self.next_message().await {
+            ProtocolMessageContent::Response(Response {
+                request_seq: seq,
+                result: Ok(response),
+            }) if seq == request_seq => response,
+            message => panic!("Expected a successful response, but got {:?}", message),
+        }
+    }
+
+    pub(crate) async fn initialize(&mut self) {
+        let args = InitializeRequestArguments::builder()
+            .adapter_id("mcfunction".to_string())
+            .build();
+        self.request(args).await;
+    }
+
+    pub(crate) async fn set_breakpoints(&mut self, path: &Path, lines: &[i32]) {
+        let request_seq = self.send_set_breakpoints(path, lines);
+        self.assert_set_breakpoints_response(request_seq).await;
+    }
+
+    /// Sends a setBreakpoints request without waiting for the response, see
+    /// [TestAdapter::assert_set_breakpoints_response].
+    pub(crate) fn send_set_breakpoints(&mut self, path: &Path, lines: &[i32]) -> SequenceNumber {
+        let breakpoints = lines
+            .iter()
+            .map(|line| SourceBreakpoint::builder().line(*line).build())
+            .collect::<Vec<_>>();
+        let args = SetBreakpointsRequestArguments::builder()
+            .source(
+                Source::builder()
+                    .path(Some(path.display().to_string()))
+                    .build(),
+            )
+            .breakpoints(breakpoints)
+            .build();
+        self.send(args)
+    }
+
+    pub(crate) async fn assert_set_breakpoints_response(&mut self, request_seq: SequenceNumber) {
+        let response = self.response(request_seq).await;
+        let SuccessResponse::SetBreakpoints(body) = response else {
+            panic!("Unexpected response {:?}", response);
+        };
+        assert!(body.breakpoints.iter().all(|it| it.verified));
+    }
+
+    pub(crate) async fn launch(&mut self, world: &TestWorld, program: &Path) {
+        self.launch_with_attributes(world, program, []).await;
+    }
+    pub(crate) async fn launch_with_attributes(
+        &mut self,
+        world: &TestWorld,
+        program: &Path,
+        attributes: impl IntoIterator<Item = (&str, Value)>,
+    ) {
+        let mut additional_attributes = world.launch_attributes(program);
+        additional_attributes.extend(
+            attributes
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value)),
+        );
+        let args = LaunchRequestArguments::builder()
+            .additional_attributes(additional_attributes)
+            .build();
+        self.request(args).await;
+    }
+
+    pub(crate) async fn continue_(&mut self) {
+        let args = ContinueRequestArguments::builder()
+            .thread_id(THREAD_ID)
+            .build();
+        self.request(args).await;
+    }
+
+    /// Continues only the suspended executor and returns `allThreadsContinued`.
+    pub(crate) async fn continue_single_thread(&mut self) -> bool {
+        let arguments = json!({"threadId": THREAD_ID, "singleThread": true});
+        let body = self.custom_request("continue", arguments).await;
+        serde_json::from_value::<ContinueResponseBody>(body)
+            .unwrap()
+            .all_threads_continued
+    }
+
+    pub(crate) async fn next(&mut self) {
+        let args = NextRequestArguments::builder().thread_id(THREAD_ID).build();
+        self.request(args).await;
+    }
+
+    pub(crate) async fn step_in(&mut self) {
+        let args = StepInRequestArguments::builder()
+            .thread_id(THREAD_ID)
+            .build();
+        self.request(args).await;
+    }
+
+    pub(crate) async fn step_in_target(&mut self, target_id: i32) {
+        let args = StepInRequestArguments::builder()
+            .thread_id(THREAD_ID)
+            .target_id(Some(target_id))
+            .build();
+        self.request(args).await;
+    }
+
+    pub(crate) async fn step_in_targets(&mut self, frame_id: i32) -> Vec<StepInTarget> {
+        let args = StepInTargetsRequestArguments::builder()
+            .frame_id(frame_id)
+            .build();
+        let response = self.request(args).await;
+        let SuccessResponse::StepInTargets(body) = response else {
+            panic!("Unexpected response {:?}", response);
+        };
+        body.targets
+    }
+
+    pub(crate) async fn step_out(&mut self) {
+        let args = StepOutRequestArguments::builder()
+            .thread_id(THREAD_ID)
+            .build();
+        self.request(args).await;
+    }
+
+    pub(crate) async fn stack_trace(&mut self) -> Vec<StackFrame> {
+        let args = StackTraceRequestArguments::builder()
+            .thread_id(THREAD_ID)
+            .build();
+        let response = self.request(args).await;
+        let SuccessResponse::StackTrace(body) = response else {
+            panic!("Unexpected response {:?}", response);
+        };
+        body.stack_frames
+    }
+
+    pub(crate) async fn threads(&mut self) -> Vec<Thread> {
+        let response = self.request(Request::Threads).await;
+        let SuccessResponse::Threads(body) = response else {
+            panic!("Unexpected response {:?}", response);
+        };
+        body.threads
+    }
+
+    pub(crate) async fn assert_stopped(&mut self, reason: StoppedEventReason) {
+        match self.next_message().await {
+            ProtocolMessageContent::Event(Event::Stopped(body)) => assert_eq!(body.reason, reason),
+            message => panic!("Expected a stopped event, but got {:?}", message),
+        }
+    }
+
+    /// Asserts that the debugged function exited and disconnects.
+    pub(crate) async fn assert_terminated(mut self) {
+        match self.next_message().await {
+            ProtocolMessageContent::Event(Event::Terminated(_)) => {}
+            message => panic!("Expected a terminated event, but got {:?}", message),
+        }
+        self.request(DisconnectRequestArguments::builder().build())
+            .await;
+        timeout(TIMEOUT, self.handle)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+}
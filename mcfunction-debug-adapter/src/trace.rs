@@ -0,0 +1,236 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    custom::Message,
+    record_file::{Overflow, RecordFile},
+};
+use futures::{stream, Stream, StreamExt};
+use log::error;
+use minect::{log::LogEvent, Command};
+use serde_json::{json, Value};
+use std::{
+    io,
+    path::Path,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{fs::read_to_string, time::sleep};
+
+/// How many entries can be queued for writing before further entries are dropped.
+const TRACE_CAPACITY: usize = 1024;
+
+const CLIENT_MESSAGE: &str = "client_message";
+const ADAPTER_MESSAGE: &str = "adapter_message";
+const MINECRAFT_COMMANDS: &str = "minecraft_commands";
+const MINECRAFT_EVENT: &str = "minecraft_event";
+
+pub(crate) const TRACE_FILE_ATTRIBUTE: &str = "traceFile";
+
+/// Writes a machine-readable log of a debug session to a file. Every line of the file is a JSON
+/// object describing one message from the client, one message to the client, one batch of
+/// commands injected into Minecraft or one event received from Minecraft.
+///
+/// Recording never blocks the debug session: entries are written by a background thread and if it
+/// can't keep up, entries are dropped and counted instead.
+#[derive(Clone)]
+pub struct Tracer {
+    inner: Arc<TracerInner>,
+}
+
+struct TracerInner {
+    file: RecordFile,
+    state: Mutex<TraceState>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct TraceState {
+    stopped: bool,
+    request_id: Option<i32>,
+}
+
+impl Tracer {
+    /// Creates a [Tracer] that buffers entries until either [Tracer::start] or
+    /// [Tracer::disable] is called.
+    pub fn new() -> Tracer {
+        Tracer::with_file(RecordFile::new(TRACE_CAPACITY, Overflow::Drop))
+    }
+
+    /// Creates a [Tracer] that never records anything.
+    pub fn disabled() -> Tracer {
+        Tracer::with_file(RecordFile::disabled())
+    }
+
+    fn with_file(file: RecordFile) -> Tracer {
+        Tracer {
+            inner: Arc::new(TracerInner {
+                file,
+                state: Mutex::new(TraceState::default()),
+            }),
+        }
+    }
+
+    /// Creates the trace file and writes all buffered entries to it. Has no effect if the
+    /// [Tracer] is already started or disabled.
+    pub async fn start(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.inner.file.start(path).await
+    }
+
+    /// Discards all buffered entries and stops recording. Has no effect if the [Tracer] is
+    /// already started.
+    pub fn disable(&self) {
+        self.inner.file.disable()
+    }
+
+    /// Stops recording and waits until all queued entries are written to the trace file.
+    pub async fn close(&self) {
+        if let Err(e) = self.inner.file.close().await {
+            error!("Failed to write trace file: {}", e);
+        }
+    }
+
+    /// The number of entries that were dropped, because they could not be written fast enough.
+    pub fn dropped(&self) -> u64 {
+        self.inner.file.dropped()
+    }
+
+    pub(crate) fn set_stopped(&self, stopped: bool) {
+        self.inner.state.lock().unwrap().stopped = stopped;
+    }
+
+    pub(crate) fn set_request_id(&self, request_id: Option<i32>) {
+        self.inner.state.lock().unwrap().request_id = request_id;
+    }
+
+    pub(crate) fn client_message(&self, message: &Message) {
+        self.record(CLIENT_MESSAGE, || serde_json::to_value(message).unwrap());
+    }
+
+    pub(crate) fn adapter_message(&self, message: &Message) {
+        self.record(ADAPTER_MESSAGE, || serde_json::to_value(message).unwrap());
+    }
+
+    pub(crate) fn minecraft_commands(&self, commands: &[Command]) {
+        self.record(MINECRAFT_COMMANDS, || {
+            commands
+                .iter()
+                .map(|command| {
+                    json!({
+                        "name": command.get_name(),
+                        "command": command.get_command(),
+                    })
+                })
+                .collect()
+        });
+    }
+
+    pub(crate) fn minecraft_event(&self, event: &LogEvent) {
+        self.record(MINECRAFT_EVENT, || {
+            json!({
+                "executor": event.executor,
+                "output": event.output,
+            })
+        });
+    }
+
+    fn record(&self, kind: &str, message: impl FnOnce() -> Value) {
+        self.inner.file.record(|| {
+            let state = *self.inner.state.lock().unwrap();
+            json!({
+                "timestamp": now_millis(),
+                "kind": kind,
+                "state": if state.stopped { "stopped" } else { "running" },
+                "request_id": state.request_id,
+                "dropped": self.dropped(),
+                "message": message(),
+            })
+            .to_string()
+        });
+    }
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Tracer::new()
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Reads all messages that the client sent during a traced debug session together with the
+/// millisecond timestamp at which they were received. The attribute `traceFile` is removed from
+/// launch requests to prevent a replay from overwriting the trace it is replaying.
+pub async fn read_client_messages(path: impl AsRef<Path>) -> io::Result<Vec<(u64, Message)>> {
+    let content = read_to_string(path).await?;
+    let mut messages = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid_line = |message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid trace entry in line {}: {}", index + 1, message),
+            )
+        };
+        let mut entry: Value =
+            serde_json::from_str(line).map_err(|e| invalid_line(e.to_string()))?;
+        if entry["kind"] != CLIENT_MESSAGE {
+            continue;
+        }
+        let timestamp = entry["timestamp"]
+            .as_u64()
+            .ok_or_else(|| invalid_line("Missing timestamp".to_string()))?;
+        let mut message = entry["message"].take();
+        if message["command"] == "launch" {
+            if let Some(arguments) = message["arguments"].as_object_mut() {
+                arguments.remove(TRACE_FILE_ATTRIBUTE);
+            }
+        }
+        let message = serde_json::from_value(message).map_err(|e| invalid_line(e.to_string()))?;
+        messages.push((timestamp, message));
+    }
+    Ok(messages)
+}
+
+/// Creates a stream that yields the messages with the same delays between them as in the
+/// original debug session.
+pub fn replay(
+    messages: Vec<(u64, Message)>,
+) -> Pin<Box<dyn Stream<Item = io::Result<Message>> + Send>> {
+    let mut previous = messages.first().map(|(timestamp, _)| *timestamp);
+    stream::iter(messages)
+        .then(move |(timestamp, message)| {
+            let delay = previous.map_or(0, |previous| timestamp.saturating_sub(previous));
+            previous = Some(timestamp);
+            async move {
+                sleep(Duration::from_millis(delay)).await;
+                Ok(message)
+            }
+        })
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests;
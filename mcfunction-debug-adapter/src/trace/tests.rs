@@ -0,0 +1,146 @@
+use super::*;
+use debug_adapter_protocol::{
+    events::{Event, StoppedEventBody, StoppedEventReason},
+    requests::{LaunchRequestArguments, Request},
+    ProtocolMessage,
+};
+use serde_json::Map;
+use std::{
+    fs::{create_dir_all, remove_file},
+    path::PathBuf,
+    time::Instant,
+};
+
+fn trace_file(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("mcfunction-debug-adapter-trace-test");
+    create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let _ = remove_file(&path);
+    path
+}
+
+fn launch_request(seq: u64, attributes: Map<String, Value>) -> Message {
+    let args = LaunchRequestArguments::builder()
+        .additional_attributes(attributes)
+        .build();
+    ProtocolMessage::new(seq, Request::Launch(args)).into()
+}
+
+#[tokio::test]
+async fn test_replays_only_client_messages() {
+    // given:
+    let path = trace_file("test_replays_only_client_messages.ndjson");
+    let tracer = Tracer::new();
+    tracer.start(&path).await.unwrap();
+
+    let request = launch_request(1, Map::new());
+    tracer.client_message(&request);
+    tracer.minecraft_commands(&[Command::new("reload")]);
+    let event = Event::Stopped(
+        StoppedEventBody::builder()
+            .reason(StoppedEventReason::Breakpoint)
+            .build(),
+    );
+    tracer.adapter_message(&ProtocolMessage::new(1, event).into());
+    tracer.close().await;
+
+    // when:
+    let messages = read_client_messages(&path).await.unwrap();
+
+    // then:
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].1, request);
+}
+
+#[tokio::test]
+async fn test_replay_removes_trace_file_attribute() {
+    // given:
+    let path = trace_file("test_replay_removes_trace_file_attribute.ndjson");
+    let tracer = Tracer::new();
+    tracer.start(&path).await.unwrap();
+
+    let mut attributes = Map::new();
+    attributes.insert("program".to_string(), json!("main.mcfunction"));
+    let mut attributes_with_trace_file = attributes.clone();
+    attributes_with_trace_file.insert(TRACE_FILE_ATTRIBUTE.to_string(), json!(path));
+    tracer.client_message(&launch_request(1, attributes_with_trace_file));
+    tracer.close().await;
+
+    // when:
+    let messages = read_client_messages(&path).await.unwrap();
+
+    // then:
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].1, launch_request(1, attributes));
+}
+
+#[tokio::test]
+async fn test_writes_pending_entries_on_start() {
+    // given:
+    let path = trace_file("test_writes_pending_entries_on_start.ndjson");
+    let tracer = Tracer::new();
+    let request = launch_request(1, Map::new());
+    tracer.client_message(&request);
+
+    // when:
+    tracer.start(&path).await.unwrap();
+    tracer.close().await;
+
+    // then:
+    let messages = read_client_messages(&path).await.unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].1, request);
+}
+
+#[tokio::test]
+async fn test_records_state() {
+    // given:
+    let path = trace_file("test_records_state.ndjson");
+    let tracer = Tracer::new();
+    tracer.start(&path).await.unwrap();
+
+    // when:
+    tracer.set_stopped(true);
+    tracer.set_request_id(Some(3));
+    tracer.minecraft_commands(&[Command::new("function debug:resume")]);
+    tracer.close().await;
+
+    // then:
+    let content = read_to_string(&path).await.unwrap();
+    let entry: Value = serde_json::from_str(content.trim()).unwrap();
+    assert_eq!(entry["kind"], MINECRAFT_COMMANDS);
+    assert_eq!(entry["state"], "stopped");
+    assert_eq!(entry["request_id"], 3);
+    assert_eq!(entry["message"][0]["command"], "function debug:resume");
+}
+
+#[test]
+fn test_counts_dropped_pending_entries() {
+    // given:
+    let tracer = Tracer::new();
+
+    // when:
+    for _ in 0..TRACE_CAPACITY + 2 {
+        tracer.minecraft_commands(&[Command::new("reload")]);
+    }
+
+    // then:
+    assert_eq!(tracer.dropped(), 2);
+}
+
+#[tokio::test]
+async fn test_replay_keeps_delays() {
+    // given:
+    let messages = vec![
+        (1000, ProtocolMessage::new(1, Request::Threads).into()),
+        (1100, ProtocolMessage::new(2, Request::Threads).into()),
+    ];
+
+    // when:
+    let start = Instant::now();
+    let replayed = replay(messages).collect::<Vec<_>>().await;
+
+    // then:
+    assert_eq!(replayed.len(), 2);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
@@ -20,13 +20,20 @@ mod utils;
 
 use crate::utils::{
     added_tag_output, assert_all_breakpoints_verified, assert_error_response, connection,
-    create_datapack, datapack_dir, get_source_path, named_logged_command, start_adapter,
+    create_datapack, create_datapack_in, create_function_tag, datapack_dir, get_source_path,
+    named_logged_command, other_datapack_dir, start_adapter,
     timeout::{TimeoutStream, TimeoutStreamError},
-    Mcfunction, LISTENER_NAME, TEST_LOG_FILE,
+    Mcfunction, LISTENER_NAME, TEST_DATAPACK_NAME, TEST_LOG_FILE, TEST_WORLD_DIR,
 };
 use assert2::assert;
-use debug_adapter_protocol::types::SourceBreakpoint;
-use mcfunction_debug_adapter::adapter::SELECTED_ENTITY_SCORES;
+use debug_adapter_protocol::{
+    events::{BreakpointEventReason, LoadedSourceEventReason},
+    types::{SourceBreakpoint, StackFramePresentationHint, SteppingGranularity, Variable},
+};
+use mcfunction_debug_adapter::adapter::{
+    GLOBALS, SELECTED_ENTITY_NBT, SELECTED_ENTITY_POSITION, SELECTED_ENTITY_SCORES,
+    SELECTED_ENTITY_TAGS,
+};
 use mcfunction_debugger::parser::command::resource_location::ResourceLocation;
 use minect::{
     command::{
@@ -36,15 +43,19 @@ use minect::{
     log::LogObserver,
     Command, MinecraftConnection,
 };
+use serde_json::json;
 use serial_test::serial;
 use simple_logger::SimpleLogger;
 use std::{
+    collections::BTreeMap,
+    fs::{read_dir, remove_dir_all, remove_file, rename},
     io::{self},
-    time::Duration,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
 };
 use tokio::{
     sync::OnceCell,
-    time::{error::Elapsed, sleep, timeout},
+    time::{error::Elapsed, timeout},
 };
 use tokio_stream::StreamExt;
 
@@ -239,6 +250,70 @@ async fn test_breakpoint_at_function_call() -> io::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[serial]
+async fn test_breakpoint_after_execute_prefix() -> io::Result<()> {
+    before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_SCORES;
+
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec!["scoreboard players add @s test_local 10".to_string()],
+    };
+    let execute = "execute as @e[type=sheep,tag=test] ";
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ "scoreboard objectives add test_local dummy".to_string(),
+            /* 2 */ "kill @e[type=sheep,tag=test]".to_string(),
+            /* 3 */ "summon sheep ~ ~ ~ {Tags: [test, test1], NoAI: true}".to_string(),
+            /* 4 */ "summon sheep ~ ~ ~ {Tags: [test, test2], NoAI: true}".to_string(),
+            /* 5 */
+            "scoreboard players set @e[type=sheep,tag=test1] test_local 1".to_string(),
+            /* 6 */
+            "scoreboard players set @e[type=sheep,tag=test2] test_local 2".to_string(),
+            /* 7 */ format!("{}run function {}", execute, inner.name),
+            /* 8 */ "scoreboard objectives remove test_local".to_string(),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let run_column = execute.len() as i32 + 1;
+    let breaks = vec![SourceBreakpoint::builder()
+        .line(7)
+        .column(Some(run_column + 4))
+        .build()];
+    let response = adapter.set_breakpoints(&outer_path, &breaks).await;
+    assert_all_breakpoints_verified(&response, &breaks);
+    assert!(response.breakpoints[0].column == Some(run_column));
+
+    adapter.launch(&outer_path).await;
+
+    let mut values = Vec::new();
+    for _ in 0..2 {
+        adapter.assert_stopped_at_breakpoint().await;
+        let threads = adapter.threads().await;
+        let stack_trace = adapter.stack_trace(threads[0].id).await;
+        assert!(stack_trace.len() == 1);
+        assert!(stack_trace[0].line == 7);
+        assert!(stack_trace[0].column == run_column);
+        let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+        assert!(vars.len() == 1);
+        assert!(vars[0].name == "test_local");
+        values.push(vars[0].value.clone());
+        adapter.continue_().await;
+    }
+    values.sort();
+    assert!(values == ["1", "2"]); // Suspended before inner was executed for each sheep
+
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
 #[tokio::test]
 #[serial]
 async fn test_breakpoint_after_launch() -> io::Result<()> {
@@ -323,6 +398,227 @@ async fn test_breakpoint_removed() -> io::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[serial]
+async fn test_breakpoint_in_renamed_file() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let test_path = test.full_path();
+    let renamed = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "renamed"),
+        lines: test.lines.clone(),
+    };
+    let renamed_path = renamed.full_path();
+    create_datapack(vec![test]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    rename(&test_path, &renamed_path)?;
+    let (rebound, response) = adapter
+        .set_breakpoints_in_moved_file(&test_path, &breaks)
+        .await;
+    assert_all_breakpoints_verified(&response, &breaks);
+    let renamed_path_string = renamed_path.display().to_string();
+    assert!(rebound.len() == 1);
+    assert!(rebound[0].source.as_ref().unwrap().path.as_ref() == Some(&renamed_path_string));
+
+    adapter.launch(&renamed_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_breakpoint_in_deleted_file() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    remove_file(&test_path)?;
+    let response = adapter.set_breakpoints(&test_path, &breaks).await;
+
+    assert!(response.breakpoints.len() == 1);
+    assert!(!response.breakpoints[0].verified);
+    let message = response.breakpoints[0].message.as_ref().unwrap();
+    assert!(message.contains("does not exist"), "{}", message);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_timing() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ "say 1".to_string(),
+            /* 2 */ "say 2".to_string(),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![
+        SourceBreakpoint::builder().line(1).build(),
+        SourceBreakpoint::builder().line(2).build(),
+    ];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    let body = adapter.assert_stopped_at_breakpoint().await;
+    let description = body.description.unwrap();
+    assert!(
+        description.ends_with("ticks since launch)"),
+        "{}",
+        description
+    );
+    let timing = adapter.timing().await;
+    assert!(timing["sessionTicks"].as_i64().unwrap() >= 1);
+    assert!(timing["suspendedTicks"].is_null());
+
+    adapter.continue_().await;
+    let body = adapter.assert_stopped_at_breakpoint().await;
+    let description = body.description.unwrap();
+    assert!(description.contains("since last stop"), "{}", description);
+    let timing = adapter.timing().await;
+    assert!(timing["suspendedTicks"].as_i64().is_some());
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_loaded_sources() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ "function adapter_test:inner".to_string(),
+            /* 2 */ "say 1".to_string(),
+        ],
+    };
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![/* 1 */ "say inner".to_string()],
+    };
+    let created = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "created"),
+        lines: vec![/* 1 */ "say created".to_string()],
+    };
+    let test_path = test.full_path();
+    let created_path = created.full_path();
+    create_datapack(vec![test, inner]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let sources = adapter.loaded_sources().await;
+    let names = sources
+        .iter()
+        .map(|source| source.name.clone().unwrap())
+        .collect::<Vec<_>>();
+    assert!(names == ["adapter_test:inner", "adapter_test:test"]);
+    assert!(sources
+        .iter()
+        .all(|source| source.origin.as_deref() == Some("datapack")));
+
+    create_datapack(vec![created]);
+    let (events, _) = adapter
+        .set_breakpoints_collecting_loaded_source_events(&created_path, &[])
+        .await;
+    assert!(events.len() == 1);
+    assert!(events[0].reason == LoadedSourceEventReason::New);
+    assert!(events[0].source.name.as_deref() == Some("adapter_test:created"));
+
+    remove_file(&created_path)?;
+    let (events, _) = adapter
+        .set_breakpoints_collecting_loaded_source_events(&created_path, &[])
+        .await;
+    assert!(events.len() == 1);
+    assert!(events[0].reason == LoadedSourceEventReason::Removed);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_partitions_reflect_breakpoints() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ "say 1".to_string(),
+            /* 2 */ "function adapter_test:inner".to_string(),
+            /* 3 */ "say 2".to_string(),
+            /* 4 */ "say 3".to_string(),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let partitions = adapter.partitions(&test_path).await;
+    assert!(partitions.len() == 2);
+    assert!(partitions[0]["terminatorKind"] == "functionCall");
+
+    let breaks = vec![SourceBreakpoint::builder().line(4).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    let partitions = adapter.partitions(&test_path).await;
+    assert!(partitions.len() == 3);
+    assert!(partitions[1]["terminatorKind"] == "breakpoint");
+    assert!(partitions[1]["endLine"] == 4);
+    Ok(())
+}
+
 #[tokio::test]
 #[serial]
 #[ignore = "https://github.com/vanilla-technologies/mcfunction-debugger/issues/70"]
@@ -407,6 +703,48 @@ async fn test_breakpoint_moved() -> io::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[serial]
+async fn test_stack_trace_after_source_changed() -> io::Result<()> {
+    before_each_test().await;
+    let mut test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ "say one".to_string(),
+            /* 2 */ "say two".to_string(),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test.clone()]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert_eq!(stack_trace[0].presentation_hint, None);
+
+    test.lines[1] = "say changed".to_string();
+    create_datapack(vec![test]);
+
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace[0].name.ends_with(" (source changed)"));
+    assert_eq!(
+        stack_trace[0].presentation_hint,
+        Some(StackFramePresentationHint::Subtle)
+    );
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
 #[tokio::test]
 #[serial]
 async fn test_current_breakpoint_removed() -> io::Result<()> {
@@ -437,8 +775,6 @@ async fn test_current_breakpoint_removed() -> io::Result<()> {
     breaks.remove(0);
     adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
-    sleep(Duration::from_secs(1)).await; // Wait for minecraft to register changed breakpoints
-
     adapter.continue_().await;
     adapter.assert_terminated().await;
     assert!(listener.next().await.unwrap().output == added_tag_output("some_tag"));
@@ -490,8 +826,6 @@ async fn test_current_breakpoint_removed_while_iterating() -> io::Result<()> {
     breaks.remove(0);
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
-    sleep(Duration::from_secs(1)).await; // Wait for minecraft to register changed breakpoints
-
     adapter.continue_().await;
     adapter.assert_terminated().await;
     assert!(listener.next().await.unwrap().output == added_tag_output("some_tag")); // Second iteration was executed
@@ -499,17 +833,17 @@ async fn test_current_breakpoint_removed_while_iterating() -> io::Result<()> {
     Ok(())
 }
 
-/// Reproducer for race condition mentioned in https://github.com/vanilla-technologies/mcfunction-debugger/issues/63
 #[tokio::test]
 #[serial]
-async fn test_current_breakpoint_removed_continue_followed_by_set_breakpoints() -> io::Result<()> {
+async fn test_breakpoint_comment() -> io::Result<()> {
     before_each_test().await;
     let test = Mcfunction {
         name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
             /* 1 */ logged_command(enable_logging_command()),
-            /* 2 */ named_logged_command(add_tag_command("@s", "some_tag")),
-            /* 3 */ logged_command(reset_logging_command()),
+            /* 2 */ "# breakpoint".to_string(),
+            /* 3 */ named_logged_command(add_tag_command("@s", "some_tag")),
+            /* 4 */ logged_command(reset_logging_command()),
         ],
     };
     let test_path = test.full_path();
@@ -520,22 +854,17 @@ async fn test_current_breakpoint_removed_continue_followed_by_set_breakpoints()
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let mut breaks = vec![SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&test_path, &breaks).await;
-
-    adapter.launch(&test_path).await;
+    let events = adapter
+        .launch_collecting_breakpoint_events(&test_path)
+        .await;
+    assert!(events.len() == 1);
+    assert!(events[0].reason == BreakpointEventReason::New);
+    assert!(events[0].breakpoint.line == Some(2));
+    assert!(events[0].breakpoint.verified);
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
-
-    breaks.remove(0);
-    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Command NOT executed
 
     adapter.continue_().await;
-
-    // This runs before minecraft executes debug:resume which originally caused the breakpoint of kind continue to be deleted
-    breaks.push(SourceBreakpoint::builder().line(1).build());
-    adapter.set_breakpoints_verified(&test_path, &breaks).await;
-
     adapter.assert_terminated().await;
     assert!(listener.next().await.unwrap().output == added_tag_output("some_tag"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
@@ -544,233 +873,219 @@ async fn test_current_breakpoint_removed_continue_followed_by_set_breakpoints()
 
 #[tokio::test]
 #[serial]
-async fn test_scope_selected_entity_score() -> io::Result<()> {
+async fn test_breakpoint_comment_ignored() -> io::Result<()> {
     before_each_test().await;
-    const SCOPE: &str = SELECTED_ENTITY_SCORES;
-
-    let inner = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "inner"),
-        lines: vec![
-            /* 1 */ "scoreboard players set @s test_local 42".to_string(),
-            /* 2 */ "scoreboard objectives remove test_local".to_string(),
-        ],
-    };
-    let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            "scoreboard objectives add test_local dummy".to_string(),
-            "scoreboard players reset * test_local".to_string(),
-            "kill @e[type=sheep,tag=test]".to_string(),
-            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            format!(
-                "execute as @e[type=sheep,tag=test] run function {}",
-                inner.name
-            ),
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ "# breakpoint".to_string(),
+            /* 3 */ named_logged_command(add_tag_command("@s", "some_tag")),
+            /* 4 */ logged_command(reset_logging_command()),
         ],
     };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
 
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+    adapter
+        .launch_with_attributes(&test_path, [("ignoreBreakpointComments", json!(true))])
+        .await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("some_tag"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
 
-    adapter.launch(&outer_path).await;
+#[tokio::test]
+#[serial]
+async fn test_current_breakpoint_comment_removed() -> io::Result<()> {
+    before_each_test().await;
+    let mut test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ "# breakpoint".to_string(),
+            /* 3 */ named_logged_command(add_tag_command("@s", "some_tag")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test.clone()]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let events = adapter
+        .launch_collecting_breakpoint_events(&test_path)
+        .await;
+    let id = events[0].breakpoint.id;
     adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Command NOT executed
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
-    assert!(vars.len() == 1);
-    assert!(vars[0].name == "test_local");
-    assert!(vars[0].value == "42");
+    test.lines.remove(1);
+    create_datapack(vec![test]);
+    let (events, _response) = adapter
+        .set_breakpoints_collecting_events(&test_path, &[], true)
+        .await;
+    assert!(events.len() == 1);
+    assert!(events[0].reason == BreakpointEventReason::Removed);
+    assert!(events[0].breakpoint.id == id);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("some_tag"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_scope_selected_entity_score_can_be_removed() -> io::Result<()> {
+async fn test_terminate_runs_to_completion() -> io::Result<()> {
     before_each_test().await;
-    const SCOPE: &str = SELECTED_ENTITY_SCORES;
-
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
-            /* 1 */ "scoreboard players set @s test_local 42".to_string(),
-            /* 2 */ "scoreboard players reset @s test_local".to_string(),
-            /* 3 */ "scoreboard objectives remove test_local".to_string(),
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "some_tag")),
+            /* 3 */ logged_command(reset_logging_command()),
         ],
     };
-    let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            "scoreboard objectives add test_local dummy".to_string(),
-            "scoreboard players reset * test_local".to_string(),
             "kill @e[type=sheep,tag=test]".to_string(),
             "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
             format!(
                 "execute as @e[type=sheep,tag=test] run function {}",
                 inner.name
             ),
         ],
     };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let test_path = test.full_path();
+    let inner_path = inner.full_path();
+    create_datapack(vec![test, inner]);
 
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = [
-        SourceBreakpoint::builder().line(2).build(),
-        SourceBreakpoint::builder().line(3).build(),
-    ];
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
-    adapter.launch(&outer_path).await;
-    adapter.assert_stopped_at_breakpoint().await;
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
-    assert!(vars.len() == 1);
-    assert!(vars[0].name == "test_local");
-    assert!(vars[0].value == "42");
-
-    adapter.continue_().await;
+    adapter
+        .launch_with_attributes(&test_path, [("runToCompletionOnTerminate", json!(true))])
+        .await;
     adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("some_tag")); // First iteration was executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second iteration was NOT executed
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
-    assert!(vars.is_empty());
-
-    adapter.continue_().await;
+    adapter.terminate().await;
     adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("some_tag")); // Second iteration was executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_scope_selected_entity_score_multiple_depths() -> io::Result<()> {
+async fn test_disconnect_removes_debug_datapack() -> io::Result<()> {
     before_each_test().await;
-    const SCOPE: &str = SELECTED_ENTITY_SCORES;
-
-    let pig = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "pig"),
-        lines: vec![
-            /* 1 */ "scoreboard players set @s test_local 52".to_string(),
-            /* 2 */ "scoreboard objectives remove test_local".to_string(),
-        ],
-    };
-    let pig_path = pig.full_path();
-    let sheep = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "sheep"),
-        lines: vec![
-            /* 1 */ "scoreboard players set @s test_local 42".to_string(),
-            /* 2 */ "kill @e[type=pig,tag=test]".to_string(),
-            /* 3 */ "summon pig ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            /* 4 */ format!("execute as @e[type=pig,tag=test] run function {}", pig.name),
-        ],
-    };
     let test = Mcfunction {
         name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            "scoreboard objectives add test_local dummy".to_string(),
-            "scoreboard players reset * test_local".to_string(),
-            "kill @e[type=sheep,tag=test]".to_string(),
-            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            format!(
-                "execute as @e[type=sheep,tag=test] run function {}",
-                sheep.name
-            ),
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "some_tag")),
+            /* 3 */ logged_command(reset_logging_command()),
         ],
     };
     let test_path = test.full_path();
-    create_datapack(vec![test, sheep, pig]);
+    create_datapack(vec![test]);
 
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = [SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&pig_path, &breaks).await;
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
     adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
+    let debug_datapack = Path::new(TEST_WORLD_DIR)
+        .join("datapacks")
+        .join(format!("debug-{}", TEST_DATAPACK_NAME));
+    assert!(debug_datapack.is_dir());
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 3);
-
-    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
-    assert!(vars.len() == 1);
-    assert!(vars[0].name == "test_local");
-    assert!(vars[0].value == "52");
-
-    let vars = adapter.variables_of_scope(stack_trace[1].id, SCOPE).await;
-    assert!(vars.len() == 1);
-    assert!(vars[0].name == "test_local");
-    assert!(vars[0].value == "42");
-
-    let scopes = adapter.scopes(stack_trace[2].id).await;
-    assert!(scopes.iter().find(|it| it.name == SCOPE).is_none());
-
-    adapter.continue_().await;
-    adapter.assert_terminated().await;
+    adapter.disconnect().await;
+    assert!(!debug_datapack.exists());
     Ok(())
 }
 
+fn modification_times(dir: &Path) -> io::Result<BTreeMap<PathBuf, SystemTime>> {
+    let mut times = BTreeMap::new();
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            times.extend(modification_times(&entry.path())?);
+        } else {
+            times.insert(entry.path(), entry.metadata()?.modified()?);
+        }
+    }
+    Ok(times)
+}
+
 #[tokio::test]
 #[serial]
-async fn test_scope_selected_entity_score_server_context() -> io::Result<()> {
+async fn test_identical_breakpoints_do_not_regenerate_debug_datapack() -> io::Result<()> {
     before_each_test().await;
-    const SCOPE: &str = SELECTED_ENTITY_SCORES;
-
     let test = Mcfunction {
         name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            /* 1 */ "scoreboard objectives add test_local dummy".to_string(),
-            /* 2 */ "scoreboard players reset * test_local".to_string(),
-            /* 3 */ "scoreboard players set @s test_local 42".to_string(),
-            /* 4 */ "scoreboard objectives remove test_local".to_string(),
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 4 */ logged_command(reset_logging_command()),
         ],
     };
     let test_path = test.full_path();
     create_datapack(vec![test]);
 
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(4).build()];
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
     adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
     adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    let debug_datapack = Path::new(TEST_WORLD_DIR)
+        .join("datapacks")
+        .join(format!("debug-{}", TEST_DATAPACK_NAME));
+    let times = modification_times(&debug_datapack)?;
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    let scopes = adapter.scopes(stack_trace[0].id).await;
-    assert!(scopes.iter().find(|it| it.name == SCOPE).is_none());
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+    assert!(modification_times(&debug_datapack)? == times);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_out_of_root_function() -> io::Result<()> {
+async fn test_breakpoint_in_namespace_added_after_launch() -> io::Result<()> {
     before_each_test().await;
     let test = Mcfunction {
         name: ResourceLocation::new("adapter_test", "test"),
@@ -783,50 +1098,55 @@ async fn test_step_out_of_root_function() -> io::Result<()> {
     let test_path = test.full_path();
     create_datapack(vec![test]);
 
-    let log_observer = LogObserver::new(TEST_LOG_FILE);
-    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
     adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
     adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Line NOT executed
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_out(threads[0].id).await;
+    let added = Mcfunction {
+        name: ResourceLocation::new("adapter_test_added", "added"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "some_tag")),
+            /* 3 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let added_path = added.full_path();
+    create_datapack(vec![added]);
+
+    adapter.set_breakpoints_verified(&added_path, &breaks).await;
+
+    adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("some_tag")); // Line was executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_out_of_inner_function() -> io::Result<()> {
+async fn test_excluded_function() -> io::Result<()> {
     before_each_test().await;
-    let inner = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "inner"),
+    let excluded = Mcfunction {
+        name: ResourceLocation::new("adapter_test_lib", "excluded"),
         lines: vec![
-            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ logged_command(reset_logging_command()),
         ],
     };
-    let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
+    let excluded_path = excluded.full_path();
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            logged_command(enable_logging_command()),
-            format!("function {}", inner.name),
-            named_logged_command(add_tag_command("@s", "tag3")),
-            logged_command(reset_logging_command()),
+            /* 1 */ format!("function {}", excluded.name),
+            /* 2 */ "say test".to_string(),
         ],
     };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let test_path = test.full_path();
+    create_datapack(vec![test, excluded]);
 
     let log_observer = LogObserver::new(TEST_LOG_FILE);
     let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
@@ -834,190 +1154,194 @@ async fn test_step_out_of_inner_function() -> io::Result<()> {
     adapter.initalize().await;
 
     let breaks = vec![SourceBreakpoint::builder().line(1).build()];
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
-    adapter.launch(&outer_path).await;
+    let exclude = json!(["adapter_test_lib:**"]);
+    adapter
+        .launch_with_attributes(&test_path, [("exclude", exclude)])
+        .await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    let response = adapter.set_breakpoints(&excluded_path, &breaks).await;
+    assert!(response.breakpoints.len() == 1);
+    assert!(!response.breakpoints[0].verified);
+    assert!(
+        response.breakpoints[0].message
+            == Some("Function is excluded by pattern 'adapter_test_lib:**'".to_string())
+    );
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
-    adapter.step_out(threads[0].id).await;
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(adapter.step_in_targets(stack_trace[0].id).await.is_empty());
+
+    adapter.step_in(threads[0].id).await;
     adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Third line NOT executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(get_source_path(&stack_trace[0]) == &test_path.display().to_string());
+    assert!(stack_trace[0].line == 2);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
+/// Reproducer for race condition mentioned in https://github.com/vanilla-technologies/mcfunction-debugger/issues/63
 #[tokio::test]
 #[serial]
-async fn test_step_out_of_inner_function_with_multiple_executors() -> io::Result<()> {
+async fn test_current_breakpoint_removed_continue_followed_by_set_breakpoints() -> io::Result<()> {
     before_each_test().await;
-    let inner = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "inner"),
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "some_tag")),
+            /* 3 */ logged_command(reset_logging_command()),
         ],
     };
-    let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
-        lines: vec![
-            "kill @e[type=sheep,tag=test]".to_string(),
-            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            logged_command(enable_logging_command()),
-            format!(
-                "execute as @e[type=sheep,tag=test] run function {}",
-                inner.name
-            ),
-            named_logged_command(add_tag_command("@s", "tag3")),
-            logged_command(reset_logging_command()),
-        ],
-    };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
 
     let log_observer = LogObserver::new(TEST_LOG_FILE);
     let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let mut breaks = vec![SourceBreakpoint::builder().line(1).build()];
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+    let mut breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
-    adapter.launch(&outer_path).await;
+    adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
 
     breaks.remove(0);
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_out(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed by first sheep
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed by first sheep
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed by second sheep
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed by second sheep
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Third line NOT executed
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
     adapter.continue_().await;
+
+    // This runs before minecraft executes debug:resume which originally caused the breakpoint of kind continue to be deleted
+    breaks.push(SourceBreakpoint::builder().line(1).build());
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("some_tag"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_out_of_inner_function_with_breakpoint() -> io::Result<()> {
+async fn test_scope_selected_entity_score() -> io::Result<()> {
     before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_SCORES;
+
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
-            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 1 */ "scoreboard players set @s test_local 42".to_string(),
+            /* 2 */ "scoreboard objectives remove test_local".to_string(),
         ],
     };
     let inner_path = inner.full_path();
     let outer = Mcfunction {
         name: ResourceLocation::new("adapter_test", "outer"),
         lines: vec![
-            logged_command(enable_logging_command()),
-            format!("function {}", inner.name),
-            named_logged_command(add_tag_command("@s", "tag3")),
-            logged_command(reset_logging_command()),
+            "scoreboard objectives add test_local dummy".to_string(),
+            "scoreboard players reset * test_local".to_string(),
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
         ],
     };
     let outer_path = outer.full_path();
     create_datapack(vec![outer, inner]);
 
-    let log_observer = LogObserver::new(TEST_LOG_FILE);
-    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![
-        SourceBreakpoint::builder().line(1).build(),
-        SourceBreakpoint::builder().line(2).build(),
-    ];
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
-    adapter.step_out(threads[0].id).await;
-    adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    assert!(vars.len() == 1);
+    assert!(vars[0].name == "test_local");
+    assert!(vars[0].value == "42");
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_out_into_end_of_function() -> io::Result<()> {
+async fn test_scope_selected_entity_scores_grouped_by_objective() -> io::Result<()> {
     before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_SCORES;
+
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
-            /* 1 */ logged_command(enable_logging_command()),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 3 */ logged_command(reset_logging_command()),
+            /* 1 */ "scoreboard players set @s test_local 42".to_string(),
+            /* 2 */ "scoreboard players set @s test_other 7".to_string(),
+            /* 3 */ "scoreboard objectives remove test_local".to_string(),
+            /* 4 */ "scoreboard objectives remove test_other".to_string(),
         ],
     };
     let inner_path = inner.full_path();
-    let outer_line = format!("function {}", inner.name);
-    let outer_line_len = outer_line.len();
     let outer = Mcfunction {
         name: ResourceLocation::new("adapter_test", "outer"),
-        lines: vec![outer_line],
+        lines: vec![
+            "scoreboard objectives add test_local dummy".to_string(),
+            "scoreboard objectives add test_other dummy".to_string(),
+            "scoreboard players reset * test_local".to_string(),
+            "scoreboard players reset * test_other".to_string(),
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
+        ],
     };
     let outer_path = outer.full_path();
     create_datapack(vec![outer, inner]);
 
-    let log_observer = LogObserver::new(TEST_LOG_FILE);
-    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_out(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 1);
-    assert!(stack_trace[0].line == 1);
-    assert!(stack_trace[0].column == outer_line_len as i32 + 1);
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    let names = vars.iter().map(|it| it.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["test_local", "test_other"]);
+    assert!(vars.iter().all(|it| it.variables_reference != 0));
+    let local = adapter.variables(vars[0].variables_reference).await;
+    assert!(local.len() == 1);
+    assert!(local[0].value == "42");
+    let other = adapter.variables(vars[1].variables_reference).await;
+    assert!(other.len() == 1);
+    assert!(other[0].value == "7");
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
@@ -1026,54 +1350,51 @@ async fn test_step_out_into_end_of_function() -> io::Result<()> {
 
 #[tokio::test]
 #[serial]
-async fn test_step_out_into_end_of_function_with_breakpoint() -> io::Result<()> {
+async fn test_scope_selected_entity_tags() -> io::Result<()> {
     before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_TAGS;
+
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
-            /* 1 */ logged_command(enable_logging_command()),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 3 */ logged_command(reset_logging_command()),
+            /* 1 */ "tag @s add some_tag".to_string(),
+            /* 2 */ "tag @s remove some_tag".to_string(),
         ],
     };
     let inner_path = inner.full_path();
-    let outer_line = format!("function {}", inner.name);
-    let outer_line_len = outer_line.len();
     let outer = Mcfunction {
         name: ResourceLocation::new("adapter_test", "outer"),
-        lines: vec![outer_line],
+        lines: vec![
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
+        ],
     };
     let outer_path = outer.full_path();
     create_datapack(vec![outer, inner]);
 
-    let log_observer = LogObserver::new(TEST_LOG_FILE);
-    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
-
-    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_out(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 1);
-    assert!(stack_trace[0].line == 1);
-    assert!(stack_trace[0].column == outer_line_len as i32 + 1);
+    let scopes = adapter.scopes(stack_trace[0].id).await;
+    let scope = scopes.iter().find(|it| it.name == SCOPE).unwrap();
+    assert!(scope.variables_reference != 0);
+    let mut vars = adapter.variables(scope.variables_reference).await;
+    vars.sort_by(|a, b| a.value.cmp(&b.value));
+    let tags = vars.iter().map(|it| it.value.as_str()).collect::<Vec<_>>();
+    assert_eq!(tags, vec!["some_tag", "test"]);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
@@ -1082,75 +1403,52 @@ async fn test_step_out_into_end_of_function_with_breakpoint() -> io::Result<()>
 
 #[tokio::test]
 #[serial]
-async fn test_step_out_into_start_of_function_with_breakpoint_via_recursion() -> io::Result<()> {
+async fn test_scope_selected_entity_nbt() -> io::Result<()> {
     before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_NBT;
+
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
-            /* 1 */ logged_command(enable_logging_command()),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 3 */ logged_command(reset_logging_command()),
-            /* 4 */ "scoreboard players set test test_global 1".to_string(),
-            /* 5 */ "function adapter_test:outer".to_string(),
+            /* 1 */ "data merge entity @s {Health: 5.0f}".to_string(),
+            /* 2 */ "say inner".to_string(),
         ],
     };
     let inner_path = inner.full_path();
     let outer = Mcfunction {
         name: ResourceLocation::new("adapter_test", "outer"),
         lines: vec![
-            /* 1 */ "scoreboard objectives add test_global dummy".to_string(),
-            /* 2 */
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
             format!(
-                "execute unless score test test_global matches 1 run function {}",
+                "execute as @e[type=sheep,tag=test] run function {}",
                 inner.name
             ),
-            /* 3 */ "scoreboard objectives remove test_global".to_string(),
         ],
     };
     let outer_path = outer.full_path();
     create_datapack(vec![outer, inner]);
 
-    let log_observer = LogObserver::new(TEST_LOG_FILE);
-    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
-
-    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_out(threads[0].id).await;
-    adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 3);
-    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
-    assert!(stack_trace[0].line == 2);
-    assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &inner_path.display().to_string());
-    assert!(stack_trace[1].line == 5);
-    assert!(stack_trace[1].column == 1);
-    assert!(get_source_path(&stack_trace[2]) == &outer_path.display().to_string());
-    assert!(stack_trace[2].line == 2);
-    assert!(stack_trace[2].column == 1);
-
-    let breaks = Vec::new();
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
-
-    let breaks = Vec::new();
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    let health = vars.iter().find(|it| it.name == "Health").unwrap();
+    assert_eq!(health.value, "5.0");
+    assert_eq!(health.evaluate_name.as_deref(), Some("Health"));
+    let tags = vars.iter().find(|it| it.name == "Tags").unwrap();
+    assert!(tags.variables_reference != 0);
+    let tags = adapter.variables(tags.variables_reference).await;
+    assert!(tags.iter().any(|it| it.value == "\"test\""));
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
@@ -1159,36 +1457,32 @@ async fn test_step_out_into_start_of_function_with_breakpoint_via_recursion() ->
 
 #[tokio::test]
 #[serial]
-async fn test_step_out_of_inner_function_that_recursively_calls_outer_function() -> io::Result<()> {
+async fn test_scope_selected_entity_position() -> io::Result<()> {
     before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_POSITION;
+
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
-        lines: vec![
-            /* 1 */ logged_command(enable_logging_command()),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 3 */ logged_command(reset_logging_command()),
-            /* 4 */ "scoreboard players set test test_global 1".to_string(),
-            /* 5 */ "function adapter_test:outer".to_string(),
-        ],
+        lines: vec![/* 1 */ "say inner".to_string()],
     };
     let inner_path = inner.full_path();
     let outer = Mcfunction {
         name: ResourceLocation::new("adapter_test", "outer"),
         lines: vec![
-            /* 1 */ "scoreboard objectives add test_global dummy".to_string(),
-            /* 2 */
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "forceload add 0 0".to_string(),
+            "summon sheep 1.5 100.0 2.5 {Tags: [test], NoAI: true, NoGravity: true}".to_string(),
             format!(
-                "execute unless score test test_global matches 1 run function {}",
+                "execute as @e[type=sheep,tag=test] run function {}",
                 inner.name
             ),
-            /* 3 */ "scoreboard objectives remove test_global".to_string(),
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "forceload remove 0 0".to_string(),
         ],
     };
     let outer_path = outer.full_path();
     create_datapack(vec![outer, inner]);
 
-    let log_observer = LogObserver::new(TEST_LOG_FILE);
-    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
@@ -1197,55 +1491,1099 @@ async fn test_step_out_of_inner_function_that_recursively_calls_outer_function()
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
-
-    let breaks = vec![];
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_out(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 1);
-    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
-    assert!(stack_trace[0].line == 3);
-    assert!(stack_trace[0].column == 1);
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    let values = vars
+        .iter()
+        .map(|it| (it.name.as_str(), it.value.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        vec![
+            ("x", "1.5"),
+            ("y", "100.0"),
+            ("z", "2.5"),
+            ("dimension", "minecraft:overworld"),
+        ]
+    );
+    assert_eq!(vars[0].evaluate_name.as_deref(), Some("nbt:@s Pos[0]"));
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
     Ok(())
 }
 
-/// This was a bug caused by a resumption point and a step point at the same position
 #[tokio::test]
 #[serial]
-async fn test_step_out_of_recursive_function_to_same_position() -> io::Result<()> {
+async fn test_scope_globals() -> io::Result<()> {
     before_each_test().await;
-    let inner_name = ResourceLocation::new("adapter_test", "inner");
-    let inner = Mcfunction {
+    const SCOPE: &str = GLOBALS;
+
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            /* 1 */ "scoreboard players remove test test_global 1".to_string(),
-            /* 2 */
-            format!(
-                "execute if score test test_global matches 1.. run function {}",
-                inner_name
-            ),
-            /* 3 */ "scoreboard players reset test test_global".to_string(),
+            /* 1 */ "say first".to_string(),
+            /* 2 */ "schedule function adapter_test:scheduled 1t".to_string(),
         ],
-        name: inner_name,
     };
-    let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
-        lines: vec![
-            /* 1 */ "scoreboard objectives add test_global dummy".to_string(),
-            /* 2 */ "scoreboard players set test test_global 2".to_string(),
+    let scheduled = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "scheduled"),
+        lines: vec![/* 1 */ "say second".to_string()],
+    };
+    let test_path = test.full_path();
+    let scheduled_path = scheduled.full_path();
+    create_datapack(vec![test, scheduled]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+    adapter
+        .set_breakpoints_verified(&scheduled_path, &breaks)
+        .await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let gametime = |vars: &[Variable]| {
+        let gametime = vars.iter().find(|it| it.name == "gametime").unwrap();
+        gametime.value.parse::<i64>().unwrap()
+    };
+    let threads = adapter.threads().await;
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    let names = vars.iter().map(|it| it.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names[..4], ["gametime", "daytime", "weather", "difficulty"]);
+    let first_gametime = gametime(&vars);
+
+    adapter.continue_().await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    assert!(gametime(&vars) > first_gametime);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_scope_selected_entity_score_of_objective_created_at_runtime() -> io::Result<()> {
+    before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_SCORES;
+
+    // Objectives of skipped functions are unknown to the debugger when generating the datapack
+    let setup = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "setup"),
+        lines: vec![
+            "# mcfd:skip".to_string(),
+            "scoreboard objectives add test_dynamic dummy".to_string(),
+            "scoreboard players set @s test_dynamic 7".to_string(),
+        ],
+    };
+    let cleanup = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "cleanup"),
+        lines: vec![
+            "# mcfd:skip".to_string(),
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "scoreboard objectives remove test_dynamic".to_string(),
+        ],
+    };
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![/* 1 */ "say inner".to_string()],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                setup.name
+            ),
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
+            format!("function {}", cleanup.name),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner, setup, cleanup]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    let values = vars
+        .iter()
+        .map(|it| (it.name.as_str(), it.value.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec![("test_dynamic", "7")]);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_scope_selected_entity_score_can_be_removed() -> io::Result<()> {
+    before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_SCORES;
+
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ "scoreboard players set @s test_local 42".to_string(),
+            /* 2 */ "scoreboard players reset @s test_local".to_string(),
+            /* 3 */ "scoreboard objectives remove test_local".to_string(),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            "scoreboard objectives add test_local dummy".to_string(),
+            "scoreboard players reset * test_local".to_string(),
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = [
+        SourceBreakpoint::builder().line(2).build(),
+        SourceBreakpoint::builder().line(3).build(),
+    ];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    assert!(vars.len() == 1);
+    assert!(vars[0].name == "test_local");
+    assert!(vars[0].value == "42");
+
+    adapter.continue_().await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    assert!(vars.is_empty());
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_scope_selected_entity_score_multiple_depths() -> io::Result<()> {
+    before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_SCORES;
+
+    let pig = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "pig"),
+        lines: vec![
+            /* 1 */ "scoreboard players set @s test_local 52".to_string(),
+            /* 2 */ "scoreboard objectives remove test_local".to_string(),
+        ],
+    };
+    let pig_path = pig.full_path();
+    let sheep = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "sheep"),
+        lines: vec![
+            /* 1 */ "scoreboard players set @s test_local 42".to_string(),
+            /* 2 */ "kill @e[type=pig,tag=test]".to_string(),
+            /* 3 */ "summon pig ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 4 */ format!("execute as @e[type=pig,tag=test] run function {}", pig.name),
+        ],
+    };
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            "scoreboard objectives add test_local dummy".to_string(),
+            "scoreboard players reset * test_local".to_string(),
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                sheep.name
+            ),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test, sheep, pig]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = [SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&pig_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 3);
+
+    let vars = adapter.variables_of_scope(stack_trace[0].id, SCOPE).await;
+    assert!(vars.len() == 1);
+    assert!(vars[0].name == "test_local");
+    assert!(vars[0].value == "52");
+
+    let vars = adapter.variables_of_scope(stack_trace[1].id, SCOPE).await;
+    assert!(vars.len() == 1);
+    assert!(vars[0].name == "test_local");
+    assert!(vars[0].value == "42");
+
+    let scopes = adapter.scopes(stack_trace[2].id).await;
+    assert!(scopes.iter().find(|it| it.name == SCOPE).is_none());
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_scope_selected_entity_score_server_context() -> io::Result<()> {
+    before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_SCORES;
+
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ "scoreboard objectives add test_local dummy".to_string(),
+            /* 2 */ "scoreboard players reset * test_local".to_string(),
+            /* 3 */ "scoreboard players set @s test_local 42".to_string(),
+            /* 4 */ "scoreboard objectives remove test_local".to_string(),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(4).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    let scopes = adapter.scopes(stack_trace[0].id).await;
+    assert!(scopes.iter().find(|it| it.name == SCOPE).is_none());
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_stack_trace_with_five_nested_function_calls() -> io::Result<()> {
+    before_each_test().await;
+    const SCOPE: &str = SELECTED_ENTITY_SCORES;
+
+    let depth5 = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "depth5"),
+        lines: vec![
+            /* 1 */ "scoreboard players set @s test_global 5".to_string(),
+            /* 2 */ "kill @e[type=sheep,tag=test]".to_string(),
+        ],
+    };
+    let depth5_path = depth5.full_path();
+    let depth4 = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "depth4"),
+        lines: vec![
+            /* 1 */ "scoreboard players set @s test_global 4".to_string(),
+            /* 2 */ format!("function {}", depth5.name),
+        ],
+    };
+    let depth4_path = depth4.full_path();
+    let depth3 = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "depth3"),
+        lines: vec![/* 1 */ format!("function {}", depth4.name)],
+    };
+    let depth3_path = depth3.full_path();
+    let depth2 = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "depth2"),
+        lines: vec![
+            /* 1 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 2 */
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                depth3.name
+            ),
+        ],
+    };
+    let depth2_path = depth2.full_path();
+    let depth1 = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "depth1"),
+        lines: vec![/* 1 */ format!("function {}", depth2.name)],
+    };
+    let depth1_path = depth1.full_path();
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ "scoreboard objectives add test_global dummy".to_string(),
+            /* 2 */ "kill @e[type=sheep,tag=test]".to_string(),
+            /* 3 */ format!("function {}", depth1.name),
+            /* 4 */ "scoreboard objectives remove test_global".to_string(),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test, depth1, depth2, depth3, depth4, depth5]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = [SourceBreakpoint::builder().line(2).build()];
+    adapter
+        .set_breakpoints_verified(&depth5_path, &breaks)
+        .await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 6);
+    let expected = [
+        (&depth5_path, 2, true),
+        (&depth4_path, 2, true),
+        (&depth3_path, 1, true),
+        (&depth2_path, 2, false),
+        (&depth1_path, 1, false),
+        (&test_path, 3, false),
+    ];
+    for (frame, (path, line, entity_context)) in stack_trace.iter().zip(expected) {
+        assert!(get_source_path(frame) == &path.display().to_string());
+        assert!(frame.line == line);
+
+        let scopes = adapter.scopes(frame.id).await;
+        assert!(scopes.iter().any(|it| it.name == SCOPE) == entity_context);
+    }
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_breakpoint_in_function_executed_in_other_dimension() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ "setblock ~ ~ ~ minecraft:diamond_block".to_string(),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ "execute in minecraft:the_nether run forceload add ~ ~".to_string(),
+            /* 3 */
+            format!(
+                "execute in minecraft:the_nether run function {}",
+                inner.name
+            ),
+            /* 4 */
+            named_logged_command(
+                "execute in minecraft:the_nether if block ~ ~ ~ minecraft:diamond_block run \
+                tag @s add in_nether",
+            ),
+            /* 5 */
+            named_logged_command(
+                "execute in minecraft:overworld if block ~ ~ ~ minecraft:diamond_block run \
+                tag @s add in_overworld",
+            ),
+            /* 6 */
+            "execute in minecraft:the_nether run setblock ~ ~ ~ minecraft:air".to_string(),
+            /* 7 */ "execute in minecraft:the_nether run forceload remove ~ ~".to_string(),
+            /* 8 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 2);
+    assert!(stack_trace[0].name == "adapter_test:inner:2 in minecraft:the_nether");
+    assert!(stack_trace[1].name == "adapter_test:test:3 in minecraft:overworld");
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("in_nether"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Not in overworld
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_out_of_root_function() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "some_tag")),
+            /* 3 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("some_tag")); // Line was executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_replay_file() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ "say 1".to_string(),
+            /* 2 */ "say 2".to_string(),
+            /* 3 */ "say 3".to_string(),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+    let replay_file = std::env::temp_dir().join("mcfunction-debug-adapter-test-replay.ndjson");
+    let _ = remove_file(&replay_file);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter
+        .launch_with_attributes(&test_path, [("replayFile", json!(replay_file))])
+        .await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    adapter.next(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+
+    let records = std::fs::read_to_string(&replay_file)?
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    let times = records
+        .iter()
+        .map(|it| it["t"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert!(times.windows(2).all(|it| it[0] <= it[1]));
+
+    let requests = records
+        .iter()
+        .filter(|it| it["kind"] == "dap_request")
+        .map(|it| it["data"]["command"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        requests,
+        [
+            "initialize",
+            "setBreakpoints",
+            "launch",
+            "threads",
+            "next",
+            "continue",
+            "disconnect"
+        ]
+    );
+    let last = records.last().unwrap();
+    assert!(last["kind"] == "dap_response");
+    assert!(last["data"]["command"] == "disconnect");
+    assert!(records.iter().any(|it| it["kind"] == "mc_command"));
+    assert!(records.iter().any(|it| it["kind"] == "mc_event"));
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_out_of_inner_function() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            logged_command(enable_logging_command()),
+            format!("function {}", inner.name),
+            named_logged_command(add_tag_command("@s", "tag3")),
+            logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Third line NOT executed
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_out_of_inner_function_with_multiple_executors() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            "kill @e[type=sheep,tag=test]".to_string(),
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            logged_command(enable_logging_command()),
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
+            named_logged_command(add_tag_command("@s", "tag3")),
+            logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let mut breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    breaks.remove(0);
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed by first sheep
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed by first sheep
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed by second sheep
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed by second sheep
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Third line NOT executed
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_out_of_inner_function_with_breakpoint() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            logged_command(enable_logging_command()),
+            format!("function {}", inner.name),
+            named_logged_command(add_tag_command("@s", "tag3")),
+            logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![
+        SourceBreakpoint::builder().line(1).build(),
+        SourceBreakpoint::builder().line(2).build(),
+    ];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_out_into_end_of_function() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer_line = format!("function {}", inner.name);
+    let outer_line_len = outer_line.len();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![outer_line],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(stack_trace[0].line == 1);
+    assert!(stack_trace[0].column == outer_line_len as i32 + 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_out_into_end_of_function_with_non_ascii_characters() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer_line = format!(
+        "execute unless entity @e[name=\"héros\"] run function {}",
+        inner.name
+    );
+    let outer_line_len = outer_line.chars().count();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![outer_line],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(stack_trace[0].line == 1);
+    assert!(stack_trace[0].column == outer_line_len as i32 + 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_out_into_end_of_function_with_breakpoint() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer_line = format!("function {}", inner.name);
+    let outer_line_len = outer_line.len();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![outer_line],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(stack_trace[0].line == 1);
+    assert!(stack_trace[0].column == outer_line_len as i32 + 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_out_into_start_of_function_with_breakpoint_via_recursion() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ logged_command(reset_logging_command()),
+            /* 4 */ "scoreboard players set test test_global 1".to_string(),
+            /* 5 */ "function adapter_test:outer".to_string(),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ "scoreboard objectives add test_global dummy".to_string(),
+            /* 2 */
+            format!(
+                "execute unless score test test_global matches 1 run function {}",
+                inner.name
+            ),
+            /* 3 */ "scoreboard objectives remove test_global".to_string(),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 3);
+    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
+    assert!(stack_trace[0].line == 2);
+    assert!(stack_trace[0].column == 1);
+    assert!(stack_trace[0].end_line == Some(2));
+    assert!(stack_trace[0].end_column == Some(80));
+    assert!(get_source_path(&stack_trace[1]) == &inner_path.display().to_string());
+    assert!(stack_trace[1].line == 5);
+    assert!(stack_trace[1].column == 1);
+    assert!(stack_trace[1].end_line == Some(5));
+    assert!(stack_trace[1].end_column == Some(28));
+    assert!(get_source_path(&stack_trace[2]) == &outer_path.display().to_string());
+    assert!(stack_trace[2].line == 2);
+    assert!(stack_trace[2].column == 1);
+    assert!(stack_trace[2].end_line == Some(2));
+    assert!(stack_trace[2].end_column == Some(80));
+
+    let breaks = Vec::new();
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    let breaks = Vec::new();
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_out_of_inner_function_that_recursively_calls_outer_function() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ logged_command(reset_logging_command()),
+            /* 4 */ "scoreboard players set test test_global 1".to_string(),
+            /* 5 */ "function adapter_test:outer".to_string(),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ "scoreboard objectives add test_global dummy".to_string(),
+            /* 2 */
+            format!(
+                "execute unless score test test_global matches 1 run function {}",
+                inner.name
+            ),
+            /* 3 */ "scoreboard objectives remove test_global".to_string(),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let breaks = vec![];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
+    assert!(stack_trace[0].line == 3);
+    assert!(stack_trace[0].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+/// This was a bug caused by a resumption point and a step point at the same position
+#[tokio::test]
+#[serial]
+async fn test_step_out_of_recursive_function_to_same_position() -> io::Result<()> {
+    before_each_test().await;
+    let inner_name = ResourceLocation::new("adapter_test", "inner");
+    let inner = Mcfunction {
+        lines: vec![
+            /* 1 */ "scoreboard players remove test test_global 1".to_string(),
+            /* 2 */
+            format!(
+                "execute if score test test_global matches 1.. run function {}",
+                inner_name
+            ),
+            /* 3 */ "scoreboard players reset test test_global".to_string(),
+        ],
+        name: inner_name,
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ "scoreboard objectives add test_global dummy".to_string(),
+            /* 2 */ "scoreboard players set test test_global 2".to_string(),
             /* 3 */ format!("function {}", inner.name),
             /* 4 */ "scoreboard objectives remove test_global".to_string(),
         ],
@@ -1256,8 +2594,756 @@ async fn test_step_out_of_recursive_function_to_same_position() -> io::Result<()
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 3);
+    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
+    assert!(stack_trace[0].line == 3);
+    assert!(stack_trace[0].column == 1);
+    assert!(get_source_path(&stack_trace[1]) == &inner_path.display().to_string());
+    assert!(stack_trace[1].line == 2);
+    assert!(stack_trace[1].column == 1);
+    assert!(get_source_path(&stack_trace[2]) == &outer_path.display().to_string());
+    assert!(stack_trace[2].line == 3);
+    assert!(stack_trace[2].column == 1);
+
+    let breaks = vec![];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_out(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 2);
+    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
+    assert!(stack_trace[0].line == 3);
+    assert!(stack_trace[0].column == 1);
+    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
+    assert!(stack_trace[1].line == 3);
+    assert!(stack_trace[1].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_next_steps_over_command() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.next(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_next_steps_over_function() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            named_logged_command(add_tag_command("@s", "tag1")),
+            named_logged_command(add_tag_command("@s", "tag2")),
+        ],
+    };
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ format!("function {}", inner.name),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag3")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.next(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Third line NOT executed
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_next_steps_over_function_that_recursively_calls_current_function() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 3 */ "scoreboard players set test test_global 1".to_string(),
+            /* 4 */ "function adapter_test:outer".to_string(),
+        ],
+    };
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ "scoreboard objectives add test_global dummy".to_string(),
+            /* 2 */ logged_command(enable_logging_command()),
+            /* 3 */
+            format!(
+                "execute unless score test test_global matches 1 run function {}",
+                inner.name
+            ),
+            /* 4 */ named_logged_command(add_tag_command("@s", "tag3")),
+            /* 5 */ logged_command(reset_logging_command()),
+            /* 6 */ "scoreboard objectives remove test_global".to_string(),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+
+    let breaks = vec![];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.next(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed via recursion
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
+    assert!(stack_trace[0].line == 4);
+    assert!(stack_trace[0].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed normally
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_next_steps_into_function_with_breakpoint() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ format!("function {}", inner.name),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag3")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.next(threads[0].id).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 2);
+    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
+    assert!(stack_trace[0].line == 2);
+    assert!(stack_trace[0].column == 1);
+    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
+    assert!(stack_trace[1].line == 2);
+    assert!(stack_trace[1].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_next_steps_out_of_function() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ format!("function {}", inner.name),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.next(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
+    assert!(stack_trace[0].line == 3);
+    assert!(stack_trace[0].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_next_steps_into_next_executor() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ "kill @e[type=sheep,tag=test]".to_string(),
+            /* 2 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 3 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 4 */ logged_command(enable_logging_command()),
+            /* 5 */
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
+            /* 6 */ named_logged_command(add_tag_command("@s", "tag3")),
+            /* 7 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed by first sheep
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let breaks = vec![];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.next(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed by first sheep
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 2);
+    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
+    assert!(stack_trace[0].line == 1);
+    assert!(stack_trace[0].column == 1);
+    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
+    assert!(stack_trace[1].line == 5);
+    assert!(stack_trace[1].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed by second sheep
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed by second sheep
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_next_with_statement_granularity_steps_after_execute_prefix() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![named_logged_command(add_tag_command("@s", "tag1"))],
+    };
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ "kill @e[type=sheep,tag=test]".to_string(),
+            /* 2 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 3 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 4 */ logged_command(enable_logging_command()),
+            /* 5 */
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
+            /* 6 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 7 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    let run_column = outer.lines[4].find("run").unwrap() as i32 + 1;
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(5).build()];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    for executor in 0..2 {
+        adapter
+            .next_with_granularity(threads[0].id, SteppingGranularity::Statement)
+            .await;
+        adapter.assert_stopped_after_step().await;
+        if executor > 0 {
+            assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+            // Previous executor
+        }
+        assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+        let stack_trace = adapter.stack_trace(threads[0].id).await;
+        assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
+        assert!(stack_trace[0].line == 5);
+        assert!(stack_trace[0].column == run_column);
+    }
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_next_steps_into_next_empty_executor() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ "kill @e[type=sheep,tag=test]".to_string(),
+            /* 2 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 3 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 4 */ logged_command(enable_logging_command()),
+            /* 5 */
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
+            /* 6 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 7 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(5).build()];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_in(threads[0].id).await;
+    adapter.assert_stopped_after_step().await; // Stopped in empty function for sheep 1
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 2);
+    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
+    assert!(stack_trace[0].line == 1);
+    assert!(stack_trace[0].column == 1);
+    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
+    assert!(stack_trace[1].line == 5);
+    assert!(stack_trace[1].column == 1);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.next(threads[0].id).await;
+    adapter.assert_stopped_after_step().await; // Stopped in empty function for sheep 2
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 2);
+    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
+    assert!(stack_trace[0].line == 1);
+    assert!(stack_trace[0].column == 1);
+    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
+    assert!(stack_trace[1].line == 5);
+    assert!(stack_trace[1].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_next_steps_into_next_executor_skipping_non_commands() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ "".to_string(), // skipped
+            /* 2 */ "# comment".to_string(), // skipped
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag1")),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ "kill @e[type=sheep,tag=test]".to_string(),
+            /* 2 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 3 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
+            /* 4 */ logged_command(enable_logging_command()),
+            /* 5 */
+            format!(
+                "execute as @e[type=sheep,tag=test] run function {}",
+                inner.name
+            ),
+            /* 6 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 7 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let breaks = vec![];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.next(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // Executed by first sheep
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 2);
+    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
+    assert!(stack_trace[0].line == 3);
+    assert!(stack_trace[0].column == 1);
+    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
+    assert!(stack_trace[1].line == 5);
+    assert!(stack_trace[1].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // Executed by second sheep
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_in_steps_over_command() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_in(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_in_function() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![named_logged_command(add_tag_command("@s", "tag1"))],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ format!("function {}", inner.name),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+
+    adapter.launch(&outer_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    adapter.step_in(threads[0].id).await;
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 2);
+    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
+    assert!(stack_trace[0].line == 1);
+    assert!(stack_trace[0].column == 1);
+    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
+    assert!(stack_trace[1].line == 2);
+    assert!(stack_trace[1].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_in_target() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![named_logged_command(add_tag_command("@s", "tag1"))],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ format!("execute as @s run function {}", inner.name),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
@@ -1265,92 +3351,226 @@ async fn test_step_out_of_recursive_function_to_same_position() -> io::Result<()
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 3);
-    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
-    assert!(stack_trace[0].line == 3);
-    assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &inner_path.display().to_string());
-    assert!(stack_trace[1].line == 2);
-    assert!(stack_trace[1].column == 1);
-    assert!(get_source_path(&stack_trace[2]) == &outer_path.display().to_string());
-    assert!(stack_trace[2].line == 3);
-    assert!(stack_trace[2].column == 1);
-
-    let breaks = vec![];
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+    let targets = adapter.step_in_targets(stack_trace[0].id).await;
+    assert!(targets.len() == 1);
+    assert!(targets[0].label == "adapter_test:inner (column 18)");
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_out(threads[0].id).await;
+    adapter
+        .step_in_with_target(threads[0].id, Some(targets[0].id))
+        .await;
     adapter.assert_stopped_after_step().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
     assert!(stack_trace.len() == 2);
     assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
-    assert!(stack_trace[0].line == 3);
-    assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
-    assert!(stack_trace[1].line == 3);
-    assert!(stack_trace[1].column == 1);
+    assert!(stack_trace[0].line == 1);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_next_steps_over_command() -> io::Result<()> {
+async fn test_stop_on_entry() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ "# Comments are skipped".to_string(),
+            /* 2 */ logged_command(enable_logging_command()),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let log_observer = LogObserver::new(TEST_LOG_FILE);
+    let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    adapter
+        .launch_with_attributes(&test_path, [("stopOnEntry", json!(true))])
+        .await;
+    adapter.assert_stopped_on_entry().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(stack_trace[0].line == 2);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_stop_on_entry_with_breakpoint_on_same_line() -> io::Result<()> {
     before_each_test().await;
     let test = Mcfunction {
         name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
             /* 1 */ logged_command(enable_logging_command()),
             /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
-            /* 4 */ logged_command(reset_logging_command()),
+            /* 3 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter
+        .launch_with_attributes(&test_path, [("stopOnEntry", json!(true))])
+        .await;
+    adapter.assert_stopped_on_entry().await;
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await; // No second stop on the same line
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_stop_on_entry_of_recursive_function() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![
+            /* 1 */ "scoreboard objectives add test_local dummy".to_string(),
+            /* 2 */ "scoreboard players add recursion test_local 1".to_string(),
+            /* 3 */
+            "execute if score recursion test_local matches ..1 run function adapter_test:test"
+                .to_string(),
+            /* 4 */ "scoreboard players reset recursion test_local".to_string(),
         ],
     };
     let test_path = test.full_path();
     create_datapack(vec![test]);
 
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    adapter
+        .launch_with_attributes(&test_path, [("stopOnEntry", json!(true))])
+        .await;
+    adapter.assert_stopped_on_entry().await;
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await; // The recursive call does not stop on entry
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_stop_on_entry_of_empty_function() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    adapter
+        .launch_with_attributes(&test_path, [("stopOnEntry", json!(true))])
+        .await;
+    adapter.assert_stopped_on_entry().await;
+
+    let threads = adapter.threads().await;
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(get_source_path(&stack_trace[0]) == &test_path.display().to_string());
+    assert!(stack_trace[0].line == 1);
+    assert!(stack_trace[0].column == 1);
+
+    adapter.continue_().await;
+    adapter.assert_terminated().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_step_in_empty_function() -> io::Result<()> {
+    before_each_test().await;
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ format!("function {}", inner.name),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 4 */ logged_command(reset_logging_command()),
+        ],
+    };
+    let outer_path = outer.full_path();
+    create_datapack(vec![outer, inner]);
+
     let log_observer = LogObserver::new(TEST_LOG_FILE);
     let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
     let breaks = vec![SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
 
-    adapter.launch(&test_path).await;
+    adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
-    adapter.next(threads[0].id).await;
+    adapter.step_in(threads[0].id).await;
     adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 2);
+    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
+    assert!(stack_trace[0].line == 1);
+    assert!(stack_trace[0].column == 1);
+    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
+    assert!(stack_trace[1].line == 2);
+    assert!(stack_trace[1].column == 1);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_next_steps_over_function() -> io::Result<()> {
+async fn test_step_in_steps_over_invalid_function() -> io::Result<()> {
     before_each_test().await;
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
+            "this is an invalid command".to_string(),
             named_logged_command(add_tag_command("@s", "tag1")),
-            named_logged_command(add_tag_command("@s", "tag2")),
         ],
     };
     let outer = Mcfunction {
@@ -1358,7 +3578,7 @@ async fn test_next_steps_over_function() -> io::Result<()> {
         lines: vec![
             /* 1 */ logged_command(enable_logging_command()),
             /* 2 */ format!("function {}", inner.name),
-            /* 3 */ named_logged_command(add_tag_command("@s", "tag3")),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
             /* 4 */ logged_command(reset_logging_command()),
         ],
     };
@@ -1375,49 +3595,47 @@ async fn test_next_steps_over_function() -> io::Result<()> {
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
-    adapter.next(threads[0].id).await;
+    adapter.step_in(threads[0].id).await;
     adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Third line NOT executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+
+    let threads = adapter.threads().await;
+    assert!(threads.len() == 1);
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace.len() == 1);
+    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
+    assert!(stack_trace[0].line == 3);
+    assert!(stack_trace[0].column == 1);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // tag1 is skipped
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_next_steps_over_function_that_recursively_calls_current_function() -> io::Result<()> {
+async fn test_step_in_steps_out_of_function() -> io::Result<()> {
     before_each_test().await;
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
-            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
-            /* 3 */ "scoreboard players set test test_global 1".to_string(),
-            /* 4 */ "function adapter_test:outer".to_string(),
-        ],
-    };
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
-        lines: vec![
-            /* 1 */ "scoreboard objectives add test_global dummy".to_string(),
-            /* 2 */ logged_command(enable_logging_command()),
-            /* 3 */
-            format!(
-                "execute unless score test test_global matches 1 run function {}",
-                inner.name
-            ),
-            /* 4 */ named_logged_command(add_tag_command("@s", "tag3")),
-            /* 5 */ logged_command(reset_logging_command()),
-            /* 6 */ "scoreboard objectives remove test_global".to_string(),
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+        ],
+    };
+    let inner_path = inner.full_path();
+    let outer = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "outer"),
+        lines: vec![
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ format!("function {}", inner.name),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 4 */ logged_command(reset_logging_command()),
         ],
     };
     let outer_path = outer.full_path();
@@ -1428,49 +3646,45 @@ async fn test_next_steps_over_function_that_recursively_calls_current_function()
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
 
-    let breaks = vec![];
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
-
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
-    adapter.next(threads[0].id).await;
+    adapter.step_in(threads[0].id).await;
     adapter.assert_stopped_after_step().await;
     assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed via recursion
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
     assert!(stack_trace.len() == 1);
     assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
-    assert!(stack_trace[0].line == 4);
+    assert!(stack_trace[0].line == 3);
     assert!(stack_trace[0].column == 1);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed normally
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_next_steps_into_function_with_breakpoint() -> io::Result<()> {
+async fn test_next_over_return_steps_out_of_function() -> io::Result<()> {
     before_each_test().await;
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
             /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 2 */ "execute if entity @s run return 1".to_string(),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
         ],
     };
     let inner_path = inner.full_path();
@@ -1491,50 +3705,48 @@ async fn test_next_steps_into_function_with_breakpoint() -> io::Result<()> {
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
-
     let breaks = vec![SourceBreakpoint::builder().line(2).build()];
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
     adapter.next(threads[0].id).await;
-    adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+    adapter.assert_stopped_after_step().await;
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Third line NOT executed
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 2);
-    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
-    assert!(stack_trace[0].line == 2);
+    assert!(stack_trace.len() == 1);
+    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
+    assert!(stack_trace[0].line == 3);
     assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
-    assert!(stack_trace[1].line == 2);
-    assert!(stack_trace[1].column == 1);
+    let line_3 = named_logged_command(add_tag_command("@s", "tag3"));
+    assert!(stack_trace[0].end_line == Some(3));
+    assert!(stack_trace[0].end_column == Some(line_3.len() as i32 + 1));
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_next_steps_out_of_function() -> io::Result<()> {
+async fn test_breakpoint_after_return_is_not_hit() -> io::Result<()> {
     before_each_test().await;
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
             /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ "return 1".to_string(),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
         ],
     };
     let inner_path = inner.full_path();
@@ -1543,7 +3755,7 @@ async fn test_next_steps_out_of_function() -> io::Result<()> {
         lines: vec![
             /* 1 */ logged_command(enable_logging_command()),
             /* 2 */ format!("function {}", inner.name),
-            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag3")),
             /* 4 */ logged_command(reset_logging_command()),
         ],
     };
@@ -1555,38 +3767,20 @@ async fn test_next_steps_out_of_function() -> io::Result<()> {
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(1).build()];
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
     adapter.launch(&outer_path).await;
-    adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.next(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 1);
-    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
-    assert!(stack_trace[0].line == 3);
-    assert!(stack_trace[0].column == 1);
-
-    adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line NOT executed
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_next_steps_into_next_executor() -> io::Result<()> {
+async fn test_step_in_next_executor() -> io::Result<()> {
     before_each_test().await;
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
@@ -1633,7 +3827,7 @@ async fn test_next_steps_into_next_executor() -> io::Result<()> {
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
-    adapter.next(threads[0].id).await;
+    adapter.step_in(threads[0].id).await;
     adapter.assert_stopped_after_step().await;
     assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed by first sheep
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
@@ -1660,7 +3854,7 @@ async fn test_next_steps_into_next_executor() -> io::Result<()> {
 
 #[tokio::test]
 #[serial]
-async fn test_next_steps_into_next_empty_executor() -> io::Result<()> {
+async fn test_step_in_next_empty_executor() -> io::Result<()> {
     before_each_test().await;
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
@@ -1717,7 +3911,7 @@ async fn test_next_steps_into_next_empty_executor() -> io::Result<()> {
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
-    adapter.next(threads[0].id).await;
+    adapter.step_in(threads[0].id).await;
     adapter.assert_stopped_after_step().await; // Stopped in empty function for sheep 2
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
@@ -1741,7 +3935,7 @@ async fn test_next_steps_into_next_empty_executor() -> io::Result<()> {
 
 #[tokio::test]
 #[serial]
-async fn test_next_steps_into_next_executor_skipping_non_commands() -> io::Result<()> {
+async fn test_step_in_steps_into_next_executor_skipping_non_commands() -> io::Result<()> {
     before_each_test().await;
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
@@ -1788,7 +3982,7 @@ async fn test_next_steps_into_next_executor_skipping_non_commands() -> io::Resul
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
-    adapter.next(threads[0].id).await;
+    adapter.step_in(threads[0].id).await;
     adapter.assert_stopped_after_step().await;
     assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // Executed by first sheep
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
@@ -1814,15 +4008,16 @@ async fn test_next_steps_into_next_executor_skipping_non_commands() -> io::Resul
 
 #[tokio::test]
 #[serial]
-async fn test_step_in_steps_over_command() -> io::Result<()> {
+async fn test_goto_skips_commands() -> io::Result<()> {
     before_each_test().await;
     let test = Mcfunction {
         name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
             /* 1 */ logged_command(enable_logging_command()),
             /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
-            /* 4 */ logged_command(reset_logging_command()),
+            /* 3 */ "# comment".to_string(),
+            /* 4 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 5 */ logged_command(reset_logging_command()),
         ],
     };
     let test_path = test.full_path();
@@ -1838,42 +4033,39 @@ async fn test_step_in_steps_over_command() -> io::Result<()> {
 
     adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+
+    let targets = adapter.goto_targets(&test_path, 3).await;
+    assert!(targets.len() == 1);
+    assert!(targets[0].line == 4); // The comment is skipped
 
     let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_in(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+    adapter.goto(threads[0].id, targets[0].id).await;
+    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    assert!(stack_trace[0].line == 4);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // First line skipped
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_in_function() -> io::Result<()> {
+async fn test_run_to_location() -> io::Result<()> {
     before_each_test().await;
-    let inner = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "inner"),
-        lines: vec![named_logged_command(add_tag_command("@s", "tag1"))],
-    };
-    let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
             /* 1 */ logged_command(enable_logging_command()),
-            /* 2 */ format!("function {}", inner.name),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
             /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
-            /* 4 */ logged_command(reset_logging_command()),
+            /* 4 */ named_logged_command(add_tag_command("@s", "tag3")),
+            /* 5 */ logged_command(reset_logging_command()),
         ],
     };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
 
     let log_observer = LogObserver::new(TEST_LOG_FILE);
     let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
@@ -1881,116 +4073,130 @@ async fn test_step_in_function() -> io::Result<()> {
     adapter.initalize().await;
 
     let breaks = vec![SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
-    adapter.launch(&outer_path).await;
+    adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_in(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
 
+    adapter.run_to_location(&test_path, 4).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 2);
-    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
-    assert!(stack_trace[0].line == 1);
-    assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
-    assert!(stack_trace[1].line == 2);
-    assert!(stack_trace[1].column == 1);
+    assert!(stack_trace[0].line == 4);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_in_empty_function() -> io::Result<()> {
+async fn test_run_to_location_is_removed_on_other_stop() -> io::Result<()> {
     before_each_test().await;
-    let inner = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "inner"),
-        lines: vec![],
-    };
-    let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
             /* 1 */ logged_command(enable_logging_command()),
-            /* 2 */ format!("function {}", inner.name),
-            /* 3 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ "function adapter_test:inner".to_string(),
             /* 4 */ logged_command(reset_logging_command()),
         ],
     };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let inner = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "inner"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "inner_tag")),
+        ],
+    };
+    let test_path = test.full_path();
+    let inner_path = inner.full_path();
+    create_datapack(vec![test, inner]);
 
     let log_observer = LogObserver::new(TEST_LOG_FILE);
     let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+    let breaks = vec![
+        SourceBreakpoint::builder().line(2).build(),
+        SourceBreakpoint::builder().line(3).build(),
+    ];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
-    adapter.launch(&outer_path).await;
+    adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_in(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 2);
-    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
-    assert!(stack_trace[0].line == 1);
-    assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
-    assert!(stack_trace[1].line == 2);
-    assert!(stack_trace[1].column == 1);
+    // The breakpoint in line 3 is hit before the location is reached
+    adapter.run_to_location(&inner_path, 1).await;
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("inner_tag"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_in_steps_over_invalid_function() -> io::Result<()> {
+async fn test_truncated_execution_stops_on_exception() -> io::Result<()> {
     before_each_test().await;
-    let inner = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "inner"),
-        lines: vec![
-            "this is an invalid command".to_string(),
-            named_logged_command(add_tag_command("@s", "tag1")),
-        ],
+    let mut lines = vec![
+        /* 1 */ "gamerule maxCommandChainLength 100".to_string(),
+        /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+    ];
+    lines.extend(vec!["tag @s add filler".to_string(); 200]);
+    lines.push(named_logged_command(add_tag_command("@s", "tag2")));
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines,
     };
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
+
+    adapter.launch(&test_path).await;
+    adapter.assert_stopped_at_breakpoint().await;
+
+    adapter.continue_().await;
+    let output = adapter.assert_important_output().await;
+    assert!(output.contains("maxCommandChainLength"));
+    adapter.assert_stopped_on_exception().await;
+
+    connection()
+        .execute_commands([Command::new("gamerule maxCommandChainLength 65536")])
+        .unwrap();
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_consecutive_breakpoint_changes_use_last_breakpoints() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
             /* 1 */ logged_command(enable_logging_command()),
-            /* 2 */ format!("function {}", inner.name),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
             /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
-            /* 4 */ logged_command(reset_logging_command()),
+            /* 4 */ named_logged_command(add_tag_command("@s", "tag3")),
+            /* 5 */ named_logged_command(add_tag_command("@s", "tag4")),
+            /* 6 */ logged_command(reset_logging_command()),
         ],
     };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
 
     let log_observer = LogObserver::new(TEST_LOG_FILE);
     let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
@@ -1998,54 +4204,54 @@ async fn test_step_in_steps_over_invalid_function() -> io::Result<()> {
     adapter.initalize().await;
 
     let breaks = vec![SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
-    adapter.launch(&outer_path).await;
+    adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_in(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    for line in 3..=5 {
+        let breaks = vec![SourceBreakpoint::builder().line(line).build()];
+        adapter.set_breakpoints_verified(&test_path, &breaks).await;
+    }
+    adapter.continue_().await;
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 1);
-    assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
-    assert!(stack_trace[0].line == 3);
-    assert!(stack_trace[0].column == 1);
+    adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // tag1 is skipped
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag4"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_in_steps_out_of_function() -> io::Result<()> {
+async fn test_step_out_of_function_tag_member() -> io::Result<()> {
     before_each_test().await;
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
             /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
         ],
     };
     let inner_path = inner.full_path();
+    let tag = ResourceLocation::new("adapter_test", "tag");
     let outer = Mcfunction {
         name: ResourceLocation::new("adapter_test", "outer"),
         lines: vec![
             /* 1 */ logged_command(enable_logging_command()),
-            /* 2 */ format!("function {}", inner.name),
-            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 2 */ format!("function #{}", tag),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag3")),
             /* 4 */ logged_command(reset_logging_command()),
         ],
     };
     let outer_path = outer.full_path();
+    create_function_tag(&tag, &[&inner.name]);
     create_datapack(vec![outer, inner]);
 
     let log_observer = LogObserver::new(TEST_LOG_FILE);
@@ -2058,254 +4264,249 @@ async fn test_step_in_steps_out_of_function() -> io::Result<()> {
 
     adapter.launch(&outer_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // First line NOT executed
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
     let threads = adapter.threads().await;
     assert!(threads.len() == 1);
-    adapter.step_in(threads[0].id).await;
+    adapter.step_out(threads[0].id).await;
     adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout); // Second line NOT executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
+    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
     let stack_trace = adapter.stack_trace(threads[0].id).await;
     assert!(stack_trace.len() == 1);
     assert!(get_source_path(&stack_trace[0]) == &outer_path.display().to_string());
     assert!(stack_trace[0].line == 3);
-    assert!(stack_trace[0].column == 1);
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag3"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_in_next_executor() -> io::Result<()> {
+async fn test_attach_to_suspended_function() -> io::Result<()> {
     before_each_test().await;
-    let inner = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "inner"),
-        lines: vec![
-            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
-        ],
-    };
-    let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            /* 1 */ "kill @e[type=sheep,tag=test]".to_string(),
-            /* 2 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            /* 3 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            /* 4 */ logged_command(enable_logging_command()),
-            /* 5 */
-            format!(
-                "execute as @e[type=sheep,tag=test] run function {}",
-                inner.name
-            ),
-            /* 6 */ named_logged_command(add_tag_command("@s", "tag3")),
-            /* 7 */ logged_command(reset_logging_command()),
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 3 */ named_logged_command(add_tag_command("@s", "tag2")),
+            /* 4 */ logged_command(reset_logging_command()),
         ],
     };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
 
     let log_observer = LogObserver::new(TEST_LOG_FILE);
     let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
-    adapter.launch(&outer_path).await;
+    adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed by first sheep
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    adapter.detach().await;
 
-    let breaks = vec![];
-    adapter.set_breakpoints_verified(&inner_path, &breaks).await;
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+    adapter.set_breakpoints_verified(&test_path, &breaks).await;
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_in(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed by first sheep
+    adapter.attach(&test_path).await;
+    adapter.assert_stopped_on_entry().await;
+    let stack_trace = adapter.stack_trace(0).await;
+    assert_eq!(stack_trace[0].name, "adapter_test:test:3");
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 2);
-    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
-    assert!(stack_trace[0].line == 1);
-    assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
-    assert!(stack_trace[1].line == 5);
-    assert!(stack_trace[1].column == 1);
-
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // First line executed by second sheep
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag2")); // Second line executed by second sheep
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag3")); // Third line executed
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_in_next_empty_executor() -> io::Result<()> {
+async fn test_breakpoint_in_additional_datapack() -> io::Result<()> {
     before_each_test().await;
-    let inner = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "inner"),
-        lines: vec![],
+    let callee = Mcfunction {
+        name: ResourceLocation::new("adapter_test_other", "callee"),
+        lines: vec![
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
+        ],
     };
-    let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
+    let callee_path = callee.full_path_in(other_datapack_dir());
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            /* 1 */ "kill @e[type=sheep,tag=test]".to_string(),
-            /* 2 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            /* 3 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            /* 4 */ logged_command(enable_logging_command()),
-            /* 5 */
-            format!(
-                "execute as @e[type=sheep,tag=test] run function {}",
-                inner.name
-            ),
-            /* 6 */ named_logged_command(add_tag_command("@s", "tag1")),
-            /* 7 */ logged_command(reset_logging_command()),
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ format!("function {}", callee.name),
+            /* 3 */ logged_command(reset_logging_command()),
         ],
     };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+    create_datapack_in(other_datapack_dir(), vec![callee]);
 
     let log_observer = LogObserver::new(TEST_LOG_FILE);
     let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(5).build()];
-    adapter.set_breakpoints_verified(&outer_path, &breaks).await;
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
+    adapter
+        .set_breakpoints_verified(&callee_path, &breaks)
+        .await;
 
-    adapter.launch(&outer_path).await;
+    let additional_datapacks = json!([other_datapack_dir()]);
+    adapter
+        .launch_with_attributes(&test_path, [("additionalDatapacks", additional_datapacks)])
+        .await;
     adapter.assert_stopped_at_breakpoint().await;
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_in(threads[0].id).await;
-    adapter.assert_stopped_after_step().await; // Stopped in empty function for sheep 1
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 2);
-    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
-    assert!(stack_trace[0].line == 1);
-    assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
-    assert!(stack_trace[1].line == 5);
-    assert!(stack_trace[1].column == 1);
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_in(threads[0].id).await;
-    adapter.assert_stopped_after_step().await; // Stopped in empty function for sheep 2
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
+    let stack_trace = adapter.stack_trace(0).await;
     assert!(stack_trace.len() == 2);
-    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
-    assert!(stack_trace[0].line == 1);
-    assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
-    assert!(stack_trace[1].line == 5);
-    assert!(stack_trace[1].column == 1);
+    assert!(stack_trace[0]
+        .name
+        .starts_with("adapter_test_other:callee:2"));
+    let source_path = stack_trace[0]
+        .source
+        .as_ref()
+        .and_then(|it| it.path.clone());
+    assert!(source_path == Some(callee_path.display().to_string()));
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    remove_dir_all(other_datapack_dir())?;
     Ok(())
 }
 
 #[tokio::test]
 #[serial]
-async fn test_step_in_steps_into_next_executor_skipping_non_commands() -> io::Result<()> {
+async fn test_attach_restores_stack_trace_of_nested_function_call() -> io::Result<()> {
     before_each_test().await;
     let inner = Mcfunction {
         name: ResourceLocation::new("adapter_test", "inner"),
         lines: vec![
-            /* 1 */ "".to_string(), // skipped
-            /* 2 */ "# comment".to_string(), // skipped
-            /* 3 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 1 */ named_logged_command(add_tag_command("@s", "tag1")),
+            /* 2 */ named_logged_command(add_tag_command("@s", "tag2")),
         ],
     };
     let inner_path = inner.full_path();
-    let outer = Mcfunction {
-        name: ResourceLocation::new("adapter_test", "outer"),
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
         lines: vec![
-            /* 1 */ "kill @e[type=sheep,tag=test]".to_string(),
-            /* 2 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            /* 3 */ "summon sheep ~ ~ ~ {Tags: [test], NoAI: true}".to_string(),
-            /* 4 */ logged_command(enable_logging_command()),
-            /* 5 */
-            format!(
-                "execute as @e[type=sheep,tag=test] run function {}",
-                inner.name
-            ),
-            /* 6 */ named_logged_command(add_tag_command("@s", "tag2")),
-            /* 7 */ logged_command(reset_logging_command()),
+            /* 1 */ logged_command(enable_logging_command()),
+            /* 2 */ format!("function {}", inner.name),
+            /* 3 */ logged_command(reset_logging_command()),
         ],
     };
-    let outer_path = outer.full_path();
-    create_datapack(vec![outer, inner]);
+    let test_path = test.full_path();
+    create_datapack(vec![test, inner]);
 
     let log_observer = LogObserver::new(TEST_LOG_FILE);
     let mut listener = TimeoutStream::new(log_observer.add_named_listener(LISTENER_NAME));
     let mut adapter = start_adapter();
     adapter.initalize().await;
 
-    let breaks = vec![SourceBreakpoint::builder().line(3).build()];
+    let breaks = vec![SourceBreakpoint::builder().line(2).build()];
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
-    adapter.launch(&outer_path).await;
+    adapter.launch(&test_path).await;
     adapter.assert_stopped_at_breakpoint().await;
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
+    assert!(listener.next().await.unwrap().output == added_tag_output("tag1"));
+    let launched_stack_trace = adapter.stack_trace(0).await;
+    adapter.detach().await;
 
-    let breaks = vec![];
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
     adapter.set_breakpoints_verified(&inner_path, &breaks).await;
 
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    adapter.step_in(threads[0].id).await;
-    adapter.assert_stopped_after_step().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // Executed by first sheep
-    assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
-
-    let threads = adapter.threads().await;
-    assert!(threads.len() == 1);
-    let stack_trace = adapter.stack_trace(threads[0].id).await;
-    assert!(stack_trace.len() == 2);
-    assert!(get_source_path(&stack_trace[0]) == &inner_path.display().to_string());
-    assert!(stack_trace[0].line == 3);
-    assert!(stack_trace[0].column == 1);
-    assert!(get_source_path(&stack_trace[1]) == &outer_path.display().to_string());
-    assert!(stack_trace[1].line == 5);
-    assert!(stack_trace[1].column == 1);
+    adapter.attach(&test_path).await;
+    adapter.assert_stopped_on_entry().await;
+    let stack_trace = adapter.stack_trace(0).await;
+    assert_eq!(stack_trace.len(), 2);
+    assert_eq!(stack_trace[0].name, launched_stack_trace[0].name);
+    assert_eq!(stack_trace[1].name, launched_stack_trace[1].name);
+    assert!(stack_trace[0].name.starts_with("adapter_test:inner:2"));
+    assert!(stack_trace[1].name.starts_with("adapter_test:test:2"));
 
     adapter.continue_().await;
     adapter.assert_terminated().await;
-    assert!(listener.next().await.unwrap().output == added_tag_output("tag1")); // Executed by second sheep
     assert!(listener.next().await.unwrap().output == added_tag_output("tag2"));
     assert!(listener.try_next().unwrap_err() == TimeoutStreamError::Timeout);
     Ok(())
 }
+
+#[tokio::test]
+#[serial]
+async fn test_attach_without_suspended_function() -> io::Result<()> {
+    before_each_test().await;
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![named_logged_command(add_tag_command("@s", "tag1"))],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    let request_seq = adapter.send_attach(&test_path).await;
+    adapter.assert_connected().await;
+    let response = adapter.output.next().await.unwrap();
+    let error_response = assert_error_response(response, request_seq);
+    assert!(error_response.command == "attach");
+    assert!(error_response.message.starts_with("Cannot attach"));
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_launch_cancelled_while_connecting() -> io::Result<()> {
+    let test = Mcfunction {
+        name: ResourceLocation::new("adapter_test", "test"),
+        lines: vec![named_logged_command(add_tag_command("@s", "tag1"))],
+    };
+    let test_path = test.full_path();
+    create_datapack(vec![test]);
+    // Nothing is ever written to this log file, so the connection is never established
+    let log_file = std::env::temp_dir().join("mcfunction-debugger-never-connecting.log");
+    std::fs::write(&log_file, "")?;
+
+    let mut adapter = start_adapter();
+    adapter.initalize().await;
+
+    // The second launch shows that the adapter is not stuck after the first one was cancelled
+    for _ in 0..2 {
+        let attributes = [("minecraftLogFile", json!(log_file))];
+        let request_seq = adapter
+            .send_launch_with_attributes(&test_path, attributes)
+            .await;
+        let progress_id = adapter
+            .assert_progress_start("Connecting to Minecraft")
+            .await;
+
+        adapter.cancel_progress(&progress_id).await;
+
+        let message = "Cancelled connecting to Minecraft";
+        adapter.assert_progress_end(&progress_id, message).await;
+        let response = adapter.output.next().await.unwrap();
+        let error_response = assert_error_response(response, request_seq);
+        assert!(error_response.command == "launch");
+        assert!(error_response.message == "cancelled");
+    }
+    Ok(())
+}
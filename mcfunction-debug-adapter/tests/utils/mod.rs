@@ -21,25 +21,40 @@ pub mod timeout;
 use crate::utils::timeout::TimeoutStream;
 use assert2::{assert, let_assert};
 use debug_adapter_protocol::{
-    events::{Event, StoppedEventReason},
+    events::{
+        BreakpointEventBody, BreakpointEventReason, Event, LoadedSourceEventBody, OutputCategory,
+        StoppedEventBody, StoppedEventReason,
+    },
     requests::{
-        ContinueRequestArguments, DisconnectRequestArguments, InitializeRequestArguments,
+        CancelRequestArguments, ContinueRequestArguments, DisconnectRequestArguments,
+        GotoRequestArguments, GotoTargetsRequestArguments, InitializeRequestArguments,
         LaunchRequestArguments, NextRequestArguments, Request, ScopesRequestArguments,
         SetBreakpointsRequestArguments, StackTraceRequestArguments, StepInRequestArguments,
-        StepOutRequestArguments, VariablesRequestArguments,
+        StepInTargetsRequestArguments, StepOutRequestArguments, TerminateRequestArguments,
+        VariablesRequestArguments,
     },
     responses::{ErrorResponse, Response, SetBreakpointsResponseBody, SuccessResponse},
-    types::{Scope, Source, SourceBreakpoint, StackFrame, Thread, Variable},
+    types::{
+        Breakpoint, GotoTarget, Scope, Source, SourceBreakpoint, StackFrame, StepInTarget,
+        SteppingGranularity, Thread, Variable,
+    },
     ProtocolMessage, ProtocolMessageContent as Content, SequenceNumber,
 };
-use futures::{Sink, SinkExt, Stream};
+use futures::{Sink, SinkExt, Stream, StreamExt};
 use mcfunction_debug_adapter::{
-    adapter::McfunctionDebugAdapter, error::DebugAdapterError, run_adapter,
+    adapter::{
+        McfunctionDebugAdapter, PARTITIONS_COMMAND, RUN_TO_LOCATION_COMMAND, TIMING_COMMAND,
+    },
+    custom::{CustomMessage, CustomMessageContent, CustomRequest, Message},
+    error::DebugAdapterError,
+    recording::Recorder,
+    run_recorded_adapter,
+    trace::Tracer,
 };
 use mcfunction_debugger::parser::command::resource_location::ResourceLocation;
 use minect::MinecraftConnection;
 use sender_sink::wrappers::UnboundedSenderSink;
-use serde_json::{json, Map};
+use serde_json::{json, Map, Value};
 use std::{
     fs::{create_dir_all, write},
     io,
@@ -52,6 +67,8 @@ use tokio_stream::wrappers::UnboundedReceiverStream;
 
 const ADAPTER_ID: &str = "mcfunction";
 pub const TEST_DATAPACK_NAME: &str = "adapter-test";
+/// A second datapack for tests that debug several datapacks together.
+pub const OTHER_TEST_DATAPACK_NAME: &str = "adapter-test-other";
 pub const LISTENER_NAME: &str = "adapter-test-listener";
 pub const TEST_LOG_FILE: &str = env!("TEST_LOG_FILE");
 pub const TEST_WORLD_DIR: &str = env!("TEST_WORLD_DIR");
@@ -59,30 +76,36 @@ const THREAD_ID: i32 = 0;
 
 pub struct TestAdapter<I, O>
 where
-    I: Sink<io::Result<ProtocolMessage>, Error = io::Error> + Unpin,
+    I: Sink<io::Result<Message>, Error = io::Error> + Unpin,
     O: Stream<Item = ProtocolMessage> + Unpin,
 {
     pub handle: JoinHandle<Result<(), DebugAdapterError<io::Error, io::Error, io::Error>>>,
     pub input: ProtocolMessageSender<I>,
     pub output: TimeoutStream<O, ProtocolMessage>,
+    /// Responses to custom requests. They are not ordered with respect to [TestAdapter::output].
+    pub custom_output: TimeoutStream<UnboundedReceiverStream<CustomMessage>, CustomMessage>,
 }
 
 pub fn start_adapter() -> TestAdapter<
-    impl Sink<io::Result<ProtocolMessage>, Error = io::Error> + Unpin,
+    impl Sink<io::Result<Message>, Error = io::Error> + Unpin,
     impl Stream<Item = ProtocolMessage> + Unpin,
 > {
     let (input, adapter_input_stream) = unbound_io_channel();
-    let (adapter_output_sink, output) = unbound_io_channel();
+    let (adapter_output_sink, adapter_output) = unbound_io_channel();
+    let (output, custom_output) = split_custom_messages(adapter_output);
     let handle = tokio::task::spawn(async move {
-        run_adapter(
+        let recorder = Recorder::new();
+        run_recorded_adapter(
             adapter_input_stream,
             adapter_output_sink,
-            McfunctionDebugAdapter::new,
+            Tracer::disabled(),
+            recorder.clone(),
+            |message_sender| McfunctionDebugAdapter::new(message_sender).with_recorder(recorder),
         )
         .await
     });
 
-    let adapter_input: Box<dyn Sink<io::Result<ProtocolMessage>, Error = io::Error> + Unpin> =
+    let adapter_input: Box<dyn Sink<io::Result<Message>, Error = io::Error> + Unpin> =
         Box::new(input);
     let input = ProtocolMessageSender::new(adapter_input);
 
@@ -90,12 +113,36 @@ pub fn start_adapter() -> TestAdapter<
         handle,
         input,
         output: TimeoutStream::new(output),
+        custom_output: TimeoutStream::new(custom_output),
     }
 }
 
+fn split_custom_messages(
+    messages: impl Stream<Item = Message> + Send + 'static,
+) -> (
+    UnboundedReceiverStream<ProtocolMessage>,
+    UnboundedReceiverStream<CustomMessage>,
+) {
+    let (protocol_sender, protocol_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let (custom_sender, custom_receiver) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut messages = Box::pin(messages);
+        while let Some(message) = messages.next().await {
+            let _ = match message {
+                Message::Protocol(message) => protocol_sender.send(message).is_ok(),
+                Message::Custom(message) => custom_sender.send(message).is_ok(),
+            };
+        }
+    });
+    (
+        UnboundedReceiverStream::new(protocol_receiver),
+        UnboundedReceiverStream::new(custom_receiver),
+    )
+}
+
 impl<I, O> TestAdapter<I, O>
 where
-    I: Sink<io::Result<ProtocolMessage>, Error = io::Error> + Unpin,
+    I: Sink<io::Result<Message>, Error = io::Error> + Unpin,
     O: Stream<Item = ProtocolMessage> + Unpin,
 {
     pub async fn assert_stopped_after_step(&mut self) {
@@ -104,10 +151,73 @@ where
         assert!(body.reason == StoppedEventReason::Step);
     }
 
-    pub async fn assert_stopped_at_breakpoint(&mut self) {
+    pub async fn assert_stopped_at_breakpoint(&mut self) -> StoppedEventBody {
         let event = self.output.next().await.unwrap();
         let_assert!(Content::Event(Event::Stopped(body)) = event.content);
         assert!(body.reason == StoppedEventReason::Breakpoint);
+        body
+    }
+
+    pub async fn assert_stopped_on_exception(&mut self) {
+        let event = self.output.next().await.unwrap();
+        let_assert!(Content::Event(Event::Stopped(body)) = event.content);
+        assert!(body.reason == StoppedEventReason::Exception);
+    }
+
+    pub async fn assert_stopped_on_entry(&mut self) {
+        let event = self.output.next().await.unwrap();
+        let_assert!(Content::Event(Event::Stopped(body)) = event.content);
+        assert!(body.reason == StoppedEventReason::Entry);
+    }
+
+    pub async fn assert_connected(&mut self) {
+        let progress_start = self.output.next().await.unwrap();
+        let_assert!(Content::Event(Event::ProgressStart(body)) = progress_start.content);
+        assert!(body.title == "Connecting to Minecraft");
+
+        let progress_end = self.output.next().await.unwrap();
+        let_assert!(Content::Event(Event::ProgressEnd(body)) = progress_end.content);
+        assert!(body.message == Some("Successfully connected to Minecraft".to_string()));
+    }
+
+    pub async fn assert_datapack_generated(&mut self) {
+        let progress_id = self
+            .assert_progress_start("Generating debug datapack")
+            .await;
+        self.assert_progress_end(&progress_id, "Successfully generated debug datapack")
+            .await;
+    }
+
+    /// Returns the id of the progress.
+    pub async fn assert_progress_start(&mut self, title: &str) -> String {
+        let progress_start = self.output.next().await.unwrap();
+        let_assert!(Content::Event(Event::ProgressStart(body)) = progress_start.content);
+        assert!(body.title == title);
+        body.progress_id
+    }
+
+    pub async fn assert_progress_end(&mut self, progress_id: &str, message: &str) {
+        let progress_end = self.output.next().await.unwrap();
+        let_assert!(Content::Event(Event::ProgressEnd(body)) = progress_end.content);
+        assert!(body.progress_id == progress_id);
+        assert!(body.message == Some(message.to_string()));
+    }
+
+    pub async fn cancel_progress(&mut self, progress_id: &str) {
+        let args = CancelRequestArguments::builder()
+            .progress_id(Some(progress_id.to_string()))
+            .build();
+        let request_seq = self.input.send_ok(args).await;
+
+        let response = self.output.next().await.unwrap();
+        assert!(let SuccessResponse::Cancel = assert_success_response(response, request_seq));
+    }
+
+    pub async fn assert_important_output(&mut self) -> String {
+        let event = self.output.next().await.unwrap();
+        let_assert!(Content::Event(Event::Output(body)) = event.content);
+        assert!(body.category == OutputCategory::Important);
+        body.output
     }
 
     pub async fn assert_terminated(mut self) {
@@ -141,6 +251,49 @@ where
         assert!(let SuccessResponse::Disconnect = assert_success_response(response, request_seq));
     }
 
+    /// Disconnects without terminating the debugged program.
+    pub async fn detach(&mut self) {
+        let content = DisconnectRequestArguments::builder()
+            .terminate_debuggee(Some(false))
+            .build();
+        let request_seq = self.input.send_ok(content).await;
+
+        let response = self.output.next().await.unwrap();
+        assert!(let SuccessResponse::Disconnect = assert_success_response(response, request_seq));
+    }
+
+    pub async fn goto(&mut self, thread_id: i32, target_id: i32) {
+        let args = GotoRequestArguments::builder()
+            .thread_id(thread_id)
+            .target_id(target_id)
+            .build();
+        let request_seq = self.input.send_ok(args).await;
+
+        let event = self.output.next().await.unwrap();
+        let_assert!(Content::Event(Event::Stopped(body)) = event.content);
+        assert!(body.reason == StoppedEventReason::Goto);
+
+        let response = self.output.next().await.unwrap();
+        assert!(let SuccessResponse::Goto = assert_success_response(response, request_seq));
+    }
+
+    pub async fn goto_targets(&mut self, path: impl AsRef<Path>, line: i32) -> Vec<GotoTarget> {
+        let source = Source::builder()
+            .path(Some(path.as_ref().display().to_string()))
+            .build();
+        let args = GotoTargetsRequestArguments::builder()
+            .source(source)
+            .line(line)
+            .build();
+        let request_seq = self.input.send_ok(args).await;
+
+        let response = self.output.next().await.unwrap();
+        let_assert!(
+            SuccessResponse::GotoTargets(body) = assert_success_response(response, request_seq)
+        );
+        body.targets
+    }
+
     pub async fn initalize(&mut self) {
         let content = InitializeRequestArguments::builder()
             .adapter_id(ADAPTER_ID.to_string())
@@ -154,40 +307,154 @@ where
         assert!(let SuccessResponse::Initialize(_) = assert_success_response(response, request_seq));
     }
 
-    pub async fn launch(&mut self, test_fn_path: impl AsRef<Path>) {
-        let request_seq = self.send_launch(test_fn_path).await;
+    pub async fn attach(&mut self, test_fn_path: impl AsRef<Path>) {
+        let request_seq = self.send_attach(test_fn_path).await;
 
-        let progress_start = self.output.next().await.unwrap();
-        let_assert!(Content::Event(Event::ProgressStart(body)) = progress_start.content);
-        assert!(body.title == "Connecting to Minecraft");
+        self.assert_connected().await;
 
-        let progress_end = self.output.next().await.unwrap();
-        let_assert!(Content::Event(Event::ProgressEnd(body)) = progress_end.content);
-        assert!(body.message == Some("Successfully connected to Minecraft".to_string()));
+        let response = self.custom_output.next().await.unwrap();
+        let_assert!(CustomMessageContent::Response(response) = response.content);
+        assert!(response.request_seq == request_seq);
+        assert!(response.success);
+    }
+    pub async fn send_attach(&mut self, test_fn_path: impl AsRef<Path>) -> SequenceNumber {
+        let arguments = Value::Object(launch_attributes(test_fn_path));
+        self.input.send_custom("attach", arguments).await
+    }
+
+    pub async fn launch(&mut self, test_fn_path: impl AsRef<Path>) {
+        self.launch_with_attributes(test_fn_path, []).await;
+    }
+    pub async fn launch_with_attributes(
+        &mut self,
+        test_fn_path: impl AsRef<Path>,
+        attributes: impl IntoIterator<Item = (&str, Value)>,
+    ) {
+        let request_seq = self
+            .send_launch_with_attributes(test_fn_path, attributes)
+            .await;
+
+        self.assert_connected().await;
+        self.assert_datapack_generated().await;
 
         let response = self.output.next().await.unwrap();
         assert!(let SuccessResponse::Launch = assert_success_response(response, request_seq));
     }
+    /// Launches and returns the breakpoint events that the adapter sent before responding, for
+    /// example for `# breakpoint` comments.
+    pub async fn launch_collecting_breakpoint_events(
+        &mut self,
+        test_fn_path: impl AsRef<Path>,
+    ) -> Vec<BreakpointEventBody> {
+        let request_seq = self.send_launch(test_fn_path).await;
+
+        self.assert_connected().await;
+
+        let mut events = Vec::new();
+        loop {
+            let message = self.output.next().await.unwrap();
+            if let Content::Event(Event::ProgressStart(body)) = &message.content {
+                assert!(body.title == "Generating debug datapack");
+                let end_message = "Successfully generated debug datapack";
+                self.assert_progress_end(&body.progress_id, end_message)
+                    .await;
+                continue;
+            }
+            if let Content::Event(Event::Breakpoint(body)) = message.content {
+                events.push(body);
+                continue;
+            }
+            assert!(let SuccessResponse::Launch = assert_success_response(message, request_seq));
+            return events;
+        }
+    }
     pub async fn send_launch(&mut self, test_fn_path: impl AsRef<Path>) -> SequenceNumber {
-        let test_fn_path = test_fn_path.as_ref().display().to_string();
+        self.send_launch_with_attributes(test_fn_path, []).await
+    }
+    pub async fn send_launch_with_attributes(
+        &mut self,
+        test_fn_path: impl AsRef<Path>,
+        attributes: impl IntoIterator<Item = (&str, Value)>,
+    ) -> SequenceNumber {
+        let mut additional_attributes = launch_attributes(test_fn_path);
+        additional_attributes.extend(
+            attributes
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value)),
+        );
         let args = LaunchRequestArguments::builder()
-            .additional_attributes(Map::from_iter([
-                ("minecraftLogFile".to_string(), json!(TEST_LOG_FILE)),
-                ("minecraftWorldDir".to_string(), json!(TEST_WORLD_DIR)),
-                ("program".to_string(), json!(test_fn_path)),
-            ]))
+            .additional_attributes(additional_attributes)
             .build();
         self.input.send_ok(args).await
     }
 
+    pub async fn loaded_sources(&mut self) -> Vec<Source> {
+        let request_seq = self.input.send_ok(Request::LoadedSources).await;
+
+        let response = self.output.next().await.unwrap();
+        let_assert!(
+            SuccessResponse::LoadedSources(body) = assert_success_response(response, request_seq)
+        );
+        body.sources
+    }
+
     pub async fn next(&mut self, thread_id: i32) {
-        let args = NextRequestArguments::builder().thread_id(thread_id).build();
+        self.next_with_granularity(thread_id, SteppingGranularity::Line)
+            .await;
+    }
+    pub async fn next_with_granularity(
+        &mut self,
+        thread_id: i32,
+        granularity: SteppingGranularity,
+    ) {
+        let args = NextRequestArguments::builder()
+            .thread_id(thread_id)
+            .granularity(granularity)
+            .build();
         let request_seq = self.input.send_ok(args).await;
 
         let response = self.output.next().await.unwrap();
         assert!(let SuccessResponse::Next = assert_success_response(response, request_seq));
     }
 
+    pub async fn partitions(&mut self, path: impl AsRef<Path>) -> Vec<Value> {
+        let arguments = json!({ "path": path.as_ref() });
+        let request_seq = self.input.send_custom(PARTITIONS_COMMAND, arguments).await;
+
+        let response = self.custom_output.next().await.unwrap();
+        let_assert!(CustomMessageContent::Response(response) = response.content);
+        assert!(response.request_seq == request_seq);
+        assert!(response.success);
+        let_assert!(Value::Array(partitions) = response.body["partitions"].clone());
+        partitions
+    }
+
+    pub async fn timing(&mut self) -> Value {
+        let request_seq = self.input.send_custom(TIMING_COMMAND, Value::Null).await;
+
+        let response = self.custom_output.next().await.unwrap();
+        let_assert!(CustomMessageContent::Response(response) = response.content);
+        assert!(response.request_seq == request_seq);
+        assert!(response.success);
+        response.body
+    }
+
+    pub async fn run_to_location(&mut self, path: impl AsRef<Path>, line: i32) {
+        let arguments = json!({
+            "path": path.as_ref(),
+            "line": line,
+        });
+        let request_seq = self
+            .input
+            .send_custom(RUN_TO_LOCATION_COMMAND, arguments)
+            .await;
+
+        let response = self.custom_output.next().await.unwrap();
+        let_assert!(CustomMessageContent::Response(response) = response.content);
+        assert!(response.request_seq == request_seq);
+        assert!(response.success);
+    }
+
     pub async fn scopes(&mut self, frame_id: i32) -> Vec<Scope> {
         let args = ScopesRequestArguments::builder().frame_id(frame_id).build();
         let request_seq = self.input.send_ok(args).await;
@@ -236,6 +503,101 @@ where
         body
     }
 
+    /// Sets breakpoints and returns the breakpoint events that the adapter sent before responding.
+    pub async fn set_breakpoints_collecting_events(
+        &mut self,
+        path: impl AsRef<Path>,
+        breakpoints: &[SourceBreakpoint],
+        source_modified: bool,
+    ) -> (Vec<BreakpointEventBody>, SetBreakpointsResponseBody) {
+        let content = SetBreakpointsRequestArguments::builder()
+            .source(
+                Source::builder()
+                    .path(Some(path.as_ref().display().to_string()))
+                    .build(),
+            )
+            .breakpoints(breakpoints.into())
+            .source_modified(source_modified)
+            .build();
+        let request_seq = self.input.send_ok(content).await;
+        let mut events = Vec::new();
+        loop {
+            let message = self.output.next().await.unwrap();
+            if let Content::Event(Event::Breakpoint(body)) = message.content {
+                events.push(body);
+                continue;
+            }
+            let_assert!(
+                SuccessResponse::SetBreakpoints(body) =
+                    assert_success_response(message, request_seq)
+            );
+            return (events, body);
+        }
+    }
+
+    /// Sets breakpoints and returns the loaded source events that the adapter sent before
+    /// responding.
+    pub async fn set_breakpoints_collecting_loaded_source_events(
+        &mut self,
+        path: impl AsRef<Path>,
+        breakpoints: &[SourceBreakpoint],
+    ) -> (Vec<LoadedSourceEventBody>, SetBreakpointsResponseBody) {
+        let content = SetBreakpointsRequestArguments::builder()
+            .source(
+                Source::builder()
+                    .path(Some(path.as_ref().display().to_string()))
+                    .build(),
+            )
+            .breakpoints(breakpoints.into())
+            .build();
+        let request_seq = self.input.send_ok(content).await;
+        let mut events = Vec::new();
+        loop {
+            let message = self.output.next().await.unwrap();
+            if let Content::Event(Event::LoadedSource(body)) = message.content {
+                events.push(body);
+                continue;
+            }
+            let_assert!(
+                SuccessResponse::SetBreakpoints(body) =
+                    assert_success_response(message, request_seq)
+            );
+            return (events, body);
+        }
+    }
+
+    /// Sets breakpoints in a file that was renamed or moved. Returns the breakpoints that the
+    /// adapter rebound to the new file before responding.
+    pub async fn set_breakpoints_in_moved_file(
+        &mut self,
+        old_path: impl AsRef<Path>,
+        breakpoints: &[SourceBreakpoint],
+    ) -> (Vec<Breakpoint>, SetBreakpointsResponseBody) {
+        let content = SetBreakpointsRequestArguments::builder()
+            .source(
+                Source::builder()
+                    .path(Some(old_path.as_ref().display().to_string()))
+                    .build(),
+            )
+            .breakpoints(breakpoints.into())
+            .build();
+        let request_seq = self.input.send_ok(content).await;
+        let mut rebound = Vec::new();
+        loop {
+            let message = self.output.next().await.unwrap();
+            if let Content::Event(Event::Breakpoint(body)) = message.content {
+                assert!(body.reason == BreakpointEventReason::Changed);
+                rebound.push(body.breakpoint);
+                continue;
+            }
+            let_assert!(
+                SuccessResponse::SetBreakpoints(body) =
+                    assert_success_response(message, request_seq)
+            );
+            return (rebound, body);
+        }
+    }
+
     pub async fn stack_trace(&mut self, thread_id: i32) -> Vec<StackFrame> {
         let args = StackTraceRequestArguments::builder()
             .thread_id(thread_id)
@@ -250,8 +612,22 @@ where
     }
 
     pub async fn step_in(&mut self, thread_id: i32) {
+        self.step_in_with_target(thread_id, None).await;
+    }
+    pub async fn step_in_with_target(&mut self, thread_id: i32, target_id: Option<i32>) {
+        self.step_in_with_granularity(thread_id, target_id, SteppingGranularity::Line)
+            .await;
+    }
+    pub async fn step_in_with_granularity(
+        &mut self,
+        thread_id: i32,
+        target_id: Option<i32>,
+        granularity: SteppingGranularity,
+    ) {
         let args = StepInRequestArguments::builder()
             .thread_id(thread_id)
+            .target_id(target_id)
+            .granularity(granularity)
             .build();
         let request_seq = self.input.send_ok(args).await;
 
@@ -259,6 +635,19 @@ where
         assert!(let SuccessResponse::StepIn = assert_success_response(response, request_seq));
     }
 
+    pub async fn step_in_targets(&mut self, frame_id: i32) -> Vec<StepInTarget> {
+        let args = StepInTargetsRequestArguments::builder()
+            .frame_id(frame_id)
+            .build();
+        let request_seq = self.input.send_ok(args).await;
+
+        let response = self.output.next().await.unwrap();
+        let_assert!(
+            SuccessResponse::StepInTargets(body) = assert_success_response(response, request_seq)
+        );
+        body.targets
+    }
+
     pub async fn step_out(&mut self, thread_id: i32) {
         let args = StepOutRequestArguments::builder()
             .thread_id(thread_id)
@@ -269,6 +658,14 @@ where
         assert!(let SuccessResponse::StepOut = assert_success_response(response, request_seq));
     }
 
+    pub async fn terminate(&mut self) {
+        let args = TerminateRequestArguments::builder().build();
+        let request_seq = self.input.send_ok(args).await;
+
+        let response = self.output.next().await.unwrap();
+        assert!(let SuccessResponse::Terminate = assert_success_response(response, request_seq));
+    }
+
     pub async fn threads(&mut self) -> Vec<Thread> {
         let request_seq = self.input.send_ok(Request::Threads).await;
 
@@ -297,6 +694,15 @@ where
         body.variables
     }
 }
+fn launch_attributes(test_fn_path: impl AsRef<Path>) -> Map<String, Value> {
+    let test_fn_path = test_fn_path.as_ref().display().to_string();
+    Map::from_iter([
+        ("minecraftLogFile".to_string(), json!(TEST_LOG_FILE)),
+        ("minecraftWorldDir".to_string(), json!(TEST_WORLD_DIR)),
+        ("program".to_string(), json!(test_fn_path)),
+    ])
+}
+
 pub fn assert_all_breakpoints_verified(
     response: &SetBreakpointsResponseBody,
     breakpoints: &[SourceBreakpoint],
@@ -318,14 +724,14 @@ fn unbound_io_channel<I>() -> (impl Sink<I, Error = io::Error>, impl Stream<Item
 
 pub struct ProtocolMessageSender<I>
 where
-    I: Sink<io::Result<ProtocolMessage>, Error = io::Error>,
+    I: Sink<io::Result<Message>, Error = io::Error>,
 {
     seq: SequenceNumber,
     adapter_input: I,
 }
 impl<I> ProtocolMessageSender<I>
 where
-    I: Sink<io::Result<ProtocolMessage>, Error = io::Error> + Unpin,
+    I: Sink<io::Result<Message>, Error = io::Error> + Unpin,
 {
     fn new(adapter_input: I) -> ProtocolMessageSender<I> {
         ProtocolMessageSender {
@@ -337,12 +743,23 @@ where
     pub async fn send_ok(&mut self, content: impl Into<Content>) -> SequenceNumber {
         self.seq += 1;
         let msg = ProtocolMessage::new(self.seq, content);
-        self.send(Ok(msg)).await;
+        self.send(Ok(msg.into())).await;
+        self.seq
+    }
+
+    pub async fn send_custom(&mut self, command: &str, arguments: Value) -> SequenceNumber {
+        self.seq += 1;
+        let request = CustomRequest {
+            command: command.to_string(),
+            arguments,
+        };
+        let msg = CustomMessage::new(self.seq, request);
+        self.send(Ok(msg.into())).await;
         self.seq
     }
 
-    pub async fn send(&mut self, msg: impl Into<io::Result<ProtocolMessage>>) {
-        self.adapter_input.send(msg.into()).await.unwrap();
+    pub async fn send(&mut self, msg: io::Result<Message>) {
+        self.adapter_input.send(msg).await.unwrap();
     }
 }
 
@@ -353,7 +770,12 @@ pub struct Mcfunction {
 }
 impl Mcfunction {
     pub fn full_path(&self) -> PathBuf {
-        datapack_dir()
+        self.full_path_in(datapack_dir())
+    }
+
+    pub fn full_path_in(&self, datapack: impl AsRef<Path>) -> PathBuf {
+        datapack
+            .as_ref()
             .join("data")
             .join(self.name.namespace())
             .join("functions")
@@ -367,25 +789,47 @@ pub fn named_logged_command(command: impl Into<String>) -> String {
 }
 
 pub fn create_datapack(functions: Vec<Mcfunction>) {
-    create_dir_all(&datapack_dir()).unwrap();
+    create_datapack_in(datapack_dir(), functions);
+}
+
+pub fn create_datapack_in(datapack: impl AsRef<Path>, functions: Vec<Mcfunction>) {
+    let datapack = datapack.as_ref();
+    create_dir_all(datapack).unwrap();
     write(
-        datapack_dir().join("pack.mcmeta"),
+        datapack.join("pack.mcmeta"),
         r#"{"pack":{"pack_format":7,"description":"McFunction-Debugger adapter test"}}"#,
     )
     .unwrap();
     for function in functions {
-        let path = function.full_path();
+        let path = function.full_path_in(datapack);
         create_dir_all(&path.parent().unwrap()).unwrap();
         write(path, function.lines.join("\n")).unwrap();
     }
 }
 
+pub fn create_function_tag(tag: &ResourceLocation, values: &[&ResourceLocation]) {
+    let path = datapack_dir()
+        .join("data")
+        .join(tag.namespace())
+        .join("tags/functions")
+        .join(format!("{}.json", tag.path()));
+    create_dir_all(&path.parent().unwrap()).unwrap();
+    let values = values.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+    write(path, json!({ "values": values }).to_string()).unwrap();
+}
+
 pub fn datapack_dir() -> std::path::PathBuf {
     Path::new(TEST_WORLD_DIR)
         .join("datapacks")
         .join(TEST_DATAPACK_NAME)
 }
 
+pub fn other_datapack_dir() -> std::path::PathBuf {
+    Path::new(TEST_WORLD_DIR)
+        .join("datapacks")
+        .join(OTHER_TEST_DATAPACK_NAME)
+}
+
 pub fn connection() -> MinecraftConnection {
     MinecraftConnection::builder("mcfunction-debugger", TEST_WORLD_DIR)
         .log_file(TEST_LOG_FILE)
@@ -0,0 +1,268 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Read-only access to datapacks that are distributed as zip files. A file inside a zip file is
+//! denoted by a virtual path of the form `<pack.zip>!/data/ns/functions/foo.mcfunction`, which is
+//! the notation VS Code uses for paths in archives. Only stored and deflated entries are supported,
+//! just like Minecraft.
+
+use flate2::{read::DeflateDecoder, Crc};
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// Separates the path of a zip file from the name of an entry in a virtual path.
+pub const ARCHIVE_SEPARATOR: &str = "!/";
+
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const CENTRAL_DIRECTORY_HEADER_SIGNATURE: u32 = 0x02014b50;
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const END_OF_CENTRAL_DIRECTORY_LEN: usize = 22;
+const MAX_COMMENT_LEN: usize = u16::MAX as usize;
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// Returns true if the path denotes a zip file rather than a directory.
+pub fn is_archive(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+        && path.is_file()
+}
+
+/// Splits a virtual path into the path of the zip file and the name of the entry. Returns [None]
+/// if the path does not denote a file inside a zip file.
+pub fn split_archive_path(path: &Path) -> Option<(&Path, String)> {
+    let string = path.to_str()?;
+    let index = string.to_ascii_lowercase().find(".zip!")? + ".zip".len();
+    let entry = string[index + 1..].trim_start_matches(['/', '\\']);
+    Some((Path::new(&string[..index]), entry.replace('\\', "/")))
+}
+
+/// Returns the virtual path of the entry in the zip file.
+pub fn archive_entry_path(archive: impl AsRef<Path>, entry: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}{}{}",
+        archive.as_ref().display(),
+        ARCHIVE_SEPARATOR,
+        entry
+    ))
+}
+
+/// Joins a path relative to the root of the datapack, which may be a zip file, to the datapack.
+pub fn join_datapack_path(datapack: impl AsRef<Path>, relative_path: impl AsRef<Path>) -> PathBuf {
+    let datapack = datapack.as_ref();
+    if is_archive(datapack) {
+        let entry = relative_path
+            .as_ref()
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        archive_entry_path(datapack, &entry)
+    } else {
+        datapack.join(relative_path)
+    }
+}
+
+/// Returns the path of the file on disk that contains the file denoted by the path. This is the zip
+/// file for a virtual path and the path itself otherwise.
+pub fn physical_path(path: &Path) -> &Path {
+    split_archive_path(path).map_or(path, |(archive, _entry)| archive)
+}
+
+/// Like [Path::is_file], but also supports virtual paths.
+pub fn is_file(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    match split_archive_path(path) {
+        Some((archive, entry)) => ZipArchive::open(archive)
+            .map(|zip| zip.entries().any(|it| it == entry))
+            .unwrap_or(false),
+        None => path.is_file(),
+    }
+}
+
+/// Like [tokio::fs::read_to_string], but also supports virtual paths.
+pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    match split_archive_path(path) {
+        Some((archive, entry)) => {
+            let archive = archive.to_path_buf();
+            tokio::task::spawn_blocking(move || {
+                let bytes = ZipArchive::open(&archive)?.read(&entry)?;
+                String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            })
+            .await?
+        }
+        None => tokio::fs::read_to_string(path).await,
+    }
+}
+
+/// Returns the names of all files in the zip file, directories are skipped.
+pub fn list_archive(archive: impl AsRef<Path>) -> io::Result<Vec<String>> {
+    Ok(ZipArchive::open(archive)?
+        .entries()
+        .map(ToString::to_string)
+        .collect())
+}
+
+struct ZipEntry {
+    name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    local_header_offset: u64,
+}
+
+/// A zip file whose central directory was read. Entries are only decompressed when read.
+struct ZipArchive {
+    file: File,
+    entries: Vec<ZipEntry>,
+}
+
+impl ZipArchive {
+    fn open(path: impl AsRef<Path>) -> io::Result<ZipArchive> {
+        let mut file = File::open(path)?;
+        let len = file.seek(SeekFrom::End(0))?;
+        let tail_len = len.min((END_OF_CENTRAL_DIRECTORY_LEN + MAX_COMMENT_LEN) as u64);
+        let mut tail = vec![0; tail_len as usize];
+        file.seek(SeekFrom::Start(len - tail_len))?;
+        file.read_exact(&mut tail)?;
+
+        let eocd = (0..=tail.len().saturating_sub(END_OF_CENTRAL_DIRECTORY_LEN))
+            .rev()
+            .find(|&index| read_u32(&tail, index) == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+            .map(|index| &tail[index..])
+            .ok_or_else(|| invalid_data("Could not find end of central directory"))?;
+        let count = read_u16(eocd, 10).unwrap();
+        let size = read_u32(eocd, 12).unwrap();
+        let offset = read_u32(eocd, 16).unwrap();
+        if count == u16::MAX || size == u32::MAX || offset == u32::MAX {
+            return Err(invalid_data("Zip64 is not supported"));
+        }
+
+        let mut central_directory = vec![0; size as usize];
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.read_exact(&mut central_directory)?;
+        let mut entries = Vec::with_capacity(count as usize);
+        let mut header = central_directory.as_slice();
+        for _ in 0..count {
+            let entry = read_central_directory_header(header)
+                .ok_or_else(|| invalid_data("Invalid central directory header"))?;
+            header = &header[entry.0..];
+            entries.push(entry.1);
+        }
+        Ok(ZipArchive { file, entries })
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .filter(|name| !name.ends_with('/'))
+    }
+
+    fn read(&mut self, name: &str) -> io::Result<Vec<u8>> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No entry named {} in zip file", name),
+                )
+            })?;
+
+        let mut header = [0; 30];
+        self.file.seek(SeekFrom::Start(entry.local_header_offset))?;
+        self.file.read_exact(&mut header)?;
+        if read_u32(&header, 0) != Some(LOCAL_FILE_HEADER_SIGNATURE) {
+            return Err(invalid_data("Invalid local file header"));
+        }
+        let name_len = read_u16(&header, 26).unwrap() as i64;
+        let extra_len = read_u16(&header, 28).unwrap() as i64;
+        self.file.seek(SeekFrom::Current(name_len + extra_len))?;
+
+        let compressed = (&self.file).take(entry.compressed_size);
+        let mut content = Vec::with_capacity(entry.uncompressed_size as usize);
+        match entry.method {
+            STORED => compressed
+                .take(entry.uncompressed_size)
+                .read_to_end(&mut content)?,
+            DEFLATED => DeflateDecoder::new(compressed)
+                .take(entry.uncompressed_size)
+                .read_to_end(&mut content)?,
+            method => {
+                return Err(invalid_data(format!(
+                    "Unsupported compression method {} of entry {}",
+                    method, name
+                )))
+            }
+        };
+        let mut crc = Crc::new();
+        crc.update(&content);
+        if content.len() as u64 != entry.uncompressed_size || crc.sum() != entry.crc {
+            return Err(invalid_data(format!("Entry {} is corrupt", name)));
+        }
+        Ok(content)
+    }
+}
+
+/// Returns the length of the header and the entry it describes.
+fn read_central_directory_header(header: &[u8]) -> Option<(usize, ZipEntry)> {
+    if read_u32(header, 0)? != CENTRAL_DIRECTORY_HEADER_SIGNATURE {
+        return None;
+    }
+    let name_len = read_u16(header, 28)? as usize;
+    let extra_len = read_u16(header, 30)? as usize;
+    let comment_len = read_u16(header, 32)? as usize;
+    let name = header.get(46..46 + name_len)?;
+    let entry = ZipEntry {
+        // Some tools write backslashes on Windows
+        name: String::from_utf8_lossy(name).replace('\\', "/"),
+        method: read_u16(header, 10)?,
+        crc: read_u32(header, 16)?,
+        compressed_size: read_u32(header, 20)? as u64,
+        uncompressed_size: read_u32(header, 24)? as u64,
+        local_header_offset: read_u32(header, 42)? as u64,
+    };
+    Some((46 + name_len + extra_len + comment_len, entry))
+}
+
+fn read_u16(bytes: &[u8], index: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(index..index + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(bytes: &[u8], index: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(index..index + 4)?.try_into().ok()?,
+    ))
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+pub(crate) mod tests;
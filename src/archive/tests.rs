@@ -0,0 +1,221 @@
+use super::*;
+use flate2::{write::DeflateEncoder, Compression};
+use std::{
+    fs::{create_dir_all, remove_dir_all, write},
+    io::Write,
+};
+
+/// Writes a zip file containing the given entries. Entries ending with `.mcfunction` are deflated,
+/// all others are stored.
+pub(crate) fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+    let mut bytes = Vec::new();
+    let mut central_directory = Vec::new();
+    for (name, content) in entries {
+        let method = if name.ends_with(".mcfunction") {
+            DEFLATED
+        } else {
+            STORED
+        };
+        let data = if method == DEFLATED {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(content.as_bytes()).unwrap();
+            encoder.finish().unwrap()
+        } else {
+            content.as_bytes().to_vec()
+        };
+        let mut crc = Crc::new();
+        crc.update(content.as_bytes());
+
+        let offset = bytes.len() as u32;
+        bytes.extend(LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+        bytes.extend([20, 0, 0, 0]); // version needed, flags
+        bytes.extend(method.to_le_bytes());
+        bytes.extend([0; 4]); // modification time and date
+        bytes.extend(crc.sum().to_le_bytes());
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend((content.len() as u32).to_le_bytes());
+        bytes.extend((name.len() as u16).to_le_bytes());
+        bytes.extend([0; 2]); // extra field length
+        bytes.extend(name.as_bytes());
+        bytes.extend(&data);
+
+        central_directory.extend(CENTRAL_DIRECTORY_HEADER_SIGNATURE.to_le_bytes());
+        central_directory.extend([20, 0, 20, 0, 0, 0]); // versions, flags
+        central_directory.extend(method.to_le_bytes());
+        central_directory.extend([0; 4]); // modification time and date
+        central_directory.extend(crc.sum().to_le_bytes());
+        central_directory.extend((data.len() as u32).to_le_bytes());
+        central_directory.extend((content.len() as u32).to_le_bytes());
+        central_directory.extend((name.len() as u16).to_le_bytes());
+        central_directory.extend([0; 12]); // lengths, disk number and attributes
+        central_directory.extend(offset.to_le_bytes());
+        central_directory.extend(name.as_bytes());
+    }
+    let offset = bytes.len() as u32;
+    bytes.extend(&central_directory);
+    bytes.extend(END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+    bytes.extend([0; 4]); // disk numbers
+    bytes.extend((entries.len() as u16).to_le_bytes());
+    bytes.extend((entries.len() as u16).to_le_bytes());
+    bytes.extend((central_directory.len() as u32).to_le_bytes());
+    bytes.extend(offset.to_le_bytes());
+    bytes.extend([0; 2]); // comment length
+    write(path, bytes).unwrap();
+}
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-archive-test")
+        .join(name);
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_list_archive_skips_directories() {
+    // given:
+    let zip = test_dir("test_list_archive_skips_directories").join("pack.zip");
+    write_zip(
+        &zip,
+        &[
+            ("pack.mcmeta", "{}"),
+            ("data/", ""),
+            ("data/test/functions/main.mcfunction", "say hi"),
+        ],
+    );
+
+    // when:
+    let actual = list_archive(&zip).unwrap();
+
+    // then:
+    assert_eq!(
+        actual,
+        vec!["pack.mcmeta", "data/test/functions/main.mcfunction"]
+    );
+}
+
+#[tokio::test]
+async fn test_read_to_string_of_stored_and_deflated_entries() {
+    // given:
+    let zip = test_dir("test_read_to_string_of_stored_and_deflated_entries").join("pack.zip");
+    let function = "say hi\n".repeat(100);
+    write_zip(
+        &zip,
+        &[
+            ("pack.mcmeta", "{}"),
+            ("data/test/functions/main.mcfunction", &function),
+        ],
+    );
+
+    // when:
+    let pack_mcmeta = read_to_string(archive_entry_path(&zip, "pack.mcmeta")).await;
+    let main = read_to_string(archive_entry_path(
+        &zip,
+        "data/test/functions/main.mcfunction",
+    ))
+    .await;
+
+    // then:
+    assert_eq!(pack_mcmeta.unwrap(), "{}");
+    assert_eq!(main.unwrap(), function);
+}
+
+#[tokio::test]
+async fn test_read_to_string_of_missing_entry() {
+    // given:
+    let zip = test_dir("test_read_to_string_of_missing_entry").join("pack.zip");
+    write_zip(&zip, &[("pack.mcmeta", "{}")]);
+
+    // when:
+    let actual = read_to_string(archive_entry_path(&zip, "missing.mcfunction")).await;
+
+    // then:
+    assert_eq!(actual.unwrap_err().kind(), io::ErrorKind::NotFound);
+}
+
+#[tokio::test]
+async fn test_read_to_string_of_corrupt_entry() {
+    // given:
+    let zip = test_dir("test_read_to_string_of_corrupt_entry").join("pack.zip");
+    write_zip(&zip, &[("pack.mcmeta", "{}")]);
+    let mut bytes = std::fs::read(&zip).unwrap();
+    let data_index = 30 + "pack.mcmeta".len();
+    bytes[data_index] = b'[';
+    write(&zip, bytes).unwrap();
+
+    // when:
+    let actual = read_to_string(archive_entry_path(&zip, "pack.mcmeta")).await;
+
+    // then:
+    let error = actual.unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "Entry pack.mcmeta is corrupt");
+}
+
+#[test]
+fn test_open_file_that_is_not_a_zip_file() {
+    // given:
+    let path = test_dir("test_open_file_that_is_not_a_zip_file").join("pack.zip");
+    write(&path, "not a zip file").unwrap();
+
+    // when:
+    let actual = list_archive(&path);
+
+    // then:
+    let error = actual.unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "Could not find end of central directory");
+}
+
+#[test]
+fn test_split_archive_path() {
+    // given:
+    let path = Path::new("/world/datapacks/Pack.ZIP!/data/test/functions/main.mcfunction");
+
+    // when:
+    let actual = split_archive_path(path);
+
+    // then:
+    assert_eq!(
+        actual,
+        Some((
+            Path::new("/world/datapacks/Pack.ZIP"),
+            "data/test/functions/main.mcfunction".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_split_archive_path_of_normal_path() {
+    // given:
+    let path = Path::new("/world/datapacks/pack.zip/data/test/functions/main.mcfunction");
+
+    // when:
+    let actual = split_archive_path(path);
+
+    // then:
+    assert_eq!(actual, None);
+}
+
+#[test]
+fn test_join_datapack_path() {
+    // given:
+    let dir = test_dir("test_join_datapack_path");
+    let zip = dir.join("pack.zip");
+    write_zip(&zip, &[("pack.mcmeta", "{}")]);
+    let relative_path = Path::new("data").join("test").join("functions");
+
+    // when:
+    let in_zip = join_datapack_path(&zip, &relative_path);
+    let in_dir = join_datapack_path(&dir, &relative_path);
+
+    // then:
+    assert_eq!(
+        in_zip,
+        PathBuf::from(format!("{}!/data/test/functions", zip.display()))
+    );
+    assert_eq!(in_dir, dir.join(relative_path));
+    assert!(is_file(join_datapack_path(&zip, "pack.mcmeta")));
+    assert!(!is_file(join_datapack_path(&zip, "data")));
+}
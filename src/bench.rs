@@ -0,0 +1,108 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Entry points into the steps of [generate_debug_datapack](crate::generate_debug_datapack) for the
+//! benchmarks in the `benches` directory. Only available with the `bench` feature.
+
+use crate::{
+    config::Config,
+    find_datapack_function_files, find_tag_functions, parse_functions,
+    parser::{command::CommandParser, parse_line, Line},
+    partition::partition,
+    ResourceLocation,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::read_to_string,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The functions of a datapack, including the functions that are generated for function tags.
+pub struct DatapackFunctions {
+    functions: BTreeMap<ResourceLocation, PathBuf>,
+    tag_functions: BTreeMap<ResourceLocation, Vec<String>>,
+}
+
+impl DatapackFunctions {
+    pub async fn find(
+        datapack_path: impl AsRef<Path>,
+        config: &Config<'_>,
+    ) -> io::Result<DatapackFunctions> {
+        let (functions, _invalid_functions) =
+            find_datapack_function_files(&datapack_path, config, config.skip_invalid_functions)
+                .await?;
+        let tag_functions = find_tag_functions(&datapack_path, &functions, config).await?;
+        Ok(DatapackFunctions {
+            functions,
+            tag_functions,
+        })
+    }
+
+    /// Reads and parses all functions.
+    pub async fn parse(
+        &self,
+        parser: &CommandParser,
+        config: &Config<'_>,
+    ) -> io::Result<ParsedFunctions<'_>> {
+        let functions =
+            parse_functions(&self.functions, &self.tag_functions, parser, config).await?;
+        Ok(ParsedFunctions { functions })
+    }
+
+    /// Reads and parses all functions one after another with blocking reads, like
+    /// [parse_functions] did before it read the files asynchronously. The functions of function tags
+    /// are not parsed.
+    pub fn parse_blocking(
+        &self,
+        parser: &CommandParser,
+        config: &Config<'_>,
+    ) -> io::Result<ParsedFunctions<'_>> {
+        let functions = self
+            .functions
+            .iter()
+            .map(|(name, path)| {
+                let lines = read_to_string(path)?
+                    .split('\n')
+                    .enumerate()
+                    .map(|(line_index, line)| {
+                        let line = line.strip_suffix('\r').unwrap_or(line);
+                        let command = parse_line(parser, line, config.adapter.is_none());
+                        (line_index + 1, line.to_string(), command)
+                    })
+                    .collect();
+                Ok((name, lines))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(ParsedFunctions { functions })
+    }
+}
+
+pub struct ParsedFunctions<'l> {
+    functions: HashMap<&'l ResourceLocation, Vec<(usize, String, Line)>>,
+}
+
+impl ParsedFunctions<'_> {
+    /// Partitions all functions and returns the total number of partitions.
+    pub fn partition(&self, config: &Config<'_>) -> usize {
+        self.functions
+            .iter()
+            .map(|(function, lines)| partition(function, lines, config).len())
+            .sum()
+    }
+}
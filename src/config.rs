@@ -17,34 +17,506 @@
 // If not, see <http://www.gnu.org/licenses/>.
 
 pub mod adapter;
+mod file;
+
+pub use file::{ConfigFile, CONFIG_FILE_NAME};
 
 use crate::{
-    config::adapter::{AdapterConfig, BreakpointKind, BreakpointPositionInLine},
+    config::adapter::{
+        AdapterConfig, BreakpointKind, BreakpointPositionInLine, LocalBreakpoint, SuspendSchedules,
+    },
     parser::command::resource_location::ResourceLocation,
 };
+use multimap::MultiMap;
+use std::{collections::BTreeSet, fmt::Display, io, path::PathBuf, str::FromStr};
+
+/// The number of commands Minecraft executes in a single function chain by default
+/// (`maxCommandChainLength`).
+pub const DEFAULT_MAX_COMMANDS: usize = 65536;
+
+/// The pack_format of the generated datapack if the pack_format of the debugged datapack can't be
+/// read. This is the pack_format of Minecraft 1.17, the oldest version the debugger supports.
+pub const PACK_FORMAT: u64 = 7;
+
+/// Minecraft 1.17, which the generated datapack targets, does not allow longer objective names.
+pub const MAX_OBJECTIVE_LENGTH: usize = 16;
+
+/// Minecraft does not load a function if one of its commands is longer than this.
+pub const MAX_COMMAND_LENGTH: usize = 32500;
+
+/// A function whose first line is this comment is not debugged, like a function matching an exclude
+/// pattern.
+pub const SKIP_MARKER: &str = "# mcfd:skip";
+
+/// The scoreboard objective that marks the other [OBJECTIVES] as added. `debug:install` adds it
+/// before checking whether `-ns-:install` must run, so it is not part of [OBJECTIVES].
+pub const VERSION_OBJECTIVE: &str = "-ns-_version";
+
+/// The scoreboard objectives that the debug datapack adds, with `-ns-` as a placeholder for the
+/// namespace. Templates add and remove them with the `# -add_objectives-` and
+/// `# -remove_objectives-` directives of the template engine, so they are only listed here. A unit
+/// test ensures that templates don't use other objectives.
+pub const OBJECTIVES: [&str; 15] = [
+    "-ns-_Age",
+    "-ns-_Duration",
+    "-ns-_WaitTime",
+    "-ns-_anchor",
+    "-ns-_constant",
+    "-ns-_depth",
+    "-ns-_dim",
+    "-ns-_global",
+    "-ns-_id",
+    "-ns-_iter",
+    "-ns-_scores",
+    "-ns-_shadow",
+    "-ns-_skipped",
+    "-ns-_tmp",
+    "-ns-_valid",
+];
+
+/// The tags that the debug datapack and the debug adapter add to entities, with `-ns-` as a
+/// placeholder for the namespace. Tags of breakpoints and schedules that contain the position or
+/// function are not listed, because only entities tagged with `-ns-` carry them. Templates remove
+/// all of them with the `# -remove_tags-` directive of the template engine.
+pub const TAGS: [&str; 16] = [
+    "-ns-",
+    "-ns-_active",
+    "-ns-_before_age_increment",
+    "-ns-_breakpoint",
+    "-ns-_context",
+    "-ns-_current",
+    "-ns-_frozen",
+    "-ns-_function_call",
+    "-ns-_new",
+    "-ns-_return",
+    "-ns-_running",
+    "-ns-_schedule",
+    "-ns-_skip",
+    "-ns-_stack_trace",
+    "-ns-_suspended",
+    "-ns-_tmp",
+];
+
+/// A Minecraft release like `1.20.4`. Versions are ordered by their components and a missing patch
+/// component is `0`, so `1.20` equals `1.20.0`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct MinecraftVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+impl MinecraftVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> MinecraftVersion {
+        MinecraftVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+impl FromStr for MinecraftVersion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn from_str_inner(s: &str) -> Option<MinecraftVersion> {
+            let mut components = s.split('.');
+            let major = components.next()?.parse().ok()?;
+            let minor = components.next()?.parse().ok()?;
+            let patch = match components.next() {
+                Some(patch) => patch.parse().ok()?,
+                None => 0,
+            };
+            if components.next().is_some() {
+                return None;
+            }
+            Some(MinecraftVersion::new(major, minor, patch))
+        }
+        from_str_inner(s).ok_or(())
+    }
+}
+impl Display for MinecraftVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if self.patch != 0 {
+            write!(f, ".{}", self.patch)?;
+        }
+        Ok(())
+    }
+}
 
 pub struct Config<'l> {
     pub namespace: &'l str,
     pub shadow: bool,
+    /// Patterns of functions that should not be debugged, see [find_exclude_pattern].
+    pub exclude: &'l [String],
+    /// Functions that should not be debugged. Functions marked with [SKIP_MARKER] are added to
+    /// this set during generation.
+    pub excluded_functions: BTreeSet<ResourceLocation>,
+    /// Whether function files whose path is not a valid resource location are left out of the
+    /// debug datapack with a warning instead of failing the generation.
+    pub skip_invalid_functions: bool,
+    /// Datapacks that are debugged together with the input datapack, for example because its
+    /// functions call functions of these datapacks. All of their functions are merged into one
+    /// debug datapack.
+    pub additional_datapacks: &'l [PathBuf],
+    /// The maximum number of commands in a generated function. Larger functions are split where
+    /// possible, see [DEFAULT_MAX_COMMANDS].
+    pub max_commands: usize,
     pub adapter: Option<AdapterConfig<'l>>,
 }
-impl Config<'_> {
-    pub(crate) fn get_breakpoint_kind(
+impl<'l> Config<'l> {
+    /// Checks that Minecraft accepts the datapack generated with this config.
+    pub fn validate(&self) -> io::Result<()> {
+        if let Some(objective) = find_too_long_objectives(self.namespace).first() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Namespace '{}' is too long, the scoreboard objective {} would have more than \
+                    {} characters. Use a namespace with at most {} characters.",
+                    self.namespace,
+                    objective,
+                    MAX_OBJECTIVE_LENGTH,
+                    max_namespace_length()
+                ),
+            ));
+        }
+        let suspend_schedules = self.adapter.as_ref().map(|it| it.suspend_schedules);
+        if let Some(suspend_schedules) = suspend_schedules.filter(|_| self.shadow) {
+            if suspend_schedules != SuspendSchedules::All {
+                // Scheduled functions that keep running while suspended would call the shadowing
+                // function, which can't start debugging while another function is suspended
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Suspend schedules '{}' can't be used with shadow.",
+                        suspend_schedules
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn is_excluded(&self, function: &ResourceLocation) -> bool {
+        self.excluded_functions.contains(function)
+            || find_exclude_pattern(self.exclude, function).is_some()
+    }
+
+    /// Returns a copy of this config that additionally excludes the given functions.
+    pub(crate) fn with_excluded_functions(
         &self,
-        function: &ResourceLocation,
-        line_number: usize,
-        position_in_line: BreakpointPositionInLine,
-    ) -> Option<&BreakpointKind> {
-        if let Some(config) = self.adapter.as_ref() {
-            if let Some(vec) = config.breakpoints.get_vec(function) {
-                return vec
-                    .iter()
-                    .filter(|breakpoint| breakpoint.position.line_number == line_number)
-                    .filter(|breakpoint| breakpoint.position.position_in_line == position_in_line)
-                    .next()
-                    .map(|it| &it.kind);
+        functions: impl IntoIterator<Item = ResourceLocation>,
+    ) -> Config<'l> {
+        let mut excluded_functions = self.excluded_functions.clone();
+        excluded_functions.extend(functions);
+        Config {
+            excluded_functions,
+            ..*self
+        }
+    }
+}
+
+pub(crate) fn get_breakpoint_kind<'l>(
+    breakpoints: &'l MultiMap<ResourceLocation, LocalBreakpoint>,
+    function: &ResourceLocation,
+    line_number: usize,
+    position_in_line: BreakpointPositionInLine,
+) -> Option<&'l BreakpointKind> {
+    breakpoints
+        .get_vec(function)?
+        .iter()
+        .filter(|breakpoint| breakpoint.position.line_number == line_number)
+        .filter(|breakpoint| breakpoint.position.position_in_line == position_in_line)
+        .next()
+        .map(|it| &it.kind)
+}
+
+/// Returns the longest namespace for which all objectives of the generated datapack fit into
+/// [MAX_OBJECTIVE_LENGTH] characters.
+pub fn max_namespace_length() -> usize {
+    let longest_suffix = all_objectives()
+        .map(|objective| objective.len() - "-ns-".len())
+        .max()
+        .unwrap_or_default();
+    MAX_OBJECTIVE_LENGTH - longest_suffix
+}
+
+/// Returns all scoreboard objectives of the generated datapack that are too long for Minecraft.
+pub(crate) fn find_too_long_objectives(namespace: &str) -> Vec<String> {
+    all_objectives()
+        .map(|objective| objective.replace("-ns-", namespace))
+        .filter(|objective| objective.len() > MAX_OBJECTIVE_LENGTH)
+        .collect()
+}
+
+/// Returns [VERSION_OBJECTIVE] and [OBJECTIVES].
+fn all_objectives() -> impl Iterator<Item = &'static str> {
+    [VERSION_OBJECTIVE].into_iter().chain(OBJECTIVES)
+}
+
+/// Returns the first pattern that matches the function or [None] if the function is not excluded.
+///
+/// Patterns are matched against the full name of the function (`namespace:path`). A `*` matches
+/// any number of characters except `/`, a `**` also matches `/` and a `?` matches a single
+/// character except `/`.
+pub fn find_exclude_pattern<'p>(
+    exclude: &'p [String],
+    function: &ResourceLocation,
+) -> Option<&'p str> {
+    let function = function.to_string();
+    exclude
+        .iter()
+        .map(String::as_str)
+        .find(|pattern| matches_pattern(pattern.as_bytes(), function.as_bytes()))
+}
+
+fn matches_pattern(pattern: &[u8], string: &[u8]) -> bool {
+    match pattern {
+        [] => string.is_empty(),
+        [b'*', b'*', pattern @ ..] => {
+            (0..=string.len()).any(|index| matches_pattern(pattern, &string[index..]))
+        }
+        [b'*', pattern @ ..] => {
+            let segment_len = string
+                .iter()
+                .position(|c| *c == b'/')
+                .unwrap_or(string.len());
+            (0..=segment_len).any(|index| matches_pattern(pattern, &string[index..]))
+        }
+        [b'?', pattern @ ..] => {
+            matches!(string, [c, string @ ..] if *c != b'/' && matches_pattern(pattern, string))
+        }
+        [p, pattern @ ..] => {
+            matches!(string, [c, string @ ..] if c == p && matches_pattern(pattern, string))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::BTreeSet, fs::read_to_string};
+    use walkdir::WalkDir;
+
+    fn read_templates() -> Vec<String> {
+        let template_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/datapack_template");
+        WalkDir::new(template_dir)
+            .into_iter()
+            .map(|entry| entry.unwrap().into_path())
+            .filter(|path| path.is_file())
+            .map(|path| read_to_string(path).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_templates_only_add_version_objective() {
+        // when:
+        let mut actual = BTreeSet::new();
+        for template in read_templates() {
+            let added_objectives = template
+                .lines()
+                .filter_map(|line| line.strip_prefix("scoreboard objectives add "))
+                .filter_map(|line| line.split(' ').next())
+                .map(String::from);
+            actual.extend(added_objectives);
+        }
+
+        // then:
+        assert_eq!(actual, BTreeSet::from([VERSION_OBJECTIVE.to_string()]));
+    }
+
+    #[test]
+    fn test_templates_only_use_known_objectives_and_tags() {
+        // given:
+        let known = all_objectives().chain(TAGS).collect::<BTreeSet<_>>();
+
+        // when:
+        let mut actual = BTreeSet::new();
+        for template in read_templates() {
+            for (index, _) in template.match_indices("-ns-_") {
+                let name_len = template[index + "-ns-_".len()..]
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or_default();
+                actual.insert(template[index..index + "-ns-_".len() + name_len].to_string());
             }
         }
-        None
+
+        // then:
+        let unknown = actual
+            .iter()
+            .filter(|name| !known.contains(name.as_str()))
+            .collect::<Vec<_>>();
+        assert!(
+            unknown.is_empty(),
+            "Unknown objectives or tags: {:?}",
+            unknown
+        );
+    }
+
+    #[test]
+    fn test_minecraft_version_from_str() {
+        assert_eq!("1.20".parse(), Ok(MinecraftVersion::new(1, 20, 0)));
+        assert_eq!("1.19.4".parse(), Ok(MinecraftVersion::new(1, 19, 4)));
+        assert_eq!("1".parse::<MinecraftVersion>(), Err(()));
+        assert_eq!("1.20.4.1".parse::<MinecraftVersion>(), Err(()));
+        assert_eq!("-version-".parse::<MinecraftVersion>(), Err(()));
+    }
+
+    #[test]
+    fn test_minecraft_version_ordering() {
+        assert!(MinecraftVersion::new(1, 19, 4) < MinecraftVersion::new(1, 20, 0));
+        assert!(MinecraftVersion::new(1, 9, 0) < MinecraftVersion::new(1, 10, 0));
+        assert!(MinecraftVersion::new(1, 20, 0) < MinecraftVersion::new(1, 20, 1));
+        assert_eq!(MinecraftVersion::new(1, 20, 0).to_string(), "1.20");
+        assert_eq!(MinecraftVersion::new(1, 20, 4).to_string(), "1.20.4");
+    }
+
+    #[test]
+    fn test_validate_namespace_length() {
+        let config = |namespace| Config {
+            namespace,
+            shadow: false,
+            exclude: &[],
+            excluded_functions: BTreeSet::new(),
+            skip_invalid_functions: false,
+            additional_datapacks: &[],
+            max_commands: DEFAULT_MAX_COMMANDS,
+            adapter: None,
+        };
+        let max_namespace = "n".repeat(max_namespace_length());
+        let too_long_namespace = "n".repeat(max_namespace_length() + 1);
+
+        assert!(config("mcfd").validate().is_ok());
+        assert!(config(&max_namespace).validate().is_ok());
+        assert!(config(&too_long_namespace).validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_suspend_schedules_with_shadow() {
+        let breakpoints = MultiMap::new();
+        let config = |shadow, suspend_schedules| Config {
+            namespace: "mcfd",
+            shadow,
+            exclude: &[],
+            excluded_functions: BTreeSet::new(),
+            skip_invalid_functions: false,
+            additional_datapacks: &[],
+            max_commands: DEFAULT_MAX_COMMANDS,
+            adapter: Some(AdapterConfig {
+                adapter_listener_name: "test",
+                breakpoints: &breakpoints,
+                suspend_schedules,
+            }),
+        };
+
+        assert!(config(true, SuspendSchedules::All).validate().is_ok());
+        assert!(config(false, SuspendSchedules::CallStackOnly)
+            .validate()
+            .is_ok());
+        assert!(config(true, SuspendSchedules::CallStackOnly)
+            .validate()
+            .is_err());
+        assert!(config(true, SuspendSchedules::None).validate().is_err());
+    }
+
+    #[test]
+    fn test_suspend_schedules_from_str() {
+        for suspend_schedules in [
+            SuspendSchedules::All,
+            SuspendSchedules::CallStackOnly,
+            SuspendSchedules::None,
+        ] {
+            assert_eq!(suspend_schedules.to_string().parse(), Ok(suspend_schedules));
+        }
+        assert_eq!("callstackonly".parse::<SuspendSchedules>(), Err(()));
+    }
+
+    fn is_excluded(pattern: &str, function: &str) -> bool {
+        let (namespace, path) = function.split_once(':').unwrap();
+        let function = ResourceLocation::new(namespace, path);
+        find_exclude_pattern(&[pattern.to_string()], &function).is_some()
+    }
+
+    #[test]
+    fn test_exact_match() {
+        assert!(is_excluded("smithed:tick", "smithed:tick"));
+        assert!(!is_excluded("smithed:tick", "smithed:tick2"));
+        assert!(!is_excluded("smithed:tick", "smithed:load"));
+    }
+
+    #[test]
+    fn test_single_star_does_not_match_slash() {
+        assert!(is_excluded("math:*", "math:sqrt"));
+        assert!(!is_excluded("math:*", "math:impl/sqrt"));
+        assert!(is_excluded("math:*/sqrt", "math:impl/sqrt"));
+        assert!(is_excluded("smithed*:*", "smithed.crafter:tick"));
+    }
+
+    #[test]
+    fn test_double_star_matches_slash() {
+        assert!(is_excluded("math:**", "math:sqrt"));
+        assert!(is_excluded("math:**", "math:impl/sqrt"));
+        assert!(is_excluded("math:**/sqrt", "math:impl/internal/sqrt"));
+        assert!(!is_excluded("math:**", "mathlib:sqrt"));
+    }
+
+    #[test]
+    fn test_question_mark() {
+        assert!(is_excluded("math:sqrt?", "math:sqrt2"));
+        assert!(!is_excluded("math:sqrt?", "math:sqrt"));
+        assert!(!is_excluded("math:impl?sqrt", "math:impl/sqrt"));
+    }
+
+    #[test]
+    fn test_first_matching_pattern_is_returned() {
+        let exclude = [
+            "foo:*".to_string(),
+            "math:**".to_string(),
+            "math:*".to_string(),
+        ];
+        let function = ResourceLocation::new("math", "sqrt");
+        assert_eq!(find_exclude_pattern(&exclude, &function), Some("math:**"));
+    }
+
+    #[test]
+    fn test_config_file_load() {
+        // given:
+        let dir = std::env::temp_dir().join("mcfunction-debugger-config-file-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE_NAME);
+        std::fs::write(
+            &path,
+            "output = \"debug\"\nnamespace = \"cfg\"\nexclude_patterns = [\"math:**\"]\n",
+        )
+        .unwrap();
+
+        // when:
+        let actual = ConfigFile::load(&path).unwrap();
+
+        // then:
+        assert_eq!(
+            actual,
+            ConfigFile {
+                output: Some(dir.join("debug")),
+                namespace: Some("cfg".to_string()),
+                exclude_patterns: Some(vec!["math:**".to_string()]),
+                ..ConfigFile::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_file_rejects_unknown_fields() {
+        // given:
+        let dir = std::env::temp_dir().join("mcfunction-debugger-config-file-unknown-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE_NAME);
+        std::fs::write(&path, "namepsace = \"cfg\"\n").unwrap();
+
+        // when:
+        let actual = ConfigFile::load(&path);
+
+        // then:
+        assert_eq!(actual.unwrap_err().kind(), io::ErrorKind::InvalidData);
     }
 }
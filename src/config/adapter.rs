@@ -17,18 +17,97 @@
 // If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-    parser::command::resource_location::ResourceLocation,
+    parser::command::resource_location::{ResourceLocation, ResourceLocationRef},
     partition::{Position, PositionInLine},
 };
 use multimap::MultiMap;
-use std::{fmt::Display, str::FromStr};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, convert::TryFrom, fmt::Display, io, str::FromStr};
 
+#[derive(Clone, Copy)]
 pub struct AdapterConfig<'l> {
     pub adapter_listener_name: &'l str,
     pub breakpoints: &'l MultiMap<ResourceLocation, LocalBreakpoint>,
+    pub suspend_schedules: SuspendSchedules,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Which scheduled functions are deferred while the debugged function is suspended.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SuspendSchedules {
+    /// All scheduled functions are deferred until the debugged function is resumed.
+    #[default]
+    All,
+    /// Only functions scheduled by the functions on the suspended call stack are deferred, other
+    /// scheduled functions run without being debugged.
+    CallStackOnly,
+    /// No scheduled functions are deferred, they run without being debugged.
+    None,
+}
+impl SuspendSchedules {
+    /// The value of the score `suspend_schedules -ns-_global` that is set when the debug datapack
+    /// is installed.
+    pub(crate) fn score(&self) -> &'static str {
+        match self {
+            SuspendSchedules::All => "0",
+            SuspendSchedules::CallStackOnly => "1",
+            SuspendSchedules::None => "2",
+        }
+    }
+}
+impl FromStr for SuspendSchedules {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(SuspendSchedules::All),
+            "callStackOnly" => Ok(SuspendSchedules::CallStackOnly),
+            "none" => Ok(SuspendSchedules::None),
+            _ => Err(()),
+        }
+    }
+}
+impl Display for SuspendSchedules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuspendSchedules::All => write!(f, "all"),
+            SuspendSchedules::CallStackOnly => write!(f, "callStackOnly"),
+            SuspendSchedules::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Serializes the breakpoints of all functions to a JSON object with the function names as keys, as
+/// read by [breakpoints_from_json] and the hidden `--breakpoints-json` flag of the command line
+/// interface.
+pub fn breakpoints_to_json(
+    breakpoints: &MultiMap<ResourceLocation, LocalBreakpoint>,
+) -> serde_json::Result<String> {
+    let breakpoints = breakpoints
+        .iter_all()
+        .map(|(function, breakpoints)| (function.to_string(), breakpoints))
+        .collect::<BTreeMap<_, _>>();
+    serde_json::to_string(&breakpoints)
+}
+
+pub fn breakpoints_from_json(
+    json: &str,
+) -> io::Result<MultiMap<ResourceLocation, LocalBreakpoint>> {
+    let breakpoints = serde_json::from_str::<BTreeMap<String, Vec<LocalBreakpoint>>>(json)?;
+    let mut result = MultiMap::new();
+    for (function, breakpoints) in breakpoints {
+        let function = ResourceLocationRef::try_from(function.as_str()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid function in breakpoints: {}", function),
+            )
+        })?;
+        result.insert_many(function.to_owned(), breakpoints);
+    }
+    Ok(result)
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct LocalBreakpoint {
     pub kind: BreakpointKind,
     pub position: LocalBreakpointPosition,
@@ -43,17 +122,23 @@ impl LocalBreakpoint {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BreakpointKind {
     Normal,
+    /// Like [BreakpointKind::Normal], but removed by the debug adapter as soon as it stops.
+    Temporary,
     Invalid,
     Continue,
-    Step { condition: String },
+    Step {
+        condition: String,
+    },
 }
 impl BreakpointKind {
     pub fn can_resume(&self) -> bool {
         match self {
             BreakpointKind::Normal => true,
+            BreakpointKind::Temporary => true,
             BreakpointKind::Invalid => false,
             BreakpointKind::Continue { .. } => true,
             BreakpointKind::Step { .. } => true,
@@ -61,7 +146,8 @@ impl BreakpointKind {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct LocalBreakpointPosition {
     pub line_number: usize,
     pub position_in_line: BreakpointPositionInLine,
@@ -75,15 +161,19 @@ impl LocalBreakpointPosition {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BreakpointPositionInLine {
     Breakpoint,
+    /// After the execute prefix of a function call, so once for every executor.
+    AfterExecute,
     AfterFunction,
 }
 impl From<BreakpointPositionInLine> for PositionInLine {
     fn from(value: BreakpointPositionInLine) -> Self {
         match value {
             BreakpointPositionInLine::Breakpoint => PositionInLine::Breakpoint,
+            BreakpointPositionInLine::AfterExecute => PositionInLine::AfterExecute,
             BreakpointPositionInLine::AfterFunction => PositionInLine::AfterFunction,
         }
     }
@@ -94,6 +184,7 @@ impl FromStr for BreakpointPositionInLine {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "breakpoint" => Ok(BreakpointPositionInLine::Breakpoint),
+            "after_execute" => Ok(BreakpointPositionInLine::AfterExecute),
             "after_function" => Ok(BreakpointPositionInLine::AfterFunction),
             _ => Err(()),
         }
@@ -103,6 +194,7 @@ impl Display for BreakpointPositionInLine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             BreakpointPositionInLine::Breakpoint => write!(f, "breakpoint"),
+            BreakpointPositionInLine::AfterExecute => write!(f, "after_execute"),
             BreakpointPositionInLine::AfterFunction => write!(f, "after_function"),
         }
     }
@@ -0,0 +1,70 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use serde::Deserialize;
+use std::{
+    fs::read_to_string,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The name of the file with persistent settings for the command line interface.
+pub const CONFIG_FILE_NAME: &str = ".mcfunction-debugger.toml";
+
+/// Persistent settings for the command line interface. Every field is optional and only used as a
+/// default for the corresponding command line argument.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub namespace: Option<String>,
+    pub shadow: Option<bool>,
+    pub max_commands: Option<usize>,
+    /// How long to wait for further changes before regenerating in watch mode.
+    pub timeout_ms: Option<u64>,
+    pub minecraft_version: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    /// Functions that are not debugged, like functions marked with `# mcfd:skip`.
+    pub excluded_functions: Option<Vec<String>>,
+}
+impl ConfigFile {
+    /// Reads the config file at `path`. Relative paths in the file are resolved against the
+    /// directory containing it.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<ConfigFile> {
+        let path = path.as_ref();
+        let content = read_to_string(path)?;
+        let mut config_file = toml::from_str::<ConfigFile>(&content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to parse {}: {}", path.display(), e),
+            )
+        })?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        config_file.input = config_file.input.map(|input| dir.join(input));
+        config_file.output = config_file.output.map(|output| dir.join(output));
+        Ok(config_file)
+    }
+
+    /// Returns the path of the first config file that exists in `dirs`.
+    pub fn find<'l>(dirs: impl IntoIterator<Item = &'l Path>) -> Option<PathBuf> {
+        dirs.into_iter()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+}
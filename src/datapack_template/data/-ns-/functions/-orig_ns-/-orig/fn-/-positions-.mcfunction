@@ -18,6 +18,7 @@
 
 # Needed when continuing from a breakpoint
 execute as @e[type=area_effect_cloud,tag=-ns-_frozen] run function -ns-:unfreeze_aec
+tag @e[type=area_effect_cloud,tag=-ns-_running] remove -ns-_running
 
 tag @s remove -ns-_tmp
 
@@ -20,10 +20,24 @@ execute unless score @s -ns-_id matches 0 run scoreboard players operation @e[ta
 execute unless score @s -ns-_id matches 0 as @e[tag=!-ns-_context,scores={-ns-_id=0}] run tag @s add -ns-_tmp
 execute unless score @s -ns-_id matches 0 run scoreboard players operation @e[tag=!-ns-_context] -ns-_id += @s -ns-_id
 
-execute unless score @s -ns-_id matches 0 unless entity @e[tag=!-ns-_context,tag=-ns-_tmp] run tellraw @a [{"text":""},{"text":"[Error]","color":"red","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" Selected entity was killed!\n Start a new debugging session with: "},{"text":"/function debug:<your_namespace>/<your_function>","clickEvent":{"action":"suggest_command","value":"/function debug:"},"hoverEvent":{"action":"show_text","contents":"Click for suggestions"},"color":"aqua"}]
-execute unless score @s -ns-_id matches 0 unless entity @e[tag=!-ns-_context,tag=-ns-_tmp] run function -ns-:abort_session
-execute if score @s -ns-_id matches 0 at @s run function -ns-:-orig_ns-/-orig/fn-/-positions-
-execute if score current -ns-_anchor matches 0 at @s as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored feet run function -ns-:-orig_ns-/-orig/fn-/-positions-
-execute if score current -ns-_anchor matches 1 at @s as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored eyes run function -ns-:-orig_ns-/-orig/fn-/-positions-
+# The iteration of a killed executor is skipped
+execute unless score @s -ns-_id matches 0 unless entity @e[tag=!-ns-_context,tag=-ns-_tmp] run tag @s add -ns-_skip
+scoreboard players operation current -ns-_anchor = @s -ns-_anchor
+scoreboard players operation current -ns-_dim = @s -ns-_dim
+execute unless score @s -ns-_dim matches 1.. if score @s -ns-_id matches 0 at @s run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute unless score @s -ns-_dim matches 1.. if score @s -ns-_anchor matches 0 at @s as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored feet run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute unless score @s -ns-_dim matches 1.. if score @s -ns-_anchor matches 1 at @s as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored eyes run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute if score @s -ns-_dim matches 1 if score @s -ns-_id matches 0 at @s in minecraft:overworld run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute if score @s -ns-_dim matches 1 if score @s -ns-_anchor matches 0 at @s in minecraft:overworld as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored feet run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute if score @s -ns-_dim matches 1 if score @s -ns-_anchor matches 1 at @s in minecraft:overworld as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored eyes run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute if score @s -ns-_dim matches 2 if score @s -ns-_id matches 0 at @s in minecraft:the_nether run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute if score @s -ns-_dim matches 2 if score @s -ns-_anchor matches 0 at @s in minecraft:the_nether as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored feet run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute if score @s -ns-_dim matches 2 if score @s -ns-_anchor matches 1 at @s in minecraft:the_nether as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored eyes run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute if score @s -ns-_dim matches 3 if score @s -ns-_id matches 0 at @s in minecraft:the_end run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute if score @s -ns-_dim matches 3 if score @s -ns-_anchor matches 0 at @s in minecraft:the_end as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored feet run function -ns-:-orig_ns-/-orig/fn-/-positions-
+execute if score @s -ns-_dim matches 3 if score @s -ns-_anchor matches 1 at @s in minecraft:the_end as @e[tag=!-ns-_context,tag=-ns-_tmp] anchored eyes run function -ns-:-orig_ns-/-orig/fn-/-positions-
 
+execute if entity @s[tag=-ns-_skip] run tag @s remove -ns-_active
+execute if entity @s[tag=-ns-_skip] run function -ns-:-orig_ns-/-orig/fn-/next_iteration_or_return
+# -return_early-
 execute if entity @s[tag=!-ns-_active] run kill @s
@@ -18,13 +18,23 @@
 
 # Select next context
 execute as @e[type=area_effect_cloud,tag=-ns-_context,tag=-ns-_active] if score @s -ns-_depth = current -ns-_depth run tag @s add -ns-_tmp
-execute as @e[type=area_effect_cloud,tag=-ns-_tmp,limit=1] run tag @s add -ns-_current
+scoreboard players set min -ns-_iter 2147483647
+scoreboard players operation min -ns-_iter < @e[type=area_effect_cloud,tag=-ns-_tmp] -ns-_iter
+execute as @e[type=area_effect_cloud,tag=-ns-_tmp] if score @s -ns-_iter = min -ns-_iter run tag @s add -ns-_current
 
 # If there is no entity with -ns-_tmp, we return.
 execute unless entity @e[type=area_effect_cloud,tag=-ns-_tmp] run function -ns-:-orig_ns-/-orig/fn-/return_or_exit
 
+scoreboard players reset selected_context -ns-_global
+execute if entity @e[type=area_effect_cloud,tag=-ns-_tmp] run scoreboard players set selected_context -ns-_global 1
+
 tag @e[type=area_effect_cloud] remove -ns-_tmp
 
+# Suspend at a breakpoint after the execute prefix of the function call
+scoreboard players operation caller -ns-_depth = current -ns-_depth
+scoreboard players remove caller -ns-_depth 1
+execute if score selected_context -ns-_global matches 1 as @e[type=area_effect_cloud,tag=-ns-_context,tag=-ns-_active,tag=-ns-_current] if score @s -ns-_depth = current -ns-_depth at @s as @e[type=area_effect_cloud,tag=-ns-_function_call,tag=-ns-_active] if score @s -ns-_depth = caller -ns-_depth run function -ns-:suspend_after_execute
+
 # If we returned above, the program is now either
 # 1. suspended at a breakpoint or
 # 2. terminated, in which case there is no entity with tag=-ns-_current
@@ -17,6 +17,7 @@
 # If not, see <http://www.gnu.org/licenses/>.
 
 scoreboard players remove current -ns-_depth 1
+execute as @e[type=area_effect_cloud,tag=-ns-_schedule] if score @s -ns-_depth > current -ns-_depth run scoreboard players reset @s -ns-_depth
 scoreboard players reset found_function_call -ns-_global
 execute as @e[type=area_effect_cloud,tag=-ns-_function_call,tag=-ns-_active] if score @s -ns-_depth = current -ns-_depth run scoreboard players set found_function_call -ns-_global 1
 execute unless score found_function_call -ns-_global matches 1 run tellraw @a [{"text":""},{"text":"[Error]","color":"red","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" Debugger function call entity was killed!\n Start a new debugging session with: "},{"text":"/function debug:<your_namespace>/<your_function>","clickEvent":{"action":"suggest_command","value":"/function debug:"},"hoverEvent":{"action":"show_text","contents":"Click for suggestions"},"color":"aqua"}]
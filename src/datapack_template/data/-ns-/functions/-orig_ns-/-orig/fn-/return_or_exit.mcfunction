@@ -16,5 +16,6 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
+execute if score current -ns-_depth matches 0 run scoreboard players reset @e[type=area_effect_cloud,tag=-ns-_schedule] -ns-_depth
 execute if score current -ns-_depth matches 0 if score -fn_score_holder- -ns-_valid matches 1 unless entity @e[type=area_effect_cloud,tag=-ns-_schedule] run function -ns-:on_session_exit_successful
 execute unless score current -ns-_depth matches 0 run function -ns-:-orig_ns-/-orig/fn-/return
@@ -16,5 +16,10 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
+# Schedules that keep running while suspended run the function without debugging it, this is done
+# first, because starting the function below may suspend
+execute if score breakpoint -ns-_global matches 1 store success score schedule_success -ns-_global run kill @e[type=area_effect_cloud,tag=-ns-+schedule+-orig_ns-+-orig+fn-,tag=-ns-_running,nbt={Age: -1}]
+execute if score breakpoint -ns-_global matches 1 if score schedule_success -ns-_global matches 1 run function -orig_ns-:-orig/fn-
+
 execute unless score breakpoint -ns-_global matches 1 store success score schedule_success -ns-_global run kill @e[type=area_effect_cloud,tag=-ns-+schedule+-orig_ns-+-orig+fn-,nbt={Age: -1}]
 execute unless score breakpoint -ns-_global matches 1 if score schedule_success -ns-_global matches 1 run function -ns-:-orig_ns-/-orig/fn-/start
@@ -16,5 +16,10 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
+scoreboard players set chain_running -ns-_global 1
+
 execute unless score -fn_score_holder- -ns-_valid matches 1 run tellraw @a [{"text":""},{"text":"[Error]","color":"red","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" Cannot debug -orig_ns-:-orig/fn-, because it contains an invalid command!"}]
 execute if score -fn_score_holder- -ns-_valid matches 1 run function -ns-:-orig_ns-/-orig/fn-/start_valid
+
+# If Minecraft aborts the chain of commands, this is not executed
+scoreboard players reset chain_running -ns-_global
@@ -20,6 +20,8 @@ tellraw @a [{"text":""},{"text":"[Info]","color":"blue","hoverEvent":{"action":"
 
 scoreboard players set current -ns-_depth 0
 scoreboard players set current -ns-_anchor 0
+scoreboard players set current -ns-_dim 0
+scoreboard players reset next -ns-_iter
 
 function -ns-:select_entity
 function -ns-:-orig_ns-/-orig/fn-/next_iteration_or_return
@@ -18,7 +18,8 @@
 
 summon area_effect_cloud ~ ~ ~ {Age: -2147483648, Duration: -1, WaitTime: -2147483648, Tags: [-ns-, -ns-_breakpoint, -ns-+-orig_ns-+-orig+fn-+-position-], CustomName: '{"text":"-orig_ns-:-orig/fn-:-line_number--optional_column-"}'}
 teleport @e[type=area_effect_cloud,tag=-ns-_breakpoint] ~ ~ ~ ~ ~
-execute as @e[type=area_effect_cloud,tag=!-ns-_frozen] run function -ns-:freeze_aec
+function -ns-:select_running_schedules
+execute as @e[type=area_effect_cloud,tag=!-ns-_frozen,tag=!-ns-_running] run function -ns-:freeze_aec
 
 function -ns-:skipped_functions_warning
 # -if_not_adapter-
@@ -26,6 +27,7 @@ tellraw @a [{"text":""},{"text":"[Info]","color":"blue","hoverEvent":{"action":"
 
 scoreboard players reset * -ns-_scores
 function -ns-:update_scores
+function -ns-:update_scores_dynamic
 
 # -minect_log-
 function minect:enable_logging
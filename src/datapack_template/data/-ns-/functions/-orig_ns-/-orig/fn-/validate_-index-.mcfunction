@@ -16,4 +16,4 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
-scoreboard objectives remove -ns-_id
+# -commands-
@@ -17,25 +17,22 @@
 # If not, see <http://www.gnu.org/licenses/>.
 
 scoreboard players set version -ns-_version 1
-scoreboard objectives add -ns-_Age dummy
-scoreboard objectives add -ns-_Duration dummy
-scoreboard objectives add -ns-_WaitTime dummy
-scoreboard objectives add -ns-_anchor dummy
-scoreboard objectives add -ns-_depth dummy
-scoreboard objectives add -ns-_global dummy
-scoreboard objectives add -ns-_skipped dummy
-scoreboard objectives add -ns-_tmp dummy
-scoreboard objectives add -ns-_valid dummy
+# -add_objectives-
 
-scoreboard objectives add -ns-_constant dummy
 scoreboard players set 1 -ns-_constant 1
 scoreboard players set 88 -ns-_constant 88
 
-scoreboard objectives add -ns-_scores dummy "@s scores"
+scoreboard players set session_ticks -ns-_global 0
+scoreboard players set suspended_ticks -ns-_global 0
+# Which scheduled functions are deferred while suspended: 0 all, 1 only those scheduled by the call
+# stack, 2 none
+scoreboard players set suspend_schedules -ns-_global -suspend_schedules-
+
+scoreboard players set installed -ns-_shadow 1
+
+scoreboard objectives modify -ns-_scores displayname "@s scores"
 # -if_not_adapter-
 function debug:show_scores
 
-function -ns-:id/install
-
 # -if_not_adapter-
 tellraw @a [{"text":""},{"text":"[Info]","color":"blue","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" Datapack '-datapack-' was installed.\n Start a new debugging session with: "},{"text":"/function debug:<your_namespace>/<your_function>","clickEvent":{"action":"suggest_command","value":"/function debug:"},"hoverEvent":{"action":"show_text","contents":"Click for suggestions"},"color":"aqua"}]
@@ -20,3 +20,12 @@ function debug:install
 
 scoreboard players reset * -ns-_valid
 function -ns-:validate_all_functions
+
+# Reports the batch of injected commands that triggered this reload to the debug adapter, because
+# commands after a reload run before Minecraft finished reloading
+# -minect_log-
+function minect:enable_logging
+# -minect_log-
+scoreboard players add reload_batch -ns-_global 0
+# -minect_log-
+function minect:reset_logging
@@ -16,6 +16,11 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
+scoreboard players set chain_running -ns-_global 1
+
 execute unless entity @e[type=area_effect_cloud,tag=-ns-_breakpoint] run tellraw @a [{"text":""},{"text":"[Error]","color":"red","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" Could not find breakpoint entity!\n Start a new debugging session with: "},{"text":"/function debug:<your_namespace>/<your_function>","clickEvent":{"action":"suggest_command","value":"/function debug:"},"hoverEvent":{"action":"show_text","contents":"Click for suggestions"},"color":"aqua"}]
 execute unless entity @e[type=area_effect_cloud,tag=-ns-_breakpoint] run function -ns-:abort_session
 execute as @e[type=area_effect_cloud,tag=-ns-_breakpoint] run function -ns-:resume_self
+
+# If Minecraft aborts the chain of commands, this is not executed
+scoreboard players reset chain_running -ns-_global
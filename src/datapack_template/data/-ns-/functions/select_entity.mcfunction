@@ -21,4 +21,9 @@ summon area_effect_cloud ~ ~ ~ {Duration: 2147483647, Tags: [-ns-_new, -ns-, -ns
 teleport @e[type=area_effect_cloud,tag=-ns-_new] ~ ~ ~ ~ ~
 scoreboard players operation @e[type=area_effect_cloud,tag=-ns-_new] -ns-_id = @s -ns-_id
 scoreboard players operation @e[type=area_effect_cloud,tag=-ns-_new] -ns-_depth = current -ns-_depth
+scoreboard players operation @e[type=area_effect_cloud,tag=-ns-_new] -ns-_anchor = current -ns-_anchor
+scoreboard players operation @e[type=area_effect_cloud,tag=-ns-_new] -ns-_dim = current -ns-_dim
+# Contexts are iterated in the order of the selector, even if a breakpoint suspends an iteration
+scoreboard players add next -ns-_iter 1
+scoreboard players operation @e[type=area_effect_cloud,tag=-ns-_new] -ns-_iter = next -ns-_iter
 tag @e[type=area_effect_cloud,tag=-ns-_new] remove -ns-_new
@@ -0,0 +1,22 @@
+# McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+# Minecraft mods.
+#
+# © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+#
+# This file is part of McFunction-Debugger.
+#
+# McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+# the GNU General Public License as published by the Free Software Foundation, either version 3 of
+# the License, or (at your option) any later version.
+#
+# McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+# without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+# GNU General Public License for more details.
+#
+# You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+# If not, see <http://www.gnu.org/licenses/>.
+
+# Tags the schedules that keep running while suspended, see install. A schedule has a depth while
+# the function that scheduled it is on the call stack.
+execute if score suspend_schedules -ns-_global matches 1 as @e[type=area_effect_cloud,tag=-ns-_schedule] unless score @s -ns-_depth matches -2147483648.. run tag @s add -ns-_running
+execute if score suspend_schedules -ns-_global matches 2 run tag @e[type=area_effect_cloud,tag=-ns-_schedule] add -ns-_running
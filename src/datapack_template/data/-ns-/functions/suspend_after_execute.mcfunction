@@ -16,4 +16,5 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
-scoreboard objectives add -ns-_id dummy
+# Executed as the function call entity at the position of the current context
+# -suspend_cases-
@@ -16,6 +16,9 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
+# The last chain of commands was aborted, most likely because it exceeded maxCommandChainLength
+execute if score chain_running -ns-_global matches 1 run function -ns-:on_chain_truncated
+
 execute if score breakpoint -ns-_global matches 1 at @e[type=area_effect_cloud,tag=-ns-_breakpoint] run function -ns-:animate_context
 
 execute if score tick_resume -ns-_global matches 1 run function -ns-:resume_immediately
@@ -23,6 +26,11 @@ scoreboard players reset tick_resume -ns-_global
 
 execute as @e[type=area_effect_cloud,tag=-ns-_schedule] run function -ns-:schedule
 
+# Ticks since the launch and how many of them were spent suspended, they stop at the maximum score
+# instead of overflowing
+execute if score session_ticks -ns-_global matches ..2147483646 run scoreboard players add session_ticks -ns-_global 1
+execute if score breakpoint -ns-_global matches 1 if score suspended_ticks -ns-_global matches ..2147483646 run scoreboard players add suspended_ticks -ns-_global 1
+
 schedule function -ns-:tick_start 1t
 
 # This area_effect_cloud will die next tick when Minecraft increments it's age after running schedules and command blocks, even if someone calls decrement_age
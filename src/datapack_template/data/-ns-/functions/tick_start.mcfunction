@@ -16,4 +16,4 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
-execute if score breakpoint -ns-_global matches 1 as @e[type=area_effect_cloud] run function -ns-:decrement_age
+execute if score breakpoint -ns-_global matches 1 as @e[type=area_effect_cloud,tag=!-ns-_running] run function -ns-:decrement_age
@@ -16,20 +16,8 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
-scoreboard objectives remove -ns-_Age
-scoreboard objectives remove -ns-_Duration
-scoreboard objectives remove -ns-_WaitTime
-scoreboard objectives remove -ns-_anchor
-scoreboard objectives remove -ns-_depth
-scoreboard objectives remove -ns-_global
-scoreboard objectives remove -ns-_scores
-scoreboard objectives remove -ns-_skipped
-scoreboard objectives remove -ns-_tmp
-scoreboard objectives remove -ns-_valid
+# -remove_objectives-
 
-scoreboard objectives remove -ns-_constant
-
-function -ns-:id/uninstall
 kill @e[tag=-ns-]
 
 # -if_not_adapter-
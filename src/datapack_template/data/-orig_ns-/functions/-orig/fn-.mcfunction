@@ -16,4 +16,4 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
-function debug:-orig_ns-/-orig/fn-
+execute if score installed -ns-_shadow matches 1 run function debug:-orig_ns-/-orig/fn-
@@ -23,7 +23,9 @@ execute if score -fn_score_holder- -ns-_valid matches 1 run tag @e[type=area_eff
 
 execute if score -fn_score_holder- -ns-_valid matches 1 run scoreboard players add current -ns-_depth 1
 # -debug_anchor-
+# -debug_dimension-
 
+execute if score -fn_score_holder- -ns-_valid matches 1 run scoreboard players reset next -ns-_iter
 execute if score -fn_score_holder- -ns-_valid matches 1 run execute run function -ns-:select_entity
 execute if score -fn_score_holder- -ns-_valid matches 1 run function -ns-:-call_ns-/-call/fn-/next_iteration_or_return
 
@@ -16,5 +16,8 @@
 # You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 # If not, see <http://www.gnu.org/licenses/>.
 
-execute run summon area_effect_cloud ~ ~ ~ {Age: --ticks-, Duration: -ticks-, WaitTime: --ticks-, Tags: [-ns-, -ns-_schedule, -ns-+schedule+-schedule_ns-+-schedule+fn-]}
+execute run summon area_effect_cloud ~ ~ ~ {Age: --ticks-, Duration: -ticks-, WaitTime: --ticks-, Tags: [-ns-, -ns-_schedule, -ns-_new, -ns-+schedule+-schedule_ns-+-schedule+fn-]}
 execute if entity @e[type=area_effect_cloud,tag=-ns-_before_age_increment] as @e[type=area_effect_cloud,tag=-ns-+schedule+-schedule_ns-+-schedule+fn-,nbt={Age: --ticks-},limit=1] run function -ns-:decrement_age
+# The depth of the scheduling function is reset when it returns, see select_running_schedules
+scoreboard players operation @e[type=area_effect_cloud,tag=-ns-_schedule,tag=-ns-_new] -ns-_depth = current -ns-_depth
+tag @e[type=area_effect_cloud,tag=-ns-_schedule,tag=-ns-_new] remove -ns-_new
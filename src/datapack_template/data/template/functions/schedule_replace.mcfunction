@@ -22,5 +22,8 @@
 scoreboard players set schedule_success -ns-_global 0
 execute run scoreboard players set schedule_success -ns-_global 1
 execute if score schedule_success -ns-_global matches 1 run kill @e[type=area_effect_cloud,tag=-ns-+schedule+-schedule_ns-+-schedule+fn-]
-execute if score schedule_success -ns-_global matches 1 run summon area_effect_cloud ~ ~ ~ {Age: --ticks-, Duration: -ticks-, WaitTime: --ticks-, Tags: [-ns-, -ns-_schedule, -ns-+schedule+-schedule_ns-+-schedule+fn-]}
+execute if score schedule_success -ns-_global matches 1 run summon area_effect_cloud ~ ~ ~ {Age: --ticks-, Duration: -ticks-, WaitTime: --ticks-, Tags: [-ns-, -ns-_schedule, -ns-_new, -ns-+schedule+-schedule_ns-+-schedule+fn-]}
 execute if entity @e[type=area_effect_cloud,tag=-ns-_before_age_increment] as @e[type=area_effect_cloud,tag=-ns-+schedule+-schedule_ns-+-schedule+fn-,nbt={Age: --ticks-},limit=1] run function -ns-:decrement_age
+# The depth of the scheduling function is reset when it returns, see select_running_schedules
+scoreboard players operation @e[type=area_effect_cloud,tag=-ns-_schedule,tag=-ns-_new] -ns-_depth = current -ns-_depth
+tag @e[type=area_effect_cloud,tag=-ns-_schedule,tag=-ns-_new] remove -ns-_new
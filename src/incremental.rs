@@ -0,0 +1,351 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    calls_debugged_function,
+    config::{adapter::BreakpointPositionInLine, Config},
+    defers_load, get_fn_score_holder,
+    parser::{
+        command::resource_location::{ResourceLocation, ResourceLocationRef},
+        Line,
+    },
+    sink::FileSink,
+};
+use multimap::MultiMap;
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap},
+    convert::TryFrom,
+    hash::{Hash, Hasher},
+    io,
+    iter::repeat,
+    path::Path,
+};
+use tokio::fs::read_to_string;
+
+const STATE_FILE: &str = "incremental_state.txt";
+
+/// Global templates that only depend on the config.
+pub(crate) const TEMPLATES: &str = "templates";
+/// Global templates that depend on the names of all functions.
+pub(crate) const FUNCTIONS: &str = "functions";
+/// Global templates that depend on all breakpoints.
+pub(crate) const RESUME_SELF: &str = "resume_self";
+/// Global templates that depend on all breakpoints after the execute prefix of a function call.
+pub(crate) const SUSPEND_AFTER_EXECUTE: &str = "suspend_after_execute";
+/// Global templates that depend on all objectives.
+pub(crate) const SCORES: &str = "scores";
+/// Global templates that depend on all function calls.
+pub(crate) const SHOW_SKIPPED: &str = "show_skipped";
+/// Global templates that depend on whether the functions of `#minecraft:load` are deferred.
+pub(crate) const LOAD: &str = "load";
+
+/// Hashes of all inputs that were used to generate a debug datapack. By comparing the state of the
+/// last generation with the current one, only the files with changed inputs need to be generated
+/// again.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct IncrementalState {
+    functions: BTreeMap<ResourceLocation, u64>,
+    globals: BTreeMap<String, u64>,
+}
+
+/// The difference between two [IncrementalState]s.
+#[derive(Debug, Default)]
+pub(crate) struct Changes {
+    pub functions: BTreeSet<ResourceLocation>,
+    pub removed_functions: BTreeSet<ResourceLocation>,
+    pub globals: BTreeSet<String>,
+}
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.removed_functions.is_empty() && self.globals.is_empty()
+    }
+}
+
+impl IncrementalState {
+    pub fn new(
+        fn_ids: &HashMap<&ResourceLocation, usize>,
+        fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+        call_tree: &MultiMap<&ResourceLocation, (&ResourceLocation, &usize)>,
+        output_name: &str,
+        pack_format: u64,
+        config: &Config<'_>,
+    ) -> IncrementalState {
+        let config_hash = hash(|hasher| {
+            env!("CARGO_PKG_VERSION").hash(hasher);
+            output_name.hash(hasher);
+            pack_format.hash(hasher);
+            config.namespace.hash(hasher);
+            config.shadow.hash(hasher);
+            config.exclude.hash(hasher);
+            config.excluded_functions.hash(hasher);
+            config.max_commands.hash(hasher);
+            config
+                .adapter
+                .as_ref()
+                .map(|config| (config.adapter_listener_name, config.suspend_schedules))
+                .hash(hasher);
+        });
+
+        let functions = fn_contents
+            .iter()
+            .map(|(&name, lines)| {
+                let function_hash = hash(|hasher| {
+                    config_hash.hash(hasher);
+                    name.hash(hasher);
+                    get_fn_score_holder(name, fn_ids).hash(hasher);
+                    for (_line_number, line, parsed) in lines {
+                        line.hash(hasher);
+                        if let Line::FunctionCall { name: callee, .. } = parsed {
+                            get_fn_score_holder(callee, fn_ids).hash(hasher);
+                            fn_ids.contains_key(callee).hash(hasher);
+                        }
+                    }
+                    if let Some(config) = config.adapter.as_ref() {
+                        config.breakpoints.get_vec(name).hash(hasher);
+                    }
+                    // return_self.mcfunction depends on the callers
+                    let mut callers = call_tree.get_vec(name).cloned().unwrap_or_default();
+                    callers.sort();
+                    callers.hash(hasher);
+                });
+                (name.clone(), function_hash)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let sorted_fn_contents = fn_contents.iter().collect::<BTreeMap<_, _>>();
+
+        let functions_hash = hash(|hasher| {
+            config_hash.hash(hasher);
+            functions.keys().for_each(|name| name.hash(hasher));
+        });
+
+        let resume_self_hash = hash(|hasher| {
+            config_hash.hash(hasher);
+            for (name, lines) in &sorted_fn_contents {
+                name.hash(hasher);
+                lines
+                    .iter()
+                    .filter(|(_, _, line)| matches!(line, Line::Breakpoint))
+                    .for_each(|(line_number, _, _)| line_number.hash(hasher));
+            }
+            if let Some(config) = config.adapter.as_ref() {
+                let breakpoints = config.breakpoints.iter_all().collect::<BTreeMap<_, _>>();
+                for (name, breakpoints) in breakpoints {
+                    name.hash(hasher);
+                    breakpoints
+                        .iter()
+                        .filter(|breakpoint| breakpoint.can_resume())
+                        .for_each(|breakpoint| {
+                            breakpoint.get_position().to_string().hash(hasher);
+                            if breakpoint.position.position_in_line
+                                == BreakpointPositionInLine::AfterExecute
+                            {
+                                let line_number = breakpoint.position.line_number;
+                                calls_debugged_function(fn_contents, name, line_number)
+                                    .hash(hasher);
+                            }
+                        });
+                }
+            }
+        });
+
+        let suspend_after_execute_hash = hash(|hasher| {
+            config_hash.hash(hasher);
+            if let Some(config) = config.adapter.as_ref() {
+                let mut breakpoints = config
+                    .breakpoints
+                    .iter_all()
+                    .flat_map(|(name, breakpoints)| repeat(name).zip(breakpoints))
+                    .filter(|(_, breakpoint)| {
+                        breakpoint.position.position_in_line
+                            == BreakpointPositionInLine::AfterExecute
+                    })
+                    .collect::<Vec<_>>();
+                breakpoints
+                    .sort_by_key(|(name, breakpoint)| (*name, breakpoint.position.line_number));
+                for (name, breakpoint) in breakpoints {
+                    name.hash(hasher);
+                    breakpoint.hash(hasher);
+                    calls_debugged_function(fn_contents, name, breakpoint.position.line_number)
+                        .hash(hasher);
+                }
+            }
+        });
+
+        let scores_hash = hash(|hasher| {
+            config_hash.hash(hasher);
+            fn_contents
+                .values()
+                .flatten()
+                .filter_map(|(_, _, line)| line.objectives())
+                .flatten()
+                .collect::<BTreeSet<_>>()
+                .hash(hasher);
+        });
+
+        let show_skipped_hash = hash(|hasher| {
+            functions_hash.hash(hasher);
+            fn_contents
+                .values()
+                .flatten()
+                .filter_map(|(_, _, line)| match line {
+                    Line::FunctionCall { name, .. } => Some(name),
+                    _ => None,
+                })
+                .collect::<BTreeSet<_>>()
+                .hash(hasher);
+        });
+
+        let load_hash = hash(|hasher| {
+            config_hash.hash(hasher);
+            defers_load(fn_contents, config).hash(hasher);
+        });
+
+        let globals = BTreeMap::from([
+            (TEMPLATES.to_string(), config_hash),
+            (FUNCTIONS.to_string(), functions_hash),
+            (RESUME_SELF.to_string(), resume_self_hash),
+            (
+                SUSPEND_AFTER_EXECUTE.to_string(),
+                suspend_after_execute_hash,
+            ),
+            (SCORES.to_string(), scores_hash),
+            (SHOW_SKIPPED.to_string(), show_skipped_hash),
+            (LOAD.to_string(), load_hash),
+        ]);
+
+        IncrementalState { functions, globals }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.globals.is_empty()
+    }
+
+    pub fn changes_since(&self, old: &IncrementalState) -> Changes {
+        let functions = self
+            .functions
+            .iter()
+            .filter(|(name, hash)| old.functions.get(*name) != Some(*hash))
+            .map(|(name, _hash)| name.clone())
+            .collect();
+        let removed_functions = old
+            .functions
+            .keys()
+            .filter(|name| !self.functions.contains_key(*name))
+            .cloned()
+            .collect();
+        let globals = self
+            .globals
+            .iter()
+            .filter(|(key, hash)| old.globals.get(*key) != Some(*hash))
+            .map(|(key, _hash)| key.clone())
+            .collect();
+        Changes {
+            functions,
+            removed_functions,
+            globals,
+        }
+    }
+
+    /// Returns whether the directory contains the state of a generation, which means that it is a
+    /// debug datapack.
+    pub fn exists(output_path: impl AsRef<Path>) -> bool {
+        output_path.as_ref().join(STATE_FILE).is_file()
+    }
+
+    /// Loads the state of the last generation. If there is no valid state, an empty state is
+    /// returned.
+    pub async fn load(output_path: impl AsRef<Path>) -> IncrementalState {
+        let path = output_path.as_ref().join(STATE_FILE);
+        read_to_string(path)
+            .await
+            .ok()
+            .and_then(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    fn parse(content: &str) -> Option<IncrementalState> {
+        let mut state = IncrementalState::default();
+        for line in content.lines() {
+            let mut parts = line.splitn(3, ' ');
+            let kind = parts.next()?;
+            let hash = u64::from_str_radix(parts.next()?, 16).ok()?;
+            let key = parts.next()?;
+            match kind {
+                "function" => {
+                    let name = ResourceLocationRef::try_from(key).ok()?.to_owned();
+                    state.functions.insert(name, hash);
+                }
+                "global" => {
+                    state.globals.insert(key.to_string(), hash);
+                }
+                _ => return None,
+            }
+        }
+        Some(state)
+    }
+
+    pub async fn save(&self, output_path: impl AsRef<Path>, sink: &dyn FileSink) -> io::Result<()> {
+        let path = output_path.as_ref().join(STATE_FILE);
+        let content = self
+            .functions
+            .iter()
+            .map(|(name, hash)| format!("function {:016x} {}", hash, name))
+            .chain(
+                self.globals
+                    .iter()
+                    .map(|(key, hash)| format!("global {:016x} {}", hash, key)),
+            )
+            .collect::<Vec<_>>()
+            .join("\n");
+        sink.write(path, content).await
+    }
+
+    /// Deletes the state, so an interrupted generation results in a full generation next time.
+    pub async fn delete(output_path: impl AsRef<Path>, sink: &dyn FileSink) -> io::Result<()> {
+        sink.remove_file(output_path.as_ref().join(STATE_FILE))
+            .await
+    }
+}
+
+fn hash(f: impl FnOnce(&mut DefaultHasher)) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    f(&mut hasher);
+    hasher.finish()
+}
+
+/// Writes a generated file unless it already has the given contents. Skipping unchanged files
+/// preserves their modification time, so Minecraft and file watchers don't see spurious changes.
+pub(crate) async fn write_if_changed(
+    path: impl AsRef<Path>,
+    contents: impl AsRef<[u8]>,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let contents = contents.as_ref();
+    if let Ok(existing) = tokio::fs::read(path).await {
+        if existing == contents {
+            return Ok(());
+        }
+        // The file may be a hard link into the previous generation, see crate::staging
+        tokio::fs::remove_file(path).await?;
+    }
+    tokio::fs::write(path, contents).await
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,656 @@
+use super::*;
+use crate::{
+    config::{
+        adapter::{
+            AdapterConfig, BreakpointKind, BreakpointPositionInLine, LocalBreakpoint,
+            LocalBreakpointPosition, SuspendSchedules,
+        },
+        DEFAULT_MAX_COMMANDS,
+    },
+    generate_debug_datapack, generate_debug_datapack_with_sink,
+    parser::command::CommandParser,
+    sink::{FileSink, FsSink},
+};
+use async_trait::async_trait;
+use std::{
+    fs::{
+        create_dir_all, metadata, read_dir, read_to_string, remove_dir_all, remove_file, write,
+        File,
+    },
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+use walkdir::WalkDir;
+
+/// Writes to the file system like [FsSink] and records the files whose content is changed by a
+/// write.
+#[derive(Default)]
+struct RecordingSink {
+    writes: Mutex<Vec<PathBuf>>,
+    staged_path: Mutex<Option<PathBuf>>,
+}
+impl RecordingSink {
+    /// Returns the paths of the changed files relative to the output path.
+    fn into_writes(self) -> BTreeSet<String> {
+        let staged_path = self.staged_path.into_inner().unwrap();
+        self.writes
+            .into_inner()
+            .unwrap()
+            .iter()
+            .map(|path| {
+                let staged_path = staged_path.as_ref().unwrap();
+                let relative_path = path.strip_prefix(staged_path).unwrap();
+                relative_path.to_string_lossy().replace('\\', "/")
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl FileSink for RecordingSink {
+    async fn create_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        FsSink.create_dir_all(path).await
+    }
+
+    async fn write(&self, path: PathBuf, content: String) -> io::Result<()> {
+        if tokio::fs::read(&path).await.ok().as_deref() != Some(content.as_bytes()) {
+            self.writes.lock().unwrap().push(path.clone());
+        }
+        FsSink.write(path, content).await
+    }
+
+    async fn remove_file(&self, path: PathBuf) -> io::Result<()> {
+        FsSink.remove_file(path).await
+    }
+
+    async fn remove_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        FsSink.remove_dir_all(path).await
+    }
+
+    async fn stage(&self, output_path: PathBuf) -> io::Result<PathBuf> {
+        let staged_path = FsSink.stage(output_path).await?;
+        *self.staged_path.lock().unwrap() = Some(staged_path.clone());
+        Ok(staged_path)
+    }
+
+    async fn commit(&self, staged_path: PathBuf, output_path: PathBuf) -> io::Result<()> {
+        FsSink.commit(staged_path, output_path).await
+    }
+}
+
+struct TestDatapack {
+    input_path: PathBuf,
+    output_path: PathBuf,
+}
+impl TestDatapack {
+    fn new(name: &str) -> TestDatapack {
+        let dir = std::env::temp_dir()
+            .join("mcfunction-debugger-incremental-test")
+            .join(name);
+        let _ = remove_dir_all(&dir);
+        let input_path = dir.join("input");
+        create_dir_all(&input_path).unwrap();
+        write(
+            input_path.join("pack.mcmeta"),
+            r#"{"pack":{"pack_format":7,"description":""}}"#,
+        )
+        .unwrap();
+        TestDatapack {
+            input_path,
+            output_path: dir.join("output"),
+        }
+    }
+
+    fn function_path(&self, name: &str) -> PathBuf {
+        self.input_path
+            .join("data/test/functions")
+            .join(name)
+            .with_extension("mcfunction")
+    }
+
+    fn write_function(&self, name: &str, content: &str) {
+        let path = self.function_path(name);
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, content).unwrap();
+    }
+
+    /// Generates the debug datapack and returns the files that were changed.
+    async fn generate(&self, config: &Config<'_>) -> BTreeSet<String> {
+        let parser = CommandParser::default().unwrap();
+        let sink = RecordingSink::default();
+        generate_debug_datapack_with_sink(
+            &self.input_path,
+            &self.output_path,
+            &parser,
+            config,
+            &sink,
+        )
+        .await
+        .unwrap();
+        sink.into_writes()
+    }
+
+    fn read_output(&self) -> BTreeMap<PathBuf, String> {
+        WalkDir::new(&self.output_path)
+            .into_iter()
+            .map(|entry| entry.unwrap())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| {
+                let path = entry.path().strip_prefix(&self.output_path).unwrap();
+                (path.to_path_buf(), read_to_string(entry.path()).unwrap())
+            })
+            .collect()
+    }
+
+    /// Returns the directories that generations left next to the output path.
+    fn staging_dirs(&self) -> Vec<PathBuf> {
+        read_dir(self.output_path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path != &self.output_path && path != &self.input_path)
+            .collect()
+    }
+}
+
+fn config<'l>(breakpoints: &'l MultiMap<ResourceLocation, LocalBreakpoint>) -> Config<'l> {
+    Config {
+        namespace: "mcfd",
+        shadow: false,
+        exclude: &[],
+        excluded_functions: BTreeSet::new(),
+        skip_invalid_functions: false,
+        additional_datapacks: &[],
+        max_commands: DEFAULT_MAX_COMMANDS,
+        adapter: Some(AdapterConfig {
+            adapter_listener_name: "test",
+            breakpoints,
+            suspend_schedules: SuspendSchedules::All,
+        }),
+    }
+}
+
+fn create_functions(datapack: &TestDatapack) {
+    datapack.write_function("main", "say main\nfunction test:inner\nsay main");
+    datapack.write_function("inner", "say inner\nsay inner");
+    datapack.write_function("other", "say other");
+}
+
+#[tokio::test]
+async fn test_no_changes() {
+    // given:
+    let datapack = TestDatapack::new("test_no_changes");
+    create_functions(&datapack);
+    let breakpoints = MultiMap::new();
+    let config = config(&breakpoints);
+    let initial_writes = datapack.generate(&config).await;
+    assert!(!initial_writes.is_empty());
+
+    // when:
+    let actual = datapack.generate(&config).await;
+
+    // then:
+    assert_eq!(actual, BTreeSet::new());
+}
+
+#[tokio::test]
+async fn test_write_if_changed_skips_identical_content() {
+    // given:
+    let datapack = TestDatapack::new("test_write_if_changed_skips_identical_content");
+    create_dir_all(&datapack.output_path).unwrap();
+    let path = datapack.output_path.join("file.txt");
+    write(&path, "content").unwrap();
+    let old = SystemTime::now() - Duration::from_secs(3600);
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+
+    // when:
+    write_if_changed(&path, "content").await.unwrap();
+    let unchanged = metadata(&path).unwrap().modified().unwrap();
+    write_if_changed(&path, "changed").await.unwrap();
+    let changed = metadata(&path).unwrap().modified().unwrap();
+
+    // then:
+    assert_eq!(unchanged, old);
+    assert!(changed > old);
+    assert_eq!(read_to_string(&path).unwrap(), "changed");
+}
+
+#[tokio::test]
+async fn test_unchanged_global_files_are_not_rewritten() {
+    // given:
+    let datapack = TestDatapack::new("test_unchanged_global_files_are_not_rewritten");
+    create_functions(&datapack);
+    let breakpoints = MultiMap::new();
+    datapack.generate(&config(&breakpoints)).await;
+
+    // when:
+    // Excluding a missing function changes the config hash, but not the generated content
+    let exclude = ["test:missing".to_string()];
+    let config = Config {
+        exclude: &exclude,
+        excluded_functions: BTreeSet::new(),
+        skip_invalid_functions: false,
+        max_commands: DEFAULT_MAX_COMMANDS,
+        ..config(&breakpoints)
+    };
+    let actual = datapack.generate(&config).await;
+
+    // then:
+    for path in &actual {
+        assert!(
+            path.starts_with("data/mcfd/functions/test/")
+                || path.starts_with("data/debug/functions/test/")
+                || path.starts_with("data/mcfd/sourcemaps/test/")
+                || path == STATE_FILE,
+            "Unexpected write: {}",
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_changed_function() {
+    // given:
+    let datapack = TestDatapack::new("test_changed_function");
+    create_functions(&datapack);
+    let breakpoints = MultiMap::new();
+    let config = config(&breakpoints);
+    datapack.generate(&config).await;
+
+    // when:
+    datapack.write_function("other", "say changed");
+    let actual = datapack.generate(&config).await;
+
+    // then:
+    assert!(actual.contains("data/mcfd/functions/test/other/0_entry-1_return.mcfunction"));
+    assert!(actual.contains("data/debug/functions/test/other.mcfunction"));
+    assert!(actual.contains(STATE_FILE));
+    for path in &actual {
+        assert!(
+            path.starts_with("data/mcfd/functions/test/other/")
+                || path == "data/debug/functions/test/other.mcfunction"
+                || path == "data/mcfd/sourcemaps/test/other.json"
+                || path == STATE_FILE,
+            "Unexpected write: {}",
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_changed_breakpoint() {
+    // given:
+    let datapack = TestDatapack::new("test_changed_breakpoint");
+    create_functions(&datapack);
+    let mut breakpoints = MultiMap::new();
+    datapack.generate(&config(&breakpoints)).await;
+
+    // when:
+    breakpoints.insert(
+        ResourceLocation::new("test", "inner"),
+        LocalBreakpoint {
+            kind: BreakpointKind::Normal,
+            position: LocalBreakpointPosition {
+                line_number: 2,
+                position_in_line: BreakpointPositionInLine::Breakpoint,
+            },
+        },
+    );
+    let actual = datapack.generate(&config(&breakpoints)).await;
+
+    // then:
+    assert!(actual.contains("data/mcfd/functions/resume_self.mcfunction"));
+    assert!(actual.contains("data/mcfd/functions/test/inner/suspend_at_2_breakpoint.mcfunction"));
+    for path in &actual {
+        assert!(
+            path.starts_with("data/mcfd/functions/test/inner/")
+                || path == "data/debug/functions/test/inner.mcfunction"
+                || path == "data/mcfd/functions/resume_self.mcfunction"
+                || path == "data/mcfd/sourcemaps/test/inner.json"
+                || path == STATE_FILE,
+            "Unexpected write: {}",
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_new_caller() {
+    // given:
+    let datapack = TestDatapack::new("test_new_caller");
+    create_functions(&datapack);
+    let breakpoints = MultiMap::new();
+    let config = config(&breakpoints);
+    datapack.generate(&config).await;
+
+    // when:
+    datapack.write_function("main", "say main\nfunction test:inner\nfunction test:other");
+    let actual = datapack.generate(&config).await;
+
+    // then:
+    assert!(actual.contains("data/mcfd/functions/test/other/return_self.mcfunction"));
+    assert!(!actual.contains("data/mcfd/functions/test/inner/return_self.mcfunction"));
+}
+
+#[tokio::test]
+async fn test_removed_function() {
+    // given:
+    let datapack = TestDatapack::new("test_removed_function");
+    create_functions(&datapack);
+    let breakpoints = MultiMap::new();
+    let config = config(&breakpoints);
+    datapack.generate(&config).await;
+
+    // when:
+    remove_file(datapack.function_path("other")).unwrap();
+    datapack.generate(&config).await;
+
+    // then:
+    let output_path = &datapack.output_path;
+    assert!(!output_path
+        .join("data/mcfd/functions/test/other/0_entry-1_return.mcfunction")
+        .exists());
+    assert!(!output_path
+        .join("data/debug/functions/test/other.mcfunction")
+        .exists());
+    assert!(!output_path.join("data/mcfd/functions/test/other").exists());
+    assert!(!output_path
+        .join("data/mcfd/sourcemaps/test/other.json")
+        .exists());
+    assert!(output_path
+        .join("data/mcfd/functions/test/inner/0_entry-2_return.mcfunction")
+        .exists());
+}
+
+/// Replaces a generated file of the function `main` in the output with a directory, so that
+/// generating the function fails after some of its files were written.
+fn block_generated_file(datapack: &TestDatapack) -> PathBuf {
+    let fn_dir = datapack.output_path.join("data/mcfd/functions/test/main");
+    let path = read_dir(&fn_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_file())
+        .max()
+        .unwrap();
+    remove_file(&path).unwrap();
+    create_dir_all(&path).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn test_failed_generation_keeps_previous_debug_datapack() {
+    // given:
+    let datapack = TestDatapack::new("test_failed_generation_keeps_previous_debug_datapack");
+    create_functions(&datapack);
+    let breakpoints = MultiMap::new();
+    let config = config(&breakpoints);
+    datapack.generate(&config).await;
+    block_generated_file(&datapack);
+    let previous_output = datapack.read_output();
+    datapack.write_function("main", "say changed\nfunction test:inner");
+    remove_file(datapack.function_path("other")).unwrap();
+
+    // when:
+    let parser = CommandParser::default().unwrap();
+    let result = generate_debug_datapack(
+        &datapack.input_path,
+        &datapack.output_path,
+        &parser,
+        &config,
+    )
+    .await;
+
+    // then:
+    assert!(result.is_err());
+    assert_eq!(datapack.read_output(), previous_output);
+    assert_eq!(datapack.staging_dirs().len(), 1);
+}
+
+#[tokio::test]
+async fn test_generation_removes_staging_dirs_of_failed_generation() {
+    // given:
+    let datapack = TestDatapack::new("test_generation_removes_staging_dirs_of_failed_generation");
+    create_functions(&datapack);
+    let breakpoints = MultiMap::new();
+    let config = config(&breakpoints);
+    datapack.generate(&config).await;
+    let blocked_file = block_generated_file(&datapack);
+    datapack.write_function("main", "say changed\nfunction test:inner");
+    remove_file(datapack.function_path("other")).unwrap();
+    let parser = CommandParser::default().unwrap();
+    generate_debug_datapack(
+        &datapack.input_path,
+        &datapack.output_path,
+        &parser,
+        &config,
+    )
+    .await
+    .unwrap_err();
+    remove_dir_all(blocked_file).unwrap();
+
+    // when:
+    datapack.generate(&config).await;
+
+    // then:
+    assert_eq!(datapack.staging_dirs(), Vec::<PathBuf>::new());
+    let output_path = &datapack.output_path;
+    assert!(!output_path.join("data/mcfd/functions/test/other").exists());
+    let debug_main = read_to_string(output_path.join("data/debug/functions/test/main.mcfunction"));
+    assert!(debug_main.is_ok());
+}
+
+#[tokio::test]
+async fn test_removed_nested_function_removes_empty_dirs() {
+    // given:
+    let datapack = TestDatapack::new("test_removed_nested_function_removes_empty_dirs");
+    create_functions(&datapack);
+    datapack.write_function("dir/nested", "say nested");
+    let breakpoints = MultiMap::new();
+    let config = config(&breakpoints);
+    datapack.generate(&config).await;
+
+    // when:
+    remove_file(datapack.function_path("dir/nested")).unwrap();
+    datapack.generate(&config).await;
+
+    // then:
+    let output_path = &datapack.output_path;
+    assert!(!output_path.join("data/mcfd/functions/test/dir").exists());
+    assert!(!output_path.join("data/debug/functions/test/dir").exists());
+    assert!(output_path.join("data/mcfd/functions/test/other").exists());
+}
+
+#[tokio::test]
+async fn test_excluded_function() {
+    // given:
+    let datapack = TestDatapack::new("test_excluded_function");
+    create_functions(&datapack);
+    let mut breakpoints = MultiMap::new();
+    breakpoints.insert(
+        ResourceLocation::new("test", "inner"),
+        LocalBreakpoint {
+            kind: BreakpointKind::Normal,
+            position: LocalBreakpointPosition {
+                line_number: 2,
+                position_in_line: BreakpointPositionInLine::Breakpoint,
+            },
+        },
+    );
+    datapack.generate(&config(&breakpoints)).await;
+
+    // when:
+    let exclude = ["test:inn?r".to_string()];
+    let config = Config {
+        exclude: &exclude,
+        excluded_functions: BTreeSet::new(),
+        skip_invalid_functions: false,
+        max_commands: DEFAULT_MAX_COMMANDS,
+        ..config(&breakpoints)
+    };
+    datapack.generate(&config).await;
+
+    // then:
+    let output_path = &datapack.output_path;
+    assert!(!output_path
+        .join("data/mcfd/functions/test/inner/0_entry-2_breakpoint.mcfunction")
+        .exists());
+    assert!(!output_path
+        .join("data/debug/functions/test/inner.mcfunction")
+        .exists());
+    let resume_self =
+        read_to_string(output_path.join("data/mcfd/functions/resume_self.mcfunction")).unwrap();
+    assert!(!resume_self.contains("test/inner"), "{}", resume_self);
+    let main = read_to_string(
+        output_path.join("data/mcfd/functions/test/main/0_entry-3_return.mcfunction"),
+    )
+    .unwrap();
+    assert!(main.contains("\nfunction test:inner\n"), "{}", main);
+}
+
+#[tokio::test]
+async fn test_after_execute_breakpoint() {
+    // given:
+    let datapack = TestDatapack::new("test_after_execute_breakpoint");
+    create_functions(&datapack);
+    datapack.write_function(
+        "main",
+        "say main\nexecute as @e[type=sheep] run function test:inner\nsay main",
+    );
+    let mut breakpoints = MultiMap::new();
+    datapack.generate(&config(&breakpoints)).await;
+
+    // when:
+    breakpoints.insert(
+        ResourceLocation::new("test", "main"),
+        LocalBreakpoint {
+            kind: BreakpointKind::Normal,
+            position: LocalBreakpointPosition {
+                line_number: 2,
+                position_in_line: BreakpointPositionInLine::AfterExecute,
+            },
+        },
+    );
+    let actual = datapack.generate(&config(&breakpoints)).await;
+
+    // then:
+    assert!(actual.contains("data/mcfd/functions/suspend_after_execute.mcfunction"));
+    assert!(actual.contains("data/mcfd/functions/test/main/suspend_at_2_after_execute.mcfunction"));
+    assert!(actual.contains(
+        "data/mcfd/functions/test/main/continue_current_iteration_at_2_after_execute.mcfunction"
+    ));
+    let output_path = &datapack.output_path;
+    let suspend_at = read_to_string(
+        output_path.join("data/mcfd/functions/test/main/suspend_at_2_after_execute.mcfunction"),
+    )
+    .unwrap();
+    assert!(suspend_at.contains(r#""test:main:2:27""#), "{}", suspend_at);
+    let continue_current_iteration = read_to_string(output_path.join(
+        "data/mcfd/functions/test/main/continue_current_iteration_at_2_after_execute.mcfunction",
+    ))
+    .unwrap();
+    assert!(
+        continue_current_iteration.contains("function mcfd:test/inner/continue_at_0_entry"),
+        "{}",
+        continue_current_iteration
+    );
+}
+
+#[tokio::test]
+async fn test_after_execute_step() {
+    // given:
+    let datapack = TestDatapack::new("test_after_execute_step");
+    create_functions(&datapack);
+    datapack.write_function(
+        "main",
+        "say main\nexecute as @e[type=sheep] run function test:inner\nsay main",
+    );
+    let mut breakpoints = MultiMap::new();
+    breakpoints.insert(
+        ResourceLocation::new("test", "main"),
+        LocalBreakpoint {
+            kind: BreakpointKind::Step {
+                condition: "if score current mcfd_depth matches 1".to_string(),
+            },
+            position: LocalBreakpointPosition {
+                line_number: 2,
+                position_in_line: BreakpointPositionInLine::AfterExecute,
+            },
+        },
+    );
+
+    // when:
+    let actual = datapack.generate(&config(&breakpoints)).await;
+
+    // then:
+    assert!(actual.contains("data/mcfd/functions/test/main/suspend_at_2_after_execute.mcfunction"));
+    let suspend_after_execute = read_to_string(
+        datapack
+            .output_path
+            .join("data/mcfd/functions/suspend_after_execute.mcfunction"),
+    )
+    .unwrap();
+    assert!(
+        suspend_after_execute.contains(
+            "execute if entity @s[tag=mcfd+test+main+2] if score current mcfd_depth matches 1 run \
+            function mcfd:test/main/suspend_at_2_after_execute"
+        ),
+        "{}",
+        suspend_after_execute
+    );
+}
+
+#[tokio::test]
+async fn test_breakpoint_in_load_function() {
+    // given:
+    let datapack = TestDatapack::new("test_breakpoint_in_load_function");
+    create_functions(&datapack);
+    let load_tag = datapack
+        .input_path
+        .join("data/minecraft/tags/functions/load.json");
+    create_dir_all(load_tag.parent().unwrap()).unwrap();
+    write(load_tag, r#"{"values":["test:main"]}"#).unwrap();
+    let mut breakpoints = MultiMap::new();
+    datapack.generate(&config(&breakpoints)).await;
+    let load_json = datapack
+        .output_path
+        .join("data/minecraft/tags/functions/load.json");
+    let run_load = datapack
+        .output_path
+        .join("data/debug/functions/run_load.mcfunction");
+    assert!(!read_to_string(&load_json).unwrap().contains("defer_load"));
+    assert!(!run_load.exists());
+
+    // when:
+    breakpoints.insert(
+        ResourceLocation::new("test", "inner"),
+        LocalBreakpoint {
+            kind: BreakpointKind::Normal,
+            position: LocalBreakpointPosition {
+                line_number: 2,
+                position_in_line: BreakpointPositionInLine::Breakpoint,
+            },
+        },
+    );
+    let actual = datapack.generate(&config(&breakpoints)).await;
+
+    // then:
+    assert!(actual.contains("data/minecraft/tags/functions/load.json"));
+    assert!(actual.contains("data/mcfd/functions/defer_load.mcfunction"));
+    assert!(actual.contains("data/debug/functions/run_load.mcfunction"));
+    let content = read_to_string(&load_json).unwrap();
+    assert!(content.contains(r#""replace": true"#), "{}", content);
+    assert!(content.contains("mcfd:defer_load"), "{}", content);
+
+    // when:
+    breakpoints.clear();
+    datapack.generate(&config(&breakpoints)).await;
+
+    // then:
+    assert!(!read_to_string(&load_json).unwrap().contains("defer_load"));
+    assert!(!run_load.exists());
+}
@@ -19,38 +19,68 @@
 #[macro_use]
 mod macros;
 
+pub mod archive;
+#[cfg(feature = "bench")]
+pub mod bench;
 pub mod config;
+mod incremental;
 pub mod parser;
 mod partition;
+pub mod sink;
+pub mod source_map;
+mod staging;
+pub mod summary;
 pub mod template_engine;
 pub mod utils;
+pub mod validation;
+pub mod watch;
 
 use crate::{
-    config::{adapter::BreakpointPositionInLine, Config},
+    archive::read_to_string,
+    config::{
+        adapter::{BreakpointKind, BreakpointPositionInLine, LocalBreakpoint},
+        find_exclude_pattern, find_too_long_objectives, Config, MAX_COMMAND_LENGTH,
+        MAX_OBJECTIVE_LENGTH, PACK_FORMAT, SKIP_MARKER,
+    },
+    incremental::{
+        Changes, IncrementalState, FUNCTIONS, LOAD, RESUME_SELF, SCORES, SHOW_SKIPPED,
+        SUSPEND_AFTER_EXECUTE, TEMPLATES,
+    },
     parser::{
         command::{
-            argument::MinecraftEntityAnchor, resource_location::ResourceLocation, CommandParser,
+            argument::MinecraftEntityAnchor,
+            resource_location::{highlight_invalid_char, ResourceLocation, ResourceLocationRef},
+            CommandParser,
         },
-        parse_line, Line,
+        find_run_index, parse_line, parse_line_internal, Line,
     },
-    partition::{partition, Partition, Position, PositionInLine, Terminator},
-    template_engine::{exclude_internal_entites_from_selectors, TemplateEngine},
+    partition::{
+        partition, partition_with_breakpoints, Partition, Position, PositionInLine, Terminator,
+    },
+    sink::{FileSink, FsSink, MemorySink, RetrySink},
+    source_map::{hash_line, LineSourceMap, PartitionSourceMap, SourceMap, SOURCE_MAP_PATH},
+    summary::{FunctionListing, GeneratedSummary, PartitionRange},
+    template_engine::TemplateEngine,
+    utils::minecraft::{find_minecraft_log_file, find_minecraft_world},
+    validation::{ValidationIssue, ValidationReport},
 };
-use futures::{future::try_join_all, FutureExt};
+use futures::{future::try_join_all, Future, FutureExt};
 use multimap::MultiMap;
+use serde::Deserialize;
+use serde_json::{json, Value};
 use std::{
+    cmp::Reverse,
     collections::{BTreeMap, BTreeSet, HashMap},
     ffi::OsStr,
     fmt::Display,
-    fs::read_to_string,
     io::{self},
     iter::{repeat, FromIterator},
     path::{Path, PathBuf},
     str::FromStr,
 };
 use tokio::{
-    fs::{create_dir_all, write},
-    task::JoinHandle,
+    fs::read_dir,
+    task::{spawn_blocking, JoinHandle},
     try_join,
 };
 use walkdir::WalkDir;
@@ -59,81 +89,557 @@ use walkdir::WalkDir;
 pub async fn generate_debug_datapack<'l>(
     input_path: impl AsRef<Path>,
     output_path: impl AsRef<Path>,
+    parser: &CommandParser,
     config: &Config<'l>,
-) -> io::Result<()> {
-    let functions = find_function_files(input_path).await?;
+) -> io::Result<GeneratedSummary> {
+    generate_debug_datapack_with_sink(input_path, output_path, parser, config, &FsSink).await
+}
+
+/// Like [generate_debug_datapack], but all generated files are written to the given sink. The
+/// incremental state of a previous generation is still read from `output_path`. Operations on
+/// locked files are retried and all files that could not be written are reported together, see
+/// [RetrySink]. The sink decides whether the debug datapack is generated in a separate directory
+/// and moved to `output_path` at the end, see [FileSink::stage].
+pub async fn generate_debug_datapack_with_sink<'l>(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    parser: &CommandParser,
+    config: &Config<'l>,
+    sink: &dyn FileSink,
+) -> io::Result<GeneratedSummary> {
+    config.validate()?;
+    let sink = &RetrySink::new(sink);
+    let (mut functions, invalid_functions) =
+        find_datapack_function_files(&input_path, config, config.skip_invalid_functions).await?;
+    let config = &config.with_excluded_functions(find_skipped_functions(&functions).await?);
+    functions.retain(|function, _path| !config.is_excluded(function));
+    let tag_functions = find_tag_functions(&input_path, &functions, config).await?;
     let fn_ids = functions
         .keys()
+        .chain(tag_functions.keys())
         .enumerate()
         .map(|(index, it)| (it, index))
         .collect::<HashMap<_, _>>();
 
-    let fn_contents = parse_functions(&functions, config).await?;
+    let fn_contents = parse_functions(&functions, &tag_functions, parser, config).await?;
+    let call_tree = create_call_tree(&fn_contents);
 
     let output_name = output_path
         .as_ref()
         .file_name()
         .and_then(OsStr::to_str)
         .unwrap_or_default();
+    let mut summary = GeneratedSummary::new(output_name, config, &fn_contents, &call_tree);
+    summary
+        .warnings
+        .extend(skipped_function_warnings(invalid_functions));
+
+    let pack_format = read_pack_format(&input_path).await;
+    let old_state = IncrementalState::load(&output_path).await;
+    let new_state = IncrementalState::new(
+        &fn_ids,
+        &fn_contents,
+        &call_tree,
+        output_name,
+        pack_format,
+        config,
+    );
+    let changes = new_state.changes_since(&old_state);
+    if changes.is_empty() {
+        return Ok(summary);
+    }
+    let staged_path = sink.stage(output_path.as_ref().to_path_buf()).await?;
+    if old_state.is_empty() {
+        // Without a state we don't know which files are outdated
+        let _ = sink.remove_dir_all(staged_path.clone()).await;
+    } else {
+        IncrementalState::delete(&staged_path, sink).await?;
+    }
+
+    let suspend_schedules = config
+        .adapter
+        .as_ref()
+        .map(|config| config.suspend_schedules)
+        .unwrap_or_default();
+    let pack_format = pack_format.to_string();
     let engine = TemplateEngine::new(
-        BTreeMap::from_iter([("-ns-", config.namespace), ("-datapack-", output_name)]),
+        BTreeMap::from_iter([
+            ("-ns-", config.namespace),
+            ("-datapack-", output_name),
+            ("-pack_format-", pack_format.as_str()),
+            ("-suspend_schedules-", suspend_schedules.score()),
+        ]),
         config
             .adapter
             .as_ref()
             .map(|config| config.adapter_listener_name),
     );
-    expand_templates(&engine, &fn_ids, &fn_contents, &output_path, config).await?;
+    for function in changes.functions.iter().chain(&changes.removed_functions) {
+        remove_function_files(&engine, function, &staged_path, sink).await?;
+    }
+    for function in &changes.removed_functions {
+        remove_function_dirs(&engine, function, &staged_path, sink).await?;
+    }
 
-    write_functions_txt(functions.keys(), &output_path).await?;
+    let generation = Generation {
+        fn_ids: &fn_ids,
+        fn_contents: &fn_contents,
+        call_tree: &call_tree,
+        changes: &changes,
+        output_path: &staged_path,
+        sink,
+        config,
+    };
+    expand_templates(&engine, generation).await?;
+
+    if changes.globals.contains(FUNCTIONS) {
+        write_functions_txt(functions.keys(), &staged_path, sink).await?;
+    }
 
-    Ok(())
+    // Without a state the next generation rewrites all files that could not be written
+    sink.check_failures()?;
+    new_state.save(&staged_path, sink).await?;
+    sink.commit(staged_path, output_path.as_ref().to_path_buf())
+        .await?;
+
+    Ok(summary)
 }
 
-async fn find_function_files(
-    datapack_path: impl AsRef<Path>,
-) -> Result<BTreeMap<ResourceLocation, PathBuf>, io::Error> {
-    let data_path = datapack_path.as_ref().join("data");
-    let threads = data_path
-        .read_dir()?
-        .collect::<io::Result<Vec<_>>>()?
+/// The differences between a freshly generated debug datapack and the one at the output path.
+/// All paths are relative to the output path.
+#[derive(Debug, Default, PartialEq)]
+pub struct DatapackDiff {
+    /// Files that would be generated, but do not exist yet, with their new content.
+    pub added: BTreeMap<PathBuf, String>,
+    /// Files that exist, but would no longer be generated.
+    pub removed: BTreeSet<PathBuf>,
+    /// Files whose content would change, with their old and new content.
+    pub changed: BTreeMap<PathBuf, (String, String)>,
+}
+
+impl DatapackDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Generates the debug datapack in memory and compares it with the debug datapack at
+/// `output_path` without changing any files.
+pub async fn diff_debug_datapack<'l>(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    parser: &CommandParser,
+    config: &Config<'l>,
+) -> io::Result<DatapackDiff> {
+    let output_path = output_path.as_ref();
+    // Generate into a path without an incremental state, so that every file is generated
+    let memory_root = std::env::temp_dir()
+        .join(format!("mcfunction-debugger-diff-{}", std::process::id()))
+        .join(output_path.file_name().unwrap_or_default());
+    let sink = MemorySink::new();
+    generate_debug_datapack_with_sink(input_path, &memory_root, parser, config, &sink).await?;
+    let mut new_files = sink
+        .into_files()
         .into_iter()
-        .map(|entry| get_functions(entry).map(|result| result?));
+        .filter_map(|(path, content)| {
+            let path = path.strip_prefix(&memory_root).ok()?.to_path_buf();
+            Some((path, content))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let root = output_path.to_path_buf();
+    let old_files = spawn_blocking(move || read_files_recursively(&root))
+        .await
+        .unwrap()?;
+
+    let mut diff = DatapackDiff::default();
+    for (path, old_content) in old_files {
+        match new_files.remove(&path) {
+            Some(new_content) if new_content != old_content => {
+                diff.changed.insert(path, (old_content, new_content));
+            }
+            Some(_) => {}
+            None => {
+                diff.removed.insert(path);
+            }
+        }
+    }
+    diff.added = new_files;
+    Ok(diff)
+}
 
-    Ok(try_join_all(threads)
-        .await?
+fn read_files_recursively(root: &Path) -> io::Result<BTreeMap<PathBuf, String>> {
+    fn visit(root: &Path, dir: &Path, files: &mut BTreeMap<PathBuf, String>) -> io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                visit(root, &path, files)?;
+            } else {
+                let content = String::from_utf8_lossy(&std::fs::read(&path)?).into_owned();
+                let relative = path.strip_prefix(root).unwrap().to_path_buf();
+                files.insert(relative, content);
+            }
+        }
+        Ok(())
+    }
+    let mut files = BTreeMap::new();
+    match visit(root, root, &mut files) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        result => result.map(|()| files),
+    }
+}
+
+/// Lists all functions of the datapack that can be debugged ordered by name. Unlike
+/// [generate_debug_datapack] this only parses the functions.
+pub async fn list_functions<'l>(
+    input_path: impl AsRef<Path>,
+    parser: &CommandParser,
+    config: &Config<'l>,
+) -> io::Result<Vec<FunctionListing>> {
+    let (mut functions, _invalid_functions) =
+        find_datapack_function_files(&input_path, config, config.skip_invalid_functions).await?;
+    let config = &config.with_excluded_functions(find_skipped_functions(&functions).await?);
+    functions.retain(|function, _path| !config.is_excluded(function));
+    try_join_all(functions.iter().map(|(name, path)| async move {
+        let mut breakpoints = Vec::new();
+        let mut calls = BTreeSet::new();
+        for (line_index, line) in read_to_string(path).await?.split('\n').enumerate() {
+            match parse_line(parser, line, true) {
+                Line::Breakpoint => breakpoints.push(line_index + 1),
+                Line::FunctionCall { name, .. } => {
+                    calls.insert(name.to_string());
+                }
+                _ => {}
+            }
+        }
+        Ok(FunctionListing {
+            function: name.to_string(),
+            breakpoints,
+            calls: calls.into_iter().collect(),
+        })
+    }))
+    .await
+}
+
+/// Creates an element of the `configurations` array of a VS Code `launch.json` file that debugs the
+/// datapack with the debug adapter. The launched function is the function with the most
+/// `# breakpoint` lines, preferring functions that are not called by other functions. The world and
+/// log file are only included if they can be detected, see [utils::minecraft].
+pub async fn generate_launch_config<'l>(
+    input_path: impl AsRef<Path>,
+    parser: &CommandParser,
+    config: &Config<'l>,
+) -> io::Result<Value> {
+    let input_path = std::path::absolute(input_path)?;
+    let functions = list_functions(&input_path, parser, config).await?;
+    let called = functions
+        .iter()
+        .flat_map(|function| &function.calls)
+        .collect::<BTreeSet<_>>();
+    let entry = functions
+        .iter()
+        .min_by_key(|function| {
+            (
+                Reverse(function.breakpoints.len()),
+                called.contains(&function.function),
+                &function.function,
+            )
+        })
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not contain any functions", input_path.display()),
+            )
+        })?;
+    // Listed functions are known to be valid
+    let function = ResourceLocationRef::try_from(entry.function.as_str()).unwrap();
+    let program = archive::join_datapack_path(
+        &input_path,
+        Path::new("data").join(function.mcfunction_path()),
+    );
+
+    let mut launch_config = json!({
+        "type": "mcfunction",
+        "request": "launch",
+        "name": format!("Debug {}", entry.function),
+        "program": program.display().to_string(),
+    });
+    if let Some(world) = find_minecraft_world(&input_path) {
+        if let Some(log_file) = find_minecraft_log_file(&world) {
+            launch_config["minecraftLogFile"] = json!(log_file.display().to_string());
+        }
+        launch_config["minecraftWorldDir"] = json!(world.display().to_string());
+    }
+    Ok(launch_config)
+}
+
+/// Checks whether the datapack can be debugged without generating the debug datapack.
+pub async fn validate_datapack<'l>(
+    input_path: impl AsRef<Path>,
+    parser: &CommandParser,
+    config: &Config<'l>,
+) -> io::Result<ValidationReport> {
+    let (mut functions, invalid_functions) =
+        find_datapack_function_files(&input_path, config, true).await?;
+    let config = &config.with_excluded_functions(find_skipped_functions(&functions).await?);
+    let mut report = ValidationReport::default();
+    if config.skip_invalid_functions {
+        report
+            .warnings
+            .extend(skipped_function_warnings(invalid_functions));
+    } else {
+        report.errors.extend(
+            invalid_functions
+                .iter()
+                .map(|it| ValidationIssue::new(it.to_string())),
+        );
+    }
+    for (name, path) in functions
+        .iter()
+        .filter(|(name, _)| config.is_excluded(name))
+    {
+        for line_number in find_breakpoint_lines(name, path, parser, config).await? {
+            report.warnings.push(ValidationIssue::at(
+                name,
+                line_number,
+                "The breakpoint is ignored, because the function is excluded from debugging",
+            ));
+        }
+    }
+    functions.retain(|function, _path| !config.is_excluded(function));
+    let tag_functions = find_tag_functions(&input_path, &functions, config).await?;
+    let fn_contents = parse_functions(&functions, &tag_functions, parser, config).await?;
+    let call_tree = create_call_tree(&fn_contents);
+    let summary = GeneratedSummary::new("", config, &fn_contents, &call_tree);
+
+    let namespaces = functions
+        .keys()
+        .map(ResourceLocation::namespace)
+        .collect::<BTreeSet<_>>();
+    if namespaces.contains(config.namespace) {
+        report.errors.push(ValidationIssue::new(format!(
+            "The namespace {} is used by the datapack and the debugger, choose a different \
+            namespace for the debugger",
+            config.namespace
+        )));
+    }
+    if namespaces.contains("debug") {
+        report.warnings.push(ValidationIssue::new(
+            "Functions in the namespace debug may clash with the functions of the debugger",
+        ));
+    }
+    for objective in find_too_long_objectives(config.namespace) {
+        report.errors.push(ValidationIssue::new(format!(
+            "The scoreboard objective {} is longer than {} characters, choose a shorter \
+            namespace for the debugger",
+            objective, MAX_OBJECTIVE_LENGTH
+        )));
+    }
+
+    let mut fn_contents = fn_contents.into_iter().collect::<Vec<_>>();
+    fn_contents.sort_by_key(|(name, _lines)| *name);
+    for (name, lines) in fn_contents {
+        for (line_number, line, command) in lines {
+            if let Line::FunctionCall { name: callee, .. } = &command {
+                if !functions.contains_key(callee) && !tag_functions.contains_key(callee) {
+                    report.errors.push(ValidationIssue::at(
+                        name,
+                        line_number,
+                        format!("The called function {} does not exist", callee),
+                    ));
+                }
+            }
+            if functions.contains_key(name) {
+                let (_, error) = parse_line_internal(parser, &line, config.adapter.is_none());
+                if let Some(error) = error {
+                    report.warnings.push(ValidationIssue::at(
+                        name,
+                        line_number,
+                        format!("Failed to parse command: {}", error),
+                    ));
+                }
+            }
+        }
+    }
+    for function in summary.oversized_functions {
+        report.warnings.push(ValidationIssue::new(format!(
+            "Function {} has more than {} consecutive commands without a function call or \
+            breakpoint, Minecraft may not run it completely",
+            function, config.max_commands
+        )));
+    }
+    report.warnings.extend(summary.warnings);
+    Ok(report)
+}
+
+fn skipped_function_warnings(
+    invalid_functions: Vec<InvalidFunctionFile>,
+) -> impl Iterator<Item = ValidationIssue> {
+    invalid_functions
         .into_iter()
-        .flat_map(|it| it)
-        .collect::<BTreeMap<ResourceLocation, PathBuf>>())
+        .map(|it| ValidationIssue::new(format!("Skipped function file: {}", it)))
+}
+
+/// Finds the functions whose first line is [SKIP_MARKER].
+async fn find_skipped_functions(
+    functions: &BTreeMap<ResourceLocation, PathBuf>,
+) -> io::Result<BTreeSet<ResourceLocation>> {
+    let skipped = try_join_all(functions.iter().map(|(name, path)| async move {
+        let content = read_to_string(path).await?;
+        let first_line = content.lines().next().unwrap_or_default();
+        Ok::<_, io::Error>(Some(name.clone()).filter(|_| first_line.trim() == SKIP_MARKER))
+    }))
+    .await?;
+    Ok(skipped.into_iter().flatten().collect())
+}
+
+/// Returns the line numbers of the `# breakpoint` lines and the breakpoints set by the debug
+/// adapter in the function ordered by line number.
+async fn find_breakpoint_lines(
+    function: &ResourceLocation,
+    path: &Path,
+    parser: &CommandParser,
+    config: &Config<'_>,
+) -> io::Result<BTreeSet<usize>> {
+    let mut line_numbers = BTreeSet::new();
+    if let Some(adapter) = config.adapter.as_ref() {
+        let breakpoints = adapter.breakpoints.get_vec(function).into_iter().flatten();
+        line_numbers.extend(breakpoints.map(|breakpoint| breakpoint.position.line_number));
+    } else {
+        for (line_index, line) in read_to_string(path).await?.split('\n').enumerate() {
+            if let Line::Breakpoint = parse_line(parser, line, true) {
+                line_numbers.insert(line_index + 1);
+            }
+        }
+    }
+    Ok(line_numbers)
 }
 
-fn get_functions(
-    entry: std::fs::DirEntry,
-) -> JoinHandle<Result<Vec<(ResourceLocation, PathBuf)>, io::Error>> {
-    tokio::spawn(async move {
+/// A function file whose path is not a valid resource location, so Minecraft does not load it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvalidFunctionFile {
+    pub path: PathBuf,
+    /// The name derived from the path with the first invalid character enclosed in brackets.
+    pub name: String,
+}
+
+impl Display for InvalidFunctionFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid function name {} for file {}, only the characters a-z 0-9 _ - . and / are \
+            allowed",
+            self.name,
+            self.path.display()
+        )
+    }
+}
+
+/// Finds all function files in the input datapack and the additional datapacks of the config, see
+/// [find_valid_function_files]. A function that is defined in more than one of the datapacks
+/// causes an [io::ErrorKind::InvalidData] error naming both files, because Minecraft would only
+/// load one of them.
+async fn find_datapack_function_files(
+    input_path: impl AsRef<Path>,
+    config: &Config<'_>,
+    skip_invalid: bool,
+) -> io::Result<(
+    BTreeMap<ResourceLocation, PathBuf>,
+    Vec<InvalidFunctionFile>,
+)> {
+    let (mut functions, mut invalid_functions) =
+        find_valid_function_files(&input_path, skip_invalid).await?;
+    for datapack in config.additional_datapacks {
+        let (additional_functions, additional_invalid_functions) =
+            find_valid_function_files(datapack, skip_invalid).await?;
+        for (name, path) in additional_functions {
+            if let Some(existing) = functions.get(&name) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Function {} is defined in both {} and {}",
+                        name,
+                        existing.display(),
+                        path.display()
+                    ),
+                ));
+            }
+            functions.insert(name, path);
+        }
+        invalid_functions.extend(additional_invalid_functions);
+    }
+    Ok((functions, invalid_functions))
+}
+
+/// Finds all function files in the datapack. Files with an invalid name are returned separately if
+/// `skip_invalid` is set, otherwise they cause an [io::ErrorKind::InvalidData] error listing all of
+/// them.
+async fn find_valid_function_files(
+    datapack_path: impl AsRef<Path>,
+    skip_invalid: bool,
+) -> io::Result<(
+    BTreeMap<ResourceLocation, PathBuf>,
+    Vec<InvalidFunctionFile>,
+)> {
+    let function_files = if archive::is_archive(&datapack_path) {
+        let datapack_path = datapack_path.as_ref().to_path_buf();
+        spawn_blocking(move || get_functions_in_archive(&datapack_path)).await??
+    } else {
+        let data_path = datapack_path.as_ref().join("data");
+        let threads = data_path
+            .read_dir()?
+            .collect::<io::Result<Vec<_>>>()?
+            .into_iter()
+            .map(|entry| get_functions(entry).map(|result| result?));
+        try_join_all(threads).await?.into_iter().flatten().collect()
+    };
+
+    let mut functions = BTreeMap::new();
+    let mut invalid_functions = Vec::new();
+    for function in function_files {
+        match function {
+            Ok((name, path)) => {
+                functions.insert(name, path);
+            }
+            Err(invalid) => invalid_functions.push(invalid),
+        }
+    }
+    invalid_functions.sort_by(|a, b| a.path.cmp(&b.path));
+    if !skip_invalid && !invalid_functions.is_empty() {
+        let message = invalid_functions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+    Ok((functions, invalid_functions))
+}
+
+type FunctionFiles = Vec<Result<(ResourceLocation, PathBuf), InvalidFunctionFile>>;
+
+/// Walks the functions directory of the namespace on the blocking thread pool, so the namespaces of
+/// a datapack are traversed in parallel. The names of the functions are relative to the functions
+/// directory of the namespace.
+fn get_functions(entry: std::fs::DirEntry) -> JoinHandle<Result<FunctionFiles, io::Error>> {
+    spawn_blocking(move || {
         let mut functions = Vec::new();
         if entry.file_type()?.is_dir() {
-            let namespace = entry.file_name();
-            let namespace_path = entry.path();
-            let functions_path = namespace_path.join("functions");
+            let namespace = entry.file_name().to_string_lossy().to_string();
+            let functions_path = entry.path().join("functions");
             if functions_path.is_dir() {
                 for f_entry in WalkDir::new(&functions_path) {
                     let f_entry = f_entry?;
-                    let path = f_entry.path().to_owned();
-                    let file_type = f_entry.file_type();
-                    if file_type.is_file() {
-                        if let Some(extension) = path.extension() {
-                            if extension == "mcfunction" {
-                                let relative_path = path.strip_prefix(&functions_path).unwrap();
-                                let name = ResourceLocation::new(
-                                    namespace.to_string_lossy().as_ref(),
-                                    &relative_path
-                                        .with_extension("")
-                                        .to_string_lossy()
-                                        .replace(std::path::MAIN_SEPARATOR, "/"),
-                                );
-
-                                functions.push((name, path));
-                            }
+                    let path = f_entry.path();
+                    if f_entry.file_type().is_file()
+                        && path.extension() == Some(OsStr::new("mcfunction"))
+                    {
+                        let relative_path = path.strip_prefix(&functions_path).unwrap();
+                        let name = get_function_name(&namespace, &relative_path.with_extension(""));
+                        let path = path.to_path_buf();
+                        match name {
+                            Ok(name) => functions.push(Ok((name, path))),
+                            Err(name) => functions.push(Err(InvalidFunctionFile { path, name })),
                         }
                     }
                 }
@@ -143,106 +649,654 @@ fn get_functions(
     })
 }
 
+/// Finds the function files in a zipped datapack, their paths are virtual paths, see [archive].
+fn get_functions_in_archive(zip: &Path) -> io::Result<FunctionFiles> {
+    Ok(archive::list_archive(zip)?
+        .into_iter()
+        .filter_map(|entry| {
+            let (namespace, relative_path) = split_data_entry(&entry, "functions", ".mcfunction")?;
+            let path = archive::archive_entry_path(zip, &entry);
+            Some(
+                match get_function_name(namespace, Path::new(relative_path)) {
+                    Ok(name) => Ok((name, path)),
+                    Err(name) => Err(InvalidFunctionFile { path, name }),
+                },
+            )
+        })
+        .collect())
+}
+
+/// Splits the name of a zip entry of the form `data/<namespace>/<dir>/<relative_path><extension>`
+/// into the namespace and the relative path without extension.
+fn split_data_entry<'e>(entry: &'e str, dir: &str, extension: &str) -> Option<(&'e str, &'e str)> {
+    let (namespace, path) = entry.strip_prefix("data/")?.split_once('/')?;
+    let relative_path = path
+        .strip_prefix(dir)?
+        .strip_prefix('/')?
+        .strip_suffix(extension)?;
+    Some((namespace, relative_path))
+}
+
+/// Returns the name of a function from its path relative to the functions directory of the
+/// namespace. If it is not a valid resource location, the name with the first invalid character
+/// highlighted is returned as the error.
+fn get_function_name(namespace: &str, relative_path: &Path) -> Result<ResourceLocation, String> {
+    let path = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let name = format!("{}:{}", namespace, path);
+    if let Some(highlighted) = highlight_invalid_char(&name) {
+        return Err(highlighted);
+    }
+    ResourceLocationRef::try_from(name.as_str())
+        .ok()
+        .filter(|name| !name.is_tag())
+        .map(|name| name.to_owned())
+        .ok_or(name)
+}
+
+/// Splits the content of a function into the regions that Minecraft executes without the debugger
+/// being able to suspend, as if the debug adapter generated the function with `breakpoints`. Calls
+/// to functions matching an `exclude` pattern don't end a region.
+pub fn find_partitions(
+    function: &ResourceLocation,
+    content: &str,
+    parser: &CommandParser,
+    exclude: &[String],
+    breakpoints: &MultiMap<ResourceLocation, LocalBreakpoint>,
+) -> Vec<PartitionRange> {
+    let lines = content
+        .split('\n')
+        .enumerate()
+        .map(|(line_index, line)| {
+            let line = line.strip_suffix('\r').unwrap_or(line); // Remove trailing carriage return on Windows
+            let command = exclude_function_call(parse_line(parser, line, false), |name| {
+                find_exclude_pattern(exclude, name).is_some()
+            });
+            (line_index + 1, line.to_string(), command)
+        })
+        .collect::<Vec<_>>();
+    partition_with_breakpoints(function, &lines, Some(breakpoints))
+        .iter()
+        .map(PartitionRange::from)
+        .collect()
+}
+
 async fn parse_functions<'l>(
     functions: &'l BTreeMap<ResourceLocation, PathBuf>,
+    tag_functions: &'l BTreeMap<ResourceLocation, Vec<String>>,
+    parser: &CommandParser,
     config: &Config<'_>,
 ) -> Result<HashMap<&'l ResourceLocation, Vec<(usize, String, Line)>>, io::Error> {
-    let parser =
-        CommandParser::default().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-    functions
-        .iter()
-        .map(|(name, path)| {
-            // TODO async
-            let lines = read_to_string(path)?
-                .split('\n')
-                .enumerate()
-                .map(|(line_index, line)| {
-                    let line = line.strip_suffix('\r').unwrap_or(line); // Remove trailing carriage return on Windows
-                    let command = parse_line(&parser, line, config.adapter.is_none());
-                    (line_index + 1, line.to_string(), command)
+    let parse = |line_index: usize, line: &str| {
+        let command = parse_line(parser, line, config.adapter.is_none());
+        let command = route_function_tag_call(command, tag_functions, config.namespace);
+        let command = exclude_function_call(command, |name| config.is_excluded(name));
+        (line_index + 1, line.to_string(), command)
+    };
+    let mut fn_contents = try_join_all(functions.iter().map(|(name, path)| async move {
+        let lines = read_to_string(path)
+            .await?
+            .split('\n')
+            .enumerate()
+            .map(|(line_index, line)| {
+                let line = line.strip_suffix('\r').unwrap_or(line); // Remove trailing carriage return on Windows
+                parse(line_index, line)
+            })
+            .collect::<Vec<(usize, String, Line)>>();
+        Ok::<_, io::Error>((name, lines))
+    }))
+    .await?;
+    fn_contents.extend(tag_functions.iter().map(|(name, values)| {
+        let lines = values
+            .iter()
+            .enumerate()
+            .map(|(line_index, value)| parse(line_index, &format!("function {}", value)))
+            .collect::<Vec<(usize, String, Line)>>();
+        (name, lines)
+    }));
+    Ok(fn_contents.into_iter().collect())
+}
+
+/// Finds the function tags of the datapack that should be debugged, keyed by the name of their
+/// generated function. Like in Minecraft, the values of a tag that is defined in several of the
+/// datapacks are concatenated in the order of the datapacks.
+///
+/// Only tags whose values are all functions or debugged tags of the datapacks are debugged. Tags
+/// that are also defined by another datapack next to the datapack are not debugged either, because
+/// Minecraft merges the values of all datapacks, so the generated function would miss some of them.
+async fn find_tag_functions(
+    datapack_path: impl AsRef<Path>,
+    functions: &BTreeMap<ResourceLocation, PathBuf>,
+    config: &Config<'_>,
+) -> io::Result<BTreeMap<ResourceLocation, Vec<String>>> {
+    let datapack_path = datapack_path.as_ref();
+    let mut tags = find_function_tags(datapack_path).await?;
+    for datapack in config.additional_datapacks {
+        for (tag, values) in find_function_tags(datapack).await? {
+            tags.entry(tag).or_default().extend(values);
+        }
+    }
+    for tag in find_tags_of_other_datapacks(datapack_path, config).await {
+        tags.remove(&tag);
+    }
+    loop {
+        let incomplete = tags
+            .iter()
+            .filter(|(_tag, values)| {
+                !values.iter().all(|value| {
+                    ResourceLocationRef::try_from(value.as_str()).is_ok_and(|value| {
+                        let value = value.to_owned();
+                        if value.is_tag() {
+                            tags.contains_key(&value)
+                        } else {
+                            // Calls to excluded functions are not debugged, so they may be defined
+                            // by any datapack
+                            functions.contains_key(&value) || config.is_excluded(&value)
+                        }
+                    })
                 })
-                .collect::<Vec<(usize, String, Line)>>();
-            Ok((name, lines))
-        })
-        .collect()
+            })
+            .map(|(tag, _values)| tag.clone())
+            .collect::<Vec<_>>();
+        if incomplete.is_empty() {
+            break;
+        }
+        for tag in incomplete {
+            tags.remove(&tag);
+        }
+    }
+    Ok(tags
+        .into_iter()
+        .map(|(tag, values)| (get_tag_function_name(&tag, config.namespace), values))
+        .filter(|(name, _values)| !config.is_excluded(name))
+        .collect())
 }
 
-async fn expand_templates(
+/// Finds the function tags of the other datapacks in the directory of the datapack, usually the
+/// datapacks directory of a world. Additional datapacks and debug datapacks are ignored.
+async fn find_tags_of_other_datapacks(
+    datapack_path: &Path,
+    config: &Config<'_>,
+) -> BTreeSet<ResourceLocation> {
+    let datapack_path = archive::physical_path(datapack_path);
+    let datapacks_dir = datapack_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let is_debugged = |path: &Path| {
+        path.file_name() == datapack_path.file_name()
+            || config
+                .additional_datapacks
+                .iter()
+                .any(|datapack| is_same_file(path, archive::physical_path(datapack)))
+    };
+    let mut tags = BTreeSet::new();
+    let entries = match datapacks_dir.read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return tags,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if is_debugged(&path)
+            || !archive::is_file(archive::join_datapack_path(&path, "pack.mcmeta"))
+            || IncrementalState::exists(&path)
+        {
+            continue;
+        }
+        if let Ok(other_tags) = find_function_tags(&path).await {
+            tags.extend(other_tags.into_keys());
+        }
+    }
+    tags
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Finds all function tags of the datapack and returns the values of each tag. Tags that can't be
+/// parsed are ignored, just like Minecraft does.
+async fn find_function_tags(
+    datapack_path: impl AsRef<Path>,
+) -> io::Result<BTreeMap<ResourceLocation, Vec<String>>> {
+    let datapack_path = datapack_path.as_ref().to_path_buf();
+    let paths = spawn_blocking(move || {
+        let mut paths = Vec::new();
+        if archive::is_archive(&datapack_path) {
+            for entry in archive::list_archive(&datapack_path)? {
+                let name = split_data_entry(&entry, "tags/functions", ".json").and_then(
+                    |(namespace, relative_path)| {
+                        get_function_name(namespace, Path::new(relative_path)).ok()
+                    },
+                );
+                if let Some(name) = name {
+                    let tag = format!("#{}", name);
+                    let tag = ResourceLocationRef::try_from(tag.as_str())
+                        .unwrap()
+                        .to_owned();
+                    paths.push((tag, archive::archive_entry_path(&datapack_path, &entry)));
+                }
+            }
+            return Ok(paths);
+        }
+        for entry in datapack_path.join("data").read_dir()? {
+            let entry = entry?;
+            let tags_path = entry.path().join("tags").join("functions");
+            if entry.file_type()?.is_dir() && tags_path.is_dir() {
+                let namespace = entry.file_name().to_string_lossy().to_string();
+                find_function_tag_files(&namespace, &tags_path, &tags_path, &mut paths)?;
+            }
+        }
+        Ok::<_, io::Error>(paths)
+    })
+    .await??;
+
+    let tags = try_join_all(paths.into_iter().map(|(tag, path)| async move {
+        let tag_file = serde_json::from_str::<FunctionTagFile>(&read_to_string(path).await?);
+        Ok::<_, io::Error>(tag_file.ok().map(|tag_file| {
+            let values = tag_file.values.into_iter().map(FunctionTagValue::into_id);
+            (tag, values.collect())
+        }))
+    }))
+    .await?;
+    Ok(tags.into_iter().flatten().collect())
+}
+
+fn find_function_tag_files(
+    namespace: &str,
+    tags_path: &Path,
+    dir: &Path,
+    paths: &mut Vec<(ResourceLocation, PathBuf)>,
+) -> io::Result<()> {
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            find_function_tag_files(namespace, tags_path, &path, paths)?;
+        } else if path.extension() == Some(OsStr::new("json")) {
+            let relative_path = path.strip_prefix(tags_path).unwrap();
+            if let Ok(name) = get_function_name(namespace, &relative_path.with_extension("")) {
+                let tag = format!("#{}", name);
+                let tag = ResourceLocationRef::try_from(tag.as_str())
+                    .unwrap()
+                    .to_owned();
+                paths.push((tag, path));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct FunctionTagFile {
+    values: Vec<FunctionTagValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FunctionTagValue {
+    Id(String),
+    Entry { id: String },
+}
+impl FunctionTagValue {
+    fn into_id(self) -> String {
+        match self {
+            FunctionTagValue::Id(id) | FunctionTagValue::Entry { id } => id,
+        }
+    }
+}
+
+/// A function tag is debugged through a generated function that calls the functions of the tag in
+/// order. The generated function is placed in the namespace of the debugger, which the datapack
+/// must not use, so it can't clash with a function of the datapack. For example the tag
+/// `#my_ns:my_tag` is debugged through the function `mcfd:tags/my_ns/my_tag`.
+fn get_tag_function_name(tag: &ResourceLocation, namespace: &str) -> ResourceLocation {
+    ResourceLocation::new(
+        namespace,
+        &format!("tags/{}/{}", tag.namespace(), tag.path()),
+    )
+}
+
+/// Calls to function tags are redirected to the generated function of the tag. Tags that are not
+/// debugged (for example because they are defined in another datapack) are treated like any other
+/// command.
+fn route_function_tag_call(
+    line: Line,
+    tag_functions: &BTreeMap<ResourceLocation, Vec<String>>,
+    namespace: &str,
+) -> Line {
+    match line {
+        Line::FunctionCall {
+            column_index,
+            name,
+            anchor,
+            dimension,
+            selectors,
+            objectives,
+        } if name.is_tag() => {
+            let name = get_tag_function_name(&name, namespace);
+            if tag_functions.contains_key(&name) {
+                Line::FunctionCall {
+                    column_index,
+                    name,
+                    anchor,
+                    dimension,
+                    selectors,
+                    objectives,
+                }
+            } else {
+                Line::OtherCommand {
+                    selectors,
+                    objectives,
+                }
+            }
+        }
+        Line::Schedule {
+            schedule_start,
+            function,
+            operation,
+            selectors,
+            objectives,
+        } if function.is_tag() => {
+            let function = get_tag_function_name(&function, namespace);
+            if tag_functions.contains_key(&function) {
+                Line::Schedule {
+                    schedule_start,
+                    function,
+                    operation,
+                    selectors,
+                    objectives,
+                }
+            } else {
+                Line::OtherCommand {
+                    selectors,
+                    objectives,
+                }
+            }
+        }
+        line => line,
+    }
+}
+
+/// Calls to excluded functions are not debugged, so they are treated like any other command. This
+/// way they are executed atomically and are never reported as skipped.
+fn exclude_function_call(line: Line, is_excluded: impl Fn(&ResourceLocation) -> bool) -> Line {
+    match line {
+        Line::FunctionCall {
+            name,
+            selectors,
+            objectives,
+            ..
+        }
+        | Line::Schedule {
+            function: name,
+            selectors,
+            objectives,
+            ..
+        } if is_excluded(&name) => Line::OtherCommand {
+            selectors,
+            objectives,
+        },
+        line => line,
+    }
+}
+
+/// Removes all files that were generated for the function, so no outdated files remain.
+async fn remove_function_files(
     engine: &TemplateEngine<'_>,
-    fn_ids: &HashMap<&ResourceLocation, usize>,
-    fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+    fn_name: &ResourceLocation,
     output_path: impl AsRef<Path>,
-    config: &Config<'_>,
+    sink: &dyn FileSink,
 ) -> io::Result<()> {
+    let engine = engine.extend_orig_name(fn_name);
+    let output_path = output_path.as_ref();
+
+    // The directory may contain directories of other functions, so only files are removed
+    let fn_dir = output_path.join(engine.expand("data/-ns-/functions/-orig_ns-/-orig/fn-"));
+    match read_dir(&fn_dir).await {
+        Ok(mut entries) => {
+            while let Some(entry) = entries.next_entry().await? {
+                if entry.file_type().await?.is_file() {
+                    sink.remove_file(entry.path()).await?;
+                }
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    for path in [
+        "data/debug/functions/-orig_ns-/-orig/fn-.mcfunction",
+        "data/-orig_ns-/functions/-orig/fn-.mcfunction",
+        SOURCE_MAP_PATH,
+    ] {
+        sink.remove_file(output_path.join(engine.expand(path)))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Removes the directories that only contained the files of a function that no longer exists,
+/// see [remove_function_files].
+async fn remove_function_dirs(
+    engine: &TemplateEngine<'_>,
+    fn_name: &ResourceLocation,
+    output_path: &Path,
+    sink: &dyn FileSink,
+) -> io::Result<()> {
+    let engine = engine.extend_orig_name(fn_name);
+    let output_path = |path: &str| output_path.join(engine.expand(path));
+    let fn_dir = output_path("data/-ns-/functions/-orig_ns-/-orig/fn-");
+    let debug_file = output_path("data/debug/functions/-orig_ns-/-orig/fn-.mcfunction");
+    let shadow_file = output_path("data/-orig_ns-/functions/-orig/fn-.mcfunction");
+    for (root, mut dir) in [
+        (output_path("data/-ns-/functions"), fn_dir),
+        (output_path("data/debug/functions"), parent(debug_file)),
+        (output_path("data/-orig_ns-/functions"), parent(shadow_file)),
+    ] {
+        while dir.starts_with(&root) && dir != root && is_empty_dir(&dir).await? {
+            sink.remove_dir_all(dir.clone()).await?;
+            dir.pop();
+        }
+    }
+    Ok(())
+}
+
+fn parent(mut path: PathBuf) -> PathBuf {
+    path.pop();
+    path
+}
+
+async fn is_empty_dir(path: &Path) -> io::Result<bool> {
+    match read_dir(path).await {
+        Ok(mut entries) => Ok(entries.next_entry().await?.is_none()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// The inputs of a single generation of a debug datapack that are shared by the templates.
+#[derive(Clone, Copy)]
+struct Generation<'l> {
+    fn_ids: &'l HashMap<&'l ResourceLocation, usize>,
+    fn_contents: &'l HashMap<&'l ResourceLocation, Vec<(usize, String, Line)>>,
+    call_tree: &'l MultiMap<&'l ResourceLocation, (&'l ResourceLocation, &'l usize)>,
+    changes: &'l Changes,
+    output_path: &'l Path,
+    sink: &'l dyn FileSink,
+    config: &'l Config<'l>,
+}
+
+async fn expand_templates(
+    engine: &TemplateEngine<'_>,
+    generation: Generation<'_>,
+) -> io::Result<()> {
+    let Generation {
+        fn_ids,
+        fn_contents,
+        changes,
+        output_path,
+        sink,
+        config,
+        ..
+    } = generation;
     try_join!(
-        expand_global_templates(engine, fn_ids, fn_contents, &output_path, config),
-        expand_function_specific_templates(engine, fn_ids, fn_contents, &output_path, config),
+        expand_global_templates(
+            engine,
+            fn_ids,
+            fn_contents,
+            changes,
+            output_path,
+            sink,
+            config
+        ),
+        expand_function_specific_templates(engine, generation),
     )?;
     Ok(())
 }
 
+async fn expand_if(changed: bool, future: impl Future<Output = io::Result<()>>) -> io::Result<()> {
+    if changed {
+        future.await
+    } else {
+        Ok(())
+    }
+}
+
 macro_rules! expand_template {
-    ($e:expr, $o:expr, $p:expr) => {{
+    ($e:expr, $o:expr, $s:expr, $p:expr) => {{
         let path = $o.join($e.expand($p));
         let content = $e.expand(include_template!($p));
-        write(path, content)
+        $s.write(path, content)
     }};
+    ($e:expr, $o:expr, $s:expr, $p:expr, $slots:expr) => {{
+        let path = $o.join($e.expand($p));
+        let content = $e.expand_with_slots(include_template!($p), $slots);
+        $s.write(path, content)
+    }};
+}
+
+/// Returns the pack_format of the datapack's pack.mcmeta, which the debug datapack copies so
+/// Minecraft treats both datapacks the same. Falls back to [PACK_FORMAT] if it can't be read.
+async fn read_pack_format(datapack: impl AsRef<Path>) -> u64 {
+    read_to_string(archive::join_datapack_path(datapack, "pack.mcmeta"))
+        .await
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|pack_mcmeta| pack_mcmeta.pointer("/pack/pack_format")?.as_u64())
+        .unwrap_or(PACK_FORMAT)
 }
 
 async fn expand_global_templates(
     engine: &TemplateEngine<'_>,
     fn_ids: &HashMap<&ResourceLocation, usize>,
     fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+    changes: &Changes,
     output_path: impl AsRef<Path>,
+    sink: &dyn FileSink,
     config: &Config<'_>,
 ) -> io::Result<()> {
     let output_path = output_path.as_ref();
 
+    try_join!(
+        sink.create_dir_all(output_path.join(engine.expand("data/-ns-/functions/id"))),
+        sink.create_dir_all(output_path.join(engine.expand("data/-ns-/predicates"))),
+        sink.create_dir_all(output_path.join("data/debug/functions")),
+        sink.create_dir_all(output_path.join("data/minecraft/tags/functions")),
+    )?;
+
+    try_join!(
+        expand_if(
+            changes.globals.contains(TEMPLATES),
+            expand_config_templates(engine, output_path, sink)
+        ),
+        expand_if(
+            changes.globals.contains(RESUME_SELF),
+            expand_resume_self_template(&engine, fn_contents, &output_path, sink, config)
+        ),
+        expand_if(
+            changes.globals.contains(SUSPEND_AFTER_EXECUTE),
+            expand_suspend_after_execute_template(&engine, fn_contents, &output_path, sink, config)
+        ),
+        expand_if(
+            changes.globals.contains(FUNCTIONS),
+            expand_schedule_template(&engine, fn_contents, &output_path, sink)
+        ),
+        expand_if(
+            changes.globals.contains(SCORES),
+            expand_scores_templates(&engine, fn_contents, &output_path, sink)
+        ),
+        expand_if(
+            changes.globals.contains(FUNCTIONS),
+            expand_validate_all_functions_template(
+                &engine,
+                fn_ids,
+                fn_contents,
+                &output_path,
+                sink
+            )
+        ),
+        expand_if(
+            changes.globals.contains(SHOW_SKIPPED),
+            expand_show_skipped_template(&engine, fn_ids, fn_contents, &output_path, sink)
+        ),
+        expand_if(
+            changes.globals.contains(LOAD),
+            expand_load_templates(&engine, fn_contents, &output_path, sink, config)
+        ),
+    )?;
+
+    Ok(())
+}
+
+async fn expand_config_templates(
+    engine: &TemplateEngine<'_>,
+    output_path: &Path,
+    sink: &dyn FileSink,
+) -> io::Result<()> {
     macro_rules! expand {
         ($p:literal) => {
-            expand_template!(engine, output_path, $p)
+            expand_template!(engine, output_path, sink, $p)
         };
     }
 
-    try_join!(
-        create_dir_all(output_path.join(engine.expand("data/-ns-/functions/id"))),
-        create_dir_all(output_path.join("data/debug/functions")),
-        create_dir_all(output_path.join("data/minecraft/tags/functions")),
-    )?;
-
     try_join!(
         expand!("data/-ns-/functions/id/assign.mcfunction"),
         expand!("data/-ns-/functions/id/init_self.mcfunction"),
-        expand!("data/-ns-/functions/id/install.mcfunction"),
-        expand!("data/-ns-/functions/id/uninstall.mcfunction"),
         expand!("data/-ns-/functions/abort_session.mcfunction"),
         expand!("data/-ns-/functions/animate_context.mcfunction"),
         expand!("data/-ns-/functions/decrement_age.mcfunction"),
         expand!("data/-ns-/functions/freeze_aec.mcfunction"),
         expand!("data/-ns-/functions/install.mcfunction"),
         expand!("data/-ns-/functions/load.mcfunction"),
+        expand!("data/-ns-/functions/on_chain_truncated.mcfunction"),
         expand!("data/-ns-/functions/on_session_exit_successful.mcfunction"),
         expand!("data/-ns-/functions/on_session_exit.mcfunction"),
+        expand!("data/-ns-/functions/reset_session.mcfunction"),
         expand!("data/-ns-/functions/reset_skipped.mcfunction"),
         expand!("data/-ns-/functions/resume_immediately.mcfunction"),
-        expand_resume_self_template(&engine, fn_contents, &output_path, config),
         expand!("data/-ns-/functions/resume_unchecked.mcfunction"),
-        expand_schedule_template(&engine, fn_contents, &output_path),
         expand!("data/-ns-/functions/select_entity.mcfunction"),
+        expand!("data/-ns-/functions/select_running_schedules.mcfunction"),
         expand!("data/-ns-/functions/skipped_functions_warning.mcfunction"),
         expand!("data/-ns-/functions/tick_start.mcfunction"),
         expand!("data/-ns-/functions/tick.mcfunction"),
         expand!("data/-ns-/functions/unfreeze_aec.mcfunction"),
         expand!("data/-ns-/functions/uninstall.mcfunction"),
-        expand_scores_templates(&engine, fn_contents, &output_path),
-        expand_validate_all_functions_template(&engine, fn_ids, fn_contents, &output_path),
+        expand!("data/-ns-/predicates/raining.json"),
+        expand!("data/-ns-/predicates/thundering.json"),
         expand!("data/debug/functions/install.mcfunction"),
+        expand!("data/debug/functions/purge.mcfunction"),
         expand!("data/debug/functions/resume.mcfunction"),
         expand!("data/debug/functions/show_scores.mcfunction"),
-        expand_show_skipped_template(&engine, fn_ids, fn_contents, &output_path),
         expand!("data/debug/functions/stop.mcfunction"),
         expand!("data/debug/functions/uninstall.mcfunction"),
-        expand!("data/minecraft/tags/functions/load.json"),
         expand!("data/minecraft/tags/functions/tick.json"),
         expand!("pack.mcmeta"),
     )?;
@@ -250,10 +1304,55 @@ async fn expand_global_templates(
     Ok(())
 }
 
+async fn expand_load_templates(
+    engine: &TemplateEngine<'_>,
+    fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+    output_path: &Path,
+    sink: &dyn FileSink,
+    config: &Config<'_>,
+) -> io::Result<()> {
+    #[rustfmt::skip]
+    macro_rules! DEFER_LOAD { () => { "data/-ns-/functions/defer_load.mcfunction" }; }
+    #[rustfmt::skip]
+    macro_rules! RUN_LOAD { () => { "data/debug/functions/run_load.mcfunction" }; }
+
+    let deferred_load = defers_load(fn_contents, config);
+    // Replacing the tag prevents Minecraft from running the functions of the input datapack
+    let engine = if deferred_load {
+        engine.extend([
+            ("-replace_load-", "true"),
+            ("-load_function-", "defer_load"),
+        ])
+    } else {
+        engine.extend([("-replace_load-", "false"), ("-load_function-", "load")])
+    };
+    expand_template!(
+        engine,
+        output_path,
+        sink,
+        "data/minecraft/tags/functions/load.json"
+    )
+    .await?;
+
+    if deferred_load {
+        try_join!(
+            expand_template!(engine, output_path, sink, DEFER_LOAD!()),
+            expand_template!(engine, output_path, sink, RUN_LOAD!()),
+        )?;
+    } else {
+        for path in [DEFER_LOAD!(), RUN_LOAD!()] {
+            sink.remove_file(output_path.join(engine.expand(path)))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
 async fn expand_resume_self_template(
     engine: &TemplateEngine<'_>,
     fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
     output_path: impl AsRef<Path>,
+    sink: &dyn FileSink,
     config: &Config<'_>,
 ) -> io::Result<()> {
     let mut breakpoints = fn_contents
@@ -277,7 +1376,17 @@ async fn expand_resume_self_template(
             |(function, local_breakpoints)| {
                 local_breakpoints
                     .iter()
+                    .filter(|_| fn_contents.contains_key(function)) // Not excluded or missing
                     .filter(|breakpoint| breakpoint.can_resume())
+                    .filter(|breakpoint| {
+                        breakpoint.position.position_in_line
+                            != BreakpointPositionInLine::AfterExecute
+                            || calls_debugged_function(
+                                fn_contents,
+                                function,
+                                breakpoint.position.line_number,
+                            )
+                    })
                     .map(move |breakpoint| (function, breakpoint.get_position()))
             },
         ));
@@ -302,19 +1411,147 @@ async fn expand_resume_self_template(
 
     let engine = engine.extend([("# -resume_cases-", resume_cases.as_str())]);
     let path = output_path.as_ref();
-    expand_template!(engine, path, "data/-ns-/functions/resume_self.mcfunction").await
+    expand_template!(
+        engine,
+        path,
+        sink,
+        "data/-ns-/functions/resume_self.mcfunction"
+    )
+    .await
+}
+
+async fn expand_suspend_after_execute_template(
+    engine: &TemplateEngine<'_>,
+    fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+    output_path: impl AsRef<Path>,
+    sink: &dyn FileSink,
+    config: &Config<'_>,
+) -> io::Result<()> {
+    let mut suspend_cases = Vec::new();
+    if let Some(config) = config.adapter.as_ref() {
+        for (function, local_breakpoints) in config.breakpoints.iter_all() {
+            for breakpoint in local_breakpoints {
+                // A step after the execute prefix only suspends at the depth it was created for
+                let step_condition = match &breakpoint.kind {
+                    BreakpointKind::Normal | BreakpointKind::Temporary => String::new(),
+                    BreakpointKind::Step { condition } => format!("{} ", condition),
+                    BreakpointKind::Invalid | BreakpointKind::Continue => continue,
+                };
+                if breakpoint.position.position_in_line == BreakpointPositionInLine::AfterExecute
+                    && calls_debugged_function(
+                        fn_contents,
+                        function,
+                        breakpoint.position.line_number,
+                    )
+                {
+                    let condition = format!(
+                        "execute if entity @s[tag=-ns-+{orig_ns}+{orig_fn_tag}+{line_number}] \
+                        {step_condition}run",
+                        orig_ns = function.namespace(),
+                        orig_fn_tag = function.path().replace("/", "+"),
+                        line_number = breakpoint.position.line_number,
+                        step_condition = step_condition,
+                    );
+                    suspend_cases.push(engine.expand(&format!(
+                        "{condition} scoreboard players set breakpoint -ns-_global 1\n\
+                        {condition} function -ns-:{orig_ns}/{orig_fn}/suspend_at_{position}",
+                        condition = condition,
+                        orig_ns = function.namespace(),
+                        orig_fn = function.path(),
+                        position = breakpoint.get_position(),
+                    )));
+                }
+            }
+        }
+    }
+    suspend_cases.sort();
+    let suspend_cases = suspend_cases.join("\n");
+
+    let engine = engine.extend([("# -suspend_cases-", suspend_cases.as_str())]);
+    let path = output_path.as_ref();
+    expand_template!(
+        engine,
+        path,
+        sink,
+        "data/-ns-/functions/suspend_after_execute.mcfunction"
+    )
+    .await
+}
+
+/// Returns whether the line of the function calls a function that is part of the debug datapack.
+fn calls_debugged_function(
+    fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+    function: &ResourceLocation,
+    line_number: usize,
+) -> bool {
+    fn_contents
+        .get(function)
+        .and_then(|lines| lines.get(line_number.wrapping_sub(1)))
+        .filter(|(_, _, line)| matches!(line, Line::FunctionCall { name, .. } if fn_contents.contains_key(name)))
+        .is_some()
+}
+
+/// Returns whether the functions of `#minecraft:load` should only run when `debug:run_load` is
+/// called. This is the case if the debug adapter set a breakpoint in a function that is called
+/// during load, because the debug datapack is not installed yet when Minecraft runs these
+/// functions.
+pub(crate) fn defers_load(
+    fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+    config: &Config<'_>,
+) -> bool {
+    let adapter = match config.adapter.as_ref() {
+        Some(adapter) => adapter,
+        None => return false,
+    };
+    let load = get_tag_function_name(
+        &ResourceLocation::new("minecraft", "load"),
+        config.namespace,
+    );
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![&load];
+    while let Some(function) = pending.pop() {
+        if let Some(lines) = fn_contents.get(function) {
+            if !visited.insert(function) {
+                continue;
+            }
+            let has_breakpoints =
+                adapter
+                    .breakpoints
+                    .get_vec(function)
+                    .is_some_and(|breakpoints| {
+                        breakpoints
+                            .iter()
+                            .any(|breakpoint| matches!(breakpoint.kind, BreakpointKind::Normal))
+                    });
+            if has_breakpoints
+                || lines
+                    .iter()
+                    .any(|(_, _, line)| matches!(line, Line::Breakpoint))
+            {
+                return true;
+            }
+            pending.extend(lines.iter().filter_map(|(_, _, line)| match line {
+                Line::FunctionCall { name, .. } => Some(name),
+                _ => None,
+            }));
+        }
+    }
+    false
 }
 
 async fn expand_schedule_template(
     engine: &TemplateEngine<'_>,
     fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
     output_path: impl AsRef<Path>,
+    sink: &dyn FileSink,
 ) -> io::Result<()> {
     #[rustfmt::skip]
     macro_rules! PATH { () => { "data/-ns-/functions/schedule.mcfunction" }; }
 
     let content = fn_contents
         .keys()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
         .map(|name| {
             let engine = engine.extend_orig_name(name);
             engine.expand(include_template!(PATH!()))
@@ -323,67 +1560,114 @@ async fn expand_schedule_template(
         .join("");
 
     let path = output_path.as_ref().join(engine.expand(PATH!()));
-    write(&path, &content).await
+    sink.write(path, content).await
 }
 
+#[rustfmt::skip]
+macro_rules! LOG_SCORES { () => { "data/-ns-/functions/log_scores.mcfunction" }; }
+#[rustfmt::skip]
+macro_rules! UPDATE_SCORES { () => { "data/-ns-/functions/update_scores.mcfunction" }; }
+/// Logs the scores of objectives that are created at runtime, see [write_dynamic_scores_functions].
+const LOG_SCORES_DYNAMIC: &str = "data/-ns-/functions/log_scores_dynamic.mcfunction";
+/// Updates the scores of objectives that are created at runtime, see
+/// [write_dynamic_scores_functions].
+const UPDATE_SCORES_DYNAMIC: &str = "data/-ns-/functions/update_scores_dynamic.mcfunction";
+
 async fn expand_scores_templates(
     engine: &TemplateEngine<'_>,
     fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
     output_path: impl AsRef<Path>,
+    sink: &dyn FileSink,
 ) -> io::Result<()> {
-    let objectives = fn_contents
-        .values()
-        .flat_map(|vec| vec)
-        .filter_map(|(_, _, line)| line.objectives())
-        .flat_map(|objectives| objectives)
+    let objectives = find_objectives(fn_contents)
+        .into_iter()
+        .map(String::as_str)
         .collect::<BTreeSet<_>>();
 
+    let output_path = output_path.as_ref();
+    let write = |path: &str, template: &str, objectives: &BTreeSet<&str>| {
+        let path = output_path.join(engine.expand(path));
+        sink.write(path, expand_per_objective(engine, template, objectives))
+    };
     try_join!(
-        expand_log_scores_template(&objectives, engine, &output_path),
-        expand_update_scores_template(&objectives, engine, &output_path),
+        write(LOG_SCORES!(), include_template!(LOG_SCORES!()), &objectives),
+        write(
+            UPDATE_SCORES!(),
+            include_template!(UPDATE_SCORES!()),
+            &objectives
+        ),
+        // The debug adapter adds objectives that are created at runtime
+        write(LOG_SCORES_DYNAMIC, "", &BTreeSet::new()),
+        write(UPDATE_SCORES_DYNAMIC, "", &BTreeSet::new()),
     )?;
 
     Ok(())
 }
 
-async fn expand_log_scores_template(
-    objectives: &BTreeSet<&String>,
-    engine: &TemplateEngine<'_>,
-    output_path: impl AsRef<Path>,
-) -> Result<(), io::Error> {
-    #[rustfmt::skip]
-    macro_rules! PATH { () => { "data/-ns-/functions/log_scores.mcfunction" }; }
+/// Returns the scoreboard objectives that are used by the commands of the functions.
+pub(crate) fn find_objectives<'l>(
+    fn_contents: &'l HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+) -> BTreeSet<&'l String> {
+    fn_contents
+        .values()
+        .flat_map(|vec| vec)
+        .filter_map(|(_, _, line)| line.objectives())
+        .flat_map(|objectives| objectives)
+        .collect()
+}
 
-    let content = objectives
+/// Expands `template` once for every objective and concatenates the results.
+fn expand_per_objective(
+    engine: &TemplateEngine<'_>,
+    template: &str,
+    objectives: &BTreeSet<&str>,
+) -> String {
+    objectives
         .iter()
         .map(|objective| {
-            let engine = engine.extend([("-objective-", objective.as_str())]);
-            engine.expand(include_template!(PATH!()))
+            let engine = engine.extend([("-objective-", *objective)]);
+            engine.expand(template)
         })
         .collect::<Vec<_>>()
-        .join("");
-    let path = output_path.as_ref().join(engine.expand(PATH!()));
-    write(&path, &content).await
+        .join("")
 }
 
-async fn expand_update_scores_template(
-    objectives: &BTreeSet<&String>,
-    engine: &TemplateEngine<'_>,
+/// Writes the functions `-ns-:log_scores_dynamic` and `-ns-:update_scores_dynamic` of the debug
+/// datapack at `output_path` for objectives that are created at runtime and are therefore unknown
+/// when generating the debug datapack, for example because their name comes from another
+/// datapack. Returns the commands of `-ns-:log_scores_dynamic`, so they can be executed before
+/// Minecraft reloaded the functions.
+pub async fn write_dynamic_scores_functions(
     output_path: impl AsRef<Path>,
-) -> Result<(), io::Error> {
-    #[rustfmt::skip]
-    macro_rules! PATH { () => { "data/-ns-/functions/update_scores.mcfunction" }; }
-
-    let content = objectives
-        .iter()
-        .map(|objective| {
-            let engine = engine.extend([("-objective-", objective.as_str())]);
-            engine.expand(include_template!(PATH!()))
-        })
-        .collect::<Vec<_>>()
-        .join("");
-    let path = output_path.as_ref().join(engine.expand(PATH!()));
-    write(&path, &content).await
+    namespace: &str,
+    adapter_listener_name: &str,
+    objectives: &BTreeSet<String>,
+    sink: &dyn FileSink,
+) -> io::Result<Vec<String>> {
+    let engine = TemplateEngine::new(
+        BTreeMap::from_iter([("-ns-", namespace)]),
+        Some(adapter_listener_name),
+    );
+    let objectives = objectives.iter().map(String::as_str).collect();
+    let log_scores = expand_per_objective(&engine, include_template!(LOG_SCORES!()), &objectives);
+    let update_scores =
+        expand_per_objective(&engine, include_template!(UPDATE_SCORES!()), &objectives);
+    let output_path = output_path.as_ref();
+    try_join!(
+        sink.write(
+            output_path.join(engine.expand(LOG_SCORES_DYNAMIC)),
+            log_scores.clone()
+        ),
+        sink.write(
+            output_path.join(engine.expand(UPDATE_SCORES_DYNAMIC)),
+            update_scores
+        ),
+    )?;
+    Ok(log_scores
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
 }
 
 async fn expand_validate_all_functions_template(
@@ -391,12 +1675,15 @@ async fn expand_validate_all_functions_template(
     fn_ids: &HashMap<&ResourceLocation, usize>,
     fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
     output_path: impl AsRef<Path>,
+    sink: &dyn FileSink,
 ) -> io::Result<()> {
     #[rustfmt::skip]
     macro_rules! PATH { () => { "data/-ns-/functions/validate_all_functions.mcfunction" }; }
 
     let content = fn_contents
         .keys()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
         .map(|name| {
             let fn_score_holder = get_fn_score_holder(name, fn_ids);
             let engine = engine
@@ -408,7 +1695,7 @@ async fn expand_validate_all_functions_template(
         .join("");
 
     let path = output_path.as_ref().join(engine.expand(PATH!()));
-    write(&path, &content).await
+    sink.write(path, content).await
 }
 
 async fn expand_show_skipped_template(
@@ -416,6 +1703,7 @@ async fn expand_show_skipped_template(
     fn_ids: &HashMap<&ResourceLocation, usize>,
     fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
     output_path: impl AsRef<Path>,
+    sink: &dyn FileSink,
 ) -> io::Result<()> {
     // This may include calls to non-existent functions
     let called_functions = fn_contents
@@ -468,6 +1756,7 @@ async fn expand_show_skipped_template(
             ("# -invalid_functions-", invalid_functions.as_str()),
         ]),
         output_path,
+        sink,
         "data/debug/functions/show_skipped.mcfunction"
     )
     .await?;
@@ -477,24 +1766,16 @@ async fn expand_show_skipped_template(
 
 async fn expand_function_specific_templates(
     engine: &TemplateEngine<'_>,
-    fn_ids: &HashMap<&ResourceLocation, usize>,
-    fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
-    output_path: impl AsRef<Path>,
-    config: &Config<'_>,
+    generation: Generation<'_>,
 ) -> io::Result<()> {
-    let call_tree = create_call_tree(&fn_contents);
-
-    try_join_all(fn_contents.iter().map(|(fn_name, lines)| {
-        expand_function_templates(
-            &engine,
-            fn_name,
-            lines,
-            fn_ids,
-            &call_tree,
-            &output_path,
-            config,
-        )
-    }))
+    let changed_fn_contents = generation
+        .fn_contents
+        .iter()
+        .filter(|(fn_name, _lines)| generation.changes.functions.contains(*fn_name));
+    try_join_all(
+        changed_fn_contents
+            .map(|(fn_name, lines)| expand_function_templates(engine, fn_name, lines, generation)),
+    )
     .await?;
 
     Ok(())
@@ -522,34 +1803,67 @@ fn create_call_tree<'l>(
 async fn expand_function_templates(
     engine: &TemplateEngine<'_>,
     fn_name: &ResourceLocation,
-    lines: &Vec<(usize, String, Line)>,
-    fn_ids: &HashMap<&ResourceLocation, usize>,
-    call_tree: &MultiMap<&ResourceLocation, (&ResourceLocation, &usize)>,
-    output_path: impl AsRef<Path>,
-    config: &Config<'_>,
+    lines: &[(usize, String, Line)],
+    generation: Generation<'_>,
 ) -> io::Result<()> {
+    let Generation {
+        fn_ids,
+        call_tree,
+        output_path,
+        sink,
+        config,
+        ..
+    } = generation;
     let fn_score_holder = get_fn_score_holder(fn_name, fn_ids);
     let engine = engine
         .extend_orig_name(fn_name)
         .extend([("-fn_score_holder-", fn_score_holder.as_str())]);
 
-    let output_path = output_path.as_ref();
     let fn_dir = output_path.join(engine.expand("data/-ns-/functions/-orig_ns-/-orig/fn-"));
-    create_dir_all(&fn_dir).await?;
+    sink.create_dir_all(fn_dir).await?;
 
     let partitions = partition(fn_name, lines, config);
 
+    // A conditional return command tags the context entity, because the remaining partitions of the
+    // current iteration must not be executed
+    let return_early = if partitions
+        .iter()
+        .any(|partition| matches!(partition.terminator, Terminator::ReturnCommand { .. }))
+    {
+        "execute store success score returned -ns-_global run tag @s remove -ns-_return\n\
+        execute if score returned -ns-_global matches 1 run tag @s remove -ns-_active\n\
+        execute if score returned -ns-_global matches 1 run function -ns-:-orig_ns-/-orig/fn-/next_iteration_or_return"
+    } else {
+        ""
+    };
+
+    let mut source_map = SourceMap::default();
     let mut first = true;
+    // Partitions after an unconditional return command are never executed by Minecraft
+    let mut unreachable = false;
     for (partition_index, partition) in partitions.iter().enumerate() {
         let position = partition.start.to_string();
         let positions = format!("{}-{}", partition.start, partition.end);
+        source_map.partitions.push(PartitionSourceMap {
+            positions: positions.clone(),
+            lines: lines
+                .iter()
+                .filter(|(line_number, _, _)| {
+                    (partition.start.line_number..=partition.end.line_number).contains(line_number)
+                })
+                .map(|(line_number, line, _)| LineSourceMap {
+                    line_number: *line_number,
+                    hash: hash_line(line),
+                })
+                .collect(),
+        });
         let engine = engine.extend([
             ("-position-", position.as_str()),
             ("-positions-", positions.as_str()),
         ]);
         macro_rules! expand {
             ($p:literal) => {
-                expand_template!(engine, output_path, $p)
+                expand_template!(engine, output_path, sink, $p)
             };
         }
 
@@ -557,6 +1871,12 @@ async fn expand_function_templates(
             expand!("data/-ns-/functions/-orig_ns-/-orig/fn-/next_iteration_or_return.mcfunction")
                 .await?;
             first = false;
+        } else if unreachable {
+            #[rustfmt::skip]
+            macro_rules! PATH { () => {"data/-ns-/functions/-orig_ns-/-orig/fn-/continue_current_iteration_at_-position-.mcfunction"} }
+            let path = output_path.join(engine.expand(PATH!()));
+            let template = include_template!("data/template/functions/unreachable.mcfunction");
+            sink.write(path, engine.expand(template)).await?;
         } else {
             expand!(
                 "data/-ns-/functions/-orig_ns-/-orig/fn-/continue_current_iteration_at_-position-.mcfunction"
@@ -569,13 +1889,14 @@ async fn expand_function_templates(
         macro_rules! PATH { () => {"data/-ns-/functions/-orig_ns-/-orig/fn-/continue_at_-position-.mcfunction"} }
         let path = output_path.join(engine.expand(PATH!()));
         let template = include_template!(PATH!()).to_string();
-        write(&path, &engine.expand(&template)).await?;
+        let continue_engine = engine.extend([("# -return_early-", return_early)]);
+        sink.write(path, continue_engine.expand(&template)).await?;
 
         // -positions-.mcfunction
         let mut content = partition
             .regular_lines
             .iter()
-            .map(|line| engine.expand_line(line))
+            .map(|line| expand_regular_line(&engine, line))
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -584,6 +1905,7 @@ async fn expand_function_templates(
                 expand_breakpoint_template(
                     &engine,
                     output_path,
+                    sink,
                     &partition.end,
                     StoppedReason::Breakpoint,
                     0,
@@ -597,15 +1919,19 @@ async fn expand_function_templates(
             } => {
                 let column = match position_in_line {
                     BreakpointPositionInLine::Breakpoint => 1,
+                    BreakpointPositionInLine::AfterExecute => {
+                        unreachable!("Breakpoints after the execute prefix are no steps")
+                    }
                     BreakpointPositionInLine::AfterFunction => {
                         let (_line_number, line, _parsed) = &lines[partition.end.line_number - 1];
-                        1 + line.len()
+                        1 + line.chars().count()
                     }
                 };
                 let next_partition = &partitions[partition_index + 1];
                 expand_breakpoint_template(
                     &engine,
                     output_path,
+                    sink,
                     &partition.end,
                     StoppedReason::Step,
                     column,
@@ -626,12 +1952,30 @@ async fn expand_function_templates(
                 line,
                 name: called_fn,
                 anchor,
+                dimension,
                 selectors,
+                after_execute,
             } => {
+                if let Some(kind) = after_execute
+                    .filter(|kind| kind.can_resume())
+                    .filter(|_| fn_ids.contains_key(called_fn))
+                {
+                    let call = AfterExecuteCall {
+                        position: Position {
+                            line_number: partition.end.line_number,
+                            position_in_line: PositionInLine::AfterExecute,
+                        },
+                        kind,
+                        line,
+                        column_index: *column_index,
+                        called_fn,
+                    };
+                    expand_after_execute_templates(&engine, output_path, sink, &call).await?;
+                }
+
                 let line_number = (partition.end.line_number).to_string();
                 let fn_score_holder = get_fn_score_holder(called_fn, fn_ids);
-                let execute = &line[..*column_index];
-                let execute = exclude_internal_entites_from_selectors(execute, selectors);
+                let execute = engine.exclude_internal_entities(&line[..*column_index], selectors);
                 let debug_anchor = anchor.map_or("".to_string(), |anchor| {
                     let mut anchor_score = 0;
                     if anchor == MinecraftEntityAnchor::EYES {
@@ -643,17 +1987,62 @@ async fn expand_function_templates(
                         anchor_score = anchor_score
                     )
                 });
+                let debug_dimension = dimension.as_ref().map_or(String::new(), |dimension| {
+                    format!(
+                        "execute if score -fn_score_holder- -ns-_valid matches 1 run \
+                            scoreboard players set current -ns-_dim {dimension_score}",
+                        dimension_score = get_dimension_score(dimension)
+                    )
+                });
                 let engine = engine.extend([
                     ("-line_number-", line_number.as_str()),
                     ("-call_ns-", called_fn.namespace()),
                     ("-call/fn-", called_fn.path()),
                     ("-fn_score_holder-", fn_score_holder.as_str()),
-                    ("execute run ", &execute),
                     ("# -debug_anchor-", &debug_anchor),
+                    ("# -debug_dimension-", &debug_dimension),
                 ]);
                 let template =
                     include_template!("data/template/functions/call_function.mcfunction");
-                engine.expand(&template)
+                engine.expand_with_slots(&template, &[("execute run ", &execute)])
+            }
+            Terminator::ReturnCommand {
+                line,
+                return_index,
+                run_index,
+                selectors,
+            } => {
+                let return_run = run_index.map_or(String::new(), |run_index| {
+                    let selectors = selectors
+                        .iter()
+                        .filter(|it| **it >= run_index)
+                        .map(|it| it - run_index)
+                        .collect();
+                    engine.exclude_internal_entities(&line[run_index..], &selectors)
+                });
+                if *return_index == 0 {
+                    let template = include_template!("data/template/functions/return.mcfunction");
+                    let template = format!("# -return_run-\n{}", template);
+                    engine.expand_with_slots(&template, &[("# -return_run-", &return_run)])
+                } else {
+                    let execute = &line[..*return_index];
+                    let selectors = selectors.range(..*return_index).copied().collect();
+                    let execute = engine.exclude_internal_entities(execute, &selectors);
+                    let return_run = if return_run.is_empty() {
+                        return_run
+                    } else {
+                        format!("{}{}", execute, return_run)
+                    };
+                    let next_partition = &partitions[partition_index + 1];
+                    let next_positions = format!("{}-{}", next_partition.start, next_partition.end);
+                    let engine = engine.extend([("-next_positions-", next_positions.as_str())]);
+                    let template =
+                        include_template!("data/template/functions/return_conditional.mcfunction");
+                    engine.expand_with_slots(
+                        template,
+                        &[("execute run ", &execute), ("# -return_run-", &return_run)],
+                    )
+                }
             }
             Terminator::Return => {
                 let template = include_template!("data/template/functions/return.mcfunction");
@@ -664,21 +2053,38 @@ async fn expand_function_templates(
         content.push_str(&terminator);
 
         expand_template!(
-            engine.extend([("# -content-", content.as_str())]),
+            engine,
             output_path,
-            "data/-ns-/functions/-orig_ns-/-orig/fn-/-positions-.mcfunction"
+            sink,
+            "data/-ns-/functions/-orig_ns-/-orig/fn-/-positions-.mcfunction",
+            &[("# -content-", &content)]
         )
         .await?;
+
+        if let Terminator::ReturnCommand {
+            return_index: 0, ..
+        } = partition.terminator
+        {
+            unreachable = true;
+        }
     }
 
+    let source_map_path = output_path.join(engine.expand(SOURCE_MAP_PATH));
+    create_parent_dir(sink, &source_map_path).await?;
+    let source_map = serde_json::to_string(&source_map)?;
+    sink.write(source_map_path, source_map).await?;
+
     macro_rules! expand {
         ($p:literal) => {
-            expand_template!(engine, output_path, $p)
+            expand_template!(engine, output_path, sink, $p)
         };
     }
 
-    create_parent_dir(output_path.join(engine.expand("data/debug/functions/-orig_ns-/-orig/fn-")))
-        .await?;
+    create_parent_dir(
+        sink,
+        output_path.join(engine.expand("data/debug/functions/-orig_ns-/-orig/fn-")),
+    )
+    .await?;
 
     try_join!(
         expand!("data/-ns-/functions/-orig_ns-/-orig/fn-/return.mcfunction"),
@@ -690,8 +2096,11 @@ async fn expand_function_templates(
     )?;
 
     if config.shadow {
-        create_parent_dir(output_path.join(engine.expand("data/-orig_ns-/functions/-orig/fn-")))
-            .await?;
+        create_parent_dir(
+            sink,
+            output_path.join(engine.expand("data/-orig_ns-/functions/-orig/fn-")),
+        )
+        .await?;
         expand!("data/-orig_ns-/functions/-orig/fn-.mcfunction").await?;
     }
 
@@ -717,34 +2126,148 @@ async fn expand_function_templates(
         expand_template!(
             engine.extend([("# -return_cases-", return_cases.as_str())]),
             output_path,
+            sink,
             "data/-ns-/functions/-orig_ns-/-orig/fn-/return_self.mcfunction"
         )
         .await?;
     }
 
+    let validate_prefix = engine.expand("execute if score 1 -ns-_constant matches 0 run ");
     let commands = lines
         .iter()
         .map(|(_, line, parsed)| match parsed {
             Line::Empty | Line::Comment | Line::Breakpoint => line.to_string(),
             _ => {
-                format!(
-                    "execute if score 1 -ns-_constant matches 0 run {}",
-                    line.trim_start()
-                )
+                let command = format!("{}{}", validate_prefix, line.trim_start());
+                if exceeds_max_command_length(&command) {
+                    // A truncated command would not parse and prevent loading the whole function
+                    let prefix = line.trim_start().chars().take(64).collect::<String>();
+                    format!("# Too long to validate: {}...", prefix)
+                } else {
+                    command
+                }
             }
         })
-        .collect::<Vec<_>>()
-        .join("\n");
+        .collect::<Vec<_>>();
+    // Minecraft refuses to load functions with too many commands, so large functions are validated
+    // in chunks
+    let chunks = split_commands(&commands, config.max_commands);
+    let commands = if chunks.len() <= 1 {
+        commands.join("\n")
+    } else {
+        let mut calls = Vec::with_capacity(chunks.len());
+        for (index, chunk) in chunks.iter().enumerate() {
+            let index = (index + 1).to_string();
+            let chunk = chunk.join("\n");
+            expand_template!(
+                engine.extend([("-index-", index.as_str())]),
+                output_path,
+                sink,
+                "data/-ns-/functions/-orig_ns-/-orig/fn-/validate_-index-.mcfunction",
+                &[("# -commands-", &chunk)]
+            )
+            .await?;
+            calls.push(engine.expand(&format!(
+                "function -ns-:-orig_ns-/-orig/fn-/validate_{}",
+                index
+            )));
+        }
+        calls.join("\n")
+    };
     expand_template!(
-        engine.extend([("# -commands-", commands.as_str())]),
+        engine,
         output_path,
-        "data/-ns-/functions/-orig_ns-/-orig/fn-/validate.mcfunction"
+        sink,
+        "data/-ns-/functions/-orig_ns-/-orig/fn-/validate.mcfunction",
+        &[("# -commands-", &commands)]
     )
     .await?;
 
     Ok(())
 }
 
+/// Expands a line of a partition. If the expanded line contains a command that is too long for
+/// Minecraft, the original line is used instead, see [find_unguarded_lines].
+fn expand_regular_line(engine: &TemplateEngine<'_>, line: &(usize, String, Line)) -> String {
+    let expanded = engine.expand_line(line);
+    if exceeds_max_command_length(&expanded) {
+        line.1.to_string()
+    } else {
+        expanded
+    }
+}
+
+/// Finds the commands that are written unchanged to the debug datapack, because excluding the
+/// entities of the debugger from their selectors would exceed [MAX_COMMAND_LENGTH].
+pub(crate) fn find_unguarded_lines(
+    fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+    config: &Config<'_>,
+) -> Vec<ValidationIssue> {
+    let engine = TemplateEngine::new(BTreeMap::from_iter([("-ns-", config.namespace)]), None);
+    let mut fn_contents = fn_contents.iter().collect::<Vec<_>>();
+    fn_contents.sort_by_key(|(name, _lines)| *name);
+    fn_contents
+        .into_iter()
+        .flat_map(|(name, lines)| {
+            let engine = &engine;
+            lines
+                .iter()
+                .filter(|(_, _, command)| {
+                    matches!(
+                        command,
+                        Line::OptionalSelectorCommand { .. }
+                            | Line::Schedule { .. }
+                            | Line::OtherCommand { .. }
+                    )
+                })
+                .filter(move |line| exceeds_max_command_length(&engine.expand_line(line)))
+                .map(move |(line_number, _, _)| {
+                    ValidationIssue::at(
+                        name,
+                        *line_number,
+                        format!(
+                            "The command would be longer than {} characters in the debug \
+                            datapack, so it is not modified and may select entities of the debugger",
+                            MAX_COMMAND_LENGTH
+                        ),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Returns true if Minecraft can't load a function containing the text, because one of its commands
+/// is longer than [MAX_COMMAND_LENGTH].
+fn exceeds_max_command_length(text: &str) -> bool {
+    text.lines()
+        .any(|line| line.chars().count() > MAX_COMMAND_LENGTH)
+}
+
+/// Returns true if the line of a generated function is a command and not empty or a comment.
+fn is_command(line: &str) -> bool {
+    let line = line.trim_start();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+/// Splits the lines of a generated function into chunks with at most `max_commands` commands each.
+fn split_commands(lines: &[String], max_commands: usize) -> Vec<&[String]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut commands = 0;
+    for (index, line) in lines.iter().enumerate() {
+        if is_command(line) {
+            if commands > 0 && commands == max_commands {
+                chunks.push(&lines[start..index]);
+                start = index;
+                commands = 0;
+            }
+            commands += 1;
+        }
+    }
+    chunks.push(&lines[start..]);
+    chunks
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum StoppedReason {
     Breakpoint,
@@ -773,6 +2296,7 @@ impl Display for StoppedReason {
 async fn expand_breakpoint_template(
     engine: &TemplateEngine<'_>,
     output_path: &Path,
+    sink: &dyn FileSink,
     position: &Position,
     reason: StoppedReason,
     column: usize,
@@ -792,6 +2316,7 @@ async fn expand_breakpoint_template(
     expand_template!(
         engine,
         output_path,
+        sink,
         "data/-ns-/functions/-orig_ns-/-orig/fn-/suspend_at_-position-.mcfunction"
     )
     .await?;
@@ -813,6 +2338,76 @@ async fn expand_breakpoint_template(
     }
 }
 
+/// A function call with a breakpoint after its execute prefix.
+struct AfterExecuteCall<'l> {
+    position: Position,
+    kind: &'l BreakpointKind,
+    line: &'l str,
+    column_index: usize,
+    called_fn: &'l ResourceLocation,
+}
+
+/// A breakpoint after the execute prefix of a function call is checked by `suspend_after_execute`
+/// for every executor before the called function is entered. When resuming, the called function is
+/// entered for the current executor.
+async fn expand_after_execute_templates(
+    engine: &TemplateEngine<'_>,
+    output_path: &Path,
+    sink: &dyn FileSink,
+    call: &AfterExecuteCall<'_>,
+) -> io::Result<()> {
+    let AfterExecuteCall {
+        position,
+        kind,
+        line,
+        column_index,
+        called_fn,
+    } = call;
+    let position_string = position.to_string();
+    #[rustfmt::skip]
+    macro_rules! PATH { () => {"data/-ns-/functions/-orig_ns-/-orig/fn-/continue_current_iteration_at_-position-.mcfunction"} }
+    let path = output_path.join(
+        engine
+            .extend([("-position-", position_string.as_str())])
+            .expand(PATH!()),
+    );
+    let callee_position = Position {
+        line_number: 0,
+        position_in_line: PositionInLine::Entry,
+    }
+    .to_string();
+    let callee_engine = engine
+        .extend_orig_name(called_fn)
+        .extend([("-position-", callee_position.as_str())]);
+    sink.write(path, callee_engine.expand(include_template!(PATH!())))
+        .await?;
+
+    let reason = match kind {
+        BreakpointKind::Normal | BreakpointKind::Temporary => Some(StoppedReason::Breakpoint),
+        // The condition of the step is checked in suspend_after_execute.mcfunction
+        BreakpointKind::Step { .. } => Some(StoppedReason::Step),
+        BreakpointKind::Invalid | BreakpointKind::Continue => None,
+    };
+    if let Some(reason) = reason {
+        let column = find_run_index(line, *column_index).unwrap_or(*column_index) + 1;
+        expand_breakpoint_template(engine, output_path, sink, position, reason, column, None)
+            .await?;
+    }
+    Ok(())
+}
+
+/// The dimensions that are explicitly entered again when resuming a function in
+/// `continue_at_-position-.mcfunction`. The score of a dimension is its index plus one. Other
+/// dimensions get the score 0 and rely on the dimension of the context entity.
+const DIMENSIONS: [&str; 3] = ["overworld", "the_nether", "the_end"];
+
+fn get_dimension_score(dimension: &ResourceLocation) -> usize {
+    DIMENSIONS
+        .iter()
+        .position(|it| dimension.namespace() == "minecraft" && dimension.path() == *it)
+        .map_or(0, |index| index + 1)
+}
+
 fn get_fn_score_holder(
     fn_name: &ResourceLocation,
     fn_ids: &HashMap<&ResourceLocation, usize>,
@@ -833,6 +2428,7 @@ fn get_fn_score_holder(
 async fn write_functions_txt(
     fn_names: impl IntoIterator<Item = &ResourceLocation>,
     output_path: impl AsRef<Path>,
+    sink: &dyn FileSink,
 ) -> io::Result<()> {
     let path = output_path.as_ref().join("functions.txt");
     let content = fn_names
@@ -840,14 +2436,17 @@ async fn write_functions_txt(
         .map(|it| it.to_string())
         .collect::<Vec<_>>()
         .join("\n");
-    write(&path, content).await?;
+    sink.write(path, content).await?;
 
     Ok(())
 }
 
-async fn create_parent_dir(path: impl AsRef<Path>) -> io::Result<()> {
+async fn create_parent_dir(sink: &dyn FileSink, path: impl AsRef<Path>) -> io::Result<()> {
     if let Some(parent_dir) = path.as_ref().parent() {
-        create_dir_all(parent_dir).await?;
+        sink.create_dir_all(parent_dir.to_path_buf()).await?;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests;
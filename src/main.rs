@@ -16,17 +16,53 @@
 // You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use clap::{crate_authors, crate_version, App, Arg};
-use log::LevelFilter;
-use mcfunction_debugger::{config::Config, generate_debug_datapack};
+use clap::{crate_authors, crate_version, App, Arg, ArgMatches};
+use log::{error, info, warn, Level, LevelFilter, Log, Metadata, Record};
+use mcfunction_debugger::{
+    archive::{is_file, join_datapack_path},
+    config::{
+        adapter::{breakpoints_from_json, AdapterConfig, SuspendSchedules},
+        max_namespace_length, Config, ConfigFile, CONFIG_FILE_NAME, DEFAULT_MAX_COMMANDS,
+    },
+    diff_debug_datapack, generate_debug_datapack_with_sink, generate_launch_config, list_functions,
+    parser::command::{
+        bundled_minecraft_versions, resource_location::ResourceLocationRef, CommandParser,
+    },
+    sink::{CountingSink, DryRunSink, FileSink, FsSink, WriteFailures},
+    utils::diff::{diff_lines, format_unified_diff},
+    validate_datapack,
+    watch::{watch_debug_datapack, DEFAULT_DEBOUNCE_DURATION},
+    DatapackDiff,
+};
+use multimap::MultiMap;
+use serde::Serialize;
 use simple_logger::SimpleLogger;
-use std::{io, path::Path};
+use std::{
+    collections::BTreeSet, convert::TryFrom, env::current_dir, ffi::OsStr, io, path::Path,
+    sync::Mutex, time::Duration,
+};
+use tokio::fs::{read_to_string, write};
 
 const INPUT_ARG: &str = "datapack";
 const OUTPUT_ARG: &str = "output";
 const NAMESPACE_ARG: &str = "namespace";
 const SHADOW_ARG: &str = "shadow";
+const SKIP_INVALID_ARG: &str = "skip-invalid";
+const EXCLUDE_ARG: &str = "exclude";
+const MAX_COMMANDS_ARG: &str = "max-commands";
+const MINECRAFT_VERSION_ARG: &str = "minecraft-version";
+const WATCH_ARG: &str = "watch";
+const DRY_RUN_ARG: &str = "dry-run";
+const DIFF_ARG: &str = "diff";
+const VALIDATE_ARG: &str = "validate";
+const LIST_FUNCTIONS_ARG: &str = "list-functions";
+const GENERATE_LAUNCH_CONFIG_ARG: &str = "generate-launch-config";
+const OUTPUT_FORMAT_ARG: &str = "output-format";
+const SUMMARY_JSON_ARG: &str = "summary-json";
 const LOG_LEVEL_ARG: &str = "log-level";
+const ADAPTER_LISTENER_ARG: &str = "adapter-listener";
+const BREAKPOINTS_JSON_ARG: &str = "breakpoints-json";
+const SUSPEND_SCHEDULES_ARG: &str = "suspend-schedules";
 
 // Copy of private field log::LOG_LEVEL_NAMES
 const LOG_LEVEL_NAMES: [&str; 6] = ["OFF", "ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
@@ -40,7 +76,15 @@ const LOG_LEVELS: [LevelFilter; 6] = [
 ];
 
 #[tokio::main]
-async fn main() -> io::Result<()> {
+async fn main() {
+    let default_max_commands = DEFAULT_MAX_COMMANDS.to_string();
+    let minecraft_version_long_help = format!(
+        "The commands of the datapack are parsed with the grammar of this Minecraft version, for \
+        example to recognize the 'return' command of Minecraft 1.20. Grammars are bundled for \
+        the versions {}. For other versions a warning is logged and the nearest bundled version \
+        is used. Defaults to the latest bundled version.",
+        bundled_minecraft_versions().collect::<Vec<_>>().join(", ")
+    );
     let matches = App::new("mcfunction-debugger")
         .version(crate_version!())
         .version_short("v")
@@ -74,16 +118,17 @@ See the GNU General Public License for more details.
         )
         .arg(
             Arg::with_name(INPUT_ARG)
-                .help("The input datapack directory.")
+                .help("The input datapack directory or zip file.")
                 .long_help(
-                    "The datapack to generate a debug datapack for. Must be a directory containing \
-                    a pack.mcmeta file, for example: \
-                    '%APPDATA%\\.minecraft\\saves\\Your-World\\datapacks\\my-datapack'.",
+                    "The datapack to generate a debug datapack for. Must be a directory or zip \
+                    file containing a pack.mcmeta file, for example: \
+                    '%APPDATA%\\.minecraft\\saves\\Your-World\\datapacks\\my-datapack'. A \
+                    zip file is only read, the debug datapack is always a directory. Can be \
+                    omitted if it is specified in a config file in the current directory.",
                 )
                 .long("input")
                 .value_name("DATAPACK")
-                .takes_value(true)
-                .required(true),
+                .takes_value(true),
         )
         .arg(
             Arg::with_name(OUTPUT_ARG)
@@ -92,12 +137,12 @@ See the GNU General Public License for more details.
                     "The directory that should become the generated debug datapack. On Windows \
                     this is typically a directory in the datapacks directory of your world, for \
                     example: \
-                    '%APPDATA%\\.minecraft\\saves\\Your-World\\datapacks\\debug-my-datapack'.",
+                    '%APPDATA%\\.minecraft\\saves\\Your-World\\datapacks\\debug-my-datapack'. \
+                    Can be omitted if it is specified in a config file.",
                 )
                 .long("output")
                 .value_name("DATAPACK")
-                .takes_value(true)
-                .required(true),
+                .takes_value(true),
         )
         .arg(
             Arg::with_name(NAMESPACE_ARG)
@@ -105,7 +150,8 @@ See the GNU General Public License for more details.
                 .long_help(
                     "The namespace is used for all internal functions in the generated datapack \
                     and as a prefix for all scoreboard objectives and tags. By specifying a \
-                    different namespace with max. 7 characters you can avoid name clashes. The \
+                    different namespace you can avoid name clashes. It must be short enough for \
+                    all scoreboard objectives to have at most 16 characters. The \
                     generated functions in the 'debug' namespace such as 'debug:resume' and \
                     'debug:uninstall' are unaffected by this option.",
                 )
@@ -114,11 +160,13 @@ See the GNU General Public License for more details.
                 .takes_value(true)
                 .default_value("mcfd")
                 .validator(|namespace| {
-                    if namespace.len() <= 7 {
-                        // max len of identifiers 16 => scoreboard {}_Duration has 9 characters -> 7 remaining for namespace
+                    if namespace.len() <= max_namespace_length() {
                         return Ok(());
                     }
-                    Err(String::from("string must have <= 7 characters"))
+                    Err(format!(
+                        "string must have <= {} characters",
+                        max_namespace_length()
+                    ))
                 }),
         )
         .arg(
@@ -130,7 +178,8 @@ See the GNU General Public License for more details.
                 .long_help(
                     "When this is true the generated datapack will additionally contain functions \
                     with the same name as the functions in the input datapack. These functions \
-                    will simply forward to the appropriate function in the 'debug' namespace. When \
+                    will simply forward to the appropriate function in the 'debug' namespace, \
+                    unless the debugger was uninstalled with 'function debug:uninstall'. When \
                     using this make sure to disable the input datapack to avoid name clashes.\n\n\
                     This can be helpful when executing a function from a command block, because \
                     you don't have to change the function call to debug the function. Note however \
@@ -144,6 +193,174 @@ See the GNU General Public License for more details.
                 )
                 .long("shadow"),
         )
+        .arg(
+            Arg::with_name(SKIP_INVALID_ARG)
+                .help("Whether to skip function files with invalid names instead of failing.")
+                .long_help(
+                    "Minecraft only loads functions whose name consists of the characters a-z, \
+                    0-9, '_', '-', '.' and '/'. By default the generation fails if a function file \
+                    has a different name, for example because it contains an uppercase letter. \
+                    When this is set to true these files are left out of the debug datapack and \
+                    only reported as warnings.",
+                )
+                .long("skip-invalid"),
+        )
+        .arg(
+            Arg::with_name(EXCLUDE_ARG)
+                .help("A pattern of functions that should not be debugged.")
+                .long_help(
+                    "Functions matching this pattern are not instrumented by the debugger. Calls \
+                    to these functions are executed atomically, so the debugger can't suspend \
+                    inside them. The pattern is matched against the full function name \
+                    ('namespace:path'). A '*' matches any characters except '/', a '**' also \
+                    matches '/' and a '?' matches a single character except '/'. For example: \
+                    'smithed*:**'. Can be specified multiple times.",
+                )
+                .long("exclude")
+                .value_name("PATTERN")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name(MAX_COMMANDS_ARG)
+                .help("The maximum number of commands in a generated function.")
+                .long_help(
+                    "Minecraft does not run functions with more commands than its gamerule \
+                    'maxCommandChainLength'. Generated functions with more commands are split into \
+                    multiple functions where possible. Functions with more consecutive commands \
+                    than this between two function calls or breakpoints can't be split, a warning \
+                    is logged for them.",
+                )
+                .long("max-commands")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value(&default_max_commands)
+                .validator(|max_commands| match max_commands.parse::<usize>() {
+                    Ok(max_commands) if max_commands > 0 => Ok(()),
+                    _ => Err(String::from("must be a positive number")),
+                }),
+        )
+        .arg(
+            Arg::with_name(MINECRAFT_VERSION_ARG)
+                .help("The Minecraft version that the datapack is written for.")
+                .long_help(&minecraft_version_long_help)
+                .long("minecraft-version")
+                .value_name("VERSION")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(WATCH_ARG)
+                .help("Whether to regenerate the debug datapack when a function changes.")
+                .long_help(
+                    "When this is true the input datapack is watched after generating the debug \
+                    datapack. Whenever an *.mcfunction file changes, the debug datapack is \
+                    regenerated. Only the files of changed functions are written again. Errors \
+                    during a regeneration are reported without stopping to watch. Stop watching \
+                    with Ctrl+C.",
+                )
+                .long("watch"),
+        )
+        .arg(
+            Arg::with_name(DRY_RUN_ARG)
+                .help("Whether to print the generated files instead of writing them.")
+                .long_help(
+                    "When this is true the file system is not changed. Instead the path and the \
+                    first lines of every file that would be written are printed, as well as every \
+                    file that would be removed.",
+                )
+                .long("dry-run")
+                .conflicts_with_all(&[WATCH_ARG, SUMMARY_JSON_ARG]),
+        )
+        .arg(
+            Arg::with_name(DIFF_ARG)
+                .help("Whether to print what regenerating the debug datapack would change.")
+                .long_help(
+                    "When this is true the file system is not changed. Instead the debug datapack \
+                    is generated in memory and compared with the one at the output path. Added, \
+                    removed and changed files are printed, for changed *.mcfunction files with \
+                    the changed lines. The exit code is 1 if anything would change.",
+                )
+                .long("diff")
+                .conflicts_with_all(&[WATCH_ARG, DRY_RUN_ARG, SUMMARY_JSON_ARG]),
+        )
+        .arg(
+            Arg::with_name(VALIDATE_ARG)
+                .help("Whether to only check if the datapack can be debugged.")
+                .long_help(
+                    "When this is true no debug datapack is generated. Instead the input datapack \
+                    is checked for problems that prevent debugging it, such as calls to missing \
+                    functions or a namespace that clashes with the datapack. All problems are \
+                    logged and the exit code is 1 if there are any errors.",
+                )
+                .long("validate")
+                .conflicts_with_all(&[WATCH_ARG, SUMMARY_JSON_ARG]),
+        )
+        .arg(
+            Arg::with_name(LIST_FUNCTIONS_ARG)
+                .help("Whether to only list the functions that can be debugged.")
+                .long_help(
+                    "When this is true no debug datapack is generated. Instead all functions of \
+                    the input datapack that can be debugged are printed in alphabetical order, \
+                    one per line. With '--output-format json' a JSON array is printed that also \
+                    contains the line numbers of the '# breakpoint' lines and the called functions \
+                    of each function, for example: \
+                    [{\"function\":\"ns:path\",\"breakpoints\":[3,7],\"calls\":[\"ns:other\"]}]",
+                )
+                .long("list-functions")
+                .conflicts_with_all(&[VALIDATE_ARG, WATCH_ARG, SUMMARY_JSON_ARG]),
+        )
+        .arg(
+            Arg::with_name(GENERATE_LAUNCH_CONFIG_ARG)
+                .help("Whether to only print a launch configuration for VS Code.")
+                .long_help(
+                    "When this is true no debug datapack is generated. Instead a JSON object is \
+                    printed that can be added to the 'configurations' of a '.vscode/launch.json' \
+                    file to debug the input datapack. The launched function is the function with \
+                    the most '# breakpoint' lines. The world and log file of Minecraft are \
+                    included if they can be detected.",
+                )
+                .long("generate-launch-config")
+                .conflicts_with_all(&[
+                    VALIDATE_ARG,
+                    WATCH_ARG,
+                    SUMMARY_JSON_ARG,
+                    LIST_FUNCTIONS_ARG,
+                ]),
+        )
+        .arg(
+            Arg::with_name(OUTPUT_FORMAT_ARG)
+                .help("The format of the output.")
+                .long_help(
+                    "With 'json' all log messages are collected and a single JSON document is \
+                    printed at the end, for example: \
+                    {\"status\":\"ok\",\"files_written\":42,\"warnings\":[],\"log\":[]} or \
+                    {\"status\":\"error\",\"error\":\"Could not find pack.mcmeta\",...}. \
+                    The exit code is 1 if the status is 'error'. This can't be used with \
+                    '--watch' or '--dry-run'. With '--list-functions' only the functions are \
+                    printed as JSON.",
+                )
+                .long("output-format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .default_value("text"),
+        )
+        .arg(
+            Arg::with_name(SUMMARY_JSON_ARG)
+                .help("A file to write a JSON summary of the generated datapack to.")
+                .long_help(
+                    "After generating the debug datapack a JSON document is written to this file. \
+                    It contains the name of the generated datapack, its namespace and all debugged \
+                    functions with their number of breakpoints and the function to start debugging \
+                    them with. It also lists all called functions that are missing in the input \
+                    datapack and all functions with more consecutive commands than \
+                    '--max-commands'.",
+                )
+                .long("summary-json")
+                .value_name("PATH")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name(LOG_LEVEL_ARG)
                 .long_help(
@@ -157,29 +374,348 @@ See the GNU General Public License for more details.
                 .possible_values(&LOG_LEVEL_NAMES)
                 .default_value(LevelFilter::Info.as_str()),
         )
+        // Used by the debug adapter to run the generator out of process
+        .arg(
+            Arg::with_name(ADAPTER_LISTENER_ARG)
+                .long("adapter-listener")
+                .value_name("NAME")
+                .takes_value(true)
+                .hidden(true),
+        )
+        .arg(
+            Arg::with_name(BREAKPOINTS_JSON_ARG)
+                .long("breakpoints-json")
+                .value_name("PATH")
+                .takes_value(true)
+                .requires(ADAPTER_LISTENER_ARG)
+                .hidden(true),
+        )
+        .arg(
+            Arg::with_name(SUSPEND_SCHEDULES_ARG)
+                .long("suspend-schedules")
+                .value_name("MODE")
+                .takes_value(true)
+                .possible_values(&["all", "callStackOnly", "none"])
+                .requires(ADAPTER_LISTENER_ARG)
+                .hidden(true),
+        )
         .get_matches();
-    let input_path = Path::new(matches.value_of(INPUT_ARG).unwrap());
-    let output_path = Path::new(matches.value_of(OUTPUT_ARG).unwrap());
-    let namespace = matches.value_of(NAMESPACE_ARG).unwrap();
-    let shadow = matches.is_present(SHADOW_ARG);
+    let json_output = matches.value_of(OUTPUT_FORMAT_ARG) == Some("json");
     let log_level = parse_log_level(matches.value_of(LOG_LEVEL_ARG).unwrap()).unwrap();
 
-    SimpleLogger::new().with_level(log_level).init().unwrap();
+    if json_output {
+        log::set_logger(&JsonLogger).unwrap();
+        log::set_max_level(log_level);
+    } else {
+        SimpleLogger::new().with_level(log_level).init().unwrap();
+    }
 
-    let pack_mcmeta_path = input_path.join("pack.mcmeta");
-    assert!(pack_mcmeta_path.is_file(), "Could not find pack.mcmeta");
+    let result = run(&matches).await;
+    if json_output {
+        // The functions are printed as a JSON array instead
+        if result.is_ok()
+            && (matches.is_present(LIST_FUNCTIONS_ARG)
+                || matches.is_present(GENERATE_LAUNCH_CONFIG_ARG))
+        {
+            return;
+        }
+        let log = std::mem::take(&mut *JSON_LOG.lock().unwrap());
+        let warnings = log
+            .iter()
+            .filter(|entry| entry.level == Level::Warn.as_str())
+            .map(|entry| entry.message.clone())
+            .collect();
+        let (status, files_written, error) = match &result {
+            Ok(files_written) => ("ok", *files_written, None),
+            Err(e) => ("error", None, Some(e.to_string())),
+        };
+        let output = JsonOutput {
+            status,
+            files_written,
+            error,
+            warnings,
+            log,
+        };
+        println!("{}", serde_json::to_string(&output).unwrap());
+    } else if let Err(e) = &result {
+        match WriteFailures::find(e) {
+            Some(write_failures) => {
+                for failure in &write_failures.failures {
+                    error!("Failed to write {}", failure.error);
+                }
+                error!("Failed to write {} files", write_failures.failures.len());
+            }
+            None => error!("{}", e),
+        }
+    }
+    if result.is_err() {
+        std::process::exit(1);
+    }
+}
+
+/// Runs the command line interface and returns the number of written files if a debug datapack
+/// was generated.
+async fn run(matches: &ArgMatches<'_>) -> io::Result<Option<usize>> {
+    let cli_input_path = matches.value_of(INPUT_ARG).map(Path::new);
+    let current_dir = current_dir()?;
+    let config_file_dirs = cli_input_path.into_iter().chain([current_dir.as_path()]);
+    let config_file = match ConfigFile::find(config_file_dirs) {
+        Some(path) => {
+            info!("Using config file {}", path.display());
+            ConfigFile::load(path)?
+        }
+        None => ConfigFile::default(),
+    };
+
+    let input_path = cli_input_path
+        .or(config_file.input.as_deref())
+        .ok_or_else(|| missing_argument("--input"))?;
+    let output_path = matches
+        .value_of(OUTPUT_ARG)
+        .map(Path::new)
+        .or(config_file.output.as_deref());
+    let namespace = explicit_value(matches, NAMESPACE_ARG)
+        .or(config_file.namespace.as_deref())
+        .unwrap_or_else(|| matches.value_of(NAMESPACE_ARG).unwrap());
+    let shadow = matches.is_present(SHADOW_ARG) || config_file.shadow.unwrap_or(false);
+    let skip_invalid_functions = matches.is_present(SKIP_INVALID_ARG);
+    let exclude = match matches.values_of(EXCLUDE_ARG) {
+        Some(values) => values.map(String::from).collect::<Vec<_>>(),
+        None => config_file.exclude_patterns.clone().unwrap_or_default(),
+    };
+    let excluded_functions = config_file
+        .excluded_functions
+        .iter()
+        .flatten()
+        .map(|function| {
+            ResourceLocationRef::try_from(function.as_str())
+                .map(|function| function.to_owned())
+                .map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Invalid function in {}: {}", CONFIG_FILE_NAME, function),
+                    )
+                })
+        })
+        .collect::<io::Result<BTreeSet<_>>>()?;
+    let max_commands = match explicit_value(matches, MAX_COMMANDS_ARG) {
+        Some(max_commands) => max_commands.parse().unwrap(),
+        None => config_file.max_commands.unwrap_or(DEFAULT_MAX_COMMANDS),
+    };
+    let minecraft_version = matches
+        .value_of(MINECRAFT_VERSION_ARG)
+        .or(config_file.minecraft_version.as_deref());
+    let debounce = config_file
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_DEBOUNCE_DURATION);
+    let watch = matches.is_present(WATCH_ARG);
+    let dry_run = matches.is_present(DRY_RUN_ARG);
+    let diff = matches.is_present(DIFF_ARG);
+    let validate = matches.is_present(VALIDATE_ARG);
+    let list = matches.is_present(LIST_FUNCTIONS_ARG);
+    let launch_config = matches.is_present(GENERATE_LAUNCH_CONFIG_ARG);
+    let json_output = matches.value_of(OUTPUT_FORMAT_ARG) == Some("json");
+    let summary_json_path = matches.value_of(SUMMARY_JSON_ARG).map(Path::new);
+    let adapter_listener_name = matches.value_of(ADAPTER_LISTENER_ARG);
+    let breakpoints = match matches.value_of(BREAKPOINTS_JSON_ARG) {
+        Some(path) => breakpoints_from_json(&read_to_string(path).await?)?,
+        None => MultiMap::new(),
+    };
+    let suspend_schedules = matches
+        .value_of(SUSPEND_SCHEDULES_ARG)
+        .map(|mode| mode.parse::<SuspendSchedules>().unwrap()) // Validated by possible_values
+        .unwrap_or_default();
+
+    if json_output && (watch || dry_run || diff) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The output format json can't be used with --watch, --dry-run or --diff",
+        ));
+    }
+
+    let pack_mcmeta_path = join_datapack_path(&input_path, "pack.mcmeta");
+    if !is_file(pack_mcmeta_path) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Could not find pack.mcmeta",
+        ));
+    }
 
     let config = Config {
         namespace,
         shadow,
-        adapter: None,
+        exclude: &exclude,
+        excluded_functions,
+        skip_invalid_functions,
+        additional_datapacks: &[],
+        max_commands,
+        adapter: adapter_listener_name.map(|adapter_listener_name| AdapterConfig {
+            adapter_listener_name,
+            breakpoints: &breakpoints,
+            suspend_schedules,
+        }),
     };
-    generate_debug_datapack(input_path, output_path, &config).await?;
+    let parser = match minecraft_version {
+        Some(version) => CommandParser::for_version(version),
+        None => CommandParser::default(),
+    }
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    if list {
+        let functions = list_functions(input_path, &parser, &config).await?;
+        if json_output {
+            println!("{}", serde_json::to_string(&functions)?);
+        } else {
+            for function in functions {
+                println!("{}", function.function);
+            }
+        }
+        return Ok(None);
+    }
+    if launch_config {
+        let launch_config = generate_launch_config(input_path, &parser, &config).await?;
+        println!("{}", serde_json::to_string_pretty(&launch_config)?);
+        return Ok(None);
+    }
+    if validate {
+        let report = validate_datapack(input_path, &parser, &config).await?;
+        for issue in &report.errors {
+            error!("{}", issue);
+        }
+        for issue in &report.warnings {
+            warn!("{}", issue);
+        }
+        if report.has_errors() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} can't be debugged", input_path.display()),
+            ));
+        }
+        info!("{} can be debugged", input_path.display());
+        return Ok(None);
+    }
+    let output_path = output_path.ok_or_else(|| missing_argument("--output"))?;
+    if diff {
+        let diff = diff_debug_datapack(input_path, output_path, &parser, &config).await?;
+        print_datapack_diff(&diff);
+        if !diff.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("{} is not up to date", output_path.display()),
+            ));
+        }
+        info!("{} is up to date", output_path.display());
+        return Ok(None);
+    }
+    let dry_run_sink = DryRunSink::stdout();
+    let sink: &dyn FileSink = if dry_run { &dry_run_sink } else { &FsSink };
+    let sink = CountingSink::new(sink);
+    let summary =
+        generate_debug_datapack_with_sink(input_path, output_path, &parser, &config, &sink).await?;
+    if let Some(summary_json_path) = summary_json_path {
+        let json = serde_json::to_string_pretty(&summary)?;
+        write(summary_json_path, json).await?;
+    }
+    for function in &summary.oversized_functions {
+        warn!(
+            "Function {} has more than {} consecutive commands without a function call or \
+            breakpoint. Minecraft may not run it completely.",
+            function, max_commands
+        );
+    }
+    for warning in &summary.warnings {
+        warn!("{}", warning);
+    }
+    if shadow {
+        warn!(
+            "The functions of {} are shadowed by {}. Make sure to disable {0} in Minecraft, \
+            otherwise its functions are executed instead of the debug functions.",
+            input_path.display(),
+            output_path.display()
+        );
+    }
+    if watch {
+        watch_debug_datapack(input_path, output_path, &parser, &config, debounce).await?;
+    }
+
+    Ok(Some(sink.files_written()))
+}
+
+fn print_datapack_diff(diff: &DatapackDiff) {
+    for path in diff.added.keys() {
+        println!("Added {}", path.display());
+    }
+    for path in &diff.removed {
+        println!("Removed {}", path.display());
+    }
+    for (path, (old, new)) in &diff.changed {
+        println!("Changed {}", path.display());
+        if path.extension() == Some(OsStr::new("mcfunction")) {
+            print!("{}", format_unified_diff(&diff_lines(old, new), 3));
+        }
+    }
+}
+
+/// Returns the value of an argument only if it was given on the command line, so that values from
+/// the config file take precedence over default values.
+fn explicit_value<'l>(matches: &'l ArgMatches<'_>, name: &str) -> Option<&'l str> {
+    if matches.occurrences_of(name) > 0 {
+        matches.value_of(name)
+    } else {
+        None
+    }
+}
 
-    Ok(())
+fn missing_argument(argument: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "Missing {}, specify it on the command line or in {}",
+            argument, CONFIG_FILE_NAME
+        ),
+    )
 }
 
 fn parse_log_level(log_level: &str) -> Option<LevelFilter> {
     let index = LOG_LEVEL_NAMES.iter().position(|&it| it == log_level)?;
     Some(LOG_LEVELS[index])
 }
+
+static JSON_LOG: Mutex<Vec<LogEntry>> = Mutex::new(Vec::new());
+
+/// Collects all log messages, so they can be printed as part of the JSON output.
+struct JsonLogger;
+
+impl Log for JsonLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        JSON_LOG.lock().unwrap().push(LogEntry {
+            level: record.level().as_str(),
+            message: record.args().to_string(),
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+#[derive(Serialize)]
+struct LogEntry {
+    level: &'static str,
+    message: String,
+}
+
+/// The document that is printed with '--output-format json'.
+#[derive(Serialize)]
+struct JsonOutput {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files_written: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    warnings: Vec<String>,
+    log: Vec<LogEntry>,
+}
@@ -43,6 +43,19 @@ pub fn parse_quoted_string(string: &str, quote: char) -> Result<(&str, usize), S
     Ok((string, quote.len_utf8() + len))
 }
 
+/// Writes a string so that [parse_possibly_quoted_string] returns it again. Like the parsed string,
+/// `string` is expected to contain escape sequences rather than escaped characters, so it is quoted
+/// unchanged.
+pub fn fmt_possibly_quoted_string(f: &mut impl std::fmt::Write, string: &str) -> std::fmt::Result {
+    if !string.is_empty() && string.chars().all(is_allowed_in_unquoted_string) {
+        f.write_str(string)
+    } else if string.contains('"') && !string.contains('\'') {
+        write!(f, "'{}'", string)
+    } else {
+        write!(f, "\"{}\"", string)
+    }
+}
+
 pub fn is_quote(c: char) -> bool {
     c == '"' || c == '\''
 }
@@ -20,8 +20,8 @@ use crate::{
     parser::command::{
         argument::{
             brigadier::{
-                expect, parse_bool, parse_double, parse_integer, parse_possibly_quoted_string,
-                parse_unquoted_string,
+                expect, fmt_possibly_quoted_string, parse_bool, parse_double, parse_integer,
+                parse_possibly_quoted_string, parse_unquoted_string,
             },
             minecraft::{nbt::CompoundNbt, range::MinecraftRange},
         },
@@ -30,7 +30,10 @@ use crate::{
     utils::Map0,
 };
 use log::warn;
-use std::{collections::BTreeMap, fmt::Display};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write},
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum MinecraftEntity<'l> {
@@ -283,6 +286,89 @@ impl<'l> MinecraftSelector<'l> {
     }
 }
 
+impl Display for MinecraftSelector<'_> {
+    /// Writes the selector so that [MinecraftSelector::parse] returns an equal selector. Options
+    /// are written in a fixed order and options that are not set are omitted.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut options = Vec::new();
+        if let Some(name) = &self.name {
+            let mut option = format!("name={}", if name.inverted { "!" } else { "" });
+            fmt_possibly_quoted_string(&mut option, name.string)?;
+            options.push(option);
+        }
+        if let Some(distance) = &self.distance {
+            options.push(format!("distance={}", distance));
+        }
+        if let Some(level) = &self.level {
+            options.push(format!("level={}", level));
+        }
+        for (key, value) in [
+            ("x", self.x),
+            ("y", self.y),
+            ("z", self.z),
+            ("dx", self.dx),
+            ("dy", self.dy),
+            ("dz", self.dz),
+        ] {
+            if let Some(value) = value {
+                options.push(format!("{}={}", key, value));
+            }
+        }
+        if let Some(x_rotation) = &self.x_rotation {
+            options.push(format!("x_rotation={}", x_rotation));
+        }
+        if let Some(y_rotation) = &self.y_rotation {
+            options.push(format!("y_rotation={}", y_rotation));
+        }
+        if let Some(limit) = self.limit {
+            options.push(format!("limit={}", limit));
+        }
+        if let Some(sort) = self.sort {
+            options.push(format!("sort={}", sort));
+        }
+        if let Some(gamemode) = &self.gamemode {
+            options.push(format!("gamemode={}", gamemode));
+        }
+        if let Some(team) = &self.team {
+            options.push(format!("team={}", team));
+        }
+        if let Some(entity_type) = &self.entity_type {
+            options.push(format!("type={}", entity_type));
+        }
+        for tag in &self.tags {
+            options.push(format!("tag={}", tag));
+        }
+        for nbt in &self.nbts {
+            options.push(format!("nbt={}", nbt));
+        }
+        if !self.scores.is_empty() {
+            let scores = self
+                .scores
+                .iter()
+                .map(|(objective, range)| format!("{}={}", objective, range))
+                .collect::<Vec<_>>();
+            options.push(format!("scores={{{}}}", scores.join(",")));
+        }
+        if !self.advancements.is_empty() {
+            let advancements = self
+                .advancements
+                .iter()
+                .map(|(advancement, progress)| format!("{}={}", advancement, progress))
+                .collect::<Vec<_>>();
+            options.push(format!("advancements={{{}}}", advancements.join(",")));
+        }
+        for predicate in &self.predicates {
+            options.push(format!("predicate={}", predicate));
+        }
+
+        write!(f, "@{}", self.selector_type)?;
+        if !options.is_empty() {
+            write!(f, "[{}]", options.join(","))?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum MinecraftSelectorType {
     A,
@@ -309,6 +395,18 @@ impl MinecraftSelectorType {
     }
 }
 
+impl Display for MinecraftSelectorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_char(match self {
+            Self::A => 'a',
+            Self::E => 'e',
+            Self::P => 'p',
+            Self::R => 'r',
+            Self::S => 's',
+        })
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct InvertableString<'l> {
     inverted: bool,
@@ -343,6 +441,16 @@ impl<'l> InvertableString<'l> {
     }
 }
 
+/// Writes the string unquoted, which is only correct for options that are parsed unquoted.
+impl Display for InvertableString<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.inverted {
+            f.write_char('!')?;
+        }
+        f.write_str(self.string)
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct EntityType<'l> {
     inverted: bool,
@@ -367,6 +475,18 @@ impl<'l> EntityType<'l> {
     }
 }
 
+impl Display for EntityType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.inverted {
+            f.write_char('!')?;
+        }
+        if self.tag {
+            f.write_char('#')?;
+        }
+        self.resource_location.fmt(f)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct InvertableCompoundNbt {
     inverted: bool,
@@ -385,6 +505,15 @@ impl InvertableCompoundNbt {
     }
 }
 
+impl Display for InvertableCompoundNbt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.inverted {
+            f.write_char('!')?;
+        }
+        self.nbt.fmt(f)
+    }
+}
+
 fn parse_prefix(string: &str, prefix: char) -> (bool, &str) {
     let suffix = string.strip_prefix(prefix);
     (suffix.is_some(), suffix.unwrap_or(string).trim_start())
@@ -488,6 +617,21 @@ impl<'l> MinecraftAdvancementProgress<'l> {
     }
 }
 
+impl Display for MinecraftAdvancementProgress<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AdvancementProgress(done) => done.fmt(f),
+            Self::CriterionProgress(criteria) => {
+                let criteria = criteria
+                    .iter()
+                    .map(|(criterion, done)| format!("{}={}", criterion, done))
+                    .collect::<Vec<_>>();
+                write!(f, "{{{}}}", criteria.join(","))
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct InvertablePredicate<'l> {
     inverted: bool,
@@ -509,5 +653,16 @@ impl<'l> InvertablePredicate<'l> {
     }
 }
 
+impl Display for InvertablePredicate<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.inverted {
+            f.write_char('!')?;
+        }
+        self.predicate.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod proptests;
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,226 @@
+use super::*;
+use ::nbt::{Map, Value};
+use proptest::{collection::btree_map, collection::vec, option, prelude::*};
+use std::{convert::TryFrom, fmt::Debug};
+
+fn leak(string: String) -> &'static str {
+    Box::leak(string.into_boxed_str())
+}
+
+fn unquoted_string() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9_.+-]{0,8}"
+}
+
+fn key() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9_.+-]{1,8}"
+}
+
+/// The content of a quoted string, including escape sequences, as returned by the parser.
+fn quoted_string_content() -> impl Strategy<Value = String> {
+    vec(
+        prop_oneof![
+            "[a-zA-Z0-9 _.+-]",
+            Just(r"\\".to_string()),
+            Just(r#"\""#.to_string())
+        ],
+        0..8,
+    )
+    .prop_map(|parts| parts.concat())
+}
+
+fn finite_f64() -> impl Strategy<Value = f64> + Clone {
+    prop::num::f64::NORMAL | prop::num::f64::SUBNORMAL | prop::num::f64::ZERO
+}
+
+fn finite_f32() -> impl Strategy<Value = f32> + Clone {
+    prop::num::f32::NORMAL | prop::num::f32::SUBNORMAL | prop::num::f32::ZERO
+}
+
+fn range<N: Clone + Debug>(
+    number: impl Strategy<Value = N> + Clone,
+) -> impl Strategy<Value = MinecraftRange<N>> {
+    prop_oneof![
+        number.clone().prop_map(|n| MinecraftRange {
+            min: Some(n.clone()),
+            max: Some(n),
+        }),
+        (option::of(number.clone()), option::of(number))
+            .prop_filter("Empty range", |(min, max)| min.is_some() || max.is_some())
+            .prop_map(|(min, max)| MinecraftRange { min, max }),
+    ]
+}
+
+fn resource_location() -> impl Strategy<Value = ResourceLocationRef<&'static str>> {
+    (option::of("[a-z0-9_.-]{1,8}"), "[a-z0-9_./-]{1,8}").prop_map(|(namespace, path)| {
+        let string = match namespace {
+            Some(namespace) => format!("{}:{}", namespace, path),
+            None => path,
+        };
+        ResourceLocationRef::try_from(leak(string)).unwrap()
+    })
+}
+
+fn invertable_unquoted_string() -> impl Strategy<Value = InvertableString<'static>> {
+    (any::<bool>(), unquoted_string()).prop_map(|(inverted, string)| InvertableString {
+        inverted,
+        string: leak(string),
+    })
+}
+
+/// Arrays are not generated, because [CompoundNbt::parse] does not support them yet. Numbers are
+/// not generated, because they are parsed as strings.
+fn nbt_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![unquoted_string(), quoted_string_content()].prop_map(Value::String);
+    leaf.prop_recursive(3, 16, 4, |value| {
+        prop_oneof![
+            vec(value.clone(), 0..4).prop_map(Value::List),
+            nbt_compound(value).prop_map(Value::Compound),
+        ]
+    })
+}
+
+fn nbt_compound(value: impl Strategy<Value = Value>) -> impl Strategy<Value = Map<String, Value>> {
+    let key = prop_oneof![
+        key(),
+        quoted_string_content().prop_filter("Empty key", |key| !key.is_empty())
+    ];
+    vec((key, value), 0..4).prop_map(|entries| entries.into_iter().collect())
+}
+
+fn advancement_progress() -> impl Strategy<Value = MinecraftAdvancementProgress<'static>> {
+    prop_oneof![
+        any::<bool>().prop_map(MinecraftAdvancementProgress::AdvancementProgress),
+        btree_map(key().prop_map(leak), any::<bool>(), 0..4)
+            .prop_map(MinecraftAdvancementProgress::CriterionProgress),
+    ]
+}
+
+fn selector_type() -> impl Strategy<Value = MinecraftSelectorType> {
+    prop_oneof![
+        Just(MinecraftSelectorType::A),
+        Just(MinecraftSelectorType::E),
+        Just(MinecraftSelectorType::P),
+        Just(MinecraftSelectorType::R),
+        Just(MinecraftSelectorType::S),
+    ]
+}
+
+fn selector() -> impl Strategy<Value = MinecraftSelector<'static>> {
+    let name = option::of((any::<bool>(), quoted_string_content()).prop_map(
+        |(inverted, string)| InvertableString {
+            inverted,
+            string: leak(string),
+        },
+    ));
+    let position = (
+        option::of(finite_f64()),
+        option::of(finite_f64()),
+        option::of(finite_f64()),
+        option::of(finite_f64()),
+        option::of(finite_f64()),
+        option::of(finite_f64()),
+    );
+    let ranges = (
+        option::of(range(finite_f64())),
+        option::of(range(any::<i32>())),
+        option::of(range(finite_f32())),
+        option::of(range(finite_f32())),
+    );
+    let strings = (
+        option::of(any::<i32>()),
+        option::of("[a-z_]{0,8}".prop_map(leak)),
+        option::of(invertable_unquoted_string()),
+        option::of(invertable_unquoted_string()),
+    );
+    let entity_type = option::of(
+        (any::<bool>(), any::<bool>(), resource_location()).prop_map(
+            |(inverted, tag, resource_location)| EntityType {
+                inverted,
+                tag,
+                resource_location,
+            },
+        ),
+    );
+    let lists = (
+        vec(invertable_unquoted_string(), 0..3),
+        vec(
+            (any::<bool>(), nbt_compound(nbt_value())).prop_map(|(inverted, compound)| {
+                InvertableCompoundNbt {
+                    inverted,
+                    nbt: CompoundNbt(compound),
+                }
+            }),
+            0..3,
+        ),
+        btree_map(key().prop_map(leak), range(any::<i32>()), 0..3),
+        btree_map(resource_location(), advancement_progress(), 0..3),
+        vec(
+            (any::<bool>(), resource_location()).prop_map(|(inverted, predicate)| {
+                InvertablePredicate {
+                    inverted,
+                    predicate,
+                }
+            }),
+            0..3,
+        ),
+    );
+    (
+        selector_type(),
+        name,
+        position,
+        ranges,
+        strings,
+        entity_type,
+        lists,
+    )
+        .prop_map(
+            |(
+                selector_type,
+                name,
+                (x, y, z, dx, dy, dz),
+                (distance, level, x_rotation, y_rotation),
+                (limit, sort, gamemode, team),
+                entity_type,
+                (tags, nbts, scores, advancements, predicates),
+            )| MinecraftSelector {
+                selector_type,
+                name,
+                distance,
+                level,
+                x,
+                y,
+                z,
+                dx,
+                dy,
+                dz,
+                x_rotation,
+                y_rotation,
+                limit,
+                sort,
+                gamemode,
+                team,
+                entity_type,
+                tags,
+                nbts,
+                scores,
+                advancements,
+                predicates,
+            },
+        )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+
+    #[test]
+    fn test_parse_displayed_selector(selector in selector()) {
+        // given:
+        let string = selector.to_string();
+
+        // when:
+        let actual = MinecraftSelector::parse(&string);
+
+        // then:
+        prop_assert_eq!(actual, Ok((selector, string.len())));
+    }
+}
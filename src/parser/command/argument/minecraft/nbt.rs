@@ -17,11 +17,11 @@
 // If not, see <http://www.gnu.org/licenses/>.
 
 use crate::parser::command::argument::brigadier::{
-    expect, is_quote, parse_integer, parse_possibly_quoted_string, parse_quoted_string,
-    parse_unquoted_string,
+    expect, fmt_possibly_quoted_string, is_quote, parse_integer, parse_possibly_quoted_string,
+    parse_quoted_string, parse_unquoted_string,
 };
 use ::nbt::{Map, Value};
-use std::convert::TryFrom;
+use std::{convert::TryFrom, fmt::Display};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct MinecraftNbtPath<'l>(Vec<MinecraftNbtPathNode<'l>>);
@@ -137,6 +137,11 @@ pub struct CompoundNbt(pub Map<String, Value>);
 
 impl CompoundNbt {
     pub fn parse(string: &str) -> Result<(Self, usize), String> {
+        CompoundNbt::parse_nested(string, 0)
+    }
+
+    fn parse_nested(string: &str, depth: usize) -> Result<(Self, usize), String> {
+        check_depth(depth)?;
         let mut compound = Map::new();
 
         let mut suffix = expect(string, '{')?.trim_start();
@@ -147,7 +152,7 @@ impl CompoundNbt {
 
                 suffix = expect(suffix, ':')?.trim_start();
 
-                let (value, len) = parse_value(suffix)?;
+                let (value, len) = parse_value(suffix, depth + 1)?;
                 suffix = &suffix[len..].trim_start();
 
                 compound.insert(key, value);
@@ -169,9 +174,84 @@ impl CompoundNbt {
     }
 }
 
+impl Display for CompoundNbt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_compound(f, &self.0)
+    }
+}
+
+fn fmt_compound(
+    f: &mut std::fmt::Formatter<'_>,
+    compound: &Map<String, Value>,
+) -> std::fmt::Result {
+    f.write_str("{")?;
+    for (index, (key, value)) in compound.iter().enumerate() {
+        if index > 0 {
+            f.write_str(",")?;
+        }
+        fmt_possibly_quoted_string(f, key)?;
+        f.write_str(":")?;
+        fmt_value(f, value)?;
+    }
+    f.write_str("}")
+}
+
+/// Writes a value in SNBT. Like the parsed strings, strings are written unchanged.
+fn fmt_value(f: &mut std::fmt::Formatter<'_>, value: &Value) -> std::fmt::Result {
+    fn fmt_elements<E: Display>(
+        f: &mut std::fmt::Formatter<'_>,
+        prefix: &str,
+        elements: &[E],
+        suffix: &str,
+    ) -> std::fmt::Result {
+        f.write_str(prefix)?;
+        for (index, element) in elements.iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}{}", element, suffix)?;
+        }
+        f.write_str("]")
+    }
+    match value {
+        Value::Byte(value) => write!(f, "{}b", value),
+        Value::Short(value) => write!(f, "{}s", value),
+        Value::Int(value) => write!(f, "{}", value),
+        Value::Long(value) => write!(f, "{}L", value),
+        Value::Float(value) => write!(f, "{}f", value),
+        Value::Double(value) => write!(f, "{}d", value),
+        Value::ByteArray(values) => fmt_elements(f, "[B;", values, "b"),
+        Value::String(value) => fmt_possibly_quoted_string(f, value),
+        Value::List(values) => {
+            f.write_str("[")?;
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(",")?;
+                }
+                fmt_value(f, value)?;
+            }
+            f.write_str("]")
+        }
+        Value::Compound(compound) => fmt_compound(f, compound),
+        Value::IntArray(values) => fmt_elements(f, "[I;", values, ""),
+        Value::LongArray(values) => fmt_elements(f, "[L;", values, "L"),
+    }
+}
+
 const EXPECTED_KEY: &str = "Expected key";
 const EXPECTED_VALUE: &str = "Expected value";
 
+/// Like in Minecraft, deeper NBT is rejected. This also prevents a stack overflow while parsing.
+const MAX_DEPTH: usize = 512;
+
+fn check_depth(depth: usize) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        Err(format!("NBT is nested deeper than {} levels", MAX_DEPTH))
+    } else {
+        Ok(())
+    }
+}
+
 fn parse_key(string: &str) -> Result<(String, usize), String> {
     let (string, len) = parse_possibly_quoted_string(string)?;
     if string.is_empty() {
@@ -181,15 +261,15 @@ fn parse_key(string: &str) -> Result<(String, usize), String> {
     }
 }
 
-fn parse_value(string: &str) -> Result<(Value, usize), String> {
+fn parse_value(string: &str, depth: usize) -> Result<(Value, usize), String> {
     let c = string.chars().next();
     if let Some(c) = c {
         match c {
             '{' => {
-                let (CompoundNbt(compound), len) = CompoundNbt::parse(string)?;
+                let (CompoundNbt(compound), len) = CompoundNbt::parse_nested(string, depth)?;
                 Ok((Value::Compound(compound), len))
             }
-            '[' => parse_array_or_list(string),
+            '[' => parse_array_or_list(string, depth),
             quote if is_quote(quote) => {
                 let (string, len) = parse_quoted_string(string, quote)?;
                 Ok((Value::String(string.to_string()), len))
@@ -205,30 +285,31 @@ fn parse_value(string: &str) -> Result<(Value, usize), String> {
     }
 }
 
-fn parse_array_or_list(string: &str) -> Result<(Value, usize), String> {
+fn parse_array_or_list(string: &str, depth: usize) -> Result<(Value, usize), String> {
+    check_depth(depth)?;
     let mut suffix = expect(string, '[')?;
-    let mut chars = string.chars();
+    let mut chars = suffix.chars();
     if let (Some(array_type), Some(';')) = (chars.next(), chars.next()) {
         suffix = &suffix[array_type.len_utf8() + ';'.len_utf8()..].trim_start();
         let (value, len) = match array_type {
-            'B' => ByteArrayParser.parse_suffix(suffix),
-            'L' => LongArrayParser.parse_suffix(suffix),
-            'I' => IntArrayParser.parse_suffix(suffix),
+            'B' => ByteArrayParser.parse_suffix(suffix, depth),
+            'L' => LongArrayParser.parse_suffix(suffix, depth),
+            'I' => IntArrayParser.parse_suffix(suffix, depth),
             value => Err(format!("Invalid array type '{}'", value)),
         }?;
         Ok((value, (string.len() - suffix.len()) + len))
     } else {
-        let (list, len) = parse_list(string)?;
+        let (list, len) = parse_list(string, depth)?;
         Ok((Value::List(list), len))
     }
 }
 
-fn parse_list(string: &str) -> Result<(Vec<Value>, usize), String> {
+fn parse_list(string: &str, depth: usize) -> Result<(Vec<Value>, usize), String> {
     let mut vec = Vec::new();
 
     let mut suffix = expect(string, '[')?.trim_start();
     while !suffix.starts_with(']') {
-        let (value, len) = parse_value(suffix)?;
+        let (value, len) = parse_value(suffix, depth + 1)?;
         suffix = &suffix[len..].trim_start();
 
         vec.push(value);
@@ -268,11 +349,11 @@ impl TryFrom<char> for NbtArrayType {
 }
 
 trait NbtArrayParser<E> {
-    fn parse_suffix(&self, string: &str) -> Result<(Value, usize), String> {
+    fn parse_suffix(&self, string: &str, depth: usize) -> Result<(Value, usize), String> {
         let mut vec = Vec::new();
         let mut suffix = string;
         while !suffix.starts_with(']') {
-            let (value, len) = parse_value(suffix)?;
+            let (value, len) = parse_value(suffix, depth + 1)?;
             suffix = &suffix[len..].trim_start();
 
             let tag_name = value.tag_name().to_string();
@@ -354,3 +435,6 @@ impl NbtArrayParser<i64> for LongArrayParser {
         Value::LongArray(vec)
     }
 }
+
+#[cfg(test)]
+mod tests;
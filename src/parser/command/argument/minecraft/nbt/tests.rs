@@ -0,0 +1,67 @@
+use super::*;
+
+#[test]
+fn test_array_type_before_multi_byte_char() {
+    // given:
+    let string = "{a:[;é]}";
+
+    // when:
+    let actual = CompoundNbt::parse(string);
+
+    // then:
+    assert_eq!(actual, Err("Expected ']'".to_string()));
+}
+
+#[test]
+fn test_array_type_at_end() {
+    // given:
+    let string = "a{b:[;";
+
+    // when:
+    let actual = MinecraftNbtPath::parse(string);
+
+    // then:
+    assert_eq!(actual, Err("Expected ']'".to_string()));
+}
+
+#[test]
+fn test_max_depth() {
+    // given:
+    let string = format!("{}{{}}{}", "{a:".repeat(MAX_DEPTH), "}".repeat(MAX_DEPTH));
+
+    // when:
+    let actual = CompoundNbt::parse(&string);
+
+    // then:
+    assert_eq!(actual.map(|(_, len)| len), Ok(string.len()));
+}
+
+#[test]
+fn test_deeply_nested_compound() {
+    // given:
+    let string = "{a:".repeat(100_000);
+
+    // when:
+    let actual = CompoundNbt::parse(&string);
+
+    // then:
+    assert_eq!(
+        actual,
+        Err("NBT is nested deeper than 512 levels".to_string())
+    );
+}
+
+#[test]
+fn test_deeply_nested_list() {
+    // given:
+    let string = format!("{{a:{}", "[".repeat(100_000));
+
+    // when:
+    let actual = CompoundNbt::parse(&string);
+
+    // then:
+    assert_eq!(
+        actual,
+        Err("NBT is nested deeper than 512 levels".to_string())
+    );
+}
@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct MinecraftRange<N> {
@@ -79,3 +79,21 @@ impl<N: Clone + FromStr> MinecraftRange<N> {
         }
     }
 }
+
+impl<N: Display + PartialEq> Display for MinecraftRange<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.min, &self.max) {
+            (Some(min), Some(max)) if min == max => min.fmt(f),
+            (min, max) => {
+                if let Some(min) = min {
+                    min.fmt(f)?;
+                }
+                f.write_str("..")?;
+                if let Some(max) = max {
+                    max.fmt(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -38,6 +38,8 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use std::{u32, usize};
 
+type MinecraftComponent<'l> = &'l str;
+
 type MinecraftDimension<'l> = ResourceLocationRef<&'l str>;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -123,6 +125,7 @@ pub enum Argument<'l> {
     BrigadierString(&'l str),
     MinecraftBlockPos(MinecraftBlockPos),
     MinecraftBlockPredicate(MinecraftBlockPredicate<'l>),
+    MinecraftComponent(MinecraftComponent<'l>),
     MinecraftDimension(MinecraftDimension<'l>),
     MinecraftEntity(MinecraftEntity<'l>),
     MinecraftEntityAnchor(MinecraftEntityAnchor),
@@ -218,8 +221,16 @@ pub enum ArgumentParser {
     MinecraftOperation,
     #[serde(rename = "minecraft:particle")]
     MinecraftParticle,
+    #[serde(rename = "minecraft:resource")]
+    MinecraftResource { registry: String },
+    #[serde(rename = "minecraft:resource_key")]
+    MinecraftResourceKey { registry: String },
     #[serde(rename = "minecraft:resource_location")]
     MinecraftResourceLocation,
+    #[serde(rename = "minecraft:resource_or_tag")]
+    MinecraftResourceOrTag { registry: String },
+    #[serde(rename = "minecraft:resource_or_tag_key")]
+    MinecraftResourceOrTagKey { registry: String },
     #[serde(rename = "minecraft:rotation")]
     MinecraftRotation,
     #[serde(rename = "minecraft:score_holder")]
@@ -296,6 +307,9 @@ impl ArgumentParser {
             }
             Self::MinecraftBlockPredicate => MinecraftBlockPredicate::parse(string)
                 .map(|it| it.map0(Argument::MinecraftBlockPredicate)),
+            Self::MinecraftComponent => {
+                parse_minecraft_component(string).map(|it| it.map0(Argument::MinecraftComponent))
+            }
             Self::MinecraftDimension => {
                 MinecraftDimension::parse(string).map(|it| it.map0(Argument::MinecraftDimension))
             }
@@ -304,9 +318,8 @@ impl ArgumentParser {
             }
             Self::MinecraftEntityAnchor => parse_minecraft_entity_anchor(string)
                 .map(|it| it.map0(Argument::MinecraftEntityAnchor)),
-            Self::MinecraftFunction => {
-                MinecraftFunction::parse(string).map(|it| it.map0(Argument::MinecraftFunction))
-            }
+            Self::MinecraftFunction => MinecraftFunction::parse_tag_or_id(string)
+                .map(|it| it.map0(Argument::MinecraftFunction)),
             Self::MinecraftIntRange => {
                 MinecraftRange::parse(string).map(|it| it.map0(Argument::MinecraftIntRange))
             }
@@ -324,8 +337,13 @@ impl ArgumentParser {
             Self::MinecraftOperation => {
                 parse_minecraft_operation(string).map(|it| it.map0(Argument::MinecraftOperation))
             }
-            Self::MinecraftResourceLocation => MinecraftResourceLocation::parse(string)
+            Self::MinecraftResource { .. }
+            | Self::MinecraftResourceKey { .. }
+            | Self::MinecraftResourceLocation => MinecraftResourceLocation::parse(string)
                 .map(|it| it.map0(Argument::MinecraftResourceLocation)),
+            Self::MinecraftResourceOrTag { .. } | Self::MinecraftResourceOrTagKey { .. } => {
+                parse_unknown(string).map(|it| it.map0(Argument::Unknown))
+            }
             Self::MinecraftRotation => {
                 MinecraftRotation::parse(string).map(|it| it.map0(Argument::MinecraftRotation))
             }
@@ -354,6 +372,18 @@ impl ArgumentParser {
     }
 }
 
+fn parse_minecraft_component(string: &str) -> Result<(MinecraftComponent, usize), String> {
+    let mut values = serde_json::Deserializer::from_str(string).into_iter::<serde_json::Value>();
+    match values.next() {
+        Some(Ok(_)) => {
+            let len = values.byte_offset();
+            Ok((&string[..len], len))
+        }
+        Some(Err(e)) => Err(format!("Invalid chat component: {}", e)),
+        None => Err("Invalid chat component".to_string()),
+    }
+}
+
 fn parse_minecraft_entity_anchor(string: &str) -> Result<(MinecraftEntityAnchor, usize), String> {
     let eyes = "eyes";
     let feet = "feet";
@@ -19,6 +19,7 @@
 pub mod argument;
 pub mod resource_location;
 
+use log::warn;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
@@ -28,14 +29,61 @@ use std::{
 
 use self::argument::{Argument, ArgumentParser};
 
+/// The command grammars bundled with the debugger ordered by Minecraft version. The first grammar
+/// is complete, every other grammar contains the commands that were removed, added or changed
+/// since the previous version. A changed command is replaced as a whole. Like `execute run`, the
+/// `return run` node has neither children nor a redirect, because it redirects to the root node.
+const GRAMMARS: [(&str, &str); 3] = [
+    ("1.17", include_str!("commands/1.17.json")),
+    ("1.19", include_str!("commands/1.19.json")),
+    ("1.20", include_str!("commands/1.20.json")),
+];
+
+/// The Minecraft versions with a bundled command grammar ordered by version.
+pub fn bundled_minecraft_versions() -> impl Iterator<Item = &'static str> {
+    GRAMMARS.iter().map(|(version, _json)| *version)
+}
+
+/// The maximum number of nested nodes in a command. Every node is parsed recursively, so a long
+/// chain like `execute as @s as @s ...` could otherwise overflow the stack.
+const MAX_DEPTH: usize = 256;
+
 pub struct CommandParser {
     specs: BTreeMap<String, CommandSpec>,
 }
 
 impl CommandParser {
+    /// Creates a parser for the latest bundled Minecraft version, see [GRAMMARS].
     pub fn default() -> Result<CommandParser, serde_json::Error> {
-        let json = include_str!("commands.json");
-        CommandParser::from_str(json)
+        CommandParser::for_grammar(GRAMMARS.len() - 1)
+    }
+
+    /// Creates a parser for the commands of the given Minecraft version, for example `1.20.1`. If
+    /// no grammar is bundled for the version, a warning is logged and the nearest bundled version
+    /// is used instead.
+    pub fn for_version(version: &str) -> Result<CommandParser, serde_json::Error> {
+        let index = find_nearest_grammar(version);
+        let bundled_version = GRAMMARS[index].0;
+        if parse_version(version) != parse_version(bundled_version) {
+            warn!(
+                "No command grammar is bundled for Minecraft {}, using {} instead",
+                version, bundled_version
+            );
+        }
+        CommandParser::for_grammar(index)
+    }
+
+    fn for_grammar(index: usize) -> Result<CommandParser, serde_json::Error> {
+        let (_version, json) = GRAMMARS[0];
+        let mut parser = CommandParser::from_str(json)?;
+        for (_version, json) in &GRAMMARS[1..=index] {
+            let changes: GrammarChanges = serde_json::from_str(json)?;
+            for removed in changes.removed {
+                parser.specs.remove(&removed);
+            }
+            parser.specs.extend(changes.children);
+        }
+        Ok(parser)
     }
 
     pub fn from_str(json: &str) -> serde_json::Result<CommandParser> {
@@ -46,7 +94,7 @@ impl CommandParser {
     }
 
     pub fn parse<'l>(&'l self, command: &'l str) -> CommandParserResult<'l> {
-        self.parse_from_specs(command, 0, &self.specs)
+        self.parse_from_specs(command, 0, &self.specs, 0)
     }
 
     fn parse_from_specs<'l>(
@@ -54,10 +102,22 @@ impl CommandParser {
         command: &'l str,
         index: usize,
         specs: &'l BTreeMap<String, CommandSpec>,
+        depth: usize,
     ) -> CommandParserResult<'l> {
+        if depth > MAX_DEPTH {
+            return CommandParserResult {
+                parsed_nodes: Vec::new(),
+                error: Some(CommandParserError {
+                    message: format!("Command has more than {} nodes", MAX_DEPTH),
+                    command,
+                    index,
+                }),
+            };
+        }
+
         let parsed = Self::find_relevant_commands(command, index, specs)
             .into_iter()
-            .map(|(name, spec)| (self.parse_from_single_spec(name, spec, command, index)))
+            .map(|(name, spec)| self.parse_from_single_spec(name, spec, command, index, depth))
             .collect::<Vec<_>>();
 
         let only_errors = parsed.iter().all(|parsed| parsed.error.is_some());
@@ -118,6 +178,7 @@ impl CommandParser {
         spec: &'l CommandSpec,
         command: &'l str,
         mut index: usize,
+        depth: usize,
     ) -> CommandParserResult<'l> {
         let mut parsed_nodes = Vec::new();
 
@@ -185,7 +246,7 @@ impl CommandParser {
         } else {
             return Err!("Incorrect argument for command".to_string());
         };
-        let mut result = self.parse_from_specs(command, index, children);
+        let mut result = self.parse_from_specs(command, index, children, depth + 1);
         parsed_nodes.extend_from_slice(&result.parsed_nodes);
         result.parsed_nodes = parsed_nodes;
         result
@@ -246,6 +307,40 @@ struct RootNode {
     children: BTreeMap<String, CommandSpec>,
 }
 
+/// The commands that were removed, added or changed in a Minecraft version.
+#[derive(Deserialize, Debug)]
+struct GrammarChanges {
+    removed: Vec<String>,
+    children: BTreeMap<String, CommandSpec>,
+}
+
+/// Returns the major and minor version of a Minecraft version like `1.20.1`. Patch versions don't
+/// change the command grammar.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.').map(str::parse::<u32>);
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor))
+}
+
+/// Returns the index of the bundled grammar that is closest to the version. For versions in the
+/// middle of two grammars the older one is used. Versions that can't be parsed use the latest
+/// grammar.
+fn find_nearest_grammar(version: &str) -> usize {
+    // Minor versions are far below 1000, so this orders versions like (major, minor)
+    let ordinal = |(major, minor): (u32, u32)| major.saturating_mul(1000).saturating_add(minor);
+    let version = match parse_version(version) {
+        Some(version) => ordinal(version),
+        None => return GRAMMARS.len() - 1,
+    };
+    (0..GRAMMARS.len())
+        .min_by_key(|index| {
+            let bundled_version = parse_version(GRAMMARS[*index].0).unwrap();
+            version.abs_diff(ordinal(bundled_version))
+        })
+        .unwrap()
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum CommandSpec {
@@ -352,6 +447,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_default_is_latest_grammar() {
+        // when:
+        let actual = CommandParser::default().unwrap().specs;
+
+        // then:
+        assert!(!actual.contains_key("locatebiome"));
+        assert!(actual.contains_key("return"));
+        assert!(actual.contains_key("tick"));
+    }
+
+    #[test]
+    fn test_for_version() {
+        // when:
+        let v1_17 = CommandParser::for_version("1.17").unwrap().specs;
+        let v1_19 = CommandParser::for_version("1.19").unwrap().specs;
+        let v1_20 = CommandParser::for_version("1.20.1").unwrap().specs;
+
+        // then:
+        assert!(!v1_17.contains_key("return"));
+        assert!(!v1_17.contains_key("ride"));
+        assert!(v1_17.contains_key("locatebiome"));
+        assert!(!v1_19.contains_key("return"));
+        assert!(!v1_19.contains_key("locatebiome"));
+        assert!(v1_19.contains_key("ride"));
+        assert!(v1_19.contains_key("damage"));
+        assert!(v1_20.contains_key("return"));
+        assert!(v1_20.contains_key("tick"));
+        assert!(v1_20.contains_key("ride"));
+    }
+
+    #[test]
+    fn test_parse_for_version() {
+        // given:
+        let commands = [
+            "execute on passengers run say hi",
+            "execute summon minecraft:pig run tag @s add new",
+            "execute if function test:check run say hi",
+            "ride @s mount @e[type=minecraft:pig,limit=1]",
+            "return run function test:inner",
+            "tick freeze",
+        ];
+        let parse_all = |version| {
+            let parser = CommandParser::for_version(version).unwrap();
+            commands.map(|command| parser.parse(command).error.is_none())
+        };
+
+        // when:
+        let v1_17 = parse_all("1.17");
+        let v1_19 = parse_all("1.19");
+        let v1_20 = parse_all("1.20");
+
+        // then:
+        assert_eq!(v1_17, [false, false, false, false, false, false]);
+        assert_eq!(v1_19, [true, true, false, true, false, false]);
+        assert_eq!(v1_20, [true, true, true, true, true, true]);
+    }
+
+    #[test]
+    fn test_parse_too_many_nodes() {
+        // given:
+        let parser = CommandParser::default().unwrap();
+        let command = format!("execute {}run say hi", "as @s ".repeat(10_000));
+
+        // when:
+        let actual = parser.parse(&command);
+
+        // then:
+        assert_eq!(
+            actual.error.map(|error| error.message),
+            Some("Command has more than 256 nodes".to_string())
+        );
+    }
+
+    #[test]
+    fn test_find_nearest_grammar() {
+        // when:
+        let actual = [
+            "1.16.5", "1.17.1", "1.18.2", "1.19.4", "1.21", "2.0", "snapshot",
+        ]
+        .map(|version| GRAMMARS[find_nearest_grammar(version)].0);
+
+        // then:
+        assert_eq!(
+            actual,
+            ["1.17", "1.17", "1.17", "1.19", "1.20", "1.20", "1.20"]
+        );
+    }
+
     #[test]
     fn test_serialize() {
         // when:
@@ -36,7 +36,8 @@ impl<'l> TryFrom<&'l str> for ResourceLocationRef<&'l str> {
     type Error = InvalidResourceLocation;
 
     fn try_from(string: &'l str) -> Result<Self, Self::Error> {
-        let (path, namespace_len) = if let Some((namespace, path)) = string.split_once(':') {
+        let id = string.strip_prefix('#').unwrap_or(string);
+        let (path, namespace_len) = if let Some((namespace, path)) = id.split_once(':') {
             if !namespace.chars().all(is_valid_namespace_char) {
                 return Err(InvalidResourceLocation::InvalidNamespace);
             }
@@ -64,6 +65,27 @@ fn is_valid_path_char(c: char) -> bool {
     c >= '0' && c <= '9' || c >= 'a' && c <= 'z' || c == '-' || c == '.' || c == '/' || c == '_'
 }
 
+/// Returns `string` with the first character that is not allowed in a resource location enclosed in
+/// brackets, for example `my_ns:[M]y_function`, or [None] if all characters are allowed.
+pub fn highlight_invalid_char(string: &str) -> Option<String> {
+    let namespace_len = string.find(':').unwrap_or(0);
+    let (index, c) = string.char_indices().find(|(index, c)| {
+        if *index < namespace_len {
+            !is_valid_namespace_char(*c)
+        } else if *index == namespace_len && namespace_len > 0 {
+            false // The separator
+        } else {
+            !is_valid_path_char(*c)
+        }
+    })?;
+    Some(format!(
+        "{}[{}]{}",
+        &string[..index],
+        c,
+        &string[index + c.len_utf8()..]
+    ))
+}
+
 impl<S: AsRef<str>> ResourceLocationRef<S> {
     pub fn new(namespace: &str, path: &str) -> ResourceLocation {
         ResourceLocationRef {
@@ -72,19 +94,29 @@ impl<S: AsRef<str>> ResourceLocationRef<S> {
         }
     }
 
+    /// Whether this refers to a tag, which is written with a leading `#`.
+    pub fn is_tag(&self) -> bool {
+        self.string.as_ref().starts_with('#')
+    }
+
+    fn id(&self) -> &str {
+        let string = self.string.as_ref();
+        string.strip_prefix('#').unwrap_or(string)
+    }
+
     pub fn namespace(&self) -> &str {
         if self.namespace_len == usize::MAX {
             "minecraft"
         } else {
-            &self.string.as_ref()[..self.namespace_len]
+            &self.id()[..self.namespace_len]
         }
     }
 
     pub fn path(&self) -> &str {
         if self.namespace_len == usize::MAX {
-            self.string.as_ref()
+            self.id()
         } else {
-            &self.string.as_ref()[self.namespace_len + 1..]
+            &self.id()[self.namespace_len + 1..]
         }
     }
 
@@ -112,7 +144,9 @@ impl ResourceLocation {
 
 impl<S: AsRef<str>> PartialEq for ResourceLocationRef<S> {
     fn eq(&self, other: &Self) -> bool {
-        self.namespace() == other.namespace() && self.path() == other.path()
+        self.is_tag() == other.is_tag()
+            && self.namespace() == other.namespace()
+            && self.path() == other.path()
     }
 }
 
@@ -126,14 +160,16 @@ impl<S: AsRef<str>> PartialOrd for ResourceLocationRef<S> {
 
 impl<S: AsRef<str>> Ord for ResourceLocationRef<S> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.namespace()
-            .cmp(other.namespace())
+        self.is_tag()
+            .cmp(&other.is_tag())
+            .then_with(|| self.namespace().cmp(other.namespace()))
             .then_with(|| self.path().cmp(other.path()))
     }
 }
 
 impl<S: AsRef<str>> Hash for ResourceLocationRef<S> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.is_tag().hash(state);
         self.namespace().hash(state);
         self.path().hash(state);
     }
@@ -153,6 +189,23 @@ impl<'l> ResourceLocationRef<&'l str> {
         Ok((resource_location, len))
     }
 
+    /// Like [Self::parse], but also accepts a tag with a leading `#`.
+    pub fn parse_tag_or_id(string: &'l str) -> Result<(Self, usize), String> {
+        if let Some(suffix) = string.strip_prefix('#') {
+            let (resource_location, len) = Self::parse(suffix)?;
+            let len = '#'.len_utf8() + len;
+            Ok((
+                ResourceLocationRef {
+                    string: &string[..len],
+                    namespace_len: resource_location.namespace_len,
+                },
+                len,
+            ))
+        } else {
+            Self::parse(string)
+        }
+    }
+
     fn is_allowed_in_resource_location(c: char) -> bool {
         return c >= '0' && c <= '9'
             || c >= 'a' && c <= 'z'
@@ -20,7 +20,8 @@ pub mod command;
 
 use self::command::{
     argument::{
-        Argument, MinecraftEntityAnchor, MinecraftMessage, MinecraftScoreHolder, MinecraftTime,
+        minecraft::entity::MinecraftEntity, Argument, MinecraftEntityAnchor, MinecraftMessage,
+        MinecraftScoreHolder, MinecraftTime,
     },
     resource_location::{ResourceLocation, ResourceLocationRef},
     CommandParser, CommandParserError, CommandParserResult, ParsedNode,
@@ -37,6 +38,8 @@ pub enum Line {
         column_index: usize,
         name: ResourceLocation,
         anchor: Option<MinecraftEntityAnchor>,
+        /// The dimension of the last `execute in` before the function call.
+        dimension: Option<ResourceLocation>,
         selectors: BTreeSet<usize>,
         objectives: BTreeSet<String>,
     },
@@ -52,6 +55,17 @@ pub enum Line {
         selectors: BTreeSet<usize>,
         objectives: BTreeSet<String>,
     },
+    /// A `return` command, which ends the execution of the function early. Like in
+    /// [Line::FunctionCall] the indices are relative to the trimmed line.
+    Return {
+        return_index: usize,
+        /// The value of `return <value>`.
+        value: Option<i32>,
+        /// The index of the command of `return run <command>`.
+        run_index: Option<usize>,
+        selectors: BTreeSet<usize>,
+        objectives: BTreeSet<String>,
+    },
     OtherCommand {
         selectors: BTreeSet<usize>,
         objectives: BTreeSet<String>,
@@ -64,6 +78,7 @@ impl Line {
             Line::FunctionCall { objectives, .. }
             | Line::OptionalSelectorCommand { objectives, .. }
             | Line::Schedule { objectives, .. }
+            | Line::Return { objectives, .. }
             | Line::OtherCommand { objectives, .. } => Some(objectives),
             _ => None,
         }
@@ -85,7 +100,7 @@ pub fn parse_line(parser: &CommandParser, line: &str, breakpoint_comments: bool)
     line
 }
 
-fn parse_line_internal<'l>(
+pub(crate) fn parse_line_internal<'l>(
     parser: &'l CommandParser,
     line: &'l str,
     breakpoint_comments: bool,
@@ -116,12 +131,13 @@ fn parse_command<'l>(
     let mut selectors = BTreeSet::new();
     let mut objectives = BTreeSet::new();
     let mut maybe_anchor: Option<MinecraftEntityAnchor> = None;
+    let mut maybe_dimension: Option<ResourceLocation> = None;
 
     while let [_, tail @ ..] = nodes {
         match nodes {
             [ParsedNode::Argument {
                 argument:
-                    Argument::MinecraftEntity(..)
+                    Argument::MinecraftEntity(MinecraftEntity::Selector(..))
                     | Argument::MinecraftScoreHolder(MinecraftScoreHolder::Selector(..)),
                 index,
                 ..
@@ -175,6 +191,16 @@ fn parse_command<'l>(
                 maybe_anchor = Some(*anchor);
             }
 
+            [ParsedNode::Literal {
+                literal: "execute", ..
+            }
+            | ParsedNode::Redirect("execute"), ParsedNode::Literal { literal: "in", .. }, ParsedNode::Argument {
+                argument: Argument::MinecraftDimension(dimension),
+                ..
+            }, ..] => {
+                maybe_dimension = Some(dimension.to_owned());
+            }
+
             _ => {}
         }
 
@@ -182,12 +208,26 @@ fn parse_command<'l>(
     }
 
     if error.is_none() {
+        if let Some((return_index, value, run_index)) = as_return(&parsed_nodes) {
+            return (
+                Line::Return {
+                    return_index,
+                    value,
+                    run_index,
+                    selectors,
+                    objectives,
+                },
+                None,
+            );
+        }
+
         if let Some((column_index, name)) = as_function_call(&parsed_nodes) {
             return (
                 Line::FunctionCall {
                     column_index,
                     name,
                     anchor: maybe_anchor,
+                    dimension: maybe_dimension,
                     selectors,
                     objectives,
                 },
@@ -245,6 +285,99 @@ fn as_function_call(nodes: &[ParsedNode]) -> Option<(usize, ResourceLocation)> {
     }
 }
 
+/// Returns the column index and name of every function that the command calls, including the
+/// functions of `execute if function` and `execute unless function`. Scheduled functions are not
+/// called by the command. Like in [Line::FunctionCall] the column indices are relative to the
+/// trimmed line.
+pub fn parse_function_calls(parser: &CommandParser, line: &str) -> Vec<(usize, ResourceLocation)> {
+    let line = line.trim();
+    if line.starts_with('#') || line.is_empty() {
+        return Vec::new();
+    }
+    let CommandParserResult {
+        parsed_nodes,
+        error,
+    } = parser.parse(line);
+    if error.is_some() {
+        return Vec::new();
+    }
+    as_function_calls(&parsed_nodes)
+}
+
+fn as_function_calls(nodes: &[ParsedNode]) -> Vec<(usize, ResourceLocation)> {
+    let mut function_calls = Vec::new();
+    let mut scheduled = false;
+    for (node_index, node) in nodes.iter().enumerate() {
+        if let [ParsedNode::Literal {
+            literal: "function",
+            index,
+        }, ParsedNode::Argument {
+            argument: Argument::MinecraftFunction(function),
+            ..
+        }, ..] = &nodes[node_index..]
+        {
+            if !scheduled {
+                function_calls.push((*index, function.to_owned()));
+            }
+        }
+        scheduled = matches!(
+            node,
+            ParsedNode::Literal {
+                literal: "schedule",
+                ..
+            }
+        );
+    }
+    function_calls
+}
+
+fn as_return(mut nodes: &[ParsedNode]) -> Option<(usize, Option<i32>, Option<usize>)> {
+    while let [_, tail @ ..] = nodes {
+        match nodes {
+            [ParsedNode::Literal {
+                literal: "return",
+                index,
+            }, ParsedNode::Argument {
+                argument: Argument::BrigadierInteger(value),
+                ..
+            }] => return Some((*index, Some(*value), None)),
+
+            [ParsedNode::Literal {
+                literal: "return",
+                index,
+            }, ParsedNode::Literal { literal: "run", .. }, command, ..] => {
+                let run_index = match command {
+                    ParsedNode::Literal { index, .. } | ParsedNode::Argument { index, .. } => {
+                        *index
+                    }
+                    ParsedNode::Redirect(_) => return None,
+                };
+                return Some((*index, None, Some(run_index)));
+            }
+            _ => {}
+        }
+
+        nodes = tail;
+    }
+
+    None
+}
+
+/// Returns the index of the `run` keyword in the line if the function call at `column_index` has an
+/// execute prefix. Like in [Line::FunctionCall] the `column_index` is relative to the trimmed line,
+/// but the returned index is relative to the untrimmed line.
+pub fn find_run_index(line: &str, column_index: usize) -> Option<usize> {
+    const RUN: &str = "run ";
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let prefix = trimmed.get(..column_index)?;
+    if prefix.ends_with(RUN) {
+        Some(indent + column_index - RUN.len())
+    } else {
+        None
+    }
+}
+
 fn as_schedule(mut nodes: &[ParsedNode]) -> Option<(usize, ResourceLocation, ScheduleOperation)> {
     while let [_, tail @ ..] = nodes {
         match nodes {
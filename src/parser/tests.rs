@@ -47,13 +47,103 @@ fn test_tellraw() {
     let actual = parse_line_internal(&parser, line, true);
 
     // then:
-    // TODO support argument type: minecraft:component
     assert_eq!(
-        actual.0,
-        Line::OtherCommand {
-            selectors: BTreeSet::from_iter([8]),
-            objectives: BTreeSet::from_iter([]),
-        }
+        actual,
+        (
+            Line::OtherCommand {
+                selectors: BTreeSet::from_iter([8]),
+                objectives: BTreeSet::from_iter([]),
+            },
+            None
+        )
+    );
+}
+
+#[test]
+fn test_bossbar_add() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = r#"bossbar add test:bar "Test""#;
+
+    // when:
+    let actual = parse_line_internal(&parser, line, true);
+
+    // then:
+    assert_eq!(
+        actual,
+        (
+            Line::OtherCommand {
+                selectors: BTreeSet::from_iter([]),
+                objectives: BTreeSet::from_iter([]),
+            },
+            None
+        )
+    );
+}
+
+#[test]
+fn test_bossbar_set_value() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = "bossbar set test:bar value 50";
+
+    // when:
+    let actual = parse_line_internal(&parser, line, true);
+
+    // then:
+    assert_eq!(
+        actual,
+        (
+            Line::OtherCommand {
+                selectors: BTreeSet::from_iter([]),
+                objectives: BTreeSet::from_iter([]),
+            },
+            None
+        )
+    );
+}
+
+#[test]
+fn test_bossbar_set_players() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = "bossbar set test:bar players @a[tag=test]";
+
+    // when:
+    let actual = parse_line_internal(&parser, line, true);
+
+    // then:
+    assert_eq!(
+        actual,
+        (
+            Line::OtherCommand {
+                selectors: BTreeSet::from_iter([29]),
+                objectives: BTreeSet::from_iter([]),
+            },
+            None
+        )
+    );
+}
+
+#[test]
+fn test_bossbar_remove() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = "bossbar remove test:bar";
+
+    // when:
+    let actual = parse_line_internal(&parser, line, true);
+
+    // then:
+    assert_eq!(
+        actual,
+        (
+            Line::OtherCommand {
+                selectors: BTreeSet::from_iter([]),
+                objectives: BTreeSet::from_iter([]),
+            },
+            None
+        )
     );
 }
 
@@ -208,6 +298,7 @@ fn test_execute() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -216,6 +307,83 @@ fn test_execute() {
     );
 }
 
+#[test]
+fn test_function_tag() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = "function #test:tag";
+
+    // when:
+    let actual = parse_line_internal(&parser, line, true);
+
+    // then:
+    let (line, error) = actual;
+    assert_eq!(error, None);
+    if let Line::FunctionCall {
+        column_index, name, ..
+    } = line
+    {
+        assert_eq!(column_index, 0);
+        assert!(name.is_tag());
+        assert_eq!(name.namespace(), "test");
+        assert_eq!(name.path(), "tag");
+        assert_eq!(name.to_string(), "#test:tag");
+        assert_ne!(name, ResourceLocation::new("test", "tag"));
+    } else {
+        panic!("Expected a function call, but got {:?}", line);
+    }
+}
+
+#[test]
+fn test_return_value() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = "execute if entity @s run return 1";
+
+    // when:
+    let actual = parse_line_internal(&parser, line, true);
+
+    // then:
+    assert_eq!(
+        actual,
+        (
+            Line::Return {
+                return_index: 25,
+                value: Some(1),
+                run_index: None,
+                selectors: BTreeSet::from_iter([18]),
+                objectives: BTreeSet::from_iter([]),
+            },
+            None
+        )
+    );
+}
+
+#[test]
+fn test_return_run() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = "return run say @e";
+
+    // when:
+    let actual = parse_line_internal(&parser, line, true);
+
+    // then:
+    assert_eq!(
+        actual,
+        (
+            Line::Return {
+                return_index: 0,
+                value: None,
+                run_index: Some(11),
+                selectors: BTreeSet::from_iter([15]),
+                objectives: BTreeSet::from_iter([]),
+            },
+            None
+        )
+    );
+}
+
 #[test]
 fn test_execute_align() {
     // given:
@@ -235,6 +403,7 @@ fn test_execute_align() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -262,6 +431,7 @@ fn test_execute_anchored() {
                     .unwrap()
                     .to_owned(),
                 anchor: Some(MinecraftEntityAnchor::EYES),
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -289,6 +459,7 @@ fn test_execute_multiple_anchored() {
                     .unwrap()
                     .to_owned(),
                 anchor: Some(MinecraftEntityAnchor::EYES),
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -316,6 +487,7 @@ fn test_multiple_execute_anchored() {
                     .unwrap()
                     .to_owned(),
                 anchor: Some(MinecraftEntityAnchor::EYES),
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -343,6 +515,7 @@ fn test_multiple_execute_some_anchored() {
                     .unwrap()
                     .to_owned(),
                 anchor: Some(MinecraftEntityAnchor::EYES),
+                dimension: None,
                 selectors: BTreeSet::from_iter([37]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -370,6 +543,7 @@ fn test_execute_as() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([11]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -397,6 +571,7 @@ fn test_execute_at() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([11]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -424,6 +599,7 @@ fn test_execute_facing_pos() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -451,6 +627,7 @@ fn test_execute_facing_entity() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([22]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -478,6 +655,11 @@ fn test_execute_in() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: Some(
+                    ResourceLocationRef::try_from("the_nether")
+                        .unwrap()
+                        .to_owned()
+                ),
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -505,6 +687,11 @@ fn test_execute_in_qualified() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: Some(
+                    ResourceLocationRef::try_from("minecraft:the_end")
+                        .unwrap()
+                        .to_owned()
+                ),
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -513,6 +700,28 @@ fn test_execute_in_qualified() {
     );
 }
 
+#[test]
+fn test_multiple_execute_in() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = "execute in the_nether run execute in the_end run function test:func";
+
+    // when:
+    let actual = parse_line_internal(&parser, line, true);
+
+    // then:
+    let (line, error) = actual;
+    assert_eq!(error, None);
+    if let Line::FunctionCall { dimension, .. } = line {
+        assert_eq!(
+            dimension,
+            Some(ResourceLocation::new("minecraft", "the_end"))
+        );
+    } else {
+        panic!("Expected a function call, but got {:?}", line);
+    }
+}
+
 #[test]
 fn test_execute_positioned_absolute() {
     // given:
@@ -532,6 +741,7 @@ fn test_execute_positioned_absolute() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -559,6 +769,7 @@ fn test_execute_positioned_local() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -586,6 +797,7 @@ fn test_execute_positioned_relative() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -613,6 +825,7 @@ fn test_execute_positioned_as() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([22]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -640,6 +853,7 @@ fn test_execute_rotated_absolute() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -667,6 +881,7 @@ fn test_execute_rotated_relative() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -694,6 +909,7 @@ fn test_execute_rotated_as() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([19]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -721,6 +937,7 @@ fn test_execute_if_block() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -749,6 +966,7 @@ fn test_execute_if_block_tag() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -777,6 +995,7 @@ fn test_execute_if_blocks() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -804,6 +1023,7 @@ fn test_execute_if_data_block() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -831,6 +1051,7 @@ fn test_execute_if_data_entity() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([23]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -858,6 +1079,7 @@ fn test_execute_if_data_storage() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -885,6 +1107,7 @@ fn test_execute_if_entity() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([18]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -912,6 +1135,7 @@ fn test_execute_if_predicate() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -939,6 +1163,7 @@ fn test_execute_if_score() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter(["test_global".to_string()]),
             },
@@ -966,6 +1191,7 @@ fn test_execute_if_score_matches() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter(["test_global".to_string()]),
             },
@@ -993,6 +1219,7 @@ fn test_execute_store_block() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -1020,6 +1247,7 @@ fn test_execute_store_bossbar() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -1047,6 +1275,7 @@ fn test_execute_store_entity() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([29]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -1074,6 +1303,7 @@ fn test_execute_store_score() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([28]),
                 objectives: BTreeSet::from_iter(["test_global".to_string()]),
             },
@@ -1101,6 +1331,7 @@ fn test_execute_store_storage() {
                     .unwrap()
                     .to_owned(),
                 anchor: None,
+                dimension: None,
                 selectors: BTreeSet::from_iter([]),
                 objectives: BTreeSet::from_iter([]),
             },
@@ -1493,3 +1724,71 @@ fn test_teleport_destination_selector() {
         )
     );
 }
+
+#[test]
+fn test_find_run_index() {
+    // given:
+    let line = "execute as @e[type=sheep] run function test:func";
+
+    // when:
+    let actual = find_run_index(line, 30);
+
+    // then:
+    assert_eq!(actual, Some(26));
+}
+
+#[test]
+fn test_find_run_index_without_execute() {
+    // given:
+    let line = "function test:func";
+
+    // when:
+    let actual = find_run_index(line, 0);
+
+    // then:
+    assert_eq!(actual, None);
+}
+
+#[test]
+fn test_find_run_index_indented() {
+    // given:
+    let line = "  execute as @e[type=sheep] run function test:func";
+
+    // when:
+    let actual = find_run_index(line, 30);
+
+    // then:
+    assert_eq!(actual, Some(28));
+}
+
+#[test]
+fn test_parse_function_calls_with_two_callees() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = "execute if function test:a run function test:b";
+
+    // when:
+    let actual = parse_function_calls(&parser, line);
+
+    // then:
+    assert_eq!(
+        actual,
+        vec![
+            (11, ResourceLocation::new("test", "a")),
+            (31, ResourceLocation::new("test", "b"))
+        ]
+    );
+}
+
+#[test]
+fn test_parse_function_calls_ignores_scheduled_functions() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let line = "  execute if function test:a run schedule function test:b 1t";
+
+    // when:
+    let actual = parse_function_calls(&parser, line);
+
+    // then:
+    assert_eq!(actual, vec![(11, ResourceLocation::new("test", "a"))]);
+}
@@ -18,16 +18,19 @@
 
 use crate::{
     config::{
-        adapter::{BreakpointKind, BreakpointPositionInLine},
-        Config,
+        adapter::{BreakpointKind, BreakpointPositionInLine, LocalBreakpoint},
+        get_breakpoint_kind, Config,
     },
     parser::{
         command::{argument::MinecraftEntityAnchor, resource_location::ResourceLocation},
         Line,
     },
+    summary::PartitionRange,
 };
+use multimap::MultiMap;
 use std::{collections::BTreeSet, fmt::Display, str::FromStr};
 
+#[derive(Debug, PartialEq)]
 pub(crate) struct Partition<'l> {
     pub(crate) start: Position,
     pub(crate) end: Position,
@@ -35,6 +38,7 @@ pub(crate) struct Partition<'l> {
     pub(crate) terminator: Terminator<'l>,
 }
 
+#[derive(Debug, PartialEq)]
 pub(crate) enum Terminator<'l> {
     Breakpoint,
     Step {
@@ -49,11 +53,32 @@ pub(crate) enum Terminator<'l> {
         line: &'l str,
         name: &'l ResourceLocation,
         anchor: &'l Option<MinecraftEntityAnchor>,
+        dimension: &'l Option<ResourceLocation>,
+        selectors: &'l BTreeSet<usize>,
+        /// A breakpoint that is hit for every executor after the execute prefix was evaluated.
+        after_execute: Option<&'l BreakpointKind>,
+    },
+    /// A `return` command in the middle of a function.
+    ReturnCommand {
+        line: &'l str,
+        return_index: usize,
+        run_index: Option<usize>,
         selectors: &'l BTreeSet<usize>,
     },
     Return,
 }
 impl Terminator<'_> {
+    fn get_kind(&self) -> &'static str {
+        match self {
+            Terminator::Breakpoint => "breakpoint",
+            Terminator::Step { .. } => "step",
+            Terminator::Continue { .. } => "continue",
+            Terminator::FunctionCall { .. } => "functionCall",
+            Terminator::ReturnCommand { .. } => "returnCommand",
+            Terminator::Return => "return",
+        }
+    }
+
     fn get_position_in_line(&self) -> PositionInLine {
         match self {
             Terminator::Breakpoint => PositionInLine::Breakpoint,
@@ -62,7 +87,20 @@ impl Terminator<'_> {
             } => (*position_in_line).into(),
             Terminator::Continue { position_in_line } => (*position_in_line).into(),
             Terminator::FunctionCall { .. } => PositionInLine::Function,
-            Terminator::Return => PositionInLine::Return,
+            Terminator::ReturnCommand { .. } | Terminator::Return => PositionInLine::Return,
+        }
+    }
+}
+
+impl From<&Partition<'_>> for PartitionRange {
+    fn from(partition: &Partition<'_>) -> Self {
+        PartitionRange {
+            // The entry of a function is before its first line
+            start_line: partition.start.line_number.max(1),
+            start_position: partition.start.position_in_line.to_string(),
+            end_line: partition.end.line_number,
+            end_position: partition.end.position_in_line.to_string(),
+            terminator_kind: partition.terminator.get_kind().to_string(),
         }
     }
 }
@@ -71,6 +109,17 @@ pub(crate) fn partition<'l>(
     function: &ResourceLocation,
     lines: &'l [(usize, String, Line)],
     config: &'l Config,
+) -> Vec<Partition<'l>> {
+    let breakpoints = config.adapter.as_ref().map(|adapter| adapter.breakpoints);
+    partition_with_breakpoints(function, lines, breakpoints)
+}
+
+/// Like [partition], but only needs the breakpoints of the debug adapter instead of a full
+/// [Config].
+pub(crate) fn partition_with_breakpoints<'l>(
+    function: &ResourceLocation,
+    lines: &'l [(usize, String, Line)],
+    breakpoints: Option<&'l MultiMap<ResourceLocation, LocalBreakpoint>>,
 ) -> Vec<Partition<'l>> {
     let mut partitions = Vec::new();
     let mut start_line_index = 0;
@@ -96,12 +145,13 @@ pub(crate) fn partition<'l>(
             start_line_index = line_index;
             partition
         };
-        let get_breakpoint_terminator = |position_in_line| match config.get_breakpoint_kind(
-            function,
-            line_number,
-            position_in_line,
-        ) {
-            Some(BreakpointKind::Normal) => Some(Terminator::Breakpoint),
+        let get_kind = |position_in_line| {
+            get_breakpoint_kind(breakpoints?, function, line_number, position_in_line)
+        };
+        let get_breakpoint_terminator = |position_in_line| match get_kind(position_in_line) {
+            Some(BreakpointKind::Normal | BreakpointKind::Temporary) => {
+                Some(Terminator::Breakpoint)
+            }
             Some(BreakpointKind::Invalid) => None,
             Some(BreakpointKind::Continue) => Some(Terminator::Continue { position_in_line }),
             Some(BreakpointKind::Step { condition }) => Some(Terminator::Step {
@@ -121,25 +171,46 @@ pub(crate) fn partition<'l>(
             column_index,
             name,
             anchor,
+            dimension,
             selectors,
             ..
         } = command
         {
+            let after_execute = get_kind(BreakpointPositionInLine::AfterExecute);
             partitions.push(next_partition(Terminator::FunctionCall {
                 column_index: *column_index,
                 line,
                 name,
                 anchor,
+                dimension,
                 selectors,
+                after_execute,
             }));
         }
         if let Some(terminator) = get_breakpoint_terminator(BreakpointPositionInLine::AfterFunction)
         {
             partitions.push(next_partition(terminator));
         }
+        if let Line::Return {
+            return_index,
+            run_index,
+            selectors,
+            ..
+        } = command
+        {
+            partitions.push(next_partition(Terminator::ReturnCommand {
+                line,
+                return_index: *return_index,
+                run_index: *run_index,
+                selectors,
+            }));
+        }
 
-        if matches!(command, Line::Breakpoint | Line::FunctionCall { .. }) {
-            start_line_index += 1; // Skip the line containing the breakpoint / function call
+        if matches!(
+            command,
+            Line::Breakpoint | Line::FunctionCall { .. } | Line::Return { .. }
+        ) {
+            start_line_index += 1; // Skip the line containing the breakpoint / function call / return
         }
     }
     partitions.push(Partition {
@@ -185,6 +256,7 @@ pub(crate) enum PositionInLine {
     Entry,
     Breakpoint,
     Function,
+    AfterExecute,
     AfterFunction,
     Return,
 }
@@ -196,6 +268,7 @@ impl FromStr for PositionInLine {
             "entry" => Ok(PositionInLine::Entry),
             "breakpoint" => Ok(PositionInLine::Breakpoint),
             "function" => Ok(PositionInLine::Function),
+            "after_execute" => Ok(PositionInLine::AfterExecute),
             "after_function" => Ok(PositionInLine::AfterFunction),
             "return" => Ok(PositionInLine::Return),
             _ => Err(()),
@@ -208,8 +281,12 @@ impl Display for PositionInLine {
             PositionInLine::Entry => write!(f, "entry"),
             PositionInLine::Breakpoint => write!(f, "breakpoint"),
             PositionInLine::Function => write!(f, "function"),
+            PositionInLine::AfterExecute => write!(f, "after_execute"),
             PositionInLine::AfterFunction => write!(f, "after_function"),
             PositionInLine::Return => write!(f, "return"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,435 @@
+use super::*;
+use crate::{
+    config::{
+        adapter::{AdapterConfig, LocalBreakpointPosition, SuspendSchedules},
+        DEFAULT_MAX_COMMANDS,
+    },
+    parser::{command::CommandParser, parse_line},
+};
+
+fn config() -> Config<'static> {
+    Config {
+        namespace: "mcfd",
+        shadow: false,
+        exclude: &[],
+        excluded_functions: BTreeSet::new(),
+        skip_invalid_functions: false,
+        additional_datapacks: &[],
+        max_commands: DEFAULT_MAX_COMMANDS,
+        adapter: None,
+    }
+}
+
+fn parse_lines(parser: &CommandParser, lines: &[&str]) -> Vec<(usize, String, Line)> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(line_index, line)| {
+            let command = parse_line(parser, line, true);
+            (line_index + 1, line.to_string(), command)
+        })
+        .collect()
+}
+
+#[test]
+fn test_return_command_terminates_partition() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let config = config();
+    let function = ResourceLocation::new("test", "main");
+    let lines = parse_lines(
+        &parser,
+        &["say 1", "execute if entity @s run return 1", "say 2"],
+    );
+
+    // when:
+    let actual = partition(&function, &lines, &config);
+
+    // then:
+    let return_position = Position {
+        line_number: 2,
+        position_in_line: PositionInLine::Return,
+    };
+    assert_eq!(actual.len(), 2);
+    assert_eq!(actual[0].end, return_position);
+    assert_eq!(actual[0].regular_lines, &lines[0..1]);
+    assert_eq!(
+        actual[0].terminator,
+        Terminator::ReturnCommand {
+            line: "execute if entity @s run return 1",
+            return_index: 25,
+            run_index: None,
+            selectors: &BTreeSet::from([18]),
+        }
+    );
+    assert_eq!(actual[1].start, return_position);
+    assert_eq!(actual[1].regular_lines, &lines[2..3]);
+    assert_eq!(actual[1].terminator, Terminator::Return);
+}
+
+#[test]
+fn test_partition_with_only_comments_and_empty_lines() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let config = config();
+    let function = ResourceLocation::new("test", "main");
+    let lines = parse_lines(&parser, &["# first comment", "", "# second comment", ""]);
+
+    // when:
+    let actual = partition(&function, &lines, &config);
+
+    // then:
+    assert_eq!(actual.len(), 1);
+    assert_eq!(actual[0].start, position(0, PositionInLine::Entry));
+    assert_eq!(actual[0].end, position(4, PositionInLine::Return));
+    assert_eq!(actual[0].regular_lines, &lines[0..4]);
+    assert_eq!(actual[0].terminator, Terminator::Return);
+}
+
+fn config_with_breakpoints(
+    breakpoints: &MultiMap<ResourceLocation, LocalBreakpoint>,
+) -> Config<'_> {
+    Config {
+        adapter: Some(AdapterConfig {
+            adapter_listener_name: "test",
+            breakpoints,
+            suspend_schedules: SuspendSchedules::All,
+        }),
+        ..config()
+    }
+}
+
+fn position(line_number: usize, position_in_line: PositionInLine) -> Position {
+    Position {
+        line_number,
+        position_in_line,
+    }
+}
+
+fn breakpoint(
+    line_number: usize,
+    position_in_line: BreakpointPositionInLine,
+    kind: BreakpointKind,
+) -> LocalBreakpoint {
+    LocalBreakpoint {
+        kind,
+        position: LocalBreakpointPosition {
+            line_number,
+            position_in_line,
+        },
+    }
+}
+
+fn is_function_call(terminator: &Terminator, function: &str) -> bool {
+    matches!(terminator, Terminator::FunctionCall { name, .. } if name.to_string() == function)
+}
+
+#[test]
+fn test_partition_with_single_breakpoint() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let config = config();
+    let function = ResourceLocation::new("test", "main");
+    let lines = parse_lines(&parser, &["say 1", "say 2", "# breakpoint", "say 3"]);
+
+    // when:
+    let actual = partition(&function, &lines, &config);
+
+    // then:
+    assert_eq!(actual.len(), 2);
+    assert_eq!(actual[0].start, position(0, PositionInLine::Entry));
+    assert_eq!(actual[0].end, position(3, PositionInLine::Breakpoint));
+    assert_eq!(actual[0].regular_lines.len(), 2);
+    assert_eq!(actual[0].terminator, Terminator::Breakpoint);
+    assert_eq!(actual[1].start, position(3, PositionInLine::Breakpoint));
+    assert_eq!(actual[1].end, position(4, PositionInLine::Return));
+    assert_eq!(actual[1].regular_lines.len(), 1);
+    assert_eq!(actual[1].terminator, Terminator::Return);
+}
+
+#[test]
+fn test_partition_with_function_call_as_last_line() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let config = config();
+    let function = ResourceLocation::new("test", "main");
+    let lines = parse_lines(&parser, &["say 1", "function test:inner"]);
+
+    // when:
+    let actual = partition(&function, &lines, &config);
+
+    // then:
+    assert_eq!(actual.len(), 2);
+    assert_eq!(actual[0].start, position(0, PositionInLine::Entry));
+    assert_eq!(actual[0].end, position(2, PositionInLine::Function));
+    assert_eq!(actual[0].regular_lines.len(), 1);
+    assert!(is_function_call(&actual[0].terminator, "test:inner"));
+    assert_eq!(actual[1].start, position(2, PositionInLine::Function));
+    assert_eq!(actual[1].end, position(2, PositionInLine::Return));
+    assert_eq!(actual[1].regular_lines.len(), 0);
+    assert_eq!(actual[1].terminator, Terminator::Return);
+}
+
+#[test]
+fn test_partition_with_function_call_in_the_middle() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let config = config();
+    let function = ResourceLocation::new("test", "main");
+    let lines = parse_lines(
+        &parser,
+        &[
+            "say 1",
+            "execute as @e run function test:inner",
+            "say 2",
+            "say 3",
+        ],
+    );
+
+    // when:
+    let actual = partition(&function, &lines, &config);
+
+    // then:
+    assert_eq!(actual.len(), 2);
+    assert_eq!(actual[0].start, position(0, PositionInLine::Entry));
+    assert_eq!(actual[0].end, position(2, PositionInLine::Function));
+    assert_eq!(actual[0].regular_lines, &lines[0..1]);
+    assert_eq!(
+        actual[0].terminator,
+        Terminator::FunctionCall {
+            column_index: 18,
+            line: "execute as @e run function test:inner",
+            name: &ResourceLocation::new("test", "inner"),
+            anchor: &None,
+            dimension: &None,
+            selectors: &BTreeSet::from([11]),
+            after_execute: None,
+        }
+    );
+    assert_eq!(actual[1].start, position(2, PositionInLine::Function));
+    assert_eq!(actual[1].end, position(4, PositionInLine::Return));
+    assert_eq!(actual[1].regular_lines, &lines[2..4]);
+    assert_eq!(actual[1].terminator, Terminator::Return);
+}
+
+/// The step breakpoints that the debug adapter creates for step in, step over, step out and step
+/// into a target.
+#[test]
+fn test_partition_with_step_breakpoints() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let function = ResourceLocation::new("test", "main");
+    let step = |condition: &str| BreakpointKind::Step {
+        condition: condition.to_string(),
+    };
+    let mut breakpoints = MultiMap::new();
+    for breakpoint in [
+        breakpoint(1, BreakpointPositionInLine::Breakpoint, step("in")),
+        breakpoint(2, BreakpointPositionInLine::Breakpoint, step("over")),
+        breakpoint(2, BreakpointPositionInLine::AfterFunction, step("out")),
+        breakpoint(3, BreakpointPositionInLine::AfterExecute, step("target")),
+    ] {
+        breakpoints.insert(function.clone(), breakpoint);
+    }
+    let config = config_with_breakpoints(&breakpoints);
+    let lines = parse_lines(
+        &parser,
+        &[
+            "say 1",
+            "function test:inner",
+            "execute as @e run function test:inner",
+        ],
+    );
+
+    // when:
+    let actual = partition(&function, &lines, &config);
+
+    // then:
+    assert_eq!(actual.len(), 6);
+    assert_eq!(actual[0].start, position(0, PositionInLine::Entry));
+    assert_eq!(actual[0].end, position(1, PositionInLine::Breakpoint));
+    assert_eq!(actual[0].regular_lines.len(), 0);
+    assert_eq!(
+        actual[0].terminator,
+        Terminator::Step {
+            condition: "in",
+            position_in_line: BreakpointPositionInLine::Breakpoint,
+        }
+    );
+    assert_eq!(actual[1].start, position(1, PositionInLine::Breakpoint));
+    assert_eq!(actual[1].end, position(2, PositionInLine::Breakpoint));
+    assert_eq!(actual[1].regular_lines, &lines[0..1]);
+    assert_eq!(
+        actual[1].terminator,
+        Terminator::Step {
+            condition: "over",
+            position_in_line: BreakpointPositionInLine::Breakpoint,
+        }
+    );
+    assert_eq!(actual[2].start, position(2, PositionInLine::Breakpoint));
+    assert_eq!(actual[2].end, position(2, PositionInLine::Function));
+    assert_eq!(actual[2].regular_lines.len(), 0);
+    assert!(is_function_call(&actual[2].terminator, "test:inner"));
+    assert_eq!(actual[3].start, position(2, PositionInLine::Function));
+    assert_eq!(actual[3].end, position(2, PositionInLine::AfterFunction));
+    assert_eq!(actual[3].regular_lines.len(), 0);
+    assert_eq!(
+        actual[3].terminator,
+        Terminator::Step {
+            condition: "out",
+            position_in_line: BreakpointPositionInLine::AfterFunction,
+        }
+    );
+    // A step breakpoint after the execute prefix is part of the function call
+    assert_eq!(actual[4].start, position(2, PositionInLine::AfterFunction));
+    assert_eq!(actual[4].end, position(3, PositionInLine::Function));
+    assert_eq!(actual[4].regular_lines.len(), 0);
+    assert!(matches!(
+        actual[4].terminator,
+        Terminator::FunctionCall {
+            after_execute: Some(BreakpointKind::Step { condition }),
+            ..
+        } if condition == "target"
+    ));
+    assert_eq!(actual[5].start, position(3, PositionInLine::Function));
+    assert_eq!(actual[5].end, position(3, PositionInLine::Return));
+    assert_eq!(actual[5].regular_lines.len(), 0);
+    assert_eq!(actual[5].terminator, Terminator::Return);
+}
+
+#[test]
+fn test_partition_with_continue_breakpoints() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let function = ResourceLocation::new("test", "main");
+    let mut breakpoints = MultiMap::new();
+    for breakpoint in [
+        breakpoint(
+            2,
+            BreakpointPositionInLine::Breakpoint,
+            BreakpointKind::Continue,
+        ),
+        breakpoint(
+            3,
+            BreakpointPositionInLine::Breakpoint,
+            BreakpointKind::Continue,
+        ),
+        breakpoint(
+            3,
+            BreakpointPositionInLine::AfterFunction,
+            BreakpointKind::Continue,
+        ),
+    ] {
+        breakpoints.insert(function.clone(), breakpoint);
+    }
+    let config = config_with_breakpoints(&breakpoints);
+    let lines = parse_lines(&parser, &["say 1", "say 2", "function test:inner", "say 3"]);
+
+    // when:
+    let actual = partition(&function, &lines, &config);
+
+    // then:
+    let continue_at = |position_in_line| Terminator::Continue { position_in_line };
+    assert_eq!(actual.len(), 5);
+    assert_eq!(actual[0].start, position(0, PositionInLine::Entry));
+    assert_eq!(actual[0].end, position(2, PositionInLine::Breakpoint));
+    assert_eq!(actual[0].regular_lines, &lines[0..1]);
+    assert_eq!(
+        actual[0].terminator,
+        continue_at(BreakpointPositionInLine::Breakpoint)
+    );
+    assert_eq!(actual[1].start, position(2, PositionInLine::Breakpoint));
+    assert_eq!(actual[1].end, position(3, PositionInLine::Breakpoint));
+    assert_eq!(actual[1].regular_lines, &lines[1..2]);
+    assert_eq!(
+        actual[1].terminator,
+        continue_at(BreakpointPositionInLine::Breakpoint)
+    );
+    assert_eq!(actual[2].start, position(3, PositionInLine::Breakpoint));
+    assert_eq!(actual[2].end, position(3, PositionInLine::Function));
+    assert_eq!(actual[2].regular_lines.len(), 0);
+    assert!(is_function_call(&actual[2].terminator, "test:inner"));
+    assert_eq!(actual[3].start, position(3, PositionInLine::Function));
+    assert_eq!(actual[3].end, position(3, PositionInLine::AfterFunction));
+    assert_eq!(actual[3].regular_lines.len(), 0);
+    assert_eq!(
+        actual[3].terminator,
+        continue_at(BreakpointPositionInLine::AfterFunction)
+    );
+    assert_eq!(actual[4].start, position(3, PositionInLine::AfterFunction));
+    assert_eq!(actual[4].end, position(4, PositionInLine::Return));
+    assert_eq!(actual[4].regular_lines, &lines[3..4]);
+    assert_eq!(actual[4].terminator, Terminator::Return);
+}
+
+#[test]
+fn test_partition_with_invalid_breakpoint() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let function = ResourceLocation::new("test", "main");
+    let mut breakpoints = MultiMap::new();
+    breakpoints.insert(
+        function.clone(),
+        breakpoint(
+            2,
+            BreakpointPositionInLine::Breakpoint,
+            BreakpointKind::Invalid,
+        ),
+    );
+    let config = config_with_breakpoints(&breakpoints);
+    let lines = parse_lines(&parser, &["say 1", "say 2"]);
+
+    // when:
+    let actual = partition(&function, &lines, &config);
+
+    // then:
+    assert_eq!(actual.len(), 1);
+    assert_eq!(actual[0].start, position(0, PositionInLine::Entry));
+    assert_eq!(actual[0].end, position(2, PositionInLine::Return));
+    assert_eq!(actual[0].regular_lines.len(), 2);
+    assert_eq!(actual[0].terminator, Terminator::Return);
+}
+
+#[test]
+fn test_partition_with_only_function_calls() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let config = config();
+    let function = ResourceLocation::new("test", "main");
+    let calls = (0..10)
+        .map(|index| format!("function test:fn{}", index))
+        .collect::<Vec<_>>();
+    let lines = parse_lines(
+        &parser,
+        &calls.iter().map(String::as_str).collect::<Vec<_>>(),
+    );
+
+    // when:
+    let actual = partition(&function, &lines, &config);
+
+    // then:
+    assert_eq!(actual.len(), 11);
+    assert_eq!(actual[0].start, position(0, PositionInLine::Entry));
+    for (index, partition) in actual.iter().take(10).enumerate() {
+        let line_number = index + 1;
+        if index > 0 {
+            assert_eq!(
+                partition.start,
+                position(line_number - 1, PositionInLine::Function)
+            );
+        }
+        assert_eq!(
+            partition.end,
+            position(line_number, PositionInLine::Function)
+        );
+        assert_eq!(partition.regular_lines.len(), 0);
+        assert!(is_function_call(
+            &partition.terminator,
+            &format!("test:fn{}", index)
+        ));
+    }
+    assert_eq!(actual[10].start, position(10, PositionInLine::Function));
+    assert_eq!(actual[10].end, position(10, PositionInLine::Return));
+    assert_eq!(actual[10].regular_lines.len(), 0);
+    assert_eq!(actual[10].terminator, Terminator::Return);
+}
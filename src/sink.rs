@@ -0,0 +1,390 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    incremental::write_if_changed,
+    staging::{commit, stage},
+};
+use async_trait::async_trait;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    future::Future,
+    io::{self, Stdout, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+use tokio::{
+    fs::{create_dir_all, remove_dir_all, remove_file},
+    time::sleep,
+};
+
+/// The destination of the files of a generated debug datapack.
+#[async_trait]
+pub trait FileSink: Sync {
+    async fn create_dir_all(&self, path: PathBuf) -> io::Result<()>;
+
+    async fn write(&self, path: PathBuf, content: String) -> io::Result<()>;
+
+    /// Removes an outdated file. Files that don't exist are ignored.
+    async fn remove_file(&self, path: PathBuf) -> io::Result<()>;
+
+    async fn remove_dir_all(&self, path: PathBuf) -> io::Result<()>;
+
+    /// Prepares the generation of a debug datapack at `output_path` and returns the directory that
+    /// the files are written to instead. By default they are written to `output_path` directly.
+    async fn stage(&self, output_path: PathBuf) -> io::Result<PathBuf> {
+        Ok(output_path)
+    }
+
+    /// Moves a debug datapack that was generated into the directory returned by
+    /// [FileSink::stage] to `output_path`.
+    async fn commit(&self, _staged_path: PathBuf, _output_path: PathBuf) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes files to the file system, skipping files that already have the given contents. The debug
+/// datapack is generated into a separate directory and moved into place at the end, see
+/// [crate::staging].
+pub struct FsSink;
+
+#[async_trait]
+impl FileSink for FsSink {
+    async fn create_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        create_dir_all(path).await
+    }
+
+    async fn write(&self, path: PathBuf, content: String) -> io::Result<()> {
+        write_if_changed(path, content).await
+    }
+
+    async fn remove_file(&self, path: PathBuf) -> io::Result<()> {
+        match remove_file(path).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    async fn remove_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        remove_dir_all(path).await
+    }
+
+    async fn stage(&self, output_path: PathBuf) -> io::Result<PathBuf> {
+        stage(output_path).await
+    }
+
+    async fn commit(&self, staged_path: PathBuf, output_path: PathBuf) -> io::Result<()> {
+        commit(staged_path, output_path).await
+    }
+}
+
+/// Keeps all written files in memory. Visible for testing only.
+#[derive(Default)]
+pub struct MemorySink {
+    files: Mutex<BTreeMap<PathBuf, String>>,
+}
+
+impl MemorySink {
+    pub fn new() -> MemorySink {
+        MemorySink::default()
+    }
+
+    pub fn into_files(self) -> BTreeMap<PathBuf, String> {
+        self.files.into_inner().unwrap()
+    }
+}
+
+#[async_trait]
+impl FileSink for MemorySink {
+    async fn create_dir_all(&self, _path: PathBuf) -> io::Result<()> {
+        Ok(())
+    }
+
+    async fn write(&self, path: PathBuf, content: String) -> io::Result<()> {
+        self.files.lock().unwrap().insert(path, content);
+        Ok(())
+    }
+
+    async fn remove_file(&self, path: PathBuf) -> io::Result<()> {
+        self.files.lock().unwrap().remove(&path);
+        Ok(())
+    }
+
+    async fn remove_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .retain(|file, _content| !file.starts_with(&path));
+        Ok(())
+    }
+}
+
+/// Prints what would be written or removed instead of changing the file system. Only the first
+/// [DryRunSink::PREVIEW_LINES] lines of each file are printed.
+pub struct DryRunSink<W> {
+    out: Mutex<W>,
+}
+
+impl DryRunSink<Stdout> {
+    pub fn stdout() -> DryRunSink<Stdout> {
+        DryRunSink::new(io::stdout())
+    }
+}
+
+impl<W: Write + Send> DryRunSink<W> {
+    pub const PREVIEW_LINES: usize = 5;
+
+    pub fn new(out: W) -> DryRunSink<W> {
+        DryRunSink {
+            out: Mutex::new(out),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out.into_inner().unwrap()
+    }
+
+    fn remove(&self, path: PathBuf) -> io::Result<()> {
+        if path.exists() {
+            writeln!(self.out.lock().unwrap(), "remove {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send> FileSink for DryRunSink<W> {
+    async fn create_dir_all(&self, _path: PathBuf) -> io::Result<()> {
+        Ok(())
+    }
+
+    async fn write(&self, path: PathBuf, content: String) -> io::Result<()> {
+        let mut out = self.out.lock().unwrap();
+        writeln!(out, "write {}", path.display())?;
+        let mut lines = content.lines();
+        for line in lines.by_ref().take(Self::PREVIEW_LINES) {
+            writeln!(out, "    {}", line)?;
+        }
+        if lines.next().is_some() {
+            writeln!(out, "    ...")?;
+        }
+        Ok(())
+    }
+
+    async fn remove_file(&self, path: PathBuf) -> io::Result<()> {
+        self.remove(path)
+    }
+
+    async fn remove_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        self.remove(path)
+    }
+}
+
+/// Forwards everything to another sink and counts the written files.
+pub struct CountingSink<'l> {
+    inner: &'l dyn FileSink,
+    files_written: AtomicUsize,
+}
+
+impl<'l> CountingSink<'l> {
+    pub fn new(inner: &'l dyn FileSink) -> CountingSink<'l> {
+        CountingSink {
+            inner,
+            files_written: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn files_written(&self) -> usize {
+        self.files_written.load(Ordering::Relaxed)
+    }
+}
+
+#[async_trait]
+impl FileSink for CountingSink<'_> {
+    async fn create_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        self.inner.create_dir_all(path).await
+    }
+
+    async fn write(&self, path: PathBuf, content: String) -> io::Result<()> {
+        self.inner.write(path, content).await?;
+        self.files_written.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn remove_file(&self, path: PathBuf) -> io::Result<()> {
+        self.inner.remove_file(path).await
+    }
+
+    async fn remove_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        self.inner.remove_dir_all(path).await
+    }
+
+    async fn stage(&self, output_path: PathBuf) -> io::Result<PathBuf> {
+        self.inner.stage(output_path).await
+    }
+
+    async fn commit(&self, staged_path: PathBuf, output_path: PathBuf) -> io::Result<()> {
+        self.inner.commit(staged_path, output_path).await
+    }
+}
+
+/// Forwards everything to another sink and retries operations that fail because a file is locked,
+/// which happens for example when the output is in a folder synchronized by OneDrive or Dropbox.
+///
+/// Writes that fail even after retrying don't fail the generation. Instead they are collected, so
+/// [RetrySink::check_failures] can report all files that could not be written.
+pub struct RetrySink<'l> {
+    inner: &'l dyn FileSink,
+    failures: Mutex<Vec<WriteFailure>>,
+}
+
+impl<'l> RetrySink<'l> {
+    /// The delays before retrying a failed operation.
+    pub const RETRY_DELAYS: [Duration; 3] = [
+        Duration::from_millis(50),
+        Duration::from_millis(200),
+        Duration::from_millis(800),
+    ];
+
+    pub fn new(inner: &'l dyn FileSink) -> RetrySink<'l> {
+        RetrySink {
+            inner,
+            failures: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns a [WriteFailures] error if any write failed.
+    pub fn check_failures(&self) -> io::Result<()> {
+        let failures = std::mem::take(&mut *self.failures.lock().unwrap());
+        match failures.first() {
+            Some(first) => Err(io::Error::new(
+                first.error.kind(),
+                WriteFailures { failures },
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Runs the operation and retries it up to three times while it fails because of a locked file.
+/// The returned error contains the path.
+async fn retry<F, O>(path: &Path, mut operation: F) -> io::Result<()>
+where
+    F: FnMut() -> O,
+    O: Future<Output = io::Result<()>>,
+{
+    let mut result = operation().await;
+    for delay in RetrySink::RETRY_DELAYS {
+        match &result {
+            Err(e) if is_locked(e) => sleep(delay).await,
+            _ => break,
+        }
+        result = operation().await;
+    }
+    result.map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+/// Whether the error is typical for a file that is locked by another process.
+fn is_locked(error: &io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    error.kind() == io::ErrorKind::PermissionDenied
+        || cfg!(windows)
+            && matches!(
+                error.raw_os_error(),
+                Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+            )
+}
+
+#[async_trait]
+impl FileSink for RetrySink<'_> {
+    async fn create_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        retry(&path, || self.inner.create_dir_all(path.clone())).await
+    }
+
+    async fn write(&self, path: PathBuf, content: String) -> io::Result<()> {
+        let result = retry(&path, || self.inner.write(path.clone(), content.clone())).await;
+        if let Err(error) = result {
+            self.failures
+                .lock()
+                .unwrap()
+                .push(WriteFailure { path, error });
+        }
+        Ok(())
+    }
+
+    async fn remove_file(&self, path: PathBuf) -> io::Result<()> {
+        retry(&path, || self.inner.remove_file(path.clone())).await
+    }
+
+    async fn remove_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        retry(&path, || self.inner.remove_dir_all(path.clone())).await
+    }
+
+    async fn stage(&self, output_path: PathBuf) -> io::Result<PathBuf> {
+        self.inner.stage(output_path).await
+    }
+
+    async fn commit(&self, staged_path: PathBuf, output_path: PathBuf) -> io::Result<()> {
+        retry(&output_path, || {
+            self.inner.commit(staged_path.clone(), output_path.clone())
+        })
+        .await
+    }
+}
+
+/// The files of a debug datapack that could not be written, see [RetrySink].
+#[derive(Debug)]
+pub struct WriteFailures {
+    pub failures: Vec<WriteFailure>,
+}
+
+#[derive(Debug)]
+pub struct WriteFailure {
+    pub path: PathBuf,
+    /// The error of the last attempt, which already contains the path.
+    pub error: io::Error,
+}
+
+impl WriteFailures {
+    /// Returns the [WriteFailures] that caused `error`, if any.
+    pub fn find(error: &io::Error) -> Option<&WriteFailures> {
+        error.get_ref()?.downcast_ref()
+    }
+}
+
+impl Display for WriteFailures {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to write {} files:", self.failures.len())?;
+        for failure in &self.failures {
+            write!(f, "\n{}", failure.error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for WriteFailures {}
+
+#[cfg(test)]
+pub(crate) mod tests;
@@ -0,0 +1,162 @@
+use super::*;
+use std::collections::HashMap;
+use tokio::time::Instant;
+
+/// A [MemorySink] whose writes fail for the configured paths.
+#[derive(Default)]
+pub(crate) struct FlakySink {
+    inner: MemorySink,
+    /// The error kind and the number of remaining failures for each path.
+    failures: Mutex<HashMap<PathBuf, (io::ErrorKind, usize)>>,
+    attempts: Mutex<HashMap<PathBuf, usize>>,
+}
+
+impl FlakySink {
+    pub(crate) fn new() -> FlakySink {
+        FlakySink::default()
+    }
+
+    /// Lets the next `count` writes of `path` fail with `kind`.
+    pub(crate) fn fail(self, path: impl Into<PathBuf>, kind: io::ErrorKind, count: usize) -> Self {
+        self.failures
+            .lock()
+            .unwrap()
+            .insert(path.into(), (kind, count));
+        self
+    }
+
+    pub(crate) fn attempts(&self, path: impl AsRef<Path>) -> usize {
+        let attempts = self.attempts.lock().unwrap();
+        attempts.get(path.as_ref()).copied().unwrap_or_default()
+    }
+
+    pub(crate) fn into_files(self) -> BTreeMap<PathBuf, String> {
+        self.inner.into_files()
+    }
+}
+
+#[async_trait]
+impl FileSink for FlakySink {
+    async fn create_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        self.inner.create_dir_all(path).await
+    }
+
+    async fn write(&self, path: PathBuf, content: String) -> io::Result<()> {
+        *self
+            .attempts
+            .lock()
+            .unwrap()
+            .entry(path.clone())
+            .or_default() += 1;
+        if let Some((kind, remaining)) = self.failures.lock().unwrap().get_mut(&path) {
+            if *remaining > 0 {
+                *remaining -= 1;
+                return Err(io::Error::new(*kind, "flaky"));
+            }
+        }
+        self.inner.write(path, content).await
+    }
+
+    async fn remove_file(&self, path: PathBuf) -> io::Result<()> {
+        self.inner.remove_file(path).await
+    }
+
+    async fn remove_dir_all(&self, path: PathBuf) -> io::Result<()> {
+        self.inner.remove_dir_all(path).await
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retry_sink_retries_locked_file() {
+    // given:
+    let path = PathBuf::from("out/locked.mcfunction");
+    let flaky = FlakySink::new().fail(&path, io::ErrorKind::PermissionDenied, 2);
+    let sink = RetrySink::new(&flaky);
+    let start = Instant::now();
+
+    // when:
+    sink.write(path.clone(), "say hi".to_string())
+        .await
+        .unwrap();
+
+    // then:
+    assert_eq!(start.elapsed(), Duration::from_millis(50 + 200));
+    sink.check_failures().unwrap();
+    assert_eq!(flaky.attempts(&path), 3);
+    assert_eq!(flaky.into_files()[&path], "say hi");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retry_sink_collects_all_failures() {
+    // given:
+    let first = PathBuf::from("out/first.mcfunction");
+    let second = PathBuf::from("out/second.mcfunction");
+    let written = PathBuf::from("out/written.mcfunction");
+    let flaky = FlakySink::new()
+        .fail(&first, io::ErrorKind::PermissionDenied, usize::MAX)
+        .fail(&second, io::ErrorKind::PermissionDenied, usize::MAX);
+    let sink = RetrySink::new(&flaky);
+    let start = Instant::now();
+
+    // when:
+    sink.write(first.clone(), String::new()).await.unwrap();
+    sink.write(second.clone(), String::new()).await.unwrap();
+    sink.write(written.clone(), String::new()).await.unwrap();
+
+    // then:
+    assert_eq!(start.elapsed(), 2 * Duration::from_millis(50 + 200 + 800));
+    assert_eq!(flaky.attempts(&first), 4);
+    assert_eq!(flaky.attempts(&second), 4);
+    assert_eq!(flaky.attempts(&written), 1);
+
+    let error = sink.check_failures().unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    let failures = &WriteFailures::find(&error).unwrap().failures;
+    let paths = failures.iter().map(|it| &it.path).collect::<Vec<_>>();
+    assert_eq!(paths, [&first, &second]);
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Failed to write 2 files:\n{}: flaky\n{}: flaky",
+            first.display(),
+            second.display()
+        )
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retry_sink_does_not_retry_other_errors() {
+    // given:
+    let path = PathBuf::from("out/missing/fn.mcfunction");
+    let flaky = FlakySink::new().fail(&path, io::ErrorKind::NotFound, 1);
+    let sink = RetrySink::new(&flaky);
+    let start = Instant::now();
+
+    // when:
+    sink.write(path.clone(), String::new()).await.unwrap();
+
+    // then:
+    assert_eq!(start.elapsed(), Duration::ZERO);
+    assert_eq!(flaky.attempts(&path), 1);
+    let error = sink.check_failures().unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_check_failures_resets_failures() {
+    // given:
+    let memory = MemorySink::new();
+    let sink = RetrySink::new(&memory);
+    sink.failures.lock().unwrap().push(WriteFailure {
+        path: PathBuf::from("out/fn.mcfunction"),
+        error: io::Error::new(io::ErrorKind::PermissionDenied, "locked"),
+    });
+
+    // when:
+    let first = sink.check_failures();
+    let second = sink.check_failures();
+
+    // then:
+    assert!(first.is_err());
+    assert!(second.is_ok());
+}
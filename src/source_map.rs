@@ -0,0 +1,81 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Source maps record which lines of the original functions each generated partition was created
+//! from. When a function file is edited after the debug datapack was generated, the line numbers
+//! Minecraft executes no longer match the file, which can be detected with [SourceMap::is_changed].
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// The path of the source map of a function in the debug datapack.
+pub const SOURCE_MAP_PATH: &str = "data/-ns-/sourcemaps/-orig_ns-/-orig/fn-.json";
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SourceMap {
+    pub partitions: Vec<PartitionSourceMap>,
+}
+impl SourceMap {
+    /// Returns the hash of the original text of the line when the debug datapack was generated.
+    pub fn get_line_hash(&self, line_number: usize) -> Option<u64> {
+        self.partitions
+            .iter()
+            .flat_map(|partition| &partition.lines)
+            .find(|line| line.line_number == line_number)
+            .map(|line| line.hash)
+    }
+
+    /// Returns true if the line in `content` differs from the line the debug datapack was generated
+    /// from. Lines that are not part of the source map are considered unchanged.
+    pub fn is_changed(&self, line_number: usize, content: &str) -> bool {
+        match self.get_line_hash(line_number) {
+            Some(hash) => {
+                let line = content.lines().nth(line_number - 1);
+                line.is_none_or(|line| hash_line(line) != hash)
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionSourceMap {
+    /// The start and end position of the partition as used in the name of its function, for
+    /// example `1_entry-3_function`.
+    pub positions: String,
+    pub lines: Vec<LineSourceMap>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineSourceMap {
+    /// The line number in the original function, starting at 1.
+    pub line_number: usize,
+    /// The hash of the original text of the line, see [hash_line].
+    pub hash: u64,
+}
+
+pub fn hash_line(line: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.hash(&mut hasher);
+    hasher.finish()
+}
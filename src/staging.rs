@@ -0,0 +1,128 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! A debug datapack is generated into a sibling directory of the output path and moved into place
+//! at the end, so a generation that is interrupted, for example because the process is killed,
+//! never leaves a partially written debug datapack that Minecraft could load.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    fs::{read_dir, remove_dir_all, rename},
+    task::spawn_blocking,
+};
+
+/// Prepares the generation of a debug datapack at `output_path` and returns the directory that
+/// the files are written to instead. It starts with hard links to the files of the previous
+/// generation, so unchanged files don't need to be written again.
+pub(crate) async fn stage(output_path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let output_path = output_path.as_ref();
+    remove_staging_dirs(output_path).await?;
+    let staged_path = staging_path(output_path);
+    let from = output_path.to_path_buf();
+    let to = staged_path.clone();
+    spawn_blocking(move || link_dir_all(&from, &to))
+        .await
+        .unwrap()?;
+    Ok(staged_path)
+}
+
+/// Moves a debug datapack that was generated into the directory returned by [stage] to
+/// `output_path`.
+pub(crate) async fn commit(
+    staged_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let staged_path = staged_path.as_ref();
+    let output_path = output_path.as_ref();
+    match rename(staged_path, output_path).await {
+        // Windows can't rename over an existing directory, other systems over a non-empty one
+        Err(_) if output_path.is_dir() => {}
+        result => return result,
+    }
+    let old_path = staging_path(output_path);
+    rename(output_path, &old_path).await?;
+    if let Err(e) = rename(staged_path, output_path).await {
+        let _ = rename(&old_path, output_path).await;
+        return Err(e);
+    }
+    // If this fails, the next generation removes the directory
+    let _ = remove_dir_all(old_path).await;
+    Ok(())
+}
+
+/// Returns a new path next to `output_path` for a generation that is not finished yet.
+fn staging_path(output_path: &Path) -> PathBuf {
+    let random = RandomState::new().build_hasher().finish();
+    let mut file_name = output_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".tmp-{:016x}", random));
+    output_path.with_file_name(file_name)
+}
+
+/// Removes the directories that generations into `output_path` left behind when they were
+/// interrupted, see [staging_path].
+async fn remove_staging_dirs(output_path: &Path) -> io::Result<()> {
+    let mut prefix = output_path.file_name().unwrap_or_default().to_os_string();
+    prefix.push(".tmp-");
+    let prefix = prefix.to_string_lossy();
+    let parent = match output_path.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    };
+    let mut entries = match read_dir(parent).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_name().to_string_lossy().starts_with(&*prefix)
+            && entry.file_type().await?.is_dir()
+        {
+            remove_dir_all(entry.path()).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Recreates the directory `from` at `to` with hard links to its files. Files are copied if they
+/// can't be linked.
+fn link_dir_all(from: &Path, to: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    let entries = match std::fs::read_dir(from) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let to = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            link_dir_all(&entry.path(), &to)?;
+        } else if std::fs::hard_link(entry.path(), &to).is_err() {
+            std::fs::copy(entry.path(), &to)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
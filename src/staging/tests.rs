@@ -0,0 +1,54 @@
+use super::*;
+use std::fs::{create_dir_all, read_to_string, remove_file, write};
+
+#[tokio::test]
+async fn test_commit_replaces_output() {
+    // given:
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-staging-test")
+        .join("test_commit_replaces_output");
+    let _ = std::fs::remove_dir_all(&dir);
+    let output_path = dir.join("output");
+    create_dir_all(output_path.join("data")).unwrap();
+    write(output_path.join("data/changed.txt"), "old").unwrap();
+    write(output_path.join("data/removed.txt"), "old").unwrap();
+
+    // when:
+    let staged_path = stage(&output_path).await.unwrap();
+    remove_file(staged_path.join("data/changed.txt")).unwrap();
+    write(staged_path.join("data/changed.txt"), "new").unwrap();
+    remove_file(staged_path.join("data/removed.txt")).unwrap();
+    let before_commit = read_to_string(output_path.join("data/changed.txt")).unwrap();
+    commit(&staged_path, &output_path).await.unwrap();
+
+    // then:
+    assert_eq!(before_commit, "old");
+    let changed = read_to_string(output_path.join("data/changed.txt")).unwrap();
+    assert_eq!(changed, "new");
+    assert!(!output_path.join("data/removed.txt").exists());
+    assert!(!staged_path.exists());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn test_stage_removes_leftover_staging_dirs() {
+    // given:
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-staging-test")
+        .join("test_stage_removes_leftover_staging_dirs");
+    let _ = std::fs::remove_dir_all(&dir);
+    let output_path = dir.join("output");
+    create_dir_all(&output_path).unwrap();
+    let leftover = dir.join("output.tmp-0123456789abcdef");
+    create_dir_all(leftover.join("data")).unwrap();
+    let other = dir.join("other.tmp-0123456789abcdef");
+    create_dir_all(&other).unwrap();
+
+    // when:
+    let staged_path = stage(&output_path).await.unwrap();
+
+    // then:
+    assert!(!leftover.exists());
+    assert!(other.exists());
+    assert!(staged_path.is_dir());
+}
@@ -0,0 +1,176 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    config::Config,
+    defers_load, find_objectives, find_unguarded_lines,
+    parser::{command::resource_location::ResourceLocation, Line},
+    partition::partition,
+    validation::ValidationIssue,
+};
+use multimap::MultiMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Facts about a generated debug datapack for tools that invoke the generator.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedSummary {
+    /// The directory name of the generated datapack. Minecraft calls it `file/<output_name>`.
+    pub output_name: String,
+    /// The internal namespace of the generated datapack.
+    pub namespace: String,
+    /// All debugged functions ordered by name.
+    pub functions: Vec<FunctionSummary>,
+    /// Functions that are called, but not defined in the input datapack.
+    pub missing_functions: Vec<String>,
+    /// Functions that are not debugged, because they are marked with `# mcfd:skip` or listed in
+    /// the config, ordered by name. Functions excluded by a pattern are not listed.
+    pub excluded_functions: Vec<String>,
+    /// Functions with more consecutive commands than the configured maximum. The commands between
+    /// two function calls or breakpoints can't be split, so Minecraft may not run these functions.
+    pub oversized_functions: Vec<String>,
+    /// The largest number of commands between two function calls or breakpoints. Minecraft's
+    /// gamerule `maxCommandChainLength` must be at least this large to run the debugged functions.
+    pub largest_partition: usize,
+    /// Problems of the generated datapack that don't prevent debugging, like commands that can't be
+    /// modified, because they are too long.
+    pub warnings: Vec<ValidationIssue>,
+    /// Whether the functions of `#minecraft:load` only run when `debug:run_load` is called, because
+    /// the debug adapter set a breakpoint in a function that is called during load.
+    pub deferred_load: bool,
+    /// The scoreboard objectives used by the debugged functions ordered by name. The scores of
+    /// other objectives are only known to the debugger after they were created at runtime.
+    pub objectives: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct FunctionSummary {
+    pub name: String,
+    /// The number of `# breakpoint` lines in the function.
+    pub breakpoints: usize,
+    /// The function to start debugging this function with.
+    pub entry_point: String,
+}
+
+/// A function of the input datapack as listed by [crate::list_functions].
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FunctionListing {
+    pub function: String,
+    /// The line numbers of all `# breakpoint` lines.
+    pub breakpoints: Vec<usize>,
+    /// The functions and function tags called by the function ordered by name.
+    pub calls: Vec<String>,
+}
+
+/// A region of a function that Minecraft executes without the debugger being able to suspend, as
+/// returned by [crate::find_partitions]. Line numbers start at 1.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionRange {
+    pub start_line: usize,
+    /// Where in the start line the region starts, for example `entry` or `breakpoint`.
+    pub start_position: String,
+    pub end_line: usize,
+    /// Where in the end line the region ends, for example `function` or `return`.
+    pub end_position: String,
+    /// What ends the region: `breakpoint`, `step`, `continue`, `functionCall`, `returnCommand` or
+    /// `return`.
+    pub terminator_kind: String,
+}
+
+impl GeneratedSummary {
+    pub(crate) fn new(
+        output_name: &str,
+        config: &Config<'_>,
+        fn_contents: &HashMap<&ResourceLocation, Vec<(usize, String, Line)>>,
+        call_tree: &MultiMap<&ResourceLocation, (&ResourceLocation, &usize)>,
+    ) -> GeneratedSummary {
+        let mut functions = fn_contents
+            .iter()
+            .map(|(name, lines)| FunctionSummary {
+                name: name.to_string(),
+                breakpoints: lines
+                    .iter()
+                    .filter(|(_, _, line)| matches!(line, Line::Breakpoint))
+                    .count(),
+                entry_point: format!("debug:{}/{}", name.namespace(), name.path()),
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let missing_functions = call_tree
+            .keys()
+            .filter(|callee| !fn_contents.contains_key(*callee))
+            .map(|callee| callee.to_string())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let largest_partitions = fn_contents
+            .iter()
+            .map(|(name, lines)| (name, get_largest_partition(name, lines, config)))
+            .collect::<Vec<_>>();
+        let mut oversized_functions = largest_partitions
+            .iter()
+            .filter(|(_name, commands)| *commands > config.max_commands)
+            .map(|(name, _commands)| name.to_string())
+            .collect::<Vec<_>>();
+        oversized_functions.sort();
+        let largest_partition = largest_partitions
+            .iter()
+            .map(|(_name, commands)| *commands)
+            .max()
+            .unwrap_or_default();
+
+        GeneratedSummary {
+            output_name: output_name.to_string(),
+            namespace: config.namespace.to_string(),
+            functions,
+            missing_functions,
+            excluded_functions: config
+                .excluded_functions
+                .iter()
+                .map(ResourceLocation::to_string)
+                .collect(),
+            oversized_functions,
+            largest_partition,
+            warnings: find_unguarded_lines(fn_contents, config),
+            deferred_load: defers_load(fn_contents, config),
+            objectives: find_objectives(fn_contents).into_iter().cloned().collect(),
+        }
+    }
+}
+
+/// Returns the largest number of commands in a partition of the function.
+fn get_largest_partition(
+    function: &ResourceLocation,
+    lines: &[(usize, String, Line)],
+    config: &Config<'_>,
+) -> usize {
+    partition(function, lines, config)
+        .iter()
+        .map(|partition| {
+            partition
+                .regular_lines
+                .iter()
+                .filter(|(_, _, line)| !matches!(line, Line::Empty | Line::Comment))
+                .count()
+        })
+        .max()
+        .unwrap_or_default()
+}
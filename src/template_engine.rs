@@ -16,7 +16,10 @@
 // You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use crate::parser::{command::resource_location::ResourceLocationRef, Line, ScheduleOperation};
+use crate::{
+    config::{MinecraftVersion, OBJECTIVES, TAGS},
+    parser::{command::resource_location::ResourceLocationRef, Line, ScheduleOperation},
+};
 use minect::command::named_logged_command;
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -64,7 +67,7 @@ impl<'l> TemplateEngine<'l> {
         engine
     }
 
-    pub fn expand(&self, string: &str) -> String {
+    fn apply_replacements(&self, string: &str) -> String {
         let mut with_replacements_applied = string.to_owned();
         for (from, to) in &self.replacements {
             with_replacements_applied = with_replacements_applied.replace(from, to);
@@ -72,11 +75,40 @@ impl<'l> TemplateEngine<'l> {
         for (from, to) in &self.replacements_owned {
             with_replacements_applied = with_replacements_applied.replace(from, to);
         }
+        with_replacements_applied
+    }
+
+    /// Applies the replacements of this engine and expands the directives of the template. The
+    /// directives `# -add_objectives-`, `# -remove_objectives-` and `# -remove_tags-` are replaced
+    /// with one command for each of the [OBJECTIVES] or [TAGS]. Lines between `# @if <condition>`,
+    /// `# @else` and `# @endif` are only kept if the condition holds after the replacements were
+    /// applied, see [evaluate_condition].
+    pub fn expand(&self, string: &str) -> String {
+        let with_replacements_applied = self.apply_replacements(string);
 
         let mut result = String::new();
 
+        // Whether the current branch of every enclosing `# @if` is taken
+        let mut conditions = Vec::new();
         let mut lines = with_replacements_applied.split_inclusive('\n');
         while let Some(line) = lines.next() {
+            if let Some(condition) = line.trim().strip_prefix("# @if ") {
+                conditions.push(evaluate_condition(condition));
+                continue;
+            }
+            match line.trim() {
+                "# @else" => {
+                    let condition = conditions.last_mut().expect("# @else without # @if");
+                    *condition = !*condition;
+                    continue;
+                }
+                "# @endif" => {
+                    conditions.pop().expect("# @endif without # @if");
+                    continue;
+                }
+                _ if conditions.contains(&false) => continue,
+                _ => {}
+            }
             match line.trim() {
                 "# -minect_log-" => {
                     if let Some(command) = lines.next() {
@@ -91,6 +123,21 @@ impl<'l> TemplateEngine<'l> {
                         }
                     }
                 }
+                "# -add_objectives-" => {
+                    result.push_str(&self.expand_for_each(&OBJECTIVES, |objective| {
+                        format!("scoreboard objectives add {} dummy", objective)
+                    }))
+                }
+                "# -remove_objectives-" => {
+                    result.push_str(&self.expand_for_each(&OBJECTIVES, |objective| {
+                        format!("scoreboard objectives remove {}", objective)
+                    }))
+                }
+                "# -remove_tags-" => {
+                    result.push_str(&self.expand_for_each(&TAGS, |tag| {
+                        format!("tag @e[tag={}] remove {}", tag, tag)
+                    }))
+                }
                 "# -if_not_adapter-" => {
                     if let Some(command) = lines.next() {
                         if self.adapter_listener_name.is_none() {
@@ -103,16 +150,37 @@ impl<'l> TemplateEngine<'l> {
                 }
             }
         }
+        assert!(conditions.is_empty(), "# @if without # @endif");
 
         result
     }
 
+    /// Returns the command for each of the names on its own line with the replacements applied.
+    fn expand_for_each(&self, names: &[&str], command: impl Fn(&str) -> String) -> String {
+        names
+            .iter()
+            .map(|name| self.apply_replacements(&command(name)) + "\n")
+            .collect()
+    }
+
+    /// Like [TemplateEngine::expand], but afterwards every slot in the template is replaced with
+    /// its text. The text is inserted unchanged, so it can contain commands of the user.
+    pub fn expand_with_slots(&self, template: &str, slots: &[(&str, &str)]) -> String {
+        fill_slots(&self.expand(template), slots)
+    }
+
+    /// Like [exclude_internal_entites_from_selectors], but with the namespace of this engine. The
+    /// rest of the line is not expanded, so it is preserved byte for byte.
+    pub fn exclude_internal_entities(&self, line: &str, selectors: &BTreeSet<usize>) -> String {
+        exclude_entities_with_tag(line, selectors, &self.expand("-ns-"))
+    }
+
     pub fn expand_line(&self, (_line_number, line, command): &(usize, String, Line)) -> String {
         match command {
             Line::Breakpoint => {
                 unreachable!()
             }
-            Line::FunctionCall { .. } => {
+            Line::FunctionCall { .. } | Line::Return { .. } => {
                 unreachable!()
             }
             Line::OptionalSelectorCommand {
@@ -134,8 +202,7 @@ impl<'l> TemplateEngine<'l> {
                     })
                     .collect::<BTreeSet<_>>();
                 selectors.insert(*missing_selector + 1);
-                let line = exclude_internal_entites_from_selectors(&line, &selectors);
-                self.expand(&line)
+                self.exclude_internal_entities(&line, &selectors)
             }
             Line::Schedule {
                 schedule_start,
@@ -145,12 +212,10 @@ impl<'l> TemplateEngine<'l> {
                 ..
             } => {
                 let schedule_fn = function.path().replace('/', "+");
-                let execute =
-                    exclude_internal_entites_from_selectors(&line[..*schedule_start], selectors);
+                let execute = self.exclude_internal_entities(&line[..*schedule_start], selectors);
                 let mut engine = self.extend([
                     ("-schedule_ns-", function.namespace()),
                     ("-schedule+fn-", &schedule_fn),
-                    ("execute run ", &execute),
                 ]);
 
                 let ticks;
@@ -173,19 +238,67 @@ impl<'l> TemplateEngine<'l> {
                     }
                 };
 
-                engine.expand(template)
-            }
-            Line::OtherCommand { selectors, .. } => {
-                let line = exclude_internal_entites_from_selectors(line, selectors);
-                self.expand(&line)
+                engine.expand_with_slots(template, &[("execute run ", &execute)])
             }
-            Line::Comment => self.expand(&line),
-            Line::Empty => line.to_owned(),
+            Line::OtherCommand { selectors, .. } => self.exclude_internal_entities(line, selectors),
+            Line::Comment | Line::Empty => line.to_owned(),
         }
     }
 }
 
+/// Evaluates the condition of an `# @if` directive. A condition compares two [MinecraftVersion]s
+/// with one of the operators `==`, `!=`, `<`, `<=`, `>` or `>=`, for example `1.19.4 >= 1.20`.
+///
+/// # Panics
+///
+/// Panics if the condition is malformed, for example because a replacement is missing.
+fn evaluate_condition(condition: &str) -> bool {
+    let parse_version = |version: &str| {
+        version.parse::<MinecraftVersion>().unwrap_or_else(|()| {
+            panic!("Invalid version '{}' in condition '{}'", version, condition)
+        })
+    };
+    let (left, operator, right) = match condition.split_whitespace().collect::<Vec<_>>()[..] {
+        [left, operator, right] => (parse_version(left), operator, parse_version(right)),
+        _ => panic!("Invalid condition '{}'", condition),
+    };
+    match operator {
+        "==" => left == right,
+        "!=" => left != right,
+        "<" => left < right,
+        "<=" => left <= right,
+        ">" => left > right,
+        ">=" => left >= right,
+        _ => panic!(
+            "Invalid operator '{}' in condition '{}'",
+            operator, condition
+        ),
+    }
+}
+
+/// Replaces every occurrence of a slot in `string` with the text of the slot. The inserted text is
+/// never searched for other slots.
+fn fill_slots(string: &str, slots: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(string.len());
+    let mut remaining = string;
+    while let Some((index, slot, text)) = slots
+        .iter()
+        .filter_map(|(slot, text)| Some((remaining.find(slot)?, slot, text)))
+        .min_by_key(|(index, _slot, _text)| *index)
+    {
+        result.push_str(&remaining[..index]);
+        result.push_str(text);
+        remaining = &remaining[index + slot.len()..];
+    }
+    result.push_str(remaining);
+    result
+}
+
 pub fn exclude_internal_entites_from_selectors(line: &str, selectors: &BTreeSet<usize>) -> String {
+    exclude_entities_with_tag(line, selectors, "-ns-")
+}
+
+fn exclude_entities_with_tag(line: &str, selectors: &BTreeSet<usize>, tag: &str) -> String {
     let mut index = 0;
     let mut result = String::new();
     for selector in selectors {
@@ -194,7 +307,8 @@ pub fn exclude_internal_entites_from_selectors(line: &str, selectors: &BTreeSet<
         result.push_str(&prefix[index..]);
         index = prefix.len();
 
-        result.push_str("[tag=!-ns-");
+        result.push_str("[tag=!");
+        result.push_str(tag);
         if remaining_line.starts_with('[') {
             index += 1;
             result.push(',');
@@ -205,3 +319,6 @@ pub fn exclude_internal_entites_from_selectors(line: &str, selectors: &BTreeSet<
     result.push_str(&line[index..]);
     result
 }
+
+#[cfg(test)]
+mod tests;
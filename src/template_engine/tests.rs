@@ -0,0 +1,171 @@
+use super::*;
+use crate::parser::{command::CommandParser, parse_line};
+
+fn exclude_internal_entities(line: &str) -> String {
+    let parser = CommandParser::default().unwrap();
+    let selectors = match parse_line(&parser, line, false) {
+        Line::FunctionCall { selectors, .. }
+        | Line::OptionalSelectorCommand { selectors, .. }
+        | Line::Schedule { selectors, .. }
+        | Line::Return { selectors, .. }
+        | Line::OtherCommand { selectors, .. } => selectors,
+        line => panic!("Expected a command, but got {:?}", line),
+    };
+    exclude_internal_entites_from_selectors(line, &selectors)
+}
+
+#[test]
+fn test_exclude_internal_entities_with_nbt_containing_spaces() {
+    // given:
+    let line = r#"execute as @e[nbt={Item: {id: "minecraft:stone"}}] run function test:func"#;
+
+    // when:
+    let actual = exclude_internal_entities(line);
+
+    // then:
+    assert_eq!(
+        actual,
+        r#"execute as @e[tag=!-ns-,nbt={Item: {id: "minecraft:stone"}}] run function test:func"#
+    );
+}
+
+#[test]
+fn test_exclude_internal_entities_with_brackets_and_commas_in_strings() {
+    // given:
+    let line = r#"execute as @e[name="a], @e[b",tag=x] at @s run function test:func"#;
+
+    // when:
+    let actual = exclude_internal_entities(line);
+
+    // then:
+    assert_eq!(
+        actual,
+        r#"execute as @e[tag=!-ns-,name="a], @e[b",tag=x] at @s[tag=!-ns-] run function test:func"#
+    );
+}
+
+#[test]
+fn test_exclude_internal_entities_in_nested_execute() {
+    // given:
+    let line = "execute as @a run execute as @e[scores={x=1..}] run return run say @e";
+
+    // when:
+    let actual = exclude_internal_entities(line);
+
+    // then:
+    assert_eq!(
+        actual,
+        "execute as @a[tag=!-ns-] run execute as @e[tag=!-ns-,scores={x=1..}] run return run say \
+        @e[tag=!-ns-]"
+    );
+}
+
+#[test]
+fn test_exclude_internal_entities_ignores_player_names() {
+    // given:
+    let line = "execute as Steve at @s run function test:func";
+
+    // when:
+    let actual = exclude_internal_entities(line);
+
+    // then:
+    assert_eq!(
+        actual,
+        "execute as Steve at @s[tag=!-ns-] run function test:func"
+    );
+}
+
+#[test]
+fn test_expand_with_slots_does_not_expand_slot_text() {
+    // given:
+    let engine = TemplateEngine::new(BTreeMap::from_iter([("-ns-", "mcfd")]), None);
+    let template = "say -ns-\n# -content-\nexecute run say -ns-";
+    let slots = [
+        ("# -content-", "say -ns- execute run "),
+        ("execute run ", "execute as @a[tag=!mcfd] run "),
+    ];
+
+    // when:
+    let actual = engine.expand_with_slots(template, &slots);
+
+    // then:
+    assert_eq!(
+        actual,
+        "say mcfd\nsay -ns- execute run \nexecute as @a[tag=!mcfd] run say mcfd"
+    );
+}
+
+const VERSION_GUARDED_TEMPLATE: &str = "\
+say before
+# @if -version- >= 1.20
+return 1
+# @else
+function -ns-:return
+# @endif
+say after
+";
+
+#[test]
+fn test_expand_version_guard_above_threshold() {
+    // given:
+    let engine = TemplateEngine::new(
+        BTreeMap::from_iter([("-ns-", "mcfd"), ("-version-", "1.20.4")]),
+        None,
+    );
+
+    // when:
+    let actual = engine.expand(VERSION_GUARDED_TEMPLATE);
+
+    // then:
+    assert_eq!(actual, "say before\nreturn 1\nsay after\n");
+}
+
+#[test]
+fn test_expand_version_guard_below_threshold() {
+    // given:
+    let engine = TemplateEngine::new(
+        BTreeMap::from_iter([("-ns-", "mcfd"), ("-version-", "1.19.4")]),
+        None,
+    );
+
+    // when:
+    let actual = engine.expand(VERSION_GUARDED_TEMPLATE);
+
+    // then:
+    assert_eq!(actual, "say before\nfunction mcfd:return\nsay after\n");
+}
+
+#[test]
+fn test_expand_nested_version_guards() {
+    // given:
+    let engine = TemplateEngine::new(BTreeMap::from_iter([("-version-", "1.19")]), None);
+    let template = "\
+# @if -version- < 1.20
+say old
+# @if -version- >= 1.19.4
+say 1.19.4
+# @endif
+# @else
+say new
+# @if -version- >= 1.19
+say unreachable
+# @endif
+# @endif
+";
+
+    // when:
+    let actual = engine.expand(template);
+
+    // then:
+    assert_eq!(actual, "say old\n");
+}
+
+#[test]
+#[should_panic(expected = "Invalid version '-version-'")]
+fn test_expand_version_guard_without_replacement() {
+    // given:
+    let engine = TemplateEngine::new(BTreeMap::new(), None);
+
+    // when:
+    engine.expand(VERSION_GUARDED_TEMPLATE);
+}
@@ -0,0 +1,1219 @@
+use super::*;
+use crate::{
+    archive::tests::write_zip,
+    config::adapter::{AdapterConfig, LocalBreakpointPosition, SuspendSchedules},
+    config::{DEFAULT_MAX_COMMANDS, MAX_COMMAND_LENGTH, PACK_FORMAT},
+    sink::{tests::FlakySink, DryRunSink, MemorySink, WriteFailures},
+    summary::{FunctionListing, FunctionSummary, GeneratedSummary, PartitionRange},
+};
+use std::fs::{create_dir_all, remove_dir_all, write};
+
+/// Creates an empty directory for the test in the temporary directory.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-lib-test")
+        .join(name);
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Creates a datapack for the test in the temporary directory. The files are given as their path in
+/// the datapack and their content. Unless a `pack.mcmeta` is given, an empty one is created.
+fn temp_datapack(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let datapack = temp_dir(name);
+    write(datapack.join("pack.mcmeta"), "{}").unwrap();
+    for (path, content) in files {
+        let path = datapack.join(path);
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, content).unwrap();
+    }
+    datapack
+}
+
+fn test_config() -> Config<'static> {
+    Config {
+        namespace: "mcfd",
+        shadow: false,
+        exclude: &[],
+        excluded_functions: BTreeSet::new(),
+        skip_invalid_functions: false,
+        additional_datapacks: &[],
+        max_commands: DEFAULT_MAX_COMMANDS,
+        adapter: None,
+    }
+}
+
+#[tokio::test]
+async fn test_find_function_files_in_nested_directories() {
+    // given:
+    let datapack = temp_datapack(
+        "test_find_function_files_in_nested_directories",
+        &[
+            ("data/ns0/functions/main.mcfunction", "say hi"),
+            ("data/ns0/functions/not_a_function.txt", ""),
+            ("data/ns0/functions/dir0/fn0.mcfunction", "say hi"),
+            ("data/ns0/functions/dir0/sub/fn1.mcfunction", "say hi"),
+            ("data/ns1/functions/main.mcfunction", "say hi"),
+            ("data/ns1/functions/dir0/fn0.mcfunction", "say hi"),
+            ("data/ns1/functions/dir0/sub/fn1.mcfunction", "say hi"),
+            (
+                "data/no_functions/tags/functions/tag.json",
+                r#"{"values":[]}"#,
+            ),
+        ],
+    );
+
+    // when:
+    let actual = find_valid_function_files(&datapack, false)
+        .await
+        .map(|(functions, _invalid)| functions)
+        .unwrap();
+
+    // then:
+    let names = actual.keys().map(|it| it.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "ns0:dir0/fn0",
+            "ns0:dir0/sub/fn1",
+            "ns0:main",
+            "ns1:dir0/fn0",
+            "ns1:dir0/sub/fn1",
+            "ns1:main",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_find_function_files_with_unicode_namespace() {
+    // given:
+    let datapack = temp_datapack(
+        "test_find_function_files_with_unicode_namespace",
+        &[("data/überwelt/functions/main.mcfunction", "say hi")],
+    );
+
+    // when:
+    let actual = find_valid_function_files(&datapack, false)
+        .await
+        .map(|(functions, _invalid)| functions);
+
+    // then:
+    let error = actual.unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert!(
+        error.to_string().contains("main.mcfunction"),
+        "Unexpected error: {}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_find_function_files_in_unicode_datapack_dir() {
+    // given:
+    let datapack = temp_datapack(
+        "test_find_function_files_in_unicode_datapack_dir_é",
+        &[
+            ("data/ns0/functions/main.mcfunction", "say hi"),
+            ("data/ns0/functions/dir0/fn0.mcfunction", "say hi"),
+        ],
+    );
+
+    // when:
+    let actual = find_valid_function_files(&datapack, false)
+        .await
+        .map(|(functions, _invalid)| functions)
+        .unwrap();
+
+    // then:
+    let names = actual.keys().map(|it| it.to_string()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["ns0:dir0/fn0", "ns0:main"]);
+}
+
+#[tokio::test]
+async fn test_generate_debug_datapack_from_zip() {
+    // given:
+    let dir = temp_dir("test_generate_debug_datapack_from_zip");
+    let zip = dir.join("pack.zip");
+    write_zip(
+        &zip,
+        &[
+            (
+                "pack.mcmeta",
+                r#"{"pack":{"pack_format":7,"description":""}}"#,
+            ),
+            (
+                "data/test/functions/main.mcfunction",
+                "# breakpoint\nfunction #test:tag",
+            ),
+            ("data/test/functions/other.mcfunction", "say hi"),
+            ("data/test/functions/Invalid.mcfunction", "say hi"),
+            (
+                "data/test/tags/functions/tag.json",
+                r#"{"values":["test:other"]}"#,
+            ),
+        ],
+    );
+    let output_path = dir.join("output");
+    let parser = CommandParser::default().unwrap();
+    let config = Config {
+        skip_invalid_functions: true,
+        ..test_config()
+    };
+    let sink = MemorySink::new();
+
+    // when:
+    let actual = generate_debug_datapack_with_sink(&zip, &output_path, &parser, &config, &sink)
+        .await
+        .unwrap();
+
+    // then:
+    assert_eq!(actual.functions.len(), 3, "{:#?}", actual.functions);
+    let generated = sink.into_files();
+    let fn_dir = output_path.join("data/mcfd/functions/test");
+    assert!(generated.contains_key(&fn_dir.join("main/1_breakpoint-2_function.mcfunction")));
+    assert!(generated.contains_key(&fn_dir.join("other/start.mcfunction")));
+    let tag_dir = output_path.join("data/mcfd/functions/mcfd/tags/test/tag");
+    assert!(generated.contains_key(&tag_dir.join("start.mcfunction")));
+    assert!(!zip.with_extension("").exists());
+}
+
+async fn find_debugged_tags(datapack: &Path) -> Vec<String> {
+    let config = test_config();
+    let (functions, _invalid) = find_valid_function_files(datapack, false).await.unwrap();
+    let tag_functions = find_tag_functions(datapack, &functions, &config)
+        .await
+        .unwrap();
+    tag_functions.keys().map(|it| it.to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_find_tag_functions_with_nested_tag() {
+    // given:
+    let test = "test_find_tag_functions_with_nested_tag";
+    let datapack = temp_datapack(
+        &format!("{}/datapack", test),
+        &[
+            ("data/test/functions/main.mcfunction", "say hi"),
+            (
+                "data/test/tags/functions/outer.json",
+                r##"{"values":["test:main",{"id":"#test:inner"}]}"##,
+            ),
+            (
+                "data/test/tags/functions/inner.json",
+                r#"{"values":["test:main"]}"#,
+            ),
+        ],
+    );
+
+    // when:
+    let actual = find_debugged_tags(&datapack).await;
+
+    // then:
+    assert_eq!(actual, ["mcfd:tags/test/inner", "mcfd:tags/test/outer"]);
+}
+
+#[tokio::test]
+async fn test_find_tag_functions_ignores_tag_with_function_of_other_datapack() {
+    // given:
+    let test = "test_find_tag_functions_ignores_tag_with_function_of_other_datapack";
+    let datapack = temp_datapack(
+        &format!("{}/datapack", test),
+        &[
+            ("data/test/functions/main.mcfunction", "say hi"),
+            (
+                "data/test/tags/functions/tag.json",
+                r#"{"values":["test:main","other:main"]}"#,
+            ),
+            (
+                "data/test/tags/functions/outer.json",
+                r##"{"values":["#test:tag"]}"##,
+            ),
+        ],
+    );
+
+    // when:
+    let actual = find_debugged_tags(&datapack).await;
+
+    // then:
+    assert!(actual.is_empty(), "{:?}", actual);
+}
+
+#[tokio::test]
+async fn test_find_tag_functions_ignores_tag_of_other_datapack() {
+    // given:
+    let test = "test_find_tag_functions_ignores_tag_of_other_datapack";
+    let files = [
+        ("data/test/functions/main.mcfunction", "say hi"),
+        (
+            "data/minecraft/tags/functions/load.json",
+            r#"{"values":["test:main"]}"#,
+        ),
+        (
+            "data/test/tags/functions/tag.json",
+            r#"{"values":["test:main"]}"#,
+        ),
+    ];
+    let datapack = temp_datapack(&format!("{}/datapack", test), &files);
+    temp_datapack(&format!("{}/other", test), &files[..2]);
+    let debug_datapack = temp_datapack(&format!("{}/debug-datapack", test), &files);
+    write(debug_datapack.join("incremental_state.txt"), "").unwrap();
+
+    // when:
+    let actual = find_debugged_tags(&datapack).await;
+
+    // then:
+    assert_eq!(actual, ["mcfd:tags/test/tag"]);
+}
+
+/// Generates a debug datapack for a datapack with the given pack.mcmeta and returns the generated
+/// pack.mcmeta.
+async fn generate_pack_mcmeta(name: &str, pack_mcmeta: &str) -> Value {
+    let datapack = temp_datapack(
+        name,
+        &[
+            ("pack.mcmeta", pack_mcmeta),
+            ("data/test/functions/main.mcfunction", "say hi"),
+        ],
+    );
+    let output_path = datapack.with_file_name(format!("{}-output", name));
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = MemorySink::new();
+    generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+        .await
+        .unwrap();
+    let generated = sink.into_files();
+    serde_json::from_str(&generated[&output_path.join("pack.mcmeta")]).unwrap()
+}
+
+#[tokio::test]
+async fn test_generate_debug_datapack_copies_pack_format() {
+    // when:
+    let actual = generate_pack_mcmeta(
+        "test_generate_debug_datapack_copies_pack_format",
+        r#"{"pack":{"pack_format":15,"description":""}}"#,
+    )
+    .await;
+
+    // then:
+    assert_eq!(actual["pack"]["pack_format"], 15);
+}
+
+#[tokio::test]
+async fn test_generate_debug_datapack_without_pack_format() {
+    // when:
+    let actual =
+        generate_pack_mcmeta("test_generate_debug_datapack_without_pack_format", "{}").await;
+
+    // then:
+    assert_eq!(actual["pack"]["pack_format"], PACK_FORMAT);
+}
+
+#[tokio::test]
+async fn test_generate_launch_config_for_zip() {
+    // given:
+    let dir = temp_dir("test_generate_launch_config_for_zip");
+    let zip = dir.join("pack.zip");
+    write_zip(
+        &zip,
+        &[
+            ("pack.mcmeta", "{}"),
+            ("data/test/functions/main.mcfunction", "# breakpoint"),
+        ],
+    );
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+
+    // when:
+    let actual = generate_launch_config(&zip, &parser, &config)
+        .await
+        .unwrap();
+
+    // then:
+    assert_eq!(
+        actual["program"],
+        format!("{}!/data/test/functions/main.mcfunction", zip.display())
+    );
+}
+
+/// Creates a datapack with a valid function and a function whose file name contains uppercase
+/// letters.
+fn create_mixed_case_datapack(name: &str) -> PathBuf {
+    temp_datapack(
+        name,
+        &[
+            ("data/test/functions/main.mcfunction", "say main"),
+            ("data/test/functions/MyFunc.mcfunction", "say invalid"),
+        ],
+    )
+}
+
+#[tokio::test]
+async fn test_generate_with_invalid_function_name() {
+    // given:
+    let datapack = create_mixed_case_datapack("test_generate_with_invalid_function_name");
+    let output_path = datapack.with_file_name("test_generate_with_invalid_function_name-out");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+
+    // when:
+    let actual = generate_debug_datapack_with_sink(
+        &datapack,
+        &output_path,
+        &parser,
+        &config,
+        &MemorySink::new(),
+    )
+    .await;
+
+    // then:
+    let error = actual.unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert!(
+        error.to_string().contains("test:[M]yFunc")
+            && error.to_string().contains("MyFunc.mcfunction"),
+        "Unexpected error: {}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_generate_skipping_invalid_function_name() {
+    // given:
+    let datapack = create_mixed_case_datapack("test_generate_skipping_invalid_function_name");
+    let output_path = datapack.with_file_name("test_generate_skipping_invalid_function_name-out");
+    let parser = CommandParser::default().unwrap();
+    let config = Config {
+        skip_invalid_functions: true,
+        ..test_config()
+    };
+
+    // when:
+    let actual = generate_debug_datapack_with_sink(
+        &datapack,
+        &output_path,
+        &parser,
+        &config,
+        &MemorySink::new(),
+    )
+    .await
+    .unwrap();
+
+    // then:
+    let functions = actual
+        .functions
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(functions, vec!["test:main"]);
+    assert_eq!(actual.warnings.len(), 1, "{:?}", actual.warnings);
+    assert!(actual.warnings[0].message.contains("test:[M]yFunc"));
+}
+
+#[tokio::test]
+async fn test_generate_with_suspend_schedules() {
+    // given:
+    let datapack = temp_datapack(
+        "test_generate_with_suspend_schedules",
+        &[
+            (
+                "data/test/functions/main.mcfunction",
+                "schedule function test:scheduled 1t",
+            ),
+            ("data/test/functions/scheduled.mcfunction", "say hi"),
+        ],
+    );
+    let output_path = datapack.join("output");
+    let parser = CommandParser::default().unwrap();
+    let breakpoints = MultiMap::new();
+    let config = Config {
+        adapter: Some(AdapterConfig {
+            adapter_listener_name: "test",
+            breakpoints: &breakpoints,
+            suspend_schedules: SuspendSchedules::CallStackOnly,
+        }),
+        ..test_config()
+    };
+    let sink = MemorySink::new();
+
+    // when:
+    generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+        .await
+        .unwrap();
+
+    // then:
+    let generated = sink.into_files();
+    let install = &generated[&output_path.join("data/mcfd/functions/install.mcfunction")];
+    assert!(install
+        .lines()
+        .any(|line| line == "scoreboard players set suspend_schedules mcfd_global 1"));
+    let scheduled =
+        &generated[&output_path.join("data/mcfd/functions/test/scheduled/scheduled.mcfunction")];
+    assert!(
+        scheduled.contains(" run function test:scheduled\n"),
+        "{}",
+        scheduled
+    );
+    let main =
+        &generated[&output_path.join("data/mcfd/functions/test/main/0_entry-1_return.mcfunction")];
+    assert!(
+        main.contains(" mcfd_depth = current mcfd_depth\n"),
+        "{}",
+        main
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_generate_reports_all_write_failures() {
+    // given:
+    let datapack = temp_datapack(
+        "test_generate_reports_all_write_failures",
+        &[("data/test/functions/main.mcfunction", "say hi")],
+    );
+    let output_path = datapack.join("output");
+    let install = output_path.join("data/mcfd/functions/install.mcfunction");
+    let uninstall = output_path.join("data/mcfd/functions/uninstall.mcfunction");
+    let pack_mcmeta = output_path.join("pack.mcmeta");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = FlakySink::new()
+        .fail(&install, io::ErrorKind::PermissionDenied, usize::MAX)
+        .fail(&uninstall, io::ErrorKind::PermissionDenied, usize::MAX)
+        .fail(&pack_mcmeta, io::ErrorKind::PermissionDenied, 1);
+
+    // when:
+    let error = generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+        .await
+        .unwrap_err();
+
+    // then:
+    let failures = &WriteFailures::find(&error).unwrap().failures;
+    let paths = failures.iter().map(|it| &it.path).collect::<BTreeSet<_>>();
+    assert_eq!(paths, BTreeSet::from_iter([&install, &uninstall]));
+    let generated = sink.into_files();
+    assert!(generated.contains_key(&pack_mcmeta));
+    assert!(
+        generated.contains_key(&output_path.join("data/mcfd/functions/test/main/start.mcfunction"))
+    );
+    assert!(!generated.contains_key(&output_path.join("incremental_state.txt")));
+}
+
+#[tokio::test]
+async fn test_generate_with_additional_datapack() {
+    // given:
+    let datapack = temp_datapack(
+        "test_generate_with_additional_datapack-a",
+        &[("data/a/functions/main.mcfunction", "function b:callee")],
+    );
+    let additional_datapacks = [temp_datapack(
+        "test_generate_with_additional_datapack-b",
+        &[("data/b/functions/callee.mcfunction", "say callee")],
+    )];
+    let output_path = datapack.with_file_name("test_generate_with_additional_datapack-out");
+    let parser = CommandParser::default().unwrap();
+    let config = Config {
+        additional_datapacks: &additional_datapacks,
+        ..test_config()
+    };
+    let sink = MemorySink::new();
+
+    // when:
+    let actual =
+        generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+            .await
+            .unwrap();
+
+    // then:
+    let functions = actual
+        .functions
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(functions, vec!["a:main", "b:callee"]);
+    let files = sink.into_files();
+    assert!(
+        files.contains_key(&output_path.join("data/debug/functions/b/callee.mcfunction")),
+        "{:?}",
+        files.keys()
+    );
+}
+
+#[tokio::test]
+async fn test_generate_source_map() {
+    // given:
+    let datapack = temp_datapack(
+        "test_generate_source_map",
+        &[(
+            "data/a/functions/main.mcfunction",
+            "say one\n# breakpoint\nsay three",
+        )],
+    );
+    let output_path = datapack.with_file_name("test_generate_source_map-out");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = MemorySink::new();
+
+    // when:
+    generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+        .await
+        .unwrap();
+
+    // then:
+    let files = sink.into_files();
+    let source_map = &files[&output_path.join("data/mcfd/sourcemaps/a/main.json")];
+    let source_map = serde_json::from_str::<SourceMap>(source_map).unwrap();
+    let positions = source_map
+        .partitions
+        .iter()
+        .map(|it| it.positions.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(positions, ["0_entry-2_breakpoint", "2_breakpoint-3_return"]);
+    assert_eq!(source_map.get_line_hash(3), Some(hash_line("say three")));
+    assert!(!source_map.is_changed(3, "say one\n# breakpoint\nsay three"));
+    assert!(source_map.is_changed(3, "say one\n# breakpoint\nsay changed"));
+}
+
+#[tokio::test]
+async fn test_generate_with_function_in_multiple_datapacks() {
+    // given:
+    let datapack = temp_datapack(
+        "test_generate_with_function_in_multiple_datapacks-a",
+        &[("data/a/functions/main.mcfunction", "say a")],
+    );
+    let additional_datapacks = [temp_datapack(
+        "test_generate_with_function_in_multiple_datapacks-b",
+        &[("data/a/functions/main.mcfunction", "say b")],
+    )];
+    let output_path =
+        datapack.with_file_name("test_generate_with_function_in_multiple_datapacks-out");
+    let parser = CommandParser::default().unwrap();
+    let config = Config {
+        additional_datapacks: &additional_datapacks,
+        ..test_config()
+    };
+
+    // when:
+    let actual = generate_debug_datapack_with_sink(
+        &datapack,
+        &output_path,
+        &parser,
+        &config,
+        &MemorySink::new(),
+    )
+    .await;
+
+    // then:
+    let error = actual.unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    let expected_paths = [&datapack, &additional_datapacks[0]].map(|it| {
+        it.join("data/a/functions/main.mcfunction")
+            .display()
+            .to_string()
+    });
+    assert!(
+        error.to_string().contains("a:main")
+            && expected_paths
+                .iter()
+                .all(|it| error.to_string().contains(it)),
+        "Unexpected error: {}",
+        error
+    );
+}
+
+#[test]
+fn test_highlight_invalid_char() {
+    assert_eq!(
+        highlight_invalid_char("test:MyFunc"),
+        Some("test:[M]yFunc".to_string())
+    );
+    assert_eq!(
+        highlight_invalid_char("Test:func"),
+        Some("[T]est:func".to_string())
+    );
+    assert_eq!(
+        highlight_invalid_char("test:dir/fünc"),
+        Some("test:dir/f[ü]nc".to_string())
+    );
+    assert_eq!(highlight_invalid_char("test:dir/my_func-1.0"), None);
+}
+
+#[tokio::test]
+async fn test_write_dynamic_scores_functions() {
+    // given:
+    let output_path = Path::new("out");
+    let objectives = BTreeSet::from_iter(["test_dynamic".to_string()]);
+    let sink = MemorySink::new();
+
+    // when:
+    let actual = write_dynamic_scores_functions(output_path, "mcfd", "adapter", &objectives, &sink)
+        .await
+        .unwrap();
+
+    // then:
+    assert_eq!(actual.len(), 1);
+    assert!(actual[0].contains("test_dynamic"), "{}", actual[0]);
+    let files = sink.into_files();
+    let update_scores =
+        &files[&output_path.join("data/mcfd/functions/update_scores_dynamic.mcfunction")];
+    assert!(update_scores.contains("operation test_dynamic mcfd_scores = @s test_dynamic"));
+    assert!(
+        files.contains_key(&output_path.join("data/mcfd/functions/log_scores_dynamic.mcfunction"))
+    );
+}
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+/// Generates the golden datapack into memory and compares the given files with the snapshots in
+/// `tests/golden/snapshots`. Update the snapshots with:
+/// `UPDATE_SNAPSHOTS=1 cargo test golden`
+async fn assert_golden_files(files: &[&str]) {
+    // given:
+    let input_path = Path::new(GOLDEN_DIR).join("datapack");
+    let output_path = temp_dir("golden");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = MemorySink::new();
+
+    // when:
+    generate_debug_datapack_with_sink(&input_path, &output_path, &parser, &config, &sink)
+        .await
+        .unwrap();
+
+    // then:
+    let generated = sink.into_files();
+    for file in files {
+        let actual = generated
+            .get(&output_path.join(file))
+            .unwrap_or_else(|| panic!("File {} was not generated", file));
+        let snapshot_path = Path::new(GOLDEN_DIR).join("snapshots").join(file);
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            create_dir_all(snapshot_path.parent().unwrap()).unwrap();
+            write(&snapshot_path, actual).unwrap();
+        }
+        let expected = std::fs::read_to_string(&snapshot_path)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", snapshot_path.display(), e));
+        assert_eq!(actual, &expected, "Snapshot {} does not match", file);
+    }
+}
+
+#[tokio::test]
+async fn test_golden_resume_self() {
+    assert_golden_files(&["data/mcfd/functions/resume_self.mcfunction"]).await;
+}
+
+#[tokio::test]
+async fn test_golden_show_skipped() {
+    assert_golden_files(&["data/debug/functions/show_skipped.mcfunction"]).await;
+}
+
+#[tokio::test]
+async fn test_golden_positions() {
+    assert_golden_files(&[
+        "data/mcfd/functions/test/main/0_entry-2_breakpoint.mcfunction",
+        "data/mcfd/functions/test/main/2_breakpoint-3_function.mcfunction",
+        "data/mcfd/functions/test/main/3_function-4_return.mcfunction",
+        "data/mcfd/functions/test/main/4_return-5_function.mcfunction",
+        "data/mcfd/functions/test/main/5_function-7_return.mcfunction",
+    ])
+    .await;
+}
+
+#[tokio::test]
+async fn test_golden_function_call_in_dimension() {
+    assert_golden_files(&[
+        "data/mcfd/functions/test/nether/0_entry-1_function.mcfunction",
+        "data/mcfd/functions/test/inner/continue_at_0_entry.mcfunction",
+    ])
+    .await;
+}
+
+#[tokio::test]
+async fn test_generated_summary() {
+    // given:
+    let input_path = Path::new(GOLDEN_DIR).join("datapack");
+    let output_path = temp_dir("test_generated_summary");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+
+    // when:
+    let actual = generate_debug_datapack_with_sink(
+        &input_path,
+        &output_path,
+        &parser,
+        &config,
+        &MemorySink::new(),
+    )
+    .await
+    .unwrap();
+
+    // then:
+    let function = |name: &str, breakpoints, entry_point: &str| FunctionSummary {
+        name: name.to_string(),
+        breakpoints,
+        entry_point: entry_point.to_string(),
+    };
+    assert_eq!(
+        actual,
+        GeneratedSummary {
+            output_name: "test_generated_summary".to_string(),
+            namespace: "mcfd".to_string(),
+            functions: vec![
+                function("test:inner", 0, "debug:test/inner"),
+                function("test:main", 1, "debug:test/main"),
+                function("test:nether", 0, "debug:test/nether"),
+            ],
+            missing_functions: vec!["test:missing".to_string()],
+            excluded_functions: vec![],
+            oversized_functions: vec![],
+            largest_partition: 1,
+            warnings: vec![],
+            deferred_load: false,
+            objectives: vec![],
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_split_validate_of_large_function() {
+    // given:
+    let content = vec!["say hi"; 70_000].join("\n");
+    let datapack = temp_datapack(
+        "test_split_validate_of_large_function",
+        &[("data/test/functions/large.mcfunction", &content)],
+    );
+    let output_path = datapack.join("output");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = MemorySink::new();
+
+    // when:
+    let actual =
+        generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+            .await
+            .unwrap();
+
+    // then:
+    assert_eq!(actual.oversized_functions, vec!["test:large"]);
+    let generated = sink.into_files();
+    let fn_dir = output_path.join("data/mcfd/functions/test/large");
+    let count_commands = |file: &str| {
+        let content = generated
+            .get(&fn_dir.join(file))
+            .unwrap_or_else(|| panic!("File {} was not generated", file));
+        content.lines().filter(|line| is_command(line)).count()
+    };
+    let validate = generated.get(&fn_dir.join("validate.mcfunction")).unwrap();
+    assert!(validate.contains("function mcfd:test/large/validate_1\n"));
+    assert!(validate.contains("function mcfd:test/large/validate_2\n"));
+    assert_eq!(
+        count_commands("validate_1.mcfunction"),
+        DEFAULT_MAX_COMMANDS
+    );
+    assert_eq!(
+        count_commands("validate_2.mcfunction"),
+        70_000 - DEFAULT_MAX_COMMANDS
+    );
+    assert!(!generated.contains_key(&fn_dir.join("validate_3.mcfunction")));
+}
+
+#[tokio::test]
+async fn test_too_long_command_is_not_modified() {
+    // given:
+    let prefix = "give @a stone{display:{Name:'\"";
+    let suffix = "\"'}} 1";
+    let name = "a".repeat(MAX_COMMAND_LENGTH - 5 - prefix.len() - suffix.len());
+    let give = format!("{}{}{}", prefix, name, suffix);
+    let datapack = temp_datapack(
+        "test_too_long_command_is_not_modified",
+        &[(
+            "data/test/functions/main.mcfunction",
+            &format!("say hi\n{}", give),
+        )],
+    );
+    let output_path = datapack.join("output");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = MemorySink::new();
+
+    // when:
+    let actual =
+        generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+            .await
+            .unwrap();
+
+    // then:
+    let warnings = actual.warnings.iter().map(|warning| {
+        (
+            warning.function.as_deref().unwrap(),
+            warning.line_number.unwrap(),
+        )
+    });
+    assert_eq!(warnings.collect::<Vec<_>>(), vec![("test:main", 2)]);
+    let generated = sink.into_files();
+    for (path, content) in &generated {
+        assert!(
+            content.lines().all(|line| line.len() <= MAX_COMMAND_LENGTH),
+            "{} contains a command that is too long",
+            path.display()
+        );
+    }
+    let fn_dir = output_path.join("data/mcfd/functions/test/main");
+    let positions = generated
+        .get(&fn_dir.join("0_entry-2_return.mcfunction"))
+        .unwrap();
+    assert!(positions.lines().any(|line| line == give));
+    let validate = generated.get(&fn_dir.join("validate.mcfunction")).unwrap();
+    assert!(validate.contains("# Too long to validate: give @a stone"));
+}
+
+#[tokio::test]
+async fn test_placeholders_in_commands_are_preserved() {
+    // given:
+    let text = r#"{"text":"-ns- -orig_ns- -position- -orig/fn-"}"#;
+    let main = format!(
+        "tellraw @a {text}\n\
+        execute if data storage test:x {{y:{text}}} run function test:other\n\
+        # tellraw @a {text}",
+        text = text
+    );
+    let datapack = temp_datapack(
+        "test_placeholders_in_commands_are_preserved",
+        &[
+            ("data/test/functions/main.mcfunction", &main),
+            ("data/test/functions/other.mcfunction", "say hi"),
+        ],
+    );
+    let output_path = datapack.join("output");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = MemorySink::new();
+
+    // when:
+    generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+        .await
+        .unwrap();
+
+    // then:
+    let generated = sink.into_files();
+    let fn_dir = output_path.join("data/mcfd/functions/test/main");
+    let positions = generated
+        .get(&fn_dir.join("0_entry-2_function.mcfunction"))
+        .unwrap();
+    assert!(positions.contains(&format!("\ntellraw @a[tag=!mcfd] {}\n", text)));
+    assert!(positions.contains(&format!(
+        " run execute if data storage test:x {{y:{}}} run function mcfd:select_entity\n",
+        text
+    )));
+    let positions = generated
+        .get(&fn_dir.join("2_function-3_return.mcfunction"))
+        .unwrap();
+    assert!(positions.contains(&format!("\n# tellraw @a {}\n", text)));
+    let validate = generated.get(&fn_dir.join("validate.mcfunction")).unwrap();
+    let validate_tellraw = format!(
+        "execute if score 1 mcfd_constant matches 0 run tellraw @a {}",
+        text
+    );
+    assert!(validate.lines().any(|line| line == validate_tellraw));
+}
+
+#[tokio::test]
+async fn test_commands_after_return_are_unreachable() {
+    // given:
+    let datapack = temp_datapack(
+        "test_commands_after_return_are_unreachable",
+        &[(
+            "data/test/functions/main.mcfunction",
+            "say 1\nreturn 1\nsay 3",
+        )],
+    );
+    let output_path = datapack.join("output");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = MemorySink::new();
+
+    // when:
+    generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+        .await
+        .unwrap();
+
+    // then:
+    let generated = sink.into_files();
+    let fn_dir = output_path.join("data/mcfd/functions/test/main");
+    let get = |file: &str| {
+        generated
+            .get(&fn_dir.join(file))
+            .unwrap_or_else(|| panic!("File {} was not generated", file))
+    };
+    assert!(!get("0_entry-2_return.mcfunction").contains("2_return-3_return"));
+    let continue_after_return = get("continue_current_iteration_at_2_return.mcfunction");
+    assert!(continue_after_return.contains("[Warning]"));
+    assert!(!continue_after_return.contains("continue_at_2_return"));
+}
+
+#[tokio::test]
+async fn test_validate_datapack_with_missing_function() {
+    // given:
+    let datapack = temp_datapack(
+        "test_validate_datapack_with_missing_function",
+        &[
+            (
+                "data/test/functions/main.mcfunction",
+                "say hi\nfunction test:missing\nfunction test:other",
+            ),
+            ("data/test/functions/other.mcfunction", "say hi"),
+        ],
+    );
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+
+    // when:
+    let actual = validate_datapack(&datapack, &parser, &config)
+        .await
+        .unwrap();
+
+    // then:
+    assert_eq!(
+        actual,
+        ValidationReport {
+            errors: vec![ValidationIssue {
+                function: Some("test:main".to_string()),
+                line_number: Some(2),
+                message: "The called function test:missing does not exist".to_string(),
+            }],
+            warnings: vec![],
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_skip_marker_excludes_function() {
+    // given:
+    let datapack = temp_datapack(
+        "test_skip_marker_excludes_function",
+        &[
+            (
+                "data/test/functions/main.mcfunction",
+                "function test:skipped",
+            ),
+            (
+                "data/test/functions/skipped.mcfunction",
+                "# mcfd:skip\n# breakpoint\nsay hi",
+            ),
+        ],
+    );
+    let output_path = datapack.join("output");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = MemorySink::new();
+
+    // when:
+    let actual =
+        generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+            .await
+            .unwrap();
+
+    // then:
+    assert_eq!(actual.excluded_functions, vec!["test:skipped"]);
+    let names = actual
+        .functions
+        .iter()
+        .map(|function| function.name.as_str());
+    assert_eq!(names.collect::<Vec<_>>(), vec!["test:main"]);
+    let generated = sink.into_files();
+    assert!(!generated
+        .keys()
+        .any(|path| path.starts_with(output_path.join("data/mcfd/functions/test/skipped"))));
+}
+
+#[tokio::test]
+async fn test_validate_datapack_with_breakpoint_in_skipped_function() {
+    // given:
+    let datapack = temp_datapack(
+        "test_validate_datapack_with_breakpoint_in_skipped_function",
+        &[(
+            "data/test/functions/skipped.mcfunction",
+            "# mcfd:skip\nsay hi\n# breakpoint",
+        )],
+    );
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+
+    // when:
+    let actual = validate_datapack(&datapack, &parser, &config)
+        .await
+        .unwrap();
+
+    // then:
+    assert_eq!(
+        actual,
+        ValidationReport {
+            errors: vec![],
+            warnings: vec![ValidationIssue {
+                function: Some("test:skipped".to_string()),
+                line_number: Some(3),
+                message: "The breakpoint is ignored, because the function is excluded from \
+                    debugging"
+                    .to_string(),
+            }],
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_validate_datapack_with_long_namespace() {
+    // given:
+    let datapack = temp_datapack(
+        "test_validate_datapack_with_long_namespace",
+        &[("data/test/functions/main.mcfunction", "say hi")],
+    );
+    let parser = CommandParser::default().unwrap();
+    let config = Config {
+        namespace: "mcfd_test",
+        ..test_config()
+    };
+
+    // when:
+    let actual = validate_datapack(&datapack, &parser, &config)
+        .await
+        .unwrap();
+
+    // then:
+    let messages = actual
+        .errors
+        .iter()
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>();
+    assert!(messages.contains(
+        &"The scoreboard objective mcfd_test_Duration is longer than 16 characters, choose a \
+        shorter namespace for the debugger"
+            .to_string()
+    ));
+    assert!(actual.warnings.is_empty());
+}
+
+#[tokio::test]
+async fn test_list_functions() {
+    // given:
+    let datapack = temp_datapack(
+        "test_list_functions",
+        &[
+            (
+                "data/test/functions/main.mcfunction",
+                "say hi\n# breakpoint\nfunction test:other\nfunction #test:tag\n# breakpoint\nfunction test:other",
+            ),
+            ("data/test/functions/other.mcfunction", "say hi"),
+        ],
+    );
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+
+    // when:
+    let actual = list_functions(&datapack, &parser, &config).await.unwrap();
+
+    // then:
+    assert_eq!(
+        actual,
+        vec![
+            FunctionListing {
+                function: "test:main".to_string(),
+                breakpoints: vec![2, 5],
+                calls: vec!["#test:tag".to_string(), "test:other".to_string()],
+            },
+            FunctionListing {
+                function: "test:other".to_string(),
+                breakpoints: vec![],
+                calls: vec![],
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_dry_run_does_not_write_files() {
+    // given:
+    let datapack = temp_datapack(
+        "test_dry_run_does_not_write_files",
+        &[
+            (
+                "data/test/functions/main.mcfunction",
+                "say 1\nfunction test:other",
+            ),
+            ("data/test/functions/other.mcfunction", "say 2"),
+        ],
+    );
+    let output_path = datapack.join("output");
+    let parser = CommandParser::default().unwrap();
+    let config = test_config();
+    let sink = DryRunSink::new(Vec::new());
+
+    // when:
+    generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &sink)
+        .await
+        .unwrap();
+
+    // then:
+    assert!(!output_path.exists());
+    let printed = String::from_utf8(sink.into_inner()).unwrap();
+    let memory_sink = MemorySink::new();
+    generate_debug_datapack_with_sink(&datapack, &output_path, &parser, &config, &memory_sink)
+        .await
+        .unwrap();
+    let expected = memory_sink.into_files();
+    assert!(!expected.is_empty());
+    for path in expected.keys() {
+        let line = format!("write {}", path.display());
+        assert!(
+            printed.lines().any(|it| it == line),
+            "{} was not printed",
+            line
+        );
+    }
+}
+
+#[test]
+fn test_find_partitions() {
+    // given:
+    let parser = CommandParser::default().unwrap();
+    let function = ResourceLocation::new("test", "main");
+    let content = "say 1\nfunction test:inner\nsay 2\nfunction lib:excluded\nsay 3";
+    let mut breakpoints = MultiMap::new();
+    breakpoints.insert(
+        function.clone(),
+        LocalBreakpoint {
+            kind: BreakpointKind::Normal,
+            position: LocalBreakpointPosition {
+                line_number: 3,
+                position_in_line: BreakpointPositionInLine::Breakpoint,
+            },
+        },
+    );
+
+    // when:
+    let actual = find_partitions(
+        &function,
+        content,
+        &parser,
+        &["lib:**".to_string()],
+        &breakpoints,
+    );
+
+    // then:
+    let range = |start_line, start_position: &str, end_line, end_position: &str, kind: &str| {
+        PartitionRange {
+            start_line,
+            start_position: start_position.to_string(),
+            end_line,
+            end_position: end_position.to_string(),
+            terminator_kind: kind.to_string(),
+        }
+    };
+    assert_eq!(
+        actual,
+        vec![
+            range(1, "entry", 2, "function", "functionCall"),
+            range(2, "function", 3, "breakpoint", "breakpoint"),
+            range(3, "breakpoint", 5, "return", "return"),
+        ]
+    );
+}
@@ -16,6 +16,9 @@
 // You should have received a copy of the GNU General Public License along with McFunction-Debugger.
 // If not, see <http://www.gnu.org/licenses/>.
 
+pub mod diff;
+pub mod minecraft;
+
 pub trait Map0<T0, R0> {
     type Output;
 
@@ -0,0 +1,152 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+/// A line of a diff computed by [diff_lines].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiffLine<'l> {
+    Unchanged(&'l str),
+    Removed(&'l str),
+    Added(&'l str),
+}
+
+/// Files with more line combinations than this after removing their common prefix and suffix are
+/// not diffed line by line. Instead all old lines are removed and all new lines are added.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Computes a line based diff from `old` to `new` using the longest common subsequence of lines.
+pub fn diff_lines<'l>(old: &'l str, new: &'l str) -> Vec<DiffLine<'l>> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut diff = Vec::with_capacity(old.len().max(new.len()));
+    diff.extend(old[..prefix].iter().map(|line| DiffLine::Unchanged(line)));
+    if old_middle.len().saturating_mul(new_middle.len()) > MAX_DIFF_CELLS {
+        diff.extend(old_middle.iter().map(|line| DiffLine::Removed(line)));
+        diff.extend(new_middle.iter().map(|line| DiffLine::Added(line)));
+    } else {
+        diff_middle(old_middle, new_middle, &mut diff);
+    }
+    diff.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Unchanged(line)),
+    );
+    diff
+}
+
+fn diff_middle<'l>(old: &[&'l str], new: &[&'l str], diff: &mut Vec<DiffLine<'l>>) {
+    // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Unchanged(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    diff.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+}
+
+/// Formats a diff like `diff -u`, showing `context` unchanged lines around every change.
+pub fn format_unified_diff(diff: &[DiffLine], context: usize) -> String {
+    let changes = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Unchanged(_)))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    // Changes that are at most 2 * context lines apart are shown in the same hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for index in changes {
+        let start = index.saturating_sub(context);
+        let end = (index + 1 + context).min(diff.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut result = String::new();
+    for (start, end) in hunks {
+        let count = |range: &[DiffLine], old: bool| {
+            range
+                .iter()
+                .filter(|line| match line {
+                    DiffLine::Unchanged(_) => true,
+                    DiffLine::Removed(_) => old,
+                    DiffLine::Added(_) => !old,
+                })
+                .count()
+        };
+        let old_start = count(&diff[..start], true) + 1;
+        let new_start = count(&diff[..start], false) + 1;
+        let old_len = count(&diff[start..end], true);
+        let new_len = count(&diff[start..end], false);
+        writeln!(
+            result,
+            "@@ -{},{} +{},{} @@",
+            old_start, old_len, new_start, new_len
+        )
+        .unwrap();
+        for line in &diff[start..end] {
+            let (prefix, line) = match line {
+                DiffLine::Unchanged(line) => (' ', line),
+                DiffLine::Removed(line) => ('-', line),
+                DiffLine::Added(line) => ('+', line),
+            };
+            writeln!(result, "{}{}", prefix, line).unwrap();
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,49 @@
+use super::*;
+
+#[test]
+fn test_diff_lines() {
+    // given:
+    let old = "a\nb\nc\nd";
+    let new = "a\nc\nx\nd";
+
+    // when:
+    let actual = diff_lines(old, new);
+
+    // then:
+    assert_eq!(
+        actual,
+        vec![
+            DiffLine::Unchanged("a"),
+            DiffLine::Removed("b"),
+            DiffLine::Unchanged("c"),
+            DiffLine::Added("x"),
+            DiffLine::Unchanged("d"),
+        ]
+    );
+}
+
+#[test]
+fn test_format_unified_diff() {
+    // given:
+    let old = "1\n2\n3\n4\n5\n6\n7\n8\n9";
+    let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9";
+    let diff = diff_lines(old, new);
+
+    // when:
+    let actual = format_unified_diff(&diff, 1);
+
+    // then:
+    assert_eq!(actual, "@@ -4,3 +4,3 @@\n 4\n-5\n+five\n 6\n");
+}
+
+#[test]
+fn test_format_unified_diff_without_changes() {
+    // given:
+    let diff = diff_lines("a\nb", "a\nb");
+
+    // when:
+    let actual = format_unified_diff(&diff, 3);
+
+    // then:
+    assert_eq!(actual, "");
+}
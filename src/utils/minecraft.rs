@@ -0,0 +1,91 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Locations of Minecraft's files that are detected if they are not configured.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    env,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Returns the directory in which the Minecraft launcher stores single player worlds on this
+/// operating system or [None] if the environment variable it is located in is not set.
+pub fn minecraft_saves_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        let app_data = PathBuf::from(env::var_os("APPDATA")?);
+        Some(app_data.join(".minecraft").join("saves"))
+    } else if cfg!(target_os = "macos") {
+        let home = PathBuf::from(env::var_os("HOME")?);
+        Some(home.join("Library/Application Support/minecraft/saves"))
+    } else {
+        let home = PathBuf::from(env::var_os("HOME")?);
+        Some(home.join(".minecraft").join("saves"))
+    }
+}
+
+/// Finds the Minecraft world that the datapack is installed in. If the datapack is located in the
+/// datapacks directory of a world, that world is returned. Otherwise the worlds in
+/// [minecraft_saves_dir] are searched for a datapack with the same directory name. If several
+/// worlds contain one, the most recently modified world is returned.
+pub fn find_minecraft_world(datapack_path: &Path) -> Option<PathBuf> {
+    let datapack_name = datapack_path.file_name()?;
+    if let Some(datapacks_dir) = datapack_path.parent() {
+        if let Some(world) = datapacks_dir.parent() {
+            if datapacks_dir.file_name() == Some(OsStr::new("datapacks"))
+                && world.join("level.dat").is_file()
+            {
+                return Some(world.to_path_buf());
+            }
+        }
+    }
+
+    minecraft_saves_dir()?
+        .read_dir()
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|world| world.join("datapacks").join(datapack_name).is_dir())
+        .max_by_key(|world| get_world_modified(world))
+}
+
+/// Finds Minecraft's log file relative to the world directory. Single player worlds are located in
+/// `<minecraft>/saves/<world>`, so `logs/latest.log` is searched two directories above the world
+/// and, for launchers that nest the game directory in an instance directory like MultiMC or Prism
+/// Launcher, three directories above the world.
+pub fn find_minecraft_log_file(minecraft_world_dir: &Path) -> Option<PathBuf> {
+    minecraft_world_dir
+        .ancestors()
+        .skip(2)
+        .take(2)
+        .map(|dir| dir.join("logs").join("latest.log"))
+        .find(|log_file| log_file.is_file())
+}
+
+/// Minecraft writes the level.dat file whenever the world is saved, so its modification time is
+/// when the world was last played.
+fn get_world_modified(world: &Path) -> Option<SystemTime> {
+    std::fs::metadata(world.join("level.dat"))
+        .or_else(|_| std::fs::metadata(world))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,156 @@
+use super::*;
+use std::{
+    fs::{create_dir_all, remove_dir_all, write, File},
+    sync::Mutex,
+    time::Duration,
+};
+
+/// Tests that override the environment variable of [minecraft_saves_dir] must not run in parallel.
+static SAVES_DIR_ENV: Mutex<()> = Mutex::new(());
+
+/// Points [minecraft_saves_dir] to a subdirectory of `dir` and returns it.
+fn override_minecraft_saves_dir(dir: &Path) -> PathBuf {
+    let env_var = if cfg!(windows) { "APPDATA" } else { "HOME" };
+    env::set_var(env_var, dir);
+    let saves_dir = minecraft_saves_dir().unwrap();
+    assert!(saves_dir.starts_with(dir));
+    saves_dir
+}
+
+fn create_world(
+    saves_dir: &Path,
+    world: &str,
+    datapacks: &[&str],
+    modified: SystemTime,
+) -> PathBuf {
+    let world = saves_dir.join(world);
+    for datapack in datapacks {
+        create_dir_all(world.join("datapacks").join(datapack)).unwrap();
+    }
+    let level_dat = File::create(world.join("level.dat")).unwrap();
+    level_dat.set_modified(modified).unwrap();
+    world
+}
+
+#[test]
+fn test_find_minecraft_world_prefers_most_recently_modified() {
+    // given:
+    let _lock = SAVES_DIR_ENV.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-minecraft-test")
+        .join("test_find_minecraft_world_prefers_most_recently_modified");
+    let _ = remove_dir_all(&dir);
+    let saves_dir = override_minecraft_saves_dir(&dir);
+    let now = SystemTime::now();
+    let hour = Duration::from_secs(60 * 60);
+    create_world(&saves_dir, "old", &["my_pack"], now - 2 * hour);
+    let expected = create_world(&saves_dir, "recent", &["my_pack"], now - hour);
+    create_world(&saves_dir, "other", &["other_pack"], now);
+    let datapack = dir.join("projects").join("my_pack");
+
+    // when:
+    let actual = find_minecraft_world(&datapack);
+
+    // then:
+    assert_eq!(actual, Some(expected));
+}
+
+#[test]
+fn test_find_minecraft_world_without_matching_world() {
+    // given:
+    let _lock = SAVES_DIR_ENV.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-minecraft-test")
+        .join("test_find_minecraft_world_without_matching_world");
+    let _ = remove_dir_all(&dir);
+    let saves_dir = override_minecraft_saves_dir(&dir);
+    create_world(&saves_dir, "world", &["other_pack"], SystemTime::now());
+    let datapack = dir.join("projects").join("my_pack");
+
+    // when:
+    let actual = find_minecraft_world(&datapack);
+
+    // then:
+    assert_eq!(actual, None);
+}
+
+#[test]
+fn test_find_minecraft_world_of_installed_datapack() {
+    // given:
+    let _lock = SAVES_DIR_ENV.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-minecraft-test")
+        .join("test_find_minecraft_world_of_installed_datapack");
+    let _ = remove_dir_all(&dir);
+    override_minecraft_saves_dir(&dir.join("empty"));
+    let expected = create_world(&dir, "server_world", &["my_pack"], SystemTime::now());
+    let datapack = expected.join("datapacks").join("my_pack");
+
+    // when:
+    let actual = find_minecraft_world(&datapack);
+
+    // then:
+    assert_eq!(actual, Some(expected));
+}
+
+fn create_log_file(minecraft_dir: &Path) -> PathBuf {
+    let log_file = minecraft_dir.join("logs").join("latest.log");
+    create_dir_all(log_file.parent().unwrap()).unwrap();
+    write(&log_file, "").unwrap();
+    log_file
+}
+
+#[test]
+fn test_find_minecraft_log_file() {
+    // given:
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-minecraft-test")
+        .join("test_find_minecraft_log_file");
+    let _ = remove_dir_all(&dir);
+    let minecraft_dir = dir.join(".minecraft");
+    let expected = create_log_file(&minecraft_dir);
+    let world = minecraft_dir.join("saves").join("world");
+    create_dir_all(&world).unwrap();
+
+    // when:
+    let actual = find_minecraft_log_file(&world);
+
+    // then:
+    assert_eq!(actual, Some(expected));
+}
+
+#[test]
+fn test_find_minecraft_log_file_of_launcher_instance() {
+    // given:
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-minecraft-test")
+        .join("test_find_minecraft_log_file_of_launcher_instance");
+    let _ = remove_dir_all(&dir);
+    let instance_dir = dir.join("instances").join("my_instance");
+    let expected = create_log_file(&instance_dir);
+    let world = instance_dir.join(".minecraft").join("saves").join("world");
+    create_dir_all(&world).unwrap();
+
+    // when:
+    let actual = find_minecraft_log_file(&world);
+
+    // then:
+    assert_eq!(actual, Some(expected));
+}
+
+#[test]
+fn test_find_minecraft_log_file_without_log_file() {
+    // given:
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-minecraft-test")
+        .join("test_find_minecraft_log_file_without_log_file");
+    let _ = remove_dir_all(&dir);
+    let world = dir.join(".minecraft").join("saves").join("world");
+    create_dir_all(&world).unwrap();
+
+    // when:
+    let actual = find_minecraft_log_file(&world);
+
+    // then:
+    assert_eq!(actual, None);
+}
@@ -0,0 +1,78 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::parser::command::resource_location::ResourceLocation;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// The result of [crate::validate_datapack].
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    /// Problems that prevent debugging the datapack.
+    pub errors: Vec<ValidationIssue>,
+    /// Problems that might make the debugger behave unexpectedly.
+    pub warnings: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// The function the issue was found in, if it is specific to a function.
+    pub function: Option<String>,
+    /// The line number of the issue in the function.
+    pub line_number: Option<usize>,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub(crate) fn new(message: impl Into<String>) -> ValidationIssue {
+        ValidationIssue {
+            function: None,
+            line_number: None,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn at(
+        function: &ResourceLocation,
+        line_number: usize,
+        message: impl Into<String>,
+    ) -> ValidationIssue {
+        ValidationIssue {
+            function: Some(function.to_string()),
+            line_number: Some(line_number),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.function, self.line_number) {
+            (Some(function), Some(line_number)) => write!(f, "{}:{}: ", function, line_number)?,
+            (Some(function), None) => write!(f, "{}: ", function)?,
+            _ => {}
+        }
+        write!(f, "{}", self.message)
+    }
+}
@@ -0,0 +1,98 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{config::Config, generate_debug_datapack, parser::command::CommandParser};
+use futures::{channel::mpsc::unbounded, StreamExt};
+use log::{error, info};
+use notify::{recommended_watcher, Event, RecursiveMode, Watcher};
+use std::{
+    ffi::OsStr,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::time::timeout;
+
+/// Rapid changes within this duration only trigger a single regeneration.
+pub const DEFAULT_DEBOUNCE_DURATION: Duration = Duration::from_millis(100);
+
+/// Regenerates the debug datapack whenever an mcfunction file in the input datapack changes. Because
+/// the generation is incremental, only the files of changed functions are written again.
+///
+/// Errors during a regeneration are logged without stopping to watch. This only returns if the file
+/// watcher fails. Changes within `debounce` of each other only trigger a single regeneration, see
+/// [DEFAULT_DEBOUNCE_DURATION].
+pub async fn watch_debug_datapack(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    parser: &CommandParser,
+    config: &Config<'_>,
+    debounce: Duration,
+) -> io::Result<()> {
+    let input_path = input_path.as_ref();
+    let output_path = output_path.as_ref();
+
+    let (sender, mut events) = unbounded();
+    let mut watcher = recommended_watcher(move |event| {
+        let _ = sender.unbounded_send(event);
+    })
+    .map_err(to_io_error)?;
+    let watched_paths = [input_path]
+        .into_iter()
+        .chain(config.additional_datapacks.iter().map(PathBuf::as_path));
+    for path in watched_paths {
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .map_err(to_io_error)?;
+        info!("Watching {} for changes", path.display());
+    }
+
+    while let Some(event) = events.next().await {
+        match event {
+            Ok(event) if is_mcfunction_change(&event) => {}
+            Ok(_) => continue,
+            Err(e) => {
+                error!("Failed to watch for changes: {}", e);
+                continue;
+            }
+        }
+
+        while let Ok(Some(_)) = timeout(debounce, events.next()).await {}
+
+        info!("Regenerating debug datapack");
+        if let Err(e) = generate_debug_datapack(input_path, output_path, parser, config).await {
+            error!("Failed to regenerate debug datapack: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn is_mcfunction_change(event: &Event) -> bool {
+    !event.kind.is_access()
+        && event
+            .paths
+            .iter()
+            .any(|path| path.extension() == Some(OsStr::new("mcfunction")))
+}
+
+fn to_io_error(e: notify::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+#[cfg(test)]
+mod tests;
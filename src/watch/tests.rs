@@ -0,0 +1,83 @@
+use super::*;
+use crate::config::DEFAULT_MAX_COMMANDS;
+use std::{
+    collections::BTreeSet,
+    fs::{create_dir_all, read_to_string, remove_dir_all, write},
+    path::PathBuf,
+};
+use tokio::{select, time::sleep};
+
+fn create_datapack(name: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir()
+        .join("mcfunction-debugger-watch-test")
+        .join(name);
+    let _ = remove_dir_all(&dir);
+    let input_path = dir.join("input");
+    create_dir_all(input_path.join("data/test/functions")).unwrap();
+    write(
+        input_path.join("pack.mcmeta"),
+        r#"{"pack":{"pack_format":7,"description":""}}"#,
+    )
+    .unwrap();
+    write(
+        input_path.join("data/test/functions/main.mcfunction"),
+        "say before",
+    )
+    .unwrap();
+    (input_path, dir.join("output"))
+}
+
+#[tokio::test]
+async fn test_regenerates_on_change() {
+    // given:
+    let (input_path, output_path) = create_datapack("test_regenerates_on_change");
+    let parser = CommandParser::default().unwrap();
+    let config = Config {
+        namespace: "mcfd",
+        shadow: false,
+        exclude: &[],
+        excluded_functions: BTreeSet::new(),
+        skip_invalid_functions: false,
+        additional_datapacks: &[],
+        max_commands: DEFAULT_MAX_COMMANDS,
+        adapter: None,
+    };
+    generate_debug_datapack(&input_path, &output_path, &parser, &config)
+        .await
+        .unwrap();
+    let generated_path =
+        output_path.join("data/mcfd/functions/test/main/0_entry-1_return.mcfunction");
+
+    // when:
+    let watch = watch_debug_datapack(
+        &input_path,
+        &output_path,
+        &parser,
+        &config,
+        DEFAULT_DEBOUNCE_DURATION,
+    );
+    let change = async {
+        sleep(Duration::from_millis(100)).await; // Give the watcher time to start
+        write(
+            input_path.join("data/test/functions/main.mcfunction"),
+            "say after",
+        )
+        .unwrap();
+        for _ in 0..50 {
+            sleep(Duration::from_millis(10)).await;
+            if read_to_string(&generated_path)
+                .unwrap_or_default()
+                .contains("say after")
+            {
+                return true;
+            }
+        }
+        false
+    };
+
+    // then:
+    select! {
+        result = watch => panic!("Stopped watching: {:?}", result),
+        regenerated = change => assert!(regenerated, "Output was not regenerated within 500 ms"),
+    }
+}
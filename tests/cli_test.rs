@@ -0,0 +1,268 @@
+// McFunction-Debugger is a debugger for Minecraft's *.mcfunction files that does not require any
+// Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of McFunction-Debugger.
+//
+// McFunction-Debugger is free software: you can redistribute it and/or modify it under the terms of
+// the GNU General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// McFunction-Debugger is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with McFunction-Debugger.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use serde_json::Value;
+use std::{
+    fs::{create_dir_all, remove_dir_all, write},
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+fn create_datapack(name: &str) -> PathBuf {
+    let datapack = std::env::temp_dir()
+        .join("mcfunction-debugger-cli-test")
+        .join(name);
+    let _ = remove_dir_all(&datapack);
+    let functions = datapack.join("data/test/functions");
+    create_dir_all(&functions).unwrap();
+    write(
+        datapack.join("pack.mcmeta"),
+        r#"{"pack":{"pack_format":7,"description":""}}"#,
+    )
+    .unwrap();
+    write(
+        functions.join("main.mcfunction"),
+        "say hi\n# breakpoint\nsay hi",
+    )
+    .unwrap();
+    datapack
+}
+
+fn run(args: &[&str]) -> (Output, Value) {
+    let output = Command::new(env!("CARGO_BIN_EXE_mcfunction-debugger"))
+        .args(args)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout.clone()).unwrap();
+    let json = serde_json::from_str(&stdout)
+        .unwrap_or_else(|e| panic!("Invalid JSON output: {}\n{}", e, stdout));
+    (output, json)
+}
+
+#[test]
+fn test_output_format_json() {
+    // given:
+    let datapack = create_datapack("test_output_format_json");
+    let output_path = datapack.join("output");
+
+    // when:
+    let (output, json) = run(&[
+        "--input",
+        datapack.to_str().unwrap(),
+        "--output",
+        output_path.to_str().unwrap(),
+        "--output-format",
+        "json",
+    ]);
+
+    // then:
+    assert!(output.status.success());
+    assert_eq!(json["status"], "ok");
+    assert!(json["files_written"].as_u64().unwrap() > 0, "{}", json);
+    assert!(json["warnings"].is_array(), "{}", json);
+    assert!(json["log"].is_array(), "{}", json);
+    assert!(output_path.join("pack.mcmeta").is_file());
+}
+
+#[test]
+fn test_output_format_json_with_missing_pack_mcmeta() {
+    // given:
+    let datapack = create_datapack("test_output_format_json_with_missing_pack_mcmeta");
+    std::fs::remove_file(datapack.join("pack.mcmeta")).unwrap();
+
+    // when:
+    let (output, json) = run(&[
+        "--input",
+        datapack.to_str().unwrap(),
+        "--output",
+        datapack.join("output").to_str().unwrap(),
+        "--output-format",
+        "json",
+    ]);
+
+    // then:
+    assert!(!output.status.success());
+    assert_eq!(json["status"], "error");
+    assert_eq!(json["error"], "Could not find pack.mcmeta");
+}
+
+#[test]
+fn test_output_format_json_collects_warnings() {
+    // given:
+    let datapack = create_datapack("test_output_format_json_collects_warnings");
+
+    // when:
+    let (output, json) = run(&[
+        "--input",
+        datapack.to_str().unwrap(),
+        "--output",
+        datapack.join("output").to_str().unwrap(),
+        "--shadow",
+        "--output-format",
+        "json",
+    ]);
+
+    // then:
+    assert!(output.status.success());
+    let warnings = json["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1, "{}", json);
+    assert!(
+        warnings[0].as_str().unwrap().contains("shadowed"),
+        "{}",
+        json
+    );
+}
+
+#[test]
+fn test_namespace_from_config_file() {
+    // given:
+    let datapack = create_datapack("test_namespace_from_config_file");
+    let output_path = datapack.join("output");
+    write(
+        datapack.join(".mcfunction-debugger.toml"),
+        "namespace = \"cfg\"\n",
+    )
+    .unwrap();
+
+    // when:
+    let (output, json) = run(&[
+        "--input",
+        datapack.to_str().unwrap(),
+        "--output",
+        output_path.to_str().unwrap(),
+        "--output-format",
+        "json",
+    ]);
+
+    // then:
+    assert!(output.status.success(), "{}", json);
+    assert!(output_path.join("data/cfg/functions").is_dir());
+    assert!(!output_path.join("data/mcfd").exists());
+}
+
+#[test]
+fn test_diff() {
+    // given:
+    let datapack = create_datapack("test_diff");
+    let output_path = datapack.join("output");
+    let (output, json) = run(&[
+        "--input",
+        datapack.to_str().unwrap(),
+        "--output",
+        output_path.to_str().unwrap(),
+        "--output-format",
+        "json",
+    ]);
+    assert!(output.status.success(), "{}", json);
+    let diff = || {
+        Command::new(env!("CARGO_BIN_EXE_mcfunction-debugger"))
+            .args(&[
+                "--input",
+                datapack.to_str().unwrap(),
+                "--output",
+                output_path.to_str().unwrap(),
+                "--diff",
+            ])
+            .output()
+            .unwrap()
+    };
+    let unchanged = diff();
+
+    write(
+        datapack.join("data/test/functions/main.mcfunction"),
+        "say hi\n# breakpoint\nsay bye",
+    )
+    .unwrap();
+
+    // when:
+    let changed = diff();
+
+    // then:
+    let stdout = String::from_utf8(unchanged.stdout).unwrap();
+    assert!(unchanged.status.success(), "{}", stdout);
+    assert!(!stdout.contains("Changed "), "{}", stdout);
+    assert!(stdout.contains("is up to date"), "{}", stdout);
+
+    let stdout = String::from_utf8(changed.stdout).unwrap();
+    assert!(!changed.status.success(), "{}", stdout);
+    assert!(stdout.contains("Changed "), "{}", stdout);
+    assert!(stdout.contains("+say bye"), "{}", stdout);
+    let main = output_path.join("data/mcfd/functions/test/main/2_breakpoint-3_return.mcfunction");
+    assert!(
+        !std::fs::read_to_string(main).unwrap().contains("say bye"),
+        "The debug datapack must not be changed"
+    );
+}
+
+#[test]
+fn test_breakpoints_json() {
+    // given:
+    let datapack = create_datapack("test_breakpoints_json");
+    let output_path = datapack.join("output");
+    let breakpoints_path = datapack.join("breakpoints.json");
+    write(
+        &breakpoints_path,
+        r#"{"test:main":[{"kind":"normal","position":{"lineNumber":3,"positionInLine":"breakpoint"}}]}"#,
+    )
+    .unwrap();
+
+    // when:
+    let (output, json) = run(&[
+        "--input",
+        datapack.to_str().unwrap(),
+        "--output",
+        output_path.to_str().unwrap(),
+        "--output-format",
+        "json",
+        "--adapter-listener",
+        "mcfd_adapter",
+        "--breakpoints-json",
+        breakpoints_path.to_str().unwrap(),
+    ]);
+
+    // then:
+    assert!(output.status.success(), "{}", json);
+    assert!(output_path
+        .join("data/mcfd/functions/test/main/suspend_at_3_breakpoint.mcfunction")
+        .is_file());
+}
+
+#[test]
+fn test_generate_launch_config() {
+    // given:
+    let datapack = create_datapack("test_generate_launch_config");
+
+    // when:
+    let (output, json) = run(&[
+        "--input",
+        datapack.to_str().unwrap(),
+        "--generate-launch-config",
+    ]);
+
+    // then:
+    assert!(output.status.success(), "{}", json);
+    assert_eq!(json["type"], "mcfunction");
+    assert_eq!(json["request"], "launch");
+    assert_eq!(json["name"], "Debug test:main");
+    let program = json["program"].as_str().unwrap();
+    assert!(
+        program.ends_with("main.mcfunction"),
+        "Unexpected program: {}",
+        program
+    );
+}
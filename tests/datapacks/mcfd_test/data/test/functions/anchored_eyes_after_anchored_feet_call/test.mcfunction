@@ -0,0 +1,3 @@
+kill @e[type=sheep,tag=test]
+summon sheep ~ ~ ~ {Tags: [test], NoAI: true, Rotation: [0f, 0f]}
+execute as @e[type=sheep,tag=test] at @s anchored eyes run function test:anchored_eyes_after_anchored_feet_call/anchored_eyes
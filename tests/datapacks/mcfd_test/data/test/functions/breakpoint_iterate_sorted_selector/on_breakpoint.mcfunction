@@ -0,0 +1 @@
+function debug:resume
@@ -0,0 +1,3 @@
+scoreboard players add test_score test_global 1
+# breakpoint
+scoreboard players operation @s test_global = test_score test_global
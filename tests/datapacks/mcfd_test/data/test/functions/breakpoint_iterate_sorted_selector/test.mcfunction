@@ -0,0 +1,16 @@
+scoreboard players set test_score test_global 0
+
+kill @e[type=sheep,tag=test]
+summon sheep ~4 ~ ~ {Tags: [test, test_sheep3], NoAI: true}
+summon sheep ~ ~ ~ {Tags: [test, test_sheep1], NoAI: true}
+summon sheep ~2 ~ ~ {Tags: [test, test_sheep2], NoAI: true}
+summon sheep ~6 ~ ~ {Tags: [test, test_sheep4], NoAI: true}
+execute as @e[type=sheep,tag=test,sort=nearest,limit=3] run function test:breakpoint_iterate_sorted_selector/record_order
+
+say [@: function minect:enable_logging]
+execute if entity @e[type=sheep,tag=test_sheep1,scores={test_global=1}] if entity @e[type=sheep,tag=test_sheep2,scores={test_global=2}] if entity @e[type=sheep,tag=test_sheep3,scores={test_global=3}] unless score @e[type=sheep,tag=test_sheep4,limit=1] test_global matches -2147483648..2147483647 run say [test: tag @s add success]
+execute unless entity @e[type=sheep,tag=test_sheep1,scores={test_global=1}] run say [test: scoreboard players add @e[type=sheep,tag=test_sheep1] test_global 0]
+execute unless entity @e[type=sheep,tag=test_sheep2,scores={test_global=2}] run say [test: scoreboard players add @e[type=sheep,tag=test_sheep2] test_global 0]
+execute unless entity @e[type=sheep,tag=test_sheep3,scores={test_global=3}] run say [test: scoreboard players add @e[type=sheep,tag=test_sheep3] test_global 0]
+execute if score @e[type=sheep,tag=test_sheep4,limit=1] test_global matches -2147483648..2147483647 run say [test: scoreboard players add @e[type=sheep,tag=test_sheep4] test_global 0]
+say [@: function minect:reset_logging]
@@ -0,0 +1,3 @@
+say [@: function minect:enable_logging]
+execute unless score test_score test_global matches 0 run say [test: scoreboard players add test_score test_global 0]
+say [@: function minect:reset_logging]
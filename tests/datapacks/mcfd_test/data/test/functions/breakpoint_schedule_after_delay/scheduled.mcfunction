@@ -0,0 +1,3 @@
+scoreboard players add test_score test_global 1
+# breakpoint
+scoreboard players add test_score test_global 1
@@ -0,0 +1,4 @@
+scoreboard players set test_score test_global 0
+schedule function test:breakpoint_schedule_after_delay/assert_not_incremented 4t
+schedule function test:breakpoint_schedule_after_delay/scheduled 5t
+schedule function test:breakpoint_schedule_after_delay/assert 6t
@@ -0,0 +1 @@
+scoreboard players add called test_global 1
@@ -0,0 +1,12 @@
+# given:
+kill @e[type=sheep,tag=test]
+scoreboard players set called test_global 0
+
+# when:
+execute as @e[type=sheep,tag=test] run function test:execute_as_no_matching_entities/increase_score
+
+# then:
+say [@: function minect:enable_logging]
+execute if score called test_global matches 0 run say [test: tag @s add success]
+execute unless score called test_global matches 0 run say [test: scoreboard players add called test_global 0]
+say [@: function minect:reset_logging]
@@ -0,0 +1,5 @@
+say [@: function minect:enable_logging]
+execute unless score test_score test_global matches 1 run say [test: scoreboard players add test_score test_global 0]
+say [@: function minect:reset_logging]
+
+function debug:resume
@@ -0,0 +1,3 @@
+scoreboard players set test_score test_global 0
+function #test:function_tag_breakpoint
+function test:function_tag_breakpoint/assert
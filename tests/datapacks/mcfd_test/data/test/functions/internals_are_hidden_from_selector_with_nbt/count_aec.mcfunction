@@ -0,0 +1 @@
+scoreboard players add aec_count test_global 1
@@ -0,0 +1,2 @@
+kill @e[type=area_effect_cloud,tag=!minect_connection]
+function test:internals_are_hidden_from_selector_with_nbt/test_internal
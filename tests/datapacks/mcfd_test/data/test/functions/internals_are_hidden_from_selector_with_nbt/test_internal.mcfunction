@@ -0,0 +1,7 @@
+scoreboard players set aec_count test_global 0
+execute as @e[type=area_effect_cloud,tag=!minect_connection,nbt={Duration: 2147483647},name=!"a, [b]"] run function test:internals_are_hidden_from_selector_with_nbt/count_aec
+
+say [@: function minect:enable_logging]
+execute if score aec_count test_global matches 0 run say [test: tag @s add success]
+execute unless score aec_count test_global matches 0 run say [test: scoreboard players add aec_count test_global 0]
+say [@: function minect:reset_logging]
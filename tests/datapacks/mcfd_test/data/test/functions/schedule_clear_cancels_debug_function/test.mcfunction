@@ -0,0 +1,4 @@
+scoreboard players set test_score test_global 0
+schedule function test:schedule_clear_cancels_debug_function/increment 1t
+schedule clear test:schedule_clear_cancels_debug_function/increment
+schedule function test:schedule_clear_cancels_debug_function/assert_not_incremented 2t
@@ -0,0 +1,3 @@
+# mcfd:skip
+# breakpoint
+scoreboard players add test_score test_global 1
@@ -0,0 +1,11 @@
+# given:
+scoreboard players set test_score test_global 0
+
+# when:
+function test:skip_function_is_not_suspended/skipped
+
+# then:
+say [@: function minect:enable_logging]
+execute if score test_score test_global matches 1 run say [test: tag @s add success]
+execute unless score test_score test_global matches 1 run say [test: scoreboard players add test_score test_global 0]
+say [@: function minect:reset_logging]
@@ -0,0 +1 @@
+schedule function test_suspend_schedules:call_stack_only/resume 3t
@@ -0,0 +1 @@
+scoreboard players add test_score test_global 10
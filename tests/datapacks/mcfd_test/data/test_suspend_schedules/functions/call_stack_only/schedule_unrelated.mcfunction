@@ -0,0 +1 @@
+schedule function test_suspend_schedules:call_stack_only/unrelated 1t
@@ -0,0 +1,6 @@
+scoreboard players set suspend_schedules mcfd_global 1
+scoreboard players set test_score test_global 0
+function test_suspend_schedules:call_stack_only/schedule_unrelated
+schedule function test_suspend_schedules:call_stack_only/related 1t
+# breakpoint
+function test_suspend_schedules:call_stack_only/assert
@@ -0,0 +1,6 @@
+say start
+# breakpoint
+execute as @e[type=sheep] run function test:inner
+execute if entity @s run return 1
+function test:missing
+say end
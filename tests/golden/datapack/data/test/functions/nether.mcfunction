@@ -0,0 +1 @@
+execute in the_nether run function test:inner
@@ -0,0 +1,11 @@
+execute if score skipped_missing mcfd_global matches 0 if score skipped_invalid mcfd_global matches 0 run tellraw @s [{"text":""},{"text":"[Info]","color":"blue","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" No functions were skipped."}]
+
+execute if score skipped_missing mcfd_global matches 1.. run tellraw @s [{"text":""},{"text":"[Warning]","color":"gold","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" The following missing functions were skipped:"}]
+
+execute if score test:inner mcfd_skipped matches 1.. unless score test:inner mcfd_valid matches 0 run tellraw @s [{"text":" - test:inner ("},{"score":{"name":"test:inner","objective":"mcfd_skipped"}},{"text":"x)"}]
+execute if score test:missing mcfd_skipped matches 1.. unless score test:missing mcfd_valid matches 0 run tellraw @s [{"text":" - test:missing ("},{"score":{"name":"test:missing","objective":"mcfd_skipped"}},{"text":"x)"}]
+
+execute if score skipped_invalid mcfd_global matches 1.. run tellraw @s [{"text":""},{"text":"[Warning]","color":"gold","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" The following invalid functions were skipped:"}]
+
+execute if score test:inner mcfd_skipped matches 1.. if score test:inner mcfd_valid matches 0 run tellraw @s [{"text":" - test:inner ("},{"score":{"name":"test:inner","objective":"mcfd_skipped"}},{"text":"x)"}]
+execute if score test:missing mcfd_skipped matches 1.. if score test:missing mcfd_valid matches 0 run tellraw @s [{"text":" - test:missing ("},{"score":{"name":"test:missing","objective":"mcfd_skipped"}},{"text":"x)"}]
@@ -0,0 +1,9 @@
+tellraw @a [{"text":""},{"text":"[Info]","color":"blue","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" Resuming debugging from "},{"selector":"@s"}]
+
+scoreboard players reset found_continue_function mcfd_global
+
+execute if entity @s[tag=mcfd+test+main+2_breakpoint] run function mcfd:test/main/continue_current_iteration_at_2_breakpoint
+
+execute if score found_continue_function mcfd_global matches 1 run kill @s
+execute unless score found_continue_function mcfd_global matches 1 run tellraw @a [{"text":""},{"text":"[Error]","color":"red","hoverEvent":{"action":"show_text","contents":"mcfunction-Debugger"}},{"text":" Breakpoint at "},{"selector":"@s"},{"text":" was deleted!\n You can either restore this breakpoint or stop the current debugging session with "},{"text":"/function debug:stop","clickEvent":{"action":"suggest_command","value":"/function debug:stop"},"hoverEvent":{"action":"show_text","contents":"Click to execute"},"color":"aqua"}]
+execute unless score found_continue_function mcfd_global matches 1 run scoreboard players set breakpoint mcfd_global 1
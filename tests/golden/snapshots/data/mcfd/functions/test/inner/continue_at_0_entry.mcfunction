@@ -0,0 +1,25 @@
+execute unless score @s mcfd_id matches 0 run scoreboard players operation @e[tag=!mcfd_context] mcfd_id -= @s mcfd_id
+execute unless score @s mcfd_id matches 0 as @e[tag=!mcfd_context,scores={mcfd_id=0}] run tag @s add mcfd_tmp
+execute unless score @s mcfd_id matches 0 run scoreboard players operation @e[tag=!mcfd_context] mcfd_id += @s mcfd_id
+
+# The iteration of a killed executor is skipped
+execute unless score @s mcfd_id matches 0 unless entity @e[tag=!mcfd_context,tag=mcfd_tmp] run tag @s add mcfd_skip
+scoreboard players operation current mcfd_anchor = @s mcfd_anchor
+scoreboard players operation current mcfd_dim = @s mcfd_dim
+execute unless score @s mcfd_dim matches 1.. if score @s mcfd_id matches 0 at @s run function mcfd:test/inner/0_entry-2_return
+execute unless score @s mcfd_dim matches 1.. if score @s mcfd_anchor matches 0 at @s as @e[tag=!mcfd_context,tag=mcfd_tmp] anchored feet run function mcfd:test/inner/0_entry-2_return
+execute unless score @s mcfd_dim matches 1.. if score @s mcfd_anchor matches 1 at @s as @e[tag=!mcfd_context,tag=mcfd_tmp] anchored eyes run function mcfd:test/inner/0_entry-2_return
+execute if score @s mcfd_dim matches 1 if score @s mcfd_id matches 0 at @s in minecraft:overworld run function mcfd:test/inner/0_entry-2_return
+execute if score @s mcfd_dim matches 1 if score @s mcfd_anchor matches 0 at @s in minecraft:overworld as @e[tag=!mcfd_context,tag=mcfd_tmp] anchored feet run function mcfd:test/inner/0_entry-2_return
+execute if score @s mcfd_dim matches 1 if score @s mcfd_anchor matches 1 at @s in minecraft:overworld as @e[tag=!mcfd_context,tag=mcfd_tmp] anchored eyes run function mcfd:test/inner/0_entry-2_return
+execute if score @s mcfd_dim matches 2 if score @s mcfd_id matches 0 at @s in minecraft:the_nether run function mcfd:test/inner/0_entry-2_return
+execute if score @s mcfd_dim matches 2 if score @s mcfd_anchor matches 0 at @s in minecraft:the_nether as @e[tag=!mcfd_context,tag=mcfd_tmp] anchored feet run function mcfd:test/inner/0_entry-2_return
+execute if score @s mcfd_dim matches 2 if score @s mcfd_anchor matches 1 at @s in minecraft:the_nether as @e[tag=!mcfd_context,tag=mcfd_tmp] anchored eyes run function mcfd:test/inner/0_entry-2_return
+execute if score @s mcfd_dim matches 3 if score @s mcfd_id matches 0 at @s in minecraft:the_end run function mcfd:test/inner/0_entry-2_return
+execute if score @s mcfd_dim matches 3 if score @s mcfd_anchor matches 0 at @s in minecraft:the_end as @e[tag=!mcfd_context,tag=mcfd_tmp] anchored feet run function mcfd:test/inner/0_entry-2_return
+execute if score @s mcfd_dim matches 3 if score @s mcfd_anchor matches 1 at @s in minecraft:the_end as @e[tag=!mcfd_context,tag=mcfd_tmp] anchored eyes run function mcfd:test/inner/0_entry-2_return
+
+execute if entity @s[tag=mcfd_skip] run tag @s remove mcfd_active
+execute if entity @s[tag=mcfd_skip] run function mcfd:test/inner/next_iteration_or_return
+
+execute if entity @s[tag=!mcfd_active] run kill @s
@@ -0,0 +1,10 @@
+# Needed when continuing from a breakpoint
+execute as @e[type=area_effect_cloud,tag=mcfd_frozen] run function mcfd:unfreeze_aec
+tag @e[type=area_effect_cloud,tag=mcfd_running] remove mcfd_running
+
+tag @s remove mcfd_tmp
+
+say start
+scoreboard players set breakpoint mcfd_global 1
+function mcfd:test/main/suspend_at_2_breakpoint
+
@@ -0,0 +1,13 @@
+# Needed when continuing from a breakpoint
+execute as @e[type=area_effect_cloud,tag=mcfd_frozen] run function mcfd:unfreeze_aec
+tag @e[type=area_effect_cloud,tag=mcfd_running] remove mcfd_running
+
+tag @s remove mcfd_tmp
+
+
+scoreboard players reset return mcfd_global
+execute if entity @s[tag=!mcfd] run scoreboard players set return mcfd_global 1
+
+execute if score return mcfd_global matches 1 as @e[type=area_effect_cloud,tag=mcfd_context,tag=mcfd_active,tag=mcfd_current] if score @s mcfd_depth = current mcfd_depth run tag @s add mcfd_return
+execute unless score return mcfd_global matches 1 run function mcfd:test/main/4_return-5_function
+
@@ -0,0 +1,26 @@
+# Needed when continuing from a breakpoint
+execute as @e[type=area_effect_cloud,tag=mcfd_frozen] run function mcfd:unfreeze_aec
+tag @e[type=area_effect_cloud,tag=mcfd_running] remove mcfd_running
+
+tag @s remove mcfd_tmp
+
+
+execute if score test:missing mcfd_valid matches 1 run summon area_effect_cloud ~ ~ ~ {Duration: 2147483647, Tags: [mcfd_new, mcfd, mcfd_function_call, mcfd+test+main+5, mcfd_active], CustomName: '{"text":"test:main:5"}'}
+execute if score test:missing mcfd_valid matches 1 run scoreboard players operation @e[type=area_effect_cloud,tag=mcfd_new] mcfd_anchor = current mcfd_anchor
+execute if score test:missing mcfd_valid matches 1 run scoreboard players operation @e[type=area_effect_cloud,tag=mcfd_new] mcfd_depth = current mcfd_depth
+execute if score test:missing mcfd_valid matches 1 run tag @e[type=area_effect_cloud,tag=mcfd_new] remove mcfd_new
+
+execute if score test:missing mcfd_valid matches 1 run scoreboard players add current mcfd_depth 1
+
+
+
+execute if score test:missing mcfd_valid matches 1 run scoreboard players reset next mcfd_iter
+execute if score test:missing mcfd_valid matches 1 run function mcfd:select_entity
+execute if score test:missing mcfd_valid matches 1 run function mcfd:test/missing/next_iteration_or_return
+
+execute unless score test:missing mcfd_valid matches 1 run scoreboard players add skipped_calls mcfd_global 1
+execute unless score test:missing mcfd_valid matches 0.. unless score test:missing mcfd_skipped matches 1.. run scoreboard players add skipped_missing mcfd_global 1
+execute if score test:missing mcfd_valid matches 0 unless score test:missing mcfd_skipped matches 1.. run scoreboard players add skipped_invalid mcfd_global 1
+execute unless score test:missing mcfd_valid matches 1 run scoreboard players add test:missing mcfd_skipped 1
+execute unless score test:missing mcfd_valid matches 1 run function mcfd:test/main/continue_current_iteration_at_5_function
+
@@ -0,0 +1,11 @@
+# Needed when continuing from a breakpoint
+execute as @e[type=area_effect_cloud,tag=mcfd_frozen] run function mcfd:unfreeze_aec
+tag @e[type=area_effect_cloud,tag=mcfd_running] remove mcfd_running
+
+tag @s remove mcfd_tmp
+
+say end
+
+execute as @e[type=area_effect_cloud,tag=mcfd_context,tag=mcfd_active,tag=mcfd_current] if score @s mcfd_depth = current mcfd_depth run tag @s remove mcfd_active
+function mcfd:test/main/next_iteration_or_return
+
@@ -1,4 +1,8 @@
-use mcfunction_debugger::{config::Config, generate_debug_datapack};
+use mcfunction_debugger::{
+    config::{Config, DEFAULT_MAX_COMMANDS},
+    generate_debug_datapack,
+    parser::command::CommandParser,
+};
 use minect::{
     command::{named_logged_command, summon_named_entity_command, SummonNamedEntityOutput},
     Command, MinecraftConnection,
@@ -6,6 +10,7 @@ use minect::{
 use serial_test::serial;
 use simple_logger::SimpleLogger;
 use std::{
+    collections::BTreeSet,
     io,
     path::Path,
     sync::atomic::{AtomicBool, AtomicI8, Ordering},
@@ -102,7 +107,14 @@ mod minecraft {
                 #[tokio::test]
                 #[serial]
                 async fn $name() -> io::Result<()> {
-                    run_test(stringify!($namespace), stringify!($name), false, false).await
+                    run_test(
+                        stringify!($namespace),
+                        stringify!($name),
+                        false,
+                        false,
+                        false,
+                    )
+                    .await
                 }
             };
         }
@@ -123,7 +135,14 @@ mod minecraft {
                 #[tokio::test]
                 #[serial]
                 async fn $name() -> io::Result<()> {
-                    run_test(stringify!($namespace), stringify!($name), true, false).await
+                    run_test(
+                        stringify!($namespace),
+                        stringify!($name),
+                        true,
+                        false,
+                        false,
+                    )
+                    .await
                 }
             };
         }
@@ -148,7 +167,14 @@ mod debugger {
                 #[tokio::test]
                 #[serial]
                 async fn $name() -> io::Result<()> {
-                    run_test(stringify!($namespace), stringify!($name), false, true).await
+                    run_test(
+                        stringify!($namespace),
+                        stringify!($name),
+                        false,
+                        true,
+                        false,
+                    )
+                    .await
                 }
             };
         }
@@ -158,6 +184,21 @@ mod debugger {
             include_test_category!("test_1_15_plus");
         }
         include_test_category!("test_before_age_increment");
+
+        mod with_test_datapack {
+            use super::*;
+
+            macro_rules! test {
+                ($namespace:ident, $name:ident) => {
+                    #[tokio::test]
+                    #[serial]
+                    async fn $name() -> io::Result<()> {
+                        run_test(stringify!($namespace), stringify!($name), false, true, true).await
+                    }
+                };
+            }
+            include_test_category!("test_suspend_schedules");
+        }
     }
 
     mod after_age_increment {
@@ -168,7 +209,7 @@ mod debugger {
                 #[tokio::test]
                 #[serial]
                 async fn $name() -> io::Result<()> {
-                    run_test(stringify!($namespace), stringify!($name), true, true).await
+                    run_test(stringify!($namespace), stringify!($name), true, true, false).await
                 }
             };
         }
@@ -178,6 +219,21 @@ mod debugger {
             include_test_category!("test_1_15_plus");
         }
         include_test_category!("test_after_age_increment");
+
+        mod with_test_datapack {
+            use super::*;
+
+            macro_rules! test {
+                ($namespace:ident, $name:ident) => {
+                    #[tokio::test]
+                    #[serial]
+                    async fn $name() -> io::Result<()> {
+                        run_test(stringify!($namespace), stringify!($name), true, true, true).await
+                    }
+                };
+            }
+            include_test_category!("test_suspend_schedules");
+        }
     }
 }
 
@@ -218,11 +274,15 @@ const TEST_WORLD_DIR: &str = env!("TEST_WORLD_DIR");
 const TEST_LOG_FILE: &str = env!("TEST_LOG_FILE");
 const TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Runs the function `test` of the test. If `with_test_datapack` is true, the test datapack stays
+/// enabled while debugging, like the debug adapter does, so its functions can be called without
+/// being debugged.
 async fn run_test(
     namespace: &str,
     name: &str,
     after_age_increment: bool,
     debug: bool,
+    with_test_datapack: bool,
 ) -> io::Result<()> {
     before_each_test().await;
     // given:
@@ -234,7 +294,7 @@ async fn run_test(
     create_datapacks(namespace, name, &test_fn, after_age_increment, debug).await?;
 
     let mut connection = connection();
-    let setup_commands = get_setup_commands(after_age_increment, debug);
+    let setup_commands = get_setup_commands(after_age_increment, debug, with_test_datapack);
     if !setup_commands.is_empty() {
         connection.execute_commands(setup_commands)?;
     }
@@ -292,9 +352,16 @@ async fn do_create_debug_datapack() -> io::Result<()> {
     let config = Config {
         namespace: "mcfd",
         shadow: false,
+        exclude: &[],
+        excluded_functions: BTreeSet::new(),
+        skip_invalid_functions: false,
+        additional_datapacks: &[],
+        max_commands: DEFAULT_MAX_COMMANDS,
         adapter: None,
     };
-    generate_debug_datapack(&input_path, &output_path, &config).await?;
+    let parser =
+        CommandParser::default().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    generate_debug_datapack(&input_path, &output_path, &parser, &config).await?;
     Ok(())
 }
 
@@ -322,7 +389,11 @@ fn connection() -> MinecraftConnection {
         .build()
 }
 
-fn get_setup_commands(after_age_increment: bool, debug: bool) -> Vec<Command> {
+fn get_setup_commands(
+    after_age_increment: bool,
+    debug: bool,
+    with_test_datapack: bool,
+) -> Vec<Command> {
     let mut commands = Vec::new();
 
     static SCOREBOARD_ADDED: AtomicBool = AtomicBool::new(false);
@@ -330,7 +401,8 @@ fn get_setup_commands(after_age_increment: bool, debug: bool) -> Vec<Command> {
         commands.push(Command::new("scoreboard objectives add test_global dummy"));
     }
 
-    let enable_appropriate_datapacks = enable_appropriate_datapacks(after_age_increment, debug);
+    let enable_appropriate_datapacks =
+        enable_appropriate_datapacks(after_age_increment, debug, with_test_datapack);
     if after_age_increment || !enable_appropriate_datapacks.is_empty() {
         // Reload changes to tick datapack and load all datapacks to enable the appropriate ones
         commands.push(Command::new("reload"));
@@ -340,7 +412,11 @@ fn get_setup_commands(after_age_increment: bool, debug: bool) -> Vec<Command> {
     commands
 }
 
-fn enable_appropriate_datapacks(after_age_increment: bool, debug: bool) -> Vec<Command> {
+fn enable_appropriate_datapacks(
+    after_age_increment: bool,
+    debug: bool,
+    with_test_datapack: bool,
+) -> Vec<Command> {
     const UNKNOWN: i8 = -1;
     const FALSE: i8 = 0;
     const TRUE: i8 = 1;
@@ -353,8 +429,14 @@ fn enable_appropriate_datapacks(after_age_increment: bool, debug: bool) -> Vec<C
         if DEBUG_DATAPACK_ENABLED.swap(TRUE, Ordering::Relaxed) != TRUE {
             commands.push(Command::new(r#"datapack enable "file/mcfd_test_debug""#));
         }
-        if TEST_DATAPACK_ENABLED.swap(FALSE, Ordering::Relaxed) != FALSE {
-            commands.push(Command::new(r#"datapack disable "file/mcfd_test""#));
+        if with_test_datapack {
+            if TEST_DATAPACK_ENABLED.swap(TRUE, Ordering::Relaxed) != TRUE {
+                commands.push(Command::new(r#"datapack enable "file/mcfd_test""#));
+            }
+        } else {
+            if TEST_DATAPACK_ENABLED.swap(FALSE, Ordering::Relaxed) != FALSE {
+                commands.push(Command::new(r#"datapack disable "file/mcfd_test""#));
+            }
         }
         if TICK_DATAPACK_ENABLED.swap(TRUE, Ordering::Relaxed) != TRUE {
             // Must run before debugger tick.json